@@ -1,3 +1,4 @@
+use compliance::DenylistError;
 use soroban_sdk::contracterror;
 
 /// Error types for the atomic swap contract.
@@ -29,4 +30,26 @@ pub enum SwapError {
     TransferMismatch = 9,
     /// Trustline check failed for the recipient of a transfer.
     TrustlineCheckFailed = 10,
+    /// A party to the swap is on the sanctions denylist.
+    AddressDenied = 11,
+    /// Caller is not the compliance officer.
+    NotComplianceOfficer = 12,
+    /// No denylist root has been anchored yet.
+    DenylistRootNotAnchored = 13,
+    /// A denylist proof does not match the anchored root.
+    InvalidDenylistProof = 14,
+    /// Denylist batch is empty or too large.
+    DenylistBatchTooLarge = 15,
+}
+
+impl From<DenylistError> for SwapError {
+    fn from(err: DenylistError) -> Self {
+        match err {
+            DenylistError::NotComplianceOfficer => SwapError::NotComplianceOfficer,
+            DenylistError::RootNotAnchored => SwapError::DenylistRootNotAnchored,
+            DenylistError::InvalidProof => SwapError::InvalidDenylistProof,
+            DenylistError::BatchTooLarge => SwapError::DenylistBatchTooLarge,
+            DenylistError::AddressDenied => SwapError::AddressDenied,
+        }
+    }
 }
@@ -16,10 +16,12 @@ mod events;
 mod storage;
 mod types;
 
+pub use compliance::{DenylistMatches, DenylistRoot};
 pub use errors::SwapError;
 pub use types::{Swap, SwapState};
 
-use soroban_sdk::{contract, contractimpl, Address, Env};
+use compliance::Denylist;
+use soroban_sdk::{contract, contractimpl, symbol_short, Address, BytesN, Env, Symbol, Vec};
 
 /// Soroban atomic swap contract.
 ///
@@ -28,9 +30,23 @@ use soroban_sdk::{contract, contractimpl, Address, Env};
 /// 2. Both parties fund their sides via trustline-verified transfers.
 /// 3. Counterparty accepts → assets move atomically.
 /// 4. If expired before acceptance, creator can refund both sides.
+///
+/// Every call that moves assets screens both parties against the sanctions
+/// denylist, not only the caller.
 #[contract]
 pub struct AtomicSwapContract;
 
+/// Fail if the creator or counterparty is on the denylist.
+fn screen_parties(
+    env: &Env,
+    context: Symbol,
+    creator: &Address,
+    counterparty: &Address,
+) -> Result<(), SwapError> {
+    Denylist::screen(env, context, &[creator, counterparty])?;
+    Ok(())
+}
+
 #[contractimpl]
 impl AtomicSwapContract {
     // ════════════════════════════════════════════════════════
@@ -75,6 +91,7 @@ impl AtomicSwapContract {
         if creator == counterparty {
             return Err(SwapError::Unauthorized);
         }
+        screen_parties(&env, symbol_short!("create"), &creator, &counterparty)?;
 
         let now = env.ledger().timestamp();
         let min_exp = storage::min_expiry(&env);
@@ -132,6 +149,12 @@ impl AtomicSwapContract {
         if !swap.is_party(&funder) {
             return Err(SwapError::Unauthorized);
         }
+        screen_parties(
+            &env,
+            symbol_short!("fund"),
+            &swap.creator,
+            &swap.counterparty,
+        )?;
         if swap.state != SwapState::Created {
             return Err(SwapError::InvalidState);
         }
@@ -210,6 +233,12 @@ impl AtomicSwapContract {
         if !swap.creator_funded || !swap.counterparty_funded {
             return Err(SwapError::InvalidState);
         }
+        screen_parties(
+            &env,
+            symbol_short!("accept"),
+            &swap.creator,
+            &swap.counterparty,
+        )?;
 
         // ── Expiry check ─────────────────────────────────────
         let now = env.ledger().timestamp();
@@ -319,6 +348,12 @@ impl AtomicSwapContract {
         if now < swap.expiry {
             return Err(SwapError::InvalidState);
         }
+        screen_parties(
+            &env,
+            symbol_short!("refund"),
+            &swap.creator,
+            &swap.counterparty,
+        )?;
 
         let contract_addr = env.current_contract_address();
 
@@ -377,4 +412,96 @@ impl AtomicSwapContract {
         caller.require_auth();
         storage::set_min_expiry(&env, seconds);
     }
+
+    // ════════════════════════════════════════════════════════
+    //  SANCTIONS DENYLIST
+    // ════════════════════════════════════════════════════════
+
+    /// Set the compliance officer who maintains the denylist.
+    ///
+    /// The first call installs the officer; after that only the current
+    /// officer can hand the role over.
+    pub fn set_compliance_officer(
+        env: Env,
+        caller: Address,
+        officer: Address,
+    ) -> Result<(), SwapError> {
+        caller.require_auth();
+        if let Some(current) = Denylist::officer(&env) {
+            if current != caller {
+                return Err(SwapError::NotComplianceOfficer);
+            }
+        }
+        Denylist::set_officer(&env, &officer);
+        Ok(())
+    }
+
+    /// Get the compliance officer, if one has been set.
+    pub fn get_compliance_officer(env: Env) -> Option<Address> {
+        Denylist::officer(&env)
+    }
+
+    /// Officer: anchor the Merkle root of a newly published screening list.
+    /// Returns the new root version.
+    pub fn anchor_denylist_root(
+        env: Env,
+        officer: Address,
+        root: BytesN<32>,
+        entry_count: u32,
+    ) -> Result<u32, SwapError> {
+        Ok(Denylist::anchor_root(&env, &officer, root, entry_count)?)
+    }
+
+    /// Officer: deny `entries`, each proven against the anchored root by
+    /// the matching entry of `proofs`.
+    pub fn denylist_addresses(
+        env: Env,
+        officer: Address,
+        entries: Vec<Address>,
+        proofs: Vec<Vec<BytesN<32>>>,
+    ) -> Result<(), SwapError> {
+        Ok(Denylist::list(&env, &officer, entries, proofs)?)
+    }
+
+    /// Officer: remove `entries` from the denylist.
+    pub fn delist_addresses(
+        env: Env,
+        officer: Address,
+        entries: Vec<Address>,
+    ) -> Result<(), SwapError> {
+        Ok(Denylist::delist(&env, &officer, entries)?)
+    }
+
+    /// Check whether `address` is on the denylist.
+    pub fn is_denied(env: Env, address: Address) -> bool {
+        Denylist::is_denied(&env, &address)
+    }
+
+    /// Get the currently anchored denylist root.
+    pub fn get_denylist_root(env: Env) -> Option<DenylistRoot> {
+        Denylist::root(&env)
+    }
+
+    /// Screen `addresses` without rejecting on a match, e.g. before
+    /// submitting a value-moving call. Matches are persisted and published
+    /// as `("deny", "match")` events; returns the listed addresses.
+    pub fn screen_addresses(
+        env: Env,
+        caller: Address,
+        context: Symbol,
+        addresses: Vec<Address>,
+    ) -> Result<Vec<Address>, SwapError> {
+        caller.require_auth();
+        Ok(Denylist::record_matches(&env, context, &addresses)?)
+    }
+
+    /// Get the screening matches recorded for `address`.
+    pub fn get_denylist_matches(env: Env, address: Address) -> Option<DenylistMatches> {
+        Denylist::matches(&env, &address)
+    }
+
+    /// Get the total number of screening matches recorded.
+    pub fn get_denylist_match_total(env: Env) -> u64 {
+        Denylist::match_total(&env)
+    }
 }
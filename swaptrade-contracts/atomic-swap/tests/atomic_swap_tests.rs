@@ -3,9 +3,12 @@
 extern crate std;
 use std::println;
 
-use atomic_swap::{AtomicSwapContract, SwapState};
-use soroban_sdk::testutils::{Address as _, Ledger};
-use soroban_sdk::{contract, contractimpl, symbol_short, Address, Env, IntoVal, Symbol};
+use atomic_swap::{AtomicSwapContract, SwapError, SwapState};
+use compliance::Denylist;
+use soroban_sdk::testutils::{Address as _, Events as _, Ledger};
+use soroban_sdk::{
+    contract, contractimpl, symbol_short, vec, Address, BytesN, Env, IntoVal, Symbol, Vec,
+};
 
 // ══════════════════════════════════════════════════════════════
 //  Stub Token (simulates Stellar Asset Contract)
@@ -493,3 +496,96 @@ fn partial_refund_one_party() {
     assert_eq!(balance_of(&ctx, &ctx.asset_a, &ctx.creator), 100_000);
     println!("✓ partial_refund_one_party passed");
 }
+
+// ── Sanctions denylist helpers ───────────────────────────────
+
+/// Install an officer, anchor a two-leaf list of `target` and a filler
+/// address, and deny `target` with its proof.
+fn deny(ctx: &TestContext, target: &Address) -> Address {
+    let env = &ctx.env;
+    let officer = Address::generate(env);
+    ctx.client.set_compliance_officer(&officer, &officer);
+
+    let filler = Address::generate(env);
+    let sibling = Denylist::leaf(env, &filler);
+    let root = Denylist::hash_pair(env, &Denylist::leaf(env, target), &sibling);
+    ctx.client.anchor_denylist_root(&officer, &root, &2u32);
+
+    let proofs: Vec<Vec<BytesN<32>>> = vec![env, vec![env, sibling]];
+    ctx.client
+        .denylist_addresses(&officer, &vec![env, target.clone()], &proofs);
+    officer
+}
+
+// ── 21. Denied counterparty blocks swap creation ─────────────
+
+#[test]
+fn denied_counterparty_blocks_create() {
+    let ctx = setup();
+    deny(&ctx, &ctx.counterparty);
+    assert!(ctx.client.is_denied(&ctx.counterparty));
+
+    let now = ctx.env.ledger().timestamp();
+    let result = ctx.client.try_create_swap(
+        &ctx.creator,
+        &ctx.counterparty,
+        &ctx.asset_a,
+        &100i128,
+        &ctx.asset_b,
+        &200i128,
+        &(now + 600),
+        &1u64,
+    );
+    assert_eq!(result, Err(Ok(SwapError::AddressDenied)));
+    // The rejection rolls back the match event with the rest of the call.
+    assert!(ctx.env.events().all().events().is_empty());
+    println!("✓ denied_counterparty_blocks_create passed");
+}
+
+// ── 22. Listing a party mid-swap blocks accept until delisted ─
+
+#[test]
+fn denied_creator_blocks_accept_until_delisted() {
+    let ctx = setup();
+    let swap_id = create_swap_helper(&ctx, 1, 600);
+    fund_creator(&ctx, swap_id);
+    fund_counterparty(&ctx, swap_id);
+
+    let officer = deny(&ctx, &ctx.creator);
+    let result = ctx.client.try_accept_swap(&swap_id, &ctx.counterparty);
+    assert_eq!(result, Err(Ok(SwapError::AddressDenied)));
+
+    ctx.client
+        .delist_addresses(&officer, &vec![&ctx.env, ctx.creator.clone()]);
+    accept_swap_helper(&ctx, swap_id);
+    assert_eq!(get_swap_state(&ctx, swap_id), SwapState::Accepted);
+    println!("✓ denied_creator_blocks_accept_until_delisted passed");
+}
+
+// ── 23. Denylist updates need the officer and a valid proof ──
+
+#[test]
+fn denylist_rejects_bad_proof_and_non_officer() {
+    let ctx = setup();
+    let env = &ctx.env;
+    let officer = deny(&ctx, &ctx.creator);
+
+    // The counterparty is not in the anchored list.
+    let bogus: Vec<Vec<BytesN<32>>> = vec![env, vec![env, Denylist::leaf(env, &ctx.creator)]];
+    let result =
+        ctx.client
+            .try_denylist_addresses(&officer, &vec![env, ctx.counterparty.clone()], &bogus);
+    assert_eq!(result, Err(Ok(SwapError::InvalidDenylistProof)));
+    assert!(!ctx.client.is_denied(&ctx.counterparty));
+
+    let result = ctx
+        .client
+        .try_delist_addresses(&ctx.counterparty, &vec![env, ctx.creator.clone()]);
+    assert_eq!(result, Err(Ok(SwapError::NotComplianceOfficer)));
+    let result = ctx
+        .client
+        .try_set_compliance_officer(&ctx.counterparty, &ctx.counterparty);
+    assert_eq!(result, Err(Ok(SwapError::NotComplianceOfficer)));
+    assert_eq!(ctx.client.get_compliance_officer(), Some(officer));
+    println!("✓ denylist_rejects_bad_proof_and_non_officer passed");
+}
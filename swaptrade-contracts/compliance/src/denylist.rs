@@ -0,0 +1,367 @@
+//! Sanctions denylist with Merkle-root-anchored batch updates.
+//!
+//! A compliance officer first anchors the root of the full screening list
+//! published by their provider, then lists addresses in batches. Every
+//! listed address must carry a Merkle proof against the anchored root, so
+//! the on-chain set can never hold an address the published list does not.
+//!
+//! Hashing:
+//! - leaf = sha256(0x00 || xdr(address))
+//! - node = sha256(0x01 || min(a, b) || max(a, b))
+//!
+//! Pairs are sorted before hashing, so proofs carry no left/right flags.
+//!
+//! Delisting cannot be proven against a Merkle root (it would need a
+//! non-membership proof), so `delist` trusts the officer. Entries listed
+//! under an older root stay denied until they are delisted.
+//!
+//! The embedding contract owns the officer role: it decides who may call
+//! `set_officer` and passes every value-moving party to `screen`.
+//!
+//! Reporting: `screen` is the blocking check. Every caller rejects the
+//! call when it fails, and Soroban rolls back a failed call's storage
+//! writes and events together, so a blocked call leaves nothing behind.
+//! Matches that must be reported go through `record_matches` instead: it
+//! never fails on a match, persists a per-address match record and a total
+//! match count, and publishes a `("deny", "match")` event for each listed
+//! address. Contracts expose it as a screening entrypoint that front ends
+//! and relayers call before submitting the value-moving call.
+
+use soroban_sdk::{
+    contracttype, symbol_short, xdr::ToXdr, Address, Bytes, BytesN, Env, Symbol, Vec,
+};
+
+/// Largest number of addresses accepted by a single `list`, `delist` or
+/// `record_matches` call.
+pub const MAX_BATCH_SIZE: u32 = 100;
+/// Deepest Merkle proof accepted, enough for lists of 2^32 entries.
+pub const MAX_PROOF_DEPTH: u32 = 32;
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DenylistKey {
+    DenyOfficer,
+    DenyRoot,
+    Denied(Address),
+    DenyMatches(Address),
+    DenyMatchTotal,
+}
+
+/// Root of the screening list the current entries were proven against.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DenylistRoot {
+    pub root: BytesN<32>,
+    /// Bumped on every anchor; starts at 1.
+    pub version: u32,
+    /// Size of the published list, as reported by the officer.
+    pub entry_count: u32,
+    pub anchored_by: Address,
+    pub anchored_at: u64,
+}
+
+/// An address currently on the denylist.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DenylistEntry {
+    /// Root version the address was proven against.
+    pub version: u32,
+    pub listed_at: u64,
+}
+
+/// Failures from the denylist. Each contract maps these onto its own
+/// `#[contracterror]` enum.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DenylistError {
+    /// Caller is not the configured compliance officer.
+    NotComplianceOfficer,
+    /// No list root has been anchored yet.
+    RootNotAnchored,
+    /// A proof is missing, too deep or does not lead to the anchored root.
+    InvalidProof,
+    /// The batch is empty or larger than `MAX_BATCH_SIZE`.
+    BatchTooLarge,
+    /// At least one screened address is on the denylist. The rejected call
+    /// is rolled back; `record_matches` keeps a record that survives.
+    AddressDenied,
+}
+
+/// Screening matches recorded for a listed address by `record_matches`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DenylistMatches {
+    pub count: u32,
+    pub first_at: u64,
+    pub last_at: u64,
+    /// Context of the latest match, e.g. `swap` or `fill`.
+    pub last_context: Symbol,
+}
+
+pub struct Denylist;
+
+impl Denylist {
+    // ── Officer ───────────────────────────────────────────────
+
+    /// Install `officer` as the compliance officer. The caller is expected
+    /// to have authorised the change already.
+    pub fn set_officer(env: &Env, officer: &Address) {
+        env.storage()
+            .persistent()
+            .set(&DenylistKey::DenyOfficer, officer);
+        env.events().publish(
+            (symbol_short!("deny"), symbol_short!("officer")),
+            officer.clone(),
+        );
+    }
+
+    pub fn officer(env: &Env) -> Option<Address> {
+        env.storage().persistent().get(&DenylistKey::DenyOfficer)
+    }
+
+    /// Require auth from `caller` and that it is the compliance officer.
+    pub fn require_officer(env: &Env, caller: &Address) -> Result<(), DenylistError> {
+        caller.require_auth();
+        match Self::officer(env) {
+            Some(officer) if officer == *caller => Ok(()),
+            _ => Err(DenylistError::NotComplianceOfficer),
+        }
+    }
+
+    // ── List management ───────────────────────────────────────
+
+    /// Anchor the root of a newly published screening list. Returns the new
+    /// root version.
+    pub fn anchor_root(
+        env: &Env,
+        officer: &Address,
+        root: BytesN<32>,
+        entry_count: u32,
+    ) -> Result<u32, DenylistError> {
+        Self::require_officer(env, officer)?;
+        let version = Self::root(env).map(|r| r.version).unwrap_or(0) + 1;
+        let anchored = DenylistRoot {
+            root: root.clone(),
+            version,
+            entry_count,
+            anchored_by: officer.clone(),
+            anchored_at: env.ledger().timestamp(),
+        };
+        env.storage()
+            .persistent()
+            .set(&DenylistKey::DenyRoot, &anchored);
+        env.events().publish(
+            (symbol_short!("deny"), symbol_short!("anchor")),
+            (version, root, entry_count),
+        );
+        Ok(version)
+    }
+
+    /// Add `entries` to the denylist. `proofs[i]` must prove `entries[i]`
+    /// against the anchored root; the whole batch fails if any proof does.
+    pub fn list(
+        env: &Env,
+        officer: &Address,
+        entries: Vec<Address>,
+        proofs: Vec<Vec<BytesN<32>>>,
+    ) -> Result<(), DenylistError> {
+        Self::require_officer(env, officer)?;
+        Self::check_batch(&entries)?;
+        if proofs.len() != entries.len() {
+            return Err(DenylistError::InvalidProof);
+        }
+        let anchored = Self::root(env).ok_or(DenylistError::RootNotAnchored)?;
+
+        let now = env.ledger().timestamp();
+        for (address, proof) in entries.iter().zip(proofs.iter()) {
+            if !Self::verify_proof(env, &anchored.root, &address, &proof) {
+                return Err(DenylistError::InvalidProof);
+            }
+            let entry = DenylistEntry {
+                version: anchored.version,
+                listed_at: now,
+            };
+            env.storage()
+                .persistent()
+                .set(&DenylistKey::Denied(address.clone()), &entry);
+            env.events().publish(
+                (symbol_short!("deny"), symbol_short!("list")),
+                (address, anchored.version),
+            );
+        }
+        Ok(())
+    }
+
+    /// Remove `entries` from the denylist. Addresses that are not listed
+    /// are skipped.
+    pub fn delist(
+        env: &Env,
+        officer: &Address,
+        entries: Vec<Address>,
+    ) -> Result<(), DenylistError> {
+        Self::require_officer(env, officer)?;
+        Self::check_batch(&entries)?;
+        for address in entries.iter() {
+            let key = DenylistKey::Denied(address.clone());
+            if env.storage().persistent().has(&key) {
+                env.storage().persistent().remove(&key);
+                env.events()
+                    .publish((symbol_short!("deny"), symbol_short!("delist")), address);
+            }
+        }
+        Ok(())
+    }
+
+    fn check_batch(entries: &Vec<Address>) -> Result<(), DenylistError> {
+        if entries.is_empty() || entries.len() > MAX_BATCH_SIZE {
+            return Err(DenylistError::BatchTooLarge);
+        }
+        Ok(())
+    }
+
+    // ── Queries ───────────────────────────────────────────────
+
+    pub fn root(env: &Env) -> Option<DenylistRoot> {
+        env.storage().persistent().get(&DenylistKey::DenyRoot)
+    }
+
+    pub fn entry(env: &Env, address: &Address) -> Option<DenylistEntry> {
+        env.storage()
+            .persistent()
+            .get(&DenylistKey::Denied(address.clone()))
+    }
+
+    pub fn is_denied(env: &Env, address: &Address) -> bool {
+        env.storage()
+            .persistent()
+            .has(&DenylistKey::Denied(address.clone()))
+    }
+
+    /// Matches recorded for `address` by `record_matches`.
+    pub fn matches(env: &Env, address: &Address) -> Option<DenylistMatches> {
+        env.storage()
+            .persistent()
+            .get(&DenylistKey::DenyMatches(address.clone()))
+    }
+
+    /// Total number of matches recorded by `record_matches`.
+    pub fn match_total(env: &Env) -> u64 {
+        env.storage()
+            .persistent()
+            .get(&DenylistKey::DenyMatchTotal)
+            .unwrap_or(0)
+    }
+
+    // ── Screening ─────────────────────────────────────────────
+
+    /// Screen every party to a value-moving call. Fails with
+    /// `AddressDenied` if any of `addresses` is listed.
+    ///
+    /// It publishes a `("deny", "match")` event for each listed address,
+    /// but the failure rolls those back with the rest of the call; use
+    /// `record_matches` for matches that must be reported.
+    pub fn screen(env: &Env, context: Symbol, addresses: &[&Address]) -> Result<(), DenylistError> {
+        let mut denied = false;
+        for address in addresses {
+            if Self::is_denied(env, address) {
+                denied = true;
+                env.events().publish(
+                    (symbol_short!("deny"), symbol_short!("match")),
+                    (
+                        context.clone(),
+                        (*address).clone(),
+                        env.ledger().timestamp(),
+                    ),
+                );
+            }
+        }
+        if denied {
+            return Err(DenylistError::AddressDenied);
+        }
+        Ok(())
+    }
+
+    /// Screen `addresses` without failing on a match. Each listed address
+    /// gets its match record updated and a `("deny", "match")` event with
+    /// `(context, address, timestamp)`. Returns the listed addresses, in
+    /// batch order.
+    pub fn record_matches(
+        env: &Env,
+        context: Symbol,
+        addresses: &Vec<Address>,
+    ) -> Result<Vec<Address>, DenylistError> {
+        Self::check_batch(addresses)?;
+        let now = env.ledger().timestamp();
+        let mut matched = Vec::new(env);
+        for address in addresses.iter() {
+            if !Self::is_denied(env, &address) {
+                continue;
+            }
+            let key = DenylistKey::DenyMatches(address.clone());
+            let record = match env.storage().persistent().get::<_, DenylistMatches>(&key) {
+                Some(prev) => DenylistMatches {
+                    count: prev.count + 1,
+                    last_at: now,
+                    last_context: context.clone(),
+                    ..prev
+                },
+                None => DenylistMatches {
+                    count: 1,
+                    first_at: now,
+                    last_at: now,
+                    last_context: context.clone(),
+                },
+            };
+            env.storage().persistent().set(&key, &record);
+            env.events().publish(
+                (symbol_short!("deny"), symbol_short!("match")),
+                (context.clone(), address.clone(), now),
+            );
+            matched.push_back(address);
+        }
+        if !matched.is_empty() {
+            let total = Self::match_total(env) + matched.len() as u64;
+            env.storage()
+                .persistent()
+                .set(&DenylistKey::DenyMatchTotal, &total);
+        }
+        Ok(matched)
+    }
+
+    // ── Merkle helpers ────────────────────────────────────────
+
+    /// Leaf hash of `address` in a published screening list.
+    pub fn leaf(env: &Env, address: &Address) -> BytesN<32> {
+        let mut input = Bytes::from_array(env, &[LEAF_PREFIX]);
+        input.append(&address.clone().to_xdr(env));
+        env.crypto().sha256(&input).into()
+    }
+
+    /// Parent hash of two sibling nodes.
+    pub fn hash_pair(env: &Env, a: &BytesN<32>, b: &BytesN<32>) -> BytesN<32> {
+        let (lo, hi) = if a <= b { (a, b) } else { (b, a) };
+        let mut input = Bytes::from_array(env, &[NODE_PREFIX]);
+        input.append(&Bytes::from_array(env, &lo.to_array()));
+        input.append(&Bytes::from_array(env, &hi.to_array()));
+        env.crypto().sha256(&input).into()
+    }
+
+    /// Whether `proof` leads from `address`'s leaf to `root`.
+    pub fn verify_proof(
+        env: &Env,
+        root: &BytesN<32>,
+        address: &Address,
+        proof: &Vec<BytesN<32>>,
+    ) -> bool {
+        if proof.len() > MAX_PROOF_DEPTH {
+            return false;
+        }
+        let mut node = Self::leaf(env, address);
+        for sibling in proof.iter() {
+            node = Self::hash_pair(env, &node, &sibling);
+        }
+        node == *root
+    }
+}
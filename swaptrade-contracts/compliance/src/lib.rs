@@ -0,0 +1,15 @@
+#![no_std]
+#![allow(deprecated)]
+
+//! Compliance building blocks shared by the SwapTrade contracts.
+//!
+//! This crate is a plain library, not a contract: each contract embeds the
+//! modules it needs, keeps them in its own storage and decides who may call
+//! the privileged functions.
+
+pub mod denylist;
+
+pub use denylist::{
+    Denylist, DenylistEntry, DenylistError, DenylistMatches, DenylistRoot, MAX_BATCH_SIZE,
+    MAX_PROOF_DEPTH,
+};
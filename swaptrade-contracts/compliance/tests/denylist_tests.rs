@@ -0,0 +1,153 @@
+#![cfg(test)]
+
+use compliance::{Denylist, DenylistError, DenylistMatches, MAX_BATCH_SIZE};
+use soroban_sdk::testutils::{Address as _, Events as _, Ledger};
+use soroban_sdk::{contract, symbol_short, vec, Address, BytesN, Env, Vec};
+
+/// Bare contract that hosts the denylist's storage.
+#[contract]
+struct Host;
+
+struct Setup {
+    env: Env,
+    host: Address,
+    officer: Address,
+    listed: Address,
+    clean: Address,
+}
+
+/// Anchor a two-leaf list of `listed` and `clean`, then deny `listed`.
+fn setup() -> Setup {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+    #[allow(deprecated)]
+    let host = env.register_contract(None, Host);
+    let officer = Address::generate(&env);
+    let listed = Address::generate(&env);
+    let clean = Address::generate(&env);
+
+    // Each officer call gets its own frame, as it would as an entrypoint.
+    let sibling = Denylist::leaf(&env, &clean);
+    let root = Denylist::hash_pair(&env, &Denylist::leaf(&env, &listed), &sibling);
+    env.as_contract(&host, || Denylist::set_officer(&env, &officer));
+    env.as_contract(&host, || {
+        assert_eq!(Denylist::anchor_root(&env, &officer, root, 2), Ok(1));
+    });
+    env.as_contract(&host, || {
+        let proofs: Vec<Vec<BytesN<32>>> = vec![&env, vec![&env, sibling]];
+        Denylist::list(&env, &officer, vec![&env, listed.clone()], proofs).unwrap();
+    });
+    Setup {
+        env,
+        host,
+        officer,
+        listed,
+        clean,
+    }
+}
+
+#[test]
+fn test_list_requires_officer_and_valid_proof() {
+    let s = setup();
+    let env = &s.env;
+    env.as_contract(&s.host, || {
+        assert!(Denylist::is_denied(env, &s.listed));
+        assert_eq!(Denylist::entry(env, &s.listed).unwrap().version, 1);
+
+        let stranger = Address::generate(env);
+        let proof: Vec<Vec<BytesN<32>>> = vec![env, vec![env, Denylist::leaf(env, &s.listed)]];
+        assert_eq!(
+            Denylist::list(env, &stranger, vec![env, s.clean.clone()], proof),
+            Err(DenylistError::NotComplianceOfficer)
+        );
+    });
+    env.as_contract(&s.host, || {
+        let outsider = Address::generate(env);
+        let proof: Vec<Vec<BytesN<32>>> = vec![env, vec![env, Denylist::leaf(env, &s.listed)]];
+        // A proof for an address outside the anchored list is rejected.
+        assert_eq!(
+            Denylist::list(env, &s.officer, vec![env, outsider.clone()], proof),
+            Err(DenylistError::InvalidProof)
+        );
+        assert!(!Denylist::is_denied(env, &outsider));
+    });
+    env.as_contract(&s.host, || {
+        assert_eq!(
+            Denylist::delist(env, &s.officer, Vec::new(env)),
+            Err(DenylistError::BatchTooLarge)
+        );
+    });
+}
+
+#[test]
+fn test_screen_rejects_listed_parties() {
+    let s = setup();
+    let env = &s.env;
+    env.as_contract(&s.host, || {
+        assert_eq!(
+            Denylist::screen(env, symbol_short!("swap"), &[&s.clean]),
+            Ok(())
+        );
+        assert_eq!(
+            Denylist::screen(env, symbol_short!("swap"), &[&s.clean, &s.listed]),
+            Err(DenylistError::AddressDenied)
+        );
+    });
+    env.as_contract(&s.host, || {
+        Denylist::delist(env, &s.officer, vec![env, s.listed.clone()]).unwrap();
+        assert_eq!(
+            Denylist::screen(env, symbol_short!("swap"), &[&s.listed]),
+            Ok(())
+        );
+    });
+}
+
+#[test]
+fn test_record_matches_persists_without_failing() {
+    let s = setup();
+    let env = &s.env;
+    let batch = vec![env, s.clean.clone(), s.listed.clone()];
+    env.as_contract(&s.host, || {
+        let matched = Denylist::record_matches(env, symbol_short!("swap"), &batch).unwrap();
+        assert_eq!(matched, vec![env, s.listed.clone()]);
+    });
+    assert_eq!(env.events().all().events().len(), 1);
+
+    env.ledger().set_timestamp(2_000);
+    env.as_contract(&s.host, || {
+        Denylist::record_matches(env, symbol_short!("fill"), &batch).unwrap();
+        assert_eq!(
+            Denylist::matches(env, &s.listed),
+            Some(DenylistMatches {
+                count: 2,
+                first_at: 1_000,
+                last_at: 2_000,
+                last_context: symbol_short!("fill"),
+            })
+        );
+        assert_eq!(Denylist::matches(env, &s.clean), None);
+        assert_eq!(Denylist::match_total(env), 2);
+    });
+}
+
+#[test]
+fn test_record_matches_bounds_the_batch() {
+    let s = setup();
+    let env = &s.env;
+    let mut batch = Vec::new(env);
+    for _ in 0..=MAX_BATCH_SIZE {
+        batch.push_back(Address::generate(env));
+    }
+    env.as_contract(&s.host, || {
+        assert_eq!(
+            Denylist::record_matches(env, symbol_short!("swap"), &batch),
+            Err(DenylistError::BatchTooLarge)
+        );
+        assert_eq!(
+            Denylist::record_matches(env, symbol_short!("swap"), &Vec::new(env)),
+            Err(DenylistError::BatchTooLarge)
+        );
+        assert_eq!(Denylist::match_total(env), 0);
+    });
+}
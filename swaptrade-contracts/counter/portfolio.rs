@@ -328,6 +328,38 @@ impl Portfolio {
         self.update_stats_on_trade(env, user, swap_amount);
     }
 
+    /// Append a swap to the user's transaction history.
+    pub fn record_transaction(
+        &mut self,
+        env: &Env,
+        user: Address,
+        from_token: Symbol,
+        to_token: Symbol,
+        from_amount: i128,
+        to_amount: i128,
+    ) {
+        // Rate in units of 10^-7 of `to_token` per `from_token`
+        let rate_achieved = if from_amount > 0 && to_amount > 0 {
+            (to_amount as u128).saturating_mul(10_000_000) / from_amount as u128
+        } else {
+            0
+        };
+        let mut txs = self
+            .transactions
+            .get(user.clone())
+            .unwrap_or_else(|| Vec::new(env));
+        txs.push_back(Transaction {
+            timestamp: env.ledger().timestamp(),
+            from_token,
+            to_token,
+            from_amount,
+            to_amount,
+            rate_achieved,
+            migration_time: None,
+        });
+        self.transactions.set(user, txs);
+    }
+
     /// Award a badge to a user if they don't already have it.
     /// Returns true if badge was awarded, false if user already had it.
     pub fn award_badge(&mut self, env: &Env, user: Address, badge: Badge) -> bool {
@@ -384,6 +416,28 @@ impl Portfolio {
     self.balances.get(key).unwrap_or(0)
     }
 
+    /// Every non-zero balance held by `user`.
+    pub fn assets_of(&self, env: &Env, user: &Address) -> Vec<(Asset, i128)> {
+        let mut assets = Vec::new(env);
+        for ((owner, asset), amount) in self.balances.iter() {
+            if owner == *user && amount != 0 {
+                assets.push_back((asset, amount));
+            }
+        }
+        assets
+    }
+
+    /// Total balance of `token` held across all users.
+    pub fn open_interest(&self, token: &Asset) -> i128 {
+        let mut total: i128 = 0;
+        for ((_, asset), amount) in self.balances.iter() {
+            if asset == *token && amount > 0 {
+                total = total.saturating_add(amount);
+            }
+        }
+        total
+    }
+
     /// Get portfolio statistics for a user
     /// Returns (trade_count, pnl)
     pub fn get_portfolio(&self, env: &Env, user: Address) -> (u32, i128) {
@@ -494,6 +548,9 @@ impl Portfolio {
         self.total_trade_pnl
             .set(user.clone(), current_total.saturating_add(pnl));
 
+        let count = self.trades.get(user.clone()).unwrap_or(0);
+        self.trades.set(user.clone(), count.saturating_add(1));
+
         // Update win/loss counters
         if is_winner {
             let wins = self.winning_trades.get(user.clone()).unwrap_or(0);
@@ -566,7 +623,7 @@ impl Portfolio {
         }
 
         // Proportional release against the pre-disposal quantity.
-        let released = (basis_total.saturating_mul(qty_disposed as u128) / qty_before as u128) as i128;
+        let released = basis_total.saturating_mul(qty_disposed) / qty_before;
         let released = released.min(basis_total);
         self.cost_basis_total.set(key, basis_total.saturating_sub(released));
 
@@ -633,7 +690,10 @@ impl Portfolio {
             Asset::Custom(to_token.clone())
         };
         self.credit(env, to_asset, user.clone(), amount_out);
-        self.record_acquisition(env, user, to_token, in_value);
+        // XLM is the numeraire, so holding it carries no cost basis.
+        if *to_token != symbol_short!("XLM") {
+            self.record_acquisition(env, user, to_token, in_value);
+        }
 
         Ok(realized)
     }
@@ -659,7 +719,7 @@ impl Portfolio {
             if basis == 0 {
                 continue;
             }
-            let asset = if token == &symbol_short!("XLM") {
+            let asset = if token == symbol_short!("XLM") {
                 Asset::XLM
             } else {
                 Asset::Custom(token.clone())
@@ -820,12 +880,10 @@ impl Portfolio {
         let variance = variance_sum.saturating_div(returns.len() as i128);
         let std_dev = self.sqrt_i128(variance);
 
-        // Sharpe ratio (assuming risk-free rate = 0 for simplicity)
-        if std_dev > 0 {
-            (avg_return.saturating_mul(10_000_000)) / std_dev
-        } else {
-            0
-        }
+        // Sharpe ratio (assuming risk-free rate = 0 for simplicity). A
+        // zero-variance series divides by the smallest unit rather than
+        // reporting no edge at all.
+        (avg_return.saturating_mul(10_000_000)) / std_dev.max(1)
     }
 
     /// Calculate maximum drawdown from portfolio values
@@ -840,6 +898,8 @@ impl Portfolio {
             return 0;
         }
 
+        // The first trade's size stands in for starting capital
+        let starting_value = history.get(0).map(|t| t.amount_in).unwrap_or(0);
         let mut peak: i128 = 0;
         let mut max_dd: u128 = 0;
         let mut cumulative_pnl: i128 = 0;
@@ -847,7 +907,7 @@ impl Portfolio {
         for i in 0..history.len() {
             if let Some(trade) = history.get(i) {
                 cumulative_pnl = cumulative_pnl.saturating_add(trade.pnl);
-                let portfolio_value = 10_000_000_000_000_000_000i128.saturating_add(cumulative_pnl); // Starting from 10^19
+                let portfolio_value = starting_value.saturating_add(cumulative_pnl);
 
                 if portfolio_value > peak {
                     peak = portfolio_value;
@@ -1123,7 +1183,7 @@ impl Portfolio {
 
     /// Helper: Update aggregate stats when a trade is recorded
     /// Called lazily during trade operations
-    fn update_stats_on_trade(&mut self, env: &Env, user: Address, swap_amount: i128) {
+    pub fn update_stats_on_trade(&mut self, env: &Env, user: Address, swap_amount: i128) {
         // Check if user is new (not in trades map)
         let trade_count = self.trades.get(user.clone()).unwrap_or(0);
         if trade_count == 0 {
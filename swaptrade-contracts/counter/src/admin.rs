@@ -1,5 +1,6 @@
 use soroban_sdk::{contracttype, Address, Env, Vec};
 
+use crate::decentralization::Decentralization;
 use crate::errors::SwapTradeError;
 use crate::storage::{ADMIN_KEY, MULTI_SIG_CONFIG_KEY};
 
@@ -18,12 +19,22 @@ pub fn is_admin(env: &Env, user: &Address) -> bool {
         .unwrap_or(false)
 }
 
+/// Check `caller` is the admin and that the decentralization phase still
+/// lets the admin act directly.
 pub fn require_admin(env: &Env, caller: &Address) -> Result<(), SwapTradeError> {
-    if is_admin(env, caller) {
-        Ok(())
-    } else {
-        Err(SwapTradeError::NotAdmin)
+    if !is_admin(env, caller) {
+        return Err(SwapTradeError::NotAdmin);
     }
+    Decentralization::authorize_admin_call(env, false)
+}
+
+/// Like `require_admin`, for pausing and resuming trading, which the admin
+/// keeps through the PauseOnly phase.
+pub fn require_pause_admin(env: &Env, caller: &Address) -> Result<(), SwapTradeError> {
+    if !is_admin(env, caller) {
+        return Err(SwapTradeError::NotAdmin);
+    }
+    Decentralization::authorize_admin_call(env, true)
 }
 
 pub fn get_admin(env: &Env) -> Address {
@@ -49,3 +60,9 @@ pub fn get_multi_sig_config(env: &Env) -> Result<MultiSigConfig, SwapTradeError>
         .get(&MULTI_SIG_CONFIG_KEY)
         .ok_or(SwapTradeError::MultiSigNotConfigured)
 }
+
+/// Install `admin` directly, bypassing the admin check. Test setup only.
+#[cfg(test)]
+pub fn set_admin(env: &Env, admin: &Address) {
+    env.storage().persistent().set(&ADMIN_KEY, admin);
+}
@@ -273,8 +273,8 @@ fn test_max_drawdown_calculation() {
 
     let summary = portfolio.get_analytics_summary(&env, user);
 
-    // Max drawdown should be calculated
-    assert!(summary.max_drawdown >= 0);
+    // The losing trade after the winner opens a drawdown
+    assert!(summary.max_drawdown > 0);
 }
 
 #[test]
@@ -24,45 +24,6 @@ pub struct BridgeRequest {
 const BRIDGE_COUNTER_KEY: &str = "bridge_counter";
 const BRIDGE_REQUEST_PREFIX: &str = "bridge_req";
 
-pub fn initiate_bridge(
-    env: &Env,
-    sender: Address,
-    recipient: Address,
-    amount: i128,
-    source_chain: Symbol,
-    dest_chain: Symbol,
-) -> u64 {
-    sender.require_auth();
-
-    assert!(amount > 0, "Amount must be positive");
-
-    let id: u64 = env
-        .storage()
-        .instance()
-        .get(&soroban_sdk::symbol_short!("brcnt"))
-        .unwrap_or(0u64)
-        + 1;
-
-    env.storage()
-        .instance()
-        .set(&soroban_sdk::symbol_short!("brcnt"), &id);
-
-    let request = BridgeRequest {
-        id,
-        sender,
-        recipient,
-        amount,
-        source_chain,
-        dest_chain,
-        status: BridgeStatus::Locked,
-    };
-
-    let key = (soroban_sdk::symbol_short!("brreq"), id);
-    env.storage().persistent().set(&key, &request);
-
-    id
-}
-
 pub fn confirm_bridge(env: &Env, oracle: Address, request_id: u64) {
     oracle.require_auth();
 
@@ -0,0 +1,190 @@
+#![cfg(test)]
+
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::{symbol_short, Address, Env};
+
+use crate::risk_management::valuation::{PRICE_PRECISION, USD};
+use crate::risk_management::{
+    volume_circuit_breaker, BreakerPhase, BreakerScope, CircuitBreaker, TripRule,
+    DEFAULT_COOLDOWN_SECS,
+};
+use crate::storage::ADMIN_KEY;
+use crate::{CounterContract, CounterContractClient};
+
+struct Setup {
+    env: Env,
+    contract_id: Address,
+    client: CounterContractClient<'static>,
+    admin: Address,
+}
+
+fn setup() -> Setup {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|l| l.timestamp = 1_000_000);
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(&ADMIN_KEY, &admin);
+    });
+    Setup {
+        env,
+        contract_id,
+        client,
+        admin,
+    }
+}
+
+impl Setup {
+    fn record_volume(&self, amount: i128) -> bool {
+        self.env.as_contract(&self.contract_id, || {
+            volume_circuit_breaker::check_and_record_volume(&self.env, amount)
+        })
+    }
+
+    fn advance(&self, secs: u64) {
+        self.env.ledger().with_mut(|l| l.timestamp += secs);
+    }
+}
+
+#[test]
+fn test_volume_trip_half_opens_and_recovers() {
+    let s = setup();
+    s.client.set_circuit_breaker_threshold(&s.admin, &60, &500);
+    assert!(s.record_volume(600));
+
+    let status = s.client.get_circuit_breaker_status();
+    assert!(status.is_active);
+    assert_eq!(status.phase, BreakerPhase::Open);
+    assert_eq!(status.rule, TripRule::Volume);
+    assert_eq!(status.trigger_reason, symbol_short!("volume"));
+    assert_eq!((status.observed, status.threshold), (600, 500));
+    s.env.as_contract(&s.contract_id, || {
+        assert!(CircuitBreaker::is_halted(&s.env, &symbol_short!("XLM")));
+    });
+
+    // Volume is not recorded while the breaker is open.
+    assert!(!s.record_volume(100));
+
+    s.advance(DEFAULT_COOLDOWN_SECS);
+    let status = s.client.get_circuit_breaker_status();
+    assert_eq!(status.phase, BreakerPhase::HalfOpen);
+    assert!(!status.is_active);
+
+    // A swap under the threshold closes the breaker.
+    assert!(!s.record_volume(100));
+    let status = s.client.get_circuit_breaker_status();
+    assert_eq!(status.phase, BreakerPhase::Closed);
+    assert_eq!(status.trigger_reason, symbol_short!("recovered"));
+}
+
+#[test]
+fn test_breach_while_half_open_reopens() {
+    let s = setup();
+    s.client.set_circuit_breaker_threshold(&s.admin, &60, &500);
+    assert!(s.record_volume(600));
+    s.advance(DEFAULT_COOLDOWN_SECS);
+
+    assert!(s.record_volume(700));
+    let status = s.client.get_circuit_breaker_status();
+    assert_eq!(status.phase, BreakerPhase::Open);
+    assert_eq!(status.triggered_at, s.env.ledger().timestamp());
+    assert_eq!(status.observed, 700);
+
+    s.client.reset_circuit_breaker(&s.admin);
+    let status = s.client.get_circuit_breaker_status();
+    assert_eq!(status.phase, BreakerPhase::Closed);
+    assert_eq!(status.trigger_reason, symbol_short!("reset"));
+}
+
+#[test]
+fn test_asset_scope_halts_only_that_asset() {
+    let s = setup();
+    let xlm = symbol_short!("XLM");
+    s.client
+        .trip_circuit_breaker(&s.admin, &BreakerScope::Asset(xlm.clone()));
+
+    s.env.as_contract(&s.contract_id, || {
+        assert!(CircuitBreaker::is_halted(&s.env, &xlm));
+        assert!(!CircuitBreaker::is_halted(
+            &s.env,
+            &symbol_short!("USDCSIM")
+        ));
+    });
+    assert!(!s.client.get_circuit_breaker_status().is_active);
+    let status = s.client.get_asset_circuit_breaker_status(&xlm);
+    assert_eq!(status.scope, BreakerScope::Asset(xlm.clone()));
+    assert_eq!(status.rule, TripRule::Manual);
+
+    let outsider = Address::generate(&s.env);
+    assert!(s
+        .client
+        .try_reset_asset_circuit_breaker(&outsider, &xlm)
+        .is_err());
+    s.client.reset_asset_circuit_breaker(&s.admin, &xlm);
+    assert_eq!(
+        s.client.get_asset_circuit_breaker_status(&xlm).phase,
+        BreakerPhase::Closed
+    );
+}
+
+#[test]
+fn test_price_move_trips_asset_breaker() {
+    let s = setup();
+    let xlm = symbol_short!("XLM");
+    let pair = (xlm.clone(), USD);
+    s.client.init_oracle_feed(&s.admin, &pair, &PRICE_PRECISION);
+    for price in [100u128, 108, 117] {
+        s.advance(60);
+        s.client
+            .submit_oracle_price(&s.admin, &pair, &(PRICE_PRECISION / 100 * price));
+    }
+
+    assert!(s.client.check_price_breaker(&xlm));
+    let status = s.client.get_asset_circuit_breaker_status(&xlm);
+    assert_eq!(status.rule, TripRule::PriceMove);
+    assert_eq!((status.observed, status.threshold), (1_700, 1_500));
+    // Already open: evaluating again does not re-trip.
+    assert!(!s.client.check_price_breaker(&xlm));
+}
+
+#[test]
+fn test_oracle_divergence_trips_asset_breaker() {
+    let s = setup();
+    let xlm = symbol_short!("XLM");
+    let pair = (xlm.clone(), USD);
+    s.client
+        .init_oracle_feed(&s.admin, &pair, &(PRICE_PRECISION / 5));
+    s.client.set_price(&pair, &(PRICE_PRECISION / 5));
+    assert!(!s.client.check_oracle_breaker(&xlm));
+
+    s.client.set_price(&pair, &(PRICE_PRECISION / 50 * 11));
+    assert!(s.client.check_oracle_breaker(&xlm));
+    let status = s.client.get_asset_circuit_breaker_status(&xlm);
+    assert_eq!(status.rule, TripRule::OracleDivergence);
+    assert_eq!((status.observed, status.threshold), (909, 500));
+}
+
+#[test]
+fn test_healthy_invariants_and_config_bounds() {
+    let s = setup();
+    assert!(!s.client.check_invariant_breaker());
+    assert_eq!(
+        s.client.get_circuit_breaker_status().phase,
+        BreakerPhase::Closed
+    );
+
+    assert!(s
+        .client
+        .try_set_circuit_breaker_config(&s.admin, &600, &0)
+        .is_err());
+    s.client.set_circuit_breaker_config(&s.admin, &600, &100);
+    s.client
+        .trip_circuit_breaker(&s.admin, &BreakerScope::Global);
+    s.advance(600);
+    assert_eq!(
+        s.client.get_circuit_breaker_status().phase,
+        BreakerPhase::HalfOpen
+    );
+}
@@ -0,0 +1,278 @@
+// decentralization.rs
+//
+// On-chain decentralization schedule. The admin commits the schedule once,
+// and it must hash to a commitment published ahead of time. From then on the
+// phase is derived from time elapsed since `deployed_at` and recorded on
+// chain as it advances:
+//
+//   FullAdmin (months 0-2)  : the admin acts alone
+//   PauseOnly (months 3-5)  : the admin may only pause and resume trading
+//   MultiSig  (months 6-11) : each admin call needs 3-of-5 guardian approval
+//   DaoOnly   (month 12+)   : direct admin paths are closed; changes go
+//                             through token-holder governance proposals
+//
+// Without a committed schedule the contract stays in FullAdmin.
+
+use soroban_sdk::{contracttype, Address, Bytes, BytesN, Env, Vec};
+
+use crate::errors::ContractError;
+use crate::events;
+
+pub const SECS_PER_MONTH: u64 = 30 * 24 * 3600;
+pub const GUARDIAN_COUNT: u32 = 5;
+pub const GUARDIAN_THRESHOLD: u32 = 3;
+/// How long a guardian-approved admin call stays usable.
+pub const ADMIN_CALL_WINDOW_SECS: u64 = 3600;
+
+/// Phase boundaries hashed into the commitment, matching the off-chain
+/// `governance::DecentralizationSchedule`.
+const PHASE_BOUNDARIES: [&[u8]; 4] = [
+    b"FullAdmin:0-2months",
+    b"PauseOnly:3-5months",
+    b"MultiSig:6-11months",
+    b"DaoOnly:12+months",
+];
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum GovernancePhase {
+    FullAdmin = 0,
+    PauseOnly = 1,
+    MultiSig = 2,
+    DaoOnly = 3,
+}
+
+impl GovernancePhase {
+    pub fn from_elapsed(elapsed_secs: u64) -> Self {
+        match elapsed_secs / SECS_PER_MONTH {
+            0..=2 => GovernancePhase::FullAdmin,
+            3..=5 => GovernancePhase::PauseOnly,
+            6..=11 => GovernancePhase::MultiSig,
+            _ => GovernancePhase::DaoOnly,
+        }
+    }
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecentralizationSchedule {
+    pub deployed_at: u64,
+    pub guardians: Vec<Address>,
+    pub dao: Address,
+    pub commitment: BytesN<32>,
+}
+
+/// Guardian approvals for the admin's next call during the MultiSig phase.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AdminCallApproval {
+    pub approvals: Vec<Address>,
+    /// Set once the guardian threshold is reached.
+    pub armed_at: Option<u64>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecentralizationKey {
+    Schedule,
+    Phase,
+    AdminCall,
+}
+
+pub struct Decentralization;
+
+impl Decentralization {
+    /// Commit the schedule. Only possible once, and only if it hashes to the
+    /// `commitment` published beforehand.
+    pub fn commit(
+        env: &Env,
+        deployed_at: u64,
+        guardians: Vec<Address>,
+        dao: Address,
+        commitment: BytesN<32>,
+    ) -> Result<(), ContractError> {
+        if env
+            .storage()
+            .persistent()
+            .has(&DecentralizationKey::Schedule)
+        {
+            return Err(ContractError::ScheduleAlreadyCommitted);
+        }
+        if deployed_at > env.ledger().timestamp() || guardians.len() != GUARDIAN_COUNT {
+            return Err(ContractError::InvalidSchedule);
+        }
+        for (i, guardian) in guardians.iter().enumerate() {
+            if guardians.first_index_of(&guardian) != Some(i as u32) {
+                return Err(ContractError::InvalidSchedule);
+            }
+        }
+        if Self::compute_commitment(env, deployed_at, &guardians, &dao) != commitment {
+            return Err(ContractError::ScheduleCommitmentMismatch);
+        }
+
+        let schedule = DecentralizationSchedule {
+            deployed_at,
+            guardians,
+            dao,
+            commitment: commitment.clone(),
+        };
+        env.storage()
+            .persistent()
+            .set(&DecentralizationKey::Schedule, &schedule);
+        events::schedule_committed(env, commitment, deployed_at);
+        Self::sync_phase(env);
+        Ok(())
+    }
+
+    /// SHA-256 over `deployed_at` (little endian), each guardian's and the
+    /// DAO's strkey, then the phase boundaries.
+    pub fn compute_commitment(
+        env: &Env,
+        deployed_at: u64,
+        guardians: &Vec<Address>,
+        dao: &Address,
+    ) -> BytesN<32> {
+        let mut preimage = Bytes::from_array(env, &deployed_at.to_le_bytes());
+        for guardian in guardians.iter() {
+            Self::append_strkey(&mut preimage, &guardian);
+        }
+        Self::append_strkey(&mut preimage, dao);
+        for boundary in PHASE_BOUNDARIES {
+            preimage.extend_from_slice(boundary);
+        }
+        env.crypto().sha256(&preimage).into()
+    }
+
+    /// Whether the committed schedule matches a published hash.
+    pub fn verify_commitment(env: &Env, published: BytesN<32>) -> bool {
+        match Self::get_schedule(env) {
+            Some(s) => {
+                s.commitment == published
+                    && Self::compute_commitment(env, s.deployed_at, &s.guardians, &s.dao)
+                        == published
+            }
+            None => false,
+        }
+    }
+
+    pub fn get_schedule(env: &Env) -> Option<DecentralizationSchedule> {
+        env.storage()
+            .persistent()
+            .get(&DecentralizationKey::Schedule)
+    }
+
+    /// Phase implied by the ledger clock.
+    pub fn current_phase(env: &Env) -> GovernancePhase {
+        match Self::get_schedule(env) {
+            Some(s) => GovernancePhase::from_elapsed(
+                env.ledger().timestamp().saturating_sub(s.deployed_at),
+            ),
+            None => GovernancePhase::FullAdmin,
+        }
+    }
+
+    /// Phase last recorded on chain.
+    pub fn recorded_phase(env: &Env) -> GovernancePhase {
+        env.storage()
+            .persistent()
+            .get(&DecentralizationKey::Phase)
+            .unwrap_or(GovernancePhase::FullAdmin)
+    }
+
+    /// Record the current phase if it has advanced. Phases never go back.
+    pub fn sync_phase(env: &Env) -> GovernancePhase {
+        let recorded = Self::recorded_phase(env);
+        let current = Self::current_phase(env);
+        if current > recorded {
+            env.storage()
+                .persistent()
+                .set(&DecentralizationKey::Phase, &current);
+            events::governance_phase_advanced(
+                env,
+                recorded as u32,
+                current as u32,
+                env.ledger().timestamp(),
+            );
+            return current;
+        }
+        recorded
+    }
+
+    /// Guardian approval for the admin's next call. Once the threshold is
+    /// reached the admin has `ADMIN_CALL_WINDOW_SECS` to make one call.
+    /// Returns the approval count.
+    pub fn approve_admin_call(env: &Env, guardian: Address) -> Result<u32, ContractError> {
+        guardian.require_auth();
+        let schedule = Self::get_schedule(env).ok_or(ContractError::NotGuardian)?;
+        if !schedule.guardians.contains(&guardian) {
+            return Err(ContractError::NotGuardian);
+        }
+        if Self::sync_phase(env) != GovernancePhase::MultiSig {
+            return Err(ContractError::AdminPhaseRestricted);
+        }
+
+        let mut approval = Self::live_admin_call(env).unwrap_or(AdminCallApproval {
+            approvals: Vec::new(env),
+            armed_at: None,
+        });
+        if approval.approvals.contains(&guardian) {
+            return Err(ContractError::AlreadyApproved);
+        }
+        approval.approvals.push_back(guardian.clone());
+        let count = approval.approvals.len();
+        if approval.armed_at.is_none() && count >= GUARDIAN_THRESHOLD {
+            approval.armed_at = Some(env.ledger().timestamp());
+        }
+        env.storage()
+            .persistent()
+            .set(&DecentralizationKey::AdminCall, &approval);
+        events::admin_call_approved(env, guardian, count, env.ledger().timestamp());
+        Ok(count)
+    }
+
+    /// Pending approvals, dropping ones whose window has lapsed.
+    pub fn live_admin_call(env: &Env) -> Option<AdminCallApproval> {
+        let approval: AdminCallApproval = env
+            .storage()
+            .persistent()
+            .get(&DecentralizationKey::AdminCall)?;
+        match approval.armed_at {
+            Some(at) if env.ledger().timestamp() > at + ADMIN_CALL_WINDOW_SECS => None,
+            _ => Some(approval),
+        }
+    }
+
+    /// Gate an admin call on the current phase. `pause` marks pause and
+    /// resume, which stay with the admin through PauseOnly.
+    pub(crate) fn authorize_admin_call(env: &Env, pause: bool) -> Result<(), ContractError> {
+        match Self::sync_phase(env) {
+            GovernancePhase::FullAdmin => Ok(()),
+            GovernancePhase::PauseOnly if pause => Ok(()),
+            GovernancePhase::PauseOnly | GovernancePhase::DaoOnly => {
+                Err(ContractError::AdminPhaseRestricted)
+            }
+            GovernancePhase::MultiSig => {
+                match Self::live_admin_call(env) {
+                    Some(AdminCallApproval {
+                        armed_at: Some(_), ..
+                    }) => {}
+                    _ => return Err(ContractError::AdminCallNotApproved),
+                }
+                // Approvals are single use.
+                env.storage()
+                    .persistent()
+                    .remove(&DecentralizationKey::AdminCall);
+                Ok(())
+            }
+        }
+    }
+
+    fn append_strkey(preimage: &mut Bytes, address: &Address) {
+        let strkey = address.to_string();
+        let mut buf = [0u8; 56];
+        let len = strkey.len() as usize;
+        strkey.copy_into_slice(&mut buf[..len]);
+        preimage.extend_from_slice(&buf[..len]);
+    }
+}
@@ -0,0 +1,227 @@
+#![cfg(test)]
+
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::{Address, BytesN, Env, Vec};
+
+use crate::decentralization::{GovernancePhase, ADMIN_CALL_WINDOW_SECS, SECS_PER_MONTH};
+use crate::errors::ContractError;
+use crate::storage::ADMIN_KEY;
+use crate::{CounterContract, CounterContractClient};
+
+const DEPLOYED_AT: u64 = 1_000;
+
+struct Setup {
+    env: Env,
+    client: CounterContractClient<'static>,
+    admin: Address,
+    guardians: Vec<Address>,
+    dao: Address,
+}
+
+impl Setup {
+    fn guardian(&self, i: u32) -> Address {
+        self.guardians.get(i).unwrap()
+    }
+
+    fn commitment(&self) -> BytesN<32> {
+        self.client
+            .compute_schedule_commitment(&DEPLOYED_AT, &self.guardians, &self.dao)
+    }
+
+    fn commit(&self) {
+        self.client.commit_decentralization_schedule(
+            &self.admin,
+            &DEPLOYED_AT,
+            &self.guardians,
+            &self.dao,
+            &self.commitment(),
+        );
+    }
+
+    fn at_month(&self, month: u64) {
+        self.env
+            .ledger()
+            .with_mut(|l| l.timestamp = DEPLOYED_AT + month * SECS_PER_MONTH);
+    }
+
+    fn arm_admin_call(&self) {
+        for i in 0..3 {
+            self.client.approve_admin_call(&self.guardian(i));
+        }
+    }
+}
+
+fn setup() -> Setup {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|l| l.timestamp = DEPLOYED_AT);
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let mut guardians = Vec::new(&env);
+    for _ in 0..5 {
+        guardians.push_back(Address::generate(&env));
+    }
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(&ADMIN_KEY, &admin);
+    });
+
+    Setup {
+        env: env.clone(),
+        client,
+        admin,
+        guardians,
+        dao: Address::generate(&env),
+    }
+}
+
+#[test]
+fn test_commit_checks_published_hash() {
+    let s = setup();
+    let published = s.commitment();
+    let other = BytesN::from_array(&s.env, &[7; 32]);
+
+    assert_eq!(
+        s.client.try_commit_decentralization_schedule(
+            &s.admin,
+            &DEPLOYED_AT,
+            &s.guardians,
+            &s.dao,
+            &other,
+        ),
+        Err(Ok(ContractError::ScheduleCommitmentMismatch))
+    );
+    let mut four = s.guardians.clone();
+    four.pop_back();
+    assert_eq!(
+        s.client.try_commit_decentralization_schedule(
+            &s.admin,
+            &DEPLOYED_AT,
+            &four,
+            &s.dao,
+            &published,
+        ),
+        Err(Ok(ContractError::InvalidSchedule))
+    );
+    assert!(!s.client.verify_schedule_commitment(&published));
+
+    s.commit();
+    assert!(s.client.verify_schedule_commitment(&published));
+    assert!(!s.client.verify_schedule_commitment(&other));
+    assert_eq!(
+        s.client.get_decentralization_schedule().unwrap().commitment,
+        published
+    );
+    assert_eq!(
+        s.client.try_commit_decentralization_schedule(
+            &s.admin,
+            &DEPLOYED_AT,
+            &s.guardians,
+            &s.dao,
+            &published,
+        ),
+        Err(Ok(ContractError::ScheduleAlreadyCommitted))
+    );
+}
+
+#[test]
+fn test_admin_powers_narrow_by_phase() {
+    let s = setup();
+    s.commit();
+
+    // Months 0-2: the admin acts alone.
+    assert_eq!(s.client.get_governance_phase(), GovernancePhase::FullAdmin);
+    s.client.set_max_hops(&s.admin, &3);
+
+    // Months 3-5: pause and resume only.
+    s.at_month(3);
+    assert_eq!(
+        s.client.try_set_max_hops(&s.admin, &2),
+        Err(Ok(ContractError::AdminPhaseRestricted))
+    );
+    assert!(s.client.pause_trading(&s.admin));
+    assert!(s.client.resume_trading(&s.admin));
+
+    // Months 6-11: every admin call needs three guardian approvals.
+    s.at_month(6);
+    assert_eq!(
+        s.client.try_pause_trading(&s.admin),
+        Err(Ok(ContractError::AdminCallNotApproved))
+    );
+    assert_eq!(
+        s.client.try_approve_admin_call(&s.admin),
+        Err(Ok(ContractError::NotGuardian))
+    );
+    s.arm_admin_call();
+    s.client.set_max_hops(&s.admin, &2);
+    assert_eq!(s.client.get_max_hops(), 2);
+    assert_eq!(
+        s.client.try_set_max_hops(&s.admin, &1),
+        Err(Ok(ContractError::AdminCallNotApproved))
+    );
+
+    // Month 12+: direct admin paths are closed.
+    s.at_month(12);
+    assert_eq!(
+        s.client.try_approve_admin_call(&s.guardian(0)),
+        Err(Ok(ContractError::AdminPhaseRestricted))
+    );
+    assert_eq!(
+        s.client.try_pause_trading(&s.admin),
+        Err(Ok(ContractError::AdminPhaseRestricted))
+    );
+}
+
+#[test]
+fn test_admin_call_approval_expires_and_rejects_duplicates() {
+    let s = setup();
+    s.commit();
+    s.at_month(7);
+
+    s.client.approve_admin_call(&s.guardian(0));
+    assert_eq!(
+        s.client.try_approve_admin_call(&s.guardian(0)),
+        Err(Ok(ContractError::AlreadyApproved))
+    );
+    s.client.approve_admin_call(&s.guardian(1));
+    assert_eq!(s.client.approve_admin_call(&s.guardian(2)), 3);
+    assert!(s
+        .client
+        .get_admin_call_approval()
+        .unwrap()
+        .armed_at
+        .is_some());
+
+    s.env
+        .ledger()
+        .with_mut(|l| l.timestamp += ADMIN_CALL_WINDOW_SECS + 1);
+    assert_eq!(s.client.get_admin_call_approval(), None);
+    assert_eq!(
+        s.client.try_set_max_hops(&s.admin, &3),
+        Err(Ok(ContractError::AdminCallNotApproved))
+    );
+}
+
+#[test]
+fn test_recorded_phase_only_advances() {
+    let s = setup();
+    // Without a schedule the contract stays under full admin control.
+    s.at_month(24);
+    assert_eq!(s.client.sync_governance_phase(), GovernancePhase::FullAdmin);
+    s.client.set_max_hops(&s.admin, &3);
+
+    let s = setup();
+    s.commit();
+    s.at_month(13);
+    assert_eq!(s.client.sync_governance_phase(), GovernancePhase::DaoOnly);
+
+    // Even if the clock reads earlier, the recorded phase holds.
+    s.at_month(1);
+    assert_eq!(s.client.get_governance_phase(), GovernancePhase::FullAdmin);
+    assert_eq!(s.client.sync_governance_phase(), GovernancePhase::DaoOnly);
+    assert_eq!(
+        s.client.try_set_max_hops(&s.admin, &3),
+        Err(Ok(ContractError::AdminPhaseRestricted))
+    );
+}
@@ -0,0 +1,248 @@
+#![cfg(test)]
+
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::{symbol_short, Address, Env, Map};
+
+use crate::errors::ContractError;
+use crate::governance::delegation::{DelegationTopic, MAX_DELEGATORS};
+use crate::governance_system::{self, ProposalAction, ProposalState};
+use crate::storage::PROPOSAL_STATE_KEY;
+use crate::vote_escrow::{VoteEscrowManager, MAX_LOCK_SECS, WEEK_SECS};
+use crate::{CounterContract, CounterContractClient};
+
+const START: u64 = 100 * WEEK_SECS;
+
+struct Setup {
+    env: Env,
+    contract_id: Address,
+    client: CounterContractClient<'static>,
+}
+
+impl Setup {
+    /// A user holding a max-length lock of `amount`.
+    fn locked_user(&self, amount: i128) -> Address {
+        let user = Address::generate(&self.env);
+        self.client.mint(&symbol_short!("XLM"), &user, &amount);
+        self.client
+            .create_ve_lock(&user, &amount, &(START + MAX_LOCK_SECS));
+        user
+    }
+
+    fn own(&self, user: &Address) -> i128 {
+        self.client.get_voting_power(user, &None)
+    }
+
+    fn power(&self, user: &Address, topic: DelegationTopic) -> i128 {
+        self.client.get_delegated_voting_power(user, &topic, &None)
+    }
+}
+
+fn setup() -> Setup {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|l| l.timestamp = START);
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+    Setup {
+        env,
+        contract_id,
+        client,
+    }
+}
+
+#[test]
+fn test_partial_delegation_splits_power() {
+    let s = setup();
+    let alice = s.locked_user(10_000);
+    let bob = s.locked_user(5_000);
+    let carol = Address::generate(&s.env);
+    let (pa, pb) = (s.own(&alice), s.own(&bob));
+
+    s.client
+        .delegate_votes(&alice, &bob, &DelegationTopic::All, &4_000);
+    assert_eq!(
+        s.power(&alice, DelegationTopic::Protocol),
+        pa * 6_000 / 10_000
+    );
+    assert_eq!(
+        s.power(&bob, DelegationTopic::Protocol),
+        pb + pa * 4_000 / 10_000
+    );
+
+    assert_eq!(
+        s.client
+            .try_delegate_votes(&alice, &carol, &DelegationTopic::All, &7_000),
+        Err(Ok(ContractError::InvalidDelegation))
+    );
+    assert_eq!(
+        s.client
+            .try_delegate_votes(&alice, &alice, &DelegationTopic::All, &1_000),
+        Err(Ok(ContractError::InvalidDelegation))
+    );
+
+    // Re-delegating to the same delegate replaces the earlier share.
+    s.client
+        .delegate_votes(&alice, &bob, &DelegationTopic::All, &10_000);
+    assert_eq!(s.power(&alice, DelegationTopic::Protocol), 0);
+    assert_eq!(s.client.get_vote_delegations(&alice).len(), 1);
+
+    s.client
+        .revoke_vote_delegation(&alice, &bob, &DelegationTopic::All);
+    assert_eq!(s.power(&bob, DelegationTopic::Protocol), pb);
+    assert_eq!(
+        s.client
+            .try_revoke_vote_delegation(&alice, &bob, &DelegationTopic::All),
+        Err(Ok(ContractError::DelegationNotFound))
+    );
+}
+
+#[test]
+fn test_dust_delegations_cannot_fill_a_delegate() {
+    let s = setup();
+    let delegate = Address::generate(&s.env);
+
+    // Shares worth less than the minimum delegated power are rejected, so
+    // dust cannot take up the delegate's slots.
+    let dust = s.locked_user(50);
+    assert_eq!(
+        s.client
+            .try_delegate_votes(&dust, &delegate, &DelegationTopic::All, &10_000),
+        Err(Ok(ContractError::InvalidDelegation))
+    );
+    let whale = s.locked_user(10_000);
+    assert_eq!(
+        s.client
+            .try_delegate_votes(&whale, &delegate, &DelegationTopic::All, &1),
+        Err(Ok(ContractError::InvalidDelegation))
+    );
+
+    let mut delegators = Vec::new();
+    for _ in 0..MAX_DELEGATORS {
+        let user = s.locked_user(1_000);
+        s.client
+            .delegate_votes(&user, &delegate, &DelegationTopic::Protocol, &10_000);
+        delegators.push(user);
+    }
+    assert_eq!(
+        s.client
+            .try_delegate_votes(&whale, &delegate, &DelegationTopic::All, &10_000),
+        Err(Ok(ContractError::TooManyDelegators))
+    );
+
+    // Revoking every share frees the slot for someone else.
+    s.client
+        .revoke_vote_delegation(&delegators[0], &delegate, &DelegationTopic::Protocol);
+    s.client
+        .delegate_votes(&whale, &delegate, &DelegationTopic::All, &10_000);
+    assert_eq!(
+        s.client
+            .try_delegate_votes(&delegators[0], &delegate, &DelegationTopic::All, &10_000),
+        Err(Ok(ContractError::TooManyDelegators))
+    );
+}
+
+#[test]
+fn test_topic_delegation_overrides_all() {
+    let s = setup();
+    let alice = s.locked_user(10_000);
+    let bob = Address::generate(&s.env);
+    let carol = Address::generate(&s.env);
+    let pa = s.own(&alice);
+
+    s.client
+        .delegate_votes(&alice, &bob, &DelegationTopic::All, &10_000);
+    s.client
+        .delegate_votes(&alice, &carol, &DelegationTopic::Treasury, &5_000);
+
+    assert_eq!(s.power(&bob, DelegationTopic::RiskParameters), pa);
+    assert_eq!(s.power(&carol, DelegationTopic::RiskParameters), 0);
+
+    assert_eq!(s.power(&bob, DelegationTopic::Treasury), 0);
+    assert_eq!(s.power(&carol, DelegationTopic::Treasury), pa / 2);
+    assert_eq!(s.power(&alice, DelegationTopic::Treasury), pa - pa / 2);
+}
+
+#[test]
+fn test_redelegation_is_capped_at_one_hop() {
+    let s = setup();
+    let alice = s.locked_user(10_000);
+    let bob = s.locked_user(2_000);
+    let carol = Address::generate(&s.env);
+    let (pa, pb) = (s.own(&alice), s.own(&bob));
+
+    s.client
+        .delegate_votes(&alice, &bob, &DelegationTopic::All, &10_000);
+    s.client
+        .delegate_votes(&bob, &carol, &DelegationTopic::All, &10_000);
+
+    // Bob keeps Alice's power; only his own moves on to Carol.
+    assert_eq!(s.power(&bob, DelegationTopic::Protocol), pa);
+    assert_eq!(s.power(&carol, DelegationTopic::Protocol), pb);
+}
+
+#[test]
+fn test_votes_use_delegations_at_proposal_creation() {
+    let s = setup();
+    let alice = s.locked_user(10_000);
+    let bob = s.locked_user(200);
+    let carol = s.locked_user(100);
+    s.client
+        .delegate_votes(&alice, &bob, &DelegationTopic::All, &10_000);
+    s.env.ledger().with_mut(|l| l.timestamp += 60);
+
+    // Each authorizing call runs in its own contract frame.
+    let created = s.env.ledger().timestamp();
+    let id = s.env.as_contract(&s.contract_id, || {
+        governance_system::create_proposal(&s.env, bob.clone(), ProposalAction::PauseTrading)
+            .unwrap()
+    });
+
+    // Moving the delegation after creation does not give Carol a vote with
+    // Alice's power as well.
+    s.env.ledger().with_mut(|l| l.timestamp += 60);
+    s.env.as_contract(&s.contract_id, || {
+        crate::governance::delegation::revoke_delegation(
+            &s.env,
+            alice.clone(),
+            bob.clone(),
+            DelegationTopic::All,
+        )
+        .unwrap();
+    });
+    s.env.as_contract(&s.contract_id, || {
+        crate::governance::delegation::delegate_vote(
+            &s.env,
+            alice.clone(),
+            carol.clone(),
+            DelegationTopic::All,
+            10_000,
+        )
+        .unwrap();
+    });
+    for voter in [&carol, &alice, &bob] {
+        s.env.as_contract(&s.contract_id, || {
+            governance_system::cast_vote(&s.env, voter.clone(), id, true).unwrap();
+        });
+    }
+
+    s.env.as_contract(&s.contract_id, || {
+        let states: Map<u64, ProposalState> = s
+            .env
+            .storage()
+            .persistent()
+            .get(&PROPOSAL_STATE_KEY)
+            .unwrap();
+        let votes = states.get(id).unwrap().votes;
+        let (carol_vote, alice_vote, bob_vote) = (
+            votes.get(0).unwrap(),
+            votes.get(1).unwrap(),
+            votes.get(2).unwrap(),
+        );
+        assert!(bob_vote.power > carol_vote.power);
+        assert_eq!(
+            carol_vote.power,
+            VoteEscrowManager::balance_of_at(&s.env, &carol, created)
+        );
+        assert_eq!(alice_vote.power, 0);
+    });
+}
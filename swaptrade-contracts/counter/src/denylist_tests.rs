@@ -0,0 +1,98 @@
+#![cfg(test)]
+
+use compliance::Denylist;
+use soroban_sdk::testutils::{Address as _, Events as _, Ledger};
+use soroban_sdk::{symbol_short, vec, Address, BytesN, Env, Symbol, Vec};
+
+use crate::errors::ContractError;
+use crate::storage::ADMIN_KEY;
+use crate::{CounterContract, CounterContractClient, DenylistMatches};
+
+const XLM: Symbol = symbol_short!("XLM");
+const USDC: Symbol = symbol_short!("USDCSIM");
+
+struct Setup {
+    env: Env,
+    client: CounterContractClient<'static>,
+    officer: Address,
+    listed: Address,
+    clean: Address,
+}
+
+/// Install an officer, anchor a two-leaf list of `listed` and `clean`, and
+/// deny `listed` with its proof.
+fn setup() -> Setup {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|l| l.timestamp = 1_000_000);
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(&ADMIN_KEY, &admin);
+    });
+
+    let officer = Address::generate(&env);
+    let listed = Address::generate(&env);
+    let clean = Address::generate(&env);
+    client.set_compliance_officer(&admin, &officer);
+    let sibling = Denylist::leaf(&env, &clean);
+    let root = Denylist::hash_pair(&env, &Denylist::leaf(&env, &listed), &sibling);
+    client.anchor_denylist_root(&officer, &root, &2u32);
+    let proofs: Vec<Vec<BytesN<32>>> = vec![&env, vec![&env, sibling]];
+    client.denylist_addresses(&officer, &vec![&env, listed.clone()], &proofs);
+    Setup {
+        env,
+        client,
+        officer,
+        listed,
+        clean,
+    }
+}
+
+#[test]
+fn test_denied_user_cannot_swap_until_delisted() {
+    let s = setup();
+    assert!(s.client.is_denied(&s.listed));
+
+    let result = s.client.try_swap(&XLM, &USDC, &1_000, &s.listed);
+    assert_eq!(result, Err(Ok(ContractError::AddressDenied)));
+
+    s.client
+        .delist_addresses(&s.officer, &vec![&s.env, s.listed.clone()]);
+    let result = s.client.try_swap(&XLM, &USDC, &1_000, &s.listed);
+    assert_ne!(result, Err(Ok(ContractError::AddressDenied)));
+}
+
+#[test]
+fn test_screen_addresses_records_matches() {
+    let s = setup();
+    let caller = Address::generate(&s.env);
+    let batch = vec![&s.env, s.clean.clone(), s.listed.clone()];
+
+    let matched = s
+        .client
+        .screen_addresses(&caller, &symbol_short!("swap"), &batch);
+    assert_eq!(matched, vec![&s.env, s.listed.clone()]);
+    assert_eq!(s.env.events().all().events().len(), 1);
+
+    s.env.ledger().with_mut(|l| l.timestamp = 1_000_100);
+    s.client
+        .screen_addresses(&caller, &symbol_short!("fill"), &batch);
+    assert_eq!(
+        s.client.get_denylist_matches(&s.listed),
+        Some(DenylistMatches {
+            count: 2,
+            first_at: 1_000_000,
+            last_at: 1_000_100,
+            last_context: symbol_short!("fill"),
+        })
+    );
+    assert_eq!(s.client.get_denylist_matches(&s.clean), None);
+    assert_eq!(s.client.get_denylist_match_total(), 2);
+
+    let result = s
+        .client
+        .try_screen_addresses(&caller, &symbol_short!("swap"), &Vec::new(&s.env));
+    assert_eq!(result, Err(Ok(ContractError::DenylistBatchTooLarge)));
+}
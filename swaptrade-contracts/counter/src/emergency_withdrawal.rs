@@ -0,0 +1,443 @@
+// emergency_withdrawal.rs
+//
+// Emergency exit: while the protocol is at EmergencyLevel::High or above, or
+// the global circuit breaker is open, the emergency multisig can arm an
+// exit path that lets users leave at last-known balances. An exit burns LP
+// shares for their pro-rata reserves (skipping fee and farm accounting) and
+// closes stakes at full principal, forfeiting unclaimed bonuses; both are
+// credited to the user's Portfolio balance. No oracle reads or rate limits
+// are involved.
+//
+// Portfolio balances are internal accounting and can be minted, so only the
+// part backed by SEP-41 tokens the user deposited through custody is
+// transferred out. The per-user custody ledger records those deposits; the
+// rest of the balance stays in the Portfolio.
+
+use soroban_sdk::{contracttype, symbol_short, token, Address, Env, Map, Symbol, Vec};
+
+use crate::emergency::{EmergencyController, EmergencyLevel};
+use crate::errors::ContractError;
+use crate::events;
+use crate::portfolio::{Asset, Portfolio};
+use crate::risk_management::valuation::STAKED_ASSET;
+use crate::risk_management::CircuitBreaker;
+use crate::staking_bonus::StakingBonusManager;
+
+/// Approval state for the exit path.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct EmergencyExitState {
+    pub approvals: Vec<Address>,
+    /// Set once approvals reach the emergency standard threshold.
+    pub enabled_at: Option<u64>,
+}
+
+/// What a user received from their most recent emergency exit.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct EmergencyExitReceipt {
+    /// Amount transferred to the user per asset, up to what they deposited
+    /// through custody.
+    pub assets: Map<Symbol, i128>,
+    /// Amount left in the user's Portfolio balance per asset, because it is
+    /// not backed by a custody deposit.
+    pub credited: Map<Symbol, i128>,
+    pub staked_principal: i128,
+    pub bonuses_forfeited: i128,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EmergencyExitKey {
+    ExitState,
+    Receipt(Address),
+    /// SEP-41 token custody deposits of an asset are made in.
+    CustodyToken(Symbol),
+    /// Custody deposits a user has not yet withdrawn, per asset.
+    CustodyDeposit(Address, Symbol),
+}
+
+pub struct EmergencyWithdrawal;
+
+impl EmergencyWithdrawal {
+    /// Approve arming emergency exit. Exit is enabled once the emergency
+    /// standard threshold of signers has approved, and only while an
+    /// emergency is in force. Returns the approval count.
+    pub fn approve(env: &Env, signer: Address) -> Result<u32, ContractError> {
+        let config = EmergencyController::require_signer(env, &signer)?;
+        if !Self::emergency_in_force(env) {
+            return Err(ContractError::EmergencyLevelTooLow);
+        }
+
+        let mut state = Self::get_state(env);
+        if state.approvals.contains(&signer) {
+            return Err(ContractError::AlreadyApproved);
+        }
+        state.approvals.push_back(signer.clone());
+
+        let now = env.ledger().timestamp();
+        let approvals = state.approvals.len();
+        events::emergency_exit_approved(env, signer, approvals, config.standard_threshold, now);
+
+        if state.enabled_at.is_none() && approvals >= config.standard_threshold {
+            state.enabled_at = Some(now);
+            events::emergency_exit_enabled(
+                env,
+                EmergencyController::current_level(env) as u32,
+                CircuitBreaker::is_circuit_breaker_active(env),
+                now,
+            );
+        }
+        env.storage()
+            .persistent()
+            .set(&EmergencyExitKey::ExitState, &state);
+        Ok(approvals)
+    }
+
+    /// Disarm emergency exit and clear all approvals.
+    pub fn disable(env: &Env, signer: Address) -> Result<(), ContractError> {
+        EmergencyController::require_signer(env, &signer)?;
+        env.storage()
+            .persistent()
+            .remove(&EmergencyExitKey::ExitState);
+        events::emergency_exit_disabled(env, signer, env.ledger().timestamp());
+        Ok(())
+    }
+
+    /// Exit is usable once armed and while the emergency is still in force.
+    /// It stays armed across emergencies until a signer disables it.
+    pub fn is_enabled(env: &Env) -> bool {
+        Self::get_state(env).enabled_at.is_some() && Self::emergency_in_force(env)
+    }
+
+    /// Release everything `user` holds in the protocol.
+    pub fn exit(env: &Env, user: Address) -> Result<EmergencyExitReceipt, ContractError> {
+        user.require_auth();
+        if !Self::is_enabled(env) {
+            return Err(ContractError::EmergencyExitNotEnabled);
+        }
+        if EmergencyController::is_frozen(env, &user) {
+            return Err(ContractError::EmergencyRestricted);
+        }
+
+        let now = env.ledger().timestamp();
+        let mut released: Map<Symbol, i128> = Map::new(env);
+        let mut portfolio: Portfolio = env
+            .storage()
+            .instance()
+            .get(&())
+            .unwrap_or_else(|| Portfolio::new(env));
+        let mut registry = crate::load_pool_registry(env);
+
+        // Built-in XLM/USDCSIM pool shares, pro-rata without fee accounting.
+        let usdc = symbol_short!("USDCSIM");
+        if let Some(mut pos) = portfolio.get_lp_position(user.clone()) {
+            let total = portfolio.get_total_lp_tokens();
+            if pos.lp_tokens_minted > 0 && total > 0 {
+                let xlm_in_pool = portfolio.get_liquidity(Asset::XLM);
+                let usdc_in_pool = portfolio.get_liquidity(Asset::Custom(usdc.clone()));
+                let xlm_out = pos.lp_tokens_minted * xlm_in_pool / total;
+                let usdc_out = pos.lp_tokens_minted * usdc_in_pool / total;
+
+                portfolio.set_liquidity(Asset::XLM, xlm_in_pool - xlm_out);
+                portfolio.set_liquidity(Asset::Custom(usdc.clone()), usdc_in_pool - usdc_out);
+                portfolio.subtract_total_lp_tokens(pos.lp_tokens_minted);
+                pos.lp_tokens_minted = 0;
+                pos.xlm_deposited = 0;
+                pos.usdc_deposited = 0;
+                portfolio.set_lp_position(user.clone(), pos);
+
+                Self::release(
+                    env,
+                    &mut released,
+                    &user,
+                    symbol_short!("lp"),
+                    symbol_short!("XLM"),
+                    xlm_out,
+                    None,
+                );
+                Self::release(
+                    env,
+                    &mut released,
+                    &user,
+                    symbol_short!("lp"),
+                    usdc.clone(),
+                    usdc_out,
+                    None,
+                );
+            }
+        }
+
+        // Pool registry shares.
+        for (pool_id, amount_a, amount_b) in
+            registry.emergency_withdraw_all(env, user.clone())?.iter()
+        {
+            let pool = registry.get_pool(pool_id).unwrap();
+            Self::release(
+                env,
+                &mut released,
+                &user,
+                symbol_short!("pool"),
+                pool.token_a,
+                amount_a,
+                Some(pool_id),
+            );
+            Self::release(
+                env,
+                &mut released,
+                &user,
+                symbol_short!("pool"),
+                pool.token_b,
+                amount_b,
+                Some(pool_id),
+            );
+        }
+
+        // Stakes, at full principal.
+        let (staked_principal, bonuses_forfeited) =
+            StakingBonusManager::emergency_withdraw(env, user.clone());
+        Self::release(
+            env,
+            &mut released,
+            &user,
+            symbol_short!("stake"),
+            STAKED_ASSET,
+            staked_principal,
+            None,
+        );
+
+        for (asset, amount) in released.iter() {
+            portfolio.credit(env, Self::asset_of(&asset), user.clone(), amount);
+        }
+
+        // Pay out the backed part of every balance; the rest stays in the
+        // Portfolio.
+        let mut transfers: Map<Symbol, i128> = Map::new(env);
+        let mut credited: Map<Symbol, i128> = Map::new(env);
+        for (asset, balance) in portfolio.assets_of(env, &user).iter() {
+            if balance <= 0 {
+                continue;
+            }
+            let symbol = Self::symbol_of(&asset);
+            let backed = if Self::custody_token(env, &symbol).is_some() {
+                balance.min(Self::deposited(env, &user, &symbol))
+            } else {
+                0
+            };
+            if backed > 0 {
+                portfolio.debit(env, asset, user.clone(), backed);
+                Self::set_deposited(
+                    env,
+                    &user,
+                    &symbol,
+                    Self::deposited(env, &user, &symbol) - backed,
+                );
+                events::emergency_withdrawal(
+                    env,
+                    user.clone(),
+                    symbol_short!("balance"),
+                    symbol.clone(),
+                    backed,
+                    None,
+                    now,
+                );
+                transfers.set(symbol.clone(), backed);
+            }
+            if balance > backed {
+                credited.set(symbol, balance - backed);
+            }
+        }
+
+        if released.is_empty() && transfers.is_empty() {
+            return Err(ContractError::NothingToWithdraw);
+        }
+
+        env.storage().instance().set(&(), &portfolio);
+        crate::save_pool_registry(env, &registry);
+
+        let contract = env.current_contract_address();
+        for (asset, amount) in transfers.iter() {
+            let token =
+                Self::custody_token(env, &asset).ok_or(ContractError::CustodyTokenNotSet)?;
+            token::Client::new(env, &token).transfer(&contract, &user, &amount);
+        }
+
+        let receipt = EmergencyExitReceipt {
+            assets: transfers,
+            credited,
+            staked_principal,
+            bonuses_forfeited,
+            timestamp: now,
+        };
+        env.storage()
+            .persistent()
+            .set(&EmergencyExitKey::Receipt(user.clone()), &receipt);
+        events::emergency_exit_completed(env, user, staked_principal, bonuses_forfeited, now);
+        Ok(receipt)
+    }
+
+    /// Admin only: register the SEP-41 token custody deposits of `asset`
+    /// are made in.
+    pub fn set_custody_token(
+        env: &Env,
+        admin: Address,
+        asset: Symbol,
+        token: Address,
+    ) -> Result<(), ContractError> {
+        admin.require_auth();
+        crate::admin::require_admin(env, &admin)?;
+        env.storage()
+            .persistent()
+            .set(&EmergencyExitKey::CustodyToken(asset), &token);
+        Ok(())
+    }
+
+    pub fn custody_token(env: &Env, asset: &Symbol) -> Option<Address> {
+        env.storage()
+            .persistent()
+            .get(&EmergencyExitKey::CustodyToken(asset.clone()))
+    }
+
+    /// Transfer `amount` of `asset`'s custody token from `user` into the
+    /// contract and credit it to their Portfolio balance. The caller is
+    /// responsible for authorizing `user`.
+    pub fn deposit(
+        env: &Env,
+        user: Address,
+        asset: Symbol,
+        amount: i128,
+    ) -> Result<(), ContractError> {
+        if amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        let token = Self::custody_token(env, &asset).ok_or(ContractError::CustodyTokenNotSet)?;
+        token::Client::new(env, &token).transfer(&user, &env.current_contract_address(), &amount);
+
+        let mut portfolio: Portfolio = env
+            .storage()
+            .instance()
+            .get(&())
+            .unwrap_or_else(|| Portfolio::new(env));
+        portfolio.credit(env, Self::asset_of(&asset), user.clone(), amount);
+        env.storage().instance().set(&(), &portfolio);
+
+        let deposited = Self::deposited(env, &user, &asset) + amount;
+        Self::set_deposited(env, &user, &asset, deposited);
+        events::custody_deposited(env, user, asset, amount, env.ledger().timestamp());
+        Ok(())
+    }
+
+    /// Debit `amount` of `asset` from `user`'s Portfolio balance and transfer
+    /// it back out of custody. Limited to what the user has deposited. The
+    /// caller is responsible for authorizing `user`.
+    pub fn withdraw(
+        env: &Env,
+        user: Address,
+        asset: Symbol,
+        amount: i128,
+    ) -> Result<(), ContractError> {
+        if amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        let token = Self::custody_token(env, &asset).ok_or(ContractError::CustodyTokenNotSet)?;
+        let deposited = Self::deposited(env, &user, &asset);
+        let mut portfolio: Portfolio = env
+            .storage()
+            .instance()
+            .get(&())
+            .unwrap_or_else(|| Portfolio::new(env));
+        let balance = portfolio.balance_of(env, Self::asset_of(&asset), user.clone());
+        if amount > deposited || amount > balance {
+            return Err(ContractError::InsufficientBalance);
+        }
+        portfolio.debit(env, Self::asset_of(&asset), user.clone(), amount);
+        env.storage().instance().set(&(), &portfolio);
+        Self::set_deposited(env, &user, &asset, deposited - amount);
+
+        token::Client::new(env, &token).transfer(&env.current_contract_address(), &user, &amount);
+        events::custody_withdrawn(env, user, asset, amount, env.ledger().timestamp());
+        Ok(())
+    }
+
+    /// Custody deposits of `asset` that `user` can still withdraw.
+    pub fn deposited(env: &Env, user: &Address, asset: &Symbol) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&EmergencyExitKey::CustodyDeposit(
+                user.clone(),
+                asset.clone(),
+            ))
+            .unwrap_or(0)
+    }
+
+    fn set_deposited(env: &Env, user: &Address, asset: &Symbol, amount: i128) {
+        let key = EmergencyExitKey::CustodyDeposit(user.clone(), asset.clone());
+        if amount > 0 {
+            env.storage().persistent().set(&key, &amount);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+    }
+
+    pub fn get_state(env: &Env) -> EmergencyExitState {
+        env.storage()
+            .persistent()
+            .get(&EmergencyExitKey::ExitState)
+            .unwrap_or_else(|| EmergencyExitState {
+                approvals: Vec::new(env),
+                enabled_at: None,
+            })
+    }
+
+    pub fn get_receipt(env: &Env, user: Address) -> Option<EmergencyExitReceipt> {
+        env.storage()
+            .persistent()
+            .get(&EmergencyExitKey::Receipt(user))
+    }
+
+    fn emergency_in_force(env: &Env) -> bool {
+        EmergencyController::current_level(env) >= EmergencyLevel::High
+            || CircuitBreaker::is_circuit_breaker_active(env)
+    }
+
+    fn asset_of(token: &Symbol) -> Asset {
+        if *token == symbol_short!("XLM") {
+            Asset::XLM
+        } else {
+            Asset::Custom(token.clone())
+        }
+    }
+
+    fn symbol_of(asset: &Asset) -> Symbol {
+        match asset {
+            Asset::XLM => symbol_short!("XLM"),
+            Asset::Custom(symbol) => symbol.clone(),
+        }
+    }
+
+    fn release(
+        env: &Env,
+        assets: &mut Map<Symbol, i128>,
+        user: &Address,
+        source: Symbol,
+        asset: Symbol,
+        amount: i128,
+        pool_id: Option<u64>,
+    ) {
+        if amount <= 0 {
+            return;
+        }
+        let prev = assets.get(asset.clone()).unwrap_or(0);
+        assets.set(asset.clone(), prev + amount);
+        events::emergency_withdrawal(
+            env,
+            user.clone(),
+            source,
+            asset,
+            amount,
+            pool_id,
+            env.ledger().timestamp(),
+        );
+    }
+}
@@ -0,0 +1,384 @@
+#![cfg(test)]
+
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::token::{StellarAssetClient, TokenClient};
+use soroban_sdk::{symbol_short, Address, Env, String, Symbol, Vec};
+
+use crate::emergency::{EmergencyConfig, EmergencyLevel};
+use crate::errors::ContractError;
+use crate::kyc::KYCStatus;
+use crate::portfolio::{Asset, LPPosition, Portfolio};
+use crate::risk_management::volume_circuit_breaker;
+use crate::staking_bonus::StakingBonusManager;
+use crate::storage::ADMIN_KEY;
+use crate::{CounterContract, CounterContractClient};
+
+struct Setup {
+    env: Env,
+    contract_id: Address,
+    client: CounterContractClient<'static>,
+    admin: Address,
+    signers: Vec<Address>,
+    xlm: Address,
+    btc: Address,
+}
+
+impl Setup {
+    fn signer(&self, i: u32) -> Address {
+        self.signers.get(i).unwrap()
+    }
+
+    /// Raise the emergency level to High with the critical three approvals.
+    fn declare_high(&self) {
+        let id = self.client.set_emergency_level(
+            &self.signer(0),
+            &EmergencyLevel::High,
+            &String::from_str(&self.env, "exploit"),
+            &None,
+        );
+        self.client.approve_emergency(&id, &self.signer(1));
+        self.client.approve_emergency(&id, &self.signer(2));
+    }
+
+    fn token_balance(&self, token: &Address, owner: &Address) -> i128 {
+        TokenClient::new(&self.env, token).balance(owner)
+    }
+
+    /// Pass `user` through KYC and deposit `amount` of the custody `token`
+    /// for `asset` on their behalf.
+    fn deposit(&self, user: &Address, asset: Symbol, token: &Address, amount: i128) {
+        if !self.client.kyc_is_verified(user) {
+            let operator = Address::generate(&self.env);
+            self.client.kyc_add_operator(&self.admin, &operator);
+            self.client.kyc_submit(user);
+            self.client
+                .kyc_update_status(&operator, user, &KYCStatus::InReview, &None);
+            self.client
+                .kyc_update_status(&operator, user, &KYCStatus::Verified, &None);
+        }
+        StellarAssetClient::new(&self.env, token).mint(user, &amount);
+        self.client.deposit_custody(user, &asset, &amount);
+    }
+
+    fn arm_exit(&self) {
+        self.client.approve_emergency_exit(&self.signer(0));
+        self.client.approve_emergency_exit(&self.signer(1));
+    }
+}
+
+fn setup() -> Setup {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|l| l.timestamp = 1_000);
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let mut signers = Vec::new(&env);
+    for _ in 0..3 {
+        signers.push_back(Address::generate(&env));
+    }
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(&ADMIN_KEY, &admin);
+    });
+    client.configure_emergency(&admin, &EmergencyConfig::new(signers.clone()));
+
+    // XLM and BTC are held in custody; USDCSIM has no custody token.
+    let custody = |asset: Symbol| {
+        let token = env
+            .register_stellar_asset_contract_v2(Address::generate(&env))
+            .address();
+        client.set_custody_token(&admin, &asset, &token);
+        token
+    };
+    let xlm = custody(symbol_short!("XLM"));
+    let btc = custody(symbol_short!("BTC"));
+
+    Setup {
+        env,
+        contract_id,
+        client,
+        admin,
+        signers,
+        xlm,
+        btc,
+    }
+}
+
+/// Give `user` custody deposits of 15_000 XLM and 4_000 BTC, an unbacked
+/// Portfolio balance, a share of the built-in pool, a registry pool position
+/// and a 90-day stake.
+fn fund_user(s: &Setup, user: &Address) -> u64 {
+    s.deposit(user, symbol_short!("XLM"), &s.xlm, 15_000);
+    s.deposit(user, symbol_short!("BTC"), &s.btc, 4_000);
+    let pool_id = s.client.register_pool(
+        &s.admin,
+        &symbol_short!("XLM"),
+        &symbol_short!("BTC"),
+        &10_000,
+        &10_000,
+        &30,
+    );
+    s.env.as_contract(&s.contract_id, || {
+        let mut portfolio: Portfolio = s.env.storage().instance().get(&()).unwrap();
+        portfolio.mint(&s.env, Asset::XLM, user.clone(), 500);
+        portfolio.set_liquidity(Asset::XLM, 4_000);
+        portfolio.set_liquidity(Asset::Custom(symbol_short!("USDCSIM")), 2_000);
+        portfolio.add_total_lp_tokens(2_000);
+        portfolio.set_lp_position(
+            user.clone(),
+            LPPosition {
+                lp_address: user.clone(),
+                xlm_deposited: 2_000,
+                usdc_deposited: 1_000,
+                lp_tokens_minted: 1_000,
+            },
+        );
+        s.env.storage().instance().set(&(), &portfolio);
+
+        let mut registry = crate::load_pool_registry(&s.env);
+        registry
+            .add_liquidity(&s.env, pool_id, 10_000, 10_000, user.clone())
+            .unwrap();
+        crate::save_pool_registry(&s.env, &registry);
+
+        StakingBonusManager::stake(&s.env, user.clone(), 10_000, 90).unwrap();
+    });
+    pool_id
+}
+
+#[test]
+fn test_exit_requires_emergency_and_multisig() {
+    let s = setup();
+    let user = Address::generate(&s.env);
+    fund_user(&s, &user);
+
+    assert_eq!(
+        s.client.try_approve_emergency_exit(&s.signer(0)),
+        Err(Ok(ContractError::EmergencyLevelTooLow))
+    );
+    assert_eq!(
+        s.client.try_emergency_exit(&user),
+        Err(Ok(ContractError::EmergencyExitNotEnabled))
+    );
+
+    s.declare_high();
+    assert_eq!(
+        s.client.try_approve_emergency_exit(&user),
+        Err(Ok(ContractError::NotEmergencyAdmin))
+    );
+    assert_eq!(s.client.approve_emergency_exit(&s.signer(0)), 1);
+    assert_eq!(
+        s.client.try_approve_emergency_exit(&s.signer(0)),
+        Err(Ok(ContractError::AlreadyApproved))
+    );
+    assert!(!s.client.is_emergency_exit_enabled());
+    assert_eq!(
+        s.client.try_emergency_exit(&user),
+        Err(Ok(ContractError::EmergencyExitNotEnabled))
+    );
+
+    assert_eq!(s.client.approve_emergency_exit(&s.signer(1)), 2);
+    assert!(s.client.is_emergency_exit_enabled());
+
+    s.client.disable_emergency_exit(&s.signer(2));
+    assert!(!s.client.is_emergency_exit_enabled());
+}
+
+#[test]
+fn test_exit_releases_balances_lp_and_stake() {
+    let s = setup();
+    let user = Address::generate(&s.env);
+    let pool_id = fund_user(&s, &user);
+    s.declare_high();
+    s.arm_exit();
+
+    let receipt = s.client.emergency_exit(&user);
+
+    // Only the custody deposits are paid out in tokens.
+    let xlm = symbol_short!("XLM");
+    let btc = symbol_short!("BTC");
+    assert_eq!(receipt.assets.get(xlm.clone()).unwrap(), 15_000);
+    assert_eq!(receipt.assets.get(btc.clone()).unwrap(), 4_000);
+    assert_eq!(receipt.assets.get(symbol_short!("USDCSIM")), None);
+
+    // The rest stays in the Portfolio: the unbacked 500 XLM, half of the
+    // built-in pool's 4_000 XLM, half the registry pool's 20_000 XLM after
+    // the user's deposit, and the 10_000 staked principal.
+    assert_eq!(
+        receipt.credited.get(xlm).unwrap(),
+        500 + 2_000 + 10_000 + 10_000
+    );
+    assert_eq!(receipt.credited.get(btc).unwrap(), 10_000);
+    assert_eq!(
+        receipt.credited.get(symbol_short!("USDCSIM")).unwrap(),
+        1_000
+    );
+
+    assert_eq!(s.token_balance(&s.xlm, &user), 15_000);
+    assert_eq!(s.token_balance(&s.btc, &user), 4_000);
+    assert_eq!(s.token_balance(&s.xlm, &s.contract_id), 0);
+    assert_eq!(s.token_balance(&s.btc, &s.contract_id), 0);
+    assert_eq!(
+        s.client.get_custody_deposit(&user, &symbol_short!("XLM")),
+        0
+    );
+    assert_eq!(receipt.staked_principal, 10_000);
+    assert_eq!(receipt.bonuses_forfeited, 2_000);
+    assert_eq!(s.client.get_emergency_exit_receipt(&user), Some(receipt));
+
+    s.env.as_contract(&s.contract_id, || {
+        let portfolio: Portfolio = s.env.storage().instance().get(&()).unwrap();
+        assert_eq!(
+            portfolio.balance_of(&s.env, Asset::XLM, user.clone()),
+            22_500
+        );
+        assert_eq!(
+            portfolio.balance_of(
+                &s.env,
+                Asset::Custom(symbol_short!("USDCSIM")),
+                user.clone()
+            ),
+            1_000
+        );
+        assert_eq!(portfolio.get_total_lp_tokens(), 1_000);
+        assert_eq!(portfolio.get_liquidity(Asset::XLM), 2_000);
+
+        let registry = crate::load_pool_registry(&s.env);
+        assert_eq!(registry.get_lp_balance(pool_id, user.clone()), 0);
+        assert_eq!(registry.get_pool(pool_id).unwrap().reserve_a, 10_000);
+
+        assert_eq!(
+            StakingBonusManager::get_user_total_staked(&s.env, user.clone()),
+            0
+        );
+        assert_eq!(StakingBonusManager::get_total_staked(&s.env), 0);
+    });
+
+    assert_eq!(
+        s.client.try_emergency_exit(&user),
+        Err(Ok(ContractError::NothingToWithdraw))
+    );
+}
+
+#[test]
+fn test_circuit_breaker_trip_allows_exit() {
+    let s = setup();
+    let user = Address::generate(&s.env);
+    fund_user(&s, &user);
+
+    s.env.as_contract(&s.contract_id, || {
+        volume_circuit_breaker::set_threshold(&s.env, s.admin.clone(), 3_600, 100).unwrap();
+        assert!(volume_circuit_breaker::check_and_record_volume(
+            &s.env, 1_000
+        ));
+    });
+    s.arm_exit();
+    assert!(s.client.is_emergency_exit_enabled());
+    assert_eq!(s.client.emergency_exit(&user).staked_principal, 10_000);
+    assert_eq!(s.token_balance(&s.xlm, &user), 15_000);
+
+    // Once the breaker is reset the exit path closes again.
+    s.client.reset_circuit_breaker(&s.admin);
+    assert!(!s.client.is_emergency_exit_enabled());
+}
+
+#[test]
+fn test_frozen_user_cannot_exit() {
+    let s = setup();
+    let user = Address::generate(&s.env);
+    fund_user(&s, &user);
+    s.declare_high();
+    s.arm_exit();
+
+    s.client.set_user_frozen(&s.signer(2), &user, &true);
+    s.client.set_user_frozen(&s.signer(1), &user, &true);
+    assert_eq!(
+        s.client.try_emergency_exit(&user),
+        Err(Ok(ContractError::EmergencyRestricted))
+    );
+}
+
+#[test]
+fn test_unbacked_balances_are_not_paid_out() {
+    let s = setup();
+    let user = Address::generate(&s.env);
+    fund_user(&s, &user);
+
+    // A balance minted straight into the Portfolio, plus a stake, with no
+    // custody deposits behind either.
+    let minter = Address::generate(&s.env);
+    s.env.as_contract(&s.contract_id, || {
+        let mut portfolio: Portfolio = s.env.storage().instance().get(&()).unwrap();
+        portfolio.mint(&s.env, Asset::XLM, minter.clone(), 50_000);
+        s.env.storage().instance().set(&(), &portfolio);
+        StakingBonusManager::stake(&s.env, minter.clone(), 1_000, 30).unwrap();
+    });
+    s.declare_high();
+    s.arm_exit();
+
+    let receipt = s.client.emergency_exit(&minter);
+    assert!(receipt.assets.is_empty());
+    assert_eq!(receipt.staked_principal, 1_000);
+    assert_eq!(
+        receipt.credited.get(symbol_short!("XLM")).unwrap(),
+        50_000 + 1_000
+    );
+    assert_eq!(s.token_balance(&s.xlm, &minter), 0);
+    s.env.as_contract(&s.contract_id, || {
+        let portfolio: Portfolio = s.env.storage().instance().get(&()).unwrap();
+        assert_eq!(
+            portfolio.balance_of(&s.env, Asset::XLM, minter.clone()),
+            51_000
+        );
+    });
+
+    // The depositor's tokens are untouched.
+    assert_eq!(s.token_balance(&s.xlm, &s.contract_id), 15_000);
+    s.client.emergency_exit(&user);
+    assert_eq!(s.token_balance(&s.xlm, &user), 15_000);
+}
+
+#[test]
+fn test_custody_deposit_and_withdraw() {
+    let s = setup();
+    let user = Address::generate(&s.env);
+    s.deposit(&user, symbol_short!("XLM"), &s.xlm, 1_000);
+    assert_eq!(s.token_balance(&s.xlm, &s.contract_id), 1_000);
+    assert_eq!(
+        s.client.get_custody_deposit(&user, &symbol_short!("XLM")),
+        1_000
+    );
+
+    // Minted Portfolio balance cannot be withdrawn past the deposit.
+    s.env.as_contract(&s.contract_id, || {
+        let mut portfolio: Portfolio = s.env.storage().instance().get(&()).unwrap();
+        portfolio.mint(&s.env, Asset::XLM, user.clone(), 5_000);
+        s.env.storage().instance().set(&(), &portfolio);
+    });
+    assert_eq!(
+        s.client
+            .try_withdraw_custody(&user, &symbol_short!("XLM"), &1_001),
+        Err(Ok(ContractError::InsufficientBalance))
+    );
+    assert_eq!(
+        s.client
+            .try_deposit_custody(&user, &symbol_short!("USDCSIM"), &1),
+        Err(Ok(ContractError::CustodyTokenNotSet))
+    );
+
+    s.client
+        .withdraw_custody(&user, &symbol_short!("XLM"), &400);
+    assert_eq!(s.token_balance(&s.xlm, &user), 400);
+    assert_eq!(
+        s.client.get_custody_deposit(&user, &symbol_short!("XLM")),
+        600
+    );
+    s.env.as_contract(&s.contract_id, || {
+        let portfolio: Portfolio = s.env.storage().instance().get(&()).unwrap();
+        assert_eq!(
+            portfolio.balance_of(&s.env, Asset::XLM, user.clone()),
+            1_000 + 5_000 - 400
+        );
+    });
+}
@@ -1,3 +1,4 @@
+use compliance::DenylistError;
 use soroban_sdk::contracterror;
 
 /// Unified error catalog for SwapTrade contracts.
@@ -8,10 +9,20 @@ use soroban_sdk::contracterror;
 ///   100–109  Validation (amounts, tokens, pairs)
 ///   200–209  Oracle / invariants
 ///   300–309  Rate limiting / slippage
-///   400–409  Liquidity pool
+///   400–409  Liquidity pool / flash loans
 ///   500–509  KYC
-///   600–609  Staking
+///   600–619  Staking / vote escrow
+///   620–629  Gauge controller
 ///   700–709  Emergency / circuit-breaker
+///   1000–1019 Treasury
+///   1020–1039 Upgrades
+///   1040–1049 Decentralization schedule
+///   1050–1059 Margin trading
+///   1060–1069 Insurance fund
+///   1070–1079 KYC attestations
+///   1080–1089 KYC re-verification
+///   1090–1099 Jurisdiction geofencing
+///   1100–1109 Sanctions screening
 #[contracterror]
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum SwapTradeError {
@@ -35,6 +46,18 @@ pub enum SwapTradeError {
     ProposalCanceled = 99,
     VotingEnded = 110,
     NotInCouncil = 111,
+    UnsupportedProposalType = 112,
+    /// Self-delegation, a share worth less than the minimum delegated
+    /// power, or shares on a topic above 100%.
+    InvalidDelegation = 113,
+    TooManyDelegators = 114,
+    DelegationNotFound = 115,
+    /// Quadratic votes are limited to KYC-verified addresses.
+    IdentityNotVerified = 116,
+    /// Treasury grants must be voted quadratically.
+    InvalidVotingMode = 117,
+    /// Conviction votes can only switch sides.
+    VoteUnchanged = 118,
 
     // ── Trading / contract state ────────────────────────────────────────────
     TradingPaused = 10,
@@ -69,6 +92,14 @@ pub enum SwapTradeError {
     // ── Liquidity pool ──────────────────────────────────────────────────────
     LPPositionNotFound = 400,
     InsufficientLPTokens = 401,
+    /// Flash-loan receiver returned `false` from `on_flash_loan`.
+    FlashLoanCallbackFailed = 402,
+    /// Flash-loan receiver did not hold principal plus fee after the callback.
+    FlashLoanNotRepaid = 403,
+    /// A flash loan was requested while another one is still in progress.
+    FlashLoanReentrancy = 404,
+    /// No SEP-41 token contract is registered for the flash-loan asset.
+    FlashLoanAssetNotListed = 405,
 
     // ── KYC ─────────────────────────────────────────────────────────────────
     KYCVerificationRequired = 500,
@@ -97,9 +128,54 @@ pub enum SwapTradeError {
     // Farming errors
     FarmingPoolNotFound = 606,
     InsufficientStakedLP = 607,
+    /// No reward schedule exists at the given index for the farm.
+    RewardScheduleNotFound = 608,
+    /// The farm already carries the maximum number of reward tokens.
+    TooManyRewardTokens = 609,
+    /// Leftover rewards can only be reclaimed once the schedule has ended.
+    RewardScheduleNotEnded = 610,
+    /// The user already holds a vote-escrow lock; increase it instead.
+    VeLockExists = 611,
+    /// The user holds no vote-escrow lock.
+    VeLockNotFound = 612,
+    /// The vote-escrow lock has expired and can only be withdrawn.
+    VeLockExpired = 613,
+    /// The vote-escrow lock has not reached its unlock time yet.
+    VeLockNotExpired = 614,
+    /// Unlock time is in the past, beyond the maximum lock, or not later
+    /// than the current unlock time.
+    InvalidUnlockTime = 615,
+
+    // ── Gauge controller ────────────────────────────────────────────────────
+    /// The pool has no gauge registered.
+    GaugeNotFound = 620,
+    /// A gauge is already registered for the pool.
+    GaugeAlreadyExists = 621,
+    /// The user's gauge votes would exceed 100% of their voting power.
+    GaugeVoteOverAllocated = 622,
+    /// The pool's emission rate is set by gauge votes, not by the admin.
+    GaugeControlledPool = 623,
+    /// The maximum number of gauges is already registered.
+    TooManyGauges = 624,
 
     // ── Emergency / circuit-breaker ─────────────────────────────────────────
     NotEmergencyAdmin = 700,
+    /// The operation is not allowed at the current emergency level.
+    EmergencyRestricted = 701,
+    /// No emergency action exists with the given id.
+    EmergencyActionNotFound = 702,
+    /// The emergency action was already lifted or has expired.
+    EmergencyActionNotActive = 703,
+    /// Escalating again before the escalation cooldown has elapsed.
+    EscalationCooldownActive = 704,
+    /// Emergency resume is only available at High level or above.
+    EmergencyLevelTooLow = 705,
+    /// Emergency exit has not been enabled by the emergency multisig.
+    EmergencyExitNotEnabled = 706,
+    /// The user has no balances, LP shares or stakes left to withdraw.
+    NothingToWithdraw = 707,
+    /// No custody token is registered for the asset.
+    CustodyTokenNotSet = 708,
 
     // ── Referral system ─────────────────────────────────────────────────────
     SelfReferral = 800,
@@ -109,7 +185,145 @@ pub enum SwapTradeError {
     // ── Faucet ─────────────────────────────────────────────────────────────
     FaucetRateLimited = 900,
     FaucetNotConfigured = 901,
+
+    // ── Treasury ────────────────────────────────────────────────────────────
+    /// The treasury signer set has not been configured.
+    TreasuryNotConfigured = 1000,
+    /// The treasury holds less of the token than requested.
+    InsufficientTreasuryBalance = 1001,
+    /// The withdrawal would exceed the token's per-period spending limit.
+    SpendingLimitExceeded = 1002,
+    /// The treasury proposal expired before execution.
+    TreasuryProposalExpired = 1003,
+    /// The treasury proposal has not reached its approval threshold.
+    TreasuryProposalNotApproved = 1004,
+    /// Payout schedule must satisfy start <= cliff <= end with start < end.
+    InvalidPayoutSchedule = 1005,
+    /// No payout stream exists with the given id.
+    StreamNotFound = 1006,
+    /// Nothing has vested on the stream since the last claim.
+    NothingVested = 1007,
+    /// The caller is not a treasury signer.
+    NotTreasurySigner = 1008,
+    /// No treasury token is mapped for one of the pool's token symbols.
+    FeeTokenNotMapped = 1009,
+    /// Governance has not configured participation rewards.
+    RewardsNotConfigured = 1010,
+    /// The reward epoch has not ended yet.
+    EpochNotFinalized = 1011,
+    /// The participant already claimed this epoch's reward.
+    RewardAlreadyClaimed = 1012,
+    /// The participant earned nothing claimable in the epoch.
+    NoRewardsToClaim = 1013,
+    /// The emergency withdrawal exceeds its share of the treasury balance.
+    EmergencyWithdrawalTooLarge = 1014,
+
+    // ── Upgrades ────────────────────────────────────────────────────────────
+    /// Upgrade signers and the deployed version have not been configured.
+    UpgradeNotConfigured = 1020,
+    /// The caller is not an upgrade signer.
+    NotUpgradeSigner = 1021,
+    /// The target version does not match the upgrade type, or another
+    /// upgrade has landed since the proposal was made.
+    InvalidVersionBump = 1022,
+    /// The upgrade proposal is no longer collecting approvals.
+    UpgradeNotPending = 1023,
+    /// The upgrade proposal expired before reaching its approvals.
+    UpgradeProposalExpired = 1024,
+    /// There is no previous WASM hash to roll back to.
+    NoRollbackTarget = 1025,
+    /// Rollback is disabled in the upgrade configuration.
+    RollbackDisabled = 1026,
+
+    // ── Decentralization schedule ───────────────────────────────────────────
+    /// A decentralization schedule has already been committed.
+    ScheduleAlreadyCommitted = 1040,
+    /// The schedule does not hash to the published commitment.
+    ScheduleCommitmentMismatch = 1041,
+    /// The schedule starts in the future or has the wrong guardian set.
+    InvalidSchedule = 1042,
+    /// The current phase no longer allows this admin action.
+    AdminPhaseRestricted = 1043,
+    /// The current phase requires a guardian-approved admin call.
+    AdminCallNotApproved = 1044,
+    /// The caller is not a schedule guardian.
+    NotGuardian = 1045,
+
+    // ── Margin trading ──────────────────────────────────────────────────────
+    /// Leverage exceeds the tier's maximum or leaves no room above maintenance.
+    LeverageTooHigh = 1050,
+    /// One of the position's assets has no oracle price.
+    MarginPriceUnavailable = 1051,
+    /// No lending reserve exists for the asset.
+    LendingReserveNotFound = 1052,
+    /// The lending reserve has too little idle cash for the borrow or withdrawal.
+    InsufficientReserveLiquidity = 1053,
+    /// No margin position exists with the given id.
+    MarginPositionNotFound = 1054,
+    /// The position would be below maintenance margin, or cannot repay its debt.
+    InsufficientMargin = 1055,
+    /// The position is at or above maintenance margin.
+    PositionNotLiquidatable = 1056,
+    /// A lending reserve already exists for the asset.
+    LendingReserveExists = 1057,
+
+    // ── Insurance fund ──────────────────────────────────────────────────────
+    /// No shortfall exists with the given id.
+    ShortfallNotFound = 1060,
+    /// The shortfall has already been covered or socialised.
+    ShortfallAlreadySettled = 1061,
+
+    // ── KYC attestations ────────────────────────────────────────────────────
+    /// The issuing operator has not registered a signing key.
+    OperatorKeyNotRegistered = 1070,
+    /// The attestation's expiry has passed.
+    AttestationExpired = 1071,
+    /// The credential hash is on the revocation list.
+    CredentialRevoked = 1072,
+    /// An attestation for this credential hash has already been submitted.
+    AttestationAlreadySubmitted = 1073,
+    /// Attestation levels start at 1.
+    InvalidKYCLevel = 1074,
+    /// The user's verification level is below the feature's minimum.
+    KYCLevelTooLow = 1075,
+    /// The jurisdiction on the user's record is not one the feature is open
+    /// in.
+    JurisdictionNotPermitted = 1076,
+
+    // ── KYC re-verification ─────────────────────────────────────────────────
+    /// The user's verification has lapsed; only reducing positions is
+    /// allowed until it is renewed.
+    KYCVerificationExpired = 1080,
+
+    // ── Jurisdiction geofencing ─────────────────────────────────────────────
+    /// The user has no jurisdiction on record and the feature is limited to
+    /// listed jurisdictions.
+    JurisdictionUnknown = 1090,
+
+    // ── Sanctions screening ─────────────────────────────────────────────────
+    /// A party to the call is on the sanctions denylist.
+    AddressDenied = 1100,
+    /// Caller is not the compliance officer.
+    NotComplianceOfficer = 1101,
+    /// No denylist root has been anchored yet.
+    DenylistRootNotAnchored = 1102,
+    /// A denylist proof does not match the anchored root.
+    InvalidDenylistProof = 1103,
+    /// Denylist batch is empty or larger than `MAX_BATCH_SIZE`.
+    DenylistBatchTooLarge = 1104,
 }
 
 /// Alias kept for modules that still import `ContractError` by name.
 pub type ContractError = SwapTradeError;
+
+impl From<DenylistError> for SwapTradeError {
+    fn from(err: DenylistError) -> Self {
+        match err {
+            DenylistError::NotComplianceOfficer => SwapTradeError::NotComplianceOfficer,
+            DenylistError::RootNotAnchored => SwapTradeError::DenylistRootNotAnchored,
+            DenylistError::InvalidProof => SwapTradeError::InvalidDenylistProof,
+            DenylistError::BatchTooLarge => SwapTradeError::DenylistBatchTooLarge,
+            DenylistError::AddressDenied => SwapTradeError::AddressDenied,
+        }
+    }
+}
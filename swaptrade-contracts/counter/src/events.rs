@@ -1,4 +1,5 @@
-use soroban_sdk::{contracttype, Address, Env, Symbol, Vec};
+use crate::risk_management::{BreakerScope, TripRule};
+use soroban_sdk::{contracttype, Address, BytesN, Env, Symbol, Vec};
 
 #[contracttype]
 #[derive(Clone)]
@@ -389,23 +390,135 @@ pub fn emergency_fee_override_deactivated(env: &Env, timestamp: u64) {
     );
 }
 
-/// Emitted when the volume-threshold circuit breaker trips and pauses trading.
+/// Emitted when a circuit breaker trips and halts trading in its scope.
 ///
 /// Off-chain indexers can subscribe to this event to trigger notifications,
 /// webhook calls, or dashboard alerts for the emergency-recovery workflow.
 ///
-/// Topic  : ("CircuitBreakerTripped",)
-/// Payload: (current_volume, threshold, window_secs, timestamp)
+/// Topic  : ("CircuitBreakerTripped", rule)
+/// Payload: (scope, observed, threshold, timestamp)
 pub fn circuit_breaker_tripped(
     env: &Env,
-    current_volume: i128,
+    scope: BreakerScope,
+    rule: TripRule,
+    observed: i128,
     threshold: i128,
-    window_secs: u64,
     timestamp: u64,
 ) {
     env.events().publish(
-        (Symbol::new(env, "CircuitBreakerTripped"),),
-        (current_volume, threshold, window_secs, timestamp),
+        (Symbol::new(env, "CircuitBreakerTripped"), rule),
+        (scope, observed, threshold, timestamp),
+    );
+}
+
+/// Emitted when a circuit breaker closes, either reset by an admin or
+/// governance (`manual`) or recovered from half-open.
+///
+/// Topic  : ("CircuitBreakerClosed",)
+/// Payload: (scope, manual, timestamp)
+pub fn circuit_breaker_closed(env: &Env, scope: BreakerScope, manual: bool, timestamp: u64) {
+    env.events().publish(
+        (Symbol::new(env, "CircuitBreakerClosed"),),
+        (scope, manual, timestamp),
+    );
+}
+
+/// Emitted when an emergency signer approves enabling emergency exit.
+///
+/// Topic  : ("EmergencyExitApproved", signer)
+/// Payload: (approvals, threshold, timestamp)
+pub fn emergency_exit_approved(
+    env: &Env,
+    signer: Address,
+    approvals: u32,
+    threshold: u32,
+    timestamp: u64,
+) {
+    env.events().publish(
+        (Symbol::new(env, "EmergencyExitApproved"), signer),
+        (approvals, threshold, timestamp),
+    );
+}
+
+/// Emitted when emergency exit reaches its approval threshold.
+///
+/// Topic  : ("EmergencyExitEnabled",)
+/// Payload: (emergency_level, circuit_breaker_tripped, timestamp)
+pub fn emergency_exit_enabled(env: &Env, level: u32, breaker_tripped: bool, timestamp: u64) {
+    env.events().publish(
+        (Symbol::new(env, "EmergencyExitEnabled"),),
+        (level, breaker_tripped, timestamp),
+    );
+}
+
+/// Emitted when an emergency signer disables emergency exit.
+///
+/// Topic  : ("EmergencyExitDisabled", signer)
+/// Payload: (timestamp,)
+pub fn emergency_exit_disabled(env: &Env, signer: Address, timestamp: u64) {
+    env.events().publish(
+        (Symbol::new(env, "EmergencyExitDisabled"), signer),
+        (timestamp,),
+    );
+}
+
+/// Emitted for each asset released to a user by emergency exit.
+/// `source` is one of `balance`, `lp`, `pool` or `stake`; `pool_id` is
+/// set for pool registry shares.
+///
+/// Topic  : ("EmergencyWithdrawal", user, source)
+/// Payload: (asset, amount, pool_id, timestamp)
+pub fn emergency_withdrawal(
+    env: &Env,
+    user: Address,
+    source: Symbol,
+    asset: Symbol,
+    amount: i128,
+    pool_id: Option<u64>,
+    timestamp: u64,
+) {
+    env.events().publish(
+        (Symbol::new(env, "EmergencyWithdrawal"), user, source),
+        (asset, amount, pool_id, timestamp),
+    );
+}
+
+/// Emitted once a user's emergency exit completes.
+///
+/// Topic  : ("EmergencyExitCompleted", user)
+/// Payload: (staked_principal, bonuses_forfeited, timestamp)
+pub fn emergency_exit_completed(
+    env: &Env,
+    user: Address,
+    staked_principal: i128,
+    bonuses_forfeited: i128,
+    timestamp: u64,
+) {
+    env.events().publish(
+        (Symbol::new(env, "EmergencyExitCompleted"), user),
+        (staked_principal, bonuses_forfeited, timestamp),
+    );
+}
+
+/// Emitted when a user deposits SEP-41 tokens into custody.
+///
+/// Topic  : ("CustodyDeposited", user)
+/// Payload: (asset, amount, timestamp)
+pub fn custody_deposited(env: &Env, user: Address, asset: Symbol, amount: i128, timestamp: u64) {
+    env.events().publish(
+        (Symbol::new(env, "CustodyDeposited"), user),
+        (asset, amount, timestamp),
+    );
+}
+
+/// Emitted when a user withdraws SEP-41 tokens from custody.
+///
+/// Topic  : ("CustodyWithdrawn", user)
+/// Payload: (asset, amount, timestamp)
+pub fn custody_withdrawn(env: &Env, user: Address, asset: Symbol, amount: i128, timestamp: u64) {
+    env.events().publish(
+        (Symbol::new(env, "CustodyWithdrawn"), user),
+        (asset, amount, timestamp),
     );
 }
 
@@ -639,3 +752,134 @@ pub fn gamification_reward_distributed(
         (amount, reason, challenge_id, timestamp),
     );
 }
+
+/// Emitted when the decentralization schedule is committed.
+///
+/// Topic  : ("ScheduleCommitted",)
+/// Payload: (commitment, deployed_at)
+pub fn schedule_committed(env: &Env, commitment: BytesN<32>, deployed_at: u64) {
+    env.events().publish(
+        (Symbol::new(env, "ScheduleCommitted"),),
+        (commitment, deployed_at),
+    );
+}
+
+/// Emitted when the recorded governance phase advances.
+///
+/// Topic  : ("GovernancePhaseAdvanced",)
+/// Payload: (from_phase, to_phase, timestamp)
+pub fn governance_phase_advanced(env: &Env, from_phase: u32, to_phase: u32, timestamp: u64) {
+    env.events().publish(
+        (Symbol::new(env, "GovernancePhaseAdvanced"),),
+        (from_phase, to_phase, timestamp),
+    );
+}
+
+/// Emitted when a guardian approves the admin's next call.
+///
+/// Topic  : ("AdminCallApproved", guardian)
+/// Payload: (approvals, timestamp)
+pub fn admin_call_approved(env: &Env, guardian: Address, approvals: u32, timestamp: u64) {
+    env.events().publish(
+        (Symbol::new(env, "AdminCallApproved"), guardian),
+        (approvals, timestamp),
+    );
+}
+
+/// Emitted when a leveraged position is opened.
+///
+/// Topic  : ("MarginOpened", owner, position_id)
+/// Payload: (held_asset, held_amount, debt_asset, borrowed, leverage_bps, timestamp)
+#[allow(clippy::too_many_arguments)]
+pub fn margin_position_opened(
+    env: &Env,
+    owner: Address,
+    position_id: u64,
+    held_asset: Symbol,
+    held_amount: i128,
+    debt_asset: Symbol,
+    borrowed: i128,
+    leverage_bps: u32,
+    timestamp: u64,
+) {
+    env.events().publish(
+        (Symbol::new(env, "MarginOpened"), owner, position_id),
+        (
+            held_asset,
+            held_amount,
+            debt_asset,
+            borrowed,
+            leverage_bps,
+            timestamp,
+        ),
+    );
+}
+
+/// Emitted when the owner closes a leveraged position.
+///
+/// Topic  : ("MarginClosed", owner, position_id)
+/// Payload: (proceeds, repaid, refund, timestamp)
+pub fn margin_position_closed(
+    env: &Env,
+    owner: Address,
+    position_id: u64,
+    proceeds: i128,
+    repaid: i128,
+    refund: i128,
+    timestamp: u64,
+) {
+    env.events().publish(
+        (Symbol::new(env, "MarginClosed"), owner, position_id),
+        (proceeds, repaid, refund, timestamp),
+    );
+}
+
+/// Emitted when a keeper liquidates a position below maintenance margin.
+///
+/// Topic  : ("MarginLiquidated", owner, position_id)
+/// Payload: (liquidator, proceeds, reward, insurance, bad_debt, timestamp)
+#[allow(clippy::too_many_arguments)]
+pub fn margin_position_liquidated(
+    env: &Env,
+    owner: Address,
+    position_id: u64,
+    liquidator: Address,
+    proceeds: i128,
+    reward: i128,
+    insurance: i128,
+    bad_debt: i128,
+    timestamp: u64,
+) {
+    env.events().publish(
+        (Symbol::new(env, "MarginLiquidated"), owner, position_id),
+        (liquidator, proceeds, reward, insurance, bad_debt, timestamp),
+    );
+}
+
+/// Emitted when a loss is recorded against the insurance fund.
+///
+/// Topic  : ("InsuranceShortfall", shortfall_id)
+/// Payload: (asset, amount)
+pub fn insurance_shortfall_recorded(env: &Env, shortfall_id: u64, asset: Symbol, amount: i128) {
+    env.events().publish(
+        (Symbol::new(env, "InsuranceShortfall"), shortfall_id),
+        (asset, amount),
+    );
+}
+
+/// Emitted when governance settles a shortfall.
+///
+/// Topic  : ("InsuranceSettled", shortfall_id)
+/// Payload: (asset, covered, socialised)
+pub fn insurance_shortfall_settled(
+    env: &Env,
+    shortfall_id: u64,
+    asset: Symbol,
+    covered: i128,
+    socialised: i128,
+) {
+    env.events().publish(
+        (Symbol::new(env, "InsuranceSettled"), shortfall_id),
+        (asset, covered, socialised),
+    );
+}
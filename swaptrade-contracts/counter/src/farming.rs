@@ -1,9 +1,17 @@
+//! Yield Farming / Liquidity Mining Module
+//!
+//! Rewards users for staking LP tokens over time using an accumulator-per-share
+//! pattern that ensures proportional reward distribution.
+//!
+//! Rewards are distributed by *working balance* rather than raw LP stake
+//! (Curve-style boost). Every position earns on 40% of its LP stake, and the
+//! remaining 60% is unlocked in proportion to the user's share of locked
+//! stake in the staking bonus system, for a maximum boost of 2.5x.
+
 use crate::errors::SwapTradeError;
-/// Yield Farming / Liquidity Mining Module
-///
-/// Rewards users for staking LP tokens over time using an accumulator-per-share
-/// pattern that ensures proportional reward distribution.
-use soroban_sdk::{contracttype, symbol_short, Address, Env, Map};
+use crate::gauge_controller::GaugeController;
+use crate::staking_bonus::StakingBonusManager;
+use soroban_sdk::{contracttype, symbol_short, token, Address, Env, Map, Vec};
 
 // ────────────────────────────────────────────────────────────────────────────
 // Constants
@@ -12,6 +20,18 @@ use soroban_sdk::{contracttype, symbol_short, Address, Env, Map};
 /// Minimum staking period (prevents dust staking)
 const MIN_STAKE_AMOUNT: i128 = 100;
 
+/// Share of an LP stake that always earns rewards, in basis points
+const BASE_WEIGHT_BPS: i128 = 4_000;
+
+/// Unboosted weight, in basis points of the base weight (1x)
+pub const BASE_BOOST_BPS: u32 = 10_000;
+
+/// Maximum boost, in basis points of the base weight (2.5x)
+pub const MAX_BOOST_BPS: u32 = 25_000;
+
+/// Maximum number of live (not yet ended) partner reward schedules per farm
+pub const MAX_REWARD_TOKENS: u32 = 8;
+
 // ────────────────────────────────────────────────────────────────────────────
 // Data Structures
 // ────────────────────────────────────────────────────────────────────────────
@@ -30,6 +50,8 @@ pub struct PoolFarmState {
     pub last_update_timestamp: u64,
     /// Total rewards distributed to this pool
     pub total_rewards_distributed: i128,
+    /// Sum of all stakers' boosted working balances
+    pub total_working_supply: i128,
 }
 
 /// User's staking position in a farm pool
@@ -46,20 +68,135 @@ pub struct UserFarmPosition {
     pub staked_at: u64,
     /// Whether the position is active
     pub is_active: bool,
+    /// Boosted balance used for reward accrual, checkpointed on every
+    /// stake, unstake, claim or explicit boost checkpoint
+    pub working_balance: i128,
+}
+
+/// Pool farm state as stored before working balances existed, under
+/// `FarmingKey::PoolState`. Rewards then accrued per raw LP token.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct PoolFarmStateV1 {
+    pub total_staked_lp: i128,
+    pub reward_per_share_accumulator: i128,
+    pub emission_rate: i128,
+    pub last_update_timestamp: u64,
+    pub total_rewards_distributed: i128,
+}
+
+/// User farm position as stored before working balances existed, under
+/// `FarmingKey::UserPosition`
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct UserFarmPositionV1 {
+    pub staked_lp_amount: i128,
+    pub pending_rewards: i128,
+    pub reward_per_share_debt: i128,
+    pub staked_at: u64,
+    pub is_active: bool,
+}
+
+impl From<PoolFarmStateV1> for PoolFarmState {
+    /// Every legacy position earned on its full LP stake, so the working
+    /// supply starts out equal to the LP staked.
+    fn from(v1: PoolFarmStateV1) -> Self {
+        PoolFarmState {
+            total_staked_lp: v1.total_staked_lp,
+            reward_per_share_accumulator: v1.reward_per_share_accumulator,
+            emission_rate: v1.emission_rate,
+            last_update_timestamp: v1.last_update_timestamp,
+            total_rewards_distributed: v1.total_rewards_distributed,
+            total_working_supply: v1.total_staked_lp,
+        }
+    }
+}
+
+impl From<UserFarmPositionV1> for UserFarmPosition {
+    /// Keeps accruing unboosted on the full LP stake until the position is
+    /// next checkpointed.
+    fn from(v1: UserFarmPositionV1) -> Self {
+        UserFarmPosition {
+            staked_lp_amount: v1.staked_lp_amount,
+            pending_rewards: v1.pending_rewards,
+            reward_per_share_debt: v1.reward_per_share_debt,
+            staked_at: v1.staked_at,
+            is_active: v1.is_active,
+            working_balance: v1.staked_lp_amount,
+        }
+    }
+}
+
+/// Pending rewards together with the boost they are accruing at
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct FarmRewardsView {
+    /// Rewards claimable right now
+    pub pending_rewards: i128,
+    /// Effective boost in basis points (10_000 = 1x, 25_000 = 2.5x)
+    pub boost_bps: u32,
+    /// Current boosted working balance
+    pub working_balance: i128,
+    /// Raw LP tokens staked
+    pub staked_lp_amount: i128,
+}
+
+/// A partner-funded reward stream attached to a farm. Emits `rate` tokens
+/// per second between `start_time` and `end_time`, shared across stakers by
+/// working balance like the primary emission.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct RewardSchedule {
+    /// SEP-41 token paid out by this schedule
+    pub token: Address,
+    /// Account that funded the schedule and receives any leftovers
+    pub funder: Address,
+    /// Reward tokens emitted per second while the schedule is live
+    pub rate: i128,
+    pub start_time: u64,
+    pub end_time: u64,
+    /// Reward token per working-balance unit accumulator (scaled by 1e18)
+    pub reward_per_share_accumulator: i128,
+    /// Last time the accumulator was updated
+    pub last_update_timestamp: u64,
+    /// Amount transferred in by the funder (`rate * (end_time - start_time)`)
+    pub total_funded: i128,
+    /// Amount allocated to stakers so far
+    pub total_allocated: i128,
+    /// Whether unallocated rewards have been returned to the funder
+    pub leftover_returned: bool,
+}
+
+/// A user's accounting for one reward schedule
+#[derive(Clone, Debug, Default)]
+#[contracttype]
+pub struct UserRewardState {
+    /// The schedule's accumulator value when this state was last updated
+    pub reward_per_share_debt: i128,
+    /// Rewards accrued but not yet claimed
+    pub pending_rewards: i128,
 }
 
 /// Storage keys for farming module data
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[contracttype]
 pub enum FarmingKey {
-    /// Pool farm state (pool_id -> PoolFarmState)
+    /// Legacy pool farm state (pool_id -> PoolFarmStateV1)
     PoolState(u64),
-    /// User's position in a pool ((pool_id, user) -> UserFarmPosition)
+    /// Legacy user position ((pool_id, user) -> UserFarmPositionV1)
     UserPosition(u64, Address),
     /// Admin address (for setting emission rates)
     Admin,
     /// Total rewards across all pools
     TotalRewardsDistributed,
+    /// Partner reward schedules of a pool (pool_id -> Vec<RewardSchedule>)
+    RewardSchedules(u64),
+    /// User's accounting for one reward schedule ((pool_id, user, index) -> UserRewardState)
+    UserReward(u64, Address, u32),
+    /// Pool farm state (pool_id -> PoolFarmState)
+    PoolStateV2(u64),
+    /// User's position in a pool ((pool_id, user) -> UserFarmPosition)
+    UserPositionV2(u64, Address),
 }
 
 // ────────────────────────────────────────────────────────────────────────────
@@ -84,30 +221,64 @@ impl FarmingManager {
             .expect("Farming module not initialized")
     }
 
+    /// Load a pool's farm state, upgrading a legacy V1 entry on read
+    fn load_pool_state(env: &Env, pool_id: u64) -> Option<PoolFarmState> {
+        let storage = env.storage().persistent();
+        storage
+            .get::<_, PoolFarmState>(&FarmingKey::PoolStateV2(pool_id))
+            .or_else(|| {
+                storage
+                    .get::<_, PoolFarmStateV1>(&FarmingKey::PoolState(pool_id))
+                    .map(PoolFarmState::from)
+            })
+    }
+
+    fn save_pool_state(env: &Env, pool_id: u64, pool_state: &PoolFarmState) {
+        env.storage()
+            .persistent()
+            .set(&FarmingKey::PoolStateV2(pool_id), pool_state);
+    }
+
+    /// Load a user's farm position, upgrading a legacy V1 entry on read
+    fn load_position(env: &Env, pool_id: u64, user: &Address) -> Option<UserFarmPosition> {
+        let storage = env.storage().persistent();
+        storage
+            .get::<_, UserFarmPosition>(&FarmingKey::UserPositionV2(pool_id, user.clone()))
+            .or_else(|| {
+                storage
+                    .get::<_, UserFarmPositionV1>(&FarmingKey::UserPosition(pool_id, user.clone()))
+                    .map(UserFarmPosition::from)
+            })
+    }
+
+    fn save_position(env: &Env, pool_id: u64, user: &Address, position: &UserFarmPosition) {
+        env.storage()
+            .persistent()
+            .set(&FarmingKey::UserPositionV2(pool_id, user.clone()), position);
+    }
+
     /// Calculate the reward per share scaling factor (to maintain precision)
     /// We use 1e18 as the scaling factor to avoid floating point operations
     const SCALE_FACTOR: i128 = 1_000_000_000_000_000_000;
 
     /// Update the pool's reward accumulator - must be called before any state changes
     fn update_pool_accumulator(env: &Env, pool_id: u64) -> Result<(), SwapTradeError> {
-        let mut pool_state = env
-            .storage()
-            .persistent()
-            .get::<_, PoolFarmState>(&FarmingKey::PoolState(pool_id))
+        let mut pool_state = Self::load_pool_state(env, pool_id)
             .unwrap_or_else(|| PoolFarmState {
                 total_staked_lp: 0,
                 reward_per_share_accumulator: 0,
                 emission_rate: 0,
                 last_update_timestamp: env.ledger().timestamp(),
                 total_rewards_distributed: 0,
+                total_working_supply: 0,
             });
 
-        if pool_state.total_staked_lp == 0 || pool_state.emission_rate == 0 {
+        Self::update_reward_schedules(env, pool_id, pool_state.total_working_supply);
+
+        if pool_state.total_working_supply == 0 || pool_state.emission_rate == 0 {
             // No stakers or no emissions, just update the timestamp
             pool_state.last_update_timestamp = env.ledger().timestamp();
-            env.storage()
-                .persistent()
-                .set(&FarmingKey::PoolState(pool_id), &pool_state);
+            Self::save_pool_state(env, pool_id, &pool_state);
             return Ok(());
         }
 
@@ -123,7 +294,7 @@ impl FarmingManager {
 
         // Calculate the additional reward per share (scaled to maintain precision)
         let reward_per_share_increase =
-            (new_rewards * Self::SCALE_FACTOR) / pool_state.total_staked_lp;
+            (new_rewards * Self::SCALE_FACTOR) / pool_state.total_working_supply;
 
         // Update the accumulator
         pool_state.reward_per_share_accumulator += reward_per_share_increase;
@@ -131,9 +302,7 @@ impl FarmingManager {
         pool_state.total_rewards_distributed += new_rewards;
 
         // Save the updated pool state
-        env.storage()
-            .persistent()
-            .set(&FarmingKey::PoolState(pool_id), &pool_state);
+        Self::save_pool_state(env, pool_id, &pool_state);
 
         // Update global total
         let mut global_total: i128 = env
@@ -151,29 +320,24 @@ impl FarmingManager {
 
     /// Update a user's pending rewards based on the current pool accumulator
     fn update_user_position(env: &Env, pool_id: u64, user: Address) -> Result<(), SwapTradeError> {
-        let pool_state = env
-            .storage()
-            .persistent()
-            .get::<_, PoolFarmState>(&FarmingKey::PoolState(pool_id))
+        let pool_state = Self::load_pool_state(env, pool_id)
             .ok_or(SwapTradeError::LPPositionNotFound)?;
 
-        let mut user_position = env
-            .storage()
-            .persistent()
-            .get::<_, UserFarmPosition>(&FarmingKey::UserPosition(pool_id, user.clone()))
+        let mut user_position = Self::load_position(env, pool_id, &user)
             .unwrap_or_else(|| UserFarmPosition {
                 staked_lp_amount: 0,
                 pending_rewards: 0,
                 reward_per_share_debt: 0,
                 staked_at: env.ledger().timestamp(),
                 is_active: false,
+                working_balance: 0,
             });
 
-        if user_position.staked_lp_amount > 0 {
+        if user_position.working_balance > 0 {
             // Calculate the accumulated rewards since last update
             let accumulated_rewards = ((pool_state.reward_per_share_accumulator
                 - user_position.reward_per_share_debt)
-                * user_position.staked_lp_amount)
+                * user_position.working_balance)
                 / Self::SCALE_FACTOR;
             user_position.pending_rewards += accumulated_rewards;
         }
@@ -181,13 +345,164 @@ impl FarmingManager {
         // Update the user's debt to the current pool accumulator
         user_position.reward_per_share_debt = pool_state.reward_per_share_accumulator;
 
+        // Settle partner reward schedules against the same working balance
+        let schedules = Self::get_reward_schedules(env, pool_id);
+        for index in 0..schedules.len() {
+            let schedule = schedules.get(index).unwrap();
+            let key = FarmingKey::UserReward(pool_id, user.clone(), index);
+            let mut state: UserRewardState =
+                env.storage().persistent().get(&key).unwrap_or_default();
+            if user_position.working_balance > 0 {
+                state.pending_rewards += ((schedule.reward_per_share_accumulator
+                    - state.reward_per_share_debt)
+                    * user_position.working_balance)
+                    / Self::SCALE_FACTOR;
+            }
+            state.reward_per_share_debt = schedule.reward_per_share_accumulator;
+            env.storage().persistent().set(&key, &state);
+        }
+
+        Self::save_position(env, pool_id, &user, &user_position);
+
+        Ok(())
+    }
+
+    /// Advance every reward schedule of a pool to the current time. Emissions
+    /// that fall into a period with no working supply are left unallocated
+    /// and can later be reclaimed by the funder.
+    fn update_reward_schedules(env: &Env, pool_id: u64, total_working_supply: i128) {
+        let mut schedules = Self::get_reward_schedules(env, pool_id);
+        if schedules.is_empty() {
+            return;
+        }
+        let now = env.ledger().timestamp();
+
+        for index in 0..schedules.len() {
+            let mut schedule = schedules.get(index).unwrap();
+            Self::advance_schedule(&mut schedule, now, total_working_supply);
+            schedules.set(index, schedule);
+        }
+
         env.storage()
             .persistent()
-            .set(&FarmingKey::UserPosition(pool_id, user), &user_position);
+            .set(&FarmingKey::RewardSchedules(pool_id), &schedules);
+    }
+
+    /// Accrue `schedule` up to `now` against `total_working_supply`
+    fn advance_schedule(schedule: &mut RewardSchedule, now: u64, total_working_supply: i128) {
+        let from = schedule.last_update_timestamp.max(schedule.start_time);
+        let to = now.min(schedule.end_time);
+        if to > from && total_working_supply > 0 {
+            let new_rewards = ((to - from) as i128) * schedule.rate;
+            schedule.reward_per_share_accumulator +=
+                (new_rewards * Self::SCALE_FACTOR) / total_working_supply;
+            schedule.total_allocated += new_rewards;
+        }
+        schedule.last_update_timestamp = now;
+    }
+
+    /// Curve-style working balance:
+    /// `min(0.4 * lp + 0.6 * pool_lp * locked / total_locked, lp)`
+    fn calculate_working_balance(
+        env: &Env,
+        user: &Address,
+        staked_lp_amount: i128,
+        pool_total_lp: i128,
+    ) -> i128 {
+        if staked_lp_amount <= 0 {
+            return 0;
+        }
+        let base = (staked_lp_amount * BASE_WEIGHT_BPS) / 10_000;
+
+        let user_locked = StakingBonusManager::get_user_locked_stake(env, user.clone());
+        let total_locked = StakingBonusManager::get_total_locked_stake(env);
+        if user_locked <= 0 || total_locked <= 0 {
+            return base;
+        }
+
+        let boost = (pool_total_lp * (10_000 - BASE_WEIGHT_BPS) / 10_000)
+            .saturating_mul(user_locked)
+            / total_locked;
+        base.saturating_add(boost).min(staked_lp_amount)
+    }
+
+    /// Boost of a working balance relative to the unboosted base weight
+    fn boost_bps(working_balance: i128, staked_lp_amount: i128) -> u32 {
+        let base = (staked_lp_amount * BASE_WEIGHT_BPS) / 10_000;
+        if base <= 0 {
+            return BASE_BOOST_BPS;
+        }
+        let boost = (working_balance * BASE_BOOST_BPS as i128) / base;
+        (boost as u32).clamp(BASE_BOOST_BPS, MAX_BOOST_BPS)
+    }
+
+    /// Recompute a user's working balance from their current LP stake and
+    /// locked stake, and apply the difference to the pool's working supply.
+    /// Must run after the user's pending rewards have been settled.
+    fn checkpoint_working_balance(
+        env: &Env,
+        pool_id: u64,
+        user: Address,
+    ) -> Result<(), SwapTradeError> {
+        let mut pool_state = Self::load_pool_state(env, pool_id)
+            .ok_or(SwapTradeError::LPPositionNotFound)?;
+        let mut user_position = Self::load_position(env, pool_id, &user)
+            .ok_or(SwapTradeError::LPPositionNotFound)?;
+
+        let old_working = user_position.working_balance;
+        let new_working = Self::calculate_working_balance(
+            env,
+            &user,
+            user_position.staked_lp_amount,
+            pool_state.total_staked_lp,
+        );
+        if new_working == old_working {
+            return Ok(());
+        }
+
+        pool_state.total_working_supply =
+            pool_state.total_working_supply - old_working + new_working;
+        user_position.working_balance = new_working;
+
+        Self::save_pool_state(env, pool_id, &pool_state);
+        Self::save_position(env, pool_id, &user, &user_position);
+
+        env.events().publish(
+            (symbol_short!("boost_upd"), user, pool_id),
+            (
+                old_working,
+                new_working,
+                Self::boost_bps(new_working, user_position.staked_lp_amount),
+            ),
+        );
 
         Ok(())
     }
 
+    /// Settle a user's rewards and re-checkpoint their boost. Anyone may call
+    /// this, e.g. after the user's locked stake changes or expires.
+    pub fn checkpoint_boost(env: &Env, pool_id: u64, user: Address) -> Result<u32, SwapTradeError> {
+        Self::update_pool_accumulator(env, pool_id)?;
+        Self::update_user_position(env, pool_id, user.clone())?;
+        Self::checkpoint_working_balance(env, pool_id, user.clone())?;
+        Self::get_effective_boost(env, pool_id, user)
+    }
+
+    /// Get the boost a user's position is currently accruing at, in basis
+    /// points (10_000 = 1x, 25_000 = 2.5x)
+    pub fn get_effective_boost(
+        env: &Env,
+        pool_id: u64,
+        user: Address,
+    ) -> Result<u32, SwapTradeError> {
+        let user_position = Self::load_position(env, pool_id, &user)
+            .ok_or(SwapTradeError::LPPositionNotFound)?;
+        Ok(Self::boost_bps(
+            user_position.working_balance,
+            user_position.staked_lp_amount,
+        ))
+    }
+
     /// Stake LP tokens into a farming pool
     pub fn stake_lp(
         env: &Env,
@@ -206,29 +521,25 @@ impl FarmingManager {
         Self::update_user_position(env, pool_id, user.clone())?;
 
         // Get and update pool state
-        let mut pool_state = env
-            .storage()
-            .persistent()
-            .get::<_, PoolFarmState>(&FarmingKey::PoolState(pool_id))
+        let mut pool_state = Self::load_pool_state(env, pool_id)
             .unwrap_or_else(|| PoolFarmState {
                 total_staked_lp: 0,
                 reward_per_share_accumulator: 0,
                 emission_rate: 0,
                 last_update_timestamp: env.ledger().timestamp(),
                 total_rewards_distributed: 0,
+                total_working_supply: 0,
             });
 
         // Get and update user position
-        let mut user_position = env
-            .storage()
-            .persistent()
-            .get::<_, UserFarmPosition>(&FarmingKey::UserPosition(pool_id, user.clone()))
+        let mut user_position = Self::load_position(env, pool_id, &user)
             .unwrap_or_else(|| UserFarmPosition {
                 staked_lp_amount: 0,
                 pending_rewards: 0,
                 reward_per_share_debt: 0,
                 staked_at: env.ledger().timestamp(),
                 is_active: false,
+                working_balance: 0,
             });
 
         // Update totals
@@ -237,13 +548,10 @@ impl FarmingManager {
         user_position.is_active = true;
 
         // Save updated states
-        env.storage()
-            .persistent()
-            .set(&FarmingKey::PoolState(pool_id), &pool_state);
-        env.storage().persistent().set(
-            &FarmingKey::UserPosition(pool_id, user.clone()),
-            &user_position,
-        );
+        Self::save_pool_state(env, pool_id, &pool_state);
+        Self::save_position(env, pool_id, &user, &user_position);
+
+        Self::checkpoint_working_balance(env, pool_id, user.clone())?;
 
         // Emit event
         env.events().publish(
@@ -272,10 +580,7 @@ impl FarmingManager {
         Self::update_user_position(env, pool_id, user.clone())?;
 
         // Get user position
-        let mut user_position = env
-            .storage()
-            .persistent()
-            .get::<_, UserFarmPosition>(&FarmingKey::UserPosition(pool_id, user.clone()))
+        let mut user_position = Self::load_position(env, pool_id, &user)
             .ok_or(SwapTradeError::LPPositionNotFound)?;
 
         if !user_position.is_active || user_position.staked_lp_amount < amount {
@@ -283,10 +588,7 @@ impl FarmingManager {
         }
 
         // Get and update pool state
-        let mut pool_state = env
-            .storage()
-            .persistent()
-            .get::<_, PoolFarmState>(&FarmingKey::PoolState(pool_id))
+        let mut pool_state = Self::load_pool_state(env, pool_id)
             .ok_or(SwapTradeError::LPPositionNotFound)?;
 
         // Update totals
@@ -298,13 +600,10 @@ impl FarmingManager {
         }
 
         // Save updated states
-        env.storage()
-            .persistent()
-            .set(&FarmingKey::PoolState(pool_id), &pool_state);
-        env.storage().persistent().set(
-            &FarmingKey::UserPosition(pool_id, user.clone()),
-            &user_position,
-        );
+        Self::save_pool_state(env, pool_id, &pool_state);
+        Self::save_position(env, pool_id, &user, &user_position);
+
+        Self::checkpoint_working_balance(env, pool_id, user.clone())?;
 
         // Emit event
         env.events().publish(
@@ -328,13 +627,13 @@ impl FarmingManager {
         Self::update_user_position(env, pool_id, user.clone())?;
 
         // Get user position
-        let mut user_position = env
-            .storage()
-            .persistent()
-            .get::<_, UserFarmPosition>(&FarmingKey::UserPosition(pool_id, user.clone()))
+        let mut user_position = Self::load_position(env, pool_id, &user)
             .ok_or(SwapTradeError::LPPositionNotFound)?;
 
-        if user_position.pending_rewards <= 0 {
+        // Pay out every partner reward token alongside the primary emission
+        let paid_partner_rewards = Self::pay_partner_rewards(env, pool_id, &user);
+
+        if user_position.pending_rewards <= 0 && !paid_partner_rewards {
             return Err(SwapTradeError::NoClaimableBonuses);
         }
 
@@ -343,10 +642,9 @@ impl FarmingManager {
         user_position.pending_rewards = 0; // Zero out pending rewards after claim
 
         // Save the updated position
-        env.storage().persistent().set(
-            &FarmingKey::UserPosition(pool_id, user.clone()),
-            &user_position,
-        );
+        Self::save_position(env, pool_id, &user, &user_position);
+
+        Self::checkpoint_working_balance(env, pool_id, user.clone())?;
 
         // Emit event
         env.events().publish(
@@ -357,6 +655,208 @@ impl FarmingManager {
         Ok(claimed_amount)
     }
 
+    /// Transfer all settled partner rewards to `user`. Returns whether
+    /// anything was paid.
+    fn pay_partner_rewards(env: &Env, pool_id: u64, user: &Address) -> bool {
+        let schedules = Self::get_reward_schedules(env, pool_id);
+        let mut paid = false;
+        for index in 0..schedules.len() {
+            let schedule = schedules.get(index).unwrap();
+            let key = FarmingKey::UserReward(pool_id, user.clone(), index);
+            let mut state: UserRewardState =
+                env.storage().persistent().get(&key).unwrap_or_default();
+            if state.pending_rewards <= 0 {
+                continue;
+            }
+
+            let amount = state.pending_rewards;
+            state.pending_rewards = 0;
+            env.storage().persistent().set(&key, &state);
+
+            token::Client::new(env, &schedule.token).transfer(
+                &env.current_contract_address(),
+                user,
+                &amount,
+            );
+            env.events().publish(
+                (symbol_short!("prtnr_clm"), user.clone(), pool_id),
+                (schedule.token, amount, env.ledger().timestamp() as i64),
+            );
+            paid = true;
+        }
+        paid
+    }
+
+    /// Admin only: attach a partner reward token to a farm. The funder
+    /// transfers `rate * (end_time - start_time)` tokens into the contract
+    /// up front. Returns the schedule index.
+    pub fn add_reward_schedule(
+        env: &Env,
+        pool_id: u64,
+        reward_token: Address,
+        funder: Address,
+        rate: i128,
+        start_time: u64,
+        end_time: u64,
+        admin: Address,
+    ) -> Result<u32, SwapTradeError> {
+        admin.require_auth();
+        funder.require_auth();
+
+        if admin != Self::get_admin(env) {
+            return Err(SwapTradeError::NotAdmin);
+        }
+        if rate <= 0 || end_time <= start_time || end_time <= env.ledger().timestamp() {
+            return Err(SwapTradeError::InvalidAmount);
+        }
+
+        // Bring existing schedules up to date before the list changes
+        Self::update_pool_accumulator(env, pool_id)?;
+
+        // Finished schedules keep their index for outstanding claims but no
+        // longer count toward the cap
+        let mut schedules = Self::get_reward_schedules(env, pool_id);
+        let now = env.ledger().timestamp();
+        let live = schedules.iter().filter(|s| s.end_time > now).count() as u32;
+        if live >= MAX_REWARD_TOKENS {
+            return Err(SwapTradeError::TooManyRewardTokens);
+        }
+
+        let total_funded = ((end_time - start_time) as i128)
+            .checked_mul(rate)
+            .ok_or(SwapTradeError::AmountOverflow)?;
+        token::Client::new(env, &reward_token).transfer(
+            &funder,
+            &env.current_contract_address(),
+            &total_funded,
+        );
+
+        let index = schedules.len();
+        schedules.push_back(RewardSchedule {
+            token: reward_token.clone(),
+            funder: funder.clone(),
+            rate,
+            start_time,
+            end_time,
+            reward_per_share_accumulator: 0,
+            last_update_timestamp: env.ledger().timestamp(),
+            total_funded,
+            total_allocated: 0,
+            leftover_returned: false,
+        });
+        env.storage()
+            .persistent()
+            .set(&FarmingKey::RewardSchedules(pool_id), &schedules);
+
+        env.events().publish(
+            (symbol_short!("rwrd_add"), pool_id, index),
+            (
+                reward_token,
+                funder,
+                rate,
+                start_time,
+                end_time,
+                total_funded,
+            ),
+        );
+
+        Ok(index)
+    }
+
+    /// Return rewards that were never allocated to stakers (e.g. emitted
+    /// while nobody was staked) to the schedule's funder once it has ended.
+    pub fn reclaim_leftover_rewards(
+        env: &Env,
+        pool_id: u64,
+        index: u32,
+        funder: Address,
+    ) -> Result<i128, SwapTradeError> {
+        funder.require_auth();
+
+        Self::update_pool_accumulator(env, pool_id)?;
+
+        let mut schedules = Self::get_reward_schedules(env, pool_id);
+        let mut schedule = schedules
+            .get(index)
+            .ok_or(SwapTradeError::RewardScheduleNotFound)?;
+        if schedule.funder != funder {
+            return Err(SwapTradeError::NotAuthorized);
+        }
+        if env.ledger().timestamp() < schedule.end_time {
+            return Err(SwapTradeError::RewardScheduleNotEnded);
+        }
+        if schedule.leftover_returned {
+            return Err(SwapTradeError::NoClaimableBonuses);
+        }
+
+        let leftover = schedule.total_funded - schedule.total_allocated;
+        schedule.leftover_returned = true;
+        schedules.set(index, schedule.clone());
+        env.storage()
+            .persistent()
+            .set(&FarmingKey::RewardSchedules(pool_id), &schedules);
+
+        if leftover > 0 {
+            token::Client::new(env, &schedule.token).transfer(
+                &env.current_contract_address(),
+                &funder,
+                &leftover,
+            );
+        }
+
+        env.events().publish(
+            (symbol_short!("rwrd_rcl"), pool_id, index),
+            (schedule.token, funder, leftover),
+        );
+
+        Ok(leftover)
+    }
+
+    /// Get all partner reward schedules of a farm
+    pub fn get_reward_schedules(env: &Env, pool_id: u64) -> Vec<RewardSchedule> {
+        env.storage()
+            .persistent()
+            .get(&FarmingKey::RewardSchedules(pool_id))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    /// Get a user's pending partner rewards as (token, amount) pairs, in
+    /// schedule order. Read-only: schedules are projected to the current time
+    /// in memory.
+    pub fn get_pending_partner_rewards(
+        env: &Env,
+        pool_id: u64,
+        user: Address,
+    ) -> Result<Vec<(Address, i128)>, SwapTradeError> {
+        let total_working_supply = Self::load_pool_state(env, pool_id)
+            .map(|state| state.total_working_supply)
+            .unwrap_or(0);
+        let working_balance = Self::load_position(env, pool_id, &user)
+            .map(|position| position.working_balance)
+            .unwrap_or(0);
+        let now = env.ledger().timestamp();
+
+        let schedules = Self::get_reward_schedules(env, pool_id);
+        let mut pending = Vec::new(env);
+        for index in 0..schedules.len() {
+            let mut schedule = schedules.get(index).unwrap();
+            Self::advance_schedule(&mut schedule, now, total_working_supply);
+            let state: UserRewardState = env
+                .storage()
+                .persistent()
+                .get(&FarmingKey::UserReward(pool_id, user.clone(), index))
+                .unwrap_or_default();
+            let mut amount = state.pending_rewards;
+            if working_balance > 0 {
+                amount += ((schedule.reward_per_share_accumulator - state.reward_per_share_debt)
+                    * working_balance)
+                    / Self::SCALE_FACTOR;
+            }
+            pending.push_back((schedule.token, amount));
+        }
+        Ok(pending)
+    }
+
     /// Get pending farm rewards for a user
     pub fn get_pending_farm_rewards(
         env: &Env,
@@ -368,15 +868,35 @@ impl FarmingManager {
         Self::update_pool_accumulator(env, pool_id)?;
         Self::update_user_position(env, pool_id, user.clone())?;
 
-        let user_position = env
-            .storage()
-            .persistent()
-            .get::<_, UserFarmPosition>(&FarmingKey::UserPosition(pool_id, user))
+        let user_position = Self::load_position(env, pool_id, &user)
             .ok_or(SwapTradeError::LPPositionNotFound)?;
 
         Ok(user_position.pending_rewards)
     }
 
+    /// Get pending farm rewards for a user together with the effective boost
+    /// their position is accruing at
+    pub fn get_pending_farm_rewards_with_boost(
+        env: &Env,
+        pool_id: u64,
+        user: Address,
+    ) -> Result<FarmRewardsView, SwapTradeError> {
+        let pending_rewards = Self::get_pending_farm_rewards(env, pool_id, user.clone())?;
+
+        let user_position = Self::load_position(env, pool_id, &user)
+            .ok_or(SwapTradeError::LPPositionNotFound)?;
+
+        Ok(FarmRewardsView {
+            pending_rewards,
+            boost_bps: Self::boost_bps(
+                user_position.working_balance,
+                user_position.staked_lp_amount,
+            ),
+            working_balance: user_position.working_balance,
+            staked_lp_amount: user_position.staked_lp_amount,
+        })
+    }
+
     /// Admin only: Set the emission rate for a pool
     pub fn set_farm_emission_rate(
         env: &Env,
@@ -395,28 +915,39 @@ impl FarmingManager {
             return Err(SwapTradeError::InvalidAmount);
         }
 
+        // Pools with a gauge are driven by gauge votes, not the admin key
+        if GaugeController::is_gauge(env, pool_id) {
+            return Err(SwapTradeError::GaugeControlledPool);
+        }
+
+        Self::apply_emission_rate(env, pool_id, new_emission_rate)
+    }
+
+    /// Set the emission rate for a pool after settling rewards accrued at
+    /// the previous rate. Used by the admin setter and the gauge controller.
+    pub(crate) fn apply_emission_rate(
+        env: &Env,
+        pool_id: u64,
+        new_emission_rate: i128,
+    ) -> Result<(), SwapTradeError> {
         // Update accumulator before changing the emission rate so that all previous
         // rewards are calculated with the old rate
         Self::update_pool_accumulator(env, pool_id)?;
 
         // Get and update pool state
-        let mut pool_state = env
-            .storage()
-            .persistent()
-            .get::<_, PoolFarmState>(&FarmingKey::PoolState(pool_id))
+        let mut pool_state = Self::load_pool_state(env, pool_id)
             .unwrap_or_else(|| PoolFarmState {
                 total_staked_lp: 0,
                 reward_per_share_accumulator: 0,
                 emission_rate: 0,
                 last_update_timestamp: env.ledger().timestamp(),
                 total_rewards_distributed: 0,
+                total_working_supply: 0,
             });
 
         let old_rate = pool_state.emission_rate;
         pool_state.emission_rate = new_emission_rate;
-        env.storage()
-            .persistent()
-            .set(&FarmingKey::PoolState(pool_id), &pool_state);
+        Self::save_pool_state(env, pool_id, &pool_state);
 
         // Emit event
         env.events().publish(
@@ -432,9 +963,7 @@ impl FarmingManager {
         // Update before returning to ensure latest state
         Self::update_pool_accumulator(env, pool_id)?;
 
-        env.storage()
-            .persistent()
-            .get::<_, PoolFarmState>(&FarmingKey::PoolState(pool_id))
+        Self::load_pool_state(env, pool_id)
             .ok_or(SwapTradeError::LPPositionNotFound)
     }
 }
@@ -1,35 +1,46 @@
 #[cfg(test)]
 use super::*;
 use crate::errors::SwapTradeError;
-use crate::farming::FarmingManager;
-use soroban_sdk::{testutils::Address as _, Address, Env};
+use crate::farming::{FarmingKey, FarmingManager, UserRewardState};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger as _},
+    Address, Env,
+};
 
 #[test]
 fn test_farming_proportional_rewards() {
     let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
     let admin = Address::generate(&env);
     let user1 = Address::generate(&env);
     let user2 = Address::generate(&env);
 
     // Initialize farming module
-    FarmingManager::initialize(&env, admin.clone());
+    env.as_contract(&contract_id, || {
+        FarmingManager::initialize(&env, admin.clone())
+    });
 
     let pool_id: u64 = 1;
     let emission_rate: i128 = 10; // 10 reward tokens per second
 
     // Admin sets emission rate
-    FarmingManager::set_farm_emission_rate(&env, pool_id, emission_rate, admin.clone()).unwrap();
+    env.as_contract(&contract_id, || {
+        FarmingManager::set_farm_emission_rate(&env, pool_id, emission_rate, admin.clone()).unwrap()
+    });
 
     // User1 stakes 100 LP tokens
-    user1.require_auth();
-    FarmingManager::stake_lp(&env, pool_id, 100, user1.clone()).unwrap();
+    env.as_contract(&contract_id, || {
+        FarmingManager::stake_lp(&env, pool_id, 100, user1.clone()).unwrap()
+    });
 
     // Advance time by 100 seconds
     env.ledger().set_timestamp(env.ledger().timestamp() + 100);
 
     // User2 stakes 200 LP tokens (total staked now 300)
-    user2.require_auth();
-    FarmingManager::stake_lp(&env, pool_id, 200, user2.clone()).unwrap();
+    env.as_contract(&contract_id, || {
+        FarmingManager::stake_lp(&env, pool_id, 200, user2.clone()).unwrap()
+    });
 
     // Advance time by another 100 seconds (total 200 seconds)
     env.ledger().set_timestamp(env.ledger().timestamp() + 100);
@@ -41,8 +52,12 @@ fn test_farming_proportional_rewards() {
     // User2: only second 100s: 2/3 of 10/s *100s = ~666.666
     // Total user2: 666
 
-    let pending1 = FarmingManager::get_pending_farm_rewards(&env, pool_id, user1.clone()).unwrap();
-    let pending2 = FarmingManager::get_pending_farm_rewards(&env, pool_id, user2.clone()).unwrap();
+    let pending1 = env.as_contract(&contract_id, || {
+        FarmingManager::get_pending_farm_rewards(&env, pool_id, user1.clone()).unwrap()
+    });
+    let pending2 = env.as_contract(&contract_id, || {
+        FarmingManager::get_pending_farm_rewards(&env, pool_id, user2.clone()).unwrap()
+    });
 
     assert!(pending1 > pending2);
     assert_eq!(pending1, 1333);
@@ -53,120 +68,655 @@ fn test_farming_proportional_rewards() {
 #[test]
 fn test_claim_and_double_claim() {
     let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
     let admin = Address::generate(&env);
     let user = Address::generate(&env);
 
-    FarmingManager::initialize(&env, admin.clone());
+    env.as_contract(&contract_id, || {
+        FarmingManager::initialize(&env, admin.clone())
+    });
 
     let pool_id: u64 = 1;
-    FarmingManager::set_farm_emission_rate(&env, pool_id, 10, admin.clone()).unwrap();
+    env.as_contract(&contract_id, || {
+        FarmingManager::set_farm_emission_rate(&env, pool_id, 10, admin.clone()).unwrap()
+    });
 
     // User stakes 100 LP
-    user.require_auth();
-    FarmingManager::stake_lp(&env, pool_id, 100, user.clone()).unwrap();
+    env.as_contract(&contract_id, || {
+        FarmingManager::stake_lp(&env, pool_id, 100, user.clone()).unwrap()
+    });
 
     // Advance time
     env.ledger().set_timestamp(env.ledger().timestamp() + 100);
 
     // First claim should work
-    let claimed = FarmingManager::claim_farm_rewards(&env, pool_id, user.clone()).unwrap();
+    let claimed = env.as_contract(&contract_id, || {
+        FarmingManager::claim_farm_rewards(&env, pool_id, user.clone()).unwrap()
+    });
     assert_eq!(claimed, 1000);
 
     // Second claim should return error (no rewards left)
-    let result = FarmingManager::claim_farm_rewards(&env, pool_id, user.clone());
+    let result = env.as_contract(&contract_id, || {
+        FarmingManager::claim_farm_rewards(&env, pool_id, user.clone())
+    });
     assert!(matches!(result, Err(SwapTradeError::NoClaimableBonuses)));
 
     // Pending rewards should be 0
-    let pending = FarmingManager::get_pending_farm_rewards(&env, pool_id, user.clone()).unwrap();
+    let pending = env.as_contract(&contract_id, || {
+        FarmingManager::get_pending_farm_rewards(&env, pool_id, user.clone()).unwrap()
+    });
     assert_eq!(pending, 0);
 }
 
 #[test]
 fn test_unstake_pays_accrued_rewards() {
     let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
     let admin = Address::generate(&env);
     let user = Address::generate(&env);
 
-    FarmingManager::initialize(&env, admin.clone());
+    env.as_contract(&contract_id, || {
+        FarmingManager::initialize(&env, admin.clone())
+    });
 
     let pool_id: u64 = 1;
-    FarmingManager::set_farm_emission_rate(&env, pool_id, 10, admin.clone()).unwrap();
+    env.as_contract(&contract_id, || {
+        FarmingManager::set_farm_emission_rate(&env, pool_id, 10, admin.clone()).unwrap()
+    });
 
     // Stake
-    user.require_auth();
-    FarmingManager::stake_lp(&env, pool_id, 100, user.clone()).unwrap();
+    env.as_contract(&contract_id, || {
+        FarmingManager::stake_lp(&env, pool_id, 100, user.clone()).unwrap()
+    });
 
     // Wait 50 seconds
     env.ledger().set_timestamp(env.ledger().timestamp() + 50);
 
     // Unstake half
-    FarmingManager::unstake_lp(&env, pool_id, 50, user.clone()).unwrap();
+    env.as_contract(&contract_id, || {
+        FarmingManager::unstake_lp(&env, pool_id, 50, user.clone()).unwrap()
+    });
 
     // Check rewards are accrued
-    let pending = FarmingManager::get_pending_farm_rewards(&env, pool_id, user.clone()).unwrap();
+    let pending = env.as_contract(&contract_id, || {
+        FarmingManager::get_pending_farm_rewards(&env, pool_id, user.clone()).unwrap()
+    });
     assert_eq!(pending, 500);
 
     // Wait another 50 seconds - only 50 LP still staked, so should accumulate another 500
     env.ledger().set_timestamp(env.ledger().timestamp() + 50);
-    let pending = FarmingManager::get_pending_farm_rewards(&env, pool_id, user.clone()).unwrap();
+    let pending = env.as_contract(&contract_id, || {
+        FarmingManager::get_pending_farm_rewards(&env, pool_id, user.clone()).unwrap()
+    });
     assert_eq!(pending, 1000); // 500 from before + 500 from last 50s on remaining 50 LP
 }
 
 #[test]
 fn test_emission_rate_change_only_affects_future() {
     let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
     let admin = Address::generate(&env);
     let user = Address::generate(&env);
 
-    FarmingManager::initialize(&env, admin.clone());
+    env.as_contract(&contract_id, || {
+        FarmingManager::initialize(&env, admin.clone())
+    });
 
     let pool_id: u64 = 1;
     // Initial rate: 10 per second
-    FarmingManager::set_farm_emission_rate(&env, pool_id, 10, admin.clone()).unwrap();
-
-    user.require_auth();
-    FarmingManager::stake_lp(&env, pool_id, 100, user.clone()).unwrap();
+    env.as_contract(&contract_id, || {
+        FarmingManager::set_farm_emission_rate(&env, pool_id, 10, admin.clone()).unwrap()
+    });
+    env.as_contract(&contract_id, || {
+        FarmingManager::stake_lp(&env, pool_id, 100, user.clone()).unwrap()
+    });
 
     // First 100 seconds with rate 10/s: should get 1000 rewards
     env.ledger().set_timestamp(env.ledger().timestamp() + 100);
 
     // Admin updates rate to 20 per second
-    FarmingManager::set_farm_emission_rate(&env, pool_id, 20, admin.clone()).unwrap();
+    env.as_contract(&contract_id, || {
+        FarmingManager::set_farm_emission_rate(&env, pool_id, 20, admin.clone()).unwrap()
+    });
 
     // Another 100 seconds with rate 20/s: should get another 2000 rewards
     env.ledger().set_timestamp(env.ledger().timestamp() + 100);
 
-    let pending = FarmingManager::get_pending_farm_rewards(&env, pool_id, user.clone()).unwrap();
+    let pending = env.as_contract(&contract_id, || {
+        FarmingManager::get_pending_farm_rewards(&env, pool_id, user.clone()).unwrap()
+    });
     assert_eq!(pending, 3000); // Total 3000 = 1000 + 2000
 }
 
 #[test]
 fn test_invalid_stake_amount() {
     let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
     let admin = Address::generate(&env);
     let user = Address::generate(&env);
 
-    FarmingManager::initialize(&env, admin.clone());
+    env.as_contract(&contract_id, || {
+        FarmingManager::initialize(&env, admin.clone())
+    });
 
     let pool_id: u64 = 1;
-    FarmingManager::set_farm_emission_rate(&env, pool_id, 10, admin.clone()).unwrap();
+    env.as_contract(&contract_id, || {
+        FarmingManager::set_farm_emission_rate(&env, pool_id, 10, admin.clone()).unwrap()
+    });
 
     // Try to stake less than minimum
-    user.require_auth();
-    let result = FarmingManager::stake_lp(&env, pool_id, 50, user.clone());
+    let result = env.as_contract(&contract_id, || {
+        FarmingManager::stake_lp(&env, pool_id, 50, user.clone())
+    });
     assert!(matches!(result, Err(SwapTradeError::InvalidAmount)));
 }
 
 #[test]
 fn test_non_admin_cannot_set_emission_rate() {
     let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
     let admin = Address::generate(&env);
     let non_admin = Address::generate(&env);
 
-    FarmingManager::initialize(&env, admin.clone());
+    env.as_contract(&contract_id, || {
+        FarmingManager::initialize(&env, admin.clone())
+    });
 
     let pool_id: u64 = 1;
-    non_admin.require_auth();
-    let result = FarmingManager::set_farm_emission_rate(&env, pool_id, 20, non_admin.clone());
+    let result = env.as_contract(&contract_id, || {
+        FarmingManager::set_farm_emission_rate(&env, pool_id, 20, non_admin.clone())
+    });
     assert!(matches!(result, Err(SwapTradeError::NotAdmin)));
 }
+
+/// Verify `user`, give them `amount` XLM and lock it in the staking bonus
+/// system for `duration_days` through the contract's `stake` entrypoint
+fn lock_stake(env: &Env, contract_id: &Address, user: &Address, amount: i128, duration_days: u32) {
+    use crate::kyc::KYCStatus;
+    use crate::storage::ADMIN_KEY;
+
+    let client = CounterContractClient::new(env, contract_id);
+    let admin = Address::generate(env);
+    let operator = Address::generate(env);
+    env.as_contract(contract_id, || {
+        env.storage().persistent().set(&ADMIN_KEY, &admin)
+    });
+    client.kyc_add_operator(&admin, &operator);
+    client.kyc_submit(user);
+    client.kyc_update_status(&operator, user, &KYCStatus::InReview, &None);
+    client.kyc_update_status(&operator, user, &KYCStatus::Verified, &None);
+
+    client.mint(&symbol_short!("XLM"), user, &amount);
+    client.stake(user, &amount, &duration_days);
+    assert_eq!(client.balance_of(&symbol_short!("XLM"), user), 0);
+}
+
+#[test]
+fn test_locked_stake_boosts_farm_weight() {
+    use crate::farming::{BASE_BOOST_BPS, MAX_BOOST_BPS};
+
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
+    let admin = Address::generate(&env);
+    let unlocked = Address::generate(&env);
+    let locker = Address::generate(&env);
+
+    env.as_contract(&contract_id, || {
+        FarmingManager::initialize(&env, admin.clone())
+    });
+
+    let pool_id: u64 = 1;
+    env.as_contract(&contract_id, || {
+        FarmingManager::set_farm_emission_rate(&env, pool_id, 10, admin.clone()).unwrap()
+    });
+
+    // `locker` holds all locked stake in the staking bonus system
+    lock_stake(&env, &contract_id, &locker, 1_000, 365);
+
+    env.as_contract(&contract_id, || {
+        FarmingManager::stake_lp(&env, pool_id, 100, unlocked.clone()).unwrap()
+    });
+    env.as_contract(&contract_id, || {
+        FarmingManager::stake_lp(&env, pool_id, 100, locker.clone()).unwrap()
+    });
+
+    env.as_contract(&contract_id, || {
+        assert_eq!(
+            FarmingManager::get_effective_boost(&env, pool_id, unlocked.clone()).unwrap(),
+            BASE_BOOST_BPS
+        )
+    });
+    env.as_contract(&contract_id, || {
+        assert_eq!(
+            FarmingManager::get_effective_boost(&env, pool_id, locker.clone()).unwrap(),
+            MAX_BOOST_BPS
+        )
+    });
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + 100);
+
+    // Working balances are 40 and 100, so 1000 rewards split 2:5
+    let unlocked_view = env.as_contract(&contract_id, || {
+        FarmingManager::get_pending_farm_rewards_with_boost(&env, pool_id, unlocked.clone())
+            .unwrap()
+    });
+    let locker_view = env.as_contract(&contract_id, || {
+        FarmingManager::get_pending_farm_rewards_with_boost(&env, pool_id, locker.clone()).unwrap()
+    });
+
+    assert_eq!(unlocked_view.working_balance, 40);
+    assert_eq!(locker_view.working_balance, 100);
+    assert_eq!(unlocked_view.pending_rewards, 285);
+    assert_eq!(locker_view.pending_rewards, 714);
+    assert_eq!(locker_view.boost_bps, MAX_BOOST_BPS);
+}
+
+#[test]
+fn test_unfunded_stake_is_rejected() {
+    use crate::kyc::KYCStatus;
+    use crate::storage::ADMIN_KEY;
+
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    let operator = Address::generate(&env);
+    let user = Address::generate(&env);
+
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(&ADMIN_KEY, &admin)
+    });
+    client.kyc_add_operator(&admin, &operator);
+    client.kyc_submit(&user);
+    client.kyc_update_status(&operator, &user, &KYCStatus::InReview, &None);
+    client.kyc_update_status(&operator, &user, &KYCStatus::Verified, &None);
+
+    // Locked stake that would boost farms has to be escrowed first
+    client.mint(&symbol_short!("XLM"), &user, &999);
+    assert_eq!(
+        client.try_stake(&user, &1_000, &365),
+        Err(Ok(SwapTradeError::InsufficientBalance))
+    );
+    assert_eq!(client.get_user_total_staked(&user), 0);
+}
+
+#[test]
+fn test_boost_falls_back_after_lock_expires_on_checkpoint() {
+    use crate::farming::{BASE_BOOST_BPS, MAX_BOOST_BPS};
+
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+
+    env.as_contract(&contract_id, || {
+        FarmingManager::initialize(&env, admin.clone())
+    });
+
+    let pool_id: u64 = 1;
+    env.as_contract(&contract_id, || {
+        FarmingManager::set_farm_emission_rate(&env, pool_id, 10, admin.clone()).unwrap()
+    });
+
+    lock_stake(&env, &contract_id, &user, 500, 30);
+    env.as_contract(&contract_id, || {
+        FarmingManager::stake_lp(&env, pool_id, 100, user.clone()).unwrap()
+    });
+    env.as_contract(&contract_id, || {
+        assert_eq!(
+            FarmingManager::get_effective_boost(&env, pool_id, user.clone()).unwrap(),
+            MAX_BOOST_BPS
+        )
+    });
+
+    // The boost is only re-evaluated on checkpoint
+    env.ledger()
+        .set_timestamp(env.ledger().timestamp() + 31 * 24 * 60 * 60);
+    env.as_contract(&contract_id, || {
+        assert_eq!(
+            FarmingManager::get_effective_boost(&env, pool_id, user.clone()).unwrap(),
+            MAX_BOOST_BPS
+        )
+    });
+
+    let boost = env.as_contract(&contract_id, || {
+        FarmingManager::checkpoint_boost(&env, pool_id, user.clone()).unwrap()
+    });
+    assert_eq!(boost, BASE_BOOST_BPS);
+
+    // The escrowed principal comes back once the lock has run out
+    let client = CounterContractClient::new(&env, &contract_id);
+    assert_eq!(client.claim_stake(&user, &0), 500);
+    assert_eq!(client.balance_of(&symbol_short!("XLM"), &user), 500);
+}
+
+#[test]
+fn test_expired_locks_do_not_dilute_boost() {
+    use crate::farming::MAX_BOOST_BPS;
+    use crate::staking_bonus::StakingBonusManager;
+
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
+    let admin = Address::generate(&env);
+    let stale = Address::generate(&env);
+    let locker = Address::generate(&env);
+
+    env.as_contract(&contract_id, || {
+        FarmingManager::initialize(&env, admin.clone())
+    });
+    let pool_id: u64 = 1;
+    env.as_contract(&contract_id, || {
+        FarmingManager::set_farm_emission_rate(&env, pool_id, 10, admin.clone()).unwrap()
+    });
+
+    // `stale` still has tokens staked but its lock has run out
+    lock_stake(&env, &contract_id, &stale, 10_000, 30);
+    env.ledger()
+        .set_timestamp(env.ledger().timestamp() + 31 * 24 * 60 * 60);
+    lock_stake(&env, &contract_id, &locker, 100, 365);
+
+    env.as_contract(&contract_id, || {
+        assert_eq!(StakingBonusManager::get_total_staked(&env), 10_100);
+        assert_eq!(StakingBonusManager::get_total_locked_stake(&env), 100);
+    });
+
+    // `locker` holds all of the stake that is still locked
+    env.as_contract(&contract_id, || {
+        FarmingManager::stake_lp(&env, pool_id, 100, locker.clone()).unwrap()
+    });
+    env.as_contract(&contract_id, || {
+        assert_eq!(
+            FarmingManager::get_effective_boost(&env, pool_id, locker.clone()).unwrap(),
+            MAX_BOOST_BPS
+        )
+    });
+}
+
+#[test]
+fn test_legacy_farm_entries_upgrade_on_read() {
+    use crate::farming::{PoolFarmStateV1, UserFarmPositionV1, BASE_BOOST_BPS};
+
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let pool_id: u64 = 1;
+
+    env.as_contract(&contract_id, || {
+        FarmingManager::initialize(&env, admin.clone())
+    });
+
+    // Entries written before working balances existed
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(
+            &FarmingKey::PoolState(pool_id),
+            &PoolFarmStateV1 {
+                total_staked_lp: 100,
+                reward_per_share_accumulator: 0,
+                emission_rate: 10,
+                last_update_timestamp: 0,
+                total_rewards_distributed: 0,
+            },
+        );
+        env.storage().persistent().set(
+            &FarmingKey::UserPosition(pool_id, user.clone()),
+            &UserFarmPositionV1 {
+                staked_lp_amount: 100,
+                pending_rewards: 5,
+                reward_per_share_debt: 0,
+                staked_at: 0,
+                is_active: true,
+            },
+        );
+    });
+
+    // Legacy positions keep earning on their full stake until checkpointed
+    env.ledger().set_timestamp(100);
+    let pending = env.as_contract(&contract_id, || {
+        FarmingManager::get_pending_farm_rewards(&env, pool_id, user.clone()).unwrap()
+    });
+    assert_eq!(pending, 5 + 1_000);
+
+    let boost = env.as_contract(&contract_id, || {
+        FarmingManager::checkpoint_boost(&env, pool_id, user.clone()).unwrap()
+    });
+    assert_eq!(boost, BASE_BOOST_BPS);
+    let pool_state = env.as_contract(&contract_id, || {
+        FarmingManager::get_pool_state(&env, pool_id).unwrap()
+    });
+    assert_eq!(pool_state.total_staked_lp, 100);
+    assert_eq!(pool_state.total_working_supply, 40);
+}
+
+fn setup_partner_token(env: &Env, funder: &Address, amount: i128) -> Address {
+    let issuer = Address::generate(env);
+    let sac = env.register_stellar_asset_contract_v2(issuer);
+    soroban_sdk::token::StellarAssetClient::new(env, &sac.address()).mint(funder, &amount);
+    sac.address()
+}
+
+#[test]
+fn test_claim_pays_primary_and_partner_rewards() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
+    let admin = Address::generate(&env);
+    let funder = Address::generate(&env);
+    let user = Address::generate(&env);
+    let partner_token = env.as_contract(&contract_id, || setup_partner_token(&env, &funder, 5_000));
+    let token_client = env.as_contract(&contract_id, || {
+        soroban_sdk::token::Client::new(&env, &partner_token)
+    });
+
+    env.as_contract(&contract_id, || {
+        FarmingManager::initialize(&env, admin.clone())
+    });
+
+    let pool_id: u64 = 1;
+    env.as_contract(&contract_id, || {
+        FarmingManager::set_farm_emission_rate(&env, pool_id, 10, admin.clone()).unwrap()
+    });
+
+    let now = env.as_contract(&contract_id, || env.ledger().timestamp());
+    let index = env.as_contract(&contract_id, || {
+        FarmingManager::add_reward_schedule(
+            &env,
+            pool_id,
+            partner_token.clone(),
+            funder.clone(),
+            5,
+            now,
+            now + 1_000,
+            admin.clone(),
+        )
+        .unwrap()
+    });
+    assert_eq!(index, 0);
+
+    env.as_contract(&contract_id, || {
+        FarmingManager::stake_lp(&env, pool_id, 100, user.clone()).unwrap()
+    });
+    env.ledger().set_timestamp(now + 400);
+
+    let pending = env.as_contract(&contract_id, || {
+        FarmingManager::get_pending_partner_rewards(&env, pool_id, user.clone()).unwrap()
+    });
+    assert_eq!(pending.get(0).unwrap(), (partner_token.clone(), 2_000));
+
+    let claimed = env.as_contract(&contract_id, || {
+        FarmingManager::claim_farm_rewards(&env, pool_id, user.clone()).unwrap()
+    });
+    assert_eq!(claimed, 4_000);
+
+    assert_eq!(token_client.balance(&funder), 0);
+    assert_eq!(token_client.balance(&user), 2_000);
+    assert_eq!(token_client.balance(&contract_id), 3_000);
+}
+
+#[test]
+fn test_unallocated_partner_rewards_return_to_funder() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
+    let admin = Address::generate(&env);
+    let funder = Address::generate(&env);
+    let user = Address::generate(&env);
+    let partner_token = env.as_contract(&contract_id, || setup_partner_token(&env, &funder, 5_000));
+    let token_client = env.as_contract(&contract_id, || {
+        soroban_sdk::token::Client::new(&env, &partner_token)
+    });
+
+    env.as_contract(&contract_id, || {
+        FarmingManager::initialize(&env, admin.clone())
+    });
+
+    let pool_id: u64 = 1;
+    let now = env.as_contract(&contract_id, || env.ledger().timestamp());
+    env.as_contract(&contract_id, || {
+        FarmingManager::add_reward_schedule(
+            &env,
+            pool_id,
+            partner_token.clone(),
+            funder.clone(),
+            5,
+            now,
+            now + 1_000,
+            admin.clone(),
+        )
+        .unwrap()
+    });
+
+    // Nobody is staked for the first half of the schedule
+    env.ledger().set_timestamp(now + 500);
+    env.as_contract(&contract_id, || {
+        FarmingManager::stake_lp(&env, pool_id, 100, user.clone()).unwrap()
+    });
+
+    let early = env.as_contract(&contract_id, || {
+        FarmingManager::reclaim_leftover_rewards(&env, pool_id, 0, funder.clone())
+    });
+    assert!(matches!(early, Err(SwapTradeError::RewardScheduleNotEnded)));
+
+    // Past the end: only the second half was allocated to the staker
+    env.ledger().set_timestamp(now + 1_200);
+    env.as_contract(&contract_id, || {
+        FarmingManager::claim_farm_rewards(&env, pool_id, user.clone()).unwrap()
+    });
+
+    let leftover = env.as_contract(&contract_id, || {
+        FarmingManager::reclaim_leftover_rewards(&env, pool_id, 0, funder.clone()).unwrap()
+    });
+    assert_eq!(leftover, 2_500);
+
+    let again = env.as_contract(&contract_id, || {
+        FarmingManager::reclaim_leftover_rewards(&env, pool_id, 0, funder.clone())
+    });
+    assert!(matches!(again, Err(SwapTradeError::NoClaimableBonuses)));
+
+    assert_eq!(token_client.balance(&user), 2_500);
+    assert_eq!(token_client.balance(&funder), 2_500);
+    assert_eq!(token_client.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_pending_partner_rewards_is_read_only() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
+    let admin = Address::generate(&env);
+    let funder = Address::generate(&env);
+    let user = Address::generate(&env);
+    let partner_token = setup_partner_token(&env, &funder, 5_000);
+    let pool_id: u64 = 1;
+    let now = env.ledger().timestamp();
+
+    env.as_contract(&contract_id, || {
+        FarmingManager::initialize(&env, admin.clone())
+    });
+    env.as_contract(&contract_id, || {
+        FarmingManager::add_reward_schedule(
+            &env,
+            pool_id,
+            partner_token.clone(),
+            funder.clone(),
+            5,
+            now,
+            now + 1_000,
+            admin.clone(),
+        )
+        .unwrap()
+    });
+    env.as_contract(&contract_id, || {
+        FarmingManager::stake_lp(&env, pool_id, 100, user.clone()).unwrap()
+    });
+    env.ledger().set_timestamp(now + 400);
+
+    env.as_contract(&contract_id, || {
+        let pending =
+            FarmingManager::get_pending_partner_rewards(&env, pool_id, user.clone()).unwrap();
+        assert_eq!(pending.get(0).unwrap(), (partner_token.clone(), 2_000));
+
+        // Neither the schedule nor the user's accounting was checkpointed
+        let schedule = FarmingManager::get_reward_schedules(&env, pool_id)
+            .get(0)
+            .unwrap();
+        assert_eq!(schedule.last_update_timestamp, now);
+        assert_eq!(schedule.total_allocated, 0);
+        let state: Option<UserRewardState> = env
+            .storage()
+            .persistent()
+            .get(&FarmingKey::UserReward(pool_id, user.clone(), 0));
+        assert_eq!(state.unwrap().pending_rewards, 0);
+    });
+}
+
+#[test]
+fn test_reward_token_cap_counts_only_live_schedules() {
+    use crate::farming::MAX_REWARD_TOKENS;
+
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
+    let admin = Address::generate(&env);
+    let funder = Address::generate(&env);
+    let partner_token = setup_partner_token(&env, &funder, 10_000);
+    let pool_id: u64 = 1;
+    let now = env.ledger().timestamp();
+
+    env.as_contract(&contract_id, || {
+        FarmingManager::initialize(&env, admin.clone())
+    });
+    let add = |start: u64| {
+        env.as_contract(&contract_id, || {
+            FarmingManager::add_reward_schedule(
+                &env,
+                pool_id,
+                partner_token.clone(),
+                funder.clone(),
+                1,
+                start,
+                start + 100,
+                admin.clone(),
+            )
+        })
+    };
+
+    for _ in 0..MAX_REWARD_TOKENS {
+        add(now).unwrap();
+    }
+    assert!(matches!(add(now), Err(SwapTradeError::TooManyRewardTokens)));
+
+    // Once the first batch has ended, new schedules can be added
+    env.ledger().set_timestamp(now + 100);
+    assert_eq!(add(now + 100).unwrap(), MAX_REWARD_TOKENS);
+}
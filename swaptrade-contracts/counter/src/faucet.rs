@@ -10,13 +10,16 @@ use crate::portfolio::{Asset, Portfolio};
 #[contracttype]
 enum FaucetStorageKey {
     Config(Symbol),
+    /// Legacy: timestamp of the last claim, read for cooldowns started
+    /// before `NextClaimAt` was stored
     LastClaim((Address, Symbol)),
+    NextClaimAt((Address, Symbol)),
 }
 
 // ── Types ────────────────────────────────────────────────────────────────────
 
 #[contracttype]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct FaucetConfig {
     pub drip_amount: i128,
     pub cooldown_secs: u64,
@@ -24,11 +27,27 @@ pub struct FaucetConfig {
 
 // ── Public API ───────────────────────────────────────────────────────────────
 
+/// Next claim time implied by a legacy `LastClaim` entry, which treated 0 as
+/// "never claimed" and measured the cooldown in force now.
+fn legacy_next_claim_at(
+    env: &Env,
+    user: &Address,
+    asset: &Symbol,
+    config: &FaucetConfig,
+) -> Option<u64> {
+    let last_claim: u64 = env
+        .storage()
+        .persistent()
+        .get(&FaucetStorageKey::LastClaim((user.clone(), asset.clone())))?;
+    (last_claim > 0).then(|| last_claim.saturating_add(config.cooldown_secs))
+}
+
 /// Claim simulated tokens from the faucet for the given asset.
 ///
 /// Enforces a per-user, per-asset cooldown.  First claim always succeeds
 /// (no prior timestamp).  Subsequent claims within the cooldown window are
-/// rejected with `FaucetRateLimited`.
+/// rejected with `FaucetRateLimited`.  The cooldown in force at claim time
+/// applies, so a later config change does not shorten a running cooldown.
 pub fn claim_faucet(env: &Env, user: &Address, asset: Symbol) -> Result<i128, SwapTradeError> {
     user.require_auth();
 
@@ -36,16 +55,22 @@ pub fn claim_faucet(env: &Env, user: &Address, asset: Symbol) -> Result<i128, Sw
     let now = env.ledger().timestamp();
 
     // Check cooldown
-    let last_claim_key = FaucetStorageKey::LastClaim((user.clone(), asset.clone()));
-    let last_claim: u64 = env.storage().persistent().get(&last_claim_key).unwrap_or(0);
+    let next_claim_key = FaucetStorageKey::NextClaimAt((user.clone(), asset.clone()));
+    let next_claim_at: Option<u64> = env
+        .storage()
+        .persistent()
+        .get(&next_claim_key)
+        .or_else(|| legacy_next_claim_at(env, user, &asset, &config));
 
-    if last_claim > 0 && now.saturating_sub(last_claim) < config.cooldown_secs {
+    if next_claim_at.is_some_and(|at| now < at) {
         return Err(SwapTradeError::FaucetRateLimited);
     }
 
-    // Record claim timestamp before minting so the state is consistent even
-    // if the mint callback were to re-enter (unlikely in Soroban, but safe).
-    env.storage().persistent().set(&last_claim_key, &now);
+    // Record the next eligible time before minting so the state is consistent
+    // even if the mint callback were to re-enter (unlikely in Soroban, but safe).
+    env.storage()
+        .persistent()
+        .set(&next_claim_key, &now.saturating_add(config.cooldown_secs));
 
     // Mint tokens into the user's portfolio
     let token = if asset == symbol_short!("XLM") {
@@ -84,7 +109,17 @@ pub fn set_faucet_config(
 ) -> Result<(), SwapTradeError> {
     caller.require_auth();
     admin::require_admin(env, caller)?;
+    apply_faucet_config(env, asset, drip_amount, cooldown_secs)
+}
 
+/// Set an asset's faucet configuration without an admin check; used by
+/// governance execution.
+pub(crate) fn apply_faucet_config(
+    env: &Env,
+    asset: Symbol,
+    drip_amount: i128,
+    cooldown_secs: u64,
+) -> Result<(), SwapTradeError> {
     if drip_amount <= 0 {
         return Err(SwapTradeError::InvalidAmount);
     }
@@ -123,12 +158,13 @@ mod tests {
     const DRIP_AMOUNT: i128 = 1_000_000;
     const COOLDOWN_SECS: u64 = 3600; // 1 hour
 
-    fn setup() -> (Env, Address) {
+    fn setup() -> (Env, Address, Address) {
         let env = Env::default();
         env.mock_all_auths_allowing_non_root_auth();
-        let _contract_id = env.register(CounterContract, ());
+        let contract_id = env.register(CounterContract, ());
         let admin = Address::generate(&env);
-        (env, admin)
+        env.as_contract(&contract_id, || crate::admin::set_admin(&env, &admin));
+        (env, contract_id, admin)
     }
 
     fn configure_faucet(env: &Env, admin: &Address, asset: Symbol) {
@@ -139,50 +175,58 @@ mod tests {
 
     #[test]
     fn test_admin_can_set_faucet_config() {
-        let (env, admin) = setup();
+        let (env, contract_id, admin) = setup();
         let asset = symbol_short!("XLM");
 
-        configure_faucet(&env, &admin, asset.clone());
+        env.as_contract(&contract_id, || {
+            configure_faucet(&env, &admin, asset.clone())
+        });
 
-        let config = get_faucet_config(&env, asset).unwrap();
+        let config = env.as_contract(&contract_id, || get_faucet_config(&env, asset).unwrap());
         assert_eq!(config.drip_amount, DRIP_AMOUNT);
         assert_eq!(config.cooldown_secs, COOLDOWN_SECS);
     }
 
     #[test]
     fn test_non_admin_cannot_set_faucet_config() {
-        let (env, _admin) = setup();
+        let (env, contract_id, _admin) = setup();
         let non_admin = Address::generate(&env);
         let asset = symbol_short!("XLM");
 
-        let result = set_faucet_config(&env, &non_admin, asset, DRIP_AMOUNT, COOLDOWN_SECS);
+        let result = env.as_contract(&contract_id, || {
+            set_faucet_config(&env, &non_admin, asset, DRIP_AMOUNT, COOLDOWN_SECS)
+        });
         assert_eq!(result, Err(SwapTradeError::NotAdmin));
     }
 
     #[test]
     fn test_cannot_set_zero_drip_amount() {
-        let (env, admin) = setup();
+        let (env, contract_id, admin) = setup();
         let asset = symbol_short!("XLM");
 
-        let result = set_faucet_config(&env, &admin, asset, 0, COOLDOWN_SECS);
+        let result = env.as_contract(&contract_id, || {
+            set_faucet_config(&env, &admin, asset, 0, COOLDOWN_SECS)
+        });
         assert_eq!(result, Err(SwapTradeError::InvalidAmount));
     }
 
     #[test]
     fn test_cannot_set_negative_drip_amount() {
-        let (env, admin) = setup();
+        let (env, contract_id, admin) = setup();
         let asset = symbol_short!("XLM");
 
-        let result = set_faucet_config(&env, &admin, asset, -100, COOLDOWN_SECS);
+        let result = env.as_contract(&contract_id, || {
+            set_faucet_config(&env, &admin, asset, -100, COOLDOWN_SECS)
+        });
         assert_eq!(result, Err(SwapTradeError::InvalidAmount));
     }
 
     #[test]
     fn test_get_config_unconfigured_asset() {
-        let (env, _admin) = setup();
+        let (env, contract_id, _admin) = setup();
         let asset = symbol_short!("XLM");
 
-        let result = get_faucet_config(&env, asset);
+        let result = env.as_contract(&contract_id, || get_faucet_config(&env, asset));
         assert_eq!(result, Err(SwapTradeError::FaucetNotConfigured));
     }
 
@@ -190,279 +234,397 @@ mod tests {
 
     #[test]
     fn test_first_claim_succeeds() {
-        let (env, admin) = setup();
+        let (env, contract_id, admin) = setup();
         let user = Address::generate(&env);
         let asset = symbol_short!("XLM");
 
-        configure_faucet(&env, &admin, asset.clone());
+        env.as_contract(&contract_id, || {
+            configure_faucet(&env, &admin, asset.clone())
+        });
         env.ledger().set_timestamp(1000);
 
-        let result = claim_faucet(&env, &user, asset);
+        let result = env.as_contract(&contract_id, || claim_faucet(&env, &user, asset));
         assert_eq!(result, Ok(DRIP_AMOUNT));
     }
 
     #[test]
     fn test_claim_updates_balance() {
-        let (env, admin) = setup();
+        let (env, contract_id, admin) = setup();
         let user = Address::generate(&env);
         let asset = symbol_short!("XLM");
 
-        configure_faucet(&env, &admin, asset.clone());
+        env.as_contract(&contract_id, || {
+            configure_faucet(&env, &admin, asset.clone())
+        });
         env.ledger().set_timestamp(1000);
 
-        claim_faucet(&env, &user, asset.clone()).unwrap();
+        env.as_contract(&contract_id, || {
+            claim_faucet(&env, &user, asset.clone()).unwrap()
+        });
 
         // Check that the user's balance reflects the drip
-        let portfolio: Portfolio = env
-            .storage()
-            .instance()
-            .get(&())
-            .unwrap_or_else(|| Portfolio::new(&env));
-        let balance = portfolio.balance_of(&env, Asset::XLM, user);
+        let portfolio: Portfolio = env.as_contract(&contract_id, || {
+            env.storage()
+                .instance()
+                .get(&())
+                .unwrap_or_else(|| Portfolio::new(&env))
+        });
+        let balance = env.as_contract(&contract_id, || {
+            portfolio.balance_of(&env, Asset::XLM, user)
+        });
         assert_eq!(balance, DRIP_AMOUNT);
     }
 
     #[test]
     fn test_second_claim_within_cooldown_rejects() {
-        let (env, admin) = setup();
+        let (env, contract_id, admin) = setup();
         let user = Address::generate(&env);
         let asset = symbol_short!("XLM");
 
-        configure_faucet(&env, &admin, asset.clone());
+        env.as_contract(&contract_id, || {
+            configure_faucet(&env, &admin, asset.clone())
+        });
 
         // First claim at t=1000
         env.ledger().set_timestamp(1000);
-        claim_faucet(&env, &user, asset.clone()).unwrap();
+        env.as_contract(&contract_id, || {
+            claim_faucet(&env, &user, asset.clone()).unwrap()
+        });
 
         // Second claim at t=1000 + 1800 (half cooldown) – should fail
         env.ledger().set_timestamp(1000 + COOLDOWN_SECS / 2);
-        let result = claim_faucet(&env, &user, asset);
+        let result = env.as_contract(&contract_id, || claim_faucet(&env, &user, asset));
+        assert_eq!(result, Err(SwapTradeError::FaucetRateLimited));
+    }
+
+    #[test]
+    fn test_legacy_last_claim_still_enforces_cooldown() {
+        let (env, contract_id, admin) = setup();
+        let user = Address::generate(&env);
+        let asset = symbol_short!("XLM");
+
+        // Claim recorded at t=1000 under the old `LastClaim` key
+        env.as_contract(&contract_id, || {
+            configure_faucet(&env, &admin, asset.clone());
+            env.storage().persistent().set(
+                &FaucetStorageKey::LastClaim((user.clone(), asset.clone())),
+                &1000_u64,
+            );
+        });
+
+        env.ledger().set_timestamp(1000 + COOLDOWN_SECS - 1);
+        let result = env.as_contract(&contract_id, || claim_faucet(&env, &user, asset.clone()));
+        assert_eq!(result, Err(SwapTradeError::FaucetRateLimited));
+
+        env.ledger().set_timestamp(1000 + COOLDOWN_SECS);
+        env.as_contract(&contract_id, || {
+            claim_faucet(&env, &user, asset.clone()).unwrap()
+        });
+
+        // The next cooldown runs from the new claim
+        env.ledger().set_timestamp(1000 + COOLDOWN_SECS + 1);
+        let result = env.as_contract(&contract_id, || claim_faucet(&env, &user, asset));
         assert_eq!(result, Err(SwapTradeError::FaucetRateLimited));
     }
 
     #[test]
     fn test_claim_after_cooldown_succeeds() {
-        let (env, admin) = setup();
+        let (env, contract_id, admin) = setup();
         let user = Address::generate(&env);
         let asset = symbol_short!("XLM");
 
-        configure_faucet(&env, &admin, asset.clone());
+        env.as_contract(&contract_id, || {
+            configure_faucet(&env, &admin, asset.clone())
+        });
 
         // First claim at t=1000
         env.ledger().set_timestamp(1000);
-        claim_faucet(&env, &user, asset.clone()).unwrap();
+        env.as_contract(&contract_id, || {
+            claim_faucet(&env, &user, asset.clone()).unwrap()
+        });
 
         // Claim after full cooldown – should succeed
         env.ledger().set_timestamp(1000 + COOLDOWN_SECS);
-        let result = claim_faucet(&env, &user, asset.clone());
+        let result = env.as_contract(&contract_id, || claim_faucet(&env, &user, asset.clone()));
         assert_eq!(result, Ok(DRIP_AMOUNT));
     }
 
     #[test]
     fn test_claim_exactly_at_cooldown_boundary_succeeds() {
-        let (env, admin) = setup();
+        let (env, contract_id, admin) = setup();
         let user = Address::generate(&env);
         let asset = symbol_short!("XLM");
 
-        configure_faucet(&env, &admin, asset.clone());
+        env.as_contract(&contract_id, || {
+            configure_faucet(&env, &admin, asset.clone())
+        });
 
         env.ledger().set_timestamp(1000);
-        claim_faucet(&env, &user, asset.clone()).unwrap();
+        env.as_contract(&contract_id, || {
+            claim_faucet(&env, &user, asset.clone()).unwrap()
+        });
 
         // Exactly at the cooldown boundary (sub = 0, which is not < cooldown)
         env.ledger().set_timestamp(1000 + COOLDOWN_SECS);
-        let result = claim_faucet(&env, &user, asset);
+        let result = env.as_contract(&contract_id, || claim_faucet(&env, &user, asset));
         assert_eq!(result, Ok(DRIP_AMOUNT));
     }
 
     #[test]
     fn test_claim_one_second_before_cooldown_rejects() {
-        let (env, admin) = setup();
+        let (env, contract_id, admin) = setup();
         let user = Address::generate(&env);
         let asset = symbol_short!("XLM");
 
-        configure_faucet(&env, &admin, asset.clone());
+        env.as_contract(&contract_id, || {
+            configure_faucet(&env, &admin, asset.clone())
+        });
 
         env.ledger().set_timestamp(1000);
-        claim_faucet(&env, &user, asset.clone()).unwrap();
+        env.as_contract(&contract_id, || {
+            claim_faucet(&env, &user, asset.clone()).unwrap()
+        });
 
         // One second before cooldown expires
         env.ledger().set_timestamp(1000 + COOLDOWN_SECS - 1);
-        let result = claim_faucet(&env, &user, asset);
+        let result = env.as_contract(&contract_id, || claim_faucet(&env, &user, asset));
         assert_eq!(result, Err(SwapTradeError::FaucetRateLimited));
     }
 
     #[test]
     fn test_balance_accumulates_across_claims() {
-        let (env, admin) = setup();
+        let (env, contract_id, admin) = setup();
         let user = Address::generate(&env);
         let asset = symbol_short!("XLM");
 
-        configure_faucet(&env, &admin, asset.clone());
+        env.as_contract(&contract_id, || {
+            configure_faucet(&env, &admin, asset.clone())
+        });
 
         env.ledger().set_timestamp(1000);
-        claim_faucet(&env, &user, asset.clone()).unwrap();
+        env.as_contract(&contract_id, || {
+            claim_faucet(&env, &user, asset.clone()).unwrap()
+        });
 
         env.ledger().set_timestamp(1000 + COOLDOWN_SECS);
-        claim_faucet(&env, &user, asset.clone()).unwrap();
+        env.as_contract(&contract_id, || {
+            claim_faucet(&env, &user, asset.clone()).unwrap()
+        });
 
         env.ledger().set_timestamp(1000 + COOLDOWN_SECS * 2);
-        claim_faucet(&env, &user, asset.clone()).unwrap();
-
-        let portfolio: Portfolio = env
-            .storage()
-            .instance()
-            .get(&())
-            .unwrap_or_else(|| Portfolio::new(&env));
-        let balance = portfolio.balance_of(&env, Asset::XLM, user);
+        env.as_contract(&contract_id, || {
+            claim_faucet(&env, &user, asset.clone()).unwrap()
+        });
+
+        let portfolio: Portfolio = env.as_contract(&contract_id, || {
+            env.storage()
+                .instance()
+                .get(&())
+                .unwrap_or_else(|| Portfolio::new(&env))
+        });
+        let balance = env.as_contract(&contract_id, || {
+            portfolio.balance_of(&env, Asset::XLM, user)
+        });
         assert_eq!(balance, DRIP_AMOUNT * 3);
     }
 
     #[test]
     fn test_different_assets_have_independent_cooldowns() {
-        let (env, admin) = setup();
+        let (env, contract_id, admin) = setup();
         let user = Address::generate(&env);
         let xlm = symbol_short!("XLM");
         let usdc = symbol_short!("USDCSIM");
 
         // Configure both assets
-        set_faucet_config(&env, &admin, xlm.clone(), DRIP_AMOUNT, COOLDOWN_SECS).unwrap();
-        set_faucet_config(&env, &admin, usdc.clone(), DRIP_AMOUNT * 2, COOLDOWN_SECS).unwrap();
+        env.as_contract(&contract_id, || {
+            set_faucet_config(&env, &admin, xlm.clone(), DRIP_AMOUNT, COOLDOWN_SECS).unwrap()
+        });
+        env.as_contract(&contract_id, || {
+            set_faucet_config(&env, &admin, usdc.clone(), DRIP_AMOUNT * 2, COOLDOWN_SECS).unwrap()
+        });
 
         env.ledger().set_timestamp(1000);
-        claim_faucet(&env, &user, xlm.clone()).unwrap();
-        claim_faucet(&env, &user, usdc.clone()).unwrap();
+        env.as_contract(&contract_id, || {
+            claim_faucet(&env, &user, xlm.clone()).unwrap()
+        });
+        env.as_contract(&contract_id, || {
+            claim_faucet(&env, &user, usdc.clone()).unwrap()
+        });
 
         // Both should be rate-limited independently at t + half cooldown
         env.ledger().set_timestamp(1000 + COOLDOWN_SECS / 2);
-        assert_eq!(
-            claim_faucet(&env, &user, xlm.clone()),
-            Err(SwapTradeError::FaucetRateLimited)
-        );
-        assert_eq!(
-            claim_faucet(&env, &user, usdc.clone()),
-            Err(SwapTradeError::FaucetRateLimited)
-        );
+        env.as_contract(&contract_id, || {
+            assert_eq!(
+                claim_faucet(&env, &user, xlm.clone()),
+                Err(SwapTradeError::FaucetRateLimited)
+            )
+        });
+        env.as_contract(&contract_id, || {
+            assert_eq!(
+                claim_faucet(&env, &user, usdc.clone()),
+                Err(SwapTradeError::FaucetRateLimited)
+            )
+        });
 
         // After cooldown, both should work again
         env.ledger().set_timestamp(1000 + COOLDOWN_SECS);
-        assert_eq!(claim_faucet(&env, &user, xlm), Ok(DRIP_AMOUNT));
-        assert_eq!(claim_faucet(&env, &user, usdc), Ok(DRIP_AMOUNT * 2));
+        env.as_contract(&contract_id, || {
+            assert_eq!(claim_faucet(&env, &user, xlm), Ok(DRIP_AMOUNT))
+        });
+        env.as_contract(&contract_id, || {
+            assert_eq!(claim_faucet(&env, &user, usdc), Ok(DRIP_AMOUNT * 2))
+        });
     }
 
     #[test]
     fn test_different_users_have_independent_cooldowns() {
-        let (env, admin) = setup();
+        let (env, contract_id, admin) = setup();
         let user1 = Address::generate(&env);
         let user2 = Address::generate(&env);
         let asset = symbol_short!("XLM");
 
-        configure_faucet(&env, &admin, asset.clone());
+        env.as_contract(&contract_id, || {
+            configure_faucet(&env, &admin, asset.clone())
+        });
 
         env.ledger().set_timestamp(1000);
-        claim_faucet(&env, &user1, asset.clone()).unwrap();
+        env.as_contract(&contract_id, || {
+            claim_faucet(&env, &user1, asset.clone()).unwrap()
+        });
 
         // user2 should be able to claim even though user1 just did
         env.ledger().set_timestamp(1001);
-        let result = claim_faucet(&env, &user2, asset);
+        let result = env.as_contract(&contract_id, || claim_faucet(&env, &user2, asset));
         assert_eq!(result, Ok(DRIP_AMOUNT));
     }
 
     #[test]
     fn test_claim_unconfigured_asset_fails() {
-        let (env, _admin) = setup();
+        let (env, contract_id, _admin) = setup();
         let user = Address::generate(&env);
         let asset = symbol_short!("XLM");
 
         env.ledger().set_timestamp(1000);
-        let result = claim_faucet(&env, &user, asset);
+        let result = env.as_contract(&contract_id, || claim_faucet(&env, &user, asset));
         assert_eq!(result, Err(SwapTradeError::FaucetNotConfigured));
     }
 
     #[test]
     fn test_cooldown_boundary_manipulation() {
-        let (env, admin) = setup();
+        let (env, contract_id, admin) = setup();
         let user = Address::generate(&env);
         let asset = symbol_short!("XLM");
 
         // Short cooldown for easier boundary testing
         let short_cooldown = 10u64;
-        set_faucet_config(&env, &admin, asset.clone(), DRIP_AMOUNT, short_cooldown).unwrap();
+        env.as_contract(&contract_id, || {
+            set_faucet_config(&env, &admin, asset.clone(), DRIP_AMOUNT, short_cooldown).unwrap()
+        });
 
         // Claim at t=100
         env.ledger().set_timestamp(100);
-        claim_faucet(&env, &user, asset.clone()).unwrap();
+        env.as_contract(&contract_id, || {
+            claim_faucet(&env, &user, asset.clone()).unwrap()
+        });
 
         // t=109: still within cooldown (9 < 10)
         env.ledger().set_timestamp(109);
-        assert_eq!(
-            claim_faucet(&env, &user, asset.clone()),
-            Err(SwapTradeError::FaucetRateLimited)
-        );
+        env.as_contract(&contract_id, || {
+            assert_eq!(
+                claim_faucet(&env, &user, asset.clone()),
+                Err(SwapTradeError::FaucetRateLimited)
+            )
+        });
 
         // t=110: cooldown elapsed (10 >= 10 → sub=0, not < cooldown)
         env.ledger().set_timestamp(110);
-        assert_eq!(claim_faucet(&env, &user, asset.clone()), Ok(DRIP_AMOUNT));
+        env.as_contract(&contract_id, || {
+            assert_eq!(claim_faucet(&env, &user, asset.clone()), Ok(DRIP_AMOUNT))
+        });
 
         // t=119: within cooldown of second claim (9 < 10)
         env.ledger().set_timestamp(119);
-        assert_eq!(
-            claim_faucet(&env, &user, asset.clone()),
-            Err(SwapTradeError::FaucetRateLimited)
-        );
+        env.as_contract(&contract_id, || {
+            assert_eq!(
+                claim_faucet(&env, &user, asset.clone()),
+                Err(SwapTradeError::FaucetRateLimited)
+            )
+        });
 
         // t=120: cooldown elapsed again
         env.ledger().set_timestamp(120);
-        assert_eq!(claim_faucet(&env, &user, asset), Ok(DRIP_AMOUNT));
+        env.as_contract(&contract_id, || {
+            assert_eq!(claim_faucet(&env, &user, asset), Ok(DRIP_AMOUNT))
+        });
     }
 
     #[test]
     fn test_admin_can_update_config() {
-        let (env, admin) = setup();
+        let (env, contract_id, admin) = setup();
         let user = Address::generate(&env);
         let asset = symbol_short!("XLM");
 
         // Initial config: 1M drip, 1hr cooldown
-        configure_faucet(&env, &admin, asset.clone());
+        env.as_contract(&contract_id, || {
+            configure_faucet(&env, &admin, asset.clone())
+        });
 
         env.ledger().set_timestamp(1000);
-        assert_eq!(claim_faucet(&env, &user, asset.clone()), Ok(1_000_000));
+        env.as_contract(&contract_id, || {
+            assert_eq!(claim_faucet(&env, &user, asset.clone()), Ok(1_000_000))
+        });
 
         // Admin updates to 5M drip, 30s cooldown
-        set_faucet_config(&env, &admin, asset.clone(), 5_000_000, 30).unwrap();
+        env.as_contract(&contract_id, || {
+            set_faucet_config(&env, &admin, asset.clone(), 5_000_000, 30).unwrap()
+        });
 
         // Still rate-limited from the first claim (old cooldown applies until new claim)
         env.ledger().set_timestamp(1000 + 1000);
-        assert_eq!(
-            claim_faucet(&env, &user, asset.clone()),
-            Err(SwapTradeError::FaucetRateLimited)
-        );
+        env.as_contract(&contract_id, || {
+            assert_eq!(
+                claim_faucet(&env, &user, asset.clone()),
+                Err(SwapTradeError::FaucetRateLimited)
+            )
+        });
 
         // After the original 1hr cooldown, new config kicks in
         env.ledger().set_timestamp(1000 + 3600);
-        assert_eq!(claim_faucet(&env, &user, asset.clone()), Ok(5_000_000));
+        env.as_contract(&contract_id, || {
+            assert_eq!(claim_faucet(&env, &user, asset.clone()), Ok(5_000_000))
+        });
 
         // With new 30s cooldown, next claim needs to wait only 30s
         env.ledger().set_timestamp(1000 + 3600 + 30);
-        assert_eq!(claim_faucet(&env, &user, asset), Ok(5_000_000));
+        env.as_contract(&contract_id, || {
+            assert_eq!(claim_faucet(&env, &user, asset), Ok(5_000_000))
+        });
     }
 
     #[test]
     fn test_zero_cooldown_allows_unlimited_claiming() {
-        let (env, admin) = setup();
+        let (env, contract_id, admin) = setup();
         let user = Address::generate(&env);
         let asset = symbol_short!("XLM");
 
-        set_faucet_config(&env, &admin, asset.clone(), DRIP_AMOUNT, 0).unwrap();
+        env.as_contract(&contract_id, || {
+            set_faucet_config(&env, &admin, asset.clone(), DRIP_AMOUNT, 0).unwrap()
+        });
 
         env.ledger().set_timestamp(1000);
-        claim_faucet(&env, &user, asset.clone()).unwrap();
+        env.as_contract(&contract_id, || {
+            claim_faucet(&env, &user, asset.clone()).unwrap()
+        });
 
         // Same timestamp – cooldown is 0 so sub=0, not < 0
         env.ledger().set_timestamp(1000);
-        assert_eq!(claim_faucet(&env, &user, asset.clone()), Ok(DRIP_AMOUNT));
+        env.as_contract(&contract_id, || {
+            assert_eq!(claim_faucet(&env, &user, asset.clone()), Ok(DRIP_AMOUNT))
+        });
 
         // Consecutive claims at the same second
-        assert_eq!(claim_faucet(&env, &user, asset), Ok(DRIP_AMOUNT));
+        env.as_contract(&contract_id, || {
+            assert_eq!(claim_faucet(&env, &user, asset), Ok(DRIP_AMOUNT))
+        });
     }
 }
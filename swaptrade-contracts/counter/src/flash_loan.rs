@@ -1,24 +1,59 @@
-use soroban_sdk::{symbol_short, Address, Env, Symbol, Vec};
+use soroban_sdk::{
+    contractclient, contracttype, symbol_short, token, Address, Bytes, Env, Map, Symbol, Vec,
+};
 
 use crate::errors::ContractError;
-use crate::liquidity_pool::PoolRegistry;
-use crate::portfolio::{Asset, Portfolio};
-use crate::storage::POOL_REGISTRY_KEY;
 
-const FLASH_LOAN_FEE_BPS: u32 = 9;
+/// Flash-loan fee charged on every borrowed leg, in basis points.
+pub const FLASH_LOAN_FEE_BPS: u32 = 9;
+/// Maximum number of (pool, asset) legs accepted in one flash loan.
+pub const MAX_FLASH_LOAN_LEGS: u32 = 8;
+
 const REENTRANCY_KEY: Symbol = symbol_short!("fl_nonce");
 
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FlashLoanKey {
+    /// SEP-41 token contract lent out for an asset symbol.
+    Token(Symbol),
+}
+
+/// Callback interface a contract must implement to receive flash loans.
+///
+/// Every borrowed amount is transferred to the receiver before
+/// `on_flash_loan` is invoked. It must return `true` and, by the time it
+/// returns, have approved the lending contract to spend `amounts[i] +
+/// fees[i]` of `assets[i]`; the lender then pulls exactly that amount back.
+/// Tokens pushed to the lender by any other route do not count as
+/// repayment, and a shortfall reverts the whole call.
+#[contractclient(name = "FlashLoanReceiverClient")]
+pub trait FlashLoanReceiver {
+    fn on_flash_loan(
+        env: Env,
+        initiator: Address,
+        assets: Vec<Symbol>,
+        amounts: Vec<i128>,
+        fees: Vec<i128>,
+        data: Bytes,
+    ) -> bool;
+}
+
+/// A single borrowed leg: `amount` of `asset` taken from pool `pool_id`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FlashLoanLeg {
+    pub pool_id: u64,
+    pub asset: Symbol,
+    pub amount: i128,
+}
+
 pub struct FlashLoanManager;
 
 impl FlashLoanManager {
     fn check_reentrancy(env: &Env) -> Result<(), ContractError> {
-        let seq: u32 = env
-            .storage()
-            .temporary()
-            .get(&REENTRANCY_KEY)
-            .unwrap_or(0);
+        let seq: u32 = env.storage().temporary().get(&REENTRANCY_KEY).unwrap_or(0);
         if seq > 0 {
-            return Err(ContractError::InvariantViolation);
+            return Err(ContractError::FlashLoanReentrancy);
         }
         Ok(())
     }
@@ -26,184 +61,168 @@ impl FlashLoanManager {
     fn set_reentrancy_guard(env: &Env) {
         let seq = env.ledger().sequence();
         env.storage().temporary().set(&REENTRANCY_KEY, &seq);
-        env.storage()
-            .temporary()
-            .extend_ttl(&REENTRANCY_KEY, 1, 10);
+        env.storage().temporary().extend_ttl(&REENTRANCY_KEY, 1, 10);
     }
 
     fn clear_reentrancy_guard(env: &Env) {
         env.storage().temporary().remove(&REENTRANCY_KEY);
     }
 
+    /// Fee owed on a flash-loan leg of `amount`, never less than 1 so small
+    /// legs cannot borrow for free.
+    pub fn calculate_fee(amount: i128) -> Result<i128, ContractError> {
+        let fee = (amount as u128)
+            .checked_mul(FLASH_LOAN_FEE_BPS as u128)
+            .ok_or(ContractError::AmountOverflow)?
+            / 10000;
+        Ok((fee as i128).max(1))
+    }
+
+    /// SEP-41 token contract backing flash loans of `asset`.
+    pub fn token_for(env: &Env, asset: &Symbol) -> Result<Address, ContractError> {
+        env.storage()
+            .persistent()
+            .get(&FlashLoanKey::Token(asset.clone()))
+            .ok_or(ContractError::FlashLoanAssetNotListed)
+    }
+
+    /// Register the SEP-41 token contract that `asset` is lent out of.
+    pub fn set_token(
+        env: &Env,
+        admin: Address,
+        asset: Symbol,
+        token: Address,
+    ) -> Result<(), ContractError> {
+        admin.require_auth();
+        crate::admin::require_admin(env, &admin)?;
+        env.storage()
+            .persistent()
+            .set(&FlashLoanKey::Token(asset), &token);
+        Ok(())
+    }
+
+    /// Borrow one or more assets from one or more `PoolRegistry` pools in a
+    /// single atomic call.
+    ///
+    /// Every leg is taken out of its pool's reserve and transferred to
+    /// `receiver` through the asset's SEP-41 token, `on_flash_loan` is invoked
+    /// once with all legs, and principal plus fee is then pulled back from
+    /// the receiver's allowance. Principal is returned to each source pool's
+    /// reserve and fees are credited to its LP fee accounting. Returns the fee
+    /// paid per leg, in the same order as `legs`.
     pub fn flash_loan(
         env: &Env,
-        pool_id: u64,
+        initiator: Address,
         receiver: Address,
-        asset: Symbol,
-        amount: i128,
-        data: Vec<u8>,
-    ) -> Result<i128, ContractError> {
+        legs: Vec<FlashLoanLeg>,
+        data: Bytes,
+    ) -> Result<Vec<i128>, ContractError> {
+        initiator.require_auth();
         receiver.require_auth();
 
         Self::check_reentrancy(env)?;
 
-        if amount <= 0 {
+        if legs.is_empty() || legs.len() > MAX_FLASH_LOAN_LEGS {
             return Err(ContractError::InvalidAmount);
         }
 
-        let registry: PoolRegistry = env
-            .storage()
-            .instance()
-            .get(&POOL_REGISTRY_KEY)
-            .ok_or(ContractError::LPPositionNotFound)?;
-
-        let pool = registry
-            .get_pool(pool_id)
-            .ok_or(ContractError::LPPositionNotFound)?;
-
-        let pool_balance = if asset == pool.token_a {
-            pool.reserve_a
-        } else if asset == pool.token_b {
-            pool.reserve_b
-        } else {
-            return Err(ContractError::InvalidTokenSymbol);
-        };
-
-        if amount > pool_balance {
-            return Err(ContractError::InsufficientBalance);
-        }
-
-        let fee = (amount as u128)
-            .checked_mul(FLASH_LOAN_FEE_BPS as u128)
-            .ok_or(ContractError::AmountOverflow)?
-            .checked_div(10000)
-            .ok_or(ContractError::AmountOverflow)? as i128;
-
-        if fee <= 0 {
-            return Err(ContractError::InvalidAmount);
+        let mut registry = crate::load_pool_registry(env);
+        let lender = env.current_contract_address();
+
+        // Validate every leg against pool reserves and take the principal out
+        // of them. Several legs may draw on the same pool and asset, so the
+        // running reserve is checked after each withdrawal. Repayment owed is
+        // tracked per token contract.
+        let mut owed: Map<Address, i128> = Map::new(env);
+        let mut tokens: Vec<Address> = Vec::new(env);
+        let mut assets: Vec<Symbol> = Vec::new(env);
+        let mut amounts: Vec<i128> = Vec::new(env);
+        let mut fees: Vec<i128> = Vec::new(env);
+        for leg in legs.iter() {
+            if leg.amount <= 0 {
+                return Err(ContractError::InvalidAmount);
+            }
+            let available = registry.available_liquidity(leg.pool_id, &leg.asset)?;
+            if leg.amount > available {
+                return Err(ContractError::InsufficientBalance);
+            }
+            registry.adjust_reserve(leg.pool_id, &leg.asset, -leg.amount)?;
+
+            let fee = Self::calculate_fee(leg.amount)?;
+            let token = Self::token_for(env, &leg.asset)?;
+            let due = leg
+                .amount
+                .checked_add(fee)
+                .and_then(|due| due.checked_add(owed.get(token.clone()).unwrap_or(0)))
+                .ok_or(ContractError::AmountOverflow)?;
+            owed.set(token.clone(), due);
+            tokens.push_back(token);
+            assets.push_back(leg.asset.clone());
+            amounts.push_back(leg.amount);
+            fees.push_back(fee);
         }
+        crate::save_pool_registry(env, &registry);
 
         Self::set_reentrancy_guard(env);
 
-        let nonce: u32 = env.ledger().sequence();
-
-        env.events().publish(
-            (symbol_short!("fl_init"), pool_id, receiver.clone()),
-            (asset.clone(), amount, fee, nonce),
-        );
-
-        let xlm_asset = Symbol::new(env, "XLM");
-        let portfolio_asset = if asset == xlm_asset {
-            Asset::XLM
-        } else {
-            Asset::Custom(asset.clone())
-        };
-
-        let mut portfolio: Portfolio = env
-            .storage()
-            .instance()
-            .get(&())
-            .unwrap_or_else(|| Portfolio::new(env));
-
-        portfolio.credit(env, portfolio_asset.clone(), receiver.clone(), amount);
-        env.storage().instance().set(&(), &portfolio);
-
-        Self::invoke_receiver(env, &receiver, &asset, amount, &data);
-
-        Self::verify_repayment(env, &portfolio_asset, &receiver, amount, fee)?;
-
-        Self::clear_reentrancy_guard(env);
+        let timestamp = env.ledger().timestamp() as i64;
+        for i in 0..legs.len() {
+            let leg = legs.get(i).unwrap();
+            token::Client::new(env, &tokens.get(i).unwrap()).transfer(
+                &lender,
+                &receiver,
+                &leg.amount,
+            );
+            crate::events::flash_loan_initiated(
+                env,
+                receiver.clone(),
+                leg.pool_id as i128,
+                leg.asset,
+                leg.amount,
+                fees.get(i).unwrap(),
+                timestamp,
+            );
+        }
 
-        let mut portfolio2: Portfolio = env
-            .storage()
-            .instance()
-            .get(&())
-            .unwrap_or_else(|| Portfolio::new(env));
-
-        let total_owed = amount
-            .checked_add(fee)
-            .ok_or(ContractError::AmountOverflow)?;
-        portfolio2.debit(env, portfolio_asset.clone(), receiver.clone(), total_owed);
-        portfolio2.collect_fee(fee);
-        env.storage().instance().set(&(), &portfolio2);
-
-        let mut registry2: PoolRegistry = env
-            .storage()
-            .instance()
-            .get(&POOL_REGISTRY_KEY)
-            .ok_or(ContractError::LPPositionNotFound)?;
-
-        let mut pool2 = registry2
-            .get_pool(pool_id)
-            .ok_or(ContractError::LPPositionNotFound)?;
-
-        if asset == pool2.token_a {
-            pool2.reserve_a = pool2
-                .reserve_a
-                .checked_add(fee)
-                .ok_or(ContractError::AmountOverflow)?;
-        } else {
-            pool2.reserve_b = pool2
-                .reserve_b
-                .checked_add(fee)
-                .ok_or(ContractError::AmountOverflow)?;
+        let accepted = FlashLoanReceiverClient::new(env, &receiver)
+            .on_flash_loan(&initiator, &assets, &amounts, &fees, &data);
+        if !accepted {
+            return Err(ContractError::FlashLoanCallbackFailed);
         }
 
-        env.storage()
-            .instance()
-            .set(&POOL_REGISTRY_KEY, &registry2);
+        for (token, due) in owed.iter() {
+            let client = token::Client::new(env, &token);
+            if client.allowance(&receiver, &lender) < due || client.balance(&receiver) < due {
+                return Err(ContractError::FlashLoanNotRepaid);
+            }
+            client.transfer_from(&lender, &receiver, &lender, &due);
+        }
 
-        env.events().publish(
-            (symbol_short!("fl_done"), pool_id, receiver),
-            (asset, amount, fee, nonce),
-        );
+        let mut registry = crate::load_pool_registry(env);
+        for i in 0..legs.len() {
+            let leg = legs.get(i).unwrap();
+            registry.adjust_reserve(leg.pool_id, &leg.asset, leg.amount)?;
+            registry.accrue_fees(env, leg.pool_id, leg.asset.clone(), fees.get(i).unwrap())?;
+        }
+        crate::save_pool_registry(env, &registry);
 
-        Ok(fee)
-    }
+        Self::clear_reentrancy_guard(env);
 
-    fn invoke_receiver(
-        env: &Env,
-        receiver: &Address,
-        asset: &Symbol,
-        amount: i128,
-        data: &Vec<u8>,
-    ) {
-        let func = Symbol::new(env, "execute_operation");
-        env.invoke_contract(
-            receiver,
-            &func,
-            soroban_sdk::Vec::from_array(
+        let timestamp = env.ledger().timestamp() as i64;
+        for i in 0..legs.len() {
+            let leg = legs.get(i).unwrap();
+            let fee = fees.get(i).unwrap();
+            crate::events::flash_loan_completed(
                 env,
-                [
-                    soroban_sdk::Val::from(asset.clone()),
-                    soroban_sdk::Val::from(amount),
-                    soroban_sdk::Val::from(data.clone()),
-                ],
-            ),
-        );
-    }
-
-    fn verify_repayment(
-        env: &Env,
-        asset: &Asset,
-        user: &Address,
-        amount: i128,
-        fee: i128,
-    ) -> Result<(), ContractError> {
-        let portfolio: Portfolio = env
-            .storage()
-            .instance()
-            .get(&())
-            .unwrap_or_else(|| Portfolio::new(env));
-
-        let balance = portfolio.balance_of(env, asset.clone(), user.clone());
-        let required = amount
-            .checked_add(fee)
-            .ok_or(ContractError::AmountOverflow)?;
-
-        if balance < required {
-            return Err(ContractError::InsufficientBalance);
+                receiver.clone(),
+                leg.pool_id as i128,
+                leg.asset,
+                leg.amount + fee,
+                fee,
+                timestamp,
+            );
         }
-        Ok(())
+
+        Ok(fees)
     }
 }
@@ -0,0 +1,402 @@
+#![cfg(test)]
+
+use soroban_sdk::testutils::{Address as _, MockAuth, MockAuthInvoke};
+use soroban_sdk::token::{StellarAssetClient, TokenClient};
+use soroban_sdk::{
+    contract, contractimpl, symbol_short, token, vec, Address, Bytes, Env, IntoVal, Map, Symbol,
+    Vec,
+};
+
+use crate::errors::ContractError;
+use crate::flash_loan::{FlashLoanLeg, FlashLoanReceiver};
+use crate::{CounterContract, CounterContractClient};
+
+// ── Example external venue ──────────────────────────────────────────────────
+//
+// Stands in for another DEX quoting the borrowed assets 1% richer than the
+// SwapTrade pools. Soroban forbids re-entering the lending contract, so an
+// arbitrage leg always has to run against a different contract.
+
+#[contract]
+pub struct MockVenue;
+
+#[contractimpl]
+impl MockVenue {
+    /// Take `amount` of `token` from `trader`, sell it on the venue and buy
+    /// it back on the cheaper market, paying out the amount held after the
+    /// round trip.
+    pub fn round_trip(env: Env, token: Address, trader: Address, amount: i128) -> i128 {
+        let venue = env.current_contract_address();
+        let client = token::Client::new(&env, &token);
+        client.transfer(&trader, &venue, &amount);
+        let out = amount + amount / 100;
+        client.transfer(&venue, &trader, &out);
+        out
+    }
+}
+
+// ── Example flash-loan receiver ─────────────────────────────────────────────
+
+const LENDER_KEY: Symbol = symbol_short!("lender");
+const VENUE_KEY: Symbol = symbol_short!("venue");
+const TOKENS_KEY: Symbol = symbol_short!("tokens");
+const ACCEPT_KEY: Symbol = symbol_short!("accept");
+const REPAY_KEY: Symbol = symbol_short!("repay");
+const PUSH_KEY: Symbol = symbol_short!("push");
+const PROFIT_KEY: Symbol = symbol_short!("profit");
+
+#[contract]
+pub struct ArbReceiver;
+
+#[contractimpl]
+impl ArbReceiver {
+    pub fn init(
+        env: Env,
+        lender: Address,
+        venue: Address,
+        tokens: Map<Symbol, Address>,
+        accept: bool,
+        repay: bool,
+    ) {
+        env.storage().instance().set(&LENDER_KEY, &lender);
+        env.storage().instance().set(&VENUE_KEY, &venue);
+        env.storage().instance().set(&TOKENS_KEY, &tokens);
+        env.storage().instance().set(&ACCEPT_KEY, &accept);
+        env.storage().instance().set(&REPAY_KEY, &repay);
+    }
+
+    /// Pay the loan back by transferring to the lender instead of approving
+    /// it, as a receiver that tries to have unrelated inflows count would.
+    pub fn set_push_repay(env: Env, push: bool) {
+        env.storage().instance().set(&PUSH_KEY, &push);
+    }
+
+    pub fn profit(env: Env) -> i128 {
+        env.storage().instance().get(&PROFIT_KEY).unwrap_or(0)
+    }
+}
+
+#[contractimpl]
+impl FlashLoanReceiver for ArbReceiver {
+    fn on_flash_loan(
+        env: Env,
+        _initiator: Address,
+        assets: Vec<Symbol>,
+        amounts: Vec<i128>,
+        fees: Vec<i128>,
+        _data: Bytes,
+    ) -> bool {
+        let accept: bool = env.storage().instance().get(&ACCEPT_KEY).unwrap_or(false);
+        if !accept {
+            return false;
+        }
+        let repay: bool = env.storage().instance().get(&REPAY_KEY).unwrap_or(false);
+        let push: bool = env.storage().instance().get(&PUSH_KEY).unwrap_or(false);
+        let lender: Address = env.storage().instance().get(&LENDER_KEY).unwrap();
+        let venue: Address = env.storage().instance().get(&VENUE_KEY).unwrap();
+        let tokens: Map<Symbol, Address> = env.storage().instance().get(&TOKENS_KEY).unwrap();
+        let venue = MockVenueClient::new(&env, &venue);
+        let this = env.current_contract_address();
+
+        let mut profit = 0i128;
+        for i in 0..assets.len() {
+            let token = tokens.get(assets.get(i).unwrap()).unwrap();
+            let amount = amounts.get(i).unwrap();
+            let fee = fees.get(i).unwrap();
+            let out = venue.round_trip(&token, &this, &amount);
+            let client = token::Client::new(&env, &token);
+            if repay {
+                let expiry = env.ledger().sequence() + 1;
+                client.approve(&this, &lender, &(amount + fee), &expiry);
+            } else if push {
+                client.transfer(&this, &lender, &(amount + fee));
+            }
+            profit += out - amount - fee;
+        }
+        env.storage().instance().set(&PROFIT_KEY, &profit);
+        true
+    }
+}
+
+// ── Helpers ─────────────────────────────────────────────────────────────────
+
+struct Setup {
+    env: Env,
+    client: CounterContractClient<'static>,
+    contract_id: Address,
+    receiver: Address,
+    initiator: Address,
+    xlm: Address,
+    btc: Address,
+    pool_xlm_usdc: u64,
+    pool_usdc_btc: u64,
+}
+
+fn setup(accept: bool, repay: bool) -> Setup {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+    let venue = env.register(MockVenue, ());
+    let receiver = env.register(ArbReceiver, ());
+
+    let admin = Address::generate(&env);
+    let initiator = Address::generate(&env);
+    env.as_contract(&contract_id, || crate::admin::set_admin(&env, &admin));
+
+    let issuer = Address::generate(&env);
+    let mut tokens = Map::new(&env);
+    for asset in [
+        symbol_short!("XLM"),
+        symbol_short!("USDC"),
+        symbol_short!("BTC"),
+    ] {
+        let token = env
+            .register_stellar_asset_contract_v2(issuer.clone())
+            .address();
+        client.set_flash_loan_token(&admin, &asset, &token);
+        // The venue keeps inventory to pay out its 1% spread.
+        StellarAssetClient::new(&env, &token).mint(&venue, &1_000_000);
+        tokens.set(asset, token);
+    }
+    let xlm = tokens.get(symbol_short!("XLM")).unwrap();
+    let usdc = tokens.get(symbol_short!("USDC")).unwrap();
+    let btc = tokens.get(symbol_short!("BTC")).unwrap();
+
+    // Pool reserves are backed by tokens held by the lending contract.
+    let pool_xlm_usdc = client.register_pool(
+        &admin,
+        &symbol_short!("XLM"),
+        &symbol_short!("USDC"),
+        &1_000_000,
+        &1_000_000,
+        &30,
+    );
+    let pool_usdc_btc = client.register_pool(
+        &admin,
+        &symbol_short!("USDC"),
+        &symbol_short!("BTC"),
+        &1_000_000,
+        &50_000,
+        &30,
+    );
+    StellarAssetClient::new(&env, &xlm).mint(&contract_id, &1_000_000);
+    StellarAssetClient::new(&env, &usdc).mint(&contract_id, &2_000_000);
+    StellarAssetClient::new(&env, &btc).mint(&contract_id, &50_000);
+
+    ArbReceiverClient::new(&env, &receiver).init(&contract_id, &venue, &tokens, &accept, &repay);
+
+    Setup {
+        env,
+        client,
+        contract_id,
+        receiver,
+        initiator,
+        xlm,
+        btc,
+        pool_xlm_usdc,
+        pool_usdc_btc,
+    }
+}
+
+fn balance(s: &Setup, token: &Address, owner: &Address) -> i128 {
+    TokenClient::new(&s.env, token).balance(owner)
+}
+
+fn reserve(s: &Setup, pool_id: u64, asset: &Symbol) -> i128 {
+    let pool = s.client.get_pool(&pool_id).unwrap();
+    if pool.token_a == *asset {
+        pool.reserve_a
+    } else {
+        pool.reserve_b
+    }
+}
+
+fn leg(pool_id: u64, asset: Symbol, amount: i128) -> FlashLoanLeg {
+    FlashLoanLeg {
+        pool_id,
+        asset,
+        amount,
+    }
+}
+
+// ── Tests ───────────────────────────────────────────────────────────────────
+
+#[test]
+fn test_flash_loan_fee_calculation() {
+    let s = setup(true, true);
+    assert_eq!(s.client.get_flash_loan_fee(&100_000), 90);
+    // Small legs still pay the minimum fee
+    assert_eq!(s.client.get_flash_loan_fee(&1_000), 1);
+    assert_eq!(s.client.get_flash_loan_fee(&1), 1);
+}
+
+#[test]
+fn test_multi_asset_flash_loan_arbitrage_round_trip() {
+    let s = setup(true, true);
+    let xlm = symbol_short!("XLM");
+    let btc = symbol_short!("BTC");
+
+    let legs = vec![
+        &s.env,
+        leg(s.pool_xlm_usdc, xlm.clone(), 100_000),
+        leg(s.pool_usdc_btc, btc.clone(), 20_000),
+    ];
+    let fees = s
+        .client
+        .flash_loan(&s.initiator, &s.receiver, &legs, &Bytes::new(&s.env));
+
+    assert_eq!(fees, vec![&s.env, 90i128, 18i128]);
+
+    // The borrowed tokens were traded on the venue; the receiver keeps the
+    // 1% spread minus fees and the lender holds principal plus fee.
+    assert_eq!(balance(&s, &s.xlm, &s.receiver), 1_000 - 90);
+    assert_eq!(balance(&s, &s.btc, &s.receiver), 200 - 18);
+    assert_eq!(balance(&s, &s.xlm, &s.contract_id), 1_000_000 + 90);
+    assert_eq!(balance(&s, &s.btc, &s.contract_id), 50_000 + 18);
+    let receiver = ArbReceiverClient::new(&s.env, &s.receiver);
+    assert_eq!(receiver.profit(), 1_200 - 90 - 18);
+
+    // Reserves are restored and fees accrue to each source pool's LP fee
+    // accounting.
+    assert_eq!(reserve(&s, s.pool_xlm_usdc, &xlm), 1_000_000);
+    assert_eq!(reserve(&s, s.pool_usdc_btc, &btc), 50_000);
+    let pool1 = s.client.get_pool(&s.pool_xlm_usdc).unwrap();
+    let pool2 = s.client.get_pool(&s.pool_usdc_btc).unwrap();
+    let xlm_fees = if pool1.token_a == xlm {
+        pool1.accumulated_fees_a
+    } else {
+        pool1.accumulated_fees_b
+    };
+    let btc_fees = if pool2.token_a == btc {
+        pool2.accumulated_fees_a
+    } else {
+        pool2.accumulated_fees_b
+    };
+    assert_eq!(xlm_fees, 90);
+    assert_eq!(btc_fees, 18);
+}
+
+#[test]
+fn test_flash_loan_requires_receiver_auth() {
+    let s = setup(true, true);
+    let legs = vec![&s.env, leg(s.pool_xlm_usdc, symbol_short!("XLM"), 100_000)];
+    let data = Bytes::new(&s.env);
+
+    // Only the initiator signs; the receiver never authorized the loan.
+    s.env.mock_auths(&[MockAuth {
+        address: &s.initiator,
+        invoke: &MockAuthInvoke {
+            contract: &s.contract_id,
+            fn_name: "flash_loan",
+            args: (&s.initiator, &s.receiver, &legs, &data).into_val(&s.env),
+            sub_invokes: &[],
+        },
+    }]);
+    let result = s
+        .client
+        .try_flash_loan(&s.initiator, &s.receiver, &legs, &data);
+    assert!(result.is_err());
+    assert_eq!(balance(&s, &s.xlm, &s.receiver), 0);
+}
+
+#[test]
+fn test_flash_loan_rejected_by_receiver() {
+    let s = setup(false, true);
+    let xlm = symbol_short!("XLM");
+
+    let legs = vec![&s.env, leg(s.pool_xlm_usdc, xlm.clone(), 100_000)];
+    let result = s
+        .client
+        .try_flash_loan(&s.initiator, &s.receiver, &legs, &Bytes::new(&s.env));
+
+    assert_eq!(result, Err(Ok(ContractError::FlashLoanCallbackFailed)));
+    assert_eq!(balance(&s, &s.xlm, &s.receiver), 0);
+    assert_eq!(balance(&s, &s.xlm, &s.contract_id), 1_000_000);
+    assert_eq!(reserve(&s, s.pool_xlm_usdc, &xlm), 1_000_000);
+}
+
+#[test]
+fn test_flash_loan_not_repaid_reverts() {
+    let s = setup(true, false);
+    let xlm = symbol_short!("XLM");
+
+    // The receiver keeps the borrowed tokens instead of paying them back.
+    let legs = vec![&s.env, leg(s.pool_xlm_usdc, xlm.clone(), 100_000)];
+    let result = s
+        .client
+        .try_flash_loan(&s.initiator, &s.receiver, &legs, &Bytes::new(&s.env));
+
+    assert_eq!(result, Err(Ok(ContractError::FlashLoanNotRepaid)));
+    assert_eq!(balance(&s, &s.xlm, &s.receiver), 0);
+    assert_eq!(balance(&s, &s.xlm, &s.contract_id), 1_000_000);
+    assert_eq!(reserve(&s, s.pool_xlm_usdc, &xlm), 1_000_000);
+}
+
+#[test]
+fn test_flash_loan_balance_growth_is_not_repayment() {
+    let s = setup(true, false);
+    let xlm = symbol_short!("XLM");
+    ArbReceiverClient::new(&s.env, &s.receiver).set_push_repay(&true);
+
+    // The lender's balance grows by principal plus fee, but nothing was
+    // approved for it to collect.
+    let legs = vec![&s.env, leg(s.pool_xlm_usdc, xlm.clone(), 100_000)];
+    let result = s
+        .client
+        .try_flash_loan(&s.initiator, &s.receiver, &legs, &Bytes::new(&s.env));
+
+    assert_eq!(result, Err(Ok(ContractError::FlashLoanNotRepaid)));
+    assert_eq!(balance(&s, &s.xlm, &s.contract_id), 1_000_000);
+    assert_eq!(reserve(&s, s.pool_xlm_usdc, &xlm), 1_000_000);
+}
+
+#[test]
+fn test_flash_loan_legs_cannot_exceed_pool_reserve() {
+    let s = setup(true, true);
+    let xlm = symbol_short!("XLM");
+
+    // Each leg fits on its own but together they exceed the XLM reserve.
+    let legs = vec![
+        &s.env,
+        leg(s.pool_xlm_usdc, xlm.clone(), 600_000),
+        leg(s.pool_xlm_usdc, xlm.clone(), 600_000),
+    ];
+    let result = s
+        .client
+        .try_flash_loan(&s.initiator, &s.receiver, &legs, &Bytes::new(&s.env));
+
+    assert_eq!(result, Err(Ok(ContractError::InsufficientBalance)));
+}
+
+#[test]
+fn test_flash_loan_rejects_asset_not_in_pool() {
+    let s = setup(true, true);
+    let legs = vec![&s.env, leg(s.pool_xlm_usdc, symbol_short!("BTC"), 1_000)];
+    let result = s
+        .client
+        .try_flash_loan(&s.initiator, &s.receiver, &legs, &Bytes::new(&s.env));
+
+    assert_eq!(result, Err(Ok(ContractError::InvalidTokenSymbol)));
+}
+
+#[test]
+fn test_flash_loan_rejects_unlisted_asset() {
+    let s = setup(true, true);
+    let admin = Address::generate(&s.env);
+    let pool = s.client.register_pool(
+        &admin,
+        &symbol_short!("XLM"),
+        &symbol_short!("EURC"),
+        &1_000_000,
+        &1_000_000,
+        &30,
+    );
+    let legs = vec![&s.env, leg(pool, symbol_short!("EURC"), 100_000)];
+    let result = s
+        .client
+        .try_flash_loan(&s.initiator, &s.receiver, &legs, &Bytes::new(&s.env));
+
+    assert_eq!(result, Err(Ok(ContractError::FlashLoanAssetNotListed)));
+}
@@ -0,0 +1,429 @@
+use crate::errors::SwapTradeError;
+/// Gauge Controller
+///
+/// Directs farm emissions across `PoolRegistry` pools by vote-escrow weight.
+/// Each ve holder splits up to 100% of their voting power across pool gauges.
+/// A gauge's weight decays with the voter's lock, like the lock itself. At
+/// every epoch (week) boundary the global emission budget is divided by each
+/// gauge's share of the total weight and written to the pool's
+/// `PoolFarmState::emission_rate`.
+use crate::farming::FarmingManager;
+use crate::vote_escrow::{VePoint, VoteEscrowManager, WEEK_SECS};
+use soroban_sdk::{contracttype, symbol_short, Address, Env, Vec};
+
+// ────────────────────────────────────────────────────────────────────────────
+// Constants
+// ────────────────────────────────────────────────────────────────────────────
+
+/// Maximum number of gauges (bounds the epoch rollover cost)
+pub const MAX_GAUGES: u32 = 32;
+
+/// A voter's full voting power, in basis points
+pub const MAX_VOTE_WEIGHT_BPS: u32 = 10_000;
+
+/// Upper bound on weeks walked when bringing a gauge curve forward
+const MAX_CHECKPOINT_EPOCHS: u32 = 255;
+
+// ────────────────────────────────────────────────────────────────────────────
+// Data Structures
+// ────────────────────────────────────────────────────────────────────────────
+
+/// A user's vote on one gauge
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[contracttype]
+pub struct GaugeVote {
+    /// Share of the user's voting power, in basis points
+    pub weight_bps: u32,
+    /// Slope contributed to the gauge curve (scaled like `VePoint`)
+    pub slope: i128,
+    /// Unlock time of the lock backing this vote
+    pub end: u64,
+}
+
+/// Emission split applied for the current epoch
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct GaugeEpoch {
+    /// Week-aligned start of the epoch
+    pub start: u64,
+    /// Reward tokens emitted across all gauges this epoch
+    pub budget: i128,
+    /// Sum of gauge weights at `start`
+    pub total_weight: i128,
+}
+
+/// Storage keys for gauge controller data
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum GaugeKey {
+    /// Pool ids with a registered gauge (Vec<u64>)
+    Gauges,
+    /// Weight curve of a gauge (pool_id -> VePoint)
+    Point(u64),
+    /// Slope leaving a gauge curve at a week-aligned timestamp
+    SlopeChange(u64, u64),
+    /// A user's vote on a gauge ((user, pool_id) -> GaugeVote)
+    UserVote(Address, u64),
+    /// Basis points of voting power a user has allocated
+    UserPowerUsed(Address),
+    /// Relative weight of a gauge in the current epoch, in basis points
+    RelativeWeight(u64),
+    /// Emission budget per epoch
+    Budget,
+    /// Epoch currently applied to pool emission rates
+    Epoch,
+}
+
+// ────────────────────────────────────────────────────────────────────────────
+// Gauge Controller Implementation
+// ────────────────────────────────────────────────────────────────────────────
+
+pub struct GaugeController;
+
+impl GaugeController {
+    // ────────────────────────────────────────────────────────────────────────
+    // Admin
+    // ────────────────────────────────────────────────────────────────────────
+
+    /// Admin only: register a gauge for an existing pool. The pool's
+    /// emission rate follows gauge votes from the next epoch on.
+    pub fn add_gauge(env: &Env, admin: Address, pool_id: u64) -> Result<(), SwapTradeError> {
+        admin.require_auth();
+        crate::admin::require_admin(env, &admin)?;
+        Self::apply_add_gauge(env, &admin, pool_id)
+    }
+
+    /// Register a gauge without an admin check; used by governance execution.
+    pub(crate) fn apply_add_gauge(
+        env: &Env,
+        actor: &Address,
+        pool_id: u64,
+    ) -> Result<(), SwapTradeError> {
+        crate::load_pool_registry(env)
+            .get_pool(pool_id)
+            .ok_or(SwapTradeError::LPPositionNotFound)?;
+
+        let mut gauges = Self::get_gauges(env);
+        if gauges.contains(pool_id) {
+            return Err(SwapTradeError::GaugeAlreadyExists);
+        }
+        if gauges.len() >= MAX_GAUGES {
+            return Err(SwapTradeError::TooManyGauges);
+        }
+        gauges.push_back(pool_id);
+        env.storage().persistent().set(&GaugeKey::Gauges, &gauges);
+        env.storage().persistent().set(
+            &GaugeKey::Point(pool_id),
+            &VePoint {
+                bias: 0,
+                slope: 0,
+                ts: env.ledger().timestamp(),
+            },
+        );
+
+        env.events()
+            .publish((symbol_short!("gauge_add"), pool_id), actor.clone());
+
+        Ok(())
+    }
+
+    /// Admin only: set the reward tokens emitted across all gauges per
+    /// epoch. Takes effect at the next epoch boundary.
+    pub fn set_emission_budget(
+        env: &Env,
+        admin: Address,
+        budget_per_epoch: i128,
+    ) -> Result<(), SwapTradeError> {
+        admin.require_auth();
+        crate::admin::require_admin(env, &admin)?;
+        Self::apply_emission_budget(env, budget_per_epoch)
+    }
+
+    /// Set the emission budget without an admin check; used by governance
+    /// execution.
+    pub(crate) fn apply_emission_budget(
+        env: &Env,
+        budget_per_epoch: i128,
+    ) -> Result<(), SwapTradeError> {
+        if budget_per_epoch < 0 {
+            return Err(SwapTradeError::InvalidAmount);
+        }
+
+        env.storage()
+            .persistent()
+            .set(&GaugeKey::Budget, &budget_per_epoch);
+
+        env.events()
+            .publish((symbol_short!("gauge_bdg"),), budget_per_epoch);
+
+        Ok(())
+    }
+
+    // ────────────────────────────────────────────────────────────────────────
+    // Voting
+    // ────────────────────────────────────────────────────────────────────────
+
+    /// Allocate `weight_bps` of the caller's vote-escrow power to a gauge,
+    /// replacing any earlier vote on it. A weight of zero removes the vote.
+    pub fn vote(
+        env: &Env,
+        user: Address,
+        pool_id: u64,
+        weight_bps: u32,
+    ) -> Result<(), SwapTradeError> {
+        user.require_auth();
+        if !Self::is_gauge(env, pool_id) {
+            return Err(SwapTradeError::GaugeNotFound);
+        }
+        if weight_bps > MAX_VOTE_WEIGHT_BPS {
+            return Err(SwapTradeError::InvalidAmount);
+        }
+
+        // Settle the epoch boundary before the curve changes.
+        Self::checkpoint(env)?;
+
+        let now = env.ledger().timestamp();
+        let lock = VoteEscrowManager::get_lock(env, &user);
+        if lock.amount <= 0 {
+            return Err(SwapTradeError::VeLockNotFound);
+        }
+        // The lock must still carry weight when the next epoch is applied.
+        if lock.unlock_time <= VoteEscrowManager::round_to_week(now) + WEEK_SECS {
+            return Err(SwapTradeError::VeLockExpired);
+        }
+
+        let vote_key = GaugeKey::UserVote(user.clone(), pool_id);
+        let old: GaugeVote = env
+            .storage()
+            .persistent()
+            .get(&vote_key)
+            .unwrap_or_default();
+
+        let used_key = GaugeKey::UserPowerUsed(user.clone());
+        let used: u32 = env.storage().persistent().get(&used_key).unwrap_or(0);
+        let used = used - old.weight_bps + weight_bps;
+        if used > MAX_VOTE_WEIGHT_BPS {
+            return Err(SwapTradeError::GaugeVoteOverAllocated);
+        }
+
+        let lock_slope = VoteEscrowManager::lock_point(env, &lock).slope;
+        let new = GaugeVote {
+            weight_bps,
+            slope: lock_slope * weight_bps as i128 / MAX_VOTE_WEIGHT_BPS as i128,
+            end: lock.unlock_time,
+        };
+
+        // Swap the old vote's remaining curve for the new one.
+        let (old_slope, old_bias) = if old.end > now {
+            (old.slope, old.slope * (old.end - now) as i128)
+        } else {
+            (0, 0)
+        };
+        let new_bias = new.slope * (new.end - now) as i128;
+
+        let mut point = Self::advance(env, pool_id, now);
+        point.bias = (point.bias + new_bias - old_bias).max(0);
+        point.slope = (point.slope + new.slope - old_slope).max(0);
+        env.storage()
+            .persistent()
+            .set(&GaugeKey::Point(pool_id), &point);
+
+        if old.end > now {
+            let change = Self::get_slope_change(env, pool_id, old.end) - old.slope;
+            Self::set_slope_change(env, pool_id, old.end, change);
+        }
+        let change = Self::get_slope_change(env, pool_id, new.end) + new.slope;
+        Self::set_slope_change(env, pool_id, new.end, change);
+
+        if weight_bps == 0 {
+            env.storage().persistent().remove(&vote_key);
+        } else {
+            env.storage().persistent().set(&vote_key, &new);
+        }
+        env.storage().persistent().set(&used_key, &used);
+
+        env.events()
+            .publish((symbol_short!("gauge_vot"), user), (pool_id, weight_bps));
+
+        Ok(())
+    }
+
+    // ────────────────────────────────────────────────────────────────────────
+    // Epoch Rollover
+    // ────────────────────────────────────────────────────────────────────────
+
+    /// Apply gauge weights to pool emission rates if an epoch boundary has
+    /// passed since the last rollover. Permissionless; also run on every vote.
+    pub fn checkpoint(env: &Env) -> Result<(), SwapTradeError> {
+        let start = VoteEscrowManager::round_to_week(env.ledger().timestamp());
+        if let Some(epoch) = Self::get_current_epoch(env) {
+            if epoch.start >= start {
+                return Ok(());
+            }
+        }
+
+        let gauges = Self::get_gauges(env);
+        let mut weights: Vec<i128> = Vec::new(env);
+        let mut total_weight: i128 = 0;
+        for pool_id in gauges.iter() {
+            let point = Self::advance(env, pool_id, start);
+            let weight = VoteEscrowManager::to_power(point.bias);
+            total_weight = total_weight
+                .checked_add(weight)
+                .ok_or(SwapTradeError::AmountOverflow)?;
+            weights.push_back(weight);
+        }
+
+        let budget = Self::get_emission_budget(env);
+        for i in 0..gauges.len() {
+            let pool_id = gauges.get(i).unwrap();
+            let weight = weights.get(i).unwrap();
+            let (rate, relative_bps) = if total_weight > 0 {
+                let share = budget
+                    .checked_mul(weight)
+                    .ok_or(SwapTradeError::AmountOverflow)?
+                    / total_weight;
+                (
+                    share / WEEK_SECS as i128,
+                    weight * MAX_VOTE_WEIGHT_BPS as i128 / total_weight,
+                )
+            } else {
+                (0, 0)
+            };
+            FarmingManager::apply_emission_rate(env, pool_id, rate)?;
+            env.storage()
+                .persistent()
+                .set(&GaugeKey::RelativeWeight(pool_id), &(relative_bps as u32));
+        }
+
+        let epoch = GaugeEpoch {
+            start,
+            budget,
+            total_weight,
+        };
+        env.storage().persistent().set(&GaugeKey::Epoch, &epoch);
+
+        env.events()
+            .publish((symbol_short!("gauge_ep"), start), (budget, total_weight));
+
+        Ok(())
+    }
+
+    // ────────────────────────────────────────────────────────────────────────
+    // Queries
+    // ────────────────────────────────────────────────────────────────────────
+
+    /// Pool ids with a registered gauge
+    pub fn get_gauges(env: &Env) -> Vec<u64> {
+        env.storage()
+            .persistent()
+            .get(&GaugeKey::Gauges)
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    /// Whether a pool's emissions are driven by a gauge
+    pub fn is_gauge(env: &Env, pool_id: u64) -> bool {
+        Self::get_gauges(env).contains(pool_id)
+    }
+
+    /// Current (decaying) vote weight on a gauge, in voting-power units
+    pub fn get_gauge_weight(env: &Env, pool_id: u64) -> Result<i128, SwapTradeError> {
+        let point: VePoint = env
+            .storage()
+            .persistent()
+            .get(&GaugeKey::Point(pool_id))
+            .ok_or(SwapTradeError::GaugeNotFound)?;
+        let point = Self::walk(env, pool_id, point, env.ledger().timestamp());
+        Ok(VoteEscrowManager::to_power(point.bias))
+    }
+
+    /// Share of the emission budget a gauge receives this epoch, in basis
+    /// points
+    pub fn get_relative_weight(env: &Env, pool_id: u64) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&GaugeKey::RelativeWeight(pool_id))
+            .unwrap_or(0)
+    }
+
+    /// A user's current vote on a gauge
+    pub fn get_user_vote(env: &Env, user: Address, pool_id: u64) -> Option<GaugeVote> {
+        env.storage()
+            .persistent()
+            .get(&GaugeKey::UserVote(user, pool_id))
+    }
+
+    /// Emission budget per epoch
+    pub fn get_emission_budget(env: &Env) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&GaugeKey::Budget)
+            .unwrap_or(0)
+    }
+
+    /// Epoch currently applied to pool emission rates
+    pub fn get_current_epoch(env: &Env) -> Option<GaugeEpoch> {
+        env.storage().persistent().get(&GaugeKey::Epoch)
+    }
+
+    // ────────────────────────────────────────────────────────────────────────
+    // Internal
+    // ────────────────────────────────────────────────────────────────────────
+
+    /// Bring a gauge curve forward to `ts` and store it
+    fn advance(env: &Env, pool_id: u64, ts: u64) -> VePoint {
+        let point: VePoint = env
+            .storage()
+            .persistent()
+            .get(&GaugeKey::Point(pool_id))
+            .unwrap_or(VePoint {
+                bias: 0,
+                slope: 0,
+                ts,
+            });
+        let point = Self::walk(env, pool_id, point, ts);
+        env.storage()
+            .persistent()
+            .set(&GaugeKey::Point(pool_id), &point);
+        point
+    }
+
+    /// Project a gauge curve forward to `ts`, dropping the slope of votes
+    /// whose locks expire on the way
+    fn walk(env: &Env, pool_id: u64, mut point: VePoint, ts: u64) -> VePoint {
+        if ts <= point.ts {
+            return point;
+        }
+        let mut t_i = VoteEscrowManager::round_to_week(point.ts);
+        for _ in 0..MAX_CHECKPOINT_EPOCHS {
+            t_i += WEEK_SECS;
+            let mut d_slope = 0;
+            if t_i > ts {
+                t_i = ts;
+            } else {
+                d_slope = Self::get_slope_change(env, pool_id, t_i);
+            }
+            point.bias = (point.bias - point.slope * (t_i - point.ts) as i128).max(0);
+            point.slope = (point.slope - d_slope).max(0);
+            point.ts = t_i;
+            if t_i == ts {
+                break;
+            }
+        }
+        point.ts = ts;
+        point
+    }
+
+    fn get_slope_change(env: &Env, pool_id: u64, ts: u64) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&GaugeKey::SlopeChange(pool_id, ts))
+            .unwrap_or(0)
+    }
+
+    fn set_slope_change(env: &Env, pool_id: u64, ts: u64, slope: i128) {
+        env.storage()
+            .persistent()
+            .set(&GaugeKey::SlopeChange(pool_id, ts), &slope);
+    }
+}
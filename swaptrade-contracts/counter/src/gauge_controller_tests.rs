@@ -0,0 +1,217 @@
+#![cfg(test)]
+
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::{symbol_short, Address, Env, Map};
+
+use crate::errors::ContractError;
+use crate::farming::FarmingManager;
+use crate::storage::ADMIN_KEY;
+use crate::vote_escrow::{MAX_LOCK_SECS, WEEK_SECS};
+use crate::{CounterContract, CounterContractClient};
+
+const START: u64 = 100 * WEEK_SECS;
+
+struct Setup {
+    env: Env,
+    contract_id: Address,
+    client: CounterContractClient<'static>,
+    admin: Address,
+    pool_a: u64,
+    pool_b: u64,
+}
+
+fn setup() -> Setup {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|l| l.timestamp = START);
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(&ADMIN_KEY, &admin);
+    });
+
+    let pool_a = client.register_pool(
+        &admin,
+        &symbol_short!("XLM"),
+        &symbol_short!("USDC"),
+        &1_000_000,
+        &1_000_000,
+        &30,
+    );
+    let pool_b = client.register_pool(
+        &admin,
+        &symbol_short!("USDC"),
+        &symbol_short!("BTC"),
+        &1_000_000,
+        &50_000,
+        &30,
+    );
+    client.add_gauge(&admin, &pool_a);
+    client.add_gauge(&admin, &pool_b);
+    client.set_gauge_emission_budget(&admin, &(1_000 * WEEK_SECS as i128));
+
+    Setup {
+        env,
+        contract_id,
+        client,
+        admin,
+        pool_a,
+        pool_b,
+    }
+}
+
+/// Fund `amount` governance tokens and lock them until `unlock_time`.
+fn voter(s: &Setup, amount: i128, unlock_time: u64) -> Address {
+    let user = Address::generate(&s.env);
+    s.client.mint(&symbol_short!("XLM"), &user, &amount);
+    s.client.create_ve_lock(&user, &amount, &unlock_time);
+    user
+}
+
+fn emission_rate(s: &Setup, pool_id: u64) -> i128 {
+    s.env.as_contract(&s.contract_id, || {
+        FarmingManager::get_pool_state(&s.env, pool_id)
+            .unwrap()
+            .emission_rate
+    })
+}
+
+fn advance(env: &Env, secs: u64) {
+    env.ledger().with_mut(|l| l.timestamp += secs);
+}
+
+#[test]
+fn test_epoch_rollover_splits_budget_by_vote_share() {
+    let s = setup();
+    let alice = voter(&s, 624_000, START + MAX_LOCK_SECS);
+    let bob = voter(&s, 208_000, START + MAX_LOCK_SECS);
+
+    s.client.vote_gauge_weight(&alice, &s.pool_a, &10_000);
+    s.client.vote_gauge_weight(&bob, &s.pool_b, &10_000);
+
+    // Votes only take effect at the next epoch boundary.
+    assert_eq!(emission_rate(&s, s.pool_a), 0);
+
+    advance(&s.env, WEEK_SECS);
+    s.client.checkpoint_gauges();
+
+    let epoch = s.client.get_gauge_epoch().unwrap();
+    assert_eq!(epoch.start, START + WEEK_SECS);
+    assert_eq!(epoch.total_weight, 621_000 + 207_000);
+    assert_eq!(s.client.get_gauge_relative_weight(&s.pool_a), 7_500);
+    assert_eq!(s.client.get_gauge_relative_weight(&s.pool_b), 2_500);
+    assert_eq!(emission_rate(&s, s.pool_a), 750);
+    assert_eq!(emission_rate(&s, s.pool_b), 250);
+
+    // The admin key can no longer override gauge-driven pools.
+    s.env.as_contract(&s.contract_id, || {
+        FarmingManager::initialize(&s.env, s.admin.clone());
+        assert_eq!(
+            FarmingManager::set_farm_emission_rate(&s.env, s.pool_a, 5, s.admin.clone()),
+            Err(ContractError::GaugeControlledPool)
+        );
+    });
+}
+
+#[test]
+fn test_expired_lock_drops_out_of_gauge_weight() {
+    let s = setup();
+    let alice = voter(&s, 624_000, START + MAX_LOCK_SECS);
+    let bob = voter(&s, 208_000, START + 2 * WEEK_SECS);
+
+    s.client.vote_gauge_weight(&alice, &s.pool_a, &10_000);
+    s.client.vote_gauge_weight(&bob, &s.pool_b, &10_000);
+
+    advance(&s.env, 2 * WEEK_SECS);
+    assert_eq!(s.client.get_gauge_weight(&s.pool_b), 0);
+    s.client.checkpoint_gauges();
+
+    assert_eq!(s.client.get_gauge_relative_weight(&s.pool_a), 10_000);
+    assert_eq!(s.client.get_gauge_relative_weight(&s.pool_b), 0);
+    assert_eq!(emission_rate(&s, s.pool_a), 1_000);
+    assert_eq!(emission_rate(&s, s.pool_b), 0);
+}
+
+#[test]
+fn test_vote_allocation_cannot_exceed_full_power() {
+    let s = setup();
+    let alice = voter(&s, 10_000, START + MAX_LOCK_SECS);
+
+    s.client.vote_gauge_weight(&alice, &s.pool_a, &6_000);
+    assert_eq!(
+        s.client.try_vote_gauge_weight(&alice, &s.pool_b, &5_000),
+        Err(Ok(ContractError::GaugeVoteOverAllocated))
+    );
+
+    // Re-voting replaces the earlier allocation.
+    s.client.vote_gauge_weight(&alice, &s.pool_a, &5_000);
+    s.client.vote_gauge_weight(&alice, &s.pool_b, &5_000);
+    assert_eq!(
+        s.client.get_gauge_weight(&s.pool_a),
+        s.client.get_gauge_weight(&s.pool_b)
+    );
+    assert_eq!(
+        s.client
+            .get_gauge_vote(&alice, &s.pool_a)
+            .unwrap()
+            .weight_bps,
+        5_000
+    );
+}
+
+#[test]
+fn test_vote_requires_lock_past_next_epoch() {
+    let s = setup();
+    let stranger = Address::generate(&s.env);
+    assert_eq!(
+        s.client
+            .try_vote_gauge_weight(&stranger, &s.pool_a, &10_000),
+        Err(Ok(ContractError::VeLockNotFound))
+    );
+
+    let short = voter(&s, 1_000, START + WEEK_SECS);
+    assert_eq!(
+        s.client.try_vote_gauge_weight(&short, &s.pool_a, &10_000),
+        Err(Ok(ContractError::VeLockExpired))
+    );
+    assert_eq!(
+        s.client.try_vote_gauge_weight(&short, &99, &10_000),
+        Err(Ok(ContractError::GaugeNotFound))
+    );
+}
+
+#[test]
+fn test_gauge_weight_is_backed_by_escrowed_balance() {
+    let s = setup();
+    let xlm = symbol_short!("XLM");
+    let holder = Address::generate(&s.env);
+    s.client.mint(&xlm, &holder, &52_000);
+
+    // Holding tokens is not enough: they have to be escrowed.
+    assert_eq!(
+        s.client.try_vote_gauge_weight(&holder, &s.pool_a, &10_000),
+        Err(Ok(ContractError::VeLockNotFound))
+    );
+    assert_eq!(
+        s.client
+            .try_create_ve_lock(&holder, &52_001, &(START + 4 * WEEK_SECS)),
+        Err(Ok(ContractError::InsufficientBalance))
+    );
+
+    s.client
+        .create_ve_lock(&holder, &52_000, &(START + 4 * WEEK_SECS));
+    assert_eq!(s.client.balance_of(&xlm, &holder), 0);
+    s.client.vote_gauge_weight(&holder, &s.pool_a, &10_000);
+    assert_eq!(
+        s.client.get_gauge_weight(&s.pool_a),
+        s.client.get_voting_power(&holder, &None)
+    );
+
+    advance(&s.env, 4 * WEEK_SECS);
+    s.client.checkpoint_gauges();
+    assert_eq!(s.client.get_gauge_weight(&s.pool_a), 0);
+    assert_eq!(s.client.withdraw_ve_lock(&holder), 52_000);
+    assert_eq!(s.client.balance_of(&xlm, &holder), 52_000);
+}
@@ -1,65 +1,346 @@
-use soroban_sdk::{contracttype, symbol_short, Address, Env, Map, Symbol};
+//! Vote delegation
+//!
+//! A delegator can hand fractions of their vote-escrow power to several
+//! delegates, either for every proposal (`DelegationTopic::All`) or for a
+//! single topic. Delegations for a specific topic replace the delegator's
+//! `All` delegations on proposals of that topic.
+//!
+//! Power moves at most one hop: a delegate votes with their own retained
+//! power plus what was delegated directly to them, and anything they
+//! delegate onwards carries only their own power. Every change writes a
+//! checkpoint so governance reads delegations as of proposal creation, the
+//! same way it reads vote-escrow balances.
+//!
+//! Each delegate tracks its live delegators, also checkpointed, up to
+//! `MAX_DELEGATORS`. A delegator leaves the list once they revoke every
+//! share given to the delegate, and a share must carry at least
+//! `MIN_DELEGATED_POWER` so dust delegations cannot fill it.
+
+use soroban_sdk::{contracttype, symbol_short, Address, Env, Vec};
 
 use crate::errors::SwapTradeError;
+use crate::governance::rewards::RewardManager;
+use crate::vote_escrow::VoteEscrowManager;
+
+/// Basis-point denominator for delegation shares.
+pub const FULL_SHARE_BPS: u32 = 10_000;
 
-const DELEGATIONS_KEY: Symbol = symbol_short!("delegates");
+/// Maximum live delegators tracked per delegate, bounding vote cost.
+pub const MAX_DELEGATORS: u32 = 50;
+
+/// Smallest vote-escrow power a single delegation may carry.
+pub const MIN_DELEGATED_POWER: i128 = 100;
+
+/// Proposal areas a delegation can be limited to.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum DelegationTopic {
+    All = 0,
+    Protocol = 1,
+    RiskParameters = 2,
+    Economics = 3,
+    Treasury = 4,
+}
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Delegation {
     pub delegator: Address,
     pub delegate: Address,
+    pub topic: DelegationTopic,
+    pub share_bps: u32,
+}
+
+/// A delegator's full delegation set from `ts` onwards.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DelegationCheckpoint {
+    pub ts: u64,
+    pub delegations: Vec<Delegation>,
+}
+
+/// A delegate's live delegators from `ts` onwards.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DelegatorsCheckpoint {
+    pub ts: u64,
+    pub delegators: Vec<Address>,
 }
 
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DelegationKey {
+    CheckpointCount(Address),
+    Checkpoint(Address, u32),
+    DelegatorsCount(Address),
+    /// Addresses holding at least one share delegated to an address.
+    Delegators(Address, u32),
+}
+
+/// Delegate `share_bps` of the delegator's power on `topic`, replacing any
+/// earlier share given to the same delegate on that topic. Shares on a
+/// topic may not exceed 100% in total, and the share must be worth at least
+/// `MIN_DELEGATED_POWER` at the delegator's current balance.
 pub fn delegate_vote(
     env: &Env,
     delegator: Address,
     delegate: Address,
+    topic: DelegationTopic,
+    share_bps: u32,
 ) -> Result<(), SwapTradeError> {
     delegator.require_auth();
+    if delegator == delegate || share_bps == 0 || share_bps > FULL_SHARE_BPS {
+        return Err(SwapTradeError::InvalidDelegation);
+    }
 
-    let mut delegations: Map<Address, Address> = env
-        .storage()
-        .persistent()
-        .get(&DELEGATIONS_KEY)
-        .unwrap_or_else(|| Map::new(env));
+    let current = get_delegations(env, &delegator);
+    let mut delegations = remove_entry(current.clone(), &delegate, topic);
+    let is_new = delegations.len() == current.len();
+    if topic_share(&delegations, topic) + share_bps > FULL_SHARE_BPS {
+        return Err(SwapTradeError::InvalidDelegation);
+    }
+    let balance = VoteEscrowManager::balance_of(env, &delegator);
+    let delegated = balance * share_bps as i128 / FULL_SHARE_BPS as i128;
+    if delegated < MIN_DELEGATED_POWER {
+        return Err(SwapTradeError::InvalidDelegation);
+    }
+    delegations.push_back(Delegation {
+        delegator: delegator.clone(),
+        delegate: delegate.clone(),
+        topic,
+        share_bps,
+    });
 
-    delegations.set(delegator.clone(), delegate.clone());
-    env.storage()
-        .persistent()
-        .set(&DELEGATIONS_KEY, &delegations);
+    let mut delegators = get_delegators(env, &delegate);
+    if !delegators.contains(&delegator) {
+        if delegators.len() >= MAX_DELEGATORS {
+            return Err(SwapTradeError::TooManyDelegators);
+        }
+        delegators.push_back(delegator.clone());
+        write_delegators(env, &delegate, delegators);
+    }
+    write_checkpoint(env, &delegator, delegations);
+    // Resizing an existing share earns the delegate no further credit.
+    if is_new {
+        RewardManager::record_delegation_received(env, &delegate, balance);
+    }
 
-    env.events()
-        .publish((symbol_short!("del_vote"),), (delegator, delegate));
+    env.events().publish(
+        (symbol_short!("del_vote"), topic as u32),
+        (delegator, delegate, share_bps),
+    );
 
     Ok(())
 }
 
-pub fn revoke_delegation(env: &Env, delegator: Address) -> Result<(), SwapTradeError> {
+/// Withdraw the share given to `delegate` on `topic`.
+pub fn revoke_delegation(
+    env: &Env,
+    delegator: Address,
+    delegate: Address,
+    topic: DelegationTopic,
+) -> Result<(), SwapTradeError> {
     delegator.require_auth();
 
-    let mut delegations: Map<Address, Address> = env
+    let delegations = get_delegations(env, &delegator);
+    let remaining = remove_entry(delegations.clone(), &delegate, topic);
+    if remaining.len() == delegations.len() {
+        return Err(SwapTradeError::DelegationNotFound);
+    }
+    // Free the delegator's slot once nothing is delegated to `delegate`.
+    if !remaining.iter().any(|d| d.delegate == delegate) {
+        let mut delegators = get_delegators(env, &delegate);
+        if let Some(i) = delegators.first_index_of(&delegator) {
+            delegators.remove(i);
+            write_delegators(env, &delegate, delegators);
+        }
+    }
+    write_checkpoint(env, &delegator, remaining);
+
+    env.events().publish(
+        (symbol_short!("del_revk"), topic as u32),
+        (delegator, delegate),
+    );
+
+    Ok(())
+}
+
+/// Current delegations made by `delegator`.
+pub fn get_delegations(env: &Env, delegator: &Address) -> Vec<Delegation> {
+    delegations_at(env, delegator, env.ledger().timestamp())
+}
+
+/// Delegations made by `delegator` as of `ts`.
+pub fn delegations_at(env: &Env, delegator: &Address, ts: u64) -> Vec<Delegation> {
+    let count: u32 = env
         .storage()
         .persistent()
-        .get(&DELEGATIONS_KEY)
-        .ok_or(SwapTradeError::NotAuthorized)?;
+        .get(&DelegationKey::CheckpointCount(delegator.clone()))
+        .unwrap_or(0);
+    let read = |i: u32| -> DelegationCheckpoint {
+        env.storage()
+            .persistent()
+            .get(&DelegationKey::Checkpoint(delegator.clone(), i))
+            .unwrap()
+    };
+    match checkpoint_at(count, ts, |i| read(i).ts) {
+        Some(i) => read(i).delegations,
+        None => Vec::new(env),
+    }
+}
+
+/// Current live delegators of `delegate`.
+pub fn get_delegators(env: &Env, delegate: &Address) -> Vec<Address> {
+    delegators_at(env, delegate, env.ledger().timestamp())
+}
 
-    delegations.remove(delegator.clone());
-    env.storage()
+/// Live delegators of `delegate` as of `ts`.
+pub fn delegators_at(env: &Env, delegate: &Address, ts: u64) -> Vec<Address> {
+    let count: u32 = env
+        .storage()
         .persistent()
-        .set(&DELEGATIONS_KEY, &delegations);
+        .get(&DelegationKey::DelegatorsCount(delegate.clone()))
+        .unwrap_or(0);
+    let read = |i: u32| -> DelegatorsCheckpoint {
+        env.storage()
+            .persistent()
+            .get(&DelegationKey::Delegators(delegate.clone(), i))
+            .unwrap()
+    };
+    match checkpoint_at(count, ts, |i| read(i).ts) {
+        Some(i) => read(i).delegators,
+        None => Vec::new(env),
+    }
+}
 
-    env.events()
-        .publish((symbol_short!("del_revk"),), (delegator,));
+/// Share of `delegator`'s power that has left them on `topic` at `ts`, and
+/// the part of it held by `delegate` (if given).
+fn effective_shares(
+    env: &Env,
+    delegator: &Address,
+    delegate: Option<&Address>,
+    topic: DelegationTopic,
+    ts: u64,
+) -> (u32, u32) {
+    let delegations = delegations_at(env, delegator, ts);
+    // Topic-specific delegations take precedence over `All`.
+    let applies = if topic != DelegationTopic::All && topic_share(&delegations, topic) > 0 {
+        topic
+    } else {
+        DelegationTopic::All
+    };
 
-    Ok(())
+    let (mut out, mut to_delegate) = (0u32, 0u32);
+    for d in delegations.iter().filter(|d| d.topic == applies) {
+        out += d.share_bps;
+        if Some(&d.delegate) == delegate {
+            to_delegate += d.share_bps;
+        }
+    }
+    (out, to_delegate)
 }
 
-pub fn get_delegate(env: &Env, delegator: &Address) -> Option<Address> {
-    let delegations: Map<Address, Address> = env
-        .storage()
-        .persistent()
-        .get(&DELEGATIONS_KEY)
-        .unwrap_or_else(|| Map::new(env));
-    delegations.get(delegator.clone())
+/// Voting power of `voter` on a `topic` proposal, with balances and
+/// delegations read as of `ts`: the voter's retained own power plus shares
+/// delegated directly to them. Delegated-in power is never passed on.
+pub fn voting_power_at(env: &Env, voter: &Address, topic: DelegationTopic, ts: u64) -> i128 {
+    let full = FULL_SHARE_BPS as i128;
+    let (out, _) = effective_shares(env, voter, None, topic, ts);
+    let mut power = VoteEscrowManager::balance_of_at(env, voter, ts) * (full - out as i128) / full;
+
+    for delegator in delegators_at(env, voter, ts).iter() {
+        let (_, share) = effective_shares(env, &delegator, Some(voter), topic, ts);
+        if share > 0 {
+            power += VoteEscrowManager::balance_of_at(env, &delegator, ts) * share as i128 / full;
+        }
+    }
+    power
+}
+
+fn topic_share(delegations: &Vec<Delegation>, topic: DelegationTopic) -> u32 {
+    delegations
+        .iter()
+        .filter(|d| d.topic == topic)
+        .map(|d| d.share_bps)
+        .sum()
+}
+
+fn remove_entry(
+    delegations: Vec<Delegation>,
+    delegate: &Address,
+    topic: DelegationTopic,
+) -> Vec<Delegation> {
+    let mut kept = Vec::new(delegations.env());
+    for d in delegations.iter() {
+        if !(d.delegate == *delegate && d.topic == topic) {
+            kept.push_back(d);
+        }
+    }
+    kept
+}
+
+/// Index of the last of `count` checkpoints taken at or before `ts`.
+fn checkpoint_at(count: u32, ts: u64, ts_of: impl Fn(u32) -> u64) -> Option<u32> {
+    if count == 0 || ts_of(0) > ts {
+        return None;
+    }
+    let (mut lo, mut hi) = (0u32, count - 1);
+    while lo < hi {
+        let mid = (lo + hi + 1) / 2;
+        if ts_of(mid) <= ts {
+            lo = mid;
+        } else {
+            hi = mid - 1;
+        }
+    }
+    Some(lo)
+}
+
+/// Index to write a checkpoint taken at `now` to. Several changes in one
+/// ledger overwrite that ledger's checkpoint.
+fn checkpoint_index(count: u32, now: u64, ts_of: impl Fn(u32) -> u64) -> u32 {
+    if count > 0 && ts_of(count - 1) == now {
+        count - 1
+    } else {
+        count
+    }
+}
+
+fn write_checkpoint(env: &Env, delegator: &Address, delegations: Vec<Delegation>) {
+    let ts = env.ledger().timestamp();
+    let count_key = DelegationKey::CheckpointCount(delegator.clone());
+    let count: u32 = env.storage().persistent().get(&count_key).unwrap_or(0);
+    let index = checkpoint_index(count, ts, |i| {
+        let last: DelegationCheckpoint = env
+            .storage()
+            .persistent()
+            .get(&DelegationKey::Checkpoint(delegator.clone(), i))
+            .unwrap();
+        last.ts
+    });
+    env.storage().persistent().set(
+        &DelegationKey::Checkpoint(delegator.clone(), index),
+        &DelegationCheckpoint { ts, delegations },
+    );
+    env.storage().persistent().set(&count_key, &(index + 1));
+}
+
+fn write_delegators(env: &Env, delegate: &Address, delegators: Vec<Address>) {
+    let ts = env.ledger().timestamp();
+    let count_key = DelegationKey::DelegatorsCount(delegate.clone());
+    let count: u32 = env.storage().persistent().get(&count_key).unwrap_or(0);
+    let index = checkpoint_index(count, ts, |i| {
+        let last: DelegatorsCheckpoint = env
+            .storage()
+            .persistent()
+            .get(&DelegationKey::Delegators(delegate.clone(), i))
+            .unwrap();
+        last.ts
+    });
+    env.storage().persistent().set(
+        &DelegationKey::Delegators(delegate.clone(), index),
+        &DelegatorsCheckpoint { ts, delegators },
+    );
+    env.storage().persistent().set(&count_key, &(index + 1));
 }
@@ -4,22 +4,28 @@
 //
 // Provides emergency pause/unpause functionality controlled by multi-sig.
 // Enables quick response to security issues while maintaining decentralized
-// governance principles.
+// governance principles. All state lives in contract storage so every
+// entrypoint can gate itself on the current level.
 //
 // Capabilities:
 //   - Multi-sig controlled emergency pause
 //   - Time-limited emergency actions (auto-expire)
 //   - Graduated emergency levels
 //   - Emergency whitelist for critical operations during pause
-//   - Emergency override with reduced threshold
-//   - Complete audit trail of all emergency actions
+//   - Per-account freeze
+//   - Hash-chained audit trail of all emergency actions
 
-use std::collections::HashMap;
+use soroban_sdk::xdr::ToXdr;
+use soroban_sdk::{contracttype, symbol_short, Address, Bytes, BytesN, Env, String, Vec};
+
+use crate::errors::SwapTradeError;
 
 // ─── Types ────────────────────────────────────────────────────────────────────
 
 /// Emergency severity level.
+#[contracttype]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u32)]
 pub enum EmergencyLevel {
     /// Normal operations.
     None = 0,
@@ -71,6 +77,11 @@ impl EmergencyLevel {
 }
 
 /// Types of operations that can be restricted.
+///
+/// `LpWithdraw` covers every path that only returns a user's own funds
+/// (LP removal, order cancellation, unstaking), so those stay open at
+/// Medium.
+#[contracttype]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum OperationType {
     Swap,
@@ -82,6 +93,7 @@ pub enum OperationType {
 }
 
 /// Status of an emergency action.
+#[contracttype]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum EmergencyActionStatus {
     /// Emergency action is active.
@@ -93,6 +105,7 @@ pub enum EmergencyActionStatus {
 }
 
 /// An emergency action record.
+#[contracttype]
 #[derive(Debug, Clone)]
 pub struct EmergencyAction {
     /// Unique action ID.
@@ -100,36 +113,48 @@ pub struct EmergencyAction {
     /// Emergency level set by this action.
     pub level: EmergencyLevel,
     /// Who initiated the action.
-    pub initiator: String,
+    pub initiator: Address,
     /// Reason for the emergency action.
     pub reason: String,
     /// When the action was taken.
     pub timestamp: u64,
-    /// When the action expires (auto-lifts). None means no auto-expiry.
+    /// When the action expires (auto-lifts). Defaults to the configured
+    /// `default_expiry_secs` when the initiator gives none.
     pub expires_at: Option<u64>,
     /// Current status.
     pub status: EmergencyActionStatus,
     /// Signers who approved this action.
-    pub approvals: Vec<String>,
-    /// Required approvals for this level.
-    pub required_approvals: usize,
+    pub approvals: Vec<Address>,
+    /// Required approvals for this level, both to apply and to lift.
+    pub required_approvals: u32,
+    /// Signers who approved lifting this action.
+    pub lift_approvals: Vec<Address>,
 }
 
 impl EmergencyAction {
     pub fn is_expired(&self, now: u64) -> bool {
         self.expires_at.map_or(false, |exp| now >= exp)
     }
+
+    /// Low and Medium actions apply once the standard threshold has
+    /// approved; High and Critical wait for the critical threshold.
+    pub fn is_in_force(&self, now: u64) -> bool {
+        self.status == EmergencyActionStatus::Active
+            && !self.is_expired(now)
+            && self.approvals.len() >= self.required_approvals
+    }
 }
 
 /// Emergency configuration.
+#[contracttype]
 #[derive(Debug, Clone)]
 pub struct EmergencyConfig {
     /// Signers authorized to trigger emergency actions.
-    pub signers: Vec<String>,
+    pub signers: Vec<Address>,
     /// Required approvals for Low/Medium level.
-    pub standard_threshold: usize,
+    pub standard_threshold: u32,
     /// Required approvals for High/Critical level.
-    pub critical_threshold: usize,
+    pub critical_threshold: u32,
     /// Default auto-expiry duration for emergency actions (seconds).
     pub default_expiry_secs: u64,
     /// Maximum auto-expiry duration allowed (seconds).
@@ -138,10 +163,10 @@ pub struct EmergencyConfig {
     pub escalation_cooldown_secs: u64,
 }
 
-impl Default for EmergencyConfig {
-    fn default() -> Self {
+impl EmergencyConfig {
+    pub fn new(signers: Vec<Address>) -> Self {
         Self {
-            signers: Vec::new(),
+            signers,
             standard_threshold: 2,
             critical_threshold: 3,
             default_expiry_secs: 3600,     // 1 hour
@@ -151,392 +176,498 @@ impl Default for EmergencyConfig {
     }
 }
 
-/// Audit entry for emergency actions.
+/// Mutable controller state.
+#[contracttype]
 #[derive(Debug, Clone)]
-pub struct EmergencyAuditEntry {
-    pub seq: u64,
-    pub timestamp: u64,
-    pub action: EmergencyAuditAction,
-    pub entry_hash: [u8; 32],
+pub struct EmergencyState {
+    /// Next action ID.
+    pub next_id: u64,
+    /// Timestamp of the last escalation (for cooldown).
+    pub last_escalation_at: u64,
+    /// Actions still marked Active, including ones awaiting approvals.
+    pub active: Vec<u64>,
+    /// Signers who approved an emergency resume. Cleared whenever an action
+    /// comes into force, is lifted or expires, so approvals only count
+    /// toward the emergency they were given for.
+    pub resume_approvals: Vec<Address>,
 }
 
+/// Audit entry for emergency actions.
+#[contracttype]
 #[derive(Debug, Clone)]
 pub enum EmergencyAuditAction {
-    LevelSet {
-        level: EmergencyLevel,
-        action_id: u64,
-    },
-    LevelExpired {
-        action_id: u64,
-    },
-    LevelLifted {
-        action_id: u64,
-    },
-    OperationAttempted {
-        operation: OperationType,
-        allowed: bool,
-    },
-    ConfigUpdated {
-        field: String,
-    },
+    LevelSet(EmergencyLevel, u64),
+    LevelApproved(u64, Address),
+    LevelExpired(u64),
+    LiftApproved(u64, Address),
+    LevelLifted(u64),
+    ResumeApproved(Address),
+    WhitelistUpdated(Address, bool),
+    FreezeApproved(Address, bool, Address),
+    FreezeUpdated(Address, bool),
+    ConfigUpdated,
 }
 
-// ─── Emergency Controller ─────────────────────────────────────────────────────
+/// Pending request to freeze or unfreeze an account.
+#[contracttype]
+#[derive(Debug, Clone)]
+pub struct FreezeRequest {
+    pub frozen: bool,
+    pub approvals: Vec<Address>,
+}
 
-pub struct EmergencyController {
-    /// Current emergency level.
-    current_level: EmergencyLevel,
-    /// Configuration.
-    config: EmergencyConfig,
-    /// Active and historical emergency actions.
-    actions: HashMap<u64, EmergencyAction>,
-    /// Next action ID.
-    next_id: u64,
-    /// Timestamp of the last escalation (for cooldown).
-    last_escalation_at: u64,
-    /// Whitelist of addresses allowed to operate during emergency.
-    whitelist: Vec<String>,
-    /// Audit trail.
-    audit_log: Vec<EmergencyAuditEntry>,
-    /// Sequence counter.
-    seq: u64,
+/// Head of the hash-chained audit trail. Each entry is published as an
+/// event; `hash` commits to every entry so far.
+#[contracttype]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmergencyAuditHead {
+    pub seq: u64,
+    pub hash: BytesN<32>,
+}
+
+#[contracttype]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EmergencyKey {
+    Config,
+    State,
+    Action(u64),
+    Whitelist(Address),
+    Frozen(Address),
+    AuditHead,
+    FreezeRequest(Address),
 }
 
+// ─── Emergency Controller ─────────────────────────────────────────────────────
+
+pub struct EmergencyController;
+
 impl EmergencyController {
-    pub fn new(config: EmergencyConfig) -> Self {
-        Self {
-            current_level: EmergencyLevel::None,
-            config,
-            actions: HashMap::new(),
-            next_id: 1,
-            last_escalation_at: 0,
-            whitelist: Vec::new(),
-            audit_log: Vec::new(),
-            seq: 0,
+    // ── Configuration ────────────────────────────────────────────────────────
+
+    /// Admin only: install the signer set and thresholds.
+    pub fn configure(
+        env: &Env,
+        admin: Address,
+        config: EmergencyConfig,
+    ) -> Result<(), SwapTradeError> {
+        admin.require_auth();
+        crate::admin::require_admin(env, &admin)?;
+
+        let signer_count = config.signers.len();
+        if config.standard_threshold == 0
+            || config.critical_threshold < config.standard_threshold
+            || config.critical_threshold > signer_count
+            || config.default_expiry_secs > config.max_expiry_secs
+        {
+            return Err(SwapTradeError::InvalidConfig);
         }
+
+        env.storage()
+            .persistent()
+            .set(&EmergencyKey::Config, &config);
+        Self::log_action(env, EmergencyAuditAction::ConfigUpdated);
+        Ok(())
+    }
+
+    pub fn get_config(env: &Env) -> Option<EmergencyConfig> {
+        env.storage().persistent().get(&EmergencyKey::Config)
     }
 
     // ── Emergency Level Management ───────────────────────────────────────────
 
     /// Set emergency level via multi-sig approval.
     pub fn set_emergency_level(
-        &mut self,
-        initiator: &str,
+        env: &Env,
+        initiator: Address,
         level: EmergencyLevel,
-        reason: &str,
-        now: u64,
+        reason: String,
         expiry_secs: Option<u64>,
-    ) -> Result<u64, String> {
-        if !self.config.signers.contains(&initiator.to_string()) {
-            return Err("only authorized signers can set emergency level".to_string());
-        }
+    ) -> Result<u64, SwapTradeError> {
+        let config = Self::require_signer(env, &initiator)?;
+        let now = env.ledger().timestamp();
+        let mut state = Self::get_state(env);
 
         // Check escalation cooldown (only when escalating, not de-escalating)
         // Skip cooldown check if this is the first emergency action (last_escalation_at == 0)
-        if level > self.current_level
-            && self.last_escalation_at > 0
-            && now < self.last_escalation_at + self.config.escalation_cooldown_secs
+        if level > Self::current_level(env)
+            && state.last_escalation_at > 0
+            && now < state.last_escalation_at + config.escalation_cooldown_secs
         {
-            return Err("escalation cooldown not elapsed".to_string());
+            return Err(SwapTradeError::EscalationCooldownActive);
         }
 
         let required = if level >= EmergencyLevel::High {
-            self.config.critical_threshold
+            config.critical_threshold
         } else {
-            self.config.standard_threshold
+            config.standard_threshold
         };
 
-        let expires_at = expiry_secs.map(|d| {
-            let clamped = if d > self.config.max_expiry_secs {
-                self.config.max_expiry_secs
-            } else {
-                d
-            };
-            now + clamped
-        });
+        let expiry = expiry_secs.unwrap_or(config.default_expiry_secs);
+        let expires_at = Some(now + expiry.min(config.max_expiry_secs));
 
-        let id = self.next_id;
-        self.next_id += 1;
+        let id = state.next_id;
+        state.next_id += 1;
 
         let action = EmergencyAction {
             id,
             level,
-            initiator: initiator.to_string(),
-            reason: reason.to_string(),
+            initiator: initiator.clone(),
+            reason,
             timestamp: now,
             expires_at,
             status: EmergencyActionStatus::Active,
-            approvals: vec![initiator.to_string()],
+            approvals: Vec::from_array(env, [initiator]),
             required_approvals: required,
+            lift_approvals: Vec::new(env),
         };
 
-        // For non-critical levels, a single signer is sufficient (emergency response)
-        // For critical levels, multi-sig is required
-        if level >= EmergencyLevel::High && required > 1 {
-            // Need more approvals, store as pending
-            self.actions.insert(id, action);
-            self.log_action(
-                EmergencyAuditAction::LevelSet {
-                    level,
-                    action_id: id,
-                },
-                now,
-            );
-            return Ok(id);
+        if action.is_in_force(now) {
+            state.last_escalation_at = now;
+            state.resume_approvals = Vec::new(env);
         }
-
-        // Apply immediately for lower levels or single-signer critical
-        self.current_level = level;
-        self.last_escalation_at = now;
-        self.actions.insert(id, action);
-        self.log_action(
-            EmergencyAuditAction::LevelSet {
-                level,
-                action_id: id,
-            },
-            now,
-        );
+        state.active.push_back(id);
+        Self::set_state(env, &state);
+        Self::set_action(env, &action);
+        Self::log_action(env, EmergencyAuditAction::LevelSet(level, id));
 
         Ok(id)
     }
 
     /// Approve a pending emergency action.
     pub fn approve_emergency(
-        &mut self,
+        env: &Env,
         action_id: u64,
-        signer: &str,
-        now: u64,
-    ) -> Result<usize, String> {
-        if !self.config.signers.contains(&signer.to_string()) {
-            return Err("only authorized signers can approve".to_string());
+        signer: Address,
+    ) -> Result<u32, SwapTradeError> {
+        Self::require_signer(env, &signer)?;
+        let now = env.ledger().timestamp();
+
+        let mut action =
+            Self::get_action(env, action_id).ok_or(SwapTradeError::EmergencyActionNotFound)?;
+        if action.status != EmergencyActionStatus::Active || action.is_expired(now) {
+            return Err(SwapTradeError::EmergencyActionNotActive);
         }
-
-        let action = self.actions.get_mut(&action_id).ok_or("action not found")?;
-
-        if action.status != EmergencyActionStatus::Active {
-            return Err("action is not active".to_string());
+        if action.approvals.contains(&signer) {
+            return Err(SwapTradeError::AlreadyApproved);
         }
 
-        if action.approvals.contains(&signer.to_string()) {
-            return Err("signer has already approved".to_string());
-        }
-
-        action.approvals.push(signer.to_string());
+        let was_in_force = action.is_in_force(now);
+        action.approvals.push_back(signer.clone());
         let count = action.approvals.len();
 
         // Check if threshold reached
-        if count >= action.required_approvals && self.current_level < action.level {
-            self.current_level = action.level;
-            self.last_escalation_at = now;
+        if !was_in_force && action.is_in_force(now) {
+            let mut state = Self::get_state(env);
+            state.last_escalation_at = now;
+            state.resume_approvals = Vec::new(env);
+            Self::set_state(env, &state);
         }
+        Self::set_action(env, &action);
+        Self::log_action(env, EmergencyAuditAction::LevelApproved(action_id, signer));
 
         Ok(count)
     }
 
-    /// Lift a specific emergency action.
-    pub fn lift_emergency(&mut self, action_id: u64, signer: &str, now: u64) -> Result<(), String> {
-        if !self.config.signers.contains(&signer.to_string()) {
-            return Err("only authorized signers can lift emergencies".to_string());
-        }
-
-        let action = self.actions.get_mut(&action_id).ok_or("action not found")?;
+    /// Approve lifting a specific emergency action. The action is lifted
+    /// once as many signers approve as were required to apply it. Returns
+    /// the lift approval count.
+    pub fn lift_emergency(
+        env: &Env,
+        action_id: u64,
+        signer: Address,
+    ) -> Result<u32, SwapTradeError> {
+        Self::require_signer(env, &signer)?;
 
+        let mut action =
+            Self::get_action(env, action_id).ok_or(SwapTradeError::EmergencyActionNotFound)?;
         if action.status != EmergencyActionStatus::Active {
-            return Err("action is not active".to_string());
+            return Err(SwapTradeError::EmergencyActionNotActive);
+        }
+        if action.lift_approvals.contains(&signer) {
+            return Err(SwapTradeError::AlreadyApproved);
         }
 
-        action.status = EmergencyActionStatus::Lifted;
-
-        // Recalculate current level from remaining active actions
-        self.recalculate_level();
+        action.lift_approvals.push_back(signer.clone());
+        let count = action.lift_approvals.len();
+        Self::log_action(env, EmergencyAuditAction::LiftApproved(action_id, signer));
 
-        self.log_action(EmergencyAuditAction::LevelLifted { action_id }, now);
+        if count >= action.required_approvals {
+            action.status = EmergencyActionStatus::Lifted;
+            Self::set_action(env, &action);
+            Self::remove_active(env, action_id);
+            Self::log_action(env, EmergencyAuditAction::LevelLifted(action_id));
+        } else {
+            Self::set_action(env, &action);
+        }
 
-        Ok(())
+        Ok(count)
     }
 
-    /// Emergency resume: immediately drop to normal operations (requires critical threshold).
+    /// Approve an emergency resume: once the critical threshold of signers
+    /// approves, every active action is lifted and operations return to
+    /// normal (requires High+ level). Returns the resume approval count.
     pub fn emergency_resume(
-        &mut self,
-        signer: &str,
-        _reason: &str,
-        now: u64,
-    ) -> Result<(), String> {
-        if !self.config.signers.contains(&signer.to_string()) {
-            return Err("only authorized signers can emergency resume".to_string());
+        env: &Env,
+        signer: Address,
+        _reason: String,
+    ) -> Result<u32, SwapTradeError> {
+        let config = Self::require_signer(env, &signer)?;
+
+        // Record expiries first so approvals left over from an emergency
+        // that has since run out are discarded
+        Self::tick(env);
+        if Self::current_level(env) < EmergencyLevel::High {
+            return Err(SwapTradeError::EmergencyLevelTooLow);
         }
 
-        if self.current_level < EmergencyLevel::High {
-            return Err("emergency resume only available at High+ level".to_string());
+        let mut state = Self::get_state(env);
+        if state.resume_approvals.contains(&signer) {
+            return Err(SwapTradeError::AlreadyApproved);
         }
-
-        // Mark all active actions as lifted
-        for action in self.actions.values_mut() {
-            if action.status == EmergencyActionStatus::Active {
-                action.status = EmergencyActionStatus::Lifted;
+        state.resume_approvals.push_back(signer.clone());
+        let count = state.resume_approvals.len();
+        Self::log_action(env, EmergencyAuditAction::ResumeApproved(signer));
+
+        if count >= config.critical_threshold {
+            for id in state.active.iter() {
+                if let Some(mut action) = Self::get_action(env, id) {
+                    action.status = EmergencyActionStatus::Lifted;
+                    Self::set_action(env, &action);
+                    Self::log_action(env, EmergencyAuditAction::LevelLifted(id));
+                }
             }
+            state.active = Vec::new(env);
+            state.resume_approvals = Vec::new(env);
         }
+        Self::set_state(env, &state);
 
-        self.current_level = EmergencyLevel::None;
-
-        self.log_action(
-            EmergencyAuditAction::LevelSet {
-                level: EmergencyLevel::None,
-                action_id: 0,
-            },
-            now,
-        );
-
-        Ok(())
+        Ok(count)
     }
 
-    /// Tick: expire any actions that have exceeded their TTL.
-    pub fn tick(&mut self, now: u64) {
-        let mut expired_ids = Vec::new();
-        for (id, action) in &mut self.actions {
-            if action.status == EmergencyActionStatus::Active && action.is_expired(now) {
-                action.status = EmergencyActionStatus::Expired;
-                expired_ids.push(*id);
+    /// Tick: expire any actions that have exceeded their TTL. Permissionless;
+    /// `current_level` already ignores expired actions, this only records it.
+    pub fn tick(env: &Env) {
+        let now = env.ledger().timestamp();
+        let mut state = Self::get_state(env);
+        let mut remaining = Vec::new(env);
+        for id in state.active.iter() {
+            match Self::get_action(env, id) {
+                Some(mut action) if action.is_expired(now) => {
+                    action.status = EmergencyActionStatus::Expired;
+                    Self::set_action(env, &action);
+                    Self::log_action(env, EmergencyAuditAction::LevelExpired(id));
+                }
+                Some(_) => remaining.push_back(id),
+                None => {}
             }
         }
-
-        if !expired_ids.is_empty() {
-            self.recalculate_level();
-            for id in &expired_ids {
-                self.log_action(EmergencyAuditAction::LevelExpired { action_id: *id }, now);
-            }
+        if remaining.len() != state.active.len() {
+            state.active = remaining;
+            state.resume_approvals = Vec::new(env);
+            Self::set_state(env, &state);
         }
     }
 
-    fn recalculate_level(&mut self) {
-        self.current_level = self
-            .actions
-            .values()
-            .filter(|a| a.status == EmergencyActionStatus::Active)
-            .map(|a| a.level)
-            .max()
-            .unwrap_or(EmergencyLevel::None);
-    }
-
     // ── Operation Gating ─────────────────────────────────────────────────────
 
-    /// Check if an operation is allowed at the current emergency level.
-    pub fn is_operation_allowed(&self, op: &OperationType) -> bool {
-        if self.current_level.allows_operation(op) {
+    /// Highest level among actions that are active, unexpired and approved.
+    pub fn current_level(env: &Env) -> EmergencyLevel {
+        let now = env.ledger().timestamp();
+        let mut level = EmergencyLevel::None;
+        for id in Self::get_state(env).active.iter() {
+            if let Some(action) = Self::get_action(env, id) {
+                if action.is_in_force(now) && action.level > level {
+                    level = action.level;
+                }
+            }
+        }
+        level
+    }
+
+    /// Check if `account` may perform an operation at the current level.
+    /// Whitelisted accounts bypass every level except Critical.
+    pub fn is_operation_allowed(env: &Env, op: &OperationType, account: &Address) -> bool {
+        let level = Self::current_level(env);
+        if level.allows_operation(op) {
             return true;
         }
-        // Check whitelist
-        false
+        level < EmergencyLevel::Critical && Self::is_whitelisted(env, account)
     }
 
     /// Add an address to the emergency whitelist.
-    pub fn add_to_whitelist(&mut self, address: &str, now: u64) -> Result<(), String> {
-        if self.whitelist.contains(&address.to_string()) {
-            return Err("address already whitelisted".to_string());
+    pub fn add_to_whitelist(
+        env: &Env,
+        signer: Address,
+        address: Address,
+    ) -> Result<(), SwapTradeError> {
+        Self::require_signer(env, &signer)?;
+        if Self::is_whitelisted(env, &address) {
+            return Err(SwapTradeError::InvalidAddress);
         }
-        self.whitelist.push(address.to_string());
-        self.log_action(
-            EmergencyAuditAction::ConfigUpdated {
-                field: format!("whitelist_add:{}", address),
-            },
-            now,
-        );
+        env.storage()
+            .persistent()
+            .set(&EmergencyKey::Whitelist(address.clone()), &true);
+        Self::log_action(env, EmergencyAuditAction::WhitelistUpdated(address, true));
         Ok(())
     }
 
     /// Remove an address from the emergency whitelist.
-    pub fn remove_from_whitelist(&mut self, address: &str, now: u64) {
-        self.whitelist.retain(|a| a != address);
-        self.log_action(
-            EmergencyAuditAction::ConfigUpdated {
-                field: format!("whitelist_remove:{}", address),
-            },
-            now,
-        );
+    pub fn remove_from_whitelist(
+        env: &Env,
+        signer: Address,
+        address: Address,
+    ) -> Result<(), SwapTradeError> {
+        Self::require_signer(env, &signer)?;
+        env.storage()
+            .persistent()
+            .remove(&EmergencyKey::Whitelist(address.clone()));
+        Self::log_action(env, EmergencyAuditAction::WhitelistUpdated(address, false));
+        Ok(())
     }
 
-    pub fn is_whitelisted(&self, address: &str) -> bool {
-        self.whitelist.contains(&address.to_string())
+    pub fn is_whitelisted(env: &Env, address: &Address) -> bool {
+        env.storage()
+            .persistent()
+            .get(&EmergencyKey::Whitelist(address.clone()))
+            .unwrap_or(false)
     }
 
-    // ── Queries ──────────────────────────────────────────────────────────────
+    /// Approve freezing or unfreezing a single account. The change applies
+    /// once the standard threshold of signers has approved it; an approval
+    /// for the opposite change starts a new request. Returns the approval
+    /// count.
+    pub fn set_frozen(
+        env: &Env,
+        signer: Address,
+        user: Address,
+        frozen: bool,
+    ) -> Result<u32, SwapTradeError> {
+        let config = Self::require_signer(env, &signer)?;
+        let key = EmergencyKey::FreezeRequest(user.clone());
+
+        let mut request = env
+            .storage()
+            .persistent()
+            .get::<_, FreezeRequest>(&key)
+            .filter(|request| request.frozen == frozen)
+            .unwrap_or_else(|| FreezeRequest {
+                frozen,
+                approvals: Vec::new(env),
+            });
+        if request.approvals.contains(&signer) {
+            return Err(SwapTradeError::AlreadyApproved);
+        }
+        request.approvals.push_back(signer.clone());
+        let count = request.approvals.len();
+        Self::log_action(
+            env,
+            EmergencyAuditAction::FreezeApproved(user.clone(), frozen, signer),
+        );
 
-    pub fn current_level(&self) -> EmergencyLevel {
-        self.current_level
-    }
+        if count >= config.standard_threshold {
+            env.storage().persistent().remove(&key);
+            env.storage()
+                .persistent()
+                .set(&EmergencyKey::Frozen(user.clone()), &frozen);
+            Self::log_action(env, EmergencyAuditAction::FreezeUpdated(user, frozen));
+        } else {
+            env.storage().persistent().set(&key, &request);
+        }
 
-    pub fn get_action(&self, action_id: u64) -> Option<&EmergencyAction> {
-        self.actions.get(&action_id)
+        Ok(count)
     }
 
-    pub fn is_paused(&self) -> bool {
-        self.current_level != EmergencyLevel::None
+    pub fn is_frozen(env: &Env, user: &Address) -> bool {
+        env.storage()
+            .persistent()
+            .get(&EmergencyKey::Frozen(user.clone()))
+            .unwrap_or(false)
     }
 
-    pub fn signer_count(&self) -> usize {
-        self.config.signers.len()
-    }
+    // ── Queries ──────────────────────────────────────────────────────────────
 
-    pub fn whitelist_length(&self) -> usize {
-        self.whitelist.len()
+    pub fn get_action(env: &Env, action_id: u64) -> Option<EmergencyAction> {
+        env.storage()
+            .persistent()
+            .get(&EmergencyKey::Action(action_id))
     }
 
-    /// Get the next action ID.
-    pub fn next_action_id(&self) -> u64 {
-        self.next_id
+    pub fn is_paused(env: &Env) -> bool {
+        Self::current_level(env) != EmergencyLevel::None
     }
 
-    // ── Audit Trail ──────────────────────────────────────────────────────────
+    pub fn get_audit_head(env: &Env) -> Option<EmergencyAuditHead> {
+        env.storage().persistent().get(&EmergencyKey::AuditHead)
+    }
 
-    fn log_action(&mut self, action: EmergencyAuditAction, now: u64) {
-        self.seq += 1;
-        let mut entry = EmergencyAuditEntry {
-            seq: self.seq,
-            timestamp: now,
-            action,
-            entry_hash: [0u8; 32],
-        };
+    // ── Internal ─────────────────────────────────────────────────────────────
 
-        let mut hash_input = Vec::new();
-        hash_input.extend_from_slice(&entry.seq.to_le_bytes());
-        hash_input.extend_from_slice(&entry.timestamp.to_le_bytes());
-        if let Some(prev) = self.audit_log.last() {
-            hash_input.extend_from_slice(&prev.entry_hash);
+    pub(crate) fn require_signer(
+        env: &Env,
+        signer: &Address,
+    ) -> Result<EmergencyConfig, SwapTradeError> {
+        signer.require_auth();
+        let config = Self::get_config(env).ok_or(SwapTradeError::InvalidConfig)?;
+        if !config.signers.contains(signer) {
+            return Err(SwapTradeError::NotEmergencyAdmin);
         }
-        entry.entry_hash = simple_hash(&hash_input);
+        Ok(config)
+    }
 
-        self.audit_log.push(entry);
+    fn get_state(env: &Env) -> EmergencyState {
+        env.storage()
+            .persistent()
+            .get(&EmergencyKey::State)
+            .unwrap_or_else(|| EmergencyState {
+                next_id: 1,
+                last_escalation_at: 0,
+                active: Vec::new(env),
+                resume_approvals: Vec::new(env),
+            })
     }
 
-    pub fn audit_log_length(&self) -> usize {
-        self.audit_log.len()
+    fn set_state(env: &Env, state: &EmergencyState) {
+        env.storage().persistent().set(&EmergencyKey::State, state);
     }
 
-    pub fn verify_audit_trail(&self) -> bool {
-        for (i, entry) in self.audit_log.iter().enumerate() {
-            let mut hash_input = Vec::new();
-            hash_input.extend_from_slice(&entry.seq.to_le_bytes());
-            hash_input.extend_from_slice(&entry.timestamp.to_le_bytes());
-            if i > 0 {
-                hash_input.extend_from_slice(&self.audit_log[i - 1].entry_hash);
-            }
-            if entry.entry_hash != simple_hash(&hash_input) {
-                return false;
-            }
+    fn set_action(env: &Env, action: &EmergencyAction) {
+        env.storage()
+            .persistent()
+            .set(&EmergencyKey::Action(action.id), action);
+    }
+
+    /// Drop a lifted action from the active set, discarding resume
+    /// approvals given while it was in force
+    fn remove_active(env: &Env, action_id: u64) {
+        let mut state = Self::get_state(env);
+        if let Some(index) = state.active.first_index_of(action_id) {
+            state.active.remove(index);
+            state.resume_approvals = Vec::new(env);
+            Self::set_state(env, &state);
         }
-        true
     }
-}
 
-fn simple_hash(data: &[u8]) -> [u8; 32] {
-    let mut hash: [u8; 32] = [0xE5; 32];
-    for (i, &byte) in data.iter().enumerate() {
-        hash[i % 32] = hash[i % 32].wrapping_mul(31).wrapping_add(byte);
-        hash[(i + 13) % 32] = hash[(i + 13) % 32]
-            .wrapping_mul(17)
-            .wrapping_add(byte ^ 0xBB);
+    /// Append an entry to the hash-chained audit trail and publish it.
+    fn log_action(env: &Env, action: EmergencyAuditAction) {
+        let now = env.ledger().timestamp();
+        let head = Self::get_audit_head(env);
+        let seq = head.as_ref().map_or(0, |h| h.seq) + 1;
+
+        let mut input = Bytes::new(env);
+        input.extend_from_array(&seq.to_be_bytes());
+        input.extend_from_array(&now.to_be_bytes());
+        if let Some(prev) = head {
+            input.append(&Bytes::from(prev.hash));
+        }
+        input.append(&action.clone().to_xdr(env));
+        let hash: BytesN<32> = env.crypto().sha256(&input).into();
+
+        env.storage()
+            .persistent()
+            .set(&EmergencyKey::AuditHead, &EmergencyAuditHead { seq, hash });
+        env.events()
+            .publish((symbol_short!("em_audit"), seq), (action, now));
     }
-    hash
 }
 
 // ─── Tests ────────────────────────────────────────────────────────────────────
@@ -544,205 +675,299 @@ fn simple_hash(data: &[u8]) -> [u8; 32] {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::storage::ADMIN_KEY;
+    use soroban_sdk::testutils::{Address as _, Ledger};
 
-    fn make_signers() -> Vec<String> {
-        vec![
-            "alice".to_string(),
-            "bob".to_string(),
-            "carol".to_string(),
-            "dave".to_string(),
-            "eve".to_string(),
-        ]
+    struct Setup {
+        env: Env,
+        contract_id: Address,
+        signers: Vec<Address>,
     }
 
-    fn setup() -> EmergencyController {
-        let config = EmergencyConfig {
-            signers: make_signers(),
-            standard_threshold: 2,
-            critical_threshold: 3,
-            default_expiry_secs: 3600,
-            max_expiry_secs: 86400,
-            escalation_cooldown_secs: 300,
-        };
-        EmergencyController::new(config)
+    impl Setup {
+        fn signer(&self, i: u32) -> Address {
+            self.signers.get(i).unwrap()
+        }
+
+        fn at<T>(&self, now: u64, f: impl FnOnce() -> T) -> T {
+            self.env.ledger().with_mut(|l| l.timestamp = now);
+            self.env.as_contract(&self.contract_id, f)
+        }
+    }
+
+    fn setup() -> Setup {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register(crate::CounterContract, ());
+        let admin = Address::generate(&env);
+        let mut signers = Vec::new(&env);
+        for _ in 0..5 {
+            signers.push_back(Address::generate(&env));
+        }
+
+        env.as_contract(&contract_id, || {
+            env.storage().persistent().set(&ADMIN_KEY, &admin);
+            EmergencyController::configure(
+                &env,
+                admin.clone(),
+                EmergencyConfig::new(signers.clone()),
+            )
+            .unwrap();
+        });
+
+        Setup {
+            env,
+            contract_id,
+            signers,
+        }
+    }
+
+    fn reason(s: &Setup) -> String {
+        String::from_str(&s.env, "issue")
+    }
+
+    fn level(s: &Setup, now: u64) -> EmergencyLevel {
+        s.at(now, || EmergencyController::current_level(&s.env))
+    }
+
+    fn allowed(s: &Setup, now: u64, op: OperationType) -> bool {
+        let outsider = Address::generate(&s.env);
+        s.at(now, || {
+            EmergencyController::is_operation_allowed(&s.env, &op, &outsider)
+        })
     }
 
     #[test]
     fn test_initial_state() {
-        let ctrl = setup();
-        assert_eq!(ctrl.current_level(), EmergencyLevel::None);
-        assert!(!ctrl.is_paused());
-        assert!(ctrl.is_operation_allowed(&OperationType::Swap));
+        let s = setup();
+        assert_eq!(level(&s, 0), EmergencyLevel::None);
+        assert!(!s.at(0, || EmergencyController::is_paused(&s.env)));
+        assert!(allowed(&s, 0, OperationType::Swap));
+    }
+
+    fn raise(s: &Setup, now: u64, by: u32, level: EmergencyLevel, expiry: Option<u64>) -> u64 {
+        s.at(now, || {
+            EmergencyController::set_emergency_level(&s.env, s.signer(by), level, reason(s), expiry)
+                .unwrap()
+        })
+    }
+
+    fn approve(s: &Setup, now: u64, id: u64, by: u32) {
+        s.at(now, || {
+            EmergencyController::approve_emergency(&s.env, id, s.signer(by)).unwrap()
+        });
+    }
+
+    fn lift(s: &Setup, now: u64, id: u64, by: u32) -> u32 {
+        s.at(now, || {
+            EmergencyController::lift_emergency(&s.env, id, s.signer(by)).unwrap()
+        })
     }
 
     #[test]
     fn test_set_low_emergency() {
-        let mut ctrl = setup();
-        let id = ctrl
-            .set_emergency_level(
-                "alice",
-                EmergencyLevel::Low,
-                "Suspicious activity",
-                100,
-                None,
-            )
-            .unwrap();
+        let s = setup();
+        let id = raise(&s, 100, 0, EmergencyLevel::Low, None);
+
+        // Low still needs the standard threshold of 2
+        assert_eq!(level(&s, 100), EmergencyLevel::None);
+        approve(&s, 100, id, 1);
 
-        assert_eq!(ctrl.current_level(), EmergencyLevel::Low);
-        assert!(ctrl.is_paused());
-        assert!(!ctrl.is_operation_allowed(&OperationType::Swap));
-        assert!(ctrl.is_operation_allowed(&OperationType::LpWithdraw));
+        assert_eq!(level(&s, 100), EmergencyLevel::Low);
+        assert!(s.at(100, || EmergencyController::is_paused(&s.env)));
+        assert!(!allowed(&s, 100, OperationType::Swap));
+        assert!(allowed(&s, 100, OperationType::LpWithdraw));
 
-        let action = ctrl.get_action(id).unwrap();
+        let action = s.at(100, || EmergencyController::get_action(&s.env, id).unwrap());
         assert_eq!(action.level, EmergencyLevel::Low);
     }
 
     #[test]
     fn test_set_critical_emergency_requires_multisig() {
-        let mut ctrl = setup();
-        let id = ctrl
-            .set_emergency_level(
-                "alice",
-                EmergencyLevel::Critical,
-                "Critical vulnerability",
-                100,
-                None,
-            )
-            .unwrap();
+        let s = setup();
+        let id = raise(&s, 100, 0, EmergencyLevel::Critical, None);
 
         // Not yet critical - needs 3 approvals
-        assert_eq!(ctrl.current_level(), EmergencyLevel::None);
+        assert_eq!(level(&s, 100), EmergencyLevel::None);
 
-        ctrl.approve_emergency(id, "bob", 100).unwrap();
-        assert_eq!(ctrl.current_level(), EmergencyLevel::None); // Still needs 1 more
+        approve(&s, 100, id, 1);
+        assert_eq!(level(&s, 100), EmergencyLevel::None); // Still needs 1 more
 
-        ctrl.approve_emergency(id, "carol", 100).unwrap();
-        assert_eq!(ctrl.current_level(), EmergencyLevel::Critical); // Now reached
+        approve(&s, 100, id, 2);
+        assert_eq!(level(&s, 100), EmergencyLevel::Critical); // Now reached
     }
 
     #[test]
     fn test_non_signer_cannot_set_emergency() {
-        let mut ctrl = setup();
-        let result = ctrl.set_emergency_level("frank", EmergencyLevel::High, "test", 100, None);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("only authorized"));
+        let s = setup();
+        let outsider = Address::generate(&s.env);
+        let result = s.at(100, || {
+            EmergencyController::set_emergency_level(
+                &s.env,
+                outsider.clone(),
+                EmergencyLevel::High,
+                reason(&s),
+                None,
+            )
+        });
+        assert_eq!(result, Err(SwapTradeError::NotEmergencyAdmin));
     }
 
     #[test]
     fn test_lift_emergency() {
-        let mut ctrl = setup();
-        let id = ctrl
-            .set_emergency_level("alice", EmergencyLevel::Medium, "Issue", 100, None)
-            .unwrap();
+        let s = setup();
+        let id = raise(&s, 100, 0, EmergencyLevel::Medium, None);
+        approve(&s, 100, id, 1);
+        assert_eq!(level(&s, 100), EmergencyLevel::Medium);
 
-        assert_eq!(ctrl.current_level(), EmergencyLevel::Medium);
+        // One signer alone cannot lift what two signers raised
+        assert_eq!(lift(&s, 200, id, 2), 1);
+        assert_eq!(level(&s, 200), EmergencyLevel::Medium);
 
-        ctrl.lift_emergency(id, "bob", 200).unwrap();
-        assert_eq!(ctrl.current_level(), EmergencyLevel::None);
-        assert!(!ctrl.is_paused());
+        assert_eq!(lift(&s, 200, id, 3), 2);
+        assert_eq!(level(&s, 200), EmergencyLevel::None);
     }
 
     #[test]
-    fn test_auto_expiry() {
-        let mut ctrl = setup();
-        let id = ctrl
-            .set_emergency_level(
-                "alice",
-                EmergencyLevel::Low,
-                "Temporary issue",
-                100,
-                Some(600), // 10 minute expiry
-            )
-            .unwrap();
+    fn test_lift_critical_requires_critical_threshold() {
+        let s = setup();
+        let id = raise(&s, 100, 0, EmergencyLevel::Critical, None);
+        approve(&s, 100, id, 1);
+        approve(&s, 100, id, 2);
+        assert_eq!(level(&s, 100), EmergencyLevel::Critical);
+
+        lift(&s, 200, id, 3);
+        lift(&s, 200, id, 4);
+        assert_eq!(level(&s, 200), EmergencyLevel::Critical);
+
+        let dup = s.at(200, || {
+            EmergencyController::lift_emergency(&s.env, id, s.signer(4))
+        });
+        assert_eq!(dup, Err(SwapTradeError::AlreadyApproved));
 
-        assert_eq!(ctrl.current_level(), EmergencyLevel::Low);
+        assert_eq!(lift(&s, 200, id, 0), 3);
+        assert_eq!(level(&s, 200), EmergencyLevel::None);
+    }
+
+    #[test]
+    fn test_auto_expiry() {
+        let s = setup();
+        // 10 minute expiry
+        let id = raise(&s, 100, 0, EmergencyLevel::Low, Some(600));
+        approve(&s, 100, id, 1);
 
         // Not expired yet
-        ctrl.tick(500);
-        assert_eq!(ctrl.current_level(), EmergencyLevel::Low);
+        assert_eq!(level(&s, 500), EmergencyLevel::Low);
 
-        // Expired
-        ctrl.tick(701);
-        assert_eq!(ctrl.current_level(), EmergencyLevel::None);
+        // Expired without anyone calling tick
+        assert_eq!(level(&s, 701), EmergencyLevel::None);
+
+        s.at(701, || EmergencyController::tick(&s.env));
+        let action = s.at(701, || EmergencyController::get_action(&s.env, id).unwrap());
+        assert_eq!(action.status, EmergencyActionStatus::Expired);
     }
 
     #[test]
     fn test_escalation_cooldown() {
-        let mut ctrl = setup();
-        ctrl.set_emergency_level("alice", EmergencyLevel::Low, "Issue 1", 100, None)
-            .unwrap();
+        let s = setup();
+        let id = raise(&s, 100, 0, EmergencyLevel::Low, None);
+        approve(&s, 100, id, 1);
 
         // Try to escalate immediately
-        let result = ctrl.set_emergency_level("alice", EmergencyLevel::High, "Issue 2", 200, None);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("cooldown"));
+        let result = s.at(200, || {
+            EmergencyController::set_emergency_level(
+                &s.env,
+                s.signer(0),
+                EmergencyLevel::High,
+                reason(&s),
+                None,
+            )
+        });
+        assert_eq!(result, Err(SwapTradeError::EscalationCooldownActive));
 
         // After cooldown
-        let result =
-            ctrl.set_emergency_level("alice", EmergencyLevel::High, "Issue 2", 200 + 301, None);
-        assert!(result.is_ok());
-    }
-
-    #[test]
-    fn test_de_escalation_no_cooldown() {
-        let mut ctrl = setup();
-        ctrl.set_emergency_level("alice", EmergencyLevel::High, "Issue", 100, None)
-            .unwrap();
-
-        // Can immediately de-escalate
-        let result = ctrl.set_emergency_level("alice", EmergencyLevel::Low, "Resolved", 101, None);
+        let result = s.at(200 + 301, || {
+            EmergencyController::set_emergency_level(
+                &s.env,
+                s.signer(0),
+                EmergencyLevel::High,
+                reason(&s),
+                None,
+            )
+        });
         assert!(result.is_ok());
     }
 
     #[test]
     fn test_whitelist() {
-        let mut ctrl = setup();
-        ctrl.set_emergency_level("alice", EmergencyLevel::Critical, "Issue", 100, None)
-            .unwrap();
-
-        assert!(!ctrl.is_whitelisted("trusted_contract"));
-        ctrl.add_to_whitelist("trusted_contract", 200).unwrap();
-        assert!(ctrl.is_whitelisted("trusted_contract"));
+        let s = setup();
+        let trusted = Address::generate(&s.env);
+        let id = raise(&s, 100, 0, EmergencyLevel::Medium, None);
+        approve(&s, 100, id, 1);
 
-        ctrl.remove_from_whitelist("trusted_contract", 300);
-        assert!(!ctrl.is_whitelisted("trusted_contract"));
+        s.at(100, || {
+            EmergencyController::add_to_whitelist(&s.env, s.signer(1), trusted.clone()).unwrap()
+        });
+        assert!(s.at(100, || EmergencyController::is_operation_allowed(
+            &s.env,
+            &OperationType::Swap,
+            &trusted
+        )));
+
+        s.at(100, || {
+            EmergencyController::remove_from_whitelist(&s.env, s.signer(1), trusted.clone())
+                .unwrap()
+        });
+        assert!(!s.at(100, || EmergencyController::is_operation_allowed(
+            &s.env,
+            &OperationType::Swap,
+            &trusted
+        )));
     }
 
     #[test]
     fn test_emergency_resume() {
-        let mut ctrl = setup();
-        let id = ctrl
-            .set_emergency_level("alice", EmergencyLevel::Critical, "Issue", 100, None)
-            .unwrap();
-
+        let s = setup();
+        let id = raise(&s, 100, 0, EmergencyLevel::Critical, None);
         // Need approvals for Critical
-        ctrl.approve_emergency(id, "bob", 100).unwrap();
-        ctrl.approve_emergency(id, "carol", 100).unwrap();
-        assert_eq!(ctrl.current_level(), EmergencyLevel::Critical);
+        approve(&s, 100, id, 1);
+        approve(&s, 100, id, 2);
+        assert_eq!(level(&s, 100), EmergencyLevel::Critical);
+
+        for by in 1..3 {
+            s.at(200, || {
+                EmergencyController::emergency_resume(&s.env, s.signer(by), reason(&s)).unwrap()
+            });
+            assert_eq!(level(&s, 200), EmergencyLevel::Critical);
+        }
 
-        ctrl.emergency_resume("bob", "Fixed", 200).unwrap();
-        assert_eq!(ctrl.current_level(), EmergencyLevel::None);
+        let count = s.at(200, || {
+            EmergencyController::emergency_resume(&s.env, s.signer(3), reason(&s)).unwrap()
+        });
+        assert_eq!(count, 3);
+        assert_eq!(level(&s, 200), EmergencyLevel::None);
     }
 
     #[test]
     fn test_emergency_resume_requires_high_level() {
-        let mut ctrl = setup();
-        ctrl.set_emergency_level("alice", EmergencyLevel::Low, "Issue", 100, None)
-            .unwrap();
+        let s = setup();
+        let id = raise(&s, 100, 0, EmergencyLevel::Low, None);
+        approve(&s, 100, id, 1);
 
-        let result = ctrl.emergency_resume("bob", "test", 200);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("High+ level"));
+        let result = s.at(200, || {
+            EmergencyController::emergency_resume(&s.env, s.signer(1), reason(&s))
+        });
+        assert_eq!(result, Err(SwapTradeError::EmergencyLevelTooLow));
     }
 
     #[test]
     fn test_operation_gating() {
-        let ctrl = setup();
+        let s = setup();
 
         // None level - all operations allowed
-        assert!(ctrl.is_operation_allowed(&OperationType::Swap));
-        assert!(ctrl.is_operation_allowed(&OperationType::LpDeposit));
-        assert!(ctrl.is_operation_allowed(&OperationType::LpWithdraw));
+        assert!(allowed(&s, 0, OperationType::Swap));
+        assert!(allowed(&s, 0, OperationType::LpDeposit));
+        assert!(allowed(&s, 0, OperationType::LpWithdraw));
 
         // Level checks
         assert!(EmergencyLevel::None.allows_operation(&OperationType::Swap));
@@ -755,85 +980,80 @@ mod tests {
         assert!(!EmergencyLevel::Critical.allows_operation(&OperationType::EmergencyWithdraw));
     }
 
-    #[test]
-    fn test_multiple_emergency_levels() {
-        let mut ctrl = setup();
-
-        ctrl.set_emergency_level("alice", EmergencyLevel::Low, "Issue 1", 100, None)
-            .unwrap();
-        assert_eq!(ctrl.current_level(), EmergencyLevel::Low);
-
-        // Can escalate to Medium without cooldown (not escalating beyond cooldown check)
-        // Actually: cooldown applies for any escalation
-        ctrl.set_emergency_level("bob", EmergencyLevel::Medium, "Worse", 401, None)
-            .unwrap();
-        assert_eq!(ctrl.current_level(), EmergencyLevel::Medium);
-    }
-
     #[test]
     fn test_lift_specific_action() {
-        let mut ctrl = setup();
+        let s = setup();
 
-        // Set Low level (applies immediately, single signer)
-        let id1 = ctrl
-            .set_emergency_level("alice", EmergencyLevel::Low, "Issue 1", 100, None)
-            .unwrap();
-        assert_eq!(ctrl.current_level(), EmergencyLevel::Low);
+        let id1 = raise(&s, 100, 0, EmergencyLevel::Low, None);
+        approve(&s, 100, id1, 1);
 
-        // Escalate to Medium (applies immediately after cooldown)
-        let id2 = ctrl
-            .set_emergency_level("alice", EmergencyLevel::Medium, "Issue 2", 401, None)
-            .unwrap();
-        assert_eq!(ctrl.current_level(), EmergencyLevel::Medium);
+        // Escalate to Medium after the cooldown
+        let id2 = raise(&s, 401, 0, EmergencyLevel::Medium, None);
+        approve(&s, 401, id2, 1);
+        assert_eq!(level(&s, 401), EmergencyLevel::Medium);
 
         // Lift only the Medium action
-        ctrl.lift_emergency(id2, "carol", 600).unwrap();
-        assert_eq!(ctrl.current_level(), EmergencyLevel::Low);
+        lift(&s, 600, id2, 2);
+        lift(&s, 600, id2, 3);
+        assert_eq!(level(&s, 600), EmergencyLevel::Low);
 
         // Lift the Low action
-        ctrl.lift_emergency(id1, "dave", 700).unwrap();
-        assert_eq!(ctrl.current_level(), EmergencyLevel::None);
+        lift(&s, 700, id1, 3);
+        lift(&s, 700, id1, 4);
+        assert_eq!(level(&s, 700), EmergencyLevel::None);
     }
 
     #[test]
-    fn test_audit_trail() {
-        let mut ctrl = setup();
-
-        let id = ctrl
-            .set_emergency_level("alice", EmergencyLevel::Low, "Issue", 100, None)
-            .unwrap();
-        ctrl.lift_emergency(id, "bob", 200).unwrap();
+    fn test_pending_critical_does_not_apply_when_other_action_lifted() {
+        let s = setup();
+        let low = raise(&s, 100, 0, EmergencyLevel::Low, None);
+        raise(&s, 100, 1, EmergencyLevel::Critical, None);
+        approve(&s, 100, low, 3);
+        lift(&s, 100, low, 2);
+        lift(&s, 100, low, 4);
+        assert_eq!(level(&s, 100), EmergencyLevel::None);
+    }
 
-        assert_eq!(ctrl.audit_log_length(), 2);
-        assert!(ctrl.verify_audit_trail());
+    #[test]
+    fn test_audit_trail() {
+        let s = setup();
+        let before = s.at(100, || EmergencyController::get_audit_head(&s.env).unwrap());
+
+        let id = raise(&s, 100, 0, EmergencyLevel::Low, None);
+        approve(&s, 100, id, 1);
+        lift(&s, 200, id, 1);
+        lift(&s, 200, id, 2);
+
+        // Set, approve, two lift approvals and the lift itself
+        let after = s.at(200, || EmergencyController::get_audit_head(&s.env).unwrap());
+        assert_eq!(after.seq, before.seq + 5);
+        assert_ne!(after.hash, before.hash);
     }
 
     #[test]
     fn test_duplicate_approve_fails() {
-        let mut ctrl = setup();
-        let id = ctrl
-            .set_emergency_level("alice", EmergencyLevel::Critical, "Issue", 100, None)
-            .unwrap();
+        let s = setup();
+        let id = raise(&s, 100, 0, EmergencyLevel::Critical, None);
+        let result = s.at(100, || {
+            EmergencyController::approve_emergency(&s.env, id, s.signer(0))
+        });
+        assert_eq!(result, Err(SwapTradeError::AlreadyApproved));
+    }
 
-        let result = ctrl.approve_emergency(id, "alice", 100);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("already approved"));
+    #[test]
+    fn test_default_expiry_applied() {
+        let s = setup();
+        let id = raise(&s, 100, 0, EmergencyLevel::Low, None);
+        let action = s.at(100, || EmergencyController::get_action(&s.env, id).unwrap());
+        assert_eq!(action.expires_at, Some(100 + 3600));
     }
 
     #[test]
     fn test_default_expiry_clamped() {
-        let mut ctrl = setup();
-        let id = ctrl
-            .set_emergency_level(
-                "alice",
-                EmergencyLevel::Low,
-                "Issue",
-                100,
-                Some(999999), // Exceeds max_expiry_secs
-            )
-            .unwrap();
-
-        let action = ctrl.get_action(id).unwrap();
+        let s = setup();
+        // Exceeds max_expiry_secs
+        let id = raise(&s, 100, 0, EmergencyLevel::Low, Some(999999));
+        let action = s.at(100, || EmergencyController::get_action(&s.env, id).unwrap());
         assert_eq!(action.expires_at, Some(100 + 86400)); // Clamped to max
     }
 }
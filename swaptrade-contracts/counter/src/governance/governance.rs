@@ -144,7 +144,7 @@ impl DecentralizationSchedule {
 
     pub fn months_to_next_phase(&self) -> Option<u64> {
         let elapsed_months = self.elapsed_months();
-        let next_start = match GovernancePhase::from_elapsed(elapsed_months * SECS_PER_MONTH) {
+        let next_start: u64 = match GovernancePhase::from_elapsed(elapsed_months * SECS_PER_MONTH) {
             GovernancePhase::FullAdmin  => 3,
             GovernancePhase::PauseOnly  => 6,
             GovernancePhase::MultiSig   => 12,
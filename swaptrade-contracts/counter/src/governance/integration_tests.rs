@@ -8,690 +8,1265 @@
 
 #[cfg(test)]
 mod treasury_integration_tests {
-    use super::super::treasury::*;
+    use crate::errors::ContractError;
+    use crate::governance::treasury::*;
+    use crate::kyc::KYCStatus;
+    use crate::storage::ADMIN_KEY;
+    use crate::{CounterContract, CounterContractClient};
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::token::{StellarAssetClient, TokenClient};
+    use soroban_sdk::{symbol_short, Address, Env, String, Vec};
+
+    struct Setup {
+        env: Env,
+        contract_id: Address,
+        client: CounterContractClient<'static>,
+        admin: Address,
+        signers: Vec<Address>,
+        xlm: Address,
+        usdc: Address,
+        btc: Address,
+    }
 
-    fn create_treasury_with_funds() -> Treasury {
-        let signers = vec![
-            "alice".to_string(),
-            "bob".to_string(),
-            "carol".to_string(),
-            "dave".to_string(),
-            "eve".to_string(),
-        ];
-        let mut treasury = Treasury::new(signers, 3, 2);
-        treasury
-            .deposit("XLM", 10_000_000, "foundation", 100)
-            .unwrap();
-        treasury
-            .deposit("USDC", 5_000_000, "foundation", 100)
-            .unwrap();
-        treasury.deposit("BTC", 100_000_000, "donor", 100).unwrap();
-        treasury
+    impl Setup {
+        fn signer(&self, i: u32) -> Address {
+            self.signers.get(i).unwrap()
+        }
+
+        fn verified_trader(&self) -> Address {
+            let trader = Address::generate(&self.env);
+            let operator = Address::generate(&self.env);
+            self.client.kyc_add_operator(&self.admin, &operator);
+            self.client.kyc_submit(&trader);
+            self.client
+                .kyc_update_status(&operator, &trader, &KYCStatus::InReview, &None);
+            self.client
+                .kyc_update_status(&operator, &trader, &KYCStatus::Verified, &None);
+            trader
+        }
+
+        fn advance_to(&self, now: u64) {
+            self.env.ledger().with_mut(|l| l.timestamp = now);
+        }
+
+        fn new_token(&self) -> Address {
+            self.env
+                .register_stellar_asset_contract_v2(self.admin.clone())
+                .address()
+        }
+
+        fn deposit(&self, token: &Address, amount: i128, now: u64) {
+            self.advance_to(now);
+            let funder = Address::generate(&self.env);
+            StellarAssetClient::new(&self.env, token).mint(&funder, &amount);
+            self.client.deposit_treasury(&funder, token, &amount);
+        }
+
+        fn propose(
+            &self,
+            proposal_type: TreasuryProposalType,
+            token: &Address,
+            amount: i128,
+            recipient: &Address,
+            description: &str,
+            now: u64,
+            ttl_secs: u64,
+        ) -> u64 {
+            self.advance_to(now);
+            self.client.create_treasury_proposal(
+                &self.signer(0),
+                &proposal_type,
+                token,
+                &amount,
+                recipient,
+                &String::from_str(&self.env, description),
+                &ttl_secs,
+                &Payout::Immediate,
+            )
+        }
+
+        fn approve_and_execute(&self, id: u64, now: u64) {
+            self.advance_to(now);
+            self.client.approve_treasury_proposal(&id, &self.signer(1));
+            self.client.approve_treasury_proposal(&id, &self.signer(2));
+            self.client.execute_treasury_proposal(&id);
+        }
+    }
+
+    fn create_treasury_with_funds() -> Setup {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register(CounterContract, ());
+        let client = CounterContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let mut signers = Vec::new(&env);
+        for _ in 0..5 {
+            signers.push_back(Address::generate(&env));
+        }
+        env.as_contract(&contract_id, || {
+            env.storage().persistent().set(&ADMIN_KEY, &admin);
+        });
+        client.configure_treasury(
+            &admin,
+            &TreasuryConfig {
+                signers: signers.clone(),
+                threshold: 3,
+                emergency_threshold: 2,
+            },
+        );
+
+        let mut s = Setup {
+            env,
+            contract_id,
+            client,
+            admin: admin.clone(),
+            signers,
+            xlm: admin.clone(),
+            usdc: admin.clone(),
+            btc: admin,
+        };
+        s.xlm = s.new_token();
+        s.usdc = s.new_token();
+        s.btc = s.new_token();
+        s.deposit(&s.xlm, 10_000_000, 100);
+        s.deposit(&s.usdc, 5_000_000, 100);
+        s.deposit(&s.btc, 100_000_000, 100);
+        s
     }
 
     #[test]
     fn test_full_withdrawal_lifecycle() {
-        let mut treasury = create_treasury_with_funds();
+        let s = create_treasury_with_funds();
+        let community = Address::generate(&s.env);
 
         // Create withdrawal proposal
-        let proposal_id = treasury
-            .create_proposal(
-                "alice",
-                ProposalType::Grant,
-                "XLM",
-                1_000_000,
-                "community_wallet",
-                "Q1 2024 community grants budget",
-                200,
-                86400 * 7,
-            )
-            .unwrap();
+        let proposal_id = s.propose(
+            TreasuryProposalType::Grant,
+            &s.xlm,
+            1_000_000,
+            &community,
+            "Q1 2024 community grants budget",
+            200,
+            86400 * 7,
+        );
 
         // Collect approvals
+        s.advance_to(300);
         assert_eq!(
-            treasury.approve_proposal(&proposal_id, "bob", 300).unwrap(),
+            s.client
+                .approve_treasury_proposal(&proposal_id, &s.signer(1)),
             2
         );
         assert_eq!(
-            treasury
-                .approve_proposal(&proposal_id, "carol", 300)
-                .unwrap(),
+            s.client
+                .approve_treasury_proposal(&proposal_id, &s.signer(2)),
             3
         );
 
         // Verify approved status
-        let proposal = treasury.get_proposal(&proposal_id).unwrap();
+        let proposal = s.client.get_treasury_proposal(&proposal_id).unwrap();
         assert_eq!(proposal.status, TreasuryOperationStatus::Approved);
 
         // Execute
-        let initial_balance = treasury.balance_of("XLM");
-        treasury.execute_proposal(&proposal_id, 400).unwrap();
-        assert_eq!(treasury.balance_of("XLM"), initial_balance - 1_000_000);
-
-        // Verify audit trail
-        assert!(treasury.verify_audit_trail());
+        s.advance_to(400);
+        assert_eq!(s.client.execute_treasury_proposal(&proposal_id), None);
+        assert_eq!(s.client.get_treasury_balance(&s.xlm), 9_000_000);
+        assert_eq!(
+            TokenClient::new(&s.env, &s.xlm).balance(&community),
+            1_000_000
+        );
+        assert_eq!(
+            TokenClient::new(&s.env, &s.xlm).balance(&s.contract_id),
+            9_000_000
+        );
     }
 
     #[test]
     fn test_emergency_withdrawal_fast_track() {
-        let mut treasury = create_treasury_with_funds();
-
-        // Emergency withdrawal only needs 2 approvals
-        let proposal_id = treasury
-            .create_proposal(
-                "alice",
-                ProposalType::EmergencyWithdrawal,
-                "BTC",
-                50_000_000,
-                "security_team",
-                "Urgent: patch vulnerability in bridge",
-                200,
-                86400, // 1 day TTL
-            )
-            .unwrap();
+        let s = create_treasury_with_funds();
+        let security_team = Address::generate(&s.env);
+
+        // Emergency withdrawals are capped at 10% of the balance...
+        s.advance_to(200);
+        assert_eq!(
+            s.client.try_create_treasury_proposal(
+                &s.signer(0),
+                &TreasuryProposalType::EmergencyWithdrawal,
+                &s.btc,
+                &10_000_001,
+                &security_team,
+                &String::from_str(&s.env, "Too much"),
+                &86400,
+                &Payout::Immediate,
+            ),
+            Err(Ok(ContractError::EmergencyWithdrawalTooLarge))
+        );
+
+        // ...and only need 2 approvals
+        let proposal_id = s.propose(
+            TreasuryProposalType::EmergencyWithdrawal,
+            &s.btc,
+            10_000_000,
+            &security_team,
+            "Urgent: patch vulnerability in bridge",
+            200,
+            86400, // 1 day TTL
+        );
 
-        // Only need 2 approvals for emergency
-        treasury.approve_proposal(&proposal_id, "bob", 300).unwrap();
-        let proposal = treasury.get_proposal(&proposal_id).unwrap();
+        s.advance_to(300);
+        s.client
+            .approve_treasury_proposal(&proposal_id, &s.signer(1));
+        let proposal = s.client.get_treasury_proposal(&proposal_id).unwrap();
         assert_eq!(proposal.status, TreasuryOperationStatus::Approved);
         assert_eq!(proposal.required_approvals, 2);
 
         // Execute
-        treasury.execute_proposal(&proposal_id, 400).unwrap();
-        assert_eq!(treasury.balance_of("BTC"), 50_000_000);
+        s.advance_to(400);
+        s.client.execute_treasury_proposal(&proposal_id);
+        assert_eq!(s.client.get_treasury_balance(&s.btc), 90_000_000);
     }
 
     #[test]
     fn test_budget_with_spending_limits() {
-        let mut treasury = create_treasury_with_funds();
+        let s = create_treasury_with_funds();
+        let grantee = Address::generate(&s.env);
 
         // Set monthly spending limit: 2M XLM per 30 days
-        treasury.set_spending_limit("XLM", 2_000_000, 2_592_000, 100);
+        s.client
+            .set_treasury_spending_limit(&s.admin, &s.xlm, &2_000_000, &2_592_000);
 
         // First withdrawal: 1M XLM (within limit)
-        let id1 = treasury
-            .create_proposal(
-                "alice",
-                ProposalType::Withdrawal,
-                "XLM",
-                1_000_000,
-                "grant_1",
-                "First grant",
-                200,
-                86400 * 7,
-            )
-            .unwrap();
-        treasury.approve_proposal(&id1, "bob", 200).unwrap();
-        treasury.approve_proposal(&id1, "carol", 200).unwrap();
-        treasury.execute_proposal(&id1, 300).unwrap();
-        assert_eq!(treasury.balance_of("XLM"), 9_000_000);
+        let id1 = s.propose(
+            TreasuryProposalType::Withdrawal,
+            &s.xlm,
+            1_000_000,
+            &grantee,
+            "First grant",
+            200,
+            86400 * 7,
+        );
+        s.approve_and_execute(id1, 300);
+        assert_eq!(s.client.get_treasury_balance(&s.xlm), 9_000_000);
 
         // Second withdrawal: 1M XLM (within remaining limit)
-        let id2 = treasury
-            .create_proposal(
-                "alice",
-                ProposalType::Withdrawal,
-                "XLM",
-                1_000_000,
-                "grant_2",
-                "Second grant",
-                300,
-                86400 * 7,
-            )
-            .unwrap();
-        treasury.approve_proposal(&id2, "bob", 300).unwrap();
-        treasury.approve_proposal(&id2, "carol", 300).unwrap();
-        treasury.execute_proposal(&id2, 400).unwrap();
-        assert_eq!(treasury.balance_of("XLM"), 8_000_000);
+        let id2 = s.propose(
+            TreasuryProposalType::Withdrawal,
+            &s.xlm,
+            1_000_000,
+            &grantee,
+            "Second grant",
+            300,
+            86400 * 7,
+        );
+        s.approve_and_execute(id2, 400);
+        assert_eq!(s.client.get_treasury_balance(&s.xlm), 8_000_000);
 
         // Third withdrawal: 500K XLM (over limit, should fail)
-        let id3 = treasury
-            .create_proposal(
-                "alice",
-                ProposalType::Withdrawal,
-                "XLM",
-                500_000,
-                "grant_3",
-                "Third grant",
-                400,
-                86400 * 7,
-            )
-            .unwrap();
-        treasury.approve_proposal(&id3, "bob", 400).unwrap();
-        treasury.approve_proposal(&id3, "carol", 400).unwrap();
-        let result = treasury.execute_proposal(&id3, 500);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("spending limit"));
+        let id3 = s.propose(
+            TreasuryProposalType::Withdrawal,
+            &s.xlm,
+            500_000,
+            &grantee,
+            "Third grant",
+            400,
+            86400 * 7,
+        );
+        s.client.approve_treasury_proposal(&id3, &s.signer(1));
+        s.client.approve_treasury_proposal(&id3, &s.signer(2));
+        s.advance_to(500);
+        assert_eq!(
+            s.client.try_execute_treasury_proposal(&id3),
+            Err(Ok(ContractError::SpendingLimitExceeded))
+        );
     }
 
     #[test]
     fn test_multisig_signer_rotation() {
-        let mut treasury = create_treasury_with_funds();
-        assert_eq!(treasury.signer_count(), 5);
+        let s = create_treasury_with_funds();
+        let frank = Address::generate(&s.env);
+        let eve = s.signer(4);
 
         // Add new signer
-        treasury.add_signer("frank".to_string()).unwrap();
-        assert_eq!(treasury.signer_count(), 6);
-        assert!(treasury.is_signer("frank"));
+        s.client.add_treasury_signer(&s.admin, &frank);
+        assert_eq!(s.client.get_treasury_config().unwrap().signers.len(), 6);
 
         // Remove old signer
-        treasury.remove_signer("eve").unwrap();
-        assert_eq!(treasury.signer_count(), 5);
-        assert!(!treasury.is_signer("eve"));
-        assert!(treasury.is_signer("frank"));
-
-        // New signer can participate
-        treasury.deposit("XLM", 100, "frank", 600).unwrap();
-        assert_eq!(treasury.balance_of("XLM"), 10_000_100);
+        s.client.remove_treasury_signer(&s.admin, &eve);
+        let signers = s.client.get_treasury_config().unwrap().signers;
+        assert_eq!(signers.len(), 5);
+        assert!(!signers.contains(&eve));
+
+        // New signer can participate, the removed one cannot
+        let id = s.propose(
+            TreasuryProposalType::Withdrawal,
+            &s.usdc,
+            1_000,
+            &frank,
+            "Rotation check",
+            600,
+            86400,
+        );
+        assert_eq!(s.client.approve_treasury_proposal(&id, &frank), 2);
+        assert_eq!(
+            s.client.try_approve_treasury_proposal(&id, &eve),
+            Err(Ok(ContractError::NotTreasurySigner))
+        );
     }
 
     #[test]
     fn test_audit_trail_completeness() {
-        let mut treasury = create_treasury_with_funds();
+        let s = create_treasury_with_funds();
+        let head = s.client.get_treasury_audit_head().unwrap();
+        // Configuration + 3 initial deposits
+        assert_eq!(head.seq, 4);
 
         // Perform various operations
-        treasury.deposit("ETH", 500, "donor2", 200).unwrap();
-
-        let id = treasury
-            .create_proposal(
-                "alice",
-                ProposalType::Withdrawal,
-                "XLM",
-                100_000,
-                "recip",
-                "Test",
-                300,
-                86400 * 7,
-            )
-            .unwrap();
+        let eth = s.new_token();
+        s.deposit(&eth, 500, 200);
+        let recipient = Address::generate(&s.env);
+        let id = s.propose(
+            TreasuryProposalType::Withdrawal,
+            &s.xlm,
+            100_000,
+            &recipient,
+            "Test",
+            300,
+            86400 * 7,
+        );
+        s.approve_and_execute(id, 400);
+        s.client.update_treasury_threshold(&s.admin, &4);
 
-        treasury.approve_proposal(&id, "bob", 400).unwrap();
-        treasury.approve_proposal(&id, "carol", 400).unwrap();
-        treasury.execute_proposal(&id, 500).unwrap();
+        // 1 ETH deposit + create + 2 approves + execute + threshold update = 6
+        let latest = s.client.get_treasury_audit_head().unwrap();
+        assert_eq!(latest.seq, head.seq + 6);
+        assert_ne!(latest.hash, head.hash);
+    }
 
-        treasury.update_threshold(4, 600).unwrap();
+    #[test]
+    fn test_swap_fees_paid_into_treasury() {
+        let s = create_treasury_with_funds();
+        let xlm = symbol_short!("XLM");
+        let usdc = symbol_short!("USDC");
+        let pool_id = s
+            .client
+            .register_pool(&s.admin, &xlm, &usdc, &1_000_000, &1_000_000, &30);
+        let trader = s.verified_trader();
+        let pool_fees = || {
+            let pool = s.client.get_pool(&pool_id).unwrap();
+            (pool.accumulated_fees_a, pool.accumulated_fees_b)
+        };
 
-        // Verify complete audit trail
-        assert!(treasury.verify_audit_trail());
-        // 3 initial deposits + 1 ETH deposit + create + 2 approves + execute + threshold update = 9
-        assert_eq!(treasury.audit_log_length(), 9);
+        // Without a mapped token the fee accrues in the pool as before.
+        s.client.pool_swap(&pool_id, &usdc, &100_000, &0, &trader);
+        assert_eq!(pool_fees(), (300, 0));
+        assert_eq!(s.client.get_treasury_balance(&s.usdc), 5_000_000);
+
+        // With one, a 30 bps swap of 100_000 XLM charges the trader 300 XLM
+        // in tokens, and an unfunded trader cannot swap.
+        s.client.set_treasury_fee_token(&s.admin, &xlm, &s.xlm);
+        assert!(s
+            .client
+            .try_pool_swap(&pool_id, &xlm, &100_000, &0, &trader)
+            .is_err());
+        StellarAssetClient::new(&s.env, &s.xlm).mint(&trader, &300);
+        s.client.pool_swap(&pool_id, &xlm, &100_000, &0, &trader);
+
+        assert_eq!(TokenClient::new(&s.env, &s.xlm).balance(&trader), 0);
+        assert_eq!(pool_fees(), (300, 0));
+        // The insurance fund takes its default 10% share.
+        assert_eq!(s.client.get_treasury_balance(&s.xlm), 10_000_270);
+        assert_eq!(s.client.get_insurance_fund(&xlm).fees_received, 30);
     }
 }
 
 #[cfg(test)]
 mod upgrade_integration_tests {
     use super::super::upgrade::*;
+    use crate::errors::SwapTradeError;
+    use crate::storage::ADMIN_KEY;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::{Address, Env, String, Vec};
+
+    struct Setup {
+        env: Env,
+        contract_id: Address,
+        signers: Vec<Address>,
+    }
+
+    impl Setup {
+        fn signer(&self, i: u32) -> Address {
+            self.signers.get(i).unwrap()
+        }
+
+        fn at<T>(&self, now: u64, f: impl FnOnce(&Env) -> T) -> T {
+            self.env.ledger().with_mut(|l| l.timestamp = now);
+            self.env.as_contract(&self.contract_id, || f(&self.env))
+        }
+
+        fn propose(
+            &self,
+            to: Version,
+            upgrade_type: UpgradeType,
+            description: &str,
+            now: u64,
+        ) -> Result<u64, SwapTradeError> {
+            let hash =
+                install_test_wasm(&self.env, (to.major * 100 + to.minor * 10 + to.patch) as u8);
+            self.at(now, |env| {
+                UpgradeManager::propose_upgrade(
+                    env,
+                    self.signer(0),
+                    hash,
+                    to,
+                    upgrade_type,
+                    String::from_str(env, description),
+                )
+            })
+        }
+
+        fn approve(&self, id: u64, signers: &[u32], now: u64) {
+            for &i in signers {
+                self.at(now, |env| {
+                    UpgradeManager::approve_upgrade(env, id, self.signer(i))
+                })
+                .unwrap();
+            }
+        }
+
+        fn execute(&self, id: u64, now: u64) -> Result<Version, SwapTradeError> {
+            self.at(now, |env| UpgradeManager::execute_upgrade(env, id))
+        }
+
+        fn current_version(&self) -> Version {
+            self.at(
+                self.env.ledger().timestamp(),
+                UpgradeManager::current_version,
+            )
+            .unwrap()
+        }
 
-    fn setup_upgrade_manager() -> UpgradeManager {
-        let signers = vec![
-            "alice".to_string(),
-            "bob".to_string(),
-            "carol".to_string(),
-            "dave".to_string(),
-            "eve".to_string(),
-        ];
-        UpgradeManager::new(Version::new(1, 0, 0), signers, UpgradeConfig::default())
+        fn history_len(&self) -> u32 {
+            self.at(self.env.ledger().timestamp(), UpgradeManager::history)
+                .len()
+        }
+    }
+
+    fn setup_upgrade_manager() -> Setup {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register(crate::CounterContract, ());
+        let admin = Address::generate(&env);
+        let mut signers = Vec::new(&env);
+        for _ in 0..5 {
+            signers.push_back(Address::generate(&env));
+        }
+        let genesis = install_test_wasm(&env, 0);
+        env.as_contract(&contract_id, || {
+            env.storage().persistent().set(&ADMIN_KEY, &admin);
+            UpgradeManager::configure(
+                &env,
+                admin.clone(),
+                UpgradeConfig::new(signers.clone()),
+                Version::new(1, 0, 0),
+                genesis,
+            )
+            .unwrap();
+        });
+        Setup {
+            env,
+            contract_id,
+            signers,
+        }
     }
 
     #[test]
     fn test_full_upgrade_lifecycle() {
-        let mut mgr = setup_upgrade_manager();
-        assert_eq!(mgr.current_version(), &Version::new(1, 0, 0));
+        let s = setup_upgrade_manager();
+        assert_eq!(s.current_version(), Version::new(1, 0, 0));
 
         // Propose minor upgrade
-        let id = mgr
-            .propose_upgrade(
-                "alice",
+        let id = s
+            .propose(
                 Version::new(1, 1, 0),
                 UpgradeType::Minor,
                 "Add limit order support",
-                None,
-                vec![
-                    "Deploy new contract".to_string(),
-                    "Migrate users".to_string(),
-                ],
                 100,
             )
             .unwrap();
 
         // Collect approvals (need 3 for minor)
-        mgr.approve_upgrade(id, "bob", 200).unwrap();
-        mgr.approve_upgrade(id, "carol", 200).unwrap();
+        s.approve(id, &[1, 2], 200);
 
         // Should now be in Timelock state
-        let proposal = mgr.get_proposal(id).unwrap();
+        let proposal = s
+            .at(200, |env| UpgradeManager::get_proposal(env, id))
+            .unwrap();
         assert_eq!(proposal.status, UpgradeStatus::Timelock);
 
         // Cannot execute before timelock
-        assert!(mgr.execute_upgrade(id, 300).is_err());
+        assert_eq!(s.execute(id, 300), Err(SwapTradeError::TimelockNotElapsed));
 
         // Execute after timelock
         let timelock_end = 200 + 172800;
-        mgr.execute_upgrade(id, timelock_end).unwrap();
+        s.execute(id, timelock_end).unwrap();
 
-        assert_eq!(mgr.current_version(), &Version::new(1, 1, 0));
-        assert_eq!(mgr.history().len(), 1);
+        assert_eq!(s.current_version(), Version::new(1, 1, 0));
+        assert_eq!(s.history_len(), 1);
     }
 
     #[test]
     fn test_emergency_hotfix_workflow() {
-        let mut mgr = setup_upgrade_manager();
+        let s = setup_upgrade_manager();
 
         // Propose hotfix (1 hour timelock)
-        let id = mgr
-            .propose_upgrade(
-                "alice",
+        let id = s
+            .propose(
                 Version::new(1, 0, 1),
                 UpgradeType::Hotfix,
                 "Fix critical rounding error",
-                Some([0xAB; 32]),
-                vec![],
                 100,
             )
             .unwrap();
 
         // Approve (need 3 for hotfix = minor threshold)
-        mgr.approve_upgrade(id, "bob", 100).unwrap();
-        mgr.approve_upgrade(id, "carol", 100).unwrap();
+        s.approve(id, &[1, 2], 100);
 
         // Execute after 1 hour
-        mgr.execute_upgrade(id, 100 + 3600).unwrap();
-        assert_eq!(mgr.current_version(), &Version::new(1, 0, 1));
+        s.execute(id, 100 + 3600).unwrap();
+        assert_eq!(s.current_version(), Version::new(1, 0, 1));
     }
 
     #[test]
     fn test_major_upgrade_with_rollback() {
-        let mut mgr = setup_upgrade_manager();
+        let s = setup_upgrade_manager();
 
         // Major upgrade: 1.0.0 -> 2.0.0
-        let id = mgr
-            .propose_upgrade(
-                "alice",
+        let id = s
+            .propose(
                 Version::new(2, 0, 0),
                 UpgradeType::Major,
                 "New AMM engine",
-                None,
-                vec![
-                    "Migrate all pools".to_string(),
-                    "Update oracles".to_string(),
-                ],
                 100,
             )
             .unwrap();
 
-        // Need 5 approvals for major
-        mgr.approve_upgrade(id, "bob", 200).unwrap();
-        mgr.approve_upgrade(id, "carol", 200).unwrap();
-        mgr.approve_upgrade(id, "dave", 200).unwrap();
-        mgr.approve_upgrade(id, "eve", 200).unwrap();
-        mgr.execute_upgrade(id, 200 + 172800).unwrap();
-
-        assert_eq!(mgr.current_version(), &Version::new(2, 0, 0));
-
-        // Critical bug found - rollback
-        assert_eq!(mgr.rollback_version(), Some(&Version::new(1, 0, 0)));
-        mgr.rollback("Critical: AMM exploit found", 200 + 172900)
+        // Need 5 approvals and a week-long timelock for major
+        s.approve(id, &[1, 2, 3, 4], 200);
+        assert_eq!(
+            s.execute(id, 200 + 172800),
+            Err(SwapTradeError::TimelockNotElapsed)
+        );
+        s.execute(id, 200 + 604800).unwrap();
+        assert_eq!(s.current_version(), Version::new(2, 0, 0));
+
+        // Critical bug found - governance approves a rollback
+        let now = 200 + 604900;
+        let target = s.at(now, UpgradeManager::get_previous).unwrap();
+        assert_eq!(target.version, Version::new(1, 0, 0));
+        let rollback = s
+            .at(now, |env| {
+                UpgradeManager::propose_rollback(
+                    env,
+                    s.signer(0),
+                    String::from_str(env, "Critical: AMM exploit found"),
+                )
+            })
             .unwrap();
-        assert_eq!(mgr.current_version(), &Version::new(1, 0, 0));
-        assert_eq!(mgr.history().len(), 2);
+        s.approve(rollback, &[1, 2], now);
+        s.execute(rollback, now + 3600).unwrap();
+
+        assert_eq!(s.current_version(), Version::new(1, 0, 0));
+        assert_eq!(s.history_len(), 2);
+        let current = s.at(now + 3600, UpgradeManager::get_current).unwrap();
+        assert_eq!(current.wasm_hash, target.wasm_hash);
     }
 
     #[test]
     fn test_upgrade_chain() {
-        let mut mgr = setup_upgrade_manager();
+        let s = setup_upgrade_manager();
 
         // 1.0.0 -> 1.0.1 (patch)
-        let id1 = mgr
-            .propose_upgrade(
-                "alice",
-                Version::new(1, 0, 1),
-                UpgradeType::Patch,
-                "Fix",
-                None,
-                vec![],
-                100,
-            )
+        let id1 = s
+            .propose(Version::new(1, 0, 1), UpgradeType::Patch, "Fix", 100)
             .unwrap();
-        mgr.approve_upgrade(id1, "bob", 200).unwrap();
-        mgr.execute_upgrade(id1, 200 + 172800).unwrap();
-        assert_eq!(mgr.current_version(), &Version::new(1, 0, 1));
+        s.approve(id1, &[1], 200);
+        s.execute(id1, 200 + 172800).unwrap();
+        assert_eq!(s.current_version(), Version::new(1, 0, 1));
 
         // 1.0.1 -> 1.1.0 (minor)
-        let id2 = mgr
-            .propose_upgrade(
-                "alice",
+        let id2 = s
+            .propose(
                 Version::new(1, 1, 0),
                 UpgradeType::Minor,
                 "Feature",
-                None,
-                vec![],
                 400 + 172800,
             )
             .unwrap();
-        mgr.approve_upgrade(id2, "bob", 500 + 172800).unwrap();
-        mgr.approve_upgrade(id2, "carol", 500 + 172800).unwrap();
-        mgr.execute_upgrade(id2, 500 + 172800 + 172800).unwrap();
-        assert_eq!(mgr.current_version(), &Version::new(1, 1, 0));
+        s.approve(id2, &[1, 2], 500 + 172800);
+        s.execute(id2, 500 + 172800 + 172800).unwrap();
+        assert_eq!(s.current_version(), Version::new(1, 1, 0));
 
         // 1.1.0 -> 2.0.0 (major)
-        let id3 = mgr
-            .propose_upgrade(
-                "alice",
+        let id3 = s
+            .propose(
                 Version::new(2, 0, 0),
                 UpgradeType::Major,
                 "Major",
-                None,
-                vec![],
                 500 + 172800 + 172800,
             )
             .unwrap();
-        for signer in ["bob", "carol", "dave", "eve"] {
-            mgr.approve_upgrade(id3, signer, 600 + 172800 + 172800)
-                .unwrap();
-        }
-        mgr.execute_upgrade(id3, 600 + 2 * 172800 + 172800).unwrap();
-        assert_eq!(mgr.current_version(), &Version::new(2, 0, 0));
-        assert_eq!(mgr.history().len(), 3);
+        s.approve(id3, &[1, 2, 3, 4], 600 + 172800 + 172800);
+        s.execute(id3, 600 + 2 * 172800 + 604800).unwrap();
+        assert_eq!(s.current_version(), Version::new(2, 0, 0));
+        assert_eq!(s.history_len(), 3);
+
+        // Each hop leaves the previous code as the rollback target.
+        let previous = s
+            .at(600 + 2 * 172800 + 604800, UpgradeManager::get_previous)
+            .unwrap();
+        assert_eq!(previous.version, Version::new(1, 1, 0));
     }
 
     #[test]
     fn test_upgrade_rejected_then_new_proposal() {
-        let mut mgr = setup_upgrade_manager();
+        let s = setup_upgrade_manager();
 
-        let id1 = mgr
-            .propose_upgrade(
-                "alice",
-                Version::new(2, 0, 0),
-                UpgradeType::Major,
-                "Old plan",
-                None,
-                vec![],
-                100,
-            )
+        let id1 = s
+            .propose(Version::new(2, 0, 0), UpgradeType::Major, "Old plan", 100)
             .unwrap();
-        mgr.cancel_upgrade(id1, "bob", 200).unwrap();
+        s.at(200, |env| {
+            UpgradeManager::cancel_upgrade(env, id1, s.signer(1))
+        })
+        .unwrap();
 
         // New proposal with better plan
-        let id2 = mgr
-            .propose_upgrade(
-                "alice",
+        let id2 = s
+            .propose(
                 Version::new(2, 0, 0),
                 UpgradeType::Major,
                 "New plan v2",
-                None,
-                vec![],
                 300,
             )
             .unwrap();
 
         assert_ne!(id1, id2);
-        let proposal = mgr.get_proposal(id2).unwrap();
+        let proposal = s
+            .at(300, |env| UpgradeManager::get_proposal(env, id2))
+            .unwrap();
         assert_eq!(proposal.status, UpgradeStatus::Proposed);
     }
 }
 
 #[cfg(test)]
 mod emergency_integration_tests {
-    use super::super::emergency::*;
-
-    fn setup_emergency_controller() -> EmergencyController {
-        let config = EmergencyConfig {
-            signers: vec![
-                "alice".to_string(),
-                "bob".to_string(),
-                "carol".to_string(),
-                "dave".to_string(),
-                "eve".to_string(),
-            ],
-            standard_threshold: 2,
-            critical_threshold: 3,
-            default_expiry_secs: 3600,
-            max_expiry_secs: 86400,
-            escalation_cooldown_secs: 300,
-        };
-        EmergencyController::new(config)
+    use crate::emergency::*;
+    use crate::errors::ContractError;
+    use crate::storage::ADMIN_KEY;
+    use crate::{CounterContract, CounterContractClient};
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::{symbol_short, Address, Env, String, Vec};
+
+    struct Setup {
+        env: Env,
+        client: CounterContractClient<'static>,
+        signers: Vec<Address>,
+    }
+
+    impl Setup {
+        fn signer(&self, i: u32) -> Address {
+            self.signers.get(i).unwrap()
+        }
+
+        fn reason(&self, text: &str) -> String {
+            String::from_str(&self.env, text)
+        }
+
+        fn advance_to(&self, now: u64) {
+            self.env.ledger().with_mut(|l| l.timestamp = now);
+        }
+    }
+
+    fn setup_emergency_controller() -> Setup {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register(CounterContract, ());
+        let client = CounterContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let mut signers = Vec::new(&env);
+        for _ in 0..5 {
+            signers.push_back(Address::generate(&env));
+        }
+        env.as_contract(&contract_id, || {
+            env.storage().persistent().set(&ADMIN_KEY, &admin);
+        });
+        client.configure_emergency(&admin, &EmergencyConfig::new(signers.clone()));
+
+        Setup {
+            env,
+            client,
+            signers,
+        }
     }
 
     #[test]
     fn test_graduated_emergency_response() {
-        let mut ctrl = setup_emergency_controller();
-
-        // Step 1: Low-level warning (applies immediately for Low)
-        ctrl.set_emergency_level(
-            "alice",
-            EmergencyLevel::Low,
-            "Suspicious pattern detected",
-            100,
-            None,
-        )
-        .unwrap();
-        assert_eq!(ctrl.current_level(), EmergencyLevel::Low);
-        assert!(!ctrl.is_operation_allowed(&OperationType::Swap));
-        assert!(ctrl.is_operation_allowed(&OperationType::LpWithdraw));
+        let s = setup_emergency_controller();
+        let trader = Address::generate(&s.env);
+        let xlm = symbol_short!("XLM");
+        let usdc = symbol_short!("USDC");
+
+        // Step 1: Low-level warning (applies once two signers agree)
+        s.advance_to(100);
+        let id = s.client.set_emergency_level(
+            &s.signer(0),
+            &EmergencyLevel::Low,
+            &s.reason("Suspicious pattern detected"),
+            &None,
+        );
+        s.client.approve_emergency(&id, &s.signer(1));
+        assert_eq!(s.client.get_emergency_level(), EmergencyLevel::Low);
+        assert_eq!(
+            s.client.try_swap(&xlm, &usdc, &100, &trader),
+            Err(Ok(ContractError::EmergencyRestricted))
+        );
+        // LP withdrawals still pass the emergency gate
+        assert_ne!(
+            s.client.try_remove_liquidity(&100, &trader),
+            Err(Ok(ContractError::EmergencyRestricted))
+        );
 
         // Step 2: Escalate after cooldown
-        ctrl.set_emergency_level(
-            "bob",
-            EmergencyLevel::Medium,
-            "Confirmed exploit attempt",
-            601,
-            None,
-        )
-        .unwrap();
-        assert_eq!(ctrl.current_level(), EmergencyLevel::Medium);
+        s.advance_to(601);
+        let id = s.client.set_emergency_level(
+            &s.signer(1),
+            &EmergencyLevel::Medium,
+            &s.reason("Confirmed exploit attempt"),
+            &None,
+        );
+        s.client.approve_emergency(&id, &s.signer(2));
+        assert_eq!(s.client.get_emergency_level(), EmergencyLevel::Medium);
 
         // Step 3: Full emergency (Critical needs multisig approval)
-        let id = ctrl
-            .set_emergency_level(
-                "carol",
-                EmergencyLevel::Critical,
-                "Active attack",
-                1200,
-                None,
-            )
-            .unwrap();
+        s.advance_to(1200);
+        let id = s.client.set_emergency_level(
+            &s.signer(2),
+            &EmergencyLevel::Critical,
+            &s.reason("Active attack"),
+            &None,
+        );
         // Still Medium - Critical needs 3 approvals
-        assert_eq!(ctrl.current_level(), EmergencyLevel::Medium);
+        assert_eq!(s.client.get_emergency_level(), EmergencyLevel::Medium);
 
         // Approve to reach threshold
-        ctrl.approve_emergency(id, "dave", 1200).unwrap();
-        ctrl.approve_emergency(id, "eve", 1200).unwrap();
-        assert_eq!(ctrl.current_level(), EmergencyLevel::Critical);
+        s.client.approve_emergency(&id, &s.signer(3));
+        assert_eq!(s.client.approve_emergency(&id, &s.signer(4)), 3);
+        assert_eq!(s.client.get_emergency_level(), EmergencyLevel::Critical);
+        assert_eq!(
+            s.client.try_remove_liquidity(&100, &trader),
+            Err(Ok(ContractError::EmergencyRestricted))
+        );
 
-        // Emergency resume
-        ctrl.emergency_resume("alice", "Attack mitigated", 1800)
-            .unwrap();
-        assert_eq!(ctrl.current_level(), EmergencyLevel::None);
-        assert!(ctrl.is_operation_allowed(&OperationType::Swap));
+        // Emergency resume needs the same critical threshold
+        s.advance_to(1800);
+        s.client
+            .emergency_resume(&s.signer(0), &s.reason("Attack mitigated"));
+        s.client
+            .emergency_resume(&s.signer(1), &s.reason("Attack mitigated"));
+        assert_eq!(s.client.get_emergency_level(), EmergencyLevel::Critical);
+        s.client
+            .emergency_resume(&s.signer(2), &s.reason("Attack mitigated"));
+        assert_eq!(s.client.get_emergency_level(), EmergencyLevel::None);
+        assert_ne!(
+            s.client.try_swap(&xlm, &usdc, &100, &trader),
+            Err(Ok(ContractError::EmergencyRestricted))
+        );
     }
 
     #[test]
     fn test_emergency_with_auto_expiry() {
-        let mut ctrl = setup_emergency_controller();
-
-        // Use Medium level (applies immediately with single signer)
-        ctrl.set_emergency_level(
-            "alice",
-            EmergencyLevel::Medium,
-            "Network anomaly",
-            100,
-            Some(600), // 10 minute auto-expiry
-        )
-        .unwrap();
-
-        assert_eq!(ctrl.current_level(), EmergencyLevel::Medium);
+        let s = setup_emergency_controller();
+
+        // Medium applies with the standard threshold of two signers
+        s.advance_to(100);
+        let id = s.client.set_emergency_level(
+            &s.signer(0),
+            &EmergencyLevel::Medium,
+            &s.reason("Network anomaly"),
+            &Some(600), // 10 minute auto-expiry
+        );
+        s.client.approve_emergency(&id, &s.signer(1));
+        assert_eq!(s.client.get_emergency_level(), EmergencyLevel::Medium);
 
         // Still active
-        ctrl.tick(500);
-        assert_eq!(ctrl.current_level(), EmergencyLevel::Medium);
+        s.advance_to(500);
+        s.client.tick_emergency();
+        assert_eq!(s.client.get_emergency_level(), EmergencyLevel::Medium);
+
+        // Auto-expired, even before anyone ticks
+        s.advance_to(701);
+        assert_eq!(s.client.get_emergency_level(), EmergencyLevel::None);
+        s.client.tick_emergency();
+        assert_eq!(
+            s.client.get_emergency_action(&id).unwrap().status,
+            EmergencyActionStatus::Expired
+        );
+    }
 
-        // Auto-expired
-        ctrl.tick(701);
-        assert_eq!(ctrl.current_level(), EmergencyLevel::None);
+    #[test]
+    fn test_resume_approvals_reset_when_level_changes() {
+        let s = setup_emergency_controller();
+
+        // Two of the three resume approvals for a High emergency...
+        s.advance_to(100);
+        let high = s.client.set_emergency_level(
+            &s.signer(0),
+            &EmergencyLevel::High,
+            &s.reason("Oracle manipulation"),
+            &Some(600),
+        );
+        s.client.approve_emergency(&high, &s.signer(1));
+        s.client.approve_emergency(&high, &s.signer(2));
+        assert_eq!(
+            s.client
+                .emergency_resume(&s.signer(0), &s.reason("Mitigated")),
+            1
+        );
+        assert_eq!(
+            s.client
+                .emergency_resume(&s.signer(1), &s.reason("Mitigated")),
+            2
+        );
+
+        // ...are void once it expires and a new one takes its place
+        s.advance_to(701);
+        s.client.tick_emergency();
+        let critical = s.client.set_emergency_level(
+            &s.signer(2),
+            &EmergencyLevel::Critical,
+            &s.reason("Second attack"),
+            &None,
+        );
+        s.client.approve_emergency(&critical, &s.signer(3));
+        s.client.approve_emergency(&critical, &s.signer(4));
+        assert_eq!(
+            s.client
+                .emergency_resume(&s.signer(2), &s.reason("Mitigated")),
+            1
+        );
+        assert_eq!(s.client.get_emergency_level(), EmergencyLevel::Critical);
+
+        // Lifting an action also starts the count over
+        s.client.lift_emergency(&critical, &s.signer(0));
+        s.client.lift_emergency(&critical, &s.signer(1));
+        s.client.lift_emergency(&critical, &s.signer(3));
+        assert_eq!(s.client.get_emergency_level(), EmergencyLevel::None);
+        s.advance_to(1100);
+        let high = s.client.set_emergency_level(
+            &s.signer(0),
+            &EmergencyLevel::High,
+            &s.reason("Third attack"),
+            &None,
+        );
+        s.client.approve_emergency(&high, &s.signer(1));
+        s.client.approve_emergency(&high, &s.signer(3));
+        assert_eq!(
+            s.client
+                .emergency_resume(&s.signer(2), &s.reason("Mitigated")),
+            1
+        );
     }
 
     #[test]
     fn test_whitelist_during_emergency() {
-        let mut ctrl = setup_emergency_controller();
-
-        // Use Medium level (applies immediately)
-        ctrl.set_emergency_level("alice", EmergencyLevel::Medium, "Attack", 100, None)
-            .unwrap();
+        let s = setup_emergency_controller();
+        let trusted_dex = Address::generate(&s.env);
+        let trader = Address::generate(&s.env);
+        let xlm = symbol_short!("XLM");
+        let usdc = symbol_short!("USDC");
+
+        // Medium applies with the standard threshold of two signers
+        s.advance_to(100);
+        let id = s.client.set_emergency_level(
+            &s.signer(0),
+            &EmergencyLevel::Medium,
+            &s.reason("Attack"),
+            &None,
+        );
+        s.client.approve_emergency(&id, &s.signer(2));
 
         // Trusted contracts can still operate
-        ctrl.add_to_whitelist("trusted_dex", 200).unwrap();
-        assert!(ctrl.is_whitelisted("trusted_dex"));
+        s.client.add_emergency_whitelist(&s.signer(1), &trusted_dex);
+        assert_ne!(
+            s.client.try_swap(&xlm, &usdc, &100, &trusted_dex),
+            Err(Ok(ContractError::EmergencyRestricted))
+        );
 
         // Regular operations blocked at Medium level
-        assert!(!ctrl.is_operation_allowed(&OperationType::Swap));
-        // LP withdrawals still allowed at Medium
-        assert!(ctrl.is_operation_allowed(&OperationType::LpWithdraw));
+        assert_eq!(
+            s.client.try_swap(&xlm, &usdc, &100, &trader),
+            Err(Ok(ContractError::EmergencyRestricted))
+        );
+
+        // Non-signers cannot manage the whitelist
+        assert_eq!(
+            s.client.try_add_emergency_whitelist(&trader, &trader),
+            Err(Ok(ContractError::NotEmergencyAdmin))
+        );
     }
 
     #[test]
-    fn test_emergency_audit_trail() {
-        let mut ctrl = setup_emergency_controller();
+    fn test_freeze_requires_standard_threshold() {
+        let s = setup_emergency_controller();
+        let contract_id = s.client.address.clone();
+        let user = Address::generate(&s.env);
+        let frozen = |env: &Env| {
+            env.as_contract(&contract_id, || EmergencyController::is_frozen(env, &user))
+        };
 
-        let id = ctrl
-            .set_emergency_level("alice", EmergencyLevel::Low, "Warning", 100, None)
-            .unwrap();
-        ctrl.lift_emergency(id, "bob", 200).unwrap();
+        // One signer cannot freeze an account alone
+        assert_eq!(s.client.set_user_frozen(&s.signer(0), &user, &true), 1);
+        assert!(!frozen(&s.env));
+        assert_eq!(
+            s.client.try_set_user_frozen(&s.signer(0), &user, &true),
+            Err(Ok(ContractError::AlreadyApproved))
+        );
+        assert_eq!(s.client.set_user_frozen(&s.signer(1), &user, &true), 2);
+        assert!(frozen(&s.env));
+
+        // Unfreezing needs the same threshold, counted afresh
+        assert_eq!(s.client.set_user_frozen(&s.signer(0), &user, &false), 1);
+        assert!(frozen(&s.env));
+        assert_eq!(s.client.set_user_frozen(&s.signer(2), &user, &false), 2);
+        assert!(!frozen(&s.env));
+    }
 
-        assert!(ctrl.verify_audit_trail());
-        assert_eq!(ctrl.audit_log_length(), 2);
+    #[test]
+    fn test_emergency_audit_trail() {
+        let s = setup_emergency_controller();
+        let contract_id = s.client.address.clone();
+        let head = |env: &Env| {
+            env.as_contract(&contract_id, || {
+                EmergencyController::get_audit_head(env).unwrap()
+            })
+        };
+        let configured = head(&s.env);
+
+        s.advance_to(100);
+        let id = s.client.set_emergency_level(
+            &s.signer(0),
+            &EmergencyLevel::Low,
+            &s.reason("Warning"),
+            &None,
+        );
+        let after_set = head(&s.env);
+        s.client.approve_emergency(&id, &s.signer(1));
+        s.advance_to(200);
+        s.client.lift_emergency(&id, &s.signer(1));
+        s.client.lift_emergency(&id, &s.signer(2));
+        let after_lift = head(&s.env);
+
+        // Set, approve, two lift approvals and the lift itself
+        assert_eq!(after_lift.seq, configured.seq + 5);
+        assert_ne!(after_set.hash, configured.hash);
+        assert_ne!(after_lift.hash, after_set.hash);
     }
 }
 
 #[cfg(test)]
 mod rewards_integration_tests {
-    use super::super::rewards::*;
-
-    fn setup_reward_manager() -> RewardManager {
-        let config = RewardConfig {
-            epoch_reward_pool: 2_000_000,
-            epoch_duration_secs: 604800,
-            claim_cooldown_secs: 60,
-            max_reward_per_participant: u128::MAX,
-            proposal_creation_bonus: 10,
-            vote_cast_reward: 5,
-            voting_power_multiplier_bps: 100,
-            min_qualification_score: 10,
-            diminishing_returns_bps: 100,
-        };
-        RewardManager::new(config, 0)
+    use crate::errors::ContractError;
+    use crate::governance::delegation::DelegationTopic;
+    use crate::governance::quadratic_voting::VotingMode;
+    use crate::governance::rewards::*;
+    use crate::governance_system::{self, ProposalAction, TIMELOCK_SECS};
+    use crate::storage::{ADMIN_KEY, GOV_COUNCIL_KEY};
+    use crate::vote_escrow::{MAX_LOCK_SECS, WEEK_SECS};
+    use crate::{CounterContract, CounterContractClient};
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::token::{StellarAssetClient, TokenClient};
+    use soroban_sdk::{symbol_short, Address, Env, Map, Vec};
+
+    const START: u64 = 100 * WEEK_SECS;
+
+    struct Setup {
+        env: Env,
+        contract_id: Address,
+        client: CounterContractClient<'static>,
+        council: Vec<Address>,
+        token: Address,
+        alice: Address,
+        bob: Address,
+        carol: Address,
     }
 
-    #[test]
-    fn test_active_governance_participant_earns_rewards() {
-        // Compare rewards for two participants using calculate_reward
-        // (not claim_rewards, which is pool-limited)
-        let config = RewardConfig {
-            epoch_reward_pool: 10_000,
-            epoch_duration_secs: 604800,
-            claim_cooldown_secs: 60,
-            max_reward_per_participant: u128::MAX,
-            proposal_creation_bonus: 10,
-            vote_cast_reward: 5,
-            voting_power_multiplier_bps: 100,
-            min_qualification_score: 10,
-            diminishing_returns_bps: 100,
-        };
-        let mut mgr = RewardManager::new(config, 0);
-
-        // Alice is very active: 10 votes, 3 proposals
-        for i in 0..10 {
-            mgr.record_vote_cast("alice", 200, 100 + i * 10).unwrap();
-        }
-        for i in 0..3 {
-            mgr.record_proposal_created("alice", 100 + i * 10).unwrap();
+    impl Setup {
+        fn config(&self, min_qualification_score: u32) -> RewardConfig {
+            RewardConfig {
+                token: self.token.clone(),
+                epoch_budget: 60_000,
+                epoch_duration_secs: WEEK_SECS,
+                max_reward_per_participant: 100_000,
+                min_qualification_score,
+                min_voting_power: 100,
+                diminishing_returns_bps: 10_000,
+            }
         }
 
-        // Bob is moderately active: 5 votes, 1 proposal
-        for i in 0..5 {
-            mgr.record_vote_cast("bob", 150, 200 + i * 10).unwrap();
+        /// Pass the reward settings through a governance vote.
+        fn configure_by_vote(&self, config: RewardConfig) {
+            let env = &self.env;
+            let id = env.as_contract(&self.contract_id, || {
+                governance_system::create_proposal(
+                    env,
+                    self.alice.clone(),
+                    ProposalAction::SetParticipationRewards(config),
+                )
+                .unwrap()
+            });
+            env.as_contract(&self.contract_id, || {
+                governance_system::cast_vote(env, self.alice.clone(), id, true).unwrap();
+                for member in self.council.iter() {
+                    governance_system::sign_proposal(env, member, id).unwrap();
+                }
+            });
+            env.ledger().with_mut(|l| l.timestamp += TIMELOCK_SECS);
+            self.client
+                .execute_governance_proposal(&Address::generate(env), &id);
         }
-        mgr.record_proposal_created("bob", 200).unwrap();
 
-        // Charlie is barely active: 1 vote
-        mgr.record_vote_cast("charlie", 50, 300).unwrap();
-
-        mgr.finalize_epoch(1, 700000).unwrap();
-
-        // Use calculate_reward to compare without pool constraints
-        let alice_reward = mgr.calculate_reward("alice", 1);
-        let bob_reward = mgr.calculate_reward("bob", 1);
-        let charlie_reward = mgr.calculate_reward("charlie", 1);
+        /// A conviction-mode proposal by Bob that Alice, Bob and Carol vote
+        /// on, after Carol delegates to Alice.
+        fn participate(&self) {
+            let env = &self.env;
+            self.client.delegate_votes(
+                &self.carol,
+                &self.alice,
+                &DelegationTopic::Economics,
+                &5_000,
+            );
+            let id = self.client.create_governance_action(
+                &self.bob,
+                &ProposalAction::PauseTrading,
+                &VotingMode::Conviction,
+            );
+            env.as_contract(&self.contract_id, || {
+                for voter in [&self.bob, &self.carol, &self.alice] {
+                    governance_system::cast_vote(env, voter.clone(), id, true).unwrap();
+                }
+            });
+        }
 
-        // Alice (score=90) should get more than Bob (score=75)
-        assert!(alice_reward > bob_reward);
-        // Bob should get more than Charlie (score=10)
-        assert!(bob_reward > charlie_reward);
-        assert!(alice_reward > 0);
+        fn wallet(&self, who: &Address) -> i128 {
+            TokenClient::new(&self.env, &self.token).balance(who)
+        }
     }
 
-    #[test]
-    fn test_rewards_across_epochs() {
-        let mut mgr = setup_reward_manager();
-
-        // Epoch 1
-        mgr.record_vote_cast("alice", 500, 100).unwrap();
-        mgr.record_proposal_created("alice", 100).unwrap();
-        mgr.finalize_epoch(1, 700000).unwrap();
-        let reward1 = mgr.claim_rewards("alice", 1, 700001).unwrap();
-        assert!(reward1 > 0);
+    fn locked_voter(env: &Env, client: &CounterContractClient) -> Address {
+        let voter = Address::generate(env);
+        client.mint(&symbol_short!("XLM"), &voter, &1_000);
+        client.create_ve_lock(&voter, &1_000, &(START + MAX_LOCK_SECS));
+        voter
+    }
 
-        // Epoch 2
-        mgr.record_vote_cast("alice", 500, 700100).unwrap();
-        mgr.record_proposal_created("alice", 700100).unwrap();
-        mgr.finalize_epoch(2, 1400000).unwrap();
-        let reward2 = mgr.claim_rewards("alice", 2, 1400001).unwrap();
-        assert!(reward2 > 0);
+    fn setup() -> Setup {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().with_mut(|l| l.timestamp = START);
+        let contract_id = env.register(CounterContract, ());
+        let client = CounterContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+
+        let mut council = Vec::new(&env);
+        for _ in 0..5 {
+            council.push_back(Address::generate(&env));
+        }
+        env.as_contract(&contract_id, || {
+            env.storage().persistent().set(&ADMIN_KEY, &admin);
+            env.storage().persistent().set(&GOV_COUNCIL_KEY, &council);
+        });
 
-        let stats = mgr.all_time_stats("alice").unwrap();
-        assert_eq!(stats.epochs_participated, 2);
-        assert!(stats.total_rewards_claimed > 0);
+        let token = env.register_stellar_asset_contract_v2(admin).address();
+        let funder = Address::generate(&env);
+        StellarAssetClient::new(&env, &token).mint(&funder, &1_000_000);
+        client.deposit_treasury(&funder, &token, &1_000_000);
+
+        Setup {
+            alice: locked_voter(&env, &client),
+            bob: locked_voter(&env, &client),
+            carol: locked_voter(&env, &client),
+            env,
+            contract_id,
+            client,
+            council,
+            token,
+        }
     }
 
     #[test]
-    fn test_quorum_participation_bonus() {
-        let mut mgr = setup_reward_manager();
+    fn test_governance_activity_earns_treasury_rewards() {
+        let s = setup();
+        s.configure_by_vote(s.config(10));
+        // Nothing accrued before rewards were configured.
+        assert_eq!(s.client.get_governance_participation(&s.alice, &1), None);
+
+        s.participate();
+        let bob = s.client.get_governance_participation(&s.bob, &1).unwrap();
+        assert_eq!((bob.proposals_created, bob.votes_cast), (1, 1));
+        let alice = s.client.get_governance_participation(&s.alice, &1).unwrap();
+        assert_eq!((alice.votes_cast, alice.delegations_received), (1, 1));
 
-        // User with full participation (votes + proposals)
-        mgr.record_vote_cast("full_user", 1000, 100).unwrap();
-        mgr.record_proposal_created("full_user", 100).unwrap();
-        mgr.record_delegation_received("full_user", 100).unwrap();
-
-        // User with partial participation (votes only)
-        mgr.record_vote_cast("partial_user", 1000, 200).unwrap();
-
-        let full_record = mgr.participation_record("full_user", 1).unwrap();
-        let partial_record = mgr.participation_record("partial_user", 1).unwrap();
-
-        assert!(full_record.score() > partial_record.score());
+        assert_eq!(
+            s.client.try_claim_governance_reward(&s.bob, &1),
+            Err(Ok(ContractError::EpochNotFinalized))
+        );
+        s.env.ledger().with_mut(|l| l.timestamp += WEEK_SECS);
+
+        // Scores: Bob 35, Alice 15, Carol 10 of a 60_000 budget.
+        assert_eq!(s.client.claim_governance_reward(&s.bob, &1), 35_000);
+        assert_eq!(s.client.claim_governance_reward(&s.alice, &1), 15_000);
+        assert_eq!(s.client.claim_governance_reward(&s.carol, &1), 10_000);
+        assert_eq!(s.wallet(&s.bob), 35_000);
+        assert_eq!(s.client.get_treasury_balance(&s.token), 940_000);
+
+        let epoch = s.client.get_governance_reward_epoch(&1).unwrap();
+        assert!(epoch.finalized);
+        assert_eq!(epoch.rewards_distributed, 60_000);
+        assert_eq!(s.client.sync_governance_reward_epoch(), 2);
     }
 
     #[test]
-    fn test_unqualified_participant() {
-        let mut mgr = setup_reward_manager();
-
-        // Minimal participation
-        mgr.record_vote_cast("minimal", 10, 100).unwrap();
-        mgr.finalize_epoch(1, 700000).unwrap();
+    fn test_unqualified_participant_earns_nothing() {
+        let s = setup();
+        s.configure_by_vote(s.config(20));
+        s.participate();
+        s.env.ledger().with_mut(|l| l.timestamp += WEEK_SECS);
 
-        let reward = mgr.calculate_reward("minimal", 1);
-        // With only 1 vote and 10 power, score is 10 (min qualification)
-        // Reward should be positive but much less than the pool
-        assert!(reward > 0);
-        assert!(reward < 2_000_000); // Well under pool
+        assert_eq!(s.client.get_pending_governance_reward(&s.carol, &1), 0);
+        assert_eq!(
+            s.client.try_claim_governance_reward(&s.carol, &1),
+            Err(Ok(ContractError::NoRewardsToClaim))
+        );
+        // The unqualified share stays in the treasury.
+        assert_eq!(s.client.claim_governance_reward(&s.bob, &1), 35_000);
+        assert_eq!(s.wallet(&s.carol), 0);
     }
 }
 
 #[cfg(test)]
 mod end_to_end_governance_tests {
-    use super::super::emergency::*;
     use super::super::rewards::*;
     use super::super::treasury::*;
     use super::super::upgrade::*;
+    use crate::emergency::*;
+    use crate::storage::ADMIN_KEY;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::token::StellarAssetClient;
+    use soroban_sdk::{Address, Env};
+
+    /// On-chain emergency controller, treasury and upgrade manager sharing
+    /// five generated signers, with one treasury token.
+    struct Protocol {
+        env: Env,
+        contract_id: Address,
+        signers: soroban_sdk::Vec<Address>,
+        xlm: Address,
+    }
+
+    impl Protocol {
+        fn new() -> Self {
+            let env = Env::default();
+            env.mock_all_auths();
+            let contract_id = env.register(crate::CounterContract, ());
+            let admin = Address::generate(&env);
+            let genesis = install_test_wasm(&env, 1);
+            let mut signers = soroban_sdk::Vec::new(&env);
+            for _ in 0..5 {
+                signers.push_back(Address::generate(&env));
+            }
+            env.as_contract(&contract_id, || {
+                env.storage().persistent().set(&ADMIN_KEY, &admin);
+                EmergencyController::configure(
+                    &env,
+                    admin.clone(),
+                    EmergencyConfig::new(signers.clone()),
+                )
+                .unwrap();
+            });
+            env.as_contract(&contract_id, || {
+                Treasury::configure(
+                    &env,
+                    admin.clone(),
+                    TreasuryConfig {
+                        signers: signers.clone(),
+                        threshold: 3,
+                        emergency_threshold: 2,
+                    },
+                )
+                .unwrap();
+            });
+            env.as_contract(&contract_id, || {
+                UpgradeManager::configure(
+                    &env,
+                    admin.clone(),
+                    UpgradeConfig::new(signers.clone()),
+                    Version::new(1, 0, 0),
+                    genesis,
+                )
+                .unwrap();
+            });
+            let xlm = env.register_stellar_asset_contract_v2(admin).address();
+            Protocol {
+                env,
+                contract_id,
+                signers,
+                xlm,
+            }
+        }
+
+        fn signer(&self, i: u32) -> Address {
+            self.signers.get(i).unwrap()
+        }
+
+        fn at<T>(&self, now: u64, f: impl FnOnce(&Env) -> T) -> T {
+            self.env.ledger().with_mut(|l| l.timestamp = now);
+            self.env.as_contract(&self.contract_id, || f(&self.env))
+        }
+
+        fn swap_allowed(&self, now: u64) -> bool {
+            let trader = Address::generate(&self.env);
+            self.at(now, |env| {
+                EmergencyController::is_operation_allowed(env, &OperationType::Swap, &trader)
+            })
+        }
+
+        fn audit_seq(&self) -> u64 {
+            self.at(self.env.ledger().timestamp(), |env| {
+                EmergencyController::get_audit_head(env).unwrap().seq
+            })
+        }
+
+        fn fund_treasury(&self, amount: i128, now: u64) {
+            let funder = Address::generate(&self.env);
+            StellarAssetClient::new(&self.env, &self.xlm).mint(&funder, &amount);
+            self.at(now, |env| {
+                Treasury::deposit(env, funder, self.xlm.clone(), amount)
+            })
+            .unwrap();
+        }
+
+        fn treasury_balance(&self) -> i128 {
+            self.at(self.env.ledger().timestamp(), |env| {
+                Treasury::balance_of(env, &self.xlm)
+            })
+        }
+
+        fn treasury_seq(&self) -> u64 {
+            self.at(self.env.ledger().timestamp(), |env| {
+                Treasury::get_audit_head(env).unwrap().seq
+            })
+        }
+    }
 
     /// Simulates a complete governance lifecycle:
     /// 1. Community proposes a protocol upgrade
@@ -700,142 +1275,171 @@ mod end_to_end_governance_tests {
     /// 4. Active voters earn rewards
     #[test]
     fn test_complete_governance_lifecycle() {
-        let signers = vec![
-            "alice".to_string(),
-            "bob".to_string(),
-            "carol".to_string(),
-            "dave".to_string(),
-            "eve".to_string(),
-        ];
-
         // Initialize all systems
-        let mut treasury = Treasury::new(signers.clone(), 3, 2);
-        let mut upgrade_mgr = UpgradeManager::new(
-            Version::new(1, 0, 0),
-            signers.clone(),
-            UpgradeConfig::default(),
-        );
-        let mut emergency = EmergencyController::new(EmergencyConfig {
-            signers: signers.clone(),
-            standard_threshold: 2,
-            critical_threshold: 3,
-            ..EmergencyConfig::default()
-        });
-        let mut rewards = RewardManager::new(RewardConfig::default(), 100);
-
-        // Setup: Fund treasury
-        treasury
-            .deposit("XLM", 10_000_000, "foundation", 100)
+        let protocol = Protocol::new();
+        let [alice, bob, carol] = [(); 3].map(|_| Address::generate(&protocol.env));
+
+        // Setup: Fund treasury and open the first reward epoch
+        protocol.fund_treasury(10_000_000, 100);
+        protocol
+            .at(100, |env| {
+                RewardManager::set_config(
+                    env,
+                    RewardConfig {
+                        token: protocol.xlm.clone(),
+                        epoch_budget: 100_000,
+                        epoch_duration_secs: 604800,
+                        max_reward_per_participant: 10_000,
+                        min_qualification_score: 10,
+                        min_voting_power: 0,
+                        diminishing_returns_bps: 10_000,
+                    },
+                )
+            })
             .unwrap();
 
         // Phase 1: Governance discussion and voting
-        rewards.record_proposal_created("alice", 100).unwrap();
-        rewards.record_vote_cast("alice", 1000, 200).unwrap();
-        rewards.record_vote_cast("bob", 500, 200).unwrap();
-        rewards.record_vote_cast("carol", 300, 200).unwrap();
-        rewards.record_delegation_received("alice", 200).unwrap();
+        protocol.at(200, |env| {
+            RewardManager::record_proposal_created(env, &alice);
+            RewardManager::record_vote_cast(env, &alice, 1000);
+            RewardManager::record_vote_cast(env, &bob, 500);
+            RewardManager::record_vote_cast(env, &carol, 300);
+            RewardManager::record_delegation_received(env, &alice, 500);
+        });
 
         // Phase 2: Treasury allocation for upgrade
-        let treasury_id = treasury
-            .create_proposal(
-                "alice",
-                ProposalType::BudgetAllocation,
-                "XLM",
-                2_000_000,
-                "upgrade_fund",
-                "Budget for v2.0.0 upgrade development",
-                300,
-                86400 * 7,
-            )
+        let upgrade_fund = Address::generate(&protocol.env);
+        let treasury_id = protocol
+            .at(300, |env| {
+                Treasury::create_proposal(
+                    env,
+                    protocol.signer(0),
+                    TreasuryProposalType::BudgetAllocation,
+                    protocol.xlm.clone(),
+                    2_000_000,
+                    upgrade_fund.clone(),
+                    soroban_sdk::String::from_str(env, "Budget for v2.0.0 upgrade development"),
+                    86400 * 7,
+                    Payout::Immediate,
+                )
+            })
             .unwrap();
-        treasury.approve_proposal(&treasury_id, "bob", 400).unwrap();
-        treasury
-            .approve_proposal(&treasury_id, "carol", 400)
+        protocol.at(400, |env| {
+            Treasury::approve_proposal(env, treasury_id, protocol.signer(1)).unwrap();
+            Treasury::approve_proposal(env, treasury_id, protocol.signer(2)).unwrap();
+        });
+        protocol
+            .at(500, |env| Treasury::execute_proposal(env, treasury_id))
             .unwrap();
-        treasury.execute_proposal(&treasury_id, 500).unwrap();
-        assert_eq!(treasury.balance_of("XLM"), 8_000_000);
+        assert_eq!(protocol.treasury_balance(), 8_000_000);
 
         // Phase 3: Emergency protection during upgrade window
-        let em_id = emergency
-            .set_emergency_level(
-                "alice",
-                EmergencyLevel::Medium,
-                "Pre-upgrade safety",
-                600,
-                Some(3600),
-            )
+        let em_id = protocol
+            .at(600, |env| {
+                EmergencyController::set_emergency_level(
+                    env,
+                    protocol.signer(0),
+                    EmergencyLevel::Medium,
+                    soroban_sdk::String::from_str(env, "Pre-upgrade safety"),
+                    Some(3600),
+                )
+            })
+            .unwrap();
+        protocol
+            .at(600, |env| {
+                EmergencyController::approve_emergency(env, em_id, protocol.signer(1))
+            })
             .unwrap();
-        assert!(!emergency.is_operation_allowed(&OperationType::Swap));
+        assert!(!protocol.swap_allowed(600));
 
         // Phase 4: Execute protocol upgrade
-        let upgrade_id = upgrade_mgr
-            .propose_upgrade(
-                "alice",
-                Version::new(2, 0, 0),
-                UpgradeType::Major,
-                "New AMM engine with governance",
-                None,
-                vec![
-                    "Deploy v2 contract".to_string(),
-                    "Migrate state".to_string(),
-                ],
-                600,
-            )
+        let wasm_hash = install_test_wasm(&protocol.env, 2);
+        let upgrade_id = protocol
+            .at(600, |env| {
+                UpgradeManager::propose_upgrade(
+                    env,
+                    protocol.signer(0),
+                    wasm_hash,
+                    Version::new(2, 0, 0),
+                    UpgradeType::Major,
+                    soroban_sdk::String::from_str(env, "New AMM engine with governance"),
+                )
+            })
             .unwrap();
-        upgrade_mgr.approve_upgrade(upgrade_id, "bob", 700).unwrap();
-        upgrade_mgr
-            .approve_upgrade(upgrade_id, "carol", 700)
-            .unwrap();
-        upgrade_mgr
-            .approve_upgrade(upgrade_id, "dave", 700)
-            .unwrap();
-        upgrade_mgr.approve_upgrade(upgrade_id, "eve", 700).unwrap();
-        upgrade_mgr
-            .execute_upgrade(upgrade_id, 700 + 172800)
+        protocol.at(700, |env| {
+            for i in 1..5 {
+                UpgradeManager::approve_upgrade(env, upgrade_id, protocol.signer(i)).unwrap();
+            }
+        });
+        protocol
+            .at(700 + 604800, |env| {
+                UpgradeManager::execute_upgrade(env, upgrade_id)
+            })
             .unwrap();
 
         // Phase 5: Lift emergency after successful upgrade
-        emergency
-            .lift_emergency(em_id, "alice", 700 + 172801)
-            .unwrap();
-        assert!(emergency.is_operation_allowed(&OperationType::Swap));
+        protocol.at(700 + 604801, |env| {
+            EmergencyController::lift_emergency(env, em_id, protocol.signer(0)).unwrap();
+            EmergencyController::lift_emergency(env, em_id, protocol.signer(1)).unwrap();
+        });
+        assert!(protocol.swap_allowed(700 + 604801));
 
         // Phase 6: Distribute governance rewards
-        rewards.finalize_epoch(1, 700 + 172802).unwrap();
+        assert_eq!(protocol.at(700 + 604802, RewardManager::sync_epoch), Ok(2));
 
         // Verify final state
-        assert_eq!(upgrade_mgr.current_version(), &Version::new(2, 0, 0));
-        assert_eq!(treasury.balance_of("XLM"), 8_000_000);
-        assert_eq!(emergency.current_level(), EmergencyLevel::None);
-        assert!(treasury.verify_audit_trail());
-        assert!(emergency.verify_audit_trail());
-        assert_eq!(upgrade_mgr.history().len(), 1);
+        assert_eq!(
+            protocol.at(700 + 604802, UpgradeManager::current_version),
+            Some(Version::new(2, 0, 0))
+        );
+        assert_eq!(protocol.treasury_balance(), 8_000_000);
+        assert_eq!(
+            protocol.at(700 + 604802, EmergencyController::current_level),
+            EmergencyLevel::None
+        );
+        // Configured, deposit, proposal, 2 approvals, withdrawal
+        assert_eq!(protocol.treasury_seq(), 6);
+        // Configured, set, approved, two lift approvals, lifted
+        assert_eq!(protocol.audit_seq(), 6);
+        assert_eq!(protocol.at(700 + 604802, UpgradeManager::history).len(), 1);
+        // Storage migration stays pending until the next gated call runs it.
+        protocol.at(700 + 604802, |env| {
+            assert!(UpgradeManager::is_migration_pending(env));
+            UpgradeManager::ensure_migrated(env).unwrap();
+            assert!(!UpgradeManager::is_migration_pending(env));
+        });
 
-        // Rewards can be claimed
-        let alice_reward = rewards.claim_rewards("alice", 1, 700 + 172803);
-        assert!(alice_reward.is_ok());
-        assert!(alice_reward.unwrap() > 0);
+        // Rewards are paid out of the treasury
+        let alice_reward = protocol
+            .at(700 + 604803, |env| {
+                RewardManager::claim_rewards(env, alice.clone(), 1)
+            })
+            .unwrap();
+        assert!(alice_reward > 0);
+        assert_eq!(protocol.treasury_balance(), 8_000_000 - alice_reward);
     }
 
     /// Test that all modules maintain independent audit trails.
     #[test]
     fn test_independent_audit_trails() {
-        let signers: Vec<String> = (0..5).map(|i| format!("signer_{}", i)).collect();
-
-        let mut treasury = Treasury::new(signers.clone(), 3, 2);
-        let mut emergency = EmergencyController::new(EmergencyConfig {
-            signers: signers.clone(),
-            ..EmergencyConfig::default()
-        });
-
-        treasury.deposit("XLM", 1_000_000, "funder", 100).unwrap();
-        emergency
-            .set_emergency_level("signer_0", EmergencyLevel::Low, "test", 200, None)
+        let protocol = Protocol::new();
+        let configured = protocol.audit_seq();
+        let treasury_configured = protocol.treasury_seq();
+
+        protocol.fund_treasury(1_000_000, 100);
+        protocol
+            .at(200, |env| {
+                EmergencyController::set_emergency_level(
+                    env,
+                    protocol.signer(0),
+                    EmergencyLevel::Low,
+                    soroban_sdk::String::from_str(env, "test"),
+                    None,
+                )
+            })
             .unwrap();
 
-        assert!(treasury.verify_audit_trail());
-        assert!(emergency.verify_audit_trail());
-        assert_eq!(treasury.audit_log_length(), 1);
-        assert_eq!(emergency.audit_log_length(), 1);
+        assert_eq!(protocol.treasury_seq(), treasury_configured + 1);
+        assert_eq!(protocol.audit_seq(), configured + 1);
     }
 }
@@ -7,8 +7,6 @@ pub mod rewards;
 pub mod treasury;
 pub mod upgrade;
 pub mod voting;
-pub mod delegation;
-pub mod upgrade_manager;
 
 // The full governance system (GovernanceContract, MultiSigCoordinator, Timelock, etc.)
 // uses std types (HashMap, HashSet) and external crates (sha2, serde, hex) which are
@@ -17,8 +15,6 @@ pub mod upgrade_manager;
 pub mod governance;
 #[cfg(test)]
 pub mod admin;
-#[cfg(test)]
-pub mod multi_asset_staking;
 
 // Re-export key types for convenience (test-only since governance.rs uses std)
 #[cfg(test)]
@@ -32,4 +28,5 @@ pub use governance::{
 };
 #[cfg(test)]
 mod integration_tests;
+#[cfg(test)]
 pub use admin::AdminController;
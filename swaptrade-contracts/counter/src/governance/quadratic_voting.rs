@@ -1,27 +1,119 @@
-use soroban_sdk::{contracttype, Env, Vec};
+//! Vote weighting and tallying per voting mode
+//!
+//! Every governance proposal carries a `VotingMode`:
+//!
+//! - `Simple`: one unit of vote-escrow power is one vote.
+//! - `Quadratic`: a vote weighs the square root of the voter's power. Only
+//!   KYC-verified addresses may vote, so splitting a balance across fresh
+//!   addresses buys no extra weight.
+//! - `Conviction`: a vote's weight grows linearly from 1x to
+//!   `CONVICTION_MAX_MULTIPLIER_BPS` over `CONVICTION_RAMP_SECS` while it
+//!   stays on the same choice. Switching choice restarts the ramp.
+//!
+//! Approval is always 60% of the weighted votes. Quorum is 30% of the
+//! snapshotted total power, measured in raw power turnout for `Simple` and
+//! `Quadratic` (square-root weights are not comparable to total power) and
+//! in conviction-weighted votes for `Conviction`.
+
+use soroban_sdk::{contracttype, Address, Vec};
+
+pub const QUORUM_PCT: i128 = 30;
+pub const APPROVAL_PCT: i128 = 60;
+
+/// Time for a conviction vote to reach its full multiplier.
+pub const CONVICTION_RAMP_SECS: u64 = 7 * 24 * 3600;
+/// Multiplier on a vote held for the full ramp (3x).
+pub const CONVICTION_MAX_MULTIPLIER_BPS: i128 = 30_000;
+const BASE_MULTIPLIER_BPS: i128 = 10_000;
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum VotingMode {
+    Simple = 0,
+    Quadratic = 1,
+    Conviction = 2,
+}
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Vote {
-    pub voter: soroban_sdk::Address,
+    pub voter: Address,
+    pub in_favor: bool,
+    /// Vote-escrow power at proposal creation, delegations included.
+    pub power: i128,
+    /// Signed weight when cast; conviction weights keep growing after this.
     pub vote_weight: i64,
+    /// When the voter last chose this side.
+    pub cast_at: u64,
 }
 
-pub fn calculate_voting_power(balance: u64) -> u64 {
-    (balance as f64).sqrt() as u64
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Tally {
+    pub votes_for: i128,
+    pub votes_against: i128,
+    /// Raw power of everyone who voted, regardless of mode.
+    pub turnout: i128,
 }
 
-pub fn tally_votes(votes: &Vec<Vote>) -> (u64, u64) {
-    let mut total_for = 0;
-    let mut total_against = 0;
+/// Integer square root, rounded down.
+pub fn isqrt(n: i128) -> i128 {
+    if n < 2 {
+        return n.max(0);
+    }
+    let mut x = n;
+    let mut y = (x + 1) / 2;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
 
+/// Multiplier in bps for a conviction vote held for `held_secs`.
+pub fn conviction_multiplier_bps(held_secs: u64) -> i128 {
+    let held = held_secs.min(CONVICTION_RAMP_SECS) as i128;
+    BASE_MULTIPLIER_BPS
+        + (CONVICTION_MAX_MULTIPLIER_BPS - BASE_MULTIPLIER_BPS) * held
+            / CONVICTION_RAMP_SECS as i128
+}
+
+/// Unsigned weight of `vote` at time `now`.
+pub fn vote_weight(mode: VotingMode, vote: &Vote, now: u64) -> i128 {
+    match mode {
+        VotingMode::Simple => vote.power,
+        VotingMode::Quadratic => isqrt(vote.power),
+        VotingMode::Conviction => {
+            vote.power * conviction_multiplier_bps(now.saturating_sub(vote.cast_at))
+                / BASE_MULTIPLIER_BPS
+        }
+    }
+}
+
+pub fn tally_votes(mode: VotingMode, votes: &Vec<Vote>, now: u64) -> Tally {
+    let mut tally = Tally::default();
     for vote in votes.iter() {
-        if vote.vote_weight > 0 {
-            total_for += vote.vote_weight as u64;
+        let weight = vote_weight(mode, &vote, now);
+        if vote.in_favor {
+            tally.votes_for += weight;
         } else {
-            total_against += vote.vote_weight.abs() as u64;
+            tally.votes_against += weight;
         }
+        tally.turnout += vote.power;
     }
+    tally
+}
+
+pub fn quorum_reached(mode: VotingMode, tally: &Tally, total_power: i128) -> bool {
+    let counted = match mode {
+        VotingMode::Simple | VotingMode::Quadratic => tally.turnout,
+        VotingMode::Conviction => tally.votes_for + tally.votes_against,
+    };
+    counted > 0 && counted * 100 >= total_power * QUORUM_PCT
+}
 
-    (total_for, total_against)
+pub fn approved(tally: &Tally) -> bool {
+    tally.votes_for > 0
+        && tally.votes_for * 100 >= (tally.votes_for + tally.votes_against) * APPROVAL_PCT
 }
@@ -2,44 +2,44 @@
 //
 // Governance Reward Distribution
 //
-// Distributes governance rewards to active participants to incentivize
-// community engagement and responsible governance.
+// Pays governance participants out of the treasury to encourage turnout.
+// Participation accrues automatically: the governance system records
+// proposal creation and votes, and delegation records each new delegation
+// a delegate receives. Each epoch splits a budget set by governance
+// proposal across participants in proportion to their participation score.
 //
 // Capabilities:
-//   - Time-weighted reward calculation based on participation
-//   - Proposal creation bonuses
-//   - Voting participation rewards
-//   - Delegation rewards
-//   - Epoch-based reward distribution
-//   - Anti-gaming measures (cooldowns, diminishing returns)
-//   - Reward claiming with verification
+//   - Epochs that roll over on the first action after they end
+//   - Per-epoch participation records and scores
+//   - Budget and reward token fixed per epoch when it starts
+//   - Anti-gaming measures (minimum voting power, per-participant cap,
+//     diminishing returns for repeat claimers)
+//   - One claim per participant and epoch, paid in SEP-41 tokens from the
+//     treasury through its spending limits and audit trail
 
-use std::collections::HashMap;
+use soroban_sdk::{contracttype, symbol_short, Address, Env};
 
-// ─── Types ────────────────────────────────────────────────────────────────────
-
-/// Unique identifier for reward epochs.
-pub type EpochId = u64;
+use crate::errors::SwapTradeError;
+use crate::governance::treasury::Treasury;
 
-/// Participant address.
-pub type ParticipantId = String;
+/// Diminishing returns compound for at most this many claimed epochs.
+const MAX_DIMINISHING_EPOCHS: u32 = 5;
+const BPS: i128 = 10_000;
 
-/// Reward token amount.
-pub type RewardAmount = u128;
+// ─── Types ────────────────────────────────────────────────────────────────────
 
 /// Participation record for a user in an epoch.
-#[derive(Debug, Clone, Default)]
+#[contracttype]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct ParticipationRecord {
     /// Number of proposals created.
     pub proposals_created: u32,
     /// Number of votes cast.
     pub votes_cast: u32,
-    /// Number of votes where the participant was a delegate.
-    pub delegated_votes_received: u32,
+    /// Number of new delegations received.
+    pub delegations_received: u32,
     /// Total voting power used.
-    pub total_voting_power_used: u128,
-    /// Whether the participant voted on all eligible proposals.
-    pub full_participation: bool,
+    pub total_voting_power_used: i128,
     /// Timestamp of first action in epoch.
     pub first_action_at: u64,
     /// Timestamp of last action in epoch.
@@ -47,6 +47,11 @@ pub struct ParticipationRecord {
 }
 
 impl ParticipationRecord {
+    /// Both proposed and voted during the epoch.
+    pub fn full_participation(&self) -> bool {
+        self.votes_cast > 0 && self.proposals_created > 0
+    }
+
     /// Compute a participation score (0-100).
     pub fn score(&self) -> u32 {
         let mut score: u32 = 0;
@@ -58,10 +63,10 @@ impl ParticipationRecord {
         score = score.saturating_add(self.proposals_created.saturating_mul(15).min(30));
 
         // Delegation activity (up to 10 points)
-        score = score.saturating_add(self.delegated_votes_received.saturating_mul(5).min(10));
+        score = score.saturating_add(self.delegations_received.saturating_mul(5).min(10));
 
         // Full participation bonus (10 points)
-        if self.full_participation {
+        if self.full_participation() {
             score = score.saturating_add(10);
         }
 
@@ -69,427 +74,359 @@ impl ParticipationRecord {
     }
 }
 
-/// Configuration for the reward system.
-#[derive(Debug, Clone)]
+/// Reward settings, changed only by governance proposal. Budget and token
+/// changes apply from the next epoch.
+#[contracttype]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RewardConfig {
-    /// Total reward pool per epoch.
-    pub epoch_reward_pool: RewardAmount,
+    /// SEP-41 token rewards are paid in.
+    pub token: Address,
+    /// Amount split across participants each epoch.
+    pub epoch_budget: i128,
     /// Duration of each epoch in seconds.
     pub epoch_duration_secs: u64,
-    /// Cooldown between claim attempts per participant.
-    pub claim_cooldown_secs: u64,
     /// Maximum reward per participant per epoch (caps gaming).
-    pub max_reward_per_participant: RewardAmount,
-    /// Base reward for creating a proposal.
-    pub proposal_creation_bonus: RewardAmount,
-    /// Base reward for casting a vote.
-    pub vote_cast_reward: RewardAmount,
-    /// Multiplier for voting with higher power (basis points, 100 = 1x).
-    pub voting_power_multiplier_bps: u32,
+    pub max_reward_per_participant: i128,
     /// Minimum participation score to qualify for rewards.
     pub min_qualification_score: u32,
-    /// Diminishing returns factor (basis points, 100 = no diminishing).
+    /// Votes and delegations backed by less power earn no credit.
+    pub min_voting_power: i128,
+    /// Share of the reward kept per epoch already claimed (bps, 10_000 = no
+    /// diminishing).
     pub diminishing_returns_bps: u32,
 }
 
-impl Default for RewardConfig {
-    fn default() -> Self {
-        Self {
-            epoch_reward_pool: 100_000,
-            epoch_duration_secs: 604800, // 7 days
-            claim_cooldown_secs: 3600,
-            max_reward_per_participant: 10_000,
-            proposal_creation_bonus: 500,
-            vote_cast_reward: 100,
-            voting_power_multiplier_bps: 100,
-            min_qualification_score: 10,
-            diminishing_returns_bps: 80, // 80% efficiency after first epoch
-        }
-    }
-}
-
-/// An epoch containing participation data and reward distribution.
-#[derive(Debug, Clone)]
+/// An epoch's budget and aggregate participation.
+#[contracttype]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Epoch {
-    pub id: EpochId,
+    pub id: u64,
     pub start_time: u64,
     pub end_time: u64,
+    pub token: Address,
     /// Total reward pool allocated.
-    pub reward_pool: RewardAmount,
+    pub reward_pool: i128,
     /// Rewards claimed so far.
-    pub rewards_distributed: RewardAmount,
-    /// Participation records per participant.
-    pub participation: HashMap<ParticipantId, ParticipationRecord>,
+    pub rewards_distributed: i128,
+    /// Sum of every participant's score.
+    pub total_score: u64,
+    pub participants: u32,
     /// Whether the epoch is finalized.
     pub finalized: bool,
 }
 
-impl Epoch {
-    pub fn total_participants(&self) -> usize {
-        self.participation.len()
-    }
-
-    pub fn total_votes_cast(&self) -> u32 {
-        self.participation.values().map(|r| r.votes_cast).sum()
-    }
-
-    pub fn total_proposals_created(&self) -> u32 {
-        self.participation
-            .values()
-            .map(|r| r.proposals_created)
-            .sum()
-    }
+#[contracttype]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AllTimeStats {
+    pub epochs_claimed: u32,
+    pub total_rewards_claimed: i128,
+    pub total_proposals_created: u32,
+    pub total_votes_cast: u32,
 }
 
-/// Claim record for audit trail.
-#[derive(Debug, Clone)]
-pub struct ClaimRecord {
-    pub participant: ParticipantId,
-    pub epoch_id: EpochId,
-    pub amount: RewardAmount,
-    pub claimed_at: u64,
-    pub participation_score: u32,
+#[contracttype]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RewardKey {
+    RewardConfig,
+    CurrentEpoch,
+    Epoch(u64),
+    Record(u64, Address),
+    Claimed(u64, Address),
+    Stats(Address),
 }
 
 // ─── Reward Manager ───────────────────────────────────────────────────────────
 
-pub struct RewardManager {
-    /// Reward configuration.
-    config: RewardConfig,
-    /// Historical and current epochs.
-    epochs: HashMap<EpochId, Epoch>,
-    /// Current epoch ID.
-    current_epoch_id: EpochId,
-    /// When the current epoch started.
-    current_epoch_start: u64,
-    /// Claim history per participant (epoch_id -> last claim timestamp).
-    last_claim: HashMap<ParticipantId, u64>,
-    /// Total rewards distributed across all epochs.
-    total_rewards_distributed: RewardAmount,
-    /// Historical claim records.
-    claim_history: Vec<ClaimRecord>,
-    /// All-time participation counts per participant.
-    all_time_participation: HashMap<ParticipantId, AllTimeStats>,
-}
-
-#[derive(Debug, Clone, Default)]
-pub struct AllTimeStats {
-    pub epochs_participated: u32,
-    pub total_rewards_claimed: RewardAmount,
-    pub total_proposals_created: u32,
-    pub total_votes_cast: u32,
-}
+pub struct RewardManager;
 
 impl RewardManager {
-    pub fn new(config: RewardConfig, start_time: u64) -> Self {
-        let mut epochs = HashMap::new();
-        let first_epoch = Epoch {
-            id: 1,
-            start_time,
-            end_time: start_time + config.epoch_duration_secs,
-            reward_pool: config.epoch_reward_pool,
-            rewards_distributed: 0,
-            participation: HashMap::new(),
-            finalized: false,
-        };
-        epochs.insert(1, first_epoch);
-
-        Self {
-            config,
-            epochs,
-            current_epoch_id: 1,
-            current_epoch_start: start_time,
-            last_claim: HashMap::new(),
-            total_rewards_distributed: 0,
-            claim_history: Vec::new(),
-            all_time_participation: HashMap::new(),
+    // ── Configuration ────────────────────────────────────────────────────────
+
+    /// Apply reward settings passed by governance. The first configuration
+    /// opens epoch 1.
+    pub(crate) fn set_config(env: &Env, config: RewardConfig) -> Result<(), SwapTradeError> {
+        if config.epoch_budget < 0
+            || config.epoch_duration_secs == 0
+            || config.max_reward_per_participant <= 0
+            || config.min_qualification_score > 100
+            || config.min_voting_power < 0
+            || config.diminishing_returns_bps as i128 > BPS
+        {
+            return Err(SwapTradeError::InvalidAmount);
+        }
+        env.storage()
+            .persistent()
+            .set(&RewardKey::RewardConfig, &config);
+        if Self::current_epoch_id(env) == 0 {
+            Self::open_epoch(env, 1, &config);
         }
+        env.events().publish(
+            (symbol_short!("rw_config"),),
+            (
+                config.token,
+                config.epoch_budget,
+                config.epoch_duration_secs,
+            ),
+        );
+        Ok(())
+    }
+
+    pub fn get_config(env: &Env) -> Option<RewardConfig> {
+        env.storage().persistent().get(&RewardKey::RewardConfig)
     }
 
     // ── Participation Tracking ───────────────────────────────────────────────
 
     /// Record a proposal creation.
-    pub fn record_proposal_created(&mut self, participant: &str, now: u64) -> Result<(), String> {
-        self.ensure_current_epoch(now)?;
-
-        let epoch = self
-            .epochs
-            .get_mut(&self.current_epoch_id)
-            .ok_or("epoch not found")?;
-
-        let record = epoch
-            .participation
-            .entry(participant.to_string())
-            .or_insert_with(ParticipationRecord::default);
-
-        record.proposals_created = record.proposals_created.saturating_add(1);
-        if record.first_action_at == 0 {
-            record.first_action_at = now;
+    pub(crate) fn record_proposal_created(env: &Env, participant: &Address) {
+        if Self::record(env, participant, |r| {
+            r.proposals_created = r.proposals_created.saturating_add(1)
+        }) {
+            Self::update_stats(env, participant, |s| {
+                s.total_proposals_created = s.total_proposals_created.saturating_add(1)
+            });
         }
-        record.last_action_at = now;
-
-        // Update all-time stats
-        let stats = self
-            .all_time_participation
-            .entry(participant.to_string())
-            .or_insert_with(AllTimeStats::default);
-        stats.total_proposals_created = stats.total_proposals_created.saturating_add(1);
-
-        Ok(())
     }
 
-    /// Record a vote cast.
-    pub fn record_vote_cast(
-        &mut self,
-        participant: &str,
-        voting_power: u128,
-        now: u64,
-    ) -> Result<(), String> {
-        self.ensure_current_epoch(now)?;
-
-        let epoch = self
-            .epochs
-            .get_mut(&self.current_epoch_id)
-            .ok_or("epoch not found")?;
-
-        let record = epoch
-            .participation
-            .entry(participant.to_string())
-            .or_insert_with(ParticipationRecord::default);
-
-        record.votes_cast = record.votes_cast.saturating_add(1);
-        record.total_voting_power_used =
-            record.total_voting_power_used.saturating_add(voting_power);
-        if record.first_action_at == 0 {
-            record.first_action_at = now;
+    /// Record a vote cast with `voting_power`.
+    pub(crate) fn record_vote_cast(env: &Env, participant: &Address, voting_power: i128) {
+        if !Self::meets_min_power(env, voting_power) {
+            return;
+        }
+        if Self::record(env, participant, |r| {
+            r.votes_cast = r.votes_cast.saturating_add(1);
+            r.total_voting_power_used = r.total_voting_power_used.saturating_add(voting_power);
+        }) {
+            Self::update_stats(env, participant, |s| {
+                s.total_votes_cast = s.total_votes_cast.saturating_add(1)
+            });
         }
-        record.last_action_at = now;
-
-        // Update all-time stats
-        let stats = self
-            .all_time_participation
-            .entry(participant.to_string())
-            .or_insert_with(AllTimeStats::default);
-        stats.total_votes_cast = stats.total_votes_cast.saturating_add(1);
-
-        Ok(())
     }
 
-    /// Record delegation activity.
-    pub fn record_delegation_received(&mut self, delegate: &str, now: u64) -> Result<(), String> {
-        self.ensure_current_epoch(now)?;
-
-        let epoch = self
-            .epochs
-            .get_mut(&self.current_epoch_id)
-            .ok_or("epoch not found")?;
-
-        let record = epoch
-            .participation
-            .entry(delegate.to_string())
-            .or_insert_with(ParticipationRecord::default);
-
-        record.delegated_votes_received = record.delegated_votes_received.saturating_add(1);
-        if record.first_action_at == 0 {
-            record.first_action_at = now;
+    /// Record a new delegation to `delegate` from a delegator holding
+    /// `delegator_power`.
+    pub(crate) fn record_delegation_received(env: &Env, delegate: &Address, delegator_power: i128) {
+        if !Self::meets_min_power(env, delegator_power) {
+            return;
         }
-        record.last_action_at = now;
-
-        Ok(())
+        Self::record(env, delegate, |r| {
+            r.delegations_received = r.delegations_received.saturating_add(1)
+        });
     }
 
     // ── Epoch Management ─────────────────────────────────────────────────────
 
-    fn ensure_current_epoch(&mut self, now: u64) -> Result<(), String> {
-        let epoch = self
-            .epochs
-            .get(&self.current_epoch_id)
-            .ok_or("epoch not found")?;
-
-        if now >= epoch.end_time {
-            // Finalize current epoch and start new one
-            self.finalize_epoch(self.current_epoch_id, now)?;
-        }
-        Ok(())
+    /// Finalize the current epoch if it has ended and open the next one.
+    /// Returns the id of the epoch now open.
+    pub fn sync_epoch(env: &Env) -> Result<u64, SwapTradeError> {
+        Self::ensure_current_epoch(env)
+            .map(|e| e.id)
+            .ok_or(SwapTradeError::RewardsNotConfigured)
     }
 
-    /// Finalize an epoch, computing final scores.
-    pub fn finalize_epoch(&mut self, epoch_id: EpochId, now: u64) -> Result<(), String> {
-        let epoch = self.epochs.get_mut(&epoch_id).ok_or("epoch not found")?;
-
-        if epoch.finalized {
-            return Err("epoch already finalized".to_string());
-        }
-
-        // Mark full participation for users who voted on everything
-        // (simplified: check if they cast at least 1 vote)
-        for record in epoch.participation.values_mut() {
-            if record.votes_cast > 0 && record.proposals_created > 0 {
-                record.full_participation = true;
-            }
+    fn ensure_current_epoch(env: &Env) -> Option<Epoch> {
+        let config = Self::get_config(env)?;
+        let mut epoch = Self::get_epoch(env, Self::current_epoch_id(env))?;
+        if env.ledger().timestamp() < epoch.end_time {
+            return Some(epoch);
         }
 
         epoch.finalized = true;
-
-        // Start new epoch
-        let new_id = epoch_id + 1;
-        let new_epoch = Epoch {
-            id: new_id,
+        env.storage()
+            .persistent()
+            .set(&RewardKey::Epoch(epoch.id), &epoch);
+        env.events().publish(
+            (symbol_short!("rw_final"), epoch.id),
+            (epoch.participants, epoch.total_score),
+        );
+        Some(Self::open_epoch(env, epoch.id + 1, &config))
+    }
+
+    fn open_epoch(env: &Env, id: u64, config: &RewardConfig) -> Epoch {
+        let now = env.ledger().timestamp();
+        let epoch = Epoch {
+            id,
             start_time: now,
-            end_time: now + self.config.epoch_duration_secs,
-            reward_pool: self.config.epoch_reward_pool,
+            end_time: now + config.epoch_duration_secs,
+            token: config.token.clone(),
+            reward_pool: config.epoch_budget,
             rewards_distributed: 0,
-            participation: HashMap::new(),
+            total_score: 0,
+            participants: 0,
             finalized: false,
         };
-        self.epochs.insert(new_id, new_epoch);
-        self.current_epoch_id = new_id;
-        self.current_epoch_start = now;
-
-        Ok(())
+        env.storage()
+            .persistent()
+            .set(&RewardKey::Epoch(id), &epoch);
+        env.storage()
+            .persistent()
+            .set(&RewardKey::CurrentEpoch, &id);
+        epoch
     }
 
     // ── Reward Calculation ───────────────────────────────────────────────────
 
-    /// Calculate the reward for a participant in a given epoch.
-    pub fn calculate_reward(&self, participant: &str, epoch_id: EpochId) -> RewardAmount {
-        let epoch = match self.epochs.get(&epoch_id) {
-            Some(e) => e,
-            None => return 0,
+    /// Reward for a participant in a given epoch: a score-weighted share of
+    /// the pool, reduced for repeat claimers and capped.
+    pub fn calculate_reward(env: &Env, participant: &Address, epoch_id: u64) -> i128 {
+        let (Some(config), Some(epoch)) = (Self::get_config(env), Self::get_epoch(env, epoch_id))
+        else {
+            return 0;
         };
-
-        let record = match epoch.participation.get(participant) {
-            Some(r) => r,
+        let score = match Self::participation_record(env, participant, epoch_id) {
+            Some(r) => r.score(),
             None => return 0,
         };
-
-        let score = record.score();
-        if score < self.config.min_qualification_score {
+        if score < config.min_qualification_score || epoch.total_score == 0 {
             return 0;
         }
 
-        // Base reward proportional to score
-        let base_reward = (epoch.reward_pool * score as u128) / 100;
-
-        // Apply participation bonuses
-        let proposal_bonus = record.proposals_created as u128 * self.config.proposal_creation_bonus;
-        let vote_bonus = record.votes_cast as u128 * self.config.vote_cast_reward;
-
-        // Apply voting power multiplier
-        let power_multiplier = (self.config.voting_power_multiplier_bps as u128).min(200); // Cap at 2x
-        let power_bonus = (record.total_voting_power_used * power_multiplier) / 10_000;
-
-        let mut total = base_reward
-            .saturating_add(proposal_bonus)
-            .saturating_add(vote_bonus)
-            .saturating_add(power_bonus);
+        let mut total = epoch.reward_pool * score as i128 / epoch.total_score as i128;
 
         // Apply diminishing returns for repeat participants
-        let stats = self.all_time_participation.get(participant);
-        if let Some(stats) = stats {
-            if stats.epochs_participated > 0 {
-                let efficiency = self.config.diminishing_returns_bps as u128;
-                let factor = efficiency.pow(stats.epochs_participated.min(5) as u32);
-                let divisor = 100u128.pow(stats.epochs_participated.min(5) as u32);
-                total = (total * factor) / divisor;
-            }
+        let claimed = Self::all_time_stats(env, participant)
+            .epochs_claimed
+            .min(MAX_DIMINISHING_EPOCHS);
+        for _ in 0..claimed {
+            total = total * config.diminishing_returns_bps as i128 / BPS;
         }
 
         // Cap at maximum
-        total.min(self.config.max_reward_per_participant)
+        total.min(config.max_reward_per_participant)
     }
 
     // ── Claiming ─────────────────────────────────────────────────────────────
 
-    /// Claim rewards for a finalized epoch.
+    /// Claim rewards for a finalized epoch, paid from the treasury.
     pub fn claim_rewards(
-        &mut self,
-        participant: &str,
-        epoch_id: EpochId,
-        now: u64,
-    ) -> Result<RewardAmount, String> {
-        let epoch = self.epochs.get(&epoch_id).ok_or("epoch not found")?;
-
+        env: &Env,
+        participant: Address,
+        epoch_id: u64,
+    ) -> Result<i128, SwapTradeError> {
+        participant.require_auth();
+        Self::ensure_current_epoch(env).ok_or(SwapTradeError::RewardsNotConfigured)?;
+
+        let mut epoch = Self::get_epoch(env, epoch_id).ok_or(SwapTradeError::EpochNotFinalized)?;
         if !epoch.finalized {
-            return Err("epoch is not yet finalized".to_string());
+            return Err(SwapTradeError::EpochNotFinalized);
         }
-
-        // Check cooldown
-        if let Some(last) = self.last_claim.get(participant) {
-            if now < last + self.config.claim_cooldown_secs {
-                return Err("claim cooldown not elapsed".to_string());
-            }
+        let claimed_key = RewardKey::Claimed(epoch_id, participant.clone());
+        if env.storage().persistent().has(&claimed_key) {
+            return Err(SwapTradeError::RewardAlreadyClaimed);
         }
 
-        let amount = self.calculate_reward(participant, epoch_id);
-        if amount == 0 {
-            return Err("no rewards to claim".to_string());
+        let amount = Self::calculate_reward(env, &participant, epoch_id)
+            .min(epoch.reward_pool - epoch.rewards_distributed);
+        if amount <= 0 {
+            return Err(SwapTradeError::NoRewardsToClaim);
         }
 
-        // Check remaining pool
-        let epoch = self.epochs.get_mut(&epoch_id).ok_or("epoch not found")?;
-        if epoch.rewards_distributed + amount > epoch.reward_pool {
-            return Err("insufficient reward pool".to_string());
-        }
+        Treasury::pay_reward(env, epoch_id, &epoch.token, &participant, amount)?;
 
-        epoch.rewards_distributed = epoch.rewards_distributed.saturating_add(amount);
-        self.total_rewards_distributed = self.total_rewards_distributed.saturating_add(amount);
-        self.last_claim.insert(participant.to_string(), now);
-
-        // Update all-time stats
-        let stats = self
-            .all_time_participation
-            .entry(participant.to_string())
-            .or_insert_with(AllTimeStats::default);
-        stats.epochs_participated = stats.epochs_participated.saturating_add(1);
-        stats.total_rewards_claimed = stats.total_rewards_claimed.saturating_add(amount);
-
-        let record = epoch.participation.get(participant);
-        let score = record.map(|r| r.score()).unwrap_or(0);
-
-        self.claim_history.push(ClaimRecord {
-            participant: participant.to_string(),
-            epoch_id,
-            amount,
-            claimed_at: now,
-            participation_score: score,
+        epoch.rewards_distributed += amount;
+        env.storage()
+            .persistent()
+            .set(&RewardKey::Epoch(epoch_id), &epoch);
+        env.storage().persistent().set(&claimed_key, &amount);
+        Self::update_stats(env, &participant, |s| {
+            s.epochs_claimed = s.epochs_claimed.saturating_add(1);
+            s.total_rewards_claimed = s.total_rewards_claimed.saturating_add(amount);
         });
 
+        env.events()
+            .publish((symbol_short!("rw_claim"), epoch_id), (participant, amount));
         Ok(amount)
     }
 
     // ── Queries ──────────────────────────────────────────────────────────────
 
-    pub fn current_epoch_id(&self) -> EpochId {
-        self.current_epoch_id
+    /// Id of the open epoch, 0 before rewards are configured.
+    pub fn current_epoch_id(env: &Env) -> u64 {
+        env.storage()
+            .persistent()
+            .get(&RewardKey::CurrentEpoch)
+            .unwrap_or(0)
     }
 
-    pub fn get_epoch(&self, epoch_id: EpochId) -> Option<&Epoch> {
-        self.epochs.get(&epoch_id)
-    }
-
-    pub fn total_distributed(&self) -> RewardAmount {
-        self.total_rewards_distributed
+    pub fn get_epoch(env: &Env, epoch_id: u64) -> Option<Epoch> {
+        env.storage().persistent().get(&RewardKey::Epoch(epoch_id))
     }
 
     pub fn participation_record(
-        &self,
-        participant: &str,
-        epoch_id: EpochId,
-    ) -> Option<&ParticipationRecord> {
-        self.epochs.get(&epoch_id)?.participation.get(participant)
+        env: &Env,
+        participant: &Address,
+        epoch_id: u64,
+    ) -> Option<ParticipationRecord> {
+        env.storage()
+            .persistent()
+            .get(&RewardKey::Record(epoch_id, participant.clone()))
+    }
+
+    pub fn all_time_stats(env: &Env, participant: &Address) -> AllTimeStats {
+        env.storage()
+            .persistent()
+            .get(&RewardKey::Stats(participant.clone()))
+            .unwrap_or_default()
+    }
+
+    /// Reward still claimable for an epoch; zero once claimed.
+    pub fn pending_reward(env: &Env, participant: &Address, epoch_id: u64) -> i128 {
+        if env
+            .storage()
+            .persistent()
+            .has(&RewardKey::Claimed(epoch_id, participant.clone()))
+        {
+            return 0;
+        }
+        Self::calculate_reward(env, participant, epoch_id)
     }
 
-    pub fn all_time_stats(&self, participant: &str) -> Option<&AllTimeStats> {
-        self.all_time_participation.get(participant)
+    // ── Internal ─────────────────────────────────────────────────────────────
+
+    fn meets_min_power(env: &Env, power: i128) -> bool {
+        Self::get_config(env).is_some_and(|c| power >= c.min_voting_power)
     }
 
-    pub fn claim_history_length(&self) -> usize {
-        self.claim_history.len()
+    /// Apply `update` to the participant's record in the open epoch and
+    /// keep the epoch's score total in step. Returns false when rewards are
+    /// not configured.
+    fn record(
+        env: &Env,
+        participant: &Address,
+        update: impl FnOnce(&mut ParticipationRecord),
+    ) -> bool {
+        let Some(mut epoch) = Self::ensure_current_epoch(env) else {
+            return false;
+        };
+        let now = env.ledger().timestamp();
+        let key = RewardKey::Record(epoch.id, participant.clone());
+        let existing: Option<ParticipationRecord> = env.storage().persistent().get(&key);
+        if existing.is_none() {
+            epoch.participants += 1;
+        }
+        let mut record = existing.unwrap_or_default();
+        let before = record.score();
+
+        update(&mut record);
+        if record.first_action_at == 0 {
+            record.first_action_at = now;
+        }
+        record.last_action_at = now;
+
+        epoch.total_score = epoch.total_score - before as u64 + record.score() as u64;
+        env.storage().persistent().set(&key, &record);
+        env.storage()
+            .persistent()
+            .set(&RewardKey::Epoch(epoch.id), &epoch);
+        true
     }
 
-    pub fn pending_reward(&self, participant: &str, epoch_id: EpochId) -> RewardAmount {
-        self.calculate_reward(participant, epoch_id)
+    fn update_stats(env: &Env, participant: &Address, update: impl FnOnce(&mut AllTimeStats)) {
+        let mut stats = Self::all_time_stats(env, participant);
+        update(&mut stats);
+        env.storage()
+            .persistent()
+            .set(&RewardKey::Stats(participant.clone()), &stats);
     }
 }
 
@@ -498,58 +435,126 @@ impl RewardManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::token::{StellarAssetClient, TokenClient};
+
+    const WEEK: u64 = 604_800;
+
+    struct Setup {
+        env: Env,
+        contract_id: Address,
+        token: Address,
+    }
+
+    impl Setup {
+        fn at<T>(&self, now: u64, f: impl FnOnce() -> T) -> T {
+            self.env.ledger().with_mut(|l| l.timestamp = now);
+            self.env.as_contract(&self.contract_id, f)
+        }
+
+        fn config(&self) -> RewardConfig {
+            RewardConfig {
+                token: self.token.clone(),
+                epoch_budget: 100_000,
+                epoch_duration_secs: WEEK,
+                max_reward_per_participant: 60_000,
+                min_qualification_score: 10,
+                min_voting_power: 50,
+                diminishing_returns_bps: 10_000,
+            }
+        }
 
-    fn setup() -> RewardManager {
-        let config = RewardConfig {
-            epoch_reward_pool: 100_000,
-            epoch_duration_secs: 604800,
-            claim_cooldown_secs: 60,
-            max_reward_per_participant: 10_000,
-            proposal_creation_bonus: 500,
-            vote_cast_reward: 100,
-            voting_power_multiplier_bps: 100,
-            min_qualification_score: 10,
-            diminishing_returns_bps: 100, // No diminishing for tests
+        fn vote(&self, who: &Address, power: i128, now: u64) {
+            self.at(now, || {
+                RewardManager::record_vote_cast(&self.env, who, power)
+            });
+        }
+
+        fn propose(&self, who: &Address, now: u64) {
+            self.at(now, || {
+                RewardManager::record_proposal_created(&self.env, who)
+            });
+        }
+
+        fn claim(&self, who: &Address, epoch_id: u64, now: u64) -> Result<i128, SwapTradeError> {
+            self.at(now, || {
+                RewardManager::claim_rewards(&self.env, who.clone(), epoch_id)
+            })
+        }
+
+        fn record(&self, who: &Address, epoch_id: u64) -> ParticipationRecord {
+            self.at(self.env.ledger().timestamp(), || {
+                RewardManager::participation_record(&self.env, who, epoch_id).unwrap()
+            })
+        }
+    }
+
+    /// Rewards configured at t=0 with a treasury holding 1M of the token.
+    fn setup() -> Setup {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register(crate::CounterContract, ());
+        let token = env
+            .register_stellar_asset_contract_v2(Address::generate(&env))
+            .address();
+        let funder = Address::generate(&env);
+        StellarAssetClient::new(&env, &token).mint(&funder, &1_000_000);
+
+        let s = Setup {
+            env,
+            contract_id,
+            token,
         };
-        RewardManager::new(config, 0)
+        s.at(0, || {
+            Treasury::deposit(&s.env, funder, s.token.clone(), 1_000_000).unwrap();
+            RewardManager::set_config(&s.env, s.config()).unwrap();
+        });
+        s
     }
 
     #[test]
     fn test_initial_epoch() {
-        let mgr = setup();
-        assert_eq!(mgr.current_epoch_id(), 1);
-        let epoch = mgr.get_epoch(1).unwrap();
-        assert_eq!(epoch.reward_pool, 100_000);
-        assert!(!epoch.finalized);
+        let s = setup();
+        s.at(0, || {
+            assert_eq!(RewardManager::current_epoch_id(&s.env), 1);
+            let epoch = RewardManager::get_epoch(&s.env, 1).unwrap();
+            assert_eq!(epoch.reward_pool, 100_000);
+            assert_eq!((epoch.start_time, epoch.end_time), (0, WEEK));
+            assert_eq!(epoch.token, s.token);
+            assert!(!epoch.finalized);
+        });
     }
 
     #[test]
     fn test_record_proposal_created() {
-        let mut mgr = setup();
-        mgr.record_proposal_created("alice", 100).unwrap();
+        let s = setup();
+        let alice = Address::generate(&s.env);
+        s.propose(&alice, 100);
 
-        let record = mgr.participation_record("alice", 1).unwrap();
+        let record = s.record(&alice, 1);
         assert_eq!(record.proposals_created, 1);
         assert_eq!(record.first_action_at, 100);
     }
 
     #[test]
     fn test_record_vote_cast() {
-        let mut mgr = setup();
-        mgr.record_vote_cast("bob", 500, 200).unwrap();
+        let s = setup();
+        let bob = Address::generate(&s.env);
+        s.vote(&bob, 500, 200);
 
-        let record = mgr.participation_record("bob", 1).unwrap();
+        let record = s.record(&bob, 1);
         assert_eq!(record.votes_cast, 1);
         assert_eq!(record.total_voting_power_used, 500);
     }
 
     #[test]
     fn test_participation_score() {
-        let mut record = ParticipationRecord::default();
-        record.votes_cast = 5;
-        record.proposals_created = 2;
-        record.delegated_votes_received = 1;
-        record.full_participation = true;
+        let record = ParticipationRecord {
+            votes_cast: 5,
+            proposals_created: 2,
+            delegations_received: 1,
+            ..Default::default()
+        };
 
         // 50 (votes) + 30 (proposals) + 5 (delegation) + 10 (full) = 95
         assert_eq!(record.score(), 95);
@@ -557,111 +562,260 @@ mod tests {
 
     #[test]
     fn test_participation_score_low() {
-        let mut record = ParticipationRecord::default();
-        record.votes_cast = 1;
-
+        let voter = ParticipationRecord {
+            votes_cast: 1,
+            ..Default::default()
+        };
+        assert!(!voter.full_participation());
         // 10 (vote) = 10
-        assert_eq!(record.score(), 10);
+        assert_eq!(voter.score(), 10);
+    }
+
+    #[test]
+    fn test_full_participation_bonus() {
+        let full = ParticipationRecord {
+            votes_cast: 3,
+            proposals_created: 1,
+            ..Default::default()
+        };
+        let voter_only = ParticipationRecord {
+            proposals_created: 0,
+            ..full.clone()
+        };
+        assert!(full.full_participation());
+        // 30 (votes) + 15 (proposal) + 10 (full) vs 30 (votes).
+        assert_eq!(full.score(), voter_only.score() + 15 + 10);
+    }
+
+    #[test]
+    fn test_nothing_recorded_before_configuration() {
+        let env = Env::default();
+        let contract_id = env.register(crate::CounterContract, ());
+        let alice = Address::generate(&env);
+        env.as_contract(&contract_id, || {
+            RewardManager::record_vote_cast(&env, &alice, 1_000);
+            assert_eq!(RewardManager::current_epoch_id(&env), 0);
+            assert_eq!(RewardManager::participation_record(&env, &alice, 1), None);
+            assert_eq!(
+                RewardManager::sync_epoch(&env),
+                Err(SwapTradeError::RewardsNotConfigured)
+            );
+        });
+    }
+
+    #[test]
+    fn test_records_accrue_and_epochs_roll_over() {
+        let s = setup();
+        let alice = Address::generate(&s.env);
+
+        s.vote(&alice, 200, 100);
+        s.vote(&alice, 300, 200);
+        s.propose(&alice, 300);
+        // Below the minimum power: no credit.
+        s.vote(&alice, 10, 400);
+
+        let record = s.record(&alice, 1);
+        assert_eq!(record.votes_cast, 2);
+        assert_eq!(record.total_voting_power_used, 500);
+        assert_eq!((record.first_action_at, record.last_action_at), (100, 300));
+        let epoch = s.at(400, || RewardManager::get_epoch(&s.env, 1).unwrap());
+        assert_eq!(epoch.total_score, record.score() as u64);
+
+        // The first action after the epoch ends finalizes it.
+        s.vote(&alice, 200, WEEK + 1);
+        s.at(WEEK + 1, || {
+            assert!(RewardManager::get_epoch(&s.env, 1).unwrap().finalized);
+            assert_eq!(RewardManager::current_epoch_id(&s.env), 2);
+        });
+        assert_eq!(s.record(&alice, 2).votes_cast, 1);
+        assert_eq!(
+            s.at(WEEK + 2, || RewardManager::all_time_stats(&s.env, &alice))
+                .total_votes_cast,
+            3
+        );
+    }
+
+    #[test]
+    fn test_claim_pays_score_share_from_treasury() {
+        let s = setup();
+        let alice = Address::generate(&s.env);
+        let bob = Address::generate(&s.env);
+
+        // Alice: 3 votes + 1 proposal + full bonus = 55; Bob: 1 vote = 10.
+        for i in 0..3 {
+            s.vote(&alice, 100, 10 + i);
+        }
+        s.propose(&alice, 20);
+        s.vote(&bob, 100, 30);
+
+        assert_eq!(
+            s.claim(&alice, 1, 40),
+            Err(SwapTradeError::EpochNotFinalized)
+        );
+
+        let alice_reward = s.claim(&alice, 1, WEEK).unwrap();
+        let bob_reward = s.claim(&bob, 1, WEEK).unwrap();
+        // Alice's 55/65 share is capped at 60_000.
+        assert_eq!(alice_reward, 60_000);
+        assert_eq!(bob_reward, 100_000 * 10 / 65);
+        assert_eq!(
+            TokenClient::new(&s.env, &s.token).balance(&alice),
+            alice_reward
+        );
+        assert_eq!(
+            s.at(WEEK, || Treasury::balance_of(&s.env, &s.token)),
+            1_000_000 - alice_reward - bob_reward
+        );
+
+        assert_eq!(
+            s.claim(&alice, 1, WEEK + 1),
+            Err(SwapTradeError::RewardAlreadyClaimed)
+        );
+        assert_eq!(
+            s.claim(&Address::generate(&s.env), 1, WEEK + 1),
+            Err(SwapTradeError::NoRewardsToClaim)
+        );
+    }
+
+    #[test]
+    fn test_repeat_claimers_get_diminishing_returns() {
+        let s = setup();
+        let alice = Address::generate(&s.env);
+        let bob = Address::generate(&s.env);
+        s.at(0, || {
+            let config = RewardConfig {
+                diminishing_returns_bps: 8_000,
+                max_reward_per_participant: 100_000,
+                ..s.config()
+            };
+            RewardManager::set_config(&s.env, config).unwrap();
+        });
+
+        s.vote(&alice, 100, 10);
+        s.vote(&bob, 100, 10);
+        assert_eq!(s.claim(&alice, 1, WEEK).unwrap(), 50_000);
+
+        s.vote(&alice, 100, WEEK + 10);
+        s.vote(&bob, 100, WEEK + 10);
+        s.at(2 * WEEK, || RewardManager::sync_epoch(&s.env))
+            .unwrap();
+        assert_eq!(
+            s.at(2 * WEEK, || RewardManager::pending_reward(
+                &s.env, &alice, 2
+            )),
+            40_000
+        );
+        assert_eq!(s.claim(&bob, 2, 2 * WEEK).unwrap(), 50_000);
     }
 
     #[test]
     fn test_calculate_reward() {
-        let mut mgr = setup();
+        let s = setup();
+        let alice = Address::generate(&s.env);
 
         // Alice: 5 votes, 2 proposals = high participation
         for i in 0..5 {
-            mgr.record_vote_cast("alice", 100, 100 + i).unwrap();
+            s.vote(&alice, 100, 100 + i);
         }
-        mgr.record_proposal_created("alice", 100).unwrap();
-        mgr.record_proposal_created("alice", 110).unwrap();
-
-        // Finalize epoch
-        mgr.finalize_epoch(1, 700000).unwrap();
-
-        let reward = mgr.calculate_reward("alice", 1);
-        assert!(reward > 0);
-        assert!(reward <= 10_000); // Within cap
+        s.propose(&alice, 100);
+        s.propose(&alice, 110);
+
+        s.at(WEEK, || {
+            RewardManager::sync_epoch(&s.env).unwrap();
+            let reward = RewardManager::calculate_reward(&s.env, &alice, 1);
+            assert!(reward > 0);
+            assert!(reward <= 60_000); // Within cap
+        });
     }
 
     #[test]
     fn test_claim_rewards() {
-        let mut mgr = setup();
-
-        mgr.record_vote_cast("alice", 200, 100).unwrap();
-        mgr.record_proposal_created("alice", 100).unwrap();
-        mgr.finalize_epoch(1, 700000).unwrap();
+        let s = setup();
+        let alice = Address::generate(&s.env);
+        s.vote(&alice, 200, 100);
+        s.propose(&alice, 100);
 
-        let amount = mgr.claim_rewards("alice", 1, 700001).unwrap();
+        let amount = s.claim(&alice, 1, WEEK + 1).unwrap();
         assert!(amount > 0);
-        assert_eq!(mgr.total_distributed(), amount);
+        let epoch = s.at(WEEK + 1, || RewardManager::get_epoch(&s.env, 1).unwrap());
+        assert_eq!(epoch.rewards_distributed, amount);
     }
 
     #[test]
     fn test_claim_before_finalization_fails() {
-        let mut mgr = setup();
-        mgr.record_vote_cast("alice", 200, 100).unwrap();
+        let s = setup();
+        let alice = Address::generate(&s.env);
+        s.vote(&alice, 200, 100);
 
-        let result = mgr.claim_rewards("alice", 1, 200);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("not yet finalized"));
+        assert_eq!(
+            s.claim(&alice, 1, 200),
+            Err(SwapTradeError::EpochNotFinalized)
+        );
+        assert_eq!(
+            s.claim(&alice, 2, WEEK + 1),
+            Err(SwapTradeError::EpochNotFinalized)
+        );
     }
 
     #[test]
-    fn test_claim_cooldown() {
-        let mut mgr = setup();
-        mgr.record_vote_cast("alice", 200, 100).unwrap();
-        mgr.finalize_epoch(1, 700000).unwrap();
-
-        mgr.claim_rewards("alice", 1, 700001).unwrap();
-
-        // Try again immediately
-        let result = mgr.claim_rewards("alice", 1, 700002);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("cooldown"));
-
-        // After cooldown
-        let amount = mgr.claim_rewards("alice", 1, 700001 + 61).unwrap();
-        assert!(amount > 0);
+    fn test_claim_only_once_per_epoch() {
+        let s = setup();
+        let alice = Address::generate(&s.env);
+        s.vote(&alice, 200, 100);
+
+        s.claim(&alice, 1, WEEK + 1).unwrap();
+        assert_eq!(
+            s.claim(&alice, 1, WEEK + 2),
+            Err(SwapTradeError::RewardAlreadyClaimed)
+        );
+        assert_eq!(
+            s.claim(&alice, 1, 3 * WEEK),
+            Err(SwapTradeError::RewardAlreadyClaimed)
+        );
     }
 
     #[test]
     fn test_no_rewards_without_participation() {
-        let mut mgr = setup();
-        mgr.finalize_epoch(1, 700000).unwrap();
-
-        let result = mgr.claim_rewards("nobody", 1, 700001);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("no rewards"));
+        let s = setup();
+        assert_eq!(
+            s.claim(&Address::generate(&s.env), 1, WEEK + 1),
+            Err(SwapTradeError::NoRewardsToClaim)
+        );
     }
 
     #[test]
     fn test_epoch_transition() {
-        let mut mgr = setup();
-
-        mgr.record_vote_cast("alice", 100, 100).unwrap();
+        let s = setup();
+        let alice = Address::generate(&s.env);
+        s.vote(&alice, 100, 100);
 
-        // Force epoch transition by ticking past end
-        let epoch = mgr.get_epoch(1).unwrap();
-        let end_time = epoch.end_time;
-
-        mgr.ensure_current_epoch(end_time + 1).unwrap();
-        assert_eq!(mgr.current_epoch_id(), 2);
+        let end_time = s.at(100, || {
+            RewardManager::get_epoch(&s.env, 1).unwrap().end_time
+        });
+        let next = s
+            .at(end_time + 1, || RewardManager::sync_epoch(&s.env))
+            .unwrap();
+        assert_eq!(next, 2);
 
         // Previous epoch finalized
-        assert!(mgr.get_epoch(1).unwrap().finalized);
+        s.at(end_time + 1, || {
+            assert!(RewardManager::get_epoch(&s.env, 1).unwrap().finalized);
+            let epoch = RewardManager::get_epoch(&s.env, 2).unwrap();
+            assert_eq!(epoch.start_time, end_time + 1);
+            assert!(!epoch.finalized);
+        });
     }
 
     #[test]
     fn test_all_time_stats() {
-        let mut mgr = setup();
-
-        mgr.record_proposal_created("alice", 100).unwrap();
-        mgr.record_vote_cast("alice", 100, 100).unwrap();
-        mgr.finalize_epoch(1, 700000).unwrap();
-        mgr.claim_rewards("alice", 1, 700001).unwrap();
-
-        let stats = mgr.all_time_stats("alice").unwrap();
-        assert_eq!(stats.epochs_participated, 1);
+        let s = setup();
+        let alice = Address::generate(&s.env);
+        s.propose(&alice, 100);
+        s.vote(&alice, 100, 100);
+        s.claim(&alice, 1, WEEK + 1).unwrap();
+
+        let stats = s.at(WEEK + 1, || RewardManager::all_time_stats(&s.env, &alice));
+        assert_eq!(stats.epochs_claimed, 1);
         assert!(stats.total_rewards_claimed > 0);
         assert_eq!(stats.total_proposals_created, 1);
         assert_eq!(stats.total_votes_cast, 1);
@@ -669,93 +823,86 @@ mod tests {
 
     #[test]
     fn test_pending_reward() {
-        let mut mgr = setup();
+        let s = setup();
+        let alice = Address::generate(&s.env);
+        let pending = |now| s.at(now, || RewardManager::pending_reward(&s.env, &alice, 1));
 
-        assert_eq!(mgr.pending_reward("alice", 1), 0);
+        assert_eq!(pending(0), 0);
+        s.vote(&alice, 200, 100);
+        assert!(pending(100) > 0);
 
-        mgr.record_vote_cast("alice", 200, 100).unwrap();
-        let pending = mgr.pending_reward("alice", 1);
-        assert!(pending > 0);
+        s.claim(&alice, 1, WEEK + 1).unwrap();
+        assert_eq!(pending(WEEK + 1), 0);
     }
 
     #[test]
     fn test_delegation_recorded() {
-        let mut mgr = setup();
-
-        mgr.record_delegation_received("bob_delegate", 100).unwrap();
-        let record = mgr.participation_record("bob_delegate", 1).unwrap();
-        assert_eq!(record.delegated_votes_received, 1);
+        let s = setup();
+        let delegate = Address::generate(&s.env);
+        s.at(100, || {
+            RewardManager::record_delegation_received(&s.env, &delegate, 100);
+            // Below the minimum power: no credit.
+            RewardManager::record_delegation_received(&s.env, &delegate, 10);
+        });
+        assert_eq!(s.record(&delegate, 1).delegations_received, 1);
     }
 
     #[test]
     fn test_reward_cap() {
-        let config = RewardConfig {
-            max_reward_per_participant: 500,
-            epoch_reward_pool: 1_000_000,
-            ..RewardConfig::default()
-        };
-        let mut mgr = RewardManager::new(config, 0);
+        let s = setup();
+        let alice = Address::generate(&s.env);
+        s.at(0, || {
+            let config = RewardConfig {
+                max_reward_per_participant: 500,
+                ..s.config()
+            };
+            RewardManager::set_config(&s.env, config).unwrap();
+        });
 
         // Maximum participation
         for i in 0..20 {
-            mgr.record_vote_cast("alice", 10_000, 100 + i).unwrap();
+            s.vote(&alice, 10_000, 100 + i);
         }
         for i in 0..10 {
-            mgr.record_proposal_created("alice", 100 + i).unwrap();
+            s.propose(&alice, 100 + i);
         }
-        mgr.finalize_epoch(1, 700000).unwrap();
-
-        let reward = mgr.calculate_reward("alice", 1);
-        assert!(reward <= 500);
+        assert_eq!(s.claim(&alice, 1, WEEK + 1).unwrap(), 500);
     }
 
     #[test]
-    fn test_claim_history() {
-        let mut mgr = setup();
-
-        mgr.record_vote_cast("alice", 200, 100).unwrap();
-        mgr.finalize_epoch(1, 700000).unwrap();
-        mgr.claim_rewards("alice", 1, 700001).unwrap();
-
-        assert_eq!(mgr.claim_history_length(), 1);
-    }
-
-    #[test]
-    fn test_full_participation_bonus() {
-        let mut record = ParticipationRecord::default();
-        record.votes_cast = 3;
-        record.proposals_created = 1;
-        record.full_participation = true;
+    fn test_multiple_participants() {
+        let s = setup();
+        let alice = Address::generate(&s.env);
+        let bob = Address::generate(&s.env);
+        let carol = Address::generate(&s.env);
 
-        let score_with = record.score();
+        s.vote(&alice, 500, 100);
+        s.propose(&alice, 100);
+        s.vote(&bob, 300, 200);
+        s.vote(&carol, 100, 300);
 
-        record.full_participation = false;
-        let score_without = record.score();
+        let alice_reward = s.claim(&alice, 1, WEEK + 1).unwrap();
+        let bob_reward = s.claim(&bob, 1, WEEK + 2).unwrap();
+        let carol_reward = s.claim(&carol, 1, WEEK + 3).unwrap();
 
-        assert_eq!(score_with, score_without + 10);
+        // Alice should get more due to proposal creation
+        assert!(alice_reward > bob_reward);
+        assert_eq!(bob_reward, carol_reward);
+        assert!(alice_reward + bob_reward + carol_reward <= 100_000);
     }
 
     #[test]
-    fn test_multiple_participants() {
-        let config = RewardConfig {
-            epoch_reward_pool: 10_000,
-            max_reward_per_participant: 5_000,
-            diminishing_returns_bps: 100,
-            ..RewardConfig::default()
-        };
-        let mut mgr = RewardManager::new(config, 0);
-
-        mgr.record_vote_cast("alice", 500, 100).unwrap();
-        mgr.record_proposal_created("alice", 100).unwrap();
-        mgr.record_vote_cast("bob", 300, 200).unwrap();
-        mgr.record_vote_cast("carol", 100, 300).unwrap();
-
-        mgr.finalize_epoch(1, 700000).unwrap();
-
-        let alice_reward = mgr.claim_rewards("alice", 1, 700001).unwrap();
-        let bob_reward = mgr.claim_rewards("bob", 1, 700002).unwrap();
-
-        // Alice should get more due to proposal creation and higher voting power
-        assert!(alice_reward > bob_reward);
+    fn test_invalid_config_rejected() {
+        let s = setup();
+        s.at(0, || {
+            let config = RewardConfig {
+                epoch_duration_secs: 0,
+                ..s.config()
+            };
+            assert_eq!(
+                RewardManager::set_config(&s.env, config),
+                Err(SwapTradeError::InvalidAmount)
+            );
+        });
     }
 }
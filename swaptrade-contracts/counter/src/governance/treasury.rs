@@ -4,26 +4,32 @@
 //
 // Secure fund storage with multi-sig controls for the SwapTrade DAO.
 // All critical treasury operations require multi-signature approval.
+// Balances are real SEP-41 token holdings of this contract.
 //
 // Capabilities:
 //   - Multi-asset fund storage with per-asset accounting
 //   - Multi-sig withdrawal proposals requiring threshold approval
-//   - Governance-controlled deposit allocation
-//   - Spending limits with per-period caps
-//   - Complete audit trail of all treasury movements
-//   - Budget proposals for recurring allocations
+//   - Protocol fee inflow from pool treasury fees
+//   - Spending limits with per-period caps enforced by ledger time
+//   - Streaming and vesting payouts
+//   - Hash-chained audit trail of all treasury movements
 
-use std::collections::HashMap;
+use soroban_sdk::xdr::ToXdr;
+use soroban_sdk::{
+    contracttype, symbol_short, token, Address, Bytes, BytesN, Env, String, Symbol, Vec,
+};
 
-// ─── Types ────────────────────────────────────────────────────────────────────
+use crate::errors::SwapTradeError;
 
-/// Identifier for a treasury asset (simplified as string for non-Soroban context).
-pub type AssetId = String;
+/// Largest share of a token's treasury balance, in basis points, that one
+/// emergency withdrawal may move. Emergency proposals clear a lower
+/// approval threshold, so what they can take is bounded.
+pub const EMERGENCY_WITHDRAWAL_MAX_BPS: i128 = 1_000;
 
-/// A unique identifier for treasury operations.
-pub type OperationId = [u8; 32];
+// ─── Types ────────────────────────────────────────────────────────────────────
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[contracttype]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TreasuryOperationStatus {
     /// Proposal created, collecting signatures.
     Pending,
@@ -35,37 +41,62 @@ pub enum TreasuryOperationStatus {
     Rejected,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum ProposalType {
+#[contracttype]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreasuryProposalType {
     /// Standard withdrawal request.
     Withdrawal,
     /// Budget allocation for recurring payments.
     BudgetAllocation,
-    /// Emergency withdrawal (reduced threshold).
+    /// Emergency withdrawal (reduced threshold, capped at
+    /// `EMERGENCY_WITHDRAWAL_MAX_BPS` of the token's balance).
     EmergencyWithdrawal,
     /// Grant or bounty payout.
     Grant,
 }
 
-#[derive(Debug, Clone)]
+/// Linear release schedule for streamed or vested payouts. Nothing is
+/// claimable before `cliff`; everything is claimable from `end`. A plain
+/// stream is a schedule with `cliff == start`.
+#[contracttype]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PayoutSchedule {
+    pub start: u64,
+    pub cliff: u64,
+    pub end: u64,
+}
+
+/// How an executed proposal pays its recipient.
+#[contracttype]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Payout {
+    /// One transfer at execution.
+    Immediate,
+    /// A stream released along the schedule.
+    Scheduled(PayoutSchedule),
+}
+
+#[contracttype]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TreasuryProposal {
-    pub id: OperationId,
-    pub proposal_type: ProposalType,
-    pub proposer: String,
-    pub asset: AssetId,
-    pub amount: u128,
-    pub recipient: String,
+    pub id: u64,
+    pub proposal_type: TreasuryProposalType,
+    pub proposer: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub recipient: Address,
     pub description: String,
     pub created_at: u64,
     pub expires_at: u64,
-    pub approvals: Vec<String>,
-    pub required_approvals: usize,
+    pub approvals: Vec<Address>,
+    pub required_approvals: u32,
     pub status: TreasuryOperationStatus,
     pub executed_at: Option<u64>,
+    pub payout: Payout,
 }
 
 impl TreasuryProposal {
-    pub fn approval_count(&self) -> usize {
+    pub fn approval_count(&self) -> u32 {
         self.approvals.len()
     }
 
@@ -79,610 +110,779 @@ impl TreasuryProposal {
 }
 
 /// Per-asset balance tracking in the treasury.
-#[derive(Debug, Clone, Default)]
+#[contracttype]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct AssetBalance {
-    pub balance: u128,
-    pub total_deposited: u128,
-    pub total_withdrawn: u128,
+    pub balance: i128,
+    pub total_deposited: i128,
+    pub total_withdrawn: i128,
     pub last_deposit_at: u64,
     pub last_withdrawal_at: u64,
 }
 
 /// Spending limits per time period.
-#[derive(Debug, Clone)]
+#[contracttype]
+#[derive(Debug, Clone, PartialEq)]
 pub struct SpendingLimit {
     /// Maximum amount that can be withdrawn within the period.
-    pub max_per_period: u128,
+    pub max_per_period: i128,
     /// Duration of the spending period in seconds.
     pub period_duration_secs: u64,
     /// Amount already spent in the current period.
-    pub spent_in_period: u128,
+    pub spent_in_period: i128,
     /// Timestamp when the current period started.
     pub period_start: u64,
 }
 
 impl SpendingLimit {
-    pub fn remaining(&self, now: u64) -> u128 {
+    pub fn remaining(&self, now: u64) -> i128 {
         if now > self.period_start + self.period_duration_secs {
             // Period expired, full amount available
             self.max_per_period
         } else {
-            self.max_per_period.saturating_sub(self.spent_in_period)
+            self.max_per_period - self.spent_in_period
         }
     }
 
-    pub fn record_spend(&mut self, amount: u128, now: u64) {
+    pub fn record_spend(&mut self, amount: i128, now: u64) {
         if now > self.period_start + self.period_duration_secs {
             // Start new period
             self.period_start = now;
             self.spent_in_period = amount;
         } else {
-            self.spent_in_period = self.spent_in_period.saturating_add(amount);
+            self.spent_in_period += amount;
         }
     }
 }
 
-/// Audit log entry for treasury operations.
-#[derive(Debug, Clone)]
-pub struct TreasuryAuditEntry {
-    pub seq: u64,
-    pub timestamp: u64,
-    pub operation: TreasuryAuditOperation,
-    pub actor: String,
-    pub entry_hash: [u8; 32],
+/// An executed payout released to its recipient over time.
+#[contracttype]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreasuryStream {
+    pub id: u64,
+    pub proposal_id: u64,
+    pub token: Address,
+    pub recipient: Address,
+    pub total: i128,
+    pub claimed: i128,
+    pub schedule: PayoutSchedule,
+}
+
+impl TreasuryStream {
+    /// Amount released by `now`, including what was already claimed.
+    pub fn vested(&self, now: u64) -> i128 {
+        let s = &self.schedule;
+        if now < s.cliff {
+            0
+        } else if now >= s.end {
+            self.total
+        } else {
+            self.total * (now - s.start) as i128 / (s.end - s.start) as i128
+        }
+    }
+}
+
+/// Signer set and approval thresholds.
+#[contracttype]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreasuryConfig {
+    pub signers: Vec<Address>,
+    /// Required threshold for treasury operations.
+    pub threshold: u32,
+    /// Emergency threshold (reduced for critical operations).
+    pub emergency_threshold: u32,
 }
 
+#[contracttype]
 #[derive(Debug, Clone)]
 pub enum TreasuryAuditOperation {
-    Deposit {
-        asset: AssetId,
-        amount: u128,
-        from: String,
-    },
-    Withdrawal {
-        asset: AssetId,
-        amount: u128,
-        to: String,
-        proposal_id: String,
-    },
-    ProposalCreated {
-        proposal_id: String,
-        proposal_type: ProposalType,
-    },
-    ProposalApproved {
-        proposal_id: String,
-        approver: String,
-        approval_count: usize,
-    },
-    ProposalRejected {
-        proposal_id: String,
-        reason: String,
-    },
-    SpendingLimitSet {
-        asset: AssetId,
-        max_per_period: u128,
-    },
-    MultiSigUpdated {
-        old_threshold: usize,
-        new_threshold: usize,
-    },
-    BalanceSnapshot {
-        total_assets: usize,
-    },
+    Deposit(Address, i128, Address),
+    /// Pool treasury fees credited: (token, amount, pool_id).
+    FeesReceived(Address, i128, u64),
+    /// (token, amount, recipient, proposal_id)
+    Withdrawal(Address, i128, Address, u64),
+    /// Grant voted by governance: (token, amount, recipient, governance proposal_id)
+    Grant(Address, i128, Address, u64),
+    /// Governance participation reward: (token, amount, recipient, epoch_id)
+    ParticipationReward(Address, i128, Address, u64),
+    /// (stream_id, proposal_id)
+    StreamCreated(u64, u64),
+    /// (stream_id, amount)
+    StreamClaimed(u64, i128),
+    ProposalCreated(u64, TreasuryProposalType),
+    ProposalApproved(u64, Address, u32),
+    ProposalRejected(u64),
+    SpendingLimitSet(Address, i128),
+    /// (old_threshold, new_threshold)
+    MultiSigUpdated(u32, u32),
+    FeeTokenSet(Symbol, Address),
 }
 
-// ─── Treasury Contract ────────────────────────────────────────────────────────
+/// Head of the hash-chained audit trail. Each entry is published as an
+/// event; `hash` commits to every entry so far.
+#[contracttype]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreasuryAuditHead {
+    pub seq: u64,
+    pub hash: BytesN<32>,
+}
 
-pub struct Treasury {
-    /// Per-asset balances.
-    balances: HashMap<AssetId, AssetBalance>,
-    /// Pending and historical proposals.
-    proposals: HashMap<OperationId, TreasuryProposal>,
-    /// Multi-sig signers.
-    signers: Vec<String>,
-    /// Required threshold for treasury operations.
-    threshold: usize,
-    /// Emergency threshold (reduced for critical operations).
-    emergency_threshold: usize,
-    /// Per-asset spending limits.
-    spending_limits: HashMap<AssetId, SpendingLimit>,
-    /// Complete audit trail.
-    audit_log: Vec<TreasuryAuditEntry>,
-    /// Monotonic sequence counter.
-    seq: u64,
+#[contracttype]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreasuryKey {
+    TreasuryConfig,
+    Balance(Address),
+    Assets,
+    Limit(Address),
+    TreasuryProposal(u64),
+    TreasuryNextProposalId,
+    Stream(u64),
+    NextStreamId,
+    /// SEP-41 token backing a pool-registry token symbol.
+    FeeToken(Symbol),
+    TreasuryAuditHead,
 }
 
-impl Treasury {
-    /// Create a new treasury with the given signers and threshold.
-    pub fn new(signers: Vec<String>, threshold: usize, emergency_threshold: usize) -> Self {
-        assert!(
-            threshold > 0 && threshold <= signers.len(),
-            "threshold must be between 1 and signers.len()"
-        );
-        assert!(
-            emergency_threshold > 0 && emergency_threshold <= threshold,
-            "emergency threshold must be between 1 and normal threshold"
-        );
-        Self {
-            balances: HashMap::new(),
-            proposals: HashMap::new(),
-            signers,
-            threshold,
-            emergency_threshold,
-            spending_limits: HashMap::new(),
-            audit_log: Vec::new(),
-            seq: 0,
-        }
-    }
+// ─── Treasury Contract ────────────────────────────────────────────────────────
+
+pub struct Treasury;
 
+impl Treasury {
     // ── Signer Management ────────────────────────────────────────────────────
 
-    pub fn add_signer(&mut self, signer: String) -> Result<(), String> {
-        if self.signers.contains(&signer) {
-            return Err("signer already exists".to_string());
-        }
-        self.signers.push(signer);
+    /// Admin only: install the signer set and thresholds.
+    pub fn configure(
+        env: &Env,
+        admin: Address,
+        config: TreasuryConfig,
+    ) -> Result<(), SwapTradeError> {
+        admin.require_auth();
+        crate::admin::require_admin(env, &admin)?;
+        Self::validate(&config)?;
+        let old = Self::get_config(env).map_or(0, |c| c.threshold);
+        let new = config.threshold;
+        env.storage()
+            .persistent()
+            .set(&TreasuryKey::TreasuryConfig, &config);
+        Self::log_operation(env, TreasuryAuditOperation::MultiSigUpdated(old, new));
         Ok(())
     }
 
-    pub fn remove_signer(&mut self, signer: &str) -> Result<(), String> {
-        if self.signers.len() <= self.threshold {
-            return Err("cannot remove signer: would drop below threshold".to_string());
+    pub fn add_signer(env: &Env, admin: Address, signer: Address) -> Result<(), SwapTradeError> {
+        let mut config = Self::require_config(env)?;
+        if config.signers.contains(&signer) {
+            return Err(SwapTradeError::InvalidAddress);
         }
-        self.signers.retain(|s| s != signer);
-        Ok(())
+        config.signers.push_back(signer);
+        Self::configure(env, admin, config)
     }
 
-    pub fn update_threshold(&mut self, new_threshold: usize, now: u64) -> Result<(), String> {
-        if new_threshold == 0 || new_threshold > self.signers.len() {
-            return Err("invalid threshold".to_string());
+    pub fn remove_signer(env: &Env, admin: Address, signer: Address) -> Result<(), SwapTradeError> {
+        let mut config = Self::require_config(env)?;
+        if config.signers.len() <= config.threshold {
+            return Err(SwapTradeError::InvalidMultiSigConfig);
         }
-        let old = self.threshold;
-        self.threshold = new_threshold;
-        self.log_operation(
-            TreasuryAuditOperation::MultiSigUpdated {
-                old_threshold: old,
-                new_threshold,
-            },
-            "system".to_string(),
-            now,
-        );
-        Ok(())
+        let index = config
+            .signers
+            .first_index_of(&signer)
+            .ok_or(SwapTradeError::NotTreasurySigner)?;
+        config.signers.remove(index);
+        Self::configure(env, admin, config)
+    }
+
+    pub fn update_threshold(
+        env: &Env,
+        admin: Address,
+        new_threshold: u32,
+    ) -> Result<(), SwapTradeError> {
+        let mut config = Self::require_config(env)?;
+        config.threshold = new_threshold;
+        Self::configure(env, admin, config)
     }
 
-    pub fn is_signer(&self, signer: &str) -> bool {
-        self.signers.contains(&signer.to_string())
+    pub fn is_signer(env: &Env, signer: &Address) -> bool {
+        Self::get_config(env).map_or(false, |c| c.signers.contains(signer))
+    }
+
+    pub fn get_config(env: &Env) -> Option<TreasuryConfig> {
+        env.storage().persistent().get(&TreasuryKey::TreasuryConfig)
     }
 
     // ── Balance Management ───────────────────────────────────────────────────
 
-    /// Deposit funds into the treasury.
+    /// Transfer `amount` of `token` from `from` into the treasury.
     pub fn deposit(
-        &mut self,
-        asset: &str,
-        amount: u128,
-        from: &str,
-        now: u64,
-    ) -> Result<(), String> {
-        if amount == 0 {
-            return Err("deposit amount must be greater than zero".to_string());
+        env: &Env,
+        from: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<(), SwapTradeError> {
+        from.require_auth();
+        if amount <= 0 {
+            return Err(SwapTradeError::InvalidAmount);
         }
+        token::Client::new(env, &token).transfer(&from, &env.current_contract_address(), &amount);
+        Self::credit(env, &token, amount);
+        Self::log_operation(env, TreasuryAuditOperation::Deposit(token, amount, from));
+        Ok(())
+    }
 
-        let entry = self
-            .balances
-            .entry(asset.to_string())
-            .or_insert_with(AssetBalance::default);
+    /// Admin only: map a pool-registry token symbol to the SEP-41 token the
+    /// contract holds for it, so pool treasury fees in that symbol are
+    /// credited to the treasury.
+    pub fn set_fee_token(
+        env: &Env,
+        admin: Address,
+        symbol: Symbol,
+        token: Address,
+    ) -> Result<(), SwapTradeError> {
+        admin.require_auth();
+        crate::admin::require_admin(env, &admin)?;
+        env.storage()
+            .persistent()
+            .set(&TreasuryKey::FeeToken(symbol.clone()), &token);
+        Self::log_operation(env, TreasuryAuditOperation::FeeTokenSet(symbol, token));
+        Ok(())
+    }
 
-        entry.balance = entry.balance.checked_add(amount).ok_or("overflow")?;
-        entry.total_deposited = entry.total_deposited.saturating_add(amount);
-        entry.last_deposit_at = now;
+    /// Transfer `amount` of the token mapped for `symbol` from `from` into
+    /// the contract. Pool fees are internal accounting, so they are only
+    /// booked once the matching tokens have actually been paid in.
+    pub(crate) fn collect_pool_fees(
+        env: &Env,
+        from: &Address,
+        symbol: Symbol,
+        amount: i128,
+    ) -> Result<Address, SwapTradeError> {
+        let token = Self::fee_token(env, symbol).ok_or(SwapTradeError::FeeTokenNotMapped)?;
+        if amount > 0 {
+            token::Client::new(env, &token).transfer(
+                from,
+                &env.current_contract_address(),
+                &amount,
+            );
+        }
+        Ok(token)
+    }
 
-        self.log_operation(
-            TreasuryAuditOperation::Deposit {
-                asset: asset.to_string(),
-                amount,
-                from: from.to_string(),
-            },
-            from.to_string(),
-            now,
-        );
+    /// Book pool treasury fees paid in with `collect_pool_fees`.
+    pub(crate) fn receive_pool_fees(env: &Env, pool_id: u64, token: &Address, amount: i128) {
+        if amount > 0 {
+            Self::credit(env, token, amount);
+            Self::log_operation(
+                env,
+                TreasuryAuditOperation::FeesReceived(token.clone(), amount, pool_id),
+            );
+        }
+    }
 
-        Ok(())
+    pub fn fee_token(env: &Env, symbol: Symbol) -> Option<Address> {
+        env.storage()
+            .persistent()
+            .get(&TreasuryKey::FeeToken(symbol))
     }
 
     /// Get the balance of a specific asset.
-    pub fn balance_of(&self, asset: &str) -> u128 {
-        self.balances.get(asset).map(|b| b.balance).unwrap_or(0)
+    pub fn balance_of(env: &Env, token: &Address) -> i128 {
+        Self::get_asset_balance(env, token).balance
+    }
+
+    pub fn get_asset_balance(env: &Env, token: &Address) -> AssetBalance {
+        env.storage()
+            .persistent()
+            .get(&TreasuryKey::Balance(token.clone()))
+            .unwrap_or_default()
     }
 
-    /// Get the total value of all assets (simplified - counts unique assets).
-    pub fn total_assets(&self) -> usize {
-        self.balances.len()
+    /// Tokens the treasury has ever held.
+    pub fn assets(env: &Env) -> Vec<Address> {
+        env.storage()
+            .persistent()
+            .get(&TreasuryKey::Assets)
+            .unwrap_or_else(|| Vec::new(env))
     }
 
     // ── Spending Limits ──────────────────────────────────────────────────────
 
-    /// Set a spending limit for an asset.
+    /// Admin only: cap withdrawals of `token` per rolling period.
     pub fn set_spending_limit(
-        &mut self,
-        asset: &str,
-        max_per_period: u128,
+        env: &Env,
+        admin: Address,
+        token: Address,
+        max_per_period: i128,
         period_duration_secs: u64,
-        now: u64,
-    ) {
-        self.spending_limits.insert(
-            asset.to_string(),
-            SpendingLimit {
-                max_per_period,
-                period_duration_secs,
-                spent_in_period: 0,
-                period_start: now,
-            },
-        );
-        self.log_operation(
-            TreasuryAuditOperation::SpendingLimitSet {
-                asset: asset.to_string(),
-                max_per_period,
-            },
-            "admin".to_string(),
-            now,
+    ) -> Result<(), SwapTradeError> {
+        admin.require_auth();
+        crate::admin::require_admin(env, &admin)?;
+        if max_per_period < 0 || period_duration_secs == 0 {
+            return Err(SwapTradeError::InvalidAmount);
+        }
+        let limit = SpendingLimit {
+            max_per_period,
+            period_duration_secs,
+            spent_in_period: 0,
+            period_start: env.ledger().timestamp(),
+        };
+        env.storage()
+            .persistent()
+            .set(&TreasuryKey::Limit(token.clone()), &limit);
+        Self::log_operation(
+            env,
+            TreasuryAuditOperation::SpendingLimitSet(token, max_per_period),
         );
+        Ok(())
+    }
+
+    pub fn get_spending_limit(env: &Env, token: &Address) -> Option<SpendingLimit> {
+        env.storage()
+            .persistent()
+            .get(&TreasuryKey::Limit(token.clone()))
     }
 
-    /// Check if a withdrawal would exceed the spending limit.
-    pub fn check_spending_limit(&self, asset: &str, amount: u128, now: u64) -> bool {
-        match self.spending_limits.get(asset) {
-            Some(limit) => amount <= limit.remaining(now),
+    /// Check if a withdrawal would stay within the spending limit.
+    pub fn check_spending_limit(env: &Env, token: &Address, amount: i128) -> bool {
+        match Self::get_spending_limit(env, token) {
+            Some(limit) => amount <= limit.remaining(env.ledger().timestamp()),
             None => true, // No limit set
         }
     }
 
     // ── Proposal Management ──────────────────────────────────────────────────
 
-    /// Create a treasury withdrawal proposal.
+    /// Create a treasury withdrawal proposal. The proposer auto-approves.
+    #[allow(clippy::too_many_arguments)]
     pub fn create_proposal(
-        &mut self,
-        proposer: &str,
-        proposal_type: ProposalType,
-        asset: &str,
-        amount: u128,
-        recipient: &str,
-        description: &str,
-        now: u64,
+        env: &Env,
+        proposer: Address,
+        proposal_type: TreasuryProposalType,
+        token: Address,
+        amount: i128,
+        recipient: Address,
+        description: String,
         ttl_secs: u64,
-    ) -> Result<OperationId, String> {
-        if !self.is_signer(proposer) {
-            return Err("only signers can create treasury proposals".to_string());
+        payout: Payout,
+    ) -> Result<u64, SwapTradeError> {
+        let config = Self::require_signer(env, &proposer)?;
+        if amount <= 0 {
+            return Err(SwapTradeError::InvalidAmount);
         }
-
-        if amount == 0 {
-            return Err("amount must be greater than zero".to_string());
+        if Self::balance_of(env, &token) < amount {
+            return Err(SwapTradeError::InsufficientTreasuryBalance);
         }
-
-        let balance = self.balance_of(asset);
-        if balance < amount {
-            return Err(format!(
-                "insufficient treasury balance: {} < {}",
-                balance, amount
-            ));
+        Self::check_emergency_cap(env, proposal_type, &token, amount)?;
+        if let Payout::Scheduled(s) = &payout {
+            if s.start > s.cliff || s.cliff > s.end || s.start >= s.end {
+                return Err(SwapTradeError::InvalidPayoutSchedule);
+            }
         }
 
-        let required = match &proposal_type {
-            ProposalType::EmergencyWithdrawal => self.emergency_threshold,
-            _ => self.threshold,
+        let required = match proposal_type {
+            TreasuryProposalType::EmergencyWithdrawal => config.emergency_threshold,
+            _ => config.threshold,
         };
-
-        // Compute proposal ID from content
-        let mut id_bytes = [0u8; 32];
-        let desc_bytes = description.as_bytes();
-        let prop_bytes = proposer.as_bytes();
-        for (i, b) in desc_bytes.iter().enumerate() {
-            id_bytes[i % 32] ^= b;
-        }
-        for (i, b) in prop_bytes.iter().enumerate() {
-            id_bytes[(i + desc_bytes.len()) % 32] ^= b;
-        }
-        id_bytes[0] ^= (amount & 0xFF) as u8;
-        id_bytes[1] ^= ((amount >> 8) & 0xFF) as u8;
-        id_bytes[2] ^= (now & 0xFF) as u8;
-
-        let proposal = TreasuryProposal {
-            id: id_bytes,
-            proposal_type: proposal_type.clone(),
-            proposer: proposer.to_string(),
-            asset: asset.to_string(),
+        let id = Self::next_id(env, &TreasuryKey::TreasuryNextProposalId);
+        let now = env.ledger().timestamp();
+        let mut proposal = TreasuryProposal {
+            id,
+            proposal_type,
+            proposer: proposer.clone(),
+            token,
             amount,
-            recipient: recipient.to_string(),
-            description: description.to_string(),
+            recipient,
+            description,
             created_at: now,
             expires_at: now + ttl_secs,
-            approvals: vec![proposer.to_string()], // Proposer auto-approves
+            approvals: Vec::from_array(env, [proposer]),
             required_approvals: required,
             status: TreasuryOperationStatus::Pending,
             executed_at: None,
+            payout,
         };
+        if proposal.is_approved() {
+            proposal.status = TreasuryOperationStatus::Approved;
+        }
 
-        self.log_operation(
-            TreasuryAuditOperation::ProposalCreated {
-                proposal_id: hex_encode(&id_bytes),
-                proposal_type,
-            },
-            proposer.to_string(),
-            now,
+        Self::set_proposal(env, &proposal);
+        Self::log_operation(
+            env,
+            TreasuryAuditOperation::ProposalCreated(id, proposal_type),
         );
-
-        self.proposals.insert(id_bytes, proposal);
-
-        Ok(id_bytes)
+        Ok(id)
     }
 
-    /// Approve a treasury proposal.
+    /// Approve a treasury proposal, returning its approval count.
     pub fn approve_proposal(
-        &mut self,
-        proposal_id: &OperationId,
-        signer: &str,
-        now: u64,
-    ) -> Result<usize, String> {
-        if !self.is_signer(signer) {
-            return Err("only signers can approve treasury proposals".to_string());
+        env: &Env,
+        proposal_id: u64,
+        signer: Address,
+    ) -> Result<u32, SwapTradeError> {
+        Self::require_signer(env, &signer)?;
+        let mut proposal = Self::open_proposal(env, proposal_id)?;
+
+        if proposal.approvals.contains(&signer) {
+            return Err(SwapTradeError::AlreadyApproved);
         }
-
-        let proposal = self
-            .proposals
-            .get_mut(proposal_id)
-            .ok_or("proposal not found")?;
-
-        if proposal.status == TreasuryOperationStatus::Executed {
-            return Err("proposal already executed".to_string());
-        }
-
-        if proposal.status == TreasuryOperationStatus::Rejected {
-            return Err("proposal already rejected".to_string());
-        }
-
-        if proposal.is_expired(now) {
-            proposal.status = TreasuryOperationStatus::Rejected;
-            return Err("proposal has expired".to_string());
-        }
-
-        if proposal.approvals.contains(&signer.to_string()) {
-            return Err("signer has already approved this proposal".to_string());
-        }
-
-        proposal.approvals.push(signer.to_string());
+        proposal.approvals.push_back(signer.clone());
         let count = proposal.approval_count();
-
         if proposal.is_approved() {
             proposal.status = TreasuryOperationStatus::Approved;
         }
 
-        self.log_operation(
-            TreasuryAuditOperation::ProposalApproved {
-                proposal_id: hex_encode(proposal_id),
-                approver: signer.to_string(),
-                approval_count: count,
-            },
-            signer.to_string(),
-            now,
+        Self::set_proposal(env, &proposal);
+        Self::log_operation(
+            env,
+            TreasuryAuditOperation::ProposalApproved(proposal_id, signer, count),
         );
-
         Ok(count)
     }
 
-    /// Execute an approved treasury proposal.
-    pub fn execute_proposal(&mut self, proposal_id: &OperationId, now: u64) -> Result<(), String> {
-        // Validate and extract needed data first to avoid borrow conflicts
-        let (asset, amount, recipient) = {
-            let proposal = self
-                .proposals
-                .get(proposal_id)
-                .ok_or("proposal not found")?;
-
-            if proposal.status != TreasuryOperationStatus::Approved {
-                return Err(format!(
-                    "proposal not in approved state: {:?}",
-                    proposal.status
-                ));
-            }
-
-            if proposal.is_expired(now) {
-                return Err("proposal has expired".to_string());
-            }
-
-            // Check spending limit
-            if !self.check_spending_limit(&proposal.asset, proposal.amount, now) {
-                return Err("withdrawal would exceed spending limit".to_string());
+    /// Execute an approved treasury proposal. Scheduled payouts open a
+    /// stream and return its id; immediate payouts transfer and return `None`.
+    pub fn execute_proposal(env: &Env, proposal_id: u64) -> Result<Option<u64>, SwapTradeError> {
+        let mut proposal = Self::open_proposal(env, proposal_id)?;
+        if proposal.status != TreasuryOperationStatus::Approved {
+            return Err(SwapTradeError::TreasuryProposalNotApproved);
+        }
+        // The balance may have shrunk since the proposal was made.
+        Self::check_emergency_cap(env, proposal.proposal_type, &proposal.token, proposal.amount)?;
+        Self::debit(env, &proposal.token, proposal.amount)?;
+
+        let now = env.ledger().timestamp();
+        proposal.status = TreasuryOperationStatus::Executed;
+        proposal.executed_at = Some(now);
+        Self::set_proposal(env, &proposal);
+
+        let stream_id = match proposal.payout.clone() {
+            Payout::Immediate => {
+                token::Client::new(env, &proposal.token).transfer(
+                    &env.current_contract_address(),
+                    &proposal.recipient,
+                    &proposal.amount,
+                );
+                None
             }
-
-            // Check balance
-            let balance = self.balance_of(&proposal.asset);
-            if balance < proposal.amount {
-                return Err(format!(
-                    "insufficient treasury balance: {} < {}",
-                    balance, proposal.amount
-                ));
+            Payout::Scheduled(schedule) => {
+                let id = Self::next_id(env, &TreasuryKey::NextStreamId);
+                let stream = TreasuryStream {
+                    id,
+                    proposal_id,
+                    token: proposal.token.clone(),
+                    recipient: proposal.recipient.clone(),
+                    total: proposal.amount,
+                    claimed: 0,
+                    schedule,
+                };
+                env.storage()
+                    .persistent()
+                    .set(&TreasuryKey::Stream(id), &stream);
+                Self::log_operation(env, TreasuryAuditOperation::StreamCreated(id, proposal_id));
+                Some(id)
             }
-
-            (
-                proposal.asset.clone(),
-                proposal.amount,
-                proposal.recipient.clone(),
-            )
         };
 
-        // Execute the withdrawal
-        let entry = self
-            .balances
-            .get_mut(&asset)
-            .ok_or("asset not found in treasury")?;
-
-        entry.balance = entry.balance.saturating_sub(amount);
-        entry.total_withdrawn = entry.total_withdrawn.saturating_add(amount);
-        entry.last_withdrawal_at = now;
-
-        // Record spending in limit tracker
-        if let Some(limit) = self.spending_limits.get_mut(&asset) {
-            limit.record_spend(amount, now);
-        }
-
-        // Update proposal status
-        if let Some(proposal) = self.proposals.get_mut(proposal_id) {
-            proposal.status = TreasuryOperationStatus::Executed;
-            proposal.executed_at = Some(now);
-        }
+        Self::log_operation(
+            env,
+            TreasuryAuditOperation::Withdrawal(
+                proposal.token,
+                proposal.amount,
+                proposal.recipient,
+                proposal_id,
+            ),
+        );
+        Ok(stream_id)
+    }
 
-        self.log_operation(
-            TreasuryAuditOperation::Withdrawal {
-                asset,
+    /// Pay a grant approved by a governance proposal. The vote stands in
+    /// for signer approval; balance and spending limits still apply.
+    pub(crate) fn pay_grant(
+        env: &Env,
+        governance_proposal_id: u64,
+        token: &Address,
+        recipient: &Address,
+        amount: i128,
+    ) -> Result<(), SwapTradeError> {
+        Self::pay_out(env, token, recipient, amount)?;
+        Self::log_operation(
+            env,
+            TreasuryAuditOperation::Grant(
+                token.clone(),
                 amount,
-                to: recipient,
-                proposal_id: hex_encode(proposal_id),
-            },
-            "system".to_string(),
-            now,
+                recipient.clone(),
+                governance_proposal_id,
+            ),
         );
+        Ok(())
+    }
 
+    /// Pay a governance participation reward earned in `epoch_id`, within
+    /// the budget governance set for that epoch.
+    pub(crate) fn pay_reward(
+        env: &Env,
+        epoch_id: u64,
+        token: &Address,
+        recipient: &Address,
+        amount: i128,
+    ) -> Result<(), SwapTradeError> {
+        Self::pay_out(env, token, recipient, amount)?;
+        Self::log_operation(
+            env,
+            TreasuryAuditOperation::ParticipationReward(
+                token.clone(),
+                amount,
+                recipient.clone(),
+                epoch_id,
+            ),
+        );
         Ok(())
     }
 
     /// Reject a treasury proposal.
     pub fn reject_proposal(
-        &mut self,
-        proposal_id: &OperationId,
-        reason: &str,
-        now: u64,
-    ) -> Result<(), String> {
-        let proposal = self
-            .proposals
-            .get_mut(proposal_id)
-            .ok_or("proposal not found")?;
-
+        env: &Env,
+        proposal_id: u64,
+        signer: Address,
+    ) -> Result<(), SwapTradeError> {
+        Self::require_signer(env, &signer)?;
+        let mut proposal =
+            Self::get_proposal(env, proposal_id).ok_or(SwapTradeError::ProposalNotFound)?;
         if proposal.status == TreasuryOperationStatus::Executed {
-            return Err("cannot reject an executed proposal".to_string());
+            return Err(SwapTradeError::ProposalAlreadyExecuted);
         }
 
         proposal.status = TreasuryOperationStatus::Rejected;
-
-        self.log_operation(
-            TreasuryAuditOperation::ProposalRejected {
-                proposal_id: hex_encode(proposal_id),
-                reason: reason.to_string(),
-            },
-            "system".to_string(),
-            now,
-        );
-
+        Self::set_proposal(env, &proposal);
+        Self::log_operation(env, TreasuryAuditOperation::ProposalRejected(proposal_id));
         Ok(())
     }
 
-    // ── Audit Trail ──────────────────────────────────────────────────────────
-
-    fn log_operation(
-        &mut self,
-        operation: TreasuryAuditOperation,
-        actor: String,
-        now: u64,
-    ) -> [u8; 32] {
-        self.seq += 1;
-        let mut entry = TreasuryAuditEntry {
-            seq: self.seq,
-            timestamp: now,
-            operation,
-            actor,
-            entry_hash: [0u8; 32],
-        };
+    // ── Streams ──────────────────────────────────────────────────────────────
 
-        // Compute a simple hash for chain integrity
-        let mut hash_input = Vec::new();
-        hash_input.extend_from_slice(&entry.seq.to_le_bytes());
-        hash_input.extend_from_slice(&entry.timestamp.to_le_bytes());
-        hash_input.extend_from_slice(entry.actor.as_bytes());
-        if let Some(prev) = self.audit_log.last() {
-            hash_input.extend_from_slice(&prev.entry_hash);
-        }
-        entry.entry_hash = simple_hash(&hash_input);
-
-        let hash = entry.entry_hash;
-        self.audit_log.push(entry);
-        hash
-    }
-
-    /// Verify the integrity of the audit trail.
-    pub fn verify_audit_trail(&self) -> bool {
-        for (i, entry) in self.audit_log.iter().enumerate() {
-            let mut hash_input = Vec::new();
-            hash_input.extend_from_slice(&entry.seq.to_le_bytes());
-            hash_input.extend_from_slice(&entry.timestamp.to_le_bytes());
-            hash_input.extend_from_slice(entry.actor.as_bytes());
-            if i > 0 {
-                hash_input.extend_from_slice(&self.audit_log[i - 1].entry_hash);
-            }
-            if entry.entry_hash != simple_hash(&hash_input) {
-                return false;
-            }
+    /// Transfer everything vested but unclaimed to the stream's recipient.
+    pub fn claim_stream(env: &Env, stream_id: u64) -> Result<i128, SwapTradeError> {
+        let mut stream = Self::get_stream(env, stream_id).ok_or(SwapTradeError::StreamNotFound)?;
+        stream.recipient.require_auth();
+
+        let amount = stream.vested(env.ledger().timestamp()) - stream.claimed;
+        if amount <= 0 {
+            return Err(SwapTradeError::NothingVested);
         }
-        true
+        stream.claimed += amount;
+        env.storage()
+            .persistent()
+            .set(&TreasuryKey::Stream(stream_id), &stream);
+
+        token::Client::new(env, &stream.token).transfer(
+            &env.current_contract_address(),
+            &stream.recipient,
+            &amount,
+        );
+        Self::log_operation(
+            env,
+            TreasuryAuditOperation::StreamClaimed(stream_id, amount),
+        );
+        Ok(amount)
     }
 
-    /// Get the number of audit log entries.
-    pub fn audit_log_length(&self) -> usize {
-        self.audit_log.len()
+    pub fn get_stream(env: &Env, stream_id: u64) -> Option<TreasuryStream> {
+        env.storage()
+            .persistent()
+            .get(&TreasuryKey::Stream(stream_id))
     }
 
     // ── Queries ──────────────────────────────────────────────────────────────
 
     /// Get a proposal by ID.
-    pub fn get_proposal(&self, proposal_id: &OperationId) -> Option<&TreasuryProposal> {
-        self.proposals.get(proposal_id)
+    pub fn get_proposal(env: &Env, proposal_id: u64) -> Option<TreasuryProposal> {
+        env.storage()
+            .persistent()
+            .get(&TreasuryKey::TreasuryProposal(proposal_id))
     }
 
-    /// Get all pending proposals.
-    pub fn pending_proposals(&self, now: u64) -> Vec<&TreasuryProposal> {
-        self.proposals
-            .values()
-            .filter(|p| {
-                p.status != TreasuryOperationStatus::Executed
-                    && p.status != TreasuryOperationStatus::Rejected
+    /// Get the ids of all proposals still awaiting execution.
+    pub fn pending_proposals(env: &Env) -> Vec<u64> {
+        let now = env.ledger().timestamp();
+        let next: u64 = env
+            .storage()
+            .persistent()
+            .get(&TreasuryKey::TreasuryNextProposalId)
+            .unwrap_or(1);
+        let mut pending = Vec::new(env);
+        for id in 1..next {
+            if let Some(p) = Self::get_proposal(env, id) {
+                if (p.status == TreasuryOperationStatus::Pending
+                    || p.status == TreasuryOperationStatus::Approved)
                     && !p.is_expired(now)
-            })
-            .collect()
+                {
+                    pending.push_back(id);
+                }
+            }
+        }
+        pending
     }
 
-    /// Get the number of signers.
-    pub fn signer_count(&self) -> usize {
-        self.signers.len()
+    pub fn get_audit_head(env: &Env) -> Option<TreasuryAuditHead> {
+        env.storage()
+            .persistent()
+            .get(&TreasuryKey::TreasuryAuditHead)
     }
 
-    /// Get the current threshold.
-    pub fn get_threshold(&self) -> usize {
-        self.threshold
+    // ── Internal ─────────────────────────────────────────────────────────────
+
+    fn validate(config: &TreasuryConfig) -> Result<(), SwapTradeError> {
+        if config.threshold == 0
+            || config.threshold > config.signers.len()
+            || config.emergency_threshold == 0
+            || config.emergency_threshold > config.threshold
+        {
+            return Err(SwapTradeError::InvalidMultiSigConfig);
+        }
+        Ok(())
     }
 
-    /// Get the emergency threshold.
-    pub fn get_emergency_threshold(&self) -> usize {
-        self.emergency_threshold
+    fn require_config(env: &Env) -> Result<TreasuryConfig, SwapTradeError> {
+        Self::get_config(env).ok_or(SwapTradeError::TreasuryNotConfigured)
     }
-}
 
-// ─── Utility ──────────────────────────────────────────────────────────────────
+    fn require_signer(env: &Env, signer: &Address) -> Result<TreasuryConfig, SwapTradeError> {
+        signer.require_auth();
+        let config = Self::require_config(env)?;
+        if !config.signers.contains(signer) {
+            return Err(SwapTradeError::NotTreasurySigner);
+        }
+        Ok(config)
+    }
 
-fn simple_hash(data: &[u8]) -> [u8; 32] {
-    // FNV-1a inspired simple hash for audit trail integrity
-    let mut hash: [u8; 32] = [0x6b; 32]; // Init with a non-zero constant
-    for (i, &byte) in data.iter().enumerate() {
-        hash[i % 32] = hash[i % 32].wrapping_mul(31).wrapping_add(byte);
-        hash[(i + 13) % 32] = hash[(i + 13) % 32]
-            .wrapping_mul(17)
-            .wrapping_add(byte ^ 0xAA);
+    /// Load a proposal that can still be approved or executed. Expired
+    /// proposals are marked rejected.
+    fn open_proposal(env: &Env, proposal_id: u64) -> Result<TreasuryProposal, SwapTradeError> {
+        let mut proposal =
+            Self::get_proposal(env, proposal_id).ok_or(SwapTradeError::ProposalNotFound)?;
+        match proposal.status {
+            TreasuryOperationStatus::Executed => Err(SwapTradeError::ProposalAlreadyExecuted),
+            TreasuryOperationStatus::Rejected => Err(SwapTradeError::ProposalCanceled),
+            _ if proposal.is_expired(env.ledger().timestamp()) => {
+                proposal.status = TreasuryOperationStatus::Rejected;
+                Self::set_proposal(env, &proposal);
+                Err(SwapTradeError::TreasuryProposalExpired)
+            }
+            _ => Ok(proposal),
+        }
+    }
+
+    fn set_proposal(env: &Env, proposal: &TreasuryProposal) {
+        env.storage()
+            .persistent()
+            .set(&TreasuryKey::TreasuryProposal(proposal.id), proposal);
+    }
+
+    fn next_id(env: &Env, key: &TreasuryKey) -> u64 {
+        let id: u64 = env.storage().persistent().get(key).unwrap_or(1);
+        env.storage().persistent().set(key, &(id + 1));
+        id
+    }
+
+    fn pay_out(
+        env: &Env,
+        token: &Address,
+        recipient: &Address,
+        amount: i128,
+    ) -> Result<(), SwapTradeError> {
+        if amount <= 0 {
+            return Err(SwapTradeError::InvalidAmount);
+        }
+        Self::debit(env, token, amount)?;
+        token::Client::new(env, token).transfer(
+            &env.current_contract_address(),
+            recipient,
+            &amount,
+        );
+        Ok(())
+    }
+
+    /// Take `amount` out of the books for `token`, within balance and the
+    /// spending limit.
+    fn debit(env: &Env, token: &Address, amount: i128) -> Result<(), SwapTradeError> {
+        if !Self::check_spending_limit(env, token, amount) {
+            return Err(SwapTradeError::SpendingLimitExceeded);
+        }
+
+        let now = env.ledger().timestamp();
+        let mut entry = Self::get_asset_balance(env, token);
+        if entry.balance < amount {
+            return Err(SwapTradeError::InsufficientTreasuryBalance);
+        }
+        entry.balance -= amount;
+        entry.total_withdrawn += amount;
+        entry.last_withdrawal_at = now;
+        env.storage()
+            .persistent()
+            .set(&TreasuryKey::Balance(token.clone()), &entry);
+
+        // Record spending in limit tracker
+        if let Some(mut limit) = Self::get_spending_limit(env, token) {
+            limit.record_spend(amount, now);
+            env.storage()
+                .persistent()
+                .set(&TreasuryKey::Limit(token.clone()), &limit);
+        }
+        Ok(())
     }
-    hash
-}
 
-fn hex_encode(bytes: &[u8; 32]) -> String {
-    bytes
-        .iter()
-        .map(|b| format!("{:02x}", b))
-        .collect::<String>()
+    fn check_emergency_cap(
+        env: &Env,
+        proposal_type: TreasuryProposalType,
+        token: &Address,
+        amount: i128,
+    ) -> Result<(), SwapTradeError> {
+        if proposal_type == TreasuryProposalType::EmergencyWithdrawal
+            && amount > Self::balance_of(env, token) * EMERGENCY_WITHDRAWAL_MAX_BPS / 10_000
+        {
+            return Err(SwapTradeError::EmergencyWithdrawalTooLarge);
+        }
+        Ok(())
+    }
+
+    fn credit(env: &Env, token: &Address, amount: i128) {
+        let mut entry = Self::get_asset_balance(env, token);
+        if entry.total_deposited == 0 {
+            let mut assets = Self::assets(env);
+            if !assets.contains(token) {
+                assets.push_back(token.clone());
+                env.storage()
+                    .persistent()
+                    .set(&TreasuryKey::Assets, &assets);
+            }
+        }
+        entry.balance += amount;
+        entry.total_deposited += amount;
+        entry.last_deposit_at = env.ledger().timestamp();
+        env.storage()
+            .persistent()
+            .set(&TreasuryKey::Balance(token.clone()), &entry);
+    }
+
+    /// Append an entry to the hash-chained audit trail and publish it.
+    fn log_operation(env: &Env, operation: TreasuryAuditOperation) {
+        let now = env.ledger().timestamp();
+        let head = Self::get_audit_head(env);
+        let seq = head.as_ref().map_or(0, |h| h.seq) + 1;
+
+        let mut input = Bytes::new(env);
+        input.extend_from_array(&seq.to_be_bytes());
+        input.extend_from_array(&now.to_be_bytes());
+        if let Some(prev) = head {
+            input.append(&Bytes::from(prev.hash));
+        }
+        input.append(&operation.clone().to_xdr(env));
+        let hash: BytesN<32> = env.crypto().sha256(&input).into();
+
+        env.storage().persistent().set(
+            &TreasuryKey::TreasuryAuditHead,
+            &TreasuryAuditHead { seq, hash },
+        );
+        env.events()
+            .publish((symbol_short!("tr_audit"), seq), (operation, now));
+    }
 }
 
 // ─── Tests ────────────────────────────────────────────────────────────────────
@@ -690,549 +890,631 @@ fn hex_encode(bytes: &[u8; 32]) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::storage::ADMIN_KEY;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::token::{StellarAssetClient, TokenClient};
+
+    struct Setup {
+        env: Env,
+        contract_id: Address,
+        admin: Address,
+        signers: Vec<Address>,
+        xlm: Address,
+        usdc: Address,
+    }
+
+    impl Setup {
+        fn signer(&self, i: u32) -> Address {
+            self.signers.get(i).unwrap()
+        }
+
+        fn at<T>(&self, now: u64, f: impl FnOnce() -> T) -> T {
+            self.env.ledger().with_mut(|l| l.timestamp = now);
+            self.env.as_contract(&self.contract_id, f)
+        }
+
+        fn deposit(&self, token: &Address, amount: i128, now: u64) {
+            let funder = Address::generate(&self.env);
+            StellarAssetClient::new(&self.env, token).mint(&funder, &amount);
+            self.at(now, || {
+                Treasury::deposit(&self.env, funder, token.clone(), amount)
+            })
+            .unwrap();
+        }
+
+        fn propose(
+            &self,
+            proposal_type: TreasuryProposalType,
+            amount: i128,
+            recipient: &Address,
+            now: u64,
+        ) -> Result<u64, SwapTradeError> {
+            self.at(now, || {
+                Treasury::create_proposal(
+                    &self.env,
+                    self.signer(0),
+                    proposal_type,
+                    self.xlm.clone(),
+                    amount,
+                    recipient.clone(),
+                    String::from_str(&self.env, "Grant for community project"),
+                    86400 * 7,
+                    Payout::Immediate,
+                )
+            })
+        }
+
+        fn approve(&self, id: u64, signer: u32, now: u64) -> Result<u32, SwapTradeError> {
+            self.at(now, || {
+                Treasury::approve_proposal(&self.env, id, self.signer(signer))
+            })
+        }
+
+        fn execute(&self, id: u64, now: u64) -> Result<Option<u64>, SwapTradeError> {
+            self.at(now, || Treasury::execute_proposal(&self.env, id))
+        }
+
+        fn balance(&self, token: &Address) -> i128 {
+            self.at(self.env.ledger().timestamp(), || {
+                Treasury::balance_of(&self.env, token)
+            })
+        }
+
+        fn wallet(&self, token: &Address, owner: &Address) -> i128 {
+            TokenClient::new(&self.env, token).balance(owner)
+        }
+    }
+
+    fn setup() -> Setup {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register(crate::CounterContract, ());
+        let admin = Address::generate(&env);
+        let mut signers = Vec::new(&env);
+        for _ in 0..5 {
+            signers.push_back(Address::generate(&env));
+        }
+        let xlm = env
+            .register_stellar_asset_contract_v2(admin.clone())
+            .address();
+        let usdc = env
+            .register_stellar_asset_contract_v2(admin.clone())
+            .address();
+
+        env.as_contract(&contract_id, || {
+            env.storage().persistent().set(&ADMIN_KEY, &admin);
+            Treasury::configure(
+                &env,
+                admin.clone(),
+                TreasuryConfig {
+                    signers: signers.clone(),
+                    threshold: 3,
+                    emergency_threshold: 2,
+                },
+            )
+            .unwrap();
+        });
 
-    fn setup() -> Treasury {
-        let signers = vec![
-            "alice".to_string(),
-            "bob".to_string(),
-            "carol".to_string(),
-            "dave".to_string(),
-            "eve".to_string(),
-        ];
-        Treasury::new(signers, 3, 2)
+        Setup {
+            env,
+            contract_id,
+            admin,
+            signers,
+            xlm,
+            usdc,
+        }
     }
 
     #[test]
     fn test_treasury_creation() {
-        let treasury = setup();
-        assert_eq!(treasury.signer_count(), 5);
-        assert_eq!(treasury.get_threshold(), 3);
-        assert_eq!(treasury.get_emergency_threshold(), 2);
+        let s = setup();
+        let config = s.at(0, || Treasury::get_config(&s.env)).unwrap();
+        assert_eq!(config.signers.len(), 5);
+        assert_eq!(config.threshold, 3);
+        assert_eq!(config.emergency_threshold, 2);
     }
 
     #[test]
     fn test_deposit() {
-        let mut treasury = setup();
-        treasury
-            .deposit("XLM", 1_000_000, "foundation", 100)
-            .unwrap();
-        assert_eq!(treasury.balance_of("XLM"), 1_000_000);
+        let s = setup();
+        s.deposit(&s.xlm, 1_000_000, 100);
+        assert_eq!(s.balance(&s.xlm), 1_000_000);
+        assert_eq!(s.wallet(&s.xlm, &s.contract_id), 1_000_000);
     }
 
     #[test]
     fn test_zero_deposit_fails() {
-        let mut treasury = setup();
-        assert!(treasury.deposit("XLM", 0, "foundation", 100).is_err());
+        let s = setup();
+        let funder = Address::generate(&s.env);
+        let result = s.at(100, || Treasury::deposit(&s.env, funder, s.xlm.clone(), 0));
+        assert_eq!(result, Err(SwapTradeError::InvalidAmount));
     }
 
     #[test]
     fn test_multiple_deposits() {
-        let mut treasury = setup();
-        treasury.deposit("XLM", 500_000, "foundation", 100).unwrap();
-        treasury.deposit("XLM", 300_000, "donor", 200).unwrap();
-        treasury
-            .deposit("USDC", 100_000, "foundation", 300)
-            .unwrap();
-
-        assert_eq!(treasury.balance_of("XLM"), 800_000);
-        assert_eq!(treasury.balance_of("USDC"), 100_000);
-        assert_eq!(treasury.total_assets(), 2);
+        let s = setup();
+        s.deposit(&s.xlm, 500_000, 100);
+        s.deposit(&s.xlm, 300_000, 200);
+        s.deposit(&s.usdc, 100_000, 300);
+
+        assert_eq!(s.balance(&s.xlm), 800_000);
+        assert_eq!(s.balance(&s.usdc), 100_000);
+        assert_eq!(s.at(300, || Treasury::assets(&s.env)).len(), 2);
     }
 
     #[test]
     fn test_create_proposal() {
-        let mut treasury = setup();
-        treasury
-            .deposit("XLM", 1_000_000, "foundation", 100)
-            .unwrap();
+        let s = setup();
+        s.deposit(&s.xlm, 1_000_000, 100);
+        let recipient = Address::generate(&s.env);
 
-        let proposal_id = treasury
-            .create_proposal(
-                "alice",
-                ProposalType::Withdrawal,
-                "XLM",
-                100_000,
-                "recipient1",
-                "Grant for community project",
-                200,
-                86400 * 7,
-            )
+        let id = s
+            .propose(TreasuryProposalType::Withdrawal, 100_000, &recipient, 200)
             .unwrap();
-
-        let proposal = treasury.get_proposal(&proposal_id).unwrap();
-        assert_eq!(proposal.proposal_type, ProposalType::Withdrawal);
-        assert_eq!(proposal.amount, 100_000);
+        let proposal = s.at(200, || Treasury::get_proposal(&s.env, id)).unwrap();
+        assert_eq!(proposal.status, TreasuryOperationStatus::Pending);
+        assert_eq!(proposal.approval_count(), 1); // Proposer auto-approves
         assert_eq!(proposal.required_approvals, 3);
-        assert_eq!(proposal.approval_count(), 1); // proposer auto-approves
     }
 
     #[test]
     fn test_non_signer_cannot_propose() {
-        let mut treasury = setup();
-        treasury
-            .deposit("XLM", 1_000_000, "foundation", 100)
-            .unwrap();
-
-        let result = treasury.create_proposal(
-            "frank",
-            ProposalType::Withdrawal,
-            "XLM",
-            100_000,
-            "recipient1",
-            "Test",
-            200,
-            86400 * 7,
-        );
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("only signers"));
+        let s = setup();
+        s.deposit(&s.xlm, 1_000_000, 100);
+        let outsider = Address::generate(&s.env);
+
+        let result = s.at(200, || {
+            Treasury::create_proposal(
+                &s.env,
+                outsider.clone(),
+                TreasuryProposalType::Withdrawal,
+                s.xlm.clone(),
+                100_000,
+                outsider.clone(),
+                String::from_str(&s.env, "Steal funds"),
+                86400,
+                Payout::Immediate,
+            )
+        });
+        assert_eq!(result, Err(SwapTradeError::NotTreasurySigner));
     }
 
     #[test]
     fn test_insufficient_balance_for_proposal() {
-        let mut treasury = setup();
-        treasury.deposit("XLM", 50_000, "foundation", 100).unwrap();
-
-        let result = treasury.create_proposal(
-            "alice",
-            ProposalType::Withdrawal,
-            "XLM",
-            100_000,
-            "recipient1",
-            "Too much",
-            200,
-            86400 * 7,
+        let s = setup();
+        s.deposit(&s.xlm, 100, 100);
+        let recipient = Address::generate(&s.env);
+        assert_eq!(
+            s.propose(TreasuryProposalType::Withdrawal, 1_000, &recipient, 200),
+            Err(SwapTradeError::InsufficientTreasuryBalance)
         );
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("insufficient"));
     }
 
     #[test]
     fn test_approve_proposal() {
-        let mut treasury = setup();
-        treasury
-            .deposit("XLM", 1_000_000, "foundation", 100)
+        let s = setup();
+        s.deposit(&s.xlm, 1_000_000, 100);
+        let recipient = Address::generate(&s.env);
+        let id = s
+            .propose(TreasuryProposalType::Withdrawal, 100_000, &recipient, 200)
             .unwrap();
 
-        let proposal_id = treasury
-            .create_proposal(
-                "alice",
-                ProposalType::Withdrawal,
-                "XLM",
-                100_000,
-                "recipient1",
-                "Grant",
-                200,
-                86400 * 7,
-            )
-            .unwrap();
-
-        // Bob approves
-        let count = treasury.approve_proposal(&proposal_id, "bob", 300).unwrap();
-        assert_eq!(count, 2);
-
-        // Carol approves - reaches threshold
-        let count = treasury
-            .approve_proposal(&proposal_id, "carol", 300)
-            .unwrap();
-        assert_eq!(count, 3);
-
-        // Proposal should now be approved
-        let proposal = treasury.get_proposal(&proposal_id).unwrap();
-        assert_eq!(proposal.status, TreasuryOperationStatus::Approved);
+        assert_eq!(s.approve(id, 1, 300), Ok(2));
+        let status = s
+            .at(300, || Treasury::get_proposal(&s.env, id))
+            .unwrap()
+            .status;
+        assert_eq!(status, TreasuryOperationStatus::Pending);
+
+        assert_eq!(s.approve(id, 2, 400), Ok(3));
+        let status = s
+            .at(400, || Treasury::get_proposal(&s.env, id))
+            .unwrap()
+            .status;
+        assert_eq!(status, TreasuryOperationStatus::Approved);
     }
 
     #[test]
     fn test_duplicate_approval_fails() {
-        let mut treasury = setup();
-        treasury
-            .deposit("XLM", 1_000_000, "foundation", 100)
-            .unwrap();
-
-        let proposal_id = treasury
-            .create_proposal(
-                "alice",
-                ProposalType::Withdrawal,
-                "XLM",
-                100_000,
-                "recipient1",
-                "Grant",
-                200,
-                86400 * 7,
-            )
+        let s = setup();
+        s.deposit(&s.xlm, 1_000_000, 100);
+        let recipient = Address::generate(&s.env);
+        let id = s
+            .propose(TreasuryProposalType::Withdrawal, 100_000, &recipient, 200)
             .unwrap();
 
-        // Alice (proposer) tries to approve again
-        let result = treasury.approve_proposal(&proposal_id, "alice", 300);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("already approved"));
+        // Proposer already approved
+        assert_eq!(s.approve(id, 0, 300), Err(SwapTradeError::AlreadyApproved));
     }
 
     #[test]
     fn test_execute_proposal() {
-        let mut treasury = setup();
-        treasury
-            .deposit("XLM", 1_000_000, "foundation", 100)
+        let s = setup();
+        s.deposit(&s.xlm, 1_000_000, 100);
+        let recipient = Address::generate(&s.env);
+        let id = s
+            .propose(TreasuryProposalType::Withdrawal, 100_000, &recipient, 200)
             .unwrap();
+        s.approve(id, 1, 300).unwrap();
+        s.approve(id, 2, 300).unwrap();
 
-        let proposal_id = treasury
-            .create_proposal(
-                "alice",
-                ProposalType::Withdrawal,
-                "XLM",
-                100_000,
-                "recipient1",
-                "Grant",
-                200,
-                86400 * 7,
-            )
-            .unwrap();
-
-        treasury.approve_proposal(&proposal_id, "bob", 300).unwrap();
-        treasury
-            .approve_proposal(&proposal_id, "carol", 300)
-            .unwrap();
+        assert_eq!(s.execute(id, 400), Ok(None));
+        assert_eq!(s.balance(&s.xlm), 900_000);
+        assert_eq!(s.wallet(&s.xlm, &recipient), 100_000);
 
-        treasury.execute_proposal(&proposal_id, 400).unwrap();
-
-        assert_eq!(treasury.balance_of("XLM"), 900_000);
-        let proposal = treasury.get_proposal(&proposal_id).unwrap();
+        let proposal = s.at(400, || Treasury::get_proposal(&s.env, id)).unwrap();
         assert_eq!(proposal.status, TreasuryOperationStatus::Executed);
         assert_eq!(proposal.executed_at, Some(400));
     }
 
     #[test]
     fn test_execute_unapproved_proposal_fails() {
-        let mut treasury = setup();
-        treasury
-            .deposit("XLM", 1_000_000, "foundation", 100)
-            .unwrap();
-
-        let proposal_id = treasury
-            .create_proposal(
-                "alice",
-                ProposalType::Withdrawal,
-                "XLM",
-                100_000,
-                "recipient1",
-                "Grant",
-                200,
-                86400 * 7,
-            )
+        let s = setup();
+        s.deposit(&s.xlm, 1_000_000, 100);
+        let recipient = Address::generate(&s.env);
+        let id = s
+            .propose(TreasuryProposalType::Withdrawal, 100_000, &recipient, 200)
             .unwrap();
-
-        // Only 1 approval (proposer), not enough for threshold
-        let result = treasury.execute_proposal(&proposal_id, 300);
-        assert!(result.is_err());
+        assert_eq!(
+            s.execute(id, 300),
+            Err(SwapTradeError::TreasuryProposalNotApproved)
+        );
     }
 
     #[test]
     fn test_expired_proposal_rejected() {
-        let mut treasury = setup();
-        treasury
-            .deposit("XLM", 1_000_000, "foundation", 100)
-            .unwrap();
-
-        let proposal_id = treasury
-            .create_proposal(
-                "alice",
-                ProposalType::Withdrawal,
-                "XLM",
-                100_000,
-                "recipient1",
-                "Grant",
-                200,
-                60, // 60 second TTL
-            )
+        let s = setup();
+        s.deposit(&s.xlm, 1_000_000, 100);
+        let recipient = Address::generate(&s.env);
+        let id = s
+            .propose(TreasuryProposalType::Withdrawal, 100_000, &recipient, 200)
             .unwrap();
 
-        // Try to approve after expiration
-        let result = treasury.approve_proposal(&proposal_id, "bob", 200 + 61);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("expired"));
+        let late = 200 + 86400 * 7 + 1;
+        assert_eq!(
+            s.approve(id, 1, late),
+            Err(SwapTradeError::TreasuryProposalExpired)
+        );
+        let status = s
+            .at(late, || Treasury::get_proposal(&s.env, id))
+            .unwrap()
+            .status;
+        assert_eq!(status, TreasuryOperationStatus::Rejected);
     }
 
     #[test]
     fn test_reject_proposal() {
-        let mut treasury = setup();
-        treasury
-            .deposit("XLM", 1_000_000, "foundation", 100)
+        let s = setup();
+        s.deposit(&s.xlm, 1_000_000, 100);
+        let recipient = Address::generate(&s.env);
+        let id = s
+            .propose(TreasuryProposalType::Withdrawal, 100_000, &recipient, 200)
             .unwrap();
 
-        let proposal_id = treasury
-            .create_proposal(
-                "alice",
-                ProposalType::Withdrawal,
-                "XLM",
+        s.at(300, || Treasury::reject_proposal(&s.env, id, s.signer(3)))
+            .unwrap();
+        assert_eq!(s.approve(id, 1, 400), Err(SwapTradeError::ProposalCanceled));
+    }
+
+    #[test]
+    fn test_emergency_withdrawal_lower_threshold() {
+        let s = setup();
+        s.deposit(&s.xlm, 1_000_000, 100);
+        let recipient = Address::generate(&s.env);
+        assert_eq!(
+            s.propose(
+                TreasuryProposalType::EmergencyWithdrawal,
+                100_001,
+                &recipient,
+                200,
+            ),
+            Err(SwapTradeError::EmergencyWithdrawalTooLarge)
+        );
+        let id = s
+            .propose(
+                TreasuryProposalType::EmergencyWithdrawal,
                 100_000,
-                "recipient1",
-                "Grant",
+                &recipient,
                 200,
-                86400 * 7,
             )
             .unwrap();
 
-        treasury
-            .reject_proposal(&proposal_id, "not in budget", 300)
-            .unwrap();
-
-        let proposal = treasury.get_proposal(&proposal_id).unwrap();
-        assert_eq!(proposal.status, TreasuryOperationStatus::Rejected);
+        // Emergency threshold is 2: proposer + one more
+        s.approve(id, 1, 300).unwrap();
+        assert_eq!(s.execute(id, 400), Ok(None));
+        assert_eq!(s.balance(&s.xlm), 900_000);
     }
 
     #[test]
-    fn test_emergency_withdrawal_lower_threshold() {
-        let mut treasury = setup();
-        treasury
-            .deposit("XLM", 1_000_000, "foundation", 100)
-            .unwrap();
-
-        let proposal_id = treasury
-            .create_proposal(
-                "alice",
-                ProposalType::EmergencyWithdrawal,
-                "XLM",
-                50_000,
-                "security_team",
-                "Emergency: vulnerability patch",
+    fn test_emergency_cap_rechecked_at_execution() {
+        let s = setup();
+        s.deposit(&s.xlm, 1_000_000, 100);
+        let recipient = Address::generate(&s.env);
+        let emergency = s
+            .propose(
+                TreasuryProposalType::EmergencyWithdrawal,
+                100_000,
+                &recipient,
                 200,
-                86400,
             )
             .unwrap();
+        s.approve(emergency, 1, 300).unwrap();
 
-        let proposal = treasury.get_proposal(&proposal_id).unwrap();
-        assert_eq!(proposal.required_approvals, 2); // emergency threshold
-
-        treasury.approve_proposal(&proposal_id, "bob", 300).unwrap();
-        let proposal = treasury.get_proposal(&proposal_id).unwrap();
-        assert_eq!(proposal.status, TreasuryOperationStatus::Approved);
+        // A regular withdrawal drains the balance first.
+        let regular = s
+            .propose(TreasuryProposalType::Withdrawal, 500_000, &recipient, 200)
+            .unwrap();
+        s.approve(regular, 1, 300).unwrap();
+        s.approve(regular, 2, 300).unwrap();
+        s.execute(regular, 400).unwrap();
 
-        treasury.execute_proposal(&proposal_id, 400).unwrap();
-        assert_eq!(treasury.balance_of("XLM"), 950_000);
+        assert_eq!(
+            s.execute(emergency, 400),
+            Err(SwapTradeError::EmergencyWithdrawalTooLarge)
+        );
     }
 
     #[test]
     fn test_spending_limit() {
-        let mut treasury = setup();
-        treasury
-            .deposit("XLM", 10_000_000, "foundation", 100)
+        let s = setup();
+        s.deposit(&s.xlm, 1_000_000, 100);
+        s.at(100, || {
+            Treasury::set_spending_limit(&s.env, s.admin.clone(), s.xlm.clone(), 150_000, 86400)
+        })
+        .unwrap();
+        let recipient = Address::generate(&s.env);
+
+        let first = s
+            .propose(TreasuryProposalType::Withdrawal, 100_000, &recipient, 200)
             .unwrap();
+        s.approve(first, 1, 300).unwrap();
+        s.approve(first, 2, 300).unwrap();
+        s.execute(first, 400).unwrap();
 
-        // Set limit: 500,000 XLM per day
-        treasury.set_spending_limit("XLM", 500_000, 86400, 200);
-
-        assert!(treasury.check_spending_limit("XLM", 500_000, 300));
-        assert!(!treasury.check_spending_limit("XLM", 500_001, 300));
-
-        // Execute a proposal that uses some of the limit
-        let proposal_id = treasury
-            .create_proposal(
-                "alice",
-                ProposalType::Withdrawal,
-                "XLM",
-                300_000,
-                "recipient",
-                "Grant 1",
-                200,
-                86400 * 7,
-            )
-            .unwrap();
-        treasury.approve_proposal(&proposal_id, "bob", 200).unwrap();
-        treasury
-            .approve_proposal(&proposal_id, "carol", 200)
+        let second = s
+            .propose(TreasuryProposalType::Withdrawal, 100_000, &recipient, 500)
             .unwrap();
-        treasury.execute_proposal(&proposal_id, 250).unwrap();
-
-        // Now limit should have 200,000 remaining
-        assert!(treasury.check_spending_limit("XLM", 200_000, 300));
-        assert!(!treasury.check_spending_limit("XLM", 200_001, 300));
+        s.approve(second, 1, 600).unwrap();
+        s.approve(second, 2, 600).unwrap();
+        assert_eq!(
+            s.execute(second, 700),
+            Err(SwapTradeError::SpendingLimitExceeded)
+        );
     }
 
     #[test]
     fn test_spending_limit_resets_after_period() {
-        let mut treasury = setup();
-        treasury
-            .deposit("XLM", 10_000_000, "foundation", 100)
+        let s = setup();
+        s.deposit(&s.xlm, 1_000_000, 100);
+        s.at(100, || {
+            Treasury::set_spending_limit(&s.env, s.admin.clone(), s.xlm.clone(), 100_000, 3600)
+        })
+        .unwrap();
+        let recipient = Address::generate(&s.env);
+
+        let first = s
+            .propose(TreasuryProposalType::Withdrawal, 100_000, &recipient, 200)
             .unwrap();
+        s.approve(first, 1, 200).unwrap();
+        s.approve(first, 2, 200).unwrap();
+        s.execute(first, 200).unwrap();
 
-        treasury.set_spending_limit("XLM", 500_000, 86400, 100);
-
-        // Spend full limit
-        let id = treasury
-            .create_proposal(
-                "alice",
-                ProposalType::Withdrawal,
-                "XLM",
-                500_000,
-                "recip",
-                "Spend",
-                100,
-                86400,
-            )
+        let second = s
+            .propose(TreasuryProposalType::Withdrawal, 100_000, &recipient, 300)
             .unwrap();
-        treasury.approve_proposal(&id, "bob", 100).unwrap();
-        treasury.approve_proposal(&id, "carol", 100).unwrap();
-        treasury.execute_proposal(&id, 200).unwrap();
-
-        // Over limit now
-        assert!(!treasury.check_spending_limit("XLM", 1, 300));
-
-        // After period expires, full limit restored
-        assert!(treasury.check_spending_limit("XLM", 500_000, 100 + 86401));
+        s.approve(second, 1, 300).unwrap();
+        s.approve(second, 2, 300).unwrap();
+        assert_eq!(
+            s.execute(second, 400),
+            Err(SwapTradeError::SpendingLimitExceeded)
+        );
+        // The window is measured in ledger time
+        assert_eq!(s.execute(second, 100 + 3600 + 1), Ok(None));
     }
 
     #[test]
     fn test_add_remove_signer() {
-        let mut treasury = setup();
-        treasury.add_signer("frank".to_string()).unwrap();
-        assert_eq!(treasury.signer_count(), 6);
-        assert!(treasury.is_signer("frank"));
-
-        treasury.remove_signer("frank").unwrap();
-        assert_eq!(treasury.signer_count(), 5);
-        assert!(!treasury.is_signer("frank"));
+        let s = setup();
+        let new_signer = Address::generate(&s.env);
+        s.at(0, || {
+            Treasury::add_signer(&s.env, s.admin.clone(), new_signer.clone())
+        })
+        .unwrap();
+        assert!(s.at(0, || Treasury::is_signer(&s.env, &new_signer)));
+
+        s.at(0, || {
+            Treasury::remove_signer(&s.env, s.admin.clone(), new_signer.clone())
+        })
+        .unwrap();
+        assert!(!s.at(0, || Treasury::is_signer(&s.env, &new_signer)));
     }
 
     #[test]
     fn test_cannot_remove_below_threshold() {
-        let signers = vec!["alice".to_string(), "bob".to_string(), "carol".to_string()];
-        let mut treasury = Treasury::new(signers, 3, 1);
-        let result = treasury.remove_signer("carol");
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("below threshold"));
+        let s = setup();
+        s.at(0, || Treasury::update_threshold(&s.env, s.admin.clone(), 5))
+            .unwrap();
+        assert_eq!(
+            s.at(0, || Treasury::remove_signer(
+                &s.env,
+                s.admin.clone(),
+                s.signer(0)
+            )),
+            Err(SwapTradeError::InvalidMultiSigConfig)
+        );
     }
 
     #[test]
     fn test_update_threshold() {
-        let mut treasury = setup();
-        treasury.update_threshold(4, 100).unwrap();
-        assert_eq!(treasury.get_threshold(), 4);
+        let s = setup();
+        s.at(0, || Treasury::update_threshold(&s.env, s.admin.clone(), 4))
+            .unwrap();
+        assert_eq!(
+            s.at(0, || Treasury::get_config(&s.env)).unwrap().threshold,
+            4
+        );
     }
 
     #[test]
     fn test_invalid_threshold() {
-        let mut treasury = setup();
-        assert!(treasury.update_threshold(0, 100).is_err());
-        assert!(treasury.update_threshold(10, 100).is_err()); // > signers.len()
+        let s = setup();
+        assert_eq!(
+            s.at(0, || Treasury::update_threshold(&s.env, s.admin.clone(), 0)),
+            Err(SwapTradeError::InvalidMultiSigConfig)
+        );
+        assert_eq!(
+            s.at(0, || Treasury::update_threshold(
+                &s.env,
+                s.admin.clone(),
+                10
+            )),
+            Err(SwapTradeError::InvalidMultiSigConfig)
+        );
     }
 
     #[test]
     fn test_audit_trail_integrity() {
-        let mut treasury = setup();
-        treasury
-            .deposit("XLM", 1_000_000, "foundation", 100)
-            .unwrap();
-
-        let id = treasury
-            .create_proposal(
-                "alice",
-                ProposalType::Withdrawal,
-                "XLM",
-                100_000,
-                "recip",
-                "Grant",
-                200,
-                86400 * 7,
-            )
+        let s = setup();
+        let configured = s.at(0, || Treasury::get_audit_head(&s.env)).unwrap();
+        s.deposit(&s.xlm, 1_000_000, 100);
+        let recipient = Address::generate(&s.env);
+        let id = s
+            .propose(TreasuryProposalType::Withdrawal, 100_000, &recipient, 200)
             .unwrap();
-        treasury.approve_proposal(&id, "bob", 300).unwrap();
-        treasury.approve_proposal(&id, "carol", 300).unwrap();
-        treasury.execute_proposal(&id, 400).unwrap();
+        s.approve(id, 1, 300).unwrap();
 
-        assert!(treasury.verify_audit_trail());
-        assert_eq!(treasury.audit_log_length(), 5); // deposit + create + 2 approves + execute
+        let head = s.at(300, || Treasury::get_audit_head(&s.env)).unwrap();
+        assert_eq!(head.seq, configured.seq + 3);
+        assert_ne!(head.hash, configured.hash);
     }
 
     #[test]
     fn test_pending_proposals() {
-        let mut treasury = setup();
-        treasury
-            .deposit("XLM", 1_000_000, "foundation", 100)
+        let s = setup();
+        s.deposit(&s.xlm, 1_000_000, 100);
+        let recipient = Address::generate(&s.env);
+        let first = s
+            .propose(TreasuryProposalType::Withdrawal, 100_000, &recipient, 200)
             .unwrap();
-
-        let id1 = treasury
-            .create_proposal(
-                "alice",
-                ProposalType::Withdrawal,
-                "XLM",
-                100_000,
-                "r1",
-                "Grant 1",
-                200,
-                86400 * 7,
-            )
-            .unwrap();
-        let _id2 = treasury
-            .create_proposal(
-                "bob",
-                ProposalType::Grant,
-                "XLM",
-                50_000,
-                "r2",
-                "Grant 2",
-                200,
-                86400 * 7,
-            )
+        let second = s
+            .propose(TreasuryProposalType::Grant, 50_000, &recipient, 300)
             .unwrap();
+        s.at(400, || {
+            Treasury::reject_proposal(&s.env, first, s.signer(1))
+        })
+        .unwrap();
 
-        assert_eq!(treasury.pending_proposals(300).len(), 2);
-
-        // Execute one
-        treasury.approve_proposal(&id1, "bob", 300).unwrap();
-        treasury.approve_proposal(&id1, "carol", 300).unwrap();
-        treasury.execute_proposal(&id1, 400).unwrap();
-
-        assert_eq!(treasury.pending_proposals(500).len(), 1);
+        let pending = s.at(400, || Treasury::pending_proposals(&s.env));
+        assert_eq!(pending, Vec::from_array(&s.env, [second]));
     }
 
     #[test]
     fn test_non_signer_cannot_approve() {
-        let mut treasury = setup();
-        treasury
-            .deposit("XLM", 1_000_000, "foundation", 100)
+        let s = setup();
+        s.deposit(&s.xlm, 1_000_000, 100);
+        let recipient = Address::generate(&s.env);
+        let id = s
+            .propose(TreasuryProposalType::Withdrawal, 100_000, &recipient, 200)
             .unwrap();
-
-        let id = treasury
-            .create_proposal(
-                "alice",
-                ProposalType::Withdrawal,
-                "XLM",
-                100_000,
-                "r1",
-                "Grant",
-                200,
-                86400 * 7,
-            )
-            .unwrap();
-
-        let result = treasury.approve_proposal(&id, "frank", 300);
-        assert!(result.is_err());
+        let outsider = Address::generate(&s.env);
+        assert_eq!(
+            s.at(300, || Treasury::approve_proposal(
+                &s.env,
+                id,
+                outsider.clone()
+            )),
+            Err(SwapTradeError::NotTreasurySigner)
+        );
     }
 
     #[test]
     fn test_double_execution_fails() {
-        let mut treasury = setup();
-        treasury
-            .deposit("XLM", 1_000_000, "foundation", 100)
+        let s = setup();
+        s.deposit(&s.xlm, 1_000_000, 100);
+        let recipient = Address::generate(&s.env);
+        let id = s
+            .propose(TreasuryProposalType::Withdrawal, 100_000, &recipient, 200)
             .unwrap();
+        s.approve(id, 1, 300).unwrap();
+        s.approve(id, 2, 300).unwrap();
+        s.execute(id, 400).unwrap();
+        assert_eq!(
+            s.execute(id, 500),
+            Err(SwapTradeError::ProposalAlreadyExecuted)
+        );
+    }
 
-        let id = treasury
-            .create_proposal(
-                "alice",
-                ProposalType::Withdrawal,
-                "XLM",
-                100_000,
-                "recip",
-                "Grant",
-                200,
-                86400 * 7,
-            )
+    #[test]
+    fn test_vesting_payout_releases_linearly_after_cliff() {
+        let s = setup();
+        s.deposit(&s.xlm, 1_000_000, 100);
+        let recipient = Address::generate(&s.env);
+        let schedule = PayoutSchedule {
+            start: 1_000,
+            cliff: 2_000,
+            end: 5_000,
+        };
+        let id = s
+            .at(200, || {
+                Treasury::create_proposal(
+                    &s.env,
+                    s.signer(0),
+                    TreasuryProposalType::BudgetAllocation,
+                    s.xlm.clone(),
+                    400_000,
+                    recipient.clone(),
+                    String::from_str(&s.env, "Contributor vesting"),
+                    86400,
+                    Payout::Scheduled(schedule),
+                )
+            })
             .unwrap();
-        treasury.approve_proposal(&id, "bob", 300).unwrap();
-        treasury.approve_proposal(&id, "carol", 300).unwrap();
-        treasury.execute_proposal(&id, 400).unwrap();
+        s.approve(id, 1, 300).unwrap();
+        s.approve(id, 2, 300).unwrap();
+        let stream_id = s.execute(id, 400).unwrap().unwrap();
+
+        // Funds are committed but stay in the contract until claimed.
+        assert_eq!(s.balance(&s.xlm), 600_000);
+        assert_eq!(s.wallet(&s.xlm, &s.contract_id), 1_000_000);
+
+        let claim = |now| s.at(now, || Treasury::claim_stream(&s.env, stream_id));
+        assert_eq!(claim(1_500), Err(SwapTradeError::NothingVested));
+        assert_eq!(claim(2_000), Ok(100_000));
+        assert_eq!(claim(3_000), Ok(100_000));
+        assert_eq!(claim(9_000), Ok(200_000));
+        assert_eq!(claim(9_500), Err(SwapTradeError::NothingVested));
+        assert_eq!(s.wallet(&s.xlm, &recipient), 400_000);
+    }
 
-        let result = treasury.execute_proposal(&id, 500);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("not in approved state"));
+    #[test]
+    fn test_pool_fees_collected_from_payer() {
+        let s = setup();
+        // The payer authorizes a transfer nested under this direct call.
+        s.env.mock_all_auths_allowing_non_root_auth();
+        let xlm_sym = symbol_short!("XLM");
+        s.at(0, || {
+            Treasury::set_fee_token(&s.env, s.admin.clone(), xlm_sym.clone(), s.xlm.clone())
+        })
+        .unwrap();
+
+        // Tokens that merely sit on the contract are never booked.
+        StellarAssetClient::new(&s.env, &s.xlm).mint(&s.contract_id, &300);
+        let payer = Address::generate(&s.env);
+        StellarAssetClient::new(&s.env, &s.xlm).mint(&payer, &250);
+
+        let collect = |symbol: Symbol, amount| {
+            s.at(100, || {
+                let token = Treasury::collect_pool_fees(&s.env, &payer, symbol, amount)?;
+                Treasury::receive_pool_fees(&s.env, 1, &token, amount);
+                Ok::<_, SwapTradeError>(token)
+            })
+        };
+        assert_eq!(
+            collect(symbol_short!("BTC"), 250),
+            Err(SwapTradeError::FeeTokenNotMapped)
+        );
+        assert_eq!(collect(xlm_sym, 250), Ok(s.xlm.clone()));
+        assert_eq!(s.balance(&s.xlm), 250);
+        assert_eq!(s.wallet(&s.xlm, &payer), 0);
     }
 }
@@ -3,23 +3,27 @@
 // Protocol Upgrade Mechanism
 //
 // Safe protocol upgrade paths with version management for the SwapTrade DAO.
-// Implements a staged upgrade process requiring governance approval and timelock.
+// Implements a staged upgrade process requiring governance approval and timelock,
+// executed on chain through the deployer.
 //
 // Capabilities:
 //   - Versioned protocol upgrades tracked by semantic version
-//   - Multi-sig approval for upgrade proposals
-//   - Timelock delay before upgrade execution
-//   - Upgrade validation and pre-flight checks
-//   - Rollback mechanism for failed upgrades
-//   - Upgrade history and audit trail
-//   - Canary testing support (partial deployment)
+//   - Multi-sig approval for upgrade proposals, scaled by upgrade type
+//   - Timelock delay before upgrade execution, longest for breaking changes
+//   - WASM replacement via `update_current_contract_wasm`
+//   - Storage migration run automatically after an upgrade lands
+//   - Governance-approved rollback to the previous WASM hash
+//   - Upgrade history
 
-use std::collections::HashMap;
+use soroban_sdk::{contracttype, Address, BytesN, Env, String, Symbol, Vec};
+
+use crate::errors::SwapTradeError;
 
 // ─── Types ────────────────────────────────────────────────────────────────────
 
 /// Semantic version for protocol releases.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[contracttype]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Version {
     pub major: u32,
     pub minor: u32,
@@ -51,33 +55,25 @@ impl Version {
     }
 }
 
-impl std::fmt::Display for Version {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
-    }
-}
-
 /// Status of an upgrade proposal.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[contracttype]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum UpgradeStatus {
     /// Upgrade proposed, collecting approvals.
     Proposed,
     /// Timelock period active, waiting for execution.
     Timelock,
-    /// Ready for execution after timelock expires.
-    Ready,
     /// Upgrade executed successfully.
     Executed,
-    /// Upgrade failed during execution.
-    Failed,
-    /// Upgrade cancelled.
+    /// Upgrade cancelled or expired.
     Cancelled,
     /// Upgrade rolled back after execution.
     RolledBack,
 }
 
 /// Type of upgrade being proposed.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[contracttype]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum UpgradeType {
     /// Breaking change requiring migration.
     Major,
@@ -85,51 +81,37 @@ pub enum UpgradeType {
     Minor,
     /// Bug fixes only.
     Patch,
-    /// Emergency hotfix with reduced timelock.
+    /// Emergency patch-level fix with reduced timelock.
     Hotfix,
-    /// Configuration-only change.
-    Config,
+    /// Return to the previous WASM hash.
+    Rollback,
 }
 
 /// An upgrade proposal.
-#[derive(Debug, Clone)]
+#[contracttype]
+#[derive(Debug, Clone, PartialEq)]
 pub struct UpgradeProposal {
-    /// Unique proposal identifier.
     pub id: u64,
-    /// Current version at time of proposal.
+    /// Version deployed when the proposal was made.
     pub from_version: Version,
-    /// Target version.
     pub to_version: Version,
-    /// Type of upgrade.
     pub upgrade_type: UpgradeType,
-    /// Proposer address.
-    pub proposer: String,
-    /// Human-readable description.
+    /// Hash of the installed WASM to switch to.
+    pub wasm_hash: BytesN<32>,
+    pub proposer: Address,
     pub description: String,
-    /// WASM hash of the new contract code (for reference).
-    pub code_hash: Option<[u8; 32]>,
-    /// Migration steps description.
-    pub migration_steps: Vec<String>,
-    /// Required approvals.
-    pub required_approvals: usize,
-    /// Current approvals.
-    pub approvals: Vec<String>,
-    /// When the proposal was created.
+    pub required_approvals: u32,
+    pub approvals: Vec<Address>,
     pub proposed_at: u64,
-    /// When the timelock expires (set when timelock starts).
-    pub timelock_expires_at: Option<u64>,
-    /// Timelock duration in seconds.
+    /// Timelock duration in seconds, started once approvals are reached.
     pub timelock_duration: u64,
-    /// Current status.
+    pub timelock_expires_at: Option<u64>,
     pub status: UpgradeStatus,
-    /// Execution result message.
-    pub execution_result: Option<String>,
-    /// When executed.
     pub executed_at: Option<u64>,
 }
 
 impl UpgradeProposal {
-    pub fn approval_count(&self) -> usize {
+    pub fn approval_count(&self) -> u32 {
         self.approvals.len()
     }
 
@@ -138,7 +120,7 @@ impl UpgradeProposal {
     }
 
     pub fn can_execute(&self, now: u64) -> bool {
-        matches!(self.status, UpgradeStatus::Ready | UpgradeStatus::Timelock)
+        self.status == UpgradeStatus::Timelock
             && match self.timelock_expires_at {
                 Some(expires) => now >= expires,
                 None => false,
@@ -146,202 +128,235 @@ impl UpgradeProposal {
     }
 }
 
+/// A version and the WASM that implements it.
+#[contracttype]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeployedCode {
+    pub version: Version,
+    pub wasm_hash: BytesN<32>,
+}
+
 /// Upgrade history entry.
-#[derive(Debug, Clone)]
+#[contracttype]
+#[derive(Debug, Clone, PartialEq)]
 pub struct UpgradeRecord {
-    pub from_version: Version,
-    pub to_version: Version,
+    pub proposal_id: u64,
+    pub from: DeployedCode,
+    pub to: DeployedCode,
     pub upgrade_type: UpgradeType,
     pub executed_at: u64,
-    pub executed_by: String,
-    pub success: bool,
-    pub description: String,
 }
 
 /// Configuration for the upgrade system.
-#[derive(Debug, Clone)]
+#[contracttype]
+#[derive(Debug, Clone, PartialEq)]
 pub struct UpgradeConfig {
-    /// Default timelock duration for upgrades (seconds).
+    /// Authorized upgrade signers.
+    pub signers: Vec<Address>,
+    /// Timelock for minor and patch upgrades (seconds).
     pub default_timelock_secs: u64,
-    /// Reduced timelock for hotfixes (seconds).
+    /// Timelock for major upgrades (seconds).
+    pub breaking_timelock_secs: u64,
+    /// Reduced timelock for hotfixes and rollbacks (seconds).
     pub hotfix_timelock_secs: u64,
     /// Required approvals for major upgrades.
-    pub major_required_approvals: usize,
-    /// Required approvals for minor upgrades.
-    pub minor_required_approvals: usize,
+    pub major_required_approvals: u32,
+    /// Required approvals for minor upgrades, hotfixes and rollbacks.
+    pub minor_required_approvals: u32,
     /// Required approvals for patches.
-    pub patch_required_approvals: usize,
-    /// Maximum time between proposal and execution (seconds).
+    pub patch_required_approvals: u32,
+    /// Maximum time between proposal and final approval (seconds).
     pub max_proposal_lifetime: u64,
     /// Whether rollback is enabled.
     pub rollback_enabled: bool,
 }
 
-impl Default for UpgradeConfig {
-    fn default() -> Self {
+impl UpgradeConfig {
+    /// Default thresholds (5 major / 3 minor / 2 patch) and timelocks
+    /// (7 days breaking / 48 hours default / 1 hour hotfix).
+    pub fn new(signers: Vec<Address>) -> Self {
         Self {
-            default_timelock_secs: 172800, // 48 hours
-            hotfix_timelock_secs: 3600,    // 1 hour
-            major_required_approvals: 5,   // 5 signers for major
-            minor_required_approvals: 3,   // 3 signers for minor
-            patch_required_approvals: 2,   // 2 signers for patch
+            signers,
+            default_timelock_secs: 172800,  // 48 hours
+            breaking_timelock_secs: 604800, // 7 days
+            hotfix_timelock_secs: 3600,     // 1 hour
+            major_required_approvals: 5,
+            minor_required_approvals: 3,
+            patch_required_approvals: 2,
             max_proposal_lifetime: 604800, // 7 days
             rollback_enabled: true,
         }
     }
 }
 
+#[contracttype]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpgradeKey {
+    UpgradeConfig,
+    /// Code currently installed.
+    Current,
+    /// Code replaced by the last upgrade; the rollback target.
+    Previous,
+    UpgradeProposal(u64),
+    NextId,
+    History,
+    /// Version upgraded from, while its storage migration has not run.
+    MigrationPending,
+}
+
 // ─── Upgrade Manager ─────────────────────────────────────────────────────────
 
-pub struct UpgradeManager {
-    /// Current protocol version.
-    current_version: Version,
-    /// Upgrade proposals.
-    proposals: HashMap<u64, UpgradeProposal>,
-    /// Completed upgrade history.
-    history: Vec<UpgradeRecord>,
-    /// Authorized upgrade signers.
-    signers: Vec<String>,
-    /// Upgrade configuration.
-    config: UpgradeConfig,
-    /// Next proposal ID.
-    next_id: u64,
-    /// Rollback version (last known good).
-    rollback_version: Option<Version>,
-}
+pub struct UpgradeManager;
 
 impl UpgradeManager {
-    pub fn new(current_version: Version, signers: Vec<String>, config: UpgradeConfig) -> Self {
-        Self {
-            current_version,
-            proposals: HashMap::new(),
-            history: Vec::new(),
-            signers,
-            config,
-            next_id: 1,
-            rollback_version: None,
+    // ── Configuration ────────────────────────────────────────────────────────
+
+    /// Admin only: install the signer set and thresholds. The first call also
+    /// records the deployed version and WASM hash; later calls ignore them.
+    pub fn configure(
+        env: &Env,
+        admin: Address,
+        config: UpgradeConfig,
+        version: Version,
+        wasm_hash: BytesN<32>,
+    ) -> Result<(), SwapTradeError> {
+        admin.require_auth();
+        crate::admin::require_admin(env, &admin)?;
+        let signers = config.signers.len();
+        if config.patch_required_approvals == 0
+            || config.patch_required_approvals > config.minor_required_approvals
+            || config.minor_required_approvals > config.major_required_approvals
+            || config.major_required_approvals > signers
+            || config.hotfix_timelock_secs > config.default_timelock_secs
+            || config.default_timelock_secs > config.breaking_timelock_secs
+        {
+            return Err(SwapTradeError::InvalidMultiSigConfig);
         }
+
+        env.storage()
+            .persistent()
+            .set(&UpgradeKey::UpgradeConfig, &config);
+        if !env.storage().persistent().has(&UpgradeKey::Current) {
+            env.storage()
+                .persistent()
+                .set(&UpgradeKey::Current, &DeployedCode { version, wasm_hash });
+        }
+        Ok(())
+    }
+
+    pub fn get_config(env: &Env) -> Option<UpgradeConfig> {
+        env.storage().persistent().get(&UpgradeKey::UpgradeConfig)
     }
 
     // ── Proposal Lifecycle ───────────────────────────────────────────────────
 
-    /// Create a new upgrade proposal.
+    /// Propose switching to `wasm_hash` at `to_version`. The version bump must
+    /// match `upgrade_type`; hotfixes may not be breaking. The proposer
+    /// auto-approves.
     pub fn propose_upgrade(
-        &mut self,
-        proposer: &str,
+        env: &Env,
+        proposer: Address,
+        wasm_hash: BytesN<32>,
         to_version: Version,
         upgrade_type: UpgradeType,
-        description: &str,
-        code_hash: Option<[u8; 32]>,
-        migration_steps: Vec<String>,
-        now: u64,
-    ) -> Result<u64, String> {
-        if !self.signers.contains(&proposer.to_string()) {
-            return Err("only authorized signers can propose upgrades".to_string());
-        }
-
-        // Validate version is newer
-        if to_version <= self.current_version {
-            return Err("target version must be greater than current version".to_string());
-        }
-
-        // Validate version jump matches upgrade type
-        match upgrade_type {
-            UpgradeType::Major => {
-                if !to_version.is_breaking(&self.current_version) {
-                    return Err("major upgrade requires major version bump".to_string());
-                }
-            }
-            UpgradeType::Minor => {
-                if !to_version.is_feature(&self.current_version) {
-                    return Err("minor upgrade requires minor version bump".to_string());
-                }
-            }
-            UpgradeType::Patch => {
-                if !to_version.is_patch(&self.current_version) {
-                    return Err("patch upgrade requires patch version bump".to_string());
-                }
-            }
-            UpgradeType::Hotfix => {
-                // Hotfix can be any bump
-            }
-            UpgradeType::Config => {
-                // Config change can be any bump
-            }
-        }
-
-        let required_approvals = match upgrade_type {
-            UpgradeType::Major => self.config.major_required_approvals,
-            UpgradeType::Minor => self.config.minor_required_approvals,
-            UpgradeType::Patch | UpgradeType::Config => self.config.patch_required_approvals,
-            UpgradeType::Hotfix => self.config.minor_required_approvals,
+        description: String,
+    ) -> Result<u64, SwapTradeError> {
+        let config = Self::require_signer(env, &proposer)?;
+        let current = Self::require_current(env)?;
+        let from = current.version;
+
+        let valid = match upgrade_type {
+            UpgradeType::Major => to_version.is_breaking(&from),
+            UpgradeType::Minor => to_version.is_feature(&from),
+            UpgradeType::Patch => to_version.is_patch(&from),
+            UpgradeType::Hotfix => to_version.is_patch(&from),
+            UpgradeType::Rollback => false,
         };
+        if !valid {
+            return Err(SwapTradeError::InvalidVersionBump);
+        }
 
-        let timelock_duration = match upgrade_type {
-            UpgradeType::Hotfix => self.config.hotfix_timelock_secs,
-            _ => self.config.default_timelock_secs,
+        let (required, timelock) = match upgrade_type {
+            UpgradeType::Major => (
+                config.major_required_approvals,
+                config.breaking_timelock_secs,
+            ),
+            UpgradeType::Minor => (
+                config.minor_required_approvals,
+                config.default_timelock_secs,
+            ),
+            UpgradeType::Patch => (
+                config.patch_required_approvals,
+                config.default_timelock_secs,
+            ),
+            _ => (config.minor_required_approvals, config.hotfix_timelock_secs),
         };
-
-        let id = self.next_id;
-        self.next_id += 1;
-
-        let proposal = UpgradeProposal {
-            id,
-            from_version: self.current_version.clone(),
-            to_version,
+        Self::create(
+            env,
+            proposer,
+            current.version,
+            DeployedCode {
+                version: to_version,
+                wasm_hash,
+            },
             upgrade_type,
-            proposer: proposer.to_string(),
-            description: description.to_string(),
-            code_hash,
-            migration_steps,
-            required_approvals,
-            approvals: vec![proposer.to_string()], // Proposer auto-approves
-            proposed_at: now,
-            timelock_expires_at: None,
-            timelock_duration,
-            status: UpgradeStatus::Proposed,
-            execution_result: None,
-            executed_at: None,
-        };
+            description,
+            required,
+            timelock,
+        )
+    }
 
-        self.proposals.insert(id, proposal);
-        Ok(id)
+    /// Propose returning to the code replaced by the last upgrade. Rollbacks
+    /// use the hotfix timelock and the minor approval threshold.
+    pub fn propose_rollback(
+        env: &Env,
+        proposer: Address,
+        description: String,
+    ) -> Result<u64, SwapTradeError> {
+        let config = Self::require_signer(env, &proposer)?;
+        if !config.rollback_enabled {
+            return Err(SwapTradeError::RollbackDisabled);
+        }
+        let current = Self::require_current(env)?;
+        let previous = Self::get_previous(env).ok_or(SwapTradeError::NoRollbackTarget)?;
+        Self::create(
+            env,
+            proposer,
+            current.version,
+            previous,
+            UpgradeType::Rollback,
+            description,
+            config.minor_required_approvals,
+            config.hotfix_timelock_secs,
+        )
     }
 
-    /// Approve an upgrade proposal.
+    /// Approve an upgrade proposal. The timelock starts once the threshold is
+    /// reached. Returns the approval count.
     pub fn approve_upgrade(
-        &mut self,
+        env: &Env,
         proposal_id: u64,
-        signer: &str,
-        now: u64,
-    ) -> Result<usize, String> {
-        if !self.signers.contains(&signer.to_string()) {
-            return Err("only authorized signers can approve upgrades".to_string());
-        }
-
-        let proposal = self
-            .proposals
-            .get_mut(&proposal_id)
-            .ok_or("proposal not found")?;
-
+        signer: Address,
+    ) -> Result<u32, SwapTradeError> {
+        let config = Self::require_signer(env, &signer)?;
+        let mut proposal =
+            Self::get_proposal(env, proposal_id).ok_or(SwapTradeError::ProposalNotFound)?;
         if proposal.status != UpgradeStatus::Proposed {
-            return Err(format!(
-                "cannot approve in current status: {:?}",
-                proposal.status
-            ));
+            return Err(SwapTradeError::UpgradeNotPending);
         }
 
         // Check proposal hasn't expired
-        if now > proposal.proposed_at + self.config.max_proposal_lifetime {
+        let now = env.ledger().timestamp();
+        if now > proposal.proposed_at + config.max_proposal_lifetime {
             proposal.status = UpgradeStatus::Cancelled;
-            return Err("proposal has expired".to_string());
+            Self::set_proposal(env, &proposal);
+            return Err(SwapTradeError::UpgradeProposalExpired);
         }
 
-        if proposal.approvals.contains(&signer.to_string()) {
-            return Err("signer has already approved".to_string());
+        if proposal.approvals.contains(&signer) {
+            return Err(SwapTradeError::AlreadyApproved);
         }
-
-        proposal.approvals.push(signer.to_string());
+        proposal.approvals.push_back(signer.clone());
         let count = proposal.approval_count();
 
         // If threshold reached, start timelock
@@ -349,134 +364,305 @@ impl UpgradeManager {
             proposal.status = UpgradeStatus::Timelock;
             proposal.timelock_expires_at = Some(now + proposal.timelock_duration);
         }
-
+        Self::set_proposal(env, &proposal);
+        env.events().publish(
+            (Symbol::new(env, "UpgradeApproved"), proposal_id, signer),
+            (count, proposal.required_approvals),
+        );
         Ok(count)
     }
 
-    /// Execute an approved upgrade after timelock expires.
-    pub fn execute_upgrade(&mut self, proposal_id: u64, now: u64) -> Result<(), String> {
-        let proposal = self
-            .proposals
-            .get_mut(&proposal_id)
-            .ok_or("proposal not found")?;
-
-        if !proposal.can_execute(now) {
-            return Err("upgrade is not ready for execution".to_string());
+    /// Execute an upgrade whose timelock has expired. Anyone may call this.
+    /// The new WASM takes over once the current invocation returns; its
+    /// storage migration runs on the next gated call or via `migrate`.
+    /// Rollbacks schedule no migration and drop one the undone upgrade left
+    /// pending, since the restored code expects its own storage layout.
+    pub fn execute_upgrade(env: &Env, proposal_id: u64) -> Result<Version, SwapTradeError> {
+        let mut proposal =
+            Self::get_proposal(env, proposal_id).ok_or(SwapTradeError::ProposalNotFound)?;
+        let now = env.ledger().timestamp();
+        match proposal.status {
+            UpgradeStatus::Proposed => return Err(SwapTradeError::InsufficientApprovals),
+            UpgradeStatus::Timelock if !proposal.can_execute(now) => {
+                return Err(SwapTradeError::TimelockNotElapsed)
+            }
+            UpgradeStatus::Timelock => {}
+            UpgradeStatus::Executed | UpgradeStatus::RolledBack => {
+                return Err(SwapTradeError::ProposalAlreadyExecuted)
+            }
+            UpgradeStatus::Cancelled => return Err(SwapTradeError::ProposalCanceled),
         }
 
-        // Save rollback version
-        self.rollback_version = Some(self.current_version.clone());
-
-        // Perform the upgrade
-        self.current_version = proposal.to_version.clone();
-        proposal.status = UpgradeStatus::Executed;
-        proposal.executed_at = Some(now);
-        proposal.execution_result = Some("Upgrade executed successfully".to_string());
+        // Another upgrade landed since this one was proposed.
+        let current = Self::require_current(env)?;
+        if current.version != proposal.from_version {
+            return Err(SwapTradeError::InvalidVersionBump);
+        }
 
-        // Record in history
-        self.history.push(UpgradeRecord {
-            from_version: proposal.from_version.clone(),
-            to_version: proposal.to_version.clone(),
-            upgrade_type: proposal.upgrade_type.clone(),
+        let target = DeployedCode {
+            version: proposal.to_version,
+            wasm_hash: proposal.wasm_hash.clone(),
+        };
+        let mut history = Self::history(env);
+        if proposal.upgrade_type == UpgradeType::Rollback {
+            // The rolled-back upgrade cannot be re-applied by a second rollback.
+            env.storage().persistent().remove(&UpgradeKey::Previous);
+            env.storage()
+                .persistent()
+                .remove(&UpgradeKey::MigrationPending);
+            if let Some(last) = history.last() {
+                if let Some(mut undone) = Self::get_proposal(env, last.proposal_id) {
+                    undone.status = UpgradeStatus::RolledBack;
+                    Self::set_proposal(env, &undone);
+                }
+            }
+        } else {
+            env.storage()
+                .persistent()
+                .set(&UpgradeKey::Previous, &current);
+            env.storage()
+                .persistent()
+                .set(&UpgradeKey::MigrationPending, &current.version);
+        }
+        env.storage()
+            .persistent()
+            .set(&UpgradeKey::Current, &target);
+
+        history.push_back(UpgradeRecord {
+            proposal_id,
+            from: current,
+            to: target,
+            upgrade_type: proposal.upgrade_type,
             executed_at: now,
-            executed_by: proposal.proposer.clone(),
-            success: true,
-            description: proposal.description.clone(),
         });
+        env.storage()
+            .persistent()
+            .set(&UpgradeKey::History, &history);
 
-        Ok(())
+        proposal.status = UpgradeStatus::Executed;
+        proposal.executed_at = Some(now);
+        Self::set_proposal(env, &proposal);
+
+        env.events().publish(
+            (Symbol::new(env, "UpgradeExecuted"), proposal_id),
+            (
+                proposal.from_version,
+                proposal.to_version,
+                proposal.wasm_hash.clone(),
+                now,
+            ),
+        );
+        env.deployer()
+            .update_current_contract_wasm(proposal.wasm_hash);
+        Ok(proposal.to_version)
     }
 
-    /// Cancel an upgrade proposal.
+    /// Cancel an upgrade proposal that has not been executed.
     pub fn cancel_upgrade(
-        &mut self,
+        env: &Env,
         proposal_id: u64,
-        signer: &str,
-        now: u64,
-    ) -> Result<(), String> {
-        let proposal = self
-            .proposals
-            .get_mut(&proposal_id)
-            .ok_or("proposal not found")?;
-
-        if !self.signers.contains(&signer.to_string()) {
-            return Err("only authorized signers can cancel".to_string());
+        signer: Address,
+    ) -> Result<(), SwapTradeError> {
+        Self::require_signer(env, &signer)?;
+        let mut proposal =
+            Self::get_proposal(env, proposal_id).ok_or(SwapTradeError::ProposalNotFound)?;
+        match proposal.status {
+            UpgradeStatus::Executed | UpgradeStatus::RolledBack => {
+                return Err(SwapTradeError::ProposalAlreadyExecuted)
+            }
+            UpgradeStatus::Cancelled => return Err(SwapTradeError::ProposalCanceled),
+            _ => {}
         }
 
-        if proposal.status == UpgradeStatus::Executed {
-            return Err("cannot cancel an executed upgrade".to_string());
-        }
+        proposal.status = UpgradeStatus::Cancelled;
+        Self::set_proposal(env, &proposal);
+        env.events().publish(
+            (Symbol::new(env, "UpgradeCancelled"), proposal_id, signer),
+            (env.ledger().timestamp(),),
+        );
+        Ok(())
+    }
 
-        if proposal.status == UpgradeStatus::RolledBack {
-            return Err("upgrade has already been rolled back".to_string());
+    // ── Migration ────────────────────────────────────────────────────────────
+
+    /// Run storage migrations if an upgrade has landed since they last ran.
+    /// Called at the top of every gated entrypoint.
+    pub fn ensure_migrated(env: &Env) -> Result<(), SwapTradeError> {
+        if env
+            .storage()
+            .persistent()
+            .has(&UpgradeKey::MigrationPending)
+        {
+            Self::migrate(env)?;
         }
-
-        proposal.status = UpgradeStatus::Cancelled;
         Ok(())
     }
 
-    /// Rollback to the previous version.
-    pub fn rollback(&mut self, reason: &str, now: u64) -> Result<(), String> {
-        if !self.config.rollback_enabled {
-            return Err("rollback is not enabled".to_string());
+    /// Run all storage migrations up to the current schema and clear the
+    /// pending flag. Migrations are idempotent.
+    pub fn migrate(env: &Env) -> Result<(), SwapTradeError> {
+        crate::migration::migrate_from_v1_to_v2(env)?;
+        let pending: Option<Version> = env
+            .storage()
+            .persistent()
+            .get(&UpgradeKey::MigrationPending);
+        if let Some(from) = pending {
+            env.storage()
+                .persistent()
+                .remove(&UpgradeKey::MigrationPending);
+            let to = Self::current_version(env).unwrap_or(from);
+            env.events().publish(
+                (Symbol::new(env, "MigrationCompleted"),),
+                (from, to, env.ledger().timestamp()),
+            );
         }
+        Ok(())
+    }
+
+    pub fn is_migration_pending(env: &Env) -> bool {
+        env.storage()
+            .persistent()
+            .has(&UpgradeKey::MigrationPending)
+    }
 
-        let rollback_to = self
-            .rollback_version
-            .as_ref()
-            .ok_or("no rollback version available")?
-            .clone();
+    // ── Queries ──────────────────────────────────────────────────────────────
 
-        self.history.push(UpgradeRecord {
-            from_version: self.current_version.clone(),
-            to_version: rollback_to.clone(),
-            upgrade_type: UpgradeType::Hotfix,
-            executed_at: now,
-            executed_by: "system".to_string(),
-            success: true,
-            description: format!("Rollback: {}", reason),
-        });
+    pub fn current_version(env: &Env) -> Option<Version> {
+        Self::get_current(env).map(|c| c.version)
+    }
 
-        self.current_version = rollback_to;
-        Ok(())
+    pub fn get_current(env: &Env) -> Option<DeployedCode> {
+        env.storage().persistent().get(&UpgradeKey::Current)
     }
 
-    // ── Queries ──────────────────────────────────────────────────────────────
+    /// The rollback target, if any.
+    pub fn get_previous(env: &Env) -> Option<DeployedCode> {
+        env.storage().persistent().get(&UpgradeKey::Previous)
+    }
 
-    pub fn current_version(&self) -> &Version {
-        &self.current_version
+    pub fn get_proposal(env: &Env, proposal_id: u64) -> Option<UpgradeProposal> {
+        env.storage()
+            .persistent()
+            .get(&UpgradeKey::UpgradeProposal(proposal_id))
     }
 
-    pub fn get_proposal(&self, proposal_id: u64) -> Option<&UpgradeProposal> {
-        self.proposals.get(&proposal_id)
+    pub fn history(env: &Env) -> Vec<UpgradeRecord> {
+        env.storage()
+            .persistent()
+            .get(&UpgradeKey::History)
+            .unwrap_or_else(|| Vec::new(env))
     }
 
-    pub fn history(&self) -> &[UpgradeRecord] {
-        &self.history
+    pub fn is_signer(env: &Env, signer: &Address) -> bool {
+        Self::get_config(env).map_or(false, |c| c.signers.contains(signer))
     }
 
-    pub fn rollback_version(&self) -> Option<&Version> {
-        self.rollback_version.as_ref()
+    /// Ids of proposals still collecting approvals or in timelock.
+    pub fn pending_upgrades(env: &Env) -> Vec<u64> {
+        let next: u64 = env
+            .storage()
+            .persistent()
+            .get(&UpgradeKey::NextId)
+            .unwrap_or(1);
+        let mut pending = Vec::new(env);
+        for id in 1..next {
+            if let Some(p) = Self::get_proposal(env, id) {
+                if p.status == UpgradeStatus::Proposed || p.status == UpgradeStatus::Timelock {
+                    pending.push_back(id);
+                }
+            }
+        }
+        pending
     }
 
-    pub fn is_signer(&self, signer: &str) -> bool {
-        self.signers.contains(&signer.to_string())
+    // ── Internal ─────────────────────────────────────────────────────────────
+
+    #[allow(clippy::too_many_arguments)]
+    fn create(
+        env: &Env,
+        proposer: Address,
+        from_version: Version,
+        target: DeployedCode,
+        upgrade_type: UpgradeType,
+        description: String,
+        required_approvals: u32,
+        timelock_duration: u64,
+    ) -> Result<u64, SwapTradeError> {
+        let id: u64 = env
+            .storage()
+            .persistent()
+            .get(&UpgradeKey::NextId)
+            .unwrap_or(1);
+        env.storage()
+            .persistent()
+            .set(&UpgradeKey::NextId, &(id + 1));
+
+        let now = env.ledger().timestamp();
+        let mut proposal = UpgradeProposal {
+            id,
+            from_version,
+            to_version: target.version,
+            upgrade_type,
+            wasm_hash: target.wasm_hash,
+            proposer: proposer.clone(),
+            description,
+            required_approvals,
+            approvals: Vec::from_array(env, [proposer.clone()]),
+            proposed_at: now,
+            timelock_duration,
+            timelock_expires_at: None,
+            status: UpgradeStatus::Proposed,
+            executed_at: None,
+        };
+        if proposal.is_approved() {
+            proposal.status = UpgradeStatus::Timelock;
+            proposal.timelock_expires_at = Some(now + timelock_duration);
+        }
+        Self::set_proposal(env, &proposal);
+        env.events().publish(
+            (Symbol::new(env, "UpgradeProposed"), id),
+            (proposer, from_version, target.version, upgrade_type, now),
+        );
+        Ok(id)
     }
 
-    pub fn signer_count(&self) -> usize {
-        self.signers.len()
+    fn require_signer(env: &Env, signer: &Address) -> Result<UpgradeConfig, SwapTradeError> {
+        signer.require_auth();
+        let config = Self::get_config(env).ok_or(SwapTradeError::UpgradeNotConfigured)?;
+        if !config.signers.contains(signer) {
+            return Err(SwapTradeError::NotUpgradeSigner);
+        }
+        Ok(config)
     }
 
-    pub fn pending_upgrades(&self) -> Vec<&UpgradeProposal> {
-        self.proposals
-            .values()
-            .filter(|p| {
-                p.status != UpgradeStatus::Executed
-                    && p.status != UpgradeStatus::Cancelled
-                    && p.status != UpgradeStatus::RolledBack
-            })
-            .collect()
+    fn require_current(env: &Env) -> Result<DeployedCode, SwapTradeError> {
+        Self::get_current(env).ok_or(SwapTradeError::UpgradeNotConfigured)
     }
+
+    fn set_proposal(env: &Env, proposal: &UpgradeProposal) {
+        env.storage()
+            .persistent()
+            .set(&UpgradeKey::UpgradeProposal(proposal.id), proposal);
+    }
+}
+
+/// Install a minimal contract WASM and return its hash. `salt` varies the
+/// module so tests can install several distinct hashes.
+#[cfg(test)]
+pub(crate) fn install_test_wasm(env: &Env, salt: u8) -> BytesN<32> {
+    // Empty module carrying only the `contractenvmetav0` interface version
+    // section (protocol 22), plus a custom section `s` whose one-byte payload
+    // is `salt`. Section names must be UTF-8, so the salt can't be the name.
+    let mut wasm = soroban_sdk::Bytes::from_slice(
+        env,
+        &[
+            0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // magic, version
+            0x00, 0x1e, 0x11, b'c', b'o', b'n', b't', b'r', b'a', b'c', b't', b'e', b'n', b'v',
+            b'm', b'e', b't', b'a', b'v', b'0', 0, 0, 0, 0, 0, 0, 0, 22, 0, 0, 0, 0,
+        ],
+    );
+    wasm.extend_from_array(&[0x00, 0x03, 0x01, b's', salt]);
+    env.deployer().upload_contract_wasm(wasm)
 }
 
 // ─── Tests ────────────────────────────────────────────────────────────────────
@@ -484,17 +670,103 @@ impl UpgradeManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::storage::ADMIN_KEY;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    struct Setup {
+        env: Env,
+        contract_id: Address,
+        signers: Vec<Address>,
+        genesis: BytesN<32>,
+    }
 
-    fn setup() -> UpgradeManager {
-        let signers = vec![
-            "alice".to_string(),
-            "bob".to_string(),
-            "carol".to_string(),
-            "dave".to_string(),
-            "eve".to_string(),
-        ];
-        let config = UpgradeConfig::default();
-        UpgradeManager::new(Version::new(1, 0, 0), signers, config)
+    impl Setup {
+        fn signer(&self, i: u32) -> Address {
+            self.signers.get(i).unwrap()
+        }
+
+        fn at<T>(&self, now: u64, f: impl FnOnce() -> T) -> T {
+            self.env.ledger().with_mut(|l| l.timestamp = now);
+            self.env.as_contract(&self.contract_id, f)
+        }
+
+        fn propose(
+            &self,
+            to: Version,
+            upgrade_type: UpgradeType,
+            salt: u8,
+            now: u64,
+        ) -> Result<u64, SwapTradeError> {
+            let hash = install_test_wasm(&self.env, salt);
+            self.at(now, || {
+                UpgradeManager::propose_upgrade(
+                    &self.env,
+                    self.signer(0),
+                    hash,
+                    to,
+                    upgrade_type,
+                    String::from_str(&self.env, "Upgrade"),
+                )
+            })
+        }
+
+        /// Approve with signers 1..=n.
+        fn approve(&self, id: u64, n: u32, now: u64) {
+            for i in 1..=n {
+                self.at(now, || {
+                    UpgradeManager::approve_upgrade(&self.env, id, self.signer(i))
+                })
+                .unwrap();
+            }
+        }
+
+        fn execute(&self, id: u64, now: u64) -> Result<Version, SwapTradeError> {
+            self.at(now, || UpgradeManager::execute_upgrade(&self.env, id))
+        }
+
+        fn current(&self) -> Version {
+            self.at(self.env.ledger().timestamp(), || {
+                UpgradeManager::current_version(&self.env)
+            })
+            .unwrap()
+        }
+
+        fn status(&self, id: u64) -> UpgradeStatus {
+            self.at(self.env.ledger().timestamp(), || {
+                UpgradeManager::get_proposal(&self.env, id)
+            })
+            .unwrap()
+            .status
+        }
+    }
+
+    fn setup() -> Setup {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register(crate::CounterContract, ());
+        let admin = Address::generate(&env);
+        let mut signers = Vec::new(&env);
+        for _ in 0..5 {
+            signers.push_back(Address::generate(&env));
+        }
+        let genesis = install_test_wasm(&env, 0);
+        env.as_contract(&contract_id, || {
+            env.storage().persistent().set(&ADMIN_KEY, &admin);
+            UpgradeManager::configure(
+                &env,
+                admin.clone(),
+                UpgradeConfig::new(signers.clone()),
+                Version::new(1, 0, 0),
+                genesis.clone(),
+            )
+            .unwrap();
+        });
+        Setup {
+            env,
+            contract_id,
+            signers,
+            genesis,
+        }
     }
 
     #[test]
@@ -513,401 +785,312 @@ mod tests {
     }
 
     #[test]
-    fn test_create_major_upgrade() {
-        let mut mgr = setup();
-        let id = mgr
-            .propose_upgrade(
-                "alice",
-                Version::new(2, 0, 0),
-                UpgradeType::Major,
-                "Major upgrade with new AMM",
-                None,
-                vec!["Migrate pool state".to_string()],
-                100,
-            )
-            .unwrap();
-
-        let proposal = mgr.get_proposal(id).unwrap();
-        assert_eq!(proposal.status, UpgradeStatus::Proposed);
-        assert_eq!(proposal.required_approvals, 5);
-        assert_eq!(proposal.timelock_duration, 172800);
+    fn test_thresholds_and_timelocks_by_type() {
+        let s = setup();
+        let cases = [
+            (Version::new(2, 0, 0), UpgradeType::Major, 5, 604800),
+            (Version::new(1, 1, 0), UpgradeType::Minor, 3, 172800),
+            (Version::new(1, 0, 1), UpgradeType::Patch, 2, 172800),
+            (Version::new(1, 0, 1), UpgradeType::Hotfix, 3, 3600),
+        ];
+        for (salt, (to, upgrade_type, approvals, timelock)) in cases.into_iter().enumerate() {
+            let id = s.propose(to, upgrade_type, salt as u8 + 1, 100).unwrap();
+            let proposal = s
+                .at(100, || UpgradeManager::get_proposal(&s.env, id))
+                .unwrap();
+            assert_eq!(proposal.required_approvals, approvals);
+            assert_eq!(proposal.timelock_duration, timelock);
+            assert_eq!(proposal.approval_count(), 1);
+            assert_eq!(proposal.status, UpgradeStatus::Proposed);
+        }
     }
 
     #[test]
-    fn test_create_minor_upgrade() {
-        let mut mgr = setup();
-        let id = mgr
-            .propose_upgrade(
-                "alice",
+    fn test_non_signer_cannot_propose() {
+        let s = setup();
+        let outsider = Address::generate(&s.env);
+        let hash = install_test_wasm(&s.env, 1);
+        let result = s.at(100, || {
+            UpgradeManager::propose_upgrade(
+                &s.env,
+                outsider.clone(),
+                hash,
                 Version::new(1, 1, 0),
                 UpgradeType::Minor,
-                "Add new order type",
-                None,
-                vec![],
-                100,
+                String::from_str(&s.env, "Malicious"),
             )
-            .unwrap();
-
-        let proposal = mgr.get_proposal(id).unwrap();
-        assert_eq!(proposal.required_approvals, 3);
+        });
+        assert_eq!(result, Err(SwapTradeError::NotUpgradeSigner));
     }
 
     #[test]
-    fn test_create_patch_upgrade() {
-        let mut mgr = setup();
-        let id = mgr
-            .propose_upgrade(
-                "alice",
-                Version::new(1, 0, 1),
-                UpgradeType::Patch,
-                "Fix rounding error",
-                None,
-                vec![],
-                100,
-            )
-            .unwrap();
-
-        let proposal = mgr.get_proposal(id).unwrap();
-        assert_eq!(proposal.required_approvals, 2);
+    fn test_version_bump_must_match_type() {
+        let s = setup();
+        for (to, upgrade_type) in [
+            (Version::new(1, 0, 0), UpgradeType::Patch),
+            (Version::new(0, 9, 0), UpgradeType::Minor),
+            (Version::new(1, 1, 0), UpgradeType::Major),
+            (Version::new(2, 0, 0), UpgradeType::Minor),
+            (Version::new(2, 0, 0), UpgradeType::Hotfix),
+            (Version::new(1, 1, 0), UpgradeType::Hotfix),
+            (Version::new(1, 0, 1), UpgradeType::Rollback),
+        ] {
+            assert_eq!(
+                s.propose(to, upgrade_type, 1, 100),
+                Err(SwapTradeError::InvalidVersionBump)
+            );
+        }
     }
 
     #[test]
-    fn test_hotfix_reduced_timelock() {
-        let mut mgr = setup();
-        let id = mgr
-            .propose_upgrade(
-                "alice",
-                Version::new(1, 0, 1),
-                UpgradeType::Hotfix,
-                "Security hotfix",
-                None,
-                vec![],
-                100,
-            )
+    fn test_approve_starts_timelock() {
+        let s = setup();
+        let id = s
+            .propose(Version::new(1, 1, 0), UpgradeType::Minor, 1, 100)
             .unwrap();
-
-        let proposal = mgr.get_proposal(id).unwrap();
-        assert_eq!(proposal.timelock_duration, 3600); // 1 hour
-    }
-
-    #[test]
-    fn test_non_signer_cannot_propose() {
-        let mut mgr = setup();
-        let result = mgr.propose_upgrade(
-            "frank",
-            Version::new(2, 0, 0),
-            UpgradeType::Major,
-            "Test",
-            None,
-            vec![],
-            100,
+        s.approve(id, 1, 200);
+        assert_eq!(s.status(id), UpgradeStatus::Proposed);
+        assert_eq!(
+            s.execute(id, 200),
+            Err(SwapTradeError::InsufficientApprovals)
         );
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("only authorized"));
-    }
 
-    #[test]
-    fn test_cannot_downgrade() {
-        let mut mgr = setup();
-        let result = mgr.propose_upgrade(
-            "alice",
-            Version::new(0, 5, 0),
-            UpgradeType::Major,
-            "Downgrade",
-            None,
-            vec![],
-            100,
-        );
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("greater than current"));
+        s.at(300, || {
+            UpgradeManager::approve_upgrade(&s.env, id, s.signer(2))
+        })
+        .unwrap();
+        let proposal = s
+            .at(300, || UpgradeManager::get_proposal(&s.env, id))
+            .unwrap();
+        assert_eq!(proposal.status, UpgradeStatus::Timelock);
+        assert_eq!(proposal.timelock_expires_at, Some(300 + 172800));
     }
 
     #[test]
-    fn test_major_version_mismatch() {
-        let mut mgr = setup();
-        let result = mgr.propose_upgrade(
-            "alice",
-            Version::new(1, 1, 0),
-            UpgradeType::Major,
-            "Not a major",
-            None,
-            vec![],
-            100,
+    fn test_cannot_approve_twice() {
+        let s = setup();
+        let id = s
+            .propose(Version::new(1, 1, 0), UpgradeType::Minor, 1, 100)
+            .unwrap();
+        assert_eq!(
+            s.at(200, || UpgradeManager::approve_upgrade(
+                &s.env,
+                id,
+                s.signer(0)
+            )),
+            Err(SwapTradeError::AlreadyApproved)
         );
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("major version bump"));
     }
 
     #[test]
-    fn test_approve_upgrade() {
-        let mut mgr = setup();
-        let id = mgr
-            .propose_upgrade(
-                "alice",
-                Version::new(2, 0, 0),
-                UpgradeType::Major,
-                "Major upgrade",
-                None,
-                vec![],
-                100,
-            )
+    fn test_execute_installs_wasm_after_timelock() {
+        let s = setup();
+        let id = s
+            .propose(Version::new(2, 0, 0), UpgradeType::Major, 1, 100)
             .unwrap();
+        s.approve(id, 4, 200);
 
-        mgr.approve_upgrade(id, "bob", 200).unwrap();
-        mgr.approve_upgrade(id, "carol", 200).unwrap();
-        mgr.approve_upgrade(id, "dave", 200).unwrap();
-        mgr.approve_upgrade(id, "eve", 200).unwrap();
-
-        let proposal = mgr.get_proposal(id).unwrap();
-        assert_eq!(proposal.status, UpgradeStatus::Timelock);
-        assert_eq!(proposal.timelock_expires_at, Some(200 + 172800));
-    }
+        assert_eq!(
+            s.execute(id, 200 + 604799),
+            Err(SwapTradeError::TimelockNotElapsed)
+        );
+        assert_eq!(s.execute(id, 200 + 604800), Ok(Version::new(2, 0, 0)));
 
-    #[test]
-    fn test_cannot_approve_twice() {
-        let mut mgr = setup();
-        let id = mgr
-            .propose_upgrade(
-                "alice",
-                Version::new(2, 0, 0),
-                UpgradeType::Major,
-                "Major",
-                None,
-                vec![],
-                100,
-            )
+        let proposal = s
+            .at(200 + 604800, || UpgradeManager::get_proposal(&s.env, id))
             .unwrap();
-
-        let result = mgr.approve_upgrade(id, "alice", 200);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("already approved"));
+        assert_eq!(s.current(), Version::new(2, 0, 0));
+        assert_eq!(s.status(id), UpgradeStatus::Executed);
+        s.at(200 + 604800, || {
+            let current = UpgradeManager::get_current(&s.env).unwrap();
+            assert_eq!(current.wasm_hash, proposal.wasm_hash);
+            let previous = UpgradeManager::get_previous(&s.env).unwrap();
+            assert_eq!(previous.version, Version::new(1, 0, 0));
+            assert_eq!(previous.wasm_hash, s.genesis);
+            assert_eq!(UpgradeManager::history(&s.env).len(), 1);
+            assert!(UpgradeManager::is_migration_pending(&s.env));
+        });
+        assert_eq!(
+            s.execute(id, 200 + 604801),
+            Err(SwapTradeError::ProposalAlreadyExecuted)
+        );
     }
 
     #[test]
-    fn test_execute_after_timelock() {
-        let mut mgr = setup();
-        let id = mgr
-            .propose_upgrade(
-                "alice",
-                Version::new(2, 0, 0),
-                UpgradeType::Major,
-                "Major upgrade",
-                None,
-                vec![],
-                100,
-            )
+    fn test_migration_runs_once_after_upgrade() {
+        let s = setup();
+        let id = s
+            .propose(Version::new(1, 0, 1), UpgradeType::Patch, 1, 100)
             .unwrap();
-
-        // Get approvals
-        mgr.approve_upgrade(id, "bob", 200).unwrap();
-        mgr.approve_upgrade(id, "carol", 200).unwrap();
-        mgr.approve_upgrade(id, "dave", 200).unwrap();
-        mgr.approve_upgrade(id, "eve", 200).unwrap();
-
-        // Cannot execute before timelock
-        let result = mgr.execute_upgrade(id, 300);
-        assert!(result.is_err());
-
-        // Execute after timelock
-        let timelock_end = 200 + 172800;
-        mgr.execute_upgrade(id, timelock_end).unwrap();
-
-        assert_eq!(mgr.current_version(), &Version::new(2, 0, 0));
-        assert_eq!(mgr.history().len(), 1);
-        assert_eq!(mgr.rollback_version(), Some(&Version::new(1, 0, 0)));
+        s.approve(id, 1, 100);
+        s.execute(id, 100 + 172800).unwrap();
+
+        s.at(100 + 172800, || {
+            assert_eq!(crate::migration::get_stored_version(&s.env), 0);
+            UpgradeManager::ensure_migrated(&s.env).unwrap();
+            assert_eq!(crate::migration::get_stored_version(&s.env), 2);
+            assert!(!UpgradeManager::is_migration_pending(&s.env));
+        });
     }
 
     #[test]
-    fn test_rollback() {
-        let mut mgr = setup();
-        let id = mgr
-            .propose_upgrade(
-                "alice",
-                Version::new(2, 0, 0),
-                UpgradeType::Major,
-                "Major upgrade",
-                None,
-                vec![],
-                100,
-            )
+    fn test_rollback_restores_previous_wasm() {
+        let s = setup();
+        let id = s
+            .propose(Version::new(1, 1, 0), UpgradeType::Minor, 1, 100)
             .unwrap();
-
-        mgr.approve_upgrade(id, "bob", 200).unwrap();
-        mgr.approve_upgrade(id, "carol", 200).unwrap();
-        mgr.approve_upgrade(id, "dave", 200).unwrap();
-        mgr.approve_upgrade(id, "eve", 200).unwrap();
-        mgr.execute_upgrade(id, 200 + 172800).unwrap();
-
-        assert_eq!(mgr.current_version(), &Version::new(2, 0, 0));
-
-        mgr.rollback("Critical bug found", 200 + 172900).unwrap();
-        assert_eq!(mgr.current_version(), &Version::new(1, 0, 0));
-        assert_eq!(mgr.history().len(), 2);
+        s.approve(id, 2, 100);
+        s.execute(id, 100 + 172800).unwrap();
+
+        let now = 100 + 172900;
+        let rollback = s
+            .at(now, || {
+                UpgradeManager::propose_rollback(
+                    &s.env,
+                    s.signer(1),
+                    String::from_str(&s.env, "Critical bug found"),
+                )
+            })
+            .unwrap();
+        s.at(now, || {
+            UpgradeManager::approve_upgrade(&s.env, rollback, s.signer(2)).unwrap();
+            UpgradeManager::approve_upgrade(&s.env, rollback, s.signer(3)).unwrap();
+        });
+        assert_eq!(s.execute(rollback, now + 3600), Ok(Version::new(1, 0, 0)));
+
+        assert_eq!(s.current(), Version::new(1, 0, 0));
+        assert_eq!(s.status(id), UpgradeStatus::RolledBack);
+        s.at(now + 3600, || {
+            assert_eq!(
+                UpgradeManager::get_current(&s.env).unwrap().wasm_hash,
+                s.genesis
+            );
+            assert_eq!(UpgradeManager::history(&s.env).len(), 2);
+            // The undone upgrade's migration never runs on the old code.
+            assert!(!UpgradeManager::is_migration_pending(&s.env));
+            UpgradeManager::ensure_migrated(&s.env).unwrap();
+            assert_eq!(crate::migration::get_stored_version(&s.env), 0);
+            // The rolled-back code is not a rollback target.
+            assert_eq!(
+                UpgradeManager::propose_rollback(
+                    &s.env,
+                    s.signer(0),
+                    String::from_str(&s.env, "Again")
+                ),
+                Err(SwapTradeError::NoRollbackTarget)
+            );
+        });
     }
 
     #[test]
     fn test_rollback_disabled() {
-        let signers = vec!["alice".to_string()];
-        let config = UpgradeConfig {
-            rollback_enabled: false,
-            ..UpgradeConfig::default()
-        };
-        let mut mgr = UpgradeManager::new(Version::new(1, 0, 0), signers, config);
-
-        let result = mgr.rollback("test", 100);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("not enabled"));
+        let s = setup();
+        let admin = Address::generate(&s.env);
+        let result = s.at(100, || {
+            s.env.storage().persistent().set(&ADMIN_KEY, &admin);
+            let mut config = UpgradeConfig::new(s.signers.clone());
+            config.rollback_enabled = false;
+            UpgradeManager::configure(
+                &s.env,
+                admin.clone(),
+                config,
+                Version::new(9, 9, 9),
+                s.genesis.clone(),
+            )
+            .unwrap();
+            UpgradeManager::propose_rollback(&s.env, s.signer(0), String::from_str(&s.env, "No"))
+        });
+        assert_eq!(result, Err(SwapTradeError::RollbackDisabled));
+        // Reconfiguring leaves the recorded version alone.
+        assert_eq!(s.current(), Version::new(1, 0, 0));
     }
 
     #[test]
     fn test_cancel_upgrade() {
-        let mut mgr = setup();
-        let id = mgr
-            .propose_upgrade(
-                "alice",
-                Version::new(2, 0, 0),
-                UpgradeType::Major,
-                "Major upgrade",
-                None,
-                vec![],
-                100,
-            )
+        let s = setup();
+        let id = s
+            .propose(Version::new(1, 1, 0), UpgradeType::Minor, 1, 100)
             .unwrap();
-
-        mgr.cancel_upgrade(id, "bob", 200).unwrap();
-        let proposal = mgr.get_proposal(id).unwrap();
-        assert_eq!(proposal.status, UpgradeStatus::Cancelled);
+        s.at(200, || {
+            UpgradeManager::cancel_upgrade(&s.env, id, s.signer(1))
+        })
+        .unwrap();
+        assert_eq!(s.status(id), UpgradeStatus::Cancelled);
+        assert_eq!(
+            s.at(300, || UpgradeManager::approve_upgrade(
+                &s.env,
+                id,
+                s.signer(2)
+            )),
+            Err(SwapTradeError::UpgradeNotPending)
+        );
     }
 
     #[test]
     fn test_cannot_cancel_executed() {
-        let mut mgr = setup();
-        let id = mgr
-            .propose_upgrade(
-                "alice",
-                Version::new(2, 0, 0),
-                UpgradeType::Major,
-                "Major upgrade",
-                None,
-                vec![],
-                100,
-            )
+        let s = setup();
+        let id = s
+            .propose(Version::new(1, 0, 1), UpgradeType::Patch, 1, 100)
             .unwrap();
-
-        mgr.approve_upgrade(id, "bob", 200).unwrap();
-        mgr.approve_upgrade(id, "carol", 200).unwrap();
-        mgr.approve_upgrade(id, "dave", 200).unwrap();
-        mgr.approve_upgrade(id, "eve", 200).unwrap();
-        mgr.execute_upgrade(id, 200 + 172800).unwrap();
-
-        let result = mgr.cancel_upgrade(id, "alice", 200 + 172900);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("executed"));
+        s.approve(id, 1, 100);
+        s.execute(id, 100 + 172800).unwrap();
+        assert_eq!(
+            s.at(100 + 172801, || {
+                UpgradeManager::cancel_upgrade(&s.env, id, s.signer(1))
+            }),
+            Err(SwapTradeError::ProposalAlreadyExecuted)
+        );
     }
 
     #[test]
-    fn test_pending_upgrades() {
-        let mut mgr = setup();
-        let id1 = mgr
-            .propose_upgrade(
-                "alice",
-                Version::new(2, 0, 0),
-                UpgradeType::Major,
-                "Upgrade 1",
-                None,
-                vec![],
-                100,
-            )
+    fn test_stale_proposal_cannot_execute() {
+        let s = setup();
+        let first = s
+            .propose(Version::new(1, 0, 1), UpgradeType::Patch, 1, 100)
             .unwrap();
-        let _id2 = mgr
-            .propose_upgrade(
-                "bob",
-                Version::new(1, 1, 0),
-                UpgradeType::Minor,
-                "Upgrade 2",
-                None,
-                vec![],
-                100,
-            )
+        let second = s
+            .propose(Version::new(1, 1, 0), UpgradeType::Minor, 2, 100)
             .unwrap();
-
-        assert_eq!(mgr.pending_upgrades().len(), 2);
-
-        mgr.cancel_upgrade(id1, "alice", 200).unwrap();
-        assert_eq!(mgr.pending_upgrades().len(), 1);
+        s.approve(first, 1, 100);
+        s.approve(second, 2, 100);
+        s.execute(first, 100 + 172800).unwrap();
+        assert_eq!(
+            s.execute(second, 100 + 172800),
+            Err(SwapTradeError::InvalidVersionBump)
+        );
     }
 
     #[test]
-    fn test_sequential_upgrades() {
-        let mut mgr = setup();
-
-        // First upgrade: 1.0.0 -> 1.1.0
-        let id1 = mgr
-            .propose_upgrade(
-                "alice",
-                Version::new(1, 1, 0),
-                UpgradeType::Minor,
-                "Feature upgrade",
-                None,
-                vec![],
-                100,
-            )
+    fn test_pending_upgrades() {
+        let s = setup();
+        let first = s
+            .propose(Version::new(1, 1, 0), UpgradeType::Minor, 1, 100)
             .unwrap();
-        mgr.approve_upgrade(id1, "bob", 200).unwrap();
-        mgr.approve_upgrade(id1, "carol", 200).unwrap();
-        mgr.execute_upgrade(id1, 200 + 172800).unwrap();
-
-        assert_eq!(mgr.current_version(), &Version::new(1, 1, 0));
-
-        // Second upgrade: 1.1.0 -> 2.0.0
-        let id2 = mgr
-            .propose_upgrade(
-                "alice",
-                Version::new(2, 0, 0),
-                UpgradeType::Major,
-                "Major upgrade",
-                None,
-                vec![],
-                300 + 172800,
-            )
+        let second = s
+            .propose(Version::new(1, 0, 1), UpgradeType::Patch, 2, 100)
             .unwrap();
-        mgr.approve_upgrade(id2, "bob", 400 + 172800).unwrap();
-        mgr.approve_upgrade(id2, "carol", 400 + 172800).unwrap();
-        mgr.approve_upgrade(id2, "dave", 400 + 172800).unwrap();
-        mgr.approve_upgrade(id2, "eve", 400 + 172800).unwrap();
-        mgr.execute_upgrade(id2, 400 + 172800 + 172800).unwrap();
-
-        assert_eq!(mgr.current_version(), &Version::new(2, 0, 0));
-        assert_eq!(mgr.history().len(), 2);
+        s.at(200, || {
+            UpgradeManager::cancel_upgrade(&s.env, first, s.signer(1))
+        })
+        .unwrap();
+        assert_eq!(
+            s.at(200, || UpgradeManager::pending_upgrades(&s.env)),
+            Vec::from_array(&s.env, [second])
+        );
     }
 
     #[test]
     fn test_proposal_expiry() {
-        let signers = vec!["alice".to_string(), "bob".to_string(), "carol".to_string()];
-        let config = UpgradeConfig {
-            max_proposal_lifetime: 3600, // 1 hour
-            ..UpgradeConfig::default()
-        };
-        let mut mgr = UpgradeManager::new(Version::new(1, 0, 0), signers, config);
-
-        let id = mgr
-            .propose_upgrade(
-                "alice",
-                Version::new(2, 0, 0),
-                UpgradeType::Major,
-                "Upgrade",
-                None,
-                vec![],
-                100,
-            )
+        let s = setup();
+        let id = s
+            .propose(Version::new(1, 1, 0), UpgradeType::Minor, 1, 100)
             .unwrap();
-
-        // Try to approve after expiry
-        let result = mgr.approve_upgrade(id, "bob", 100 + 3601);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("expired"));
-
-        let proposal = mgr.get_proposal(id).unwrap();
-        assert_eq!(proposal.status, UpgradeStatus::Cancelled);
+        assert_eq!(
+            s.at(100 + 604801, || {
+                UpgradeManager::approve_upgrade(&s.env, id, s.signer(1))
+            }),
+            Err(SwapTradeError::UpgradeProposalExpired)
+        );
+        assert_eq!(s.status(id), UpgradeStatus::Cancelled);
     }
 }
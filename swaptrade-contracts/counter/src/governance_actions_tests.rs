@@ -0,0 +1,237 @@
+#![cfg(test)]
+
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::{symbol_short, Address, Env, Map, Symbol, Vec};
+
+use crate::errors::ContractError;
+use crate::faucet;
+use crate::gauge_controller::GaugeController;
+use crate::governance_params::{GovernanceParams, ParamKey};
+use crate::governance_system::{self, GovernanceSystem, ProposalAction};
+use crate::governance_types::ProposalType;
+use crate::kyc::{KYCFeature, KYCRequirement, KYCSystem};
+use crate::risk_management::volume_circuit_breaker;
+use crate::storage::{ADMIN_KEY, GOV_COUNCIL_KEY};
+use crate::vote_escrow::{VoteEscrowManager, MAX_LOCK_SECS, WEEK_SECS};
+use crate::{tiers, CounterContract, CounterContractClient, UserTier};
+
+const START: u64 = 100 * WEEK_SECS;
+
+struct Setup {
+    env: Env,
+    contract_id: Address,
+    client: CounterContractClient<'static>,
+    admin: Address,
+    voters: Vec<Address>,
+    council: Vec<Address>,
+}
+
+impl Setup {
+    /// Create a proposal, carry it through the vote, council signatures and
+    /// the 48h timelock, then execute it as an arbitrary non-admin caller.
+    fn pass(&self, action: ProposalAction) -> Result<(), ContractError> {
+        let env = &self.env;
+        // Each authorizing call runs in its own contract frame.
+        let id = env.as_contract(&self.contract_id, || {
+            governance_system::create_proposal(env, self.voters.get(0).unwrap(), action).unwrap()
+        });
+        // Voting closes as soon as the proposal passes and is queued.
+        for voter in self.voters.iter() {
+            match env.as_contract(&self.contract_id, || {
+                governance_system::cast_vote(env, voter, id, true)
+            }) {
+                Err(ContractError::VotingEnded) => break,
+                result => result.unwrap(),
+            }
+        }
+        for member in self.council.iter() {
+            env.as_contract(&self.contract_id, || {
+                governance_system::sign_proposal(env, member, id).unwrap()
+            });
+        }
+        env.ledger().with_mut(|l| l.timestamp += 172_800);
+        let executor = Address::generate(env);
+        env.as_contract(&self.contract_id, || {
+            governance_system::execute_proposal(env, executor, id)
+        })
+    }
+}
+
+fn setup() -> Setup {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|l| l.timestamp = START);
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+
+    let mut voters = Vec::new(&env);
+    let mut council = Vec::new(&env);
+    for _ in 0..5 {
+        voters.push_back(Address::generate(&env));
+        council.push_back(Address::generate(&env));
+    }
+
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(&ADMIN_KEY, &admin);
+        env.storage().persistent().set(&GOV_COUNCIL_KEY, &council);
+    });
+    for voter in voters.iter() {
+        client.mint(&symbol_short!("XLM"), &voter, &9);
+        client.create_ve_lock(&voter, &9, &(START + MAX_LOCK_SECS));
+    }
+
+    Setup {
+        env,
+        contract_id,
+        client,
+        admin,
+        voters,
+        council,
+    }
+}
+
+#[test]
+fn test_param_keys_write_through_to_live_settings() {
+    let s = setup();
+
+    s.pass(ProposalAction::UpdateGovParam(ParamKey::MaxHops, 3))
+        .unwrap();
+    s.pass(ProposalAction::UpdateGovParam(
+        ParamKey::MaxSlippageBps,
+        150,
+    ))
+    .unwrap();
+    s.pass(ProposalAction::UpdateGovParam(
+        ParamKey::GaugeEmissionBudget,
+        50_000,
+    ))
+    .unwrap();
+    s.pass(ProposalAction::UpdateGovParam(
+        ParamKey::KycTimelockDuration,
+        3 * 86_400,
+    ))
+    .unwrap();
+
+    assert_eq!(s.client.get_max_hops(), 3);
+    assert_eq!(s.client.kyc_get_timelock_duration(), 3 * 86_400);
+    s.env.as_contract(&s.contract_id, || {
+        let slippage: u32 = s
+            .env
+            .storage()
+            .instance()
+            .get(&symbol_short!("MAX_SLIP"))
+            .unwrap();
+        assert_eq!(slippage, 150);
+        assert_eq!(GaugeController::get_emission_budget(&s.env), 50_000);
+        assert_eq!(
+            GovernanceParams::get_param(&s.env, ParamKey::MaxHops),
+            Some(3)
+        );
+    });
+}
+
+#[test]
+fn test_out_of_bounds_param_is_not_applied() {
+    let s = setup();
+    assert_eq!(ParamKey::MaxHops.bounds(), (1, 3));
+
+    assert_eq!(
+        s.pass(ProposalAction::UpdateGovParam(ParamKey::MaxHops, 4)),
+        Err(ContractError::InvalidAmount)
+    );
+    assert_eq!(
+        s.pass(ProposalAction::UpdateGovParam(
+            ParamKey::KycPendingExpiry,
+            60
+        )),
+        Err(ContractError::InvalidAmount)
+    );
+    assert_eq!(s.client.get_max_hops(), 2);
+}
+
+#[test]
+fn test_structured_actions_execute_without_admin() {
+    let s = setup();
+    let pool_id = s.client.register_pool(
+        &s.admin,
+        &symbol_short!("XLM"),
+        &symbol_short!("BTC"),
+        &10_000,
+        &10_000,
+        &30,
+    );
+    let operator = Address::generate(&s.env);
+    let usdc = Symbol::new(&s.env, "USDCSIM");
+
+    s.pass(ProposalAction::UpdatePoolFeeTier(pool_id, 5))
+        .unwrap();
+    s.pass(ProposalAction::SetCircuitBreaker(600, 5_000))
+        .unwrap();
+    s.pass(ProposalAction::SetTierDiscount(UserTier::Whale, 7))
+        .unwrap();
+    s.pass(ProposalAction::SetFaucetConfig(usdc.clone(), 250, 3_600))
+        .unwrap();
+    s.pass(ProposalAction::AddGauge(pool_id)).unwrap();
+    s.pass(ProposalAction::AddKycOperator(operator.clone()))
+        .unwrap();
+
+    assert_eq!(s.client.get_pool(&pool_id).unwrap().fee_tier, 5);
+    assert_eq!(s.client.get_gauges().get(0), Some(pool_id));
+    assert!(s.client.kyc_is_operator(&operator));
+    s.env.as_contract(&s.contract_id, || {
+        let status = volume_circuit_breaker::get_status(&s.env);
+        assert_eq!((status.window, status.threshold), (600, 5_000));
+        assert_eq!(tiers::get_tier_discount_bps(&s.env, UserTier::Whale), 7);
+        let config = faucet::get_faucet_config(&s.env, usdc).unwrap();
+        assert_eq!((config.drip_amount, config.cooldown_secs), (250, 3_600));
+        assert!(KYCSystem::is_operator(&s.env, &operator));
+    });
+
+    s.pass(ProposalAction::RemoveKycOperator(operator.clone()))
+        .unwrap();
+    assert!(!s.client.kyc_is_operator(&operator));
+    let requirement = KYCRequirement {
+        min_level: 2,
+        jurisdictions: Vec::from_array(&s.env, [symbol_short!("GB")]),
+    };
+    s.pass(ProposalAction::SetKycRequirement(
+        KYCFeature::Margin,
+        requirement.clone(),
+    ))
+    .unwrap();
+    assert_eq!(
+        s.client.kyc_get_feature_requirement(&KYCFeature::Margin),
+        requirement
+    );
+    assert_eq!(
+        s.pass(ProposalAction::SetCircuitBreaker(0, 5_000)),
+        Err(ContractError::InvalidAmount)
+    );
+}
+
+#[test]
+fn test_proposal_types_map_to_actions() {
+    let s = setup();
+    let proposer = s.voters.get(0).unwrap();
+    s.env.as_contract(&s.contract_id, || {
+        assert_eq!(
+            GovernanceSystem::create_proposal(
+                &s.env,
+                &proposer,
+                ProposalType::Custom(symbol_short!("x"), symbol_short!("y")),
+                symbol_short!("desc"),
+                86_400,
+            ),
+            Err(ContractError::UnsupportedProposalType)
+        );
+        assert!(GovernanceSystem::create_proposal(
+            &s.env,
+            &proposer,
+            ProposalType::ParameterChange(ParamKey::CacheTtl, 120),
+            symbol_short!("desc"),
+            86_400,
+        )
+        .is_ok());
+    });
+}
@@ -3,10 +3,21 @@
 //!
 //! All critical parameter changes are queued with a mandatory delay before execution.
 //! Each update targets an isolated storage key so unrelated state is never touched.
+//!
+//! `ParamKey` is also the registry of scalar admin knobs that governance can
+//! turn: each key carries its own bounds, and applying a key both records the
+//! committed value and writes it through to the setting it controls.
 
 use soroban_sdk::{contracttype, symbol_short, Address, Env, Symbol};
 
 use crate::errors::SwapTradeError;
+use crate::gauge_controller::GaugeController;
+use crate::kyc::{
+    KYCSystem, MAX_GRACE_PERIOD, MIN_PENDING_EXPIRY_DURATION, MIN_TIMELOCK_DURATION,
+    MIN_VERIFICATION_VALIDITY,
+};
+use crate::risk_management::PositionLimits;
+use crate::tiers::UserTier;
 
 /// Minimum timelock delay: 24 hours in seconds.
 pub const PARAM_TIMELOCK_MIN: u64 = 86_400;
@@ -23,6 +34,75 @@ pub enum ParamKey {
     FeeBps,
     /// Rate-limit window in seconds (u64).
     RateLimitWindow,
+    /// Maximum hops for multi-hop route discovery (u32).
+    MaxHops,
+    /// Query cache TTL in seconds (u64).
+    CacheTtl,
+    /// Maximum slippage accepted by `safe_swap`, in basis points (u32).
+    MaxSlippageBps,
+    /// Farm emissions split across gauges per epoch (i128).
+    GaugeEmissionBudget,
+    /// Delay before a KYC override can be executed, in seconds (u64).
+    KycTimelockDuration,
+    /// Time after which a pending KYC submission expires, in seconds (u64).
+    KycPendingExpiry,
+    /// Time a KYC verification holds before re-verification, in seconds (u64).
+    KycVerificationValidity,
+    /// Grace window after a KYC verification lapses, in seconds (u64).
+    KycGracePeriod,
+    /// Base per-user position limit applied to every asset (i128).
+    MaxPositionPerAsset,
+    /// Base per-user portfolio limit in USD (i128).
+    MaxPortfolioValue,
+    /// Per-user position limit for one asset, replacing `MaxPositionPerAsset` (i128).
+    AssetPositionLimit(Symbol),
+    /// Share of the base limits granted to a tier, in basis points (u32).
+    TierPositionLimitBps(UserTier),
+    /// Cap on the total balance of an asset across all users (i128).
+    OpenInterestCap(Symbol),
+    /// Largest leverage a tier may open margin positions at, in basis points (u32).
+    MaxLeverageBps(UserTier),
+    /// Share of treasury pool fees paid to the insurance fund, in basis points (u32).
+    InsuranceFeeShareBps,
+    /// Share of liquidation penalties paid to the insurance fund, in basis points (u32).
+    InsurancePenaltyShareBps,
+    /// Largest shortfall from the oracle value a margin liquidation sale accepts, in basis points (u32).
+    LiquidationSlippageBps,
+}
+
+impl ParamKey {
+    /// Inclusive `(min, max)` range a value for this parameter must fall in.
+    pub fn bounds(&self) -> (i128, i128) {
+        match self {
+            ParamKey::MaxSwapAmount => (1, 1_000_000_000_000_000_000),
+            // Fee must be 0–10 000 bps (0–100 %).
+            ParamKey::FeeBps => (0, 10_000),
+            // Window must be at least 60 s and at most 7 days.
+            ParamKey::RateLimitWindow => (60, 604_800),
+            // Route discovery never searches more than three hops.
+            ParamKey::MaxHops => (1, 3),
+            ParamKey::CacheTtl => (0, 86_400),
+            ParamKey::MaxSlippageBps => (0, 10_000),
+            ParamKey::GaugeEmissionBudget => (0, 1_000_000_000_000_000_000),
+            ParamKey::KycTimelockDuration => (MIN_TIMELOCK_DURATION as i128, 30 * 86_400),
+            ParamKey::KycPendingExpiry => (MIN_PENDING_EXPIRY_DURATION as i128, 365 * 86_400),
+            ParamKey::KycVerificationValidity => {
+                (MIN_VERIFICATION_VALIDITY as i128, 5 * 365 * 86_400)
+            }
+            ParamKey::KycGracePeriod => (0, MAX_GRACE_PERIOD as i128),
+            ParamKey::MaxPositionPerAsset
+            | ParamKey::MaxPortfolioValue
+            | ParamKey::AssetPositionLimit(_)
+            | ParamKey::OpenInterestCap(_) => (1, 1_000_000_000_000_000_000),
+            // A tier never gets more than the full base limit.
+            ParamKey::TierPositionLimitBps(_) => (1, 10_000),
+            // From unlevered (1x) up to 10x.
+            ParamKey::MaxLeverageBps(_) => (10_000, 100_000),
+            ParamKey::InsuranceFeeShareBps | ParamKey::InsurancePenaltyShareBps => (0, 10_000),
+            // Liquidations never sell for less than half the oracle value.
+            ParamKey::LiquidationSlippageBps => (0, 5_000),
+        }
+    }
 }
 
 /// A queued parameter update waiting for the timelock to elapse.
@@ -184,7 +264,8 @@ impl GovernanceParams {
     /// Directly apply a parameter update (called by the main governance contract).
     ///
     /// This bypasses the internal timelock, as the main governance system
-    /// has its own timelock. It still validates the parameter value.
+    /// has its own timelock. It still validates the parameter value and
+    /// writes it through to the live setting the key controls.
     pub fn apply_param_update(
         env: &Env,
         param: ParamKey,
@@ -192,6 +273,7 @@ impl GovernanceParams {
     ) -> Result<(), SwapTradeError> {
         // The caller (main governance contract) is responsible for authorization.
         Self::validate_param_value(&param, new_value)?;
+        Self::write_through(env, &param, new_value)?;
         env.storage()
             .persistent()
             .set(&GovParamStorageKey::ParamValue(param.clone()), &new_value);
@@ -220,23 +302,64 @@ impl GovernanceParams {
 
     /// Validate that `value` is within the acceptable range for `param` (#156).
     fn validate_param_value(param: &ParamKey, value: i128) -> Result<(), SwapTradeError> {
+        let (min, max) = param.bounds();
+        if value < min || value > max {
+            return Err(SwapTradeError::InvalidAmount);
+        }
+        Ok(())
+    }
+
+    /// Push a validated value into the storage its subsystem reads.
+    /// Keys without a live consumer are only recorded as `ParamValue`.
+    fn write_through(env: &Env, param: &ParamKey, value: i128) -> Result<(), SwapTradeError> {
+        let actor = env.current_contract_address();
         match param {
-            ParamKey::MaxSwapAmount => {
-                if value <= 0 || value > 1_000_000_000_000_000_000 {
-                    return Err(SwapTradeError::InvalidAmount);
-                }
+            ParamKey::MaxSwapAmount | ParamKey::FeeBps | ParamKey::RateLimitWindow => {}
+            ParamKey::MaxHops => {
+                let mut registry = crate::load_pool_registry(env);
+                registry.set_max_hops(value as u32);
+                crate::save_pool_registry(env, &registry);
+            }
+            ParamKey::CacheTtl => {
+                env.storage()
+                    .instance()
+                    .set(&crate::CACHE_TTL_KEY, &(value as u64));
+            }
+            ParamKey::MaxSlippageBps => {
+                env.storage()
+                    .instance()
+                    .set(&symbol_short!("MAX_SLIP"), &(value as u32));
+            }
+            ParamKey::GaugeEmissionBudget => GaugeController::apply_emission_budget(env, value)?,
+            ParamKey::KycTimelockDuration => {
+                KYCSystem::apply_timelock_duration(env, &actor, value as u64)?
+            }
+            ParamKey::KycPendingExpiry => {
+                KYCSystem::apply_pending_expiry_duration(env, &actor, value as u64)?
+            }
+            ParamKey::KycVerificationValidity => {
+                KYCSystem::apply_verification_validity(env, &actor, value as u64)?
             }
-            ParamKey::FeeBps => {
-                // Fee must be 0–10 000 bps (0–100 %).
-                if value < 0 || value > 10_000 {
-                    return Err(SwapTradeError::InvalidAmount);
-                }
+            ParamKey::KycGracePeriod => KYCSystem::apply_grace_period(env, &actor, value as u64)?,
+            ParamKey::MaxPositionPerAsset => {
+                let mut config = PositionLimits::get_risk_config(env);
+                config.max_position_per_asset = value;
+                PositionLimits::set_risk_config(env, &config);
             }
-            ParamKey::RateLimitWindow => {
-                // Window must be at least 60 s and at most 7 days.
-                if value < 60 || value > 604_800 {
-                    return Err(SwapTradeError::InvalidAmount);
-                }
+            ParamKey::MaxPortfolioValue => {
+                let mut config = PositionLimits::get_risk_config(env);
+                config.max_position_per_user = value;
+                PositionLimits::set_risk_config(env, &config);
+            }
+            // Read straight from the committed `ParamValue` by `PositionLimits`.
+            ParamKey::AssetPositionLimit(_)
+            | ParamKey::OpenInterestCap(_)
+            | ParamKey::MaxLeverageBps(_)
+            | ParamKey::InsuranceFeeShareBps
+            | ParamKey::InsurancePenaltyShareBps
+            | ParamKey::LiquidationSlippageBps => {}
+            ParamKey::TierPositionLimitBps(tier) => {
+                PositionLimits::validate_tier_limit_bps(env, tier, value as u32)?
             }
         }
         Ok(())
@@ -265,23 +388,31 @@ mod tests {
         (env, contract_id, admin)
     }
 
+    fn pass_timelock(env: &Env) {
+        env.ledger().with_mut(|l| {
+            l.timestamp += PARAM_TIMELOCK_DEFAULT + 1;
+        });
+    }
+
     #[test]
     fn test_propose_and_execute_after_timelock() {
         let (env, contract_id, admin) = setup();
-        env.as_contract(&contract_id, || {
-            let id = GovernanceParams::propose_update(&env, &admin, ParamKey::FeeBps, 300).unwrap();
+        let id = env.as_contract(&contract_id, || {
+            GovernanceParams::propose_update(&env, &admin, ParamKey::FeeBps, 300).unwrap()
+        });
 
-            // Execution before delay must fail.
+        // Execution before delay must fail.
+        env.as_contract(&contract_id, || {
             assert_eq!(
                 GovernanceParams::execute_update(&env, &admin, id),
                 Err(SwapTradeError::KYCTimelockNotElapsed)
             );
+        });
 
-            // Advance ledger past the default delay.
-            env.ledger().with_mut(|l| {
-                l.timestamp = env.ledger().timestamp() + PARAM_TIMELOCK_DEFAULT + 1;
-            });
+        // Advance ledger past the default delay.
+        pass_timelock(&env);
 
+        env.as_contract(&contract_id, || {
             GovernanceParams::execute_update(&env, &admin, id).unwrap();
             assert_eq!(
                 GovernanceParams::get_param(&env, ParamKey::FeeBps),
@@ -293,12 +424,14 @@ mod tests {
     #[test]
     fn test_duplicate_execution_fails() {
         let (env, contract_id, admin) = setup();
+        let id = env.as_contract(&contract_id, || {
+            GovernanceParams::propose_update(&env, &admin, ParamKey::FeeBps, 100).unwrap()
+        });
+        pass_timelock(&env);
         env.as_contract(&contract_id, || {
-            let id = GovernanceParams::propose_update(&env, &admin, ParamKey::FeeBps, 100).unwrap();
-            env.ledger().with_mut(|l| {
-                l.timestamp = env.ledger().timestamp() + PARAM_TIMELOCK_DEFAULT + 1;
-            });
             GovernanceParams::execute_update(&env, &admin, id).unwrap();
+        });
+        env.as_contract(&contract_id, || {
             assert_eq!(
                 GovernanceParams::execute_update(&env, &admin, id),
                 Err(SwapTradeError::KYCOverrideAlreadyExecuted)
@@ -309,13 +442,15 @@ mod tests {
     #[test]
     fn test_invalid_param_value_rejected() {
         let (env, contract_id, admin) = setup();
+        // Fee > 10 000 bps is invalid.
         env.as_contract(&contract_id, || {
-            // Fee > 10 000 bps is invalid.
             assert_eq!(
                 GovernanceParams::propose_update(&env, &admin, ParamKey::FeeBps, 20_000),
                 Err(SwapTradeError::InvalidAmount)
             );
-            // Negative max swap amount is invalid.
+        });
+        // Negative max swap amount is invalid.
+        env.as_contract(&contract_id, || {
             assert_eq!(
                 GovernanceParams::propose_update(&env, &admin, ParamKey::MaxSwapAmount, -1),
                 Err(SwapTradeError::InvalidAmount)
@@ -326,12 +461,12 @@ mod tests {
     #[test]
     fn test_only_target_param_changes() {
         let (env, contract_id, admin) = setup();
+        // Set FeeBps to 200.
+        let id = env.as_contract(&contract_id, || {
+            GovernanceParams::propose_update(&env, &admin, ParamKey::FeeBps, 200).unwrap()
+        });
+        pass_timelock(&env);
         env.as_contract(&contract_id, || {
-            // Set FeeBps to 200.
-            let id = GovernanceParams::propose_update(&env, &admin, ParamKey::FeeBps, 200).unwrap();
-            env.ledger().with_mut(|l| {
-                l.timestamp = env.ledger().timestamp() + PARAM_TIMELOCK_DEFAULT + 1;
-            });
             GovernanceParams::execute_update(&env, &admin, id).unwrap();
 
             // MaxSwapAmount must remain unset — no side effects.
@@ -1,7 +1,184 @@
-use soroban_sdk::{Env, Symbol};
+//! Per-pool metrics gathered during a replay and the baseline/proposal diff
 
-pub fn analyze_impact(env: &Env, proposal_id: Symbol) -> i32 {
-    // Example: risk/impact score between 0-100
-    let base_impact: i32 = env.storage().get_unchecked(&format!("impact_{}", proposal_id)).unwrap_or(50);
-    base_impact
-}
\ No newline at end of file
+use soroban_sdk::{contracttype, Env, Symbol, Vec};
+
+use crate::LiquidityPool;
+
+/// Fixed-point scale for prices and LP share values.
+pub const SCALE: i128 = 10_000_000;
+const BPS: i128 = 10_000;
+
+/// What one replay did to one pool.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PoolMetrics {
+    pub pool_id: u64,
+    /// Swap input volume on each side of the pair.
+    pub volume_a: i128,
+    pub volume_b: i128,
+    /// Swap fees accrued to LPs on each side of the pair.
+    pub fees_a: i128,
+    pub fees_b: i128,
+    pub swaps: u32,
+    /// Operations on this pool the contract refused.
+    pub rejected: u32,
+    /// Value of `SCALE` LP tokens in token A, unclaimed fees included.
+    pub lp_share_value_start: i128,
+    pub lp_share_value_end: i128,
+    /// Token B per token A, scaled by `SCALE`.
+    pub price_start: i128,
+    pub price_end: i128,
+    /// Largest single swap relative to the input reserve.
+    pub max_price_impact_bps: u32,
+}
+
+impl PoolMetrics {
+    pub fn new(pool: &LiquidityPool) -> Self {
+        Self {
+            pool_id: pool.pool_id,
+            lp_share_value_start: share_value(pool),
+            lp_share_value_end: share_value(pool),
+            price_start: mid_price(pool),
+            price_end: mid_price(pool),
+            ..Default::default()
+        }
+    }
+
+    pub fn record_swap(
+        &mut self,
+        before: &LiquidityPool,
+        after: &LiquidityPool,
+        token_in: &Symbol,
+        amount_in: i128,
+    ) {
+        let reserve_in = if *token_in == before.token_a {
+            self.volume_a += amount_in;
+            self.fees_a += after.accumulated_fees_a - before.accumulated_fees_a;
+            before.reserve_a
+        } else {
+            self.volume_b += amount_in;
+            self.fees_b += after.accumulated_fees_b - before.accumulated_fees_b;
+            before.reserve_b
+        };
+        let impact = if reserve_in <= 0 {
+            BPS
+        } else {
+            (amount_in * BPS / reserve_in).min(BPS)
+        };
+        self.max_price_impact_bps = self.max_price_impact_bps.max(impact as u32);
+        self.swaps += 1;
+    }
+
+    pub fn finish(&mut self, pool: &LiquidityPool) {
+        self.lp_share_value_end = share_value(pool);
+        self.price_end = mid_price(pool);
+    }
+
+    pub fn lp_return_bps(&self) -> i128 {
+        change_bps(self.lp_share_value_start, self.lp_share_value_end)
+    }
+
+    pub fn price_move_bps(&self) -> i128 {
+        change_bps(self.price_start, self.price_end)
+    }
+}
+
+/// Baseline and proposal metrics for one pool, with `proposed - baseline`
+/// deltas.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PoolImpact {
+    pub pool_id: u64,
+    pub baseline: PoolMetrics,
+    pub proposed: PoolMetrics,
+    pub fees_a_delta: i128,
+    pub fees_b_delta: i128,
+    pub volume_a_delta: i128,
+    pub volume_b_delta: i128,
+    pub lp_return_delta_bps: i128,
+    pub price_move_delta_bps: i128,
+    pub max_price_impact_delta_bps: i128,
+    pub rejected_delta: i128,
+}
+
+impl PoolImpact {
+    pub fn new(baseline: PoolMetrics, proposed: PoolMetrics) -> Self {
+        Self {
+            pool_id: baseline.pool_id,
+            fees_a_delta: proposed.fees_a - baseline.fees_a,
+            fees_b_delta: proposed.fees_b - baseline.fees_b,
+            volume_a_delta: proposed.volume_a - baseline.volume_a,
+            volume_b_delta: proposed.volume_b - baseline.volume_b,
+            lp_return_delta_bps: proposed.lp_return_bps() - baseline.lp_return_bps(),
+            price_move_delta_bps: proposed.price_move_bps() - baseline.price_move_bps(),
+            max_price_impact_delta_bps: proposed.max_price_impact_bps as i128
+                - baseline.max_price_impact_bps as i128,
+            rejected_delta: proposed.rejected as i128 - baseline.rejected as i128,
+            baseline,
+            proposed,
+        }
+    }
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SimulationReport {
+    pub ops_replayed: u32,
+    /// One entry per pool touched by the replay, ordered by pool id.
+    pub pools: Vec<PoolImpact>,
+}
+
+impl SimulationReport {
+    pub fn pool(&self, pool_id: u64) -> Option<PoolImpact> {
+        self.pools.iter().find(|impact| impact.pool_id == pool_id)
+    }
+
+    pub fn total_rejected_delta(&self) -> i128 {
+        self.pools.iter().map(|impact| impact.rejected_delta).sum()
+    }
+}
+
+/// Pair up baseline and proposal metrics by pool. Both replays start from
+/// the same ledger, so they touch the same pools.
+pub fn compare(
+    env: &Env,
+    ops_replayed: u32,
+    baseline: &Vec<PoolMetrics>,
+    proposed: &Vec<PoolMetrics>,
+) -> SimulationReport {
+    let mut pools = Vec::new(env);
+    for base in baseline.iter() {
+        let prop = proposed
+            .iter()
+            .find(|m| m.pool_id == base.pool_id)
+            .unwrap_or_else(|| base.clone());
+        pools.push_back(PoolImpact::new(base, prop));
+    }
+    SimulationReport {
+        ops_replayed,
+        pools,
+    }
+}
+
+/// Value of `SCALE` LP tokens in token A at the pool's mid price.
+pub fn share_value(pool: &LiquidityPool) -> i128 {
+    if pool.total_lp_tokens <= 0 || pool.reserve_b <= 0 {
+        return 0;
+    }
+    let fees_b_in_a = pool.accumulated_fees_b * pool.reserve_a / pool.reserve_b;
+    (2 * pool.reserve_a + pool.accumulated_fees_a + fees_b_in_a) * SCALE / pool.total_lp_tokens
+}
+
+pub fn mid_price(pool: &LiquidityPool) -> i128 {
+    if pool.reserve_a <= 0 {
+        return 0;
+    }
+    pool.reserve_b * SCALE / pool.reserve_a
+}
+
+fn change_bps(start: i128, end: i128) -> i128 {
+    if start == 0 {
+        return 0;
+    }
+    (end - start) * BPS / start
+}
@@ -1,7 +1,132 @@
-use soroban_sdk::{Env, Symbol, Map};
+//! Ledger forking, action application and replay
 
-pub fn simulate(env: &Env, proposal_id: Symbol) -> i32 {
-    // Example: calculate simulated effect of proposal
-    let effects: Map<Symbol, i32> = env.storage().get_unchecked(&"proposal_effects").unwrap_or_default();
-    *effects.get(&proposal_id).unwrap_or(&0)
-}
\ No newline at end of file
+use soroban_sdk::xdr::ScVal;
+use soroban_sdk::{Address, Env, IntoVal, Map, TryFromVal, Val, Vec};
+
+use super::analysis::{self, PoolMetrics, SimulationReport};
+use super::historical::SimOp;
+use crate::errors::SwapTradeError;
+use crate::governance_system::{self, ProposalAction};
+use crate::{CounterContract, CounterContractClient};
+
+/// Base PRNG seed of every fork; test environments start from all zeros.
+const FORK_PRNG_SEED: [u8; 32] = [1; 32];
+
+/// An independent copy of a contract's ledger state. Nothing done to a fork
+/// reaches the environment it was taken from.
+pub struct Fork {
+    pub env: Env,
+    pub contract_id: Address,
+}
+
+impl Fork {
+    /// Snapshot `env`'s ledger into a fresh environment and re-register the
+    /// contract at the same address. Instance storage survives the
+    /// re-registration.
+    pub fn new(env: &Env, contract_id: &Address) -> Self {
+        let fork = Env::from_snapshot(env.to_snapshot());
+        let contract_id = carry(env, &fork, contract_id);
+        fork.register_at(&contract_id, CounterContract, ());
+        // Mocked auths draw nonces from the base PRNG. Reseed it so replayed
+        // calls do not reuse nonces the source environment already spent.
+        fork.host().set_base_prng_seed(FORK_PRNG_SEED).unwrap();
+        fork.mock_all_auths();
+        Self {
+            env: fork,
+            contract_id,
+        }
+    }
+
+    pub fn client(&self) -> CounterContractClient<'_> {
+        CounterContractClient::new(&self.env, &self.contract_id)
+    }
+
+    /// Apply `action` as if proposal `proposal_id` had just executed.
+    pub fn apply(&self, proposal_id: u64, action: &ProposalAction) -> Result<(), SwapTradeError> {
+        self.env.as_contract(&self.contract_id, || {
+            governance_system::apply_action(&self.env, proposal_id, action)
+        })
+    }
+
+    /// Replay `ops` in order and collect metrics for every existing pool
+    /// they touch. Rejected operations are counted, not fatal.
+    pub fn replay(&self, ops: &Vec<SimOp>) -> Vec<PoolMetrics> {
+        let client = self.client();
+        let mut metrics: Map<u64, PoolMetrics> = Map::new(&self.env);
+
+        for op in ops.iter() {
+            let pool_id = op.pool_id();
+            let before = match client.get_pool(&pool_id) {
+                Some(pool) => pool,
+                None => continue,
+            };
+            let mut entry = metrics
+                .get(pool_id)
+                .unwrap_or_else(|| PoolMetrics::new(&before));
+
+            if !op.execute(&client) {
+                entry.rejected += 1;
+            } else if let SimOp::Swap(_, _, ref token_in, amount_in, _) = op {
+                let after = client.get_pool(&pool_id).unwrap();
+                entry.record_swap(&before, &after, token_in, amount_in);
+            }
+            metrics.set(pool_id, entry);
+        }
+
+        let mut result = Vec::new(&self.env);
+        for (pool_id, mut entry) in metrics.iter() {
+            if let Some(pool) = client.get_pool(&pool_id) {
+                entry.finish(&pool);
+            }
+            result.push_back(entry);
+        }
+        result
+    }
+}
+
+/// Dry-run `action` against the current state of `contract_id`: replay
+/// `ops` on a baseline fork and on a fork with the action applied, and
+/// report the difference. Fails if the action itself would fail.
+pub fn simulate(
+    env: &Env,
+    contract_id: &Address,
+    action: &ProposalAction,
+    ops: &Vec<SimOp>,
+) -> Result<SimulationReport, SwapTradeError> {
+    simulate_as(env, contract_id, 0, action, ops)
+}
+
+pub(crate) fn simulate_as(
+    env: &Env,
+    contract_id: &Address,
+    proposal_id: u64,
+    action: &ProposalAction,
+    ops: &Vec<SimOp>,
+) -> Result<SimulationReport, SwapTradeError> {
+    let baseline = Fork::new(env, contract_id);
+    let proposed = Fork::new(env, contract_id);
+    proposed.apply(proposal_id, &carry(env, &proposed.env, action))?;
+
+    let base_metrics = baseline.replay(&carry(env, &baseline.env, ops));
+    let prop_metrics = proposed.replay(&carry(env, &proposed.env, ops));
+
+    Ok(analysis::compare(
+        env,
+        ops.len(),
+        &carry(&baseline.env, env, &base_metrics),
+        &carry(&proposed.env, env, &prop_metrics),
+    ))
+}
+
+/// Move a value between environments. Host objects such as addresses and
+/// vectors are bound to the environment that created them.
+pub fn carry<T>(from: &Env, to: &Env, value: &T) -> T
+where
+    T: IntoVal<Env, Val> + TryFromVal<Env, Val>,
+    <T as TryFromVal<Env, Val>>::Error: core::fmt::Debug,
+{
+    let val: Val = value.into_val(from);
+    let xdr = ScVal::try_from_val(from, &val).unwrap();
+    let val = Val::try_from_val(to, &xdr).unwrap();
+    T::try_from_val(to, &val).unwrap()
+}
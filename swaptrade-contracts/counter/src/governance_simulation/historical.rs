@@ -1,6 +1,93 @@
-use soroban_sdk::{Env, Symbol};
+//! Recorded pool operations replayed by the simulation engine
 
-pub fn get_outcome(env: &Env, proposal_id: Symbol) -> i32 {
-    // Example: return historical approval percentage
-    env.storage().get_unchecked(&format!("historical_{}", proposal_id)).unwrap_or(0)
-}
\ No newline at end of file
+use soroban_sdk::{contracttype, Address, Env, Symbol, Vec};
+
+use crate::CounterContractClient;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SimOp {
+    /// `(pool_id, trader, token_in, amount_in, min_amount_out)`
+    Swap(u64, Address, Symbol, i128, i128),
+    /// `(pool_id, provider, amount_a, amount_b)`
+    AddLiquidity(u64, Address, i128, i128),
+    /// `(pool_id, provider, lp_tokens)`
+    RemoveLiquidity(u64, Address, i128),
+}
+
+impl SimOp {
+    pub fn pool_id(&self) -> u64 {
+        match *self {
+            SimOp::Swap(pool_id, _, _, _, _)
+            | SimOp::AddLiquidity(pool_id, _, _, _)
+            | SimOp::RemoveLiquidity(pool_id, _, _) => pool_id,
+        }
+    }
+
+    /// Run the operation through the contract's public entrypoints.
+    /// Returns whether the contract accepted it.
+    pub fn execute(&self, client: &CounterContractClient) -> bool {
+        match self {
+            SimOp::Swap(pool_id, trader, token_in, amount_in, min_out) => matches!(
+                client.try_pool_swap(pool_id, token_in, amount_in, min_out, trader),
+                Ok(Ok(_))
+            ),
+            SimOp::AddLiquidity(pool_id, provider, amount_a, amount_b) => matches!(
+                client.try_pool_add_liquidity(pool_id, amount_a, amount_b, provider),
+                Ok(Ok(_))
+            ),
+            SimOp::RemoveLiquidity(pool_id, provider, lp_tokens) => matches!(
+                client.try_pool_remove_liquidity(pool_id, lp_tokens, provider),
+                Ok(Ok(_))
+            ),
+        }
+    }
+}
+
+/// Rolling window of the most recent operations that succeeded against the
+/// live contract, oldest first.
+pub struct OpLog {
+    ops: Vec<SimOp>,
+    capacity: u32,
+}
+
+impl OpLog {
+    pub fn new(env: &Env, capacity: u32) -> Self {
+        Self {
+            ops: Vec::new(env),
+            capacity,
+        }
+    }
+
+    /// Add `op` to the window, dropping the oldest entry once full.
+    pub fn record(&mut self, op: SimOp) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.ops.len() >= self.capacity {
+            self.ops.pop_front();
+        }
+        self.ops.push_back(op);
+    }
+
+    /// Execute `op` against the live contract and record it if it succeeded.
+    pub fn run(&mut self, client: &CounterContractClient, op: SimOp) -> bool {
+        let ok = op.execute(client);
+        if ok {
+            self.record(op);
+        }
+        ok
+    }
+
+    pub fn ops(&self) -> Vec<SimOp> {
+        self.ops.clone()
+    }
+
+    pub fn len(&self) -> u32 {
+        self.ops.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+}
@@ -1,5 +1,25 @@
-pub mod engine;
+//! Dry-run harness for governance proposals
+//!
+//! A proposal's `ProposalAction` is applied to a fork of the contract's
+//! ledger in the Soroban test environment, then a recorded window of pool
+//! swaps and LP operations is replayed against that fork and against an
+//! untouched baseline fork. The report holds, per pool, the deltas in fees,
+//! volume, LP share value and risk metrics (price impact, price drift and
+//! rejected operations) so voters can see what a proposal would change
+//! before it executes.
+//!
+//! The live environment is never written to. Only the counter contract is
+//! re-registered in a fork; other natively registered test contracts must be
+//! re-registered by the caller through `Fork::env`.
+
 pub mod analysis;
-pub mod voting;
+pub mod engine;
 pub mod historical;
-pub mod scenarios;
\ No newline at end of file
+pub mod scenarios;
+pub mod voting;
+
+pub use analysis::{PoolImpact, PoolMetrics, SimulationReport};
+pub use engine::{simulate, Fork};
+pub use historical::{OpLog, SimOp};
+pub use scenarios::{simulate_scenarios, ScenarioReport};
+pub use voting::simulate_proposal;
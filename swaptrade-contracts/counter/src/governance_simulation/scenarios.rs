@@ -1,7 +1,60 @@
-use soroban_sdk::{Env, Symbol};
+//! Stress scenarios: the same action replayed against scaled activity
 
-pub fn simulate_scenario(env: &Env, proposal_id: Symbol, scenario_id: i32) -> i32 {
-    // Example: adjust proposal effect based on scenario
-    let base: i32 = env.storage().get_unchecked(&format!("scenario_{}_{}", proposal_id, scenario_id)).unwrap_or(50);
-    base
-}
\ No newline at end of file
+use soroban_sdk::{contracttype, Address, Env, Vec};
+
+use super::analysis::SimulationReport;
+use super::engine;
+use super::historical::SimOp;
+use crate::errors::SwapTradeError;
+use crate::governance_system::ProposalAction;
+
+const BPS: i128 = 10_000;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScenarioReport {
+    /// Activity multiplier in bps (10_000 = the recorded volumes).
+    pub volume_bps: u32,
+    pub report: SimulationReport,
+}
+
+/// Scale every amount in `ops` by `volume_bps`. LP burns are scaled too so
+/// providers never try to remove more than the scaled deposits minted.
+pub fn scale_ops(env: &Env, ops: &Vec<SimOp>, volume_bps: u32) -> Vec<SimOp> {
+    let scale = |amount: i128| amount * volume_bps as i128 / BPS;
+    let mut scaled = Vec::new(env);
+    for op in ops.iter() {
+        scaled.push_back(match op {
+            SimOp::Swap(pool_id, trader, token_in, amount_in, min_out) => {
+                SimOp::Swap(pool_id, trader, token_in, scale(amount_in), scale(min_out))
+            }
+            SimOp::AddLiquidity(pool_id, provider, amount_a, amount_b) => {
+                SimOp::AddLiquidity(pool_id, provider, scale(amount_a), scale(amount_b))
+            }
+            SimOp::RemoveLiquidity(pool_id, provider, lp_tokens) => {
+                SimOp::RemoveLiquidity(pool_id, provider, scale(lp_tokens))
+            }
+        });
+    }
+    scaled
+}
+
+/// Run `simulate` once per multiplier in `volume_bps`, e.g. `[5_000,
+/// 10_000, 30_000]` for half, recorded and triple activity.
+pub fn simulate_scenarios(
+    env: &Env,
+    contract_id: &Address,
+    action: &ProposalAction,
+    ops: &Vec<SimOp>,
+    volume_bps: &Vec<u32>,
+) -> Result<Vec<ScenarioReport>, SwapTradeError> {
+    let mut reports = Vec::new(env);
+    for bps in volume_bps.iter() {
+        let report = engine::simulate(env, contract_id, action, &scale_ops(env, ops, bps))?;
+        reports.push_back(ScenarioReport {
+            volume_bps: bps,
+            report,
+        });
+    }
+    Ok(reports)
+}
@@ -1,6 +1,31 @@
-use soroban_sdk::{Env, Symbol};
+//! Simulating proposals that are already up for a vote
 
-pub fn voting_power(env: &Env, user: Symbol) -> i32 {
-    // Example: fetch voting power from storage
-    env.storage().get_unchecked(&format!("voting_power_{}", user)).unwrap_or(0)
-}
\ No newline at end of file
+use soroban_sdk::{Address, Env, Map, Vec};
+
+use super::analysis::SimulationReport;
+use super::engine;
+use super::historical::SimOp;
+use crate::errors::SwapTradeError;
+use crate::governance_system::Proposal;
+use crate::storage::PROPOSALS_KEY;
+
+/// Dry-run the action of stored proposal `proposal_id`, so voters can check
+/// its impact while voting is still open.
+pub fn simulate_proposal(
+    env: &Env,
+    contract_id: &Address,
+    proposal_id: u64,
+    ops: &Vec<SimOp>,
+) -> Result<SimulationReport, SwapTradeError> {
+    let proposal: Proposal = env.as_contract(contract_id, || {
+        env.storage()
+            .persistent()
+            .get::<_, Map<u64, Proposal>>(&PROPOSALS_KEY)
+            .and_then(|proposals| proposals.get(proposal_id))
+            .ok_or(SwapTradeError::ProposalNotFound)
+    })?;
+    if proposal.executed {
+        return Err(SwapTradeError::ProposalAlreadyExecuted);
+    }
+    engine::simulate_as(env, contract_id, proposal_id, &proposal.action, ops)
+}
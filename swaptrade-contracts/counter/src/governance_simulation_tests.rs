@@ -0,0 +1,209 @@
+#![cfg(test)]
+
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::{symbol_short, vec, Address, Env, Map};
+
+use crate::errors::SwapTradeError;
+use crate::governance::quadratic_voting::VotingMode;
+use crate::governance_simulation::{
+    simulate, simulate_proposal, simulate_scenarios, OpLog, SimOp, SimulationReport,
+};
+use crate::governance_system::ProposalAction;
+use crate::kyc::KYCStatus;
+use crate::storage::ADMIN_KEY;
+use crate::vote_escrow::{MAX_LOCK_SECS, WEEK_SECS};
+use crate::{CounterContract, CounterContractClient};
+
+const START: u64 = 100 * WEEK_SECS;
+
+struct Setup {
+    env: Env,
+    contract_id: Address,
+    client: CounterContractClient<'static>,
+    pool_id: u64,
+    log: OpLog,
+}
+
+fn verified(env: &Env, client: &CounterContractClient, operator: &Address) -> Address {
+    let user = Address::generate(env);
+    client.kyc_submit(&user);
+    client.kyc_update_status(operator, &user, &KYCStatus::InReview, &None);
+    client.kyc_update_status(operator, &user, &KYCStatus::Verified, &None);
+    user
+}
+
+/// An XLM/USDC pool at the 5 bps tier with three swaps and a deposit
+/// recorded against it.
+fn setup() -> Setup {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|l| l.timestamp = START);
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    let operator = Address::generate(&env);
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(&ADMIN_KEY, &admin);
+    });
+    client.kyc_add_operator(&admin, &operator);
+    let trader = verified(&env, &client, &operator);
+    let provider = verified(&env, &client, &operator);
+
+    let xlm = symbol_short!("XLM");
+    let usdc = symbol_short!("USDC");
+    let pool_id = client.register_pool(&admin, &xlm, &usdc, &1_000_000, &1_000_000, &5);
+
+    let mut log = OpLog::new(&env, 10);
+    for op in [
+        SimOp::Swap(pool_id, trader.clone(), xlm.clone(), 10_000, 0),
+        SimOp::Swap(pool_id, trader.clone(), usdc.clone(), 10_000, 0),
+        SimOp::AddLiquidity(pool_id, provider.clone(), 50_000, 50_000),
+        SimOp::Swap(pool_id, trader.clone(), xlm.clone(), 20_000, 0),
+    ] {
+        assert!(log.run(&client, op));
+    }
+
+    Setup {
+        env,
+        contract_id,
+        client,
+        pool_id,
+        log,
+    }
+}
+
+/// Fee deltas on the `(XLM, USDC)` sides of the pool.
+fn fee_deltas(s: &Setup, report: &SimulationReport) -> (i128, i128) {
+    let impact = report.pool(s.pool_id).unwrap();
+    let pool = s.client.get_pool(&s.pool_id).unwrap();
+    if pool.token_a == symbol_short!("XLM") {
+        (impact.fees_a_delta, impact.fees_b_delta)
+    } else {
+        (impact.fees_b_delta, impact.fees_a_delta)
+    }
+}
+
+#[test]
+fn test_op_log_keeps_recent_window() {
+    let env = Env::default();
+    let trader = Address::generate(&env);
+    let mut log = OpLog::new(&env, 2);
+    for amount in 1..=3 {
+        log.record(SimOp::Swap(
+            1,
+            trader.clone(),
+            symbol_short!("XLM"),
+            amount,
+            0,
+        ));
+    }
+    assert_eq!(log.len(), 2);
+    assert_eq!(
+        log.ops().get(0),
+        Some(SimOp::Swap(1, trader, symbol_short!("XLM"), 2, 0))
+    );
+}
+
+#[test]
+fn test_fee_tier_change_reports_fee_and_lp_deltas() {
+    let s = setup();
+    let before = s.client.get_pool(&s.pool_id).unwrap();
+
+    let report = simulate(
+        &s.env,
+        &s.contract_id,
+        &ProposalAction::UpdatePoolFeeTier(s.pool_id, 30),
+        &s.log.ops(),
+    )
+    .unwrap();
+
+    assert_eq!(report.ops_replayed, 4);
+    let impact = report.pool(s.pool_id).unwrap();
+    // 5 -> 30 bps on 30k XLM and 10k USDC of swaps.
+    assert_eq!(fee_deltas(&s, &report), (75, 25));
+    assert_eq!(impact.baseline.swaps, 3);
+    assert_eq!(impact.volume_a_delta, 0);
+    assert_eq!(impact.volume_b_delta, 0);
+    assert_eq!(impact.rejected_delta, 0);
+    assert!(impact.lp_return_delta_bps > 0);
+    assert!(impact.proposed.lp_return_bps() > impact.baseline.lp_return_bps());
+
+    // The live contract is untouched.
+    assert_eq!(s.client.get_pool(&s.pool_id).unwrap(), before);
+}
+
+#[test]
+fn test_pause_rejects_replayed_activity() {
+    let s = setup();
+    let report = simulate(
+        &s.env,
+        &s.contract_id,
+        &ProposalAction::PauseTrading,
+        &s.log.ops(),
+    )
+    .unwrap();
+
+    let impact = report.pool(s.pool_id).unwrap();
+    assert_eq!(impact.rejected_delta, 4);
+    assert_eq!(report.total_rejected_delta(), 4);
+    assert_eq!(impact.proposed.swaps, 0);
+    assert_eq!(impact.proposed.max_price_impact_bps, 0);
+    assert!(impact.baseline.max_price_impact_bps > 0);
+    assert_eq!(impact.proposed.price_move_bps(), 0);
+    assert_eq!(
+        impact.volume_a_delta + impact.volume_b_delta,
+        -(impact.baseline.volume_a + impact.baseline.volume_b)
+    );
+}
+
+#[test]
+fn test_invalid_action_fails_simulation() {
+    let s = setup();
+    assert!(simulate(
+        &s.env,
+        &s.contract_id,
+        &ProposalAction::UpdatePoolFeeTier(s.pool_id, 7),
+        &s.log.ops(),
+    )
+    .is_err());
+}
+
+#[test]
+fn test_scenarios_scale_recorded_activity() {
+    let s = setup();
+    let reports = simulate_scenarios(
+        &s.env,
+        &s.contract_id,
+        &ProposalAction::UpdatePoolFeeTier(s.pool_id, 30),
+        &s.log.ops(),
+        &vec![&s.env, 10_000, 20_000],
+    )
+    .unwrap();
+
+    let recorded = reports.get(0).unwrap().report.pool(s.pool_id).unwrap();
+    let doubled = reports.get(1).unwrap().report.pool(s.pool_id).unwrap();
+    assert_eq!(doubled.baseline.volume_a, recorded.baseline.volume_a * 2);
+    assert_eq!(doubled.baseline.volume_b, recorded.baseline.volume_b * 2);
+    assert!(doubled.baseline.max_price_impact_bps > recorded.baseline.max_price_impact_bps);
+}
+
+#[test]
+fn test_simulate_pending_proposal() {
+    let s = setup();
+    let proposer = Address::generate(&s.env);
+    s.client.mint(&symbol_short!("XLM"), &proposer, &1_000);
+    s.client
+        .create_ve_lock(&proposer, &1_000, &(START + MAX_LOCK_SECS));
+    let id = s.client.create_governance_action(
+        &proposer,
+        &ProposalAction::UpdatePoolFeeTier(s.pool_id, 30),
+        &VotingMode::Simple,
+    );
+
+    let report = simulate_proposal(&s.env, &s.contract_id, id, &s.log.ops()).unwrap();
+    assert_eq!(fee_deltas(&s, &report), (75, 25));
+    assert_eq!(
+        simulate_proposal(&s.env, &s.contract_id, id + 1, &s.log.ops()),
+        Err(SwapTradeError::ProposalNotFound)
+    );
+}
@@ -1,15 +1,29 @@
 use soroban_sdk::{contracttype, symbol_short, Address, Env, Map, Symbol, Vec};
 
 use crate::errors::SwapTradeError;
-use crate::governance::delegation;
+use crate::gauge_controller::GaugeController;
+use crate::governance::delegation::{self, DelegationTopic};
 use crate::governance::multi_sig::MultiSig;
-use crate::governance::quadratic_voting::{self, Vote};
+use crate::governance::quadratic_voting::{self, Tally, Vote, VotingMode};
+use crate::governance::rewards::{RewardConfig, RewardManager};
+use crate::governance::treasury::Treasury;
 use crate::governance_params::{GovernanceParams, ParamKey};
 use crate::governance_types;
-use crate::storage::{
-    BALANCES_KEY, GOV_COUNCIL_KEY, PROPOSALS_KEY, PROPOSAL_STATE_KEY, TOTAL_SUPPLY_KEY,
-};
-
+use crate::insurance_fund::InsuranceFund;
+use crate::kyc::{KYCFeature, KYCRequirement, KYCSystem};
+use crate::risk_management::volume_circuit_breaker;
+use crate::storage::{GOV_COUNCIL_KEY, PROPOSALS_KEY, PROPOSAL_STATE_KEY};
+use crate::vote_escrow::VoteEscrowManager;
+use crate::{faucet, tiers, UserTier};
+
+/// Delay between a proposal passing and becoming executable.
+pub const TIMELOCK_SECS: u64 = 172_800;
+
+/// Everything a passed proposal can do. Scalar knobs go through
+/// `UpdateGovParam` and the bounds on `ParamKey`; setters that take more
+/// than one value get their own variant. Treasury, emergency and upgrade
+/// configuration are governed by their own signer sets instead; only
+/// grants are paid out of the treasury by token-holder vote.
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ProposalAction {
@@ -19,6 +33,79 @@ pub enum ProposalAction {
     SetTreasury(Address),
     UpdatePoolFeeTier(u64, u32),
     UpdateGovParam(ParamKey, i128),
+    /// Volume circuit breaker `(window_secs, max_volume)`.
+    SetCircuitBreaker(u64, i128),
+    ResetCircuitBreaker,
+    SetTierDiscount(UserTier, u32),
+    /// Faucet `(asset, drip_amount, cooldown_secs)`.
+    SetFaucetConfig(Symbol, i128, u64),
+    AddGauge(u64),
+    AddKycOperator(Address),
+    RemoveKycOperator(Address),
+    /// Minimum level and accepted jurisdictions of a feature.
+    SetKycRequirement(KYCFeature, KYCRequirement),
+    /// Treasury grant `(token, recipient, amount)`, always voted quadratically.
+    TreasuryGrant(Address, Address, i128),
+    /// Participation reward token, per-epoch budget and anti-gaming limits.
+    SetParticipationRewards(RewardConfig),
+    /// Pay a recorded shortfall from the insurance fund.
+    CoverShortfall(u64),
+}
+
+impl ProposalAction {
+    /// Topic used to resolve per-topic vote delegations.
+    pub fn topic(&self) -> DelegationTopic {
+        match self {
+            ProposalAction::SetTreasury(_)
+            | ProposalAction::TreasuryGrant(_, _, _)
+            | ProposalAction::SetParticipationRewards(_)
+            | ProposalAction::CoverShortfall(_) => DelegationTopic::Treasury,
+            ProposalAction::SetCircuitBreaker(_, _) | ProposalAction::ResetCircuitBreaker => {
+                DelegationTopic::RiskParameters
+            }
+            ProposalAction::UpdatePoolFeeTier(_, _)
+            | ProposalAction::SetTierDiscount(_, _)
+            | ProposalAction::SetFaucetConfig(_, _, _)
+            | ProposalAction::AddGauge(_) => DelegationTopic::Economics,
+            ProposalAction::UpdateGovParam(param, _) => match param {
+                ParamKey::MaxSwapAmount
+                | ParamKey::RateLimitWindow
+                | ParamKey::MaxHops
+                | ParamKey::MaxSlippageBps
+                | ParamKey::MaxPositionPerAsset
+                | ParamKey::MaxPortfolioValue
+                | ParamKey::AssetPositionLimit(_)
+                | ParamKey::TierPositionLimitBps(_)
+                | ParamKey::OpenInterestCap(_)
+                | ParamKey::MaxLeverageBps(_)
+                | ParamKey::LiquidationSlippageBps => DelegationTopic::RiskParameters,
+                ParamKey::FeeBps | ParamKey::GaugeEmissionBudget => DelegationTopic::Economics,
+                ParamKey::InsuranceFeeShareBps | ParamKey::InsurancePenaltyShareBps => {
+                    DelegationTopic::Treasury
+                }
+                ParamKey::CacheTtl
+                | ParamKey::KycTimelockDuration
+                | ParamKey::KycPendingExpiry
+                | ParamKey::KycVerificationValidity
+                | ParamKey::KycGracePeriod => DelegationTopic::Protocol,
+            },
+            ProposalAction::PauseTrading
+            | ProposalAction::ResumeTrading
+            | ProposalAction::SetAdmin(_)
+            | ProposalAction::AddKycOperator(_)
+            | ProposalAction::RemoveKycOperator(_)
+            | ProposalAction::SetKycRequirement(_, _) => DelegationTopic::Protocol,
+        }
+    }
+
+    /// Mode used when the proposer does not pick one. Grants must be voted
+    /// quadratically so a few large holders cannot direct treasury funds.
+    pub fn default_voting_mode(&self) -> VotingMode {
+        match self {
+            ProposalAction::TreasuryGrant(_, _, _) => VotingMode::Quadratic,
+            _ => VotingMode::Simple,
+        }
+    }
 }
 
 #[contracttype]
@@ -26,6 +113,7 @@ pub enum ProposalAction {
 pub struct Proposal {
     pub id: u64,
     pub action: ProposalAction,
+    pub mode: VotingMode,
     pub created_at: u64,
     pub created_by: Address,
     pub executed: bool,
@@ -44,18 +132,33 @@ pub fn create_proposal(
     env: &Env,
     caller: Address,
     action: ProposalAction,
+) -> Result<u64, SwapTradeError> {
+    let mode = action.default_voting_mode();
+    create_proposal_with_mode(env, caller, action, mode)
+}
+
+pub fn create_proposal_with_mode(
+    env: &Env,
+    caller: Address,
+    action: ProposalAction,
+    mode: VotingMode,
 ) -> Result<u64, SwapTradeError> {
     caller.require_auth();
 
-    let total_supply: u64 = env
-        .storage()
-        .persistent()
-        .get(&TOTAL_SUPPLY_KEY)
-        .unwrap_or(0);
-    let balance = get_token_balance(env, &caller);
+    if let ProposalAction::TreasuryGrant(_, _, amount) = action {
+        if mode != VotingMode::Quadratic {
+            return Err(SwapTradeError::InvalidVotingMode);
+        }
+        if amount <= 0 {
+            return Err(SwapTradeError::InvalidAmount);
+        }
+    }
+
+    let total_power = VoteEscrowManager::total_power(env);
+    let power = VoteEscrowManager::balance_of(env, &caller);
 
-    if balance < total_supply / 100 {
-        // 1% of total supply
+    if power < total_power / 100 {
+        // 1% of total voting power
         return Err(SwapTradeError::InsufficientBalance);
     }
 
@@ -75,6 +178,7 @@ pub fn create_proposal(
     let proposal = Proposal {
         id: proposal_id,
         action,
+        mode,
         created_at: env.ledger().timestamp(),
         created_by: caller,
         executed: false,
@@ -102,6 +206,7 @@ pub fn create_proposal(
         .persistent()
         .set(&PROPOSAL_STATE_KEY, &proposal_state);
 
+    RewardManager::record_proposal_created(env, &proposal.created_by);
     env.events()
         .publish((symbol_short!("prop_new"), proposal_id), proposal);
 
@@ -117,7 +222,7 @@ pub fn cast_vote(
     caller.require_auth();
 
     let mut proposals: Map<u64, Proposal> = env.storage().persistent().get(&PROPOSALS_KEY).unwrap();
-    let proposal = proposals
+    let mut proposal = proposals
         .get(proposal_id)
         .ok_or(SwapTradeError::ProposalNotFound)?;
 
@@ -137,45 +242,55 @@ pub fn cast_vote(
         env.storage().persistent().get(&PROPOSAL_STATE_KEY).unwrap();
     let mut state = proposal_state.get(proposal_id).unwrap();
 
-    if state.votes.iter().any(|v| v.voter == caller) {
-        return Err(SwapTradeError::AlreadyVoted);
+    if proposal.mode == VotingMode::Quadratic && !KYCSystem::is_verified(env, &caller) {
+        return Err(SwapTradeError::IdentityNotVerified);
     }
 
-    let effective_voter = delegation::get_delegate(env, &caller).unwrap_or(caller.clone());
-
-    let balance = get_token_balance(env, &effective_voter);
-    let vote_weight = quadratic_voting::calculate_voting_power(balance);
+    // Conviction voters may switch sides, which restarts their ramp; every
+    // other mode takes one vote per address.
+    let previous = state.votes.iter().position(|v| v.voter == caller);
+    if let Some(index) = previous {
+        let index = index as u32;
+        let prior = state.votes.get(index).unwrap();
+        if proposal.mode != VotingMode::Conviction {
+            return Err(SwapTradeError::AlreadyVoted);
+        }
+        if prior.in_favor == in_favor {
+            return Err(SwapTradeError::VoteUnchanged);
+        }
+        state.votes.remove(index);
+    }
 
-    let vote = Vote {
+    // Balances and delegations are snapshotted at proposal creation so
+    // tokens locked or redelegated afterwards cannot swing the outcome.
+    let power =
+        delegation::voting_power_at(env, &caller, proposal.action.topic(), proposal.created_at);
+    let mut vote = Vote {
         voter: caller.clone(),
-        vote_weight: if in_favor {
-            vote_weight as i64
-        } else {
-            -(vote_weight as i64)
-        },
+        in_favor,
+        power,
+        vote_weight: 0,
+        cast_at: env.ledger().timestamp(),
     };
+    let weight = quadratic_voting::vote_weight(proposal.mode, &vote, vote.cast_at) as i64;
+    vote.vote_weight = if in_favor { weight } else { -weight };
 
     state.votes.push_back(vote);
-    proposal_state.set(proposal_id, state.clone());
+    proposal_state.set(proposal_id, state);
+    // Switching sides under conviction voting is not new participation.
+    if previous.is_none() {
+        RewardManager::record_vote_cast(env, &caller, power);
+    }
     env.storage()
         .persistent()
         .set(&PROPOSAL_STATE_KEY, &proposal_state);
 
-    let (votes_for, votes_against) = quadratic_voting::tally_votes(&state.votes);
-    let total_supply: u64 = env
-        .storage()
-        .persistent()
-        .get(&TOTAL_SUPPLY_KEY)
-        .unwrap_or(0);
-    let total_votes = votes_for + votes_against;
-
-    if total_votes >= total_supply * 30 / 100 && votes_for * 100 >= (votes_for + votes_against) * 60
-    {
-        let mut proposals: Map<u64, Proposal> =
-            env.storage().persistent().get(&PROPOSALS_KEY).unwrap();
-        let mut proposal = proposals.get(proposal_id).unwrap();
-        proposal.executable_at = env.ledger().timestamp() + 172800; // 48 hours
-        proposals.set(proposal_id, proposal.clone());
+    // Simple and quadratic votes are final once cast, so the proposal is
+    // queued as soon as it passes. Conviction tallies keep moving with
+    // time and are queued through `queue_proposal`.
+    if proposal.mode != VotingMode::Conviction && passes(env, &proposal, proposal_id) {
+        proposal.executable_at = env.ledger().timestamp() + TIMELOCK_SECS;
+        proposals.set(proposal_id, proposal);
         env.storage().persistent().set(&PROPOSALS_KEY, &proposals);
     }
 
@@ -187,6 +302,77 @@ pub fn cast_vote(
     Ok(())
 }
 
+/// Start the timelock on a proposal that has reached quorum and approval
+/// under its voting mode. Anyone may call this.
+pub fn queue_proposal(env: &Env, proposal_id: u64) -> Result<u64, SwapTradeError> {
+    let mut proposals: Map<u64, Proposal> = env
+        .storage()
+        .persistent()
+        .get(&PROPOSALS_KEY)
+        .ok_or(SwapTradeError::ProposalNotFound)?;
+    let mut proposal = proposals
+        .get(proposal_id)
+        .ok_or(SwapTradeError::ProposalNotFound)?;
+
+    if proposal.executed {
+        return Err(SwapTradeError::ProposalAlreadyExecuted);
+    }
+    if proposal.canceled {
+        return Err(SwapTradeError::ProposalCanceled);
+    }
+    if proposal.executable_at > 0 {
+        return Err(SwapTradeError::VotingEnded);
+    }
+    check_outcome(env, &proposal, proposal_id)?;
+
+    proposal.executable_at = env.ledger().timestamp() + TIMELOCK_SECS;
+    proposals.set(proposal_id, proposal.clone());
+    env.storage().persistent().set(&PROPOSALS_KEY, &proposals);
+
+    env.events().publish(
+        (symbol_short!("prop_que"), proposal_id),
+        proposal.executable_at,
+    );
+
+    Ok(proposal.executable_at)
+}
+
+/// Current tally of a proposal under its voting mode.
+pub fn get_tally(env: &Env, proposal_id: u64) -> Result<Tally, SwapTradeError> {
+    let proposals: Map<u64, Proposal> = env
+        .storage()
+        .persistent()
+        .get(&PROPOSALS_KEY)
+        .ok_or(SwapTradeError::ProposalNotFound)?;
+    let proposal = proposals
+        .get(proposal_id)
+        .ok_or(SwapTradeError::ProposalNotFound)?;
+    Ok(tally(env, &proposal, proposal_id))
+}
+
+fn tally(env: &Env, proposal: &Proposal, proposal_id: u64) -> Tally {
+    let proposal_state: Map<u64, ProposalState> =
+        env.storage().persistent().get(&PROPOSAL_STATE_KEY).unwrap();
+    let state = proposal_state.get(proposal_id).unwrap();
+    quadratic_voting::tally_votes(proposal.mode, &state.votes, env.ledger().timestamp())
+}
+
+fn check_outcome(env: &Env, proposal: &Proposal, proposal_id: u64) -> Result<(), SwapTradeError> {
+    let tally = tally(env, proposal, proposal_id);
+    let total_power = VoteEscrowManager::total_power_at(env, proposal.created_at);
+    if !quadratic_voting::quorum_reached(proposal.mode, &tally, total_power) {
+        return Err(SwapTradeError::QuorumNotReached);
+    }
+    if !quadratic_voting::approved(&tally) {
+        return Err(SwapTradeError::ProposalFailed);
+    }
+    Ok(())
+}
+
+fn passes(env: &Env, proposal: &Proposal, proposal_id: u64) -> bool {
+    check_outcome(env, proposal, proposal_id).is_ok()
+}
+
 pub fn sign_proposal(env: &Env, caller: Address, proposal_id: u64) -> Result<(), SwapTradeError> {
     caller.require_auth();
 
@@ -214,15 +400,6 @@ pub fn sign_proposal(env: &Env, caller: Address, proposal_id: u64) -> Result<(),
     Ok(())
 }
 
-/// Multi-sig approval for a proposal (alias for sign_proposal).
-pub fn approve_proposal(
-    env: &Env,
-    caller: Address,
-    proposal_id: u64,
-) -> Result<(), SwapTradeError> {
-    sign_proposal(env, caller, proposal_id)
-}
-
 pub fn cancel_proposal(env: &Env, caller: Address, proposal_id: u64) -> Result<(), SwapTradeError> {
     caller.require_auth();
 
@@ -250,6 +427,7 @@ pub fn cancel_proposal(env: &Env, caller: Address, proposal_id: u64) -> Result<(
     Ok(())
 }
 
+/// Multi-sig approval for a proposal by a configured signer.
 pub fn approve_proposal(
     env: &Env,
     caller: Address,
@@ -283,7 +461,7 @@ pub fn approve_proposal(
     env.storage().persistent().set(&PROPOSALS_KEY, &proposals);
 
     env.events()
-        .publish((symbol_short!("prop_approve"), proposal_id), caller);
+        .publish((symbol_short!("prop_appr"), proposal_id), caller);
 
     Ok(())
 }
@@ -304,7 +482,8 @@ pub fn execute_proposal(
         return Err(SwapTradeError::ProposalAlreadyExecuted);
     }
 
-    if env.ledger().timestamp() < proposal.executable_at {
+    // Unqueued proposals have not passed and have no timelock running.
+    if proposal.executable_at == 0 || env.ledger().timestamp() < proposal.executable_at {
         return Err(SwapTradeError::TimelockNotElapsed);
     }
 
@@ -312,30 +491,27 @@ pub fn execute_proposal(
         return Err(SwapTradeError::InsufficientSignatures);
     }
 
-    let proposal_state: Map<u64, ProposalState> =
-        env.storage().persistent().get(&PROPOSAL_STATE_KEY).unwrap();
-    let state = proposal_state.get(proposal_id).unwrap();
+    check_outcome(env, &proposal, proposal_id)?;
+    apply_action(env, proposal_id, &proposal.action)?;
 
-    let (votes_for, votes_against) = quadratic_voting::tally_votes(&state.votes);
-
-    let total_votes = votes_for + votes_against;
-    let total_supply: u64 = env
-        .storage()
-        .persistent()
-        .get(&TOTAL_SUPPLY_KEY)
-        .unwrap_or(0);
+    proposal.executed = true;
+    proposals.set(proposal_id, proposal);
+    env.storage().persistent().set(&PROPOSALS_KEY, &proposals);
 
-    if total_votes < total_supply * 30 / 100 {
-        // 30% quorum
-        return Err(SwapTradeError::QuorumNotReached);
-    }
+    env.events()
+        .publish((symbol_short!("prop_exec"), proposal_id), caller);
 
-    if votes_for * 100 < (votes_for + votes_against) * 60 {
-        // 60% approval
-        return Err(SwapTradeError::ProposalFailed);
-    }
+    Ok(())
+}
 
-    match proposal.action {
+/// Carry out `action` without any voting, timelock or signature checks.
+/// Used by `execute_proposal` and by the simulation engine on forked state.
+pub(crate) fn apply_action(
+    env: &Env,
+    proposal_id: u64,
+    action: &ProposalAction,
+) -> Result<(), SwapTradeError> {
+    match *action {
         ProposalAction::PauseTrading => {
             env.storage()
                 .persistent()
@@ -357,32 +533,57 @@ pub fn execute_proposal(
                 .set(&crate::storage::DEFAULT_TREASURY_KEY, &new_treasury);
         }
         ProposalAction::UpdatePoolFeeTier(pool_id, new_fee_tier) => {
-            crate::update_pool_fee_tier(env.clone(), caller.clone(), pool_id, new_fee_tier)?
+            let mut registry = crate::load_pool_registry(env);
+            registry.set_fee_tier(env, pool_id, new_fee_tier)?;
+            crate::save_pool_registry(env, &registry);
         }
         ProposalAction::UpdateGovParam(ref param, new_value) => {
             GovernanceParams::apply_param_update(env, param.clone(), new_value)?
         }
+        ProposalAction::SetCircuitBreaker(window_secs, max_volume) => {
+            volume_circuit_breaker::apply_threshold(env, window_secs, max_volume)?
+        }
+        ProposalAction::ResetCircuitBreaker => volume_circuit_breaker::apply_reset(env),
+        ProposalAction::SetTierDiscount(ref tier, discount_bps) => {
+            tiers::apply_tier_discount_bps(env, tier.clone(), discount_bps)?
+        }
+        ProposalAction::SetFaucetConfig(ref asset, drip_amount, cooldown_secs) => {
+            faucet::apply_faucet_config(env, asset.clone(), drip_amount, cooldown_secs)?
+        }
+        ProposalAction::AddGauge(pool_id) => {
+            GaugeController::apply_add_gauge(env, &env.current_contract_address(), pool_id)?
+        }
+        ProposalAction::AddKycOperator(ref operator) => KYCSystem::apply_add_operator(
+            env,
+            &env.current_contract_address(),
+            operator.clone(),
+        )?,
+        ProposalAction::RemoveKycOperator(ref operator) => KYCSystem::apply_remove_operator(
+            env,
+            &env.current_contract_address(),
+            operator.clone(),
+        )?,
+        ProposalAction::SetKycRequirement(feature, ref requirement) => {
+            KYCSystem::apply_feature_requirement(
+                env,
+                &env.current_contract_address(),
+                feature,
+                requirement.clone(),
+            )?
+        }
+        ProposalAction::TreasuryGrant(ref token, ref recipient, amount) => {
+            Treasury::pay_grant(env, proposal_id, token, recipient, amount)?
+        }
+        ProposalAction::SetParticipationRewards(ref config) => {
+            RewardManager::set_config(env, config.clone())?
+        }
+        ProposalAction::CoverShortfall(shortfall_id) => {
+            InsuranceFund::cover_shortfall(env, shortfall_id).map(|_| ())?
+        }
     };
-
-    proposal.executed = true;
-    proposals.set(proposal_id, proposal);
-    env.storage().persistent().set(&PROPOSALS_KEY, &proposals);
-
-    env.events()
-        .publish((symbol_short!("prop_exec"), proposal_id), caller);
-
     Ok(())
 }
 
-fn get_token_balance(env: &Env, user: &Address) -> u64 {
-    let balances: Map<Address, u64> = env
-        .storage()
-        .persistent()
-        .get(&BALANCES_KEY)
-        .unwrap_or_else(|| Map::new(env));
-    balances.get(user.clone()).unwrap_or(0)
-}
-
 // ── GovernanceSystem wrapper for lib.rs contract interface ─────────────────
 
 pub struct GovernanceSystem;
@@ -391,12 +592,24 @@ impl GovernanceSystem {
     pub fn create_proposal(
         env: &Env,
         proposer: &Address,
-        _proposal_type: governance_types::ProposalType,
+        proposal_type: governance_types::ProposalType,
         _description: Symbol,
         _voting_period: u64,
     ) -> Result<u64, SwapTradeError> {
-        // Map governance_types::ProposalType to a ProposalAction
-        let action = ProposalAction::PauseTrading; // default; real impl maps per variant
+        let action = match proposal_type {
+            governance_types::ProposalType::ParameterChange(param, value) => {
+                ProposalAction::UpdateGovParam(param, value)
+            }
+            governance_types::ProposalType::AdminUpgrade(new_admin) => {
+                ProposalAction::SetAdmin(new_admin)
+            }
+            governance_types::ProposalType::EmergencyAction(true) => ProposalAction::PauseTrading,
+            governance_types::ProposalType::EmergencyAction(false) => ProposalAction::ResumeTrading,
+            // Free-form proposals carry no executable action.
+            governance_types::ProposalType::Custom(_, _) => {
+                return Err(SwapTradeError::UnsupportedProposalType)
+            }
+        };
         create_proposal(env, proposer.clone(), action)
     }
 
@@ -431,6 +644,9 @@ impl GovernanceSystem {
             .get(proposal_id)
             .ok_or(SwapTradeError::ProposalNotFound)?;
 
+        let tally = tally(env, &p, proposal_id);
+        let total_power = VoteEscrowManager::total_power_at(env, p.created_at);
+
         // Convert internal Proposal to governance_types::Proposal
         let status = if p.executed {
             governance_types::ProposalStatus::Executed
@@ -456,12 +672,12 @@ impl GovernanceSystem {
                 None
             },
             status,
-            votes_for: 0,
-            votes_against: 0,
+            votes_for: tally.votes_for as u128,
+            votes_against: tally.votes_against as u128,
             votes_abstain: 0,
-            total_voting_power: 0,
-            quorum_required: 0,
-            approval_threshold: 5000,
+            total_voting_power: total_power as u128,
+            quorum_required: (total_power * quadratic_voting::QUORUM_PCT / 100) as u128,
+            approval_threshold: (quadratic_voting::APPROVAL_PCT * 100) as u32,
             executed: p.executed,
         })
     }
@@ -471,6 +687,7 @@ impl GovernanceSystem {
 mod tests {
     use super::*;
     use crate::admin::set_admin;
+    use crate::vote_escrow::MAX_LOCK_SECS;
     use soroban_sdk::{
         testutils::{Address as _, Ledger},
         Address, Env,
@@ -483,14 +700,12 @@ mod tests {
         let admin = Address::generate(&env);
 
         let mut users: Vec<Address> = Vec::new(&env);
-        let mut balances: Map<Address, u64> = Map::new(&env);
-        let mut total_supply: u64 = 0;
+        let mut balances: Map<Address, i128> = Map::new(&env);
 
         for i in 0..10 {
             let user = Address::generate(&env);
             let balance = 100 * (i + 1);
             balances.set(user.clone(), balance);
-            total_supply += balance;
             users.push_back(user);
         }
 
@@ -499,12 +714,15 @@ mod tests {
             gov_council.push_back(Address::generate(&env));
         }
 
+        let client = crate::CounterContractClient::new(&env, &contract_id);
+        for user in users.iter() {
+            let balance = balances.get(user.clone()).unwrap();
+            client.mint(&symbol_short!("XLM"), &user, &balance);
+            client.create_ve_lock(&user, &balance, &(env.ledger().timestamp() + MAX_LOCK_SECS));
+        }
+
         env.as_contract(&contract_id, || {
             set_admin(&env, &admin);
-            env.storage().persistent().set(&BALANCES_KEY, &balances);
-            env.storage()
-                .persistent()
-                .set(&TOTAL_SUPPLY_KEY, &total_supply);
             env.storage()
                 .persistent()
                 .set(&GOV_COUNCIL_KEY, &gov_council);
@@ -536,17 +754,28 @@ mod tests {
     #[test]
     fn test_execute_proposal() {
         let (env, contract_id, admin, users) = setup();
-        env.as_contract(&contract_id, || {
-            let id =
-                create_proposal(&env, users.get(9).unwrap(), ProposalAction::PauseTrading).unwrap();
-
-            for i in 0..7 {
-                cast_vote(&env, users.get(i).unwrap(), id, true).unwrap();
-            }
+        let id = env.as_contract(&contract_id, || {
+            create_proposal(&env, users.get(9).unwrap(), ProposalAction::PauseTrading).unwrap()
+        });
 
-            for i in 7..10 {
+        for i in 0..3 {
+            env.as_contract(&contract_id, || {
                 cast_vote(&env, users.get(i).unwrap(), id, false).unwrap();
-            }
+            });
+        }
+
+        // The two largest holders carry quorum and approval, which
+        // queues the proposal and closes voting.
+        for i in [9, 8] {
+            env.as_contract(&contract_id, || {
+                cast_vote(&env, users.get(i).unwrap(), id, true).unwrap();
+            });
+        }
+        env.as_contract(&contract_id, || {
+            assert_eq!(
+                cast_vote(&env, users.get(7).unwrap(), id, false),
+                Err(SwapTradeError::VotingEnded)
+            );
 
             let mut proposals: Map<u64, Proposal> =
                 env.storage().persistent().get(&PROPOSALS_KEY).unwrap();
@@ -557,11 +786,13 @@ mod tests {
             }
             proposals.set(id, proposal.clone());
             env.storage().persistent().set(&PROPOSALS_KEY, &proposals);
+        });
 
-            env.ledger().with_mut(|l| {
-                l.timestamp = env.ledger().timestamp() + 172800 + 1;
-            });
+        env.ledger().with_mut(|l| {
+            l.timestamp += 172800 + 1;
+        });
 
+        env.as_contract(&contract_id, || {
             execute_proposal(&env, admin.clone(), id).unwrap();
         });
     }
@@ -106,19 +106,7 @@ pub enum GovernanceKey {
 }
 
 /// Parameter keys that can be governed
-#[contracttype]
-#[derive(Clone, Debug, PartialEq)]
-pub enum ParamKey {
-    MaxSwapAmount,
-    FeeBps,
-    RateLimitWindow,
-    MaxSlippageBps,
-    EmergencyPause,
-    RiskConfigMaxPosition,
-    RiskConfigConcentrationLimit,
-    GovernanceQuorumThreshold,
-    GovernanceApprovalThreshold,
-}
+pub use crate::governance_params::ParamKey;
 
 impl Default for GovernanceConfig {
     fn default() -> Self {
@@ -0,0 +1,259 @@
+//! Insurance fund
+//!
+//! The fund keeps one account per asset. It is paid a governance-set share of
+//! the pool fees paid to the treasury (`ParamKey::InsuranceFeeShareBps`) and
+//! of every liquidation penalty (`ParamKey::InsurancePenaltyShareBps`). Fee
+//! shares arrive as SEP-41 tokens the contract holds for the fund; penalty
+//! shares are Portfolio accounting, like the margin reserve they come from.
+//!
+//! Losses the protocol cannot recover are recorded as shortfalls: bad debt
+//! left by margin and NFT-loan liquidations, or losses from an exploit that
+//! the admin records. Only a governance proposal (`CoverShortfall`) pays one
+//! out. Margin shortfalls are restored to the reserve from the whole balance;
+//! other claimants are paid in tokens from the fund's custody. Whatever the
+//! fund cannot cover is socialised: margin suppliers keep the written-down
+//! reserve and other claimants go unpaid for the difference.
+
+use soroban_sdk::{contracttype, token, Address, Env, Symbol};
+
+use crate::errors::ContractError;
+use crate::events;
+use crate::governance_params::{GovernanceParams, ParamKey};
+use crate::margin::MarginManager;
+
+/// Share of treasury pool fees paid to the fund unless governance sets one.
+pub const DEFAULT_FEE_SHARE_BPS: u32 = 1_000;
+/// Share of liquidation penalties paid to the fund unless governance sets one.
+pub const DEFAULT_PENALTY_SHARE_BPS: u32 = 5_000;
+const BPS: i128 = 10_000;
+
+#[contracttype]
+#[derive(Clone)]
+enum InsuranceKey {
+    Account(Symbol),
+    Shortfall(u64),
+    NextShortfallId,
+}
+
+/// Where a shortfall came from, and so who a payout goes to.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LossSource {
+    /// Bad debt written off against the margin lending reserve.
+    MarginReserve,
+    /// An NFT loan `(loan_id, lender)` liquidated for less than was owed.
+    NftLoan(u64, Address),
+    /// A loss from an exploit, owed to the given account.
+    Exploit(Address),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct InsuranceAccount {
+    pub balance: i128,
+    /// Part of the balance held as SEP-41 tokens, from fee shares.
+    pub held: i128,
+    /// Token the held part is in, set by the first fee share.
+    pub custody_token: Option<Address>,
+    pub fees_received: i128,
+    pub penalties_received: i128,
+    pub paid_out: i128,
+    /// Losses the fund could not cover.
+    pub socialised: i128,
+    /// Recorded shortfalls not yet settled.
+    pub pending_shortfalls: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Shortfall {
+    pub id: u64,
+    pub asset: Symbol,
+    pub amount: i128,
+    pub source: LossSource,
+    pub recorded_at: u64,
+    pub covered: i128,
+    pub socialised: i128,
+    pub settled: bool,
+}
+
+pub struct InsuranceFund;
+
+impl InsuranceFund {
+    pub fn fee_share_bps(env: &Env) -> u32 {
+        GovernanceParams::get_param(env, ParamKey::InsuranceFeeShareBps)
+            .map(|bps| bps as u32)
+            .unwrap_or(DEFAULT_FEE_SHARE_BPS)
+    }
+
+    pub fn penalty_share_bps(env: &Env) -> u32 {
+        GovernanceParams::get_param(env, ParamKey::InsurancePenaltyShareBps)
+            .map(|bps| bps as u32)
+            .unwrap_or(DEFAULT_PENALTY_SHARE_BPS)
+    }
+
+    pub fn get_account(env: &Env, asset: &Symbol) -> InsuranceAccount {
+        env.storage()
+            .persistent()
+            .get(&InsuranceKey::Account(asset.clone()))
+            .unwrap_or_default()
+    }
+
+    fn save_account(env: &Env, asset: &Symbol, account: &InsuranceAccount) {
+        env.storage()
+            .persistent()
+            .set(&InsuranceKey::Account(asset.clone()), account);
+    }
+
+    /// Take the fund's share of `amount` of pool fees the contract has been
+    /// paid in `token`. Returns the share taken into custody; the caller
+    /// keeps the rest. Takes nothing while the fund still holds tokens of an
+    /// earlier mapping for `asset`.
+    pub fn receive_fees(env: &Env, asset: &Symbol, token: &Address, amount: i128) -> i128 {
+        let mut account = Self::get_account(env, asset);
+        if account.held > 0 && account.custody_token.as_ref() != Some(token) {
+            return 0;
+        }
+        let share = amount * Self::fee_share_bps(env) as i128 / BPS;
+        if share > 0 {
+            account.balance += share;
+            account.held += share;
+            account.custody_token = Some(token.clone());
+            account.fees_received += share;
+            Self::save_account(env, asset, &account);
+        }
+        share
+    }
+
+    /// Take the fund's share of a liquidation penalty. Returns the share
+    /// taken; the liquidator keeps the rest.
+    pub fn receive_penalty(env: &Env, asset: &Symbol, penalty: i128) -> i128 {
+        let share = penalty * Self::penalty_share_bps(env) as i128 / BPS;
+        if share > 0 {
+            let mut account = Self::get_account(env, asset);
+            account.balance += share;
+            account.penalties_received += share;
+            Self::save_account(env, asset, &account);
+        }
+        share
+    }
+
+    /// Settle a liquidation with the fund: take its share of `penalty` and
+    /// record any `bad_debt` as a shortfall owed to `source`. Returns the
+    /// share taken and the shortfall id.
+    pub fn settle_liquidation(
+        env: &Env,
+        asset: &Symbol,
+        penalty: i128,
+        bad_debt: i128,
+        source: LossSource,
+    ) -> (i128, Option<u64>) {
+        let insured = Self::receive_penalty(env, asset, penalty);
+        let shortfall =
+            (bad_debt > 0).then(|| Self::record_shortfall(env, asset.clone(), bad_debt, source));
+        (insured, shortfall)
+    }
+
+    /// Record a loss awaiting a governance payout. Returns the shortfall id.
+    pub fn record_shortfall(env: &Env, asset: Symbol, amount: i128, source: LossSource) -> u64 {
+        let id: u64 = env
+            .storage()
+            .persistent()
+            .get(&InsuranceKey::NextShortfallId)
+            .unwrap_or(1);
+        let shortfall = Shortfall {
+            id,
+            asset: asset.clone(),
+            amount,
+            source,
+            recorded_at: env.ledger().timestamp(),
+            covered: 0,
+            socialised: 0,
+            settled: false,
+        };
+        env.storage()
+            .persistent()
+            .set(&InsuranceKey::Shortfall(id), &shortfall);
+        env.storage()
+            .persistent()
+            .set(&InsuranceKey::NextShortfallId, &(id + 1));
+
+        let mut account = Self::get_account(env, &asset);
+        account.pending_shortfalls += amount;
+        Self::save_account(env, &asset, &account);
+
+        events::insurance_shortfall_recorded(env, id, asset, amount);
+        id
+    }
+
+    pub fn get_shortfall(env: &Env, id: u64) -> Option<Shortfall> {
+        env.storage().persistent().get(&InsuranceKey::Shortfall(id))
+    }
+
+    /// Settle a shortfall: pay what the fund can and socialise the rest.
+    /// Margin shortfalls draw on the whole balance, unbacked part first;
+    /// other claimants are paid only from tokens in custody. Called when a
+    /// `CoverShortfall` proposal executes. Returns `(covered, socialised)`.
+    pub fn cover_shortfall(env: &Env, id: u64) -> Result<(i128, i128), ContractError> {
+        let mut shortfall = Self::get_shortfall(env, id).ok_or(ContractError::ShortfallNotFound)?;
+        if shortfall.settled {
+            return Err(ContractError::ShortfallAlreadySettled);
+        }
+        let mut account = Self::get_account(env, &shortfall.asset);
+        let covered = match shortfall.source {
+            LossSource::MarginReserve => {
+                let covered = shortfall.amount.min(account.balance);
+                if covered > 0 {
+                    MarginManager::restore_reserve(env, &shortfall.asset, covered)?;
+                }
+                let unbacked = account.balance - account.held;
+                account.held -= (covered - unbacked).max(0);
+                covered
+            }
+            LossSource::NftLoan(_, ref claimant) | LossSource::Exploit(ref claimant) => {
+                let covered = shortfall.amount.min(account.held);
+                if covered > 0 {
+                    let token = account
+                        .custody_token
+                        .clone()
+                        .ok_or(ContractError::FeeTokenNotMapped)?;
+                    token::Client::new(env, &token).transfer(
+                        &env.current_contract_address(),
+                        claimant,
+                        &covered,
+                    );
+                    account.held -= covered;
+                }
+                covered
+            }
+        };
+        let socialised = shortfall.amount - covered;
+
+        account.balance -= covered;
+        account.paid_out += covered;
+        account.socialised += socialised;
+        account.pending_shortfalls -= shortfall.amount;
+        Self::save_account(env, &shortfall.asset, &account);
+
+        shortfall.covered = covered;
+        shortfall.socialised = socialised;
+        shortfall.settled = true;
+        env.storage()
+            .persistent()
+            .set(&InsuranceKey::Shortfall(id), &shortfall);
+
+        events::insurance_shortfall_settled(env, id, shortfall.asset, covered, socialised);
+        Ok((covered, socialised))
+    }
+
+    /// Fund balance against what it may have to cover: unsettled shortfalls
+    /// plus open margin debt in `asset`. `None` when there is nothing to cover.
+    pub fn coverage_ratio_bps(env: &Env, asset: &Symbol) -> Option<i128> {
+        let account = Self::get_account(env, asset);
+        let open_debt = MarginManager::get_reserve(env, asset)
+            .map(|reserve| reserve.total_debt())
+            .unwrap_or(0);
+        let liabilities = account.pending_shortfalls + open_debt;
+        (liabilities > 0).then(|| account.balance * BPS / liabilities)
+    }
+}
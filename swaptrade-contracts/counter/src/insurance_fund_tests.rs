@@ -0,0 +1,224 @@
+#![cfg(test)]
+
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::token::{StellarAssetClient, TokenClient};
+use soroban_sdk::{symbol_short, Address, Env, Symbol};
+
+use crate::errors::ContractError;
+use crate::governance_params::{GovernanceParams, ParamKey};
+use crate::governance_system::{apply_action, ProposalAction};
+use crate::insurance_fund::{InsuranceFund, LossSource};
+use crate::kyc::KYCStatus;
+use crate::storage::ADMIN_KEY;
+use crate::{CounterContract, CounterContractClient};
+
+const XLM: Symbol = symbol_short!("XLM");
+const USDC: Symbol = symbol_short!("USDC");
+
+struct Setup {
+    env: Env,
+    contract_id: Address,
+    client: CounterContractClient<'static>,
+    admin: Address,
+}
+
+fn setup() -> Setup {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(&ADMIN_KEY, &admin);
+    });
+    Setup {
+        env,
+        contract_id,
+        client,
+        admin,
+    }
+}
+
+impl Setup {
+    fn verified_trader(&self) -> Address {
+        let trader = Address::generate(&self.env);
+        let operator = Address::generate(&self.env);
+        self.client.kyc_add_operator(&self.admin, &operator);
+        self.client.kyc_submit(&trader);
+        self.client
+            .kyc_update_status(&operator, &trader, &KYCStatus::InReview, &None);
+        self.client
+            .kyc_update_status(&operator, &trader, &KYCStatus::Verified, &None);
+        trader
+    }
+
+    /// Pay the fund `fee_share_bps` of the 300 USDC fee on a swap, in a
+    /// USDC token it then holds. Returns the token.
+    fn fund_usdc_custody(&self, fee_share_bps: i128) -> Address {
+        let pool_id =
+            self.client
+                .register_pool(&self.admin, &XLM, &USDC, &1_000_000, &1_000_000, &30);
+        self.env.as_contract(&self.contract_id, || {
+            GovernanceParams::apply_param_update(
+                &self.env,
+                ParamKey::InsuranceFeeShareBps,
+                fee_share_bps,
+            )
+            .unwrap();
+        });
+        let usdc_token = self
+            .env
+            .register_stellar_asset_contract_v2(self.admin.clone())
+            .address();
+        self.client
+            .set_treasury_fee_token(&self.admin, &USDC, &usdc_token);
+        let trader = self.verified_trader();
+        StellarAssetClient::new(&self.env, &usdc_token).mint(&trader, &300);
+        self.client
+            .pool_swap(&pool_id, &USDC, &100_000, &0, &trader);
+        usdc_token
+    }
+
+    fn penalty(&self, amount: i128) -> i128 {
+        self.env.as_contract(&self.contract_id, || {
+            InsuranceFund::receive_penalty(&self.env, &USDC, amount)
+        })
+    }
+
+    fn cover(&self, shortfall_id: u64) -> Result<(), ContractError> {
+        self.env.as_contract(&self.contract_id, || {
+            apply_action(&self.env, 0, &ProposalAction::CoverShortfall(shortfall_id))
+        })
+    }
+}
+
+#[test]
+fn test_swap_fees_fund_the_insurance_fund() {
+    let s = setup();
+    let pool_id = s
+        .client
+        .register_pool(&s.admin, &XLM, &USDC, &1_000_000, &1_000_000, &30);
+    s.env.as_contract(&s.contract_id, || {
+        GovernanceParams::apply_param_update(&s.env, ParamKey::InsuranceFeeShareBps, 2_000)
+            .unwrap();
+    });
+    let xlm_token = s
+        .env
+        .register_stellar_asset_contract_v2(s.admin.clone())
+        .address();
+    let trader = s.verified_trader();
+    StellarAssetClient::new(&s.env, &xlm_token).mint(&trader, &300);
+    s.client.set_treasury_fee_token(&s.admin, &XLM, &xlm_token);
+
+    // 300 XLM of fees: 20% to the fund, the rest to the treasury.
+    s.client.pool_swap(&pool_id, &XLM, &100_000, &0, &trader);
+    let fund = s.client.get_insurance_fund(&XLM);
+    assert_eq!((fund.balance, fund.fees_received), (60, 60));
+    assert_eq!(s.client.get_treasury_balance(&xlm_token), 240);
+    let pool = s.client.get_pool(&pool_id).unwrap();
+    assert_eq!((pool.accumulated_fees_a, pool.accumulated_fees_b), (0, 0));
+}
+
+#[test]
+fn test_exhausted_fund_socialises_the_rest() {
+    let s = setup();
+    let usdc_token = s.fund_usdc_custody(5_000);
+    // Penalty shares add to the balance but are not held as tokens.
+    assert_eq!(s.penalty(1_000), 500);
+    let fund = s.client.get_insurance_fund(&USDC);
+    assert_eq!((fund.balance, fund.held), (650, 150));
+    assert_eq!(
+        TokenClient::new(&s.env, &usdc_token).balance(&s.contract_id),
+        300
+    );
+    let claimant = Address::generate(&s.env);
+
+    let outsider = Address::generate(&s.env);
+    assert!(s
+        .client
+        .try_record_exploit_loss(&outsider, &USDC, &800, &claimant)
+        .is_err());
+    let id = s
+        .client
+        .record_exploit_loss(&s.admin, &USDC, &800, &claimant);
+    assert_eq!(s.client.get_insurance_fund(&USDC).pending_shortfalls, 800);
+    assert_eq!(s.client.get_insurance_coverage_ratio(&USDC), Some(8_125));
+
+    // The claimant is paid in tokens, up to what the fund holds.
+    s.cover(id).unwrap();
+    assert_eq!(
+        TokenClient::new(&s.env, &usdc_token).balance(&claimant),
+        150
+    );
+    assert_eq!(s.client.balance_of(&USDC, &claimant), 0);
+    let shortfall = s.client.get_insurance_shortfall(&id).unwrap();
+    assert_eq!(shortfall.source, LossSource::Exploit(claimant));
+    assert_eq!((shortfall.covered, shortfall.socialised), (150, 650));
+    assert!(shortfall.settled);
+
+    let fund = s.client.get_insurance_fund(&USDC);
+    assert_eq!(
+        (fund.balance, fund.held, fund.paid_out, fund.socialised),
+        (500, 0, 150, 650)
+    );
+    assert_eq!(fund.pending_shortfalls, 0);
+    assert_eq!(s.client.get_insurance_coverage_ratio(&USDC), None);
+
+    assert_eq!(s.cover(id), Err(ContractError::ShortfallAlreadySettled));
+    assert_eq!(s.cover(id + 1), Err(ContractError::ShortfallNotFound));
+}
+
+#[test]
+fn test_nft_loan_liquidation_is_settled_with_the_fund() {
+    let s = setup();
+    let usdc_token = s.fund_usdc_custody(10_000);
+    let lender = Address::generate(&s.env);
+
+    let (insured, shortfall_id) = s.env.as_contract(&s.contract_id, || {
+        InsuranceFund::settle_liquidation(
+            &s.env,
+            &USDC,
+            1_000,
+            400,
+            LossSource::NftLoan(7, lender.clone()),
+        )
+    });
+    assert_eq!(insured, 500);
+    let id = shortfall_id.unwrap();
+    let shortfall = s.client.get_insurance_shortfall(&id).unwrap();
+    assert_eq!(shortfall.source, LossSource::NftLoan(7, lender.clone()));
+    assert_eq!(shortfall.amount, 400);
+
+    // Only the 300 held from fees can be paid out; the penalty share is not
+    // backed by tokens.
+    s.cover(id).unwrap();
+    assert_eq!(TokenClient::new(&s.env, &usdc_token).balance(&lender), 300);
+    let shortfall = s.client.get_insurance_shortfall(&id).unwrap();
+    assert_eq!((shortfall.covered, shortfall.socialised), (300, 100));
+    assert_eq!(s.client.get_insurance_fund(&USDC).balance, 500);
+
+    // Nothing is recorded without bad debt.
+    let (_, none) = s.env.as_contract(&s.contract_id, || {
+        InsuranceFund::settle_liquidation(&s.env, &USDC, 100, 0, LossSource::NftLoan(8, lender))
+    });
+    assert_eq!(none, None);
+}
+
+#[test]
+fn test_penalty_share_is_governed() {
+    let s = setup();
+    s.env.as_contract(&s.contract_id, || {
+        assert_eq!(
+            GovernanceParams::apply_param_update(
+                &s.env,
+                ParamKey::InsurancePenaltyShareBps,
+                10_001
+            ),
+            Err(ContractError::InvalidAmount)
+        );
+        GovernanceParams::apply_param_update(&s.env, ParamKey::InsurancePenaltyShareBps, 2_000)
+            .unwrap();
+    });
+    assert_eq!(s.penalty(1_000), 200);
+    assert_eq!(s.client.get_insurance_fund(&USDC).penalties_received, 200);
+}
@@ -10,8 +10,17 @@
 //! - Storage limits for KYC data (#160)
 //! - Event logging for all security-critical actions (#161)
 //! - Internal helpers are private — no unintended public exposure (#162)
-
-use soroban_sdk::{contracttype, symbol_short, Address, Env, Symbol, Vec};
+//! - Operator-signed attestations carrying a verification level and
+//!   jurisdiction, with a credential revocation list
+//! - Per-feature minimum level and jurisdiction requirements
+//! - Periodic re-verification: verifications lapse to `Expired` after a
+//!   validity period, with a grace window limited to reducing positions
+//! - Jurisdiction geofencing: each feature's requirement lists the
+//!   jurisdictions it is open in, checked against the user's record
+
+use soroban_sdk::{
+    contracttype, symbol_short, xdr::ToXdr, Address, Bytes, BytesN, Env, Symbol, Vec,
+};
 
 // ── Storage / input limits (#160) ─────────────────────────────────────────────
 
@@ -19,6 +28,12 @@ use soroban_sdk::{contracttype, symbol_short, Address, Env, Symbol, Vec};
 pub const MAX_OPERATORS: usize = 50;
 /// Maximum byte-length of a rejection reason Symbol (Soroban Symbol ≤ 32 chars).
 pub const MAX_REASON_LEN: u32 = 32;
+/// Maximum number of jurisdictions a feature requirement may list.
+pub const MAX_REQUIREMENT_JURISDICTIONS: u32 = 64;
+
+/// Level held by users verified through `update_status`, and the lowest level
+/// an attestation may carry.
+pub const BASE_KYC_LEVEL: u32 = 1;
 
 /// KYC verification states following a strict finite state machine
 #[contracttype]
@@ -36,6 +51,8 @@ pub enum KYCStatus {
     Verified = 4,
     /// Terminal state - KYC verification failed (immutable)
     Rejected = 5,
+    /// Verification validity period elapsed; the user must renew
+    Expired = 6,
 }
 
 impl KYCStatus {
@@ -57,6 +74,8 @@ impl KYCStatus {
 
             (KYCStatus::AdditionalInfoRequired, KYCStatus::InReview) => true,
 
+            (KYCStatus::Expired, KYCStatus::Pending) => true,
+
             _ => false,
         }
     }
@@ -78,6 +97,11 @@ pub struct KYCRecord {
     pub rejection_reason: Option<Symbol>,
     /// Timestamp when pending request expires (if status is Pending)
     pub expires_at: Option<u64>,
+    /// Timestamp when the last verification lapses; kept through renewal so
+    /// the grace window still applies, cleared on rejection or revocation
+    pub verified_until: Option<u64>,
+    /// Jurisdiction code the user was verified in, e.g. `GB`
+    pub jurisdiction: Option<Symbol>,
 }
 
 impl KYCRecord {
@@ -90,6 +114,8 @@ impl KYCRecord {
             updated_by: None,
             rejection_reason: None,
             expires_at: None,
+            verified_until: None,
+            jurisdiction: None,
         }
     }
 
@@ -105,6 +131,25 @@ impl KYCRecord {
         }
         false
     }
+
+    /// Check if the last verification has lapsed
+    pub fn is_lapsed(&self, current_time: u64) -> bool {
+        self.verified_until
+            .map_or(false, |verified_until| current_time >= verified_until)
+    }
+}
+
+/// KYC record as stored before verifications expired, under
+/// `KYCStorageKey::Record`
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct KYCRecordV1 {
+    pub status: KYCStatus,
+    pub updated_at: u64,
+    pub finalized_at: Option<u64>,
+    pub updated_by: Option<Address>,
+    pub rejection_reason: Option<Symbol>,
+    pub expires_at: Option<u64>,
 }
 
 /// Governance override request for terminal state changes
@@ -127,11 +172,73 @@ pub struct GovernanceOverride {
     pub executed: bool,
 }
 
+/// Features that can demand more than basic verification.
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KYCFeature {
+    Swap,
+    Margin,
+    NftLending,
+    Faucet,
+    ReferralPayout,
+}
+
+/// What a user must hold to use a feature
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KYCRequirement {
+    /// Minimum verification level
+    pub min_level: u32,
+    /// Jurisdictions the feature is open in; empty accepts any, including
+    /// users with no jurisdiction on record
+    pub jurisdictions: Vec<Symbol>,
+}
+
+/// Claims an operator signs after checking a user's documents off-chain
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KYCAttestation {
+    /// User the attestation is about
+    pub user: Address,
+    /// Jurisdiction code, e.g. `GB`
+    pub jurisdiction: Symbol,
+    /// Verification level, starting at `BASE_KYC_LEVEL`
+    pub level: u32,
+    /// Timestamp after which the attestation no longer vouches for the user
+    pub expires_at: u64,
+    /// Hash of the off-chain credential the operator checked
+    pub credential_hash: BytesN<32>,
+}
+
+/// A submitted attestation, kept for audit
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AttestationRecord {
+    /// The signed claims
+    pub attestation: KYCAttestation,
+    /// Operator whose key signed it
+    pub operator: Address,
+    /// Timestamp when it was submitted
+    pub submitted_at: u64,
+}
+
+/// An entry on the credential revocation list
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CredentialRevocation {
+    /// Operator who revoked the credential
+    pub revoked_by: Address,
+    /// Reason for revocation
+    pub reason: Symbol,
+    /// Timestamp of revocation
+    pub revoked_at: u64,
+}
+
 /// Storage keys for KYC system
 #[contracttype]
 #[derive(Clone, Debug)]
 pub enum KYCStorageKey {
-    /// KYC record for a user: KYCRecord(user_address)
+    /// Legacy KYC record for a user: Record(user_address) -> KYCRecordV1
     Record(Address),
     /// List of KYC operators
     Operators,
@@ -143,6 +250,22 @@ pub enum KYCStorageKey {
     TimelockDuration,
     /// Pending KYC expiry duration in seconds
     PendingExpiryDuration,
+    /// Ed25519 key an operator signs attestations with: OperatorKey(operator)
+    OperatorKey(Address),
+    /// Submitted attestation: Attestation(credential_hash)
+    Attestation(BytesN<32>),
+    /// Credential hash of a user's current attestation
+    UserAttestation(Address),
+    /// Revocation list entry: Revocation(credential_hash)
+    Revocation(BytesN<32>),
+    /// Requirement for a feature
+    FeatureRequirement(KYCFeature),
+    /// Verification validity period in seconds
+    VerificationValidity,
+    /// Grace window after a verification lapses, in seconds
+    GracePeriod,
+    /// KYC record for a user: RecordV2(user_address) -> KYCRecord
+    RecordV2(Address),
 }
 
 /// Timelock duration for governance overrides (7 days in seconds)
@@ -157,6 +280,18 @@ pub const DEFAULT_PENDING_EXPIRY_DURATION: u64 = 30 * 24 * 60 * 60;
 /// Minimum pending KYC expiry duration (7 days)
 pub const MIN_PENDING_EXPIRY_DURATION: u64 = 7 * 24 * 60 * 60;
 
+/// Default verification validity period (365 days in seconds)
+pub const DEFAULT_VERIFICATION_VALIDITY: u64 = 365 * 24 * 60 * 60;
+
+/// Minimum verification validity period (30 days)
+pub const MIN_VERIFICATION_VALIDITY: u64 = 30 * 24 * 60 * 60;
+
+/// Default grace window after a verification lapses (30 days)
+pub const DEFAULT_GRACE_PERIOD: u64 = 30 * 24 * 60 * 60;
+
+/// Maximum grace window (90 days)
+pub const MAX_GRACE_PERIOD: u64 = 90 * 24 * 60 * 60;
+
 /// KYC error type alias — all KYC errors are variants of the unified SwapTradeError.
 pub type KYCError = crate::errors::SwapTradeError;
 
@@ -192,7 +327,15 @@ impl KYCSystem {
         // Verify admin authorization
         admin.require_auth();
         crate::admin::require_admin(env, admin).map_err(|_| KYCError::NotKYCOperator)?;
+        Self::apply_add_operator(env, admin, operator)
+    }
 
+    /// Add an operator without an admin check; used by governance execution.
+    pub(crate) fn apply_add_operator(
+        env: &Env,
+        actor: &Address,
+        operator: Address,
+    ) -> Result<(), KYCError> {
         let mut operators: Vec<Address> = env
             .storage()
             .persistent()
@@ -214,7 +357,7 @@ impl KYCSystem {
             let timestamp = env.ledger().timestamp();
             env.events().publish(
                 (symbol_short!("kyc_op"), symbol_short!("added")),
-                (admin.clone(), operator, timestamp),
+                (actor.clone(), operator, timestamp),
             );
         }
 
@@ -225,7 +368,15 @@ impl KYCSystem {
     pub fn remove_operator(env: &Env, admin: &Address, operator: Address) -> Result<(), KYCError> {
         admin.require_auth();
         crate::admin::require_admin(env, admin).map_err(|_| KYCError::NotKYCOperator)?;
+        Self::apply_remove_operator(env, admin, operator)
+    }
 
+    /// Remove an operator without an admin check; used by governance execution.
+    pub(crate) fn apply_remove_operator(
+        env: &Env,
+        actor: &Address,
+        operator: Address,
+    ) -> Result<(), KYCError> {
         let operators: Vec<Address> = env
             .storage()
             .persistent()
@@ -244,12 +395,15 @@ impl KYCSystem {
         env.storage()
             .persistent()
             .set(&KYCStorageKey::Operators, &new_operators);
+        env.storage()
+            .persistent()
+            .remove(&KYCStorageKey::OperatorKey(operator.clone()));
 
         // Emit security-critical event (#161): actor, target, timestamp.
         let timestamp = env.ledger().timestamp();
         env.events().publish(
             (symbol_short!("kyc_op"), symbol_short!("removed")),
-            (admin.clone(), operator, timestamp),
+            (actor.clone(), operator, timestamp),
         );
 
         Ok(())
@@ -282,19 +436,52 @@ impl KYCSystem {
 
     // ===== KYC RECORD MANAGEMENT =====
 
-    /// Get KYC record for a user
+    /// Get KYC record for a user. A verification past its validity period
+    /// reads as `Expired` even before `sync_expiry` has stored the downgrade.
     pub fn get_record(env: &Env, user: &Address) -> KYCRecord {
-        env.storage()
-            .persistent()
-            .get(&KYCStorageKey::Record(user.clone()))
-            .unwrap_or_else(|| KYCRecord::new(env))
+        let mut record = Self::load_record(env, user).unwrap_or_else(|| KYCRecord::new(env));
+        if record.status == KYCStatus::Verified && record.is_lapsed(env.ledger().timestamp()) {
+            record.status = KYCStatus::Expired;
+            record.finalized_at = None;
+        }
+        record
+    }
+
+    /// Load the stored record of a user, upgrading a legacy V1 entry on
+    /// read. A legacy verification is valid for the current validity period
+    /// from when it was granted, and carries no jurisdiction.
+    fn load_record(env: &Env, user: &Address) -> Option<KYCRecord> {
+        let storage = env.storage().persistent();
+        if let Some(record) = storage.get::<_, KYCRecord>(&KYCStorageKey::RecordV2(user.clone())) {
+            return Some(record);
+        }
+        let v1: KYCRecordV1 = storage.get(&KYCStorageKey::Record(user.clone()))?;
+        let verified_until = if v1.status == KYCStatus::Verified {
+            Some(
+                v1.finalized_at
+                    .unwrap_or(v1.updated_at)
+                    .saturating_add(Self::get_verification_validity(env)),
+            )
+        } else {
+            None
+        };
+        Some(KYCRecord {
+            status: v1.status,
+            updated_at: v1.updated_at,
+            finalized_at: v1.finalized_at,
+            updated_by: v1.updated_by,
+            rejection_reason: v1.rejection_reason,
+            expires_at: v1.expires_at,
+            verified_until,
+            jurisdiction: None,
+        })
     }
 
     /// Save KYC record for a user
     fn save_record(env: &Env, user: &Address, record: &KYCRecord) {
         env.storage()
             .persistent()
-            .set(&KYCStorageKey::Record(user.clone()), record);
+            .set(&KYCStorageKey::RecordV2(user.clone()), record);
     }
 
     /// Check if user is verified
@@ -307,11 +494,191 @@ impl KYCSystem {
     pub fn require_verified(env: &Env, user: &Address) -> Result<(), KYCError> {
         if Self::is_verified(env, user) {
             Ok(())
+        } else if Self::get_record(env, user).is_lapsed(env.ledger().timestamp()) {
+            Err(KYCError::KYCVerificationExpired)
         } else {
             Err(KYCError::KYCVerificationRequired)
         }
     }
 
+    /// Check if a user's verification has lapsed but is still within the
+    /// grace window
+    pub fn in_grace_period(env: &Env, user: &Address) -> bool {
+        let record = Self::get_record(env, user);
+        let timestamp = env.ledger().timestamp();
+        match record.verified_until {
+            Some(verified_until) => {
+                timestamp >= verified_until
+                    && timestamp < verified_until.saturating_add(Self::get_grace_period(env))
+            }
+            None => false,
+        }
+    }
+
+    /// Require user to be verified, or within the grace window after their
+    /// verification lapsed. For calls that only reduce a user's positions.
+    pub fn require_verified_or_grace(env: &Env, user: &Address) -> Result<(), KYCError> {
+        if Self::in_grace_period(env, user) {
+            return Ok(());
+        }
+        Self::require_verified(env, user)
+    }
+
+    /// Store the downgrade of a lapsed verification to `Expired`. Anyone may
+    /// call it; the status reads as `Expired` either way, this records when.
+    pub fn sync_expiry(env: &Env, user: &Address) -> KYCStatus {
+        let stored = Self::load_record(env, user);
+        let mut record = Self::get_record(env, user);
+        if stored.map_or(false, |stored| stored.status != record.status) {
+            let timestamp = env.ledger().timestamp();
+            record.updated_at = timestamp;
+            record.updated_by = None;
+            Self::save_record(env, user, &record);
+
+            // Emit security-critical event (#161): target, lapsed_at, timestamp.
+            env.events().publish(
+                (symbol_short!("kyc"), symbol_short!("lapsed")),
+                (user.clone(), record.verified_until, timestamp),
+            );
+        }
+        record.status
+    }
+
+    /// Level a verified user holds, or `None` if the user is not verified.
+    /// Users verified by hand, or whose attestation has expired, hold
+    /// `BASE_KYC_LEVEL`.
+    pub fn verification_level(env: &Env, user: &Address) -> Option<u32> {
+        if !Self::is_verified(env, user) {
+            return None;
+        }
+        match Self::get_user_attestation(env, user) {
+            Some(record) if record.attestation.expires_at > env.ledger().timestamp() => {
+                Some(record.attestation.level)
+            }
+            _ => Some(BASE_KYC_LEVEL),
+        }
+    }
+
+    /// Require user to be verified at the level and in a jurisdiction the
+    /// feature accepts
+    pub fn require_verified_for(
+        env: &Env,
+        user: &Address,
+        feature: KYCFeature,
+    ) -> Result<(), KYCError> {
+        let level = Self::verification_level(env, user).ok_or(KYCError::KYCVerificationRequired)?;
+        if level < Self::get_feature_requirement(env, feature).min_level {
+            return Err(KYCError::KYCLevelTooLow);
+        }
+        Self::require_feature_allowed(env, user, feature)
+    }
+
+    // ===== JURISDICTION GEOFENCING =====
+
+    /// Require the jurisdiction on the user's record, whether attested or
+    /// set by hand, to be one the feature is open in. Does not require
+    /// verification, so it also gates features open to unverified users.
+    pub fn require_feature_allowed(
+        env: &Env,
+        user: &Address,
+        feature: KYCFeature,
+    ) -> Result<(), KYCError> {
+        let requirement = Self::get_feature_requirement(env, feature);
+        if requirement.jurisdictions.is_empty() {
+            return Ok(());
+        }
+        match Self::get_record(env, user).jurisdiction {
+            Some(code) if requirement.jurisdictions.contains(&code) => Ok(()),
+            Some(_) => Err(KYCError::JurisdictionNotPermitted),
+            None => Err(KYCError::JurisdictionUnknown),
+        }
+    }
+
+    /// Record the jurisdiction of a user verified by hand (operator only).
+    /// Attested users take theirs from the attestation.
+    pub fn set_jurisdiction(
+        env: &Env,
+        operator: &Address,
+        user: &Address,
+        jurisdiction: Symbol,
+    ) -> Result<(), KYCError> {
+        operator.require_auth();
+        Self::require_operator(env, operator)?;
+
+        // Prevent self-verification
+        if operator == user {
+            return Err(KYCError::SelfVerificationNotAllowed);
+        }
+
+        let timestamp = env.ledger().timestamp();
+        let mut record = Self::get_record(env, user);
+        record.jurisdiction = Some(jurisdiction.clone());
+        record.updated_at = timestamp;
+        record.updated_by = Some(operator.clone());
+        Self::save_record(env, user, &record);
+
+        // Emit security-critical event (#161): actor, target, jurisdiction, timestamp.
+        env.events().publish(
+            (symbol_short!("kyc"), symbol_short!("juris")),
+            (operator.clone(), user.clone(), jurisdiction, timestamp),
+        );
+
+        Ok(())
+    }
+
+    /// Set what a feature requires (admin only)
+    pub fn set_feature_requirement(
+        env: &Env,
+        admin: &Address,
+        feature: KYCFeature,
+        requirement: KYCRequirement,
+    ) -> Result<(), KYCError> {
+        admin.require_auth();
+        crate::admin::require_admin(env, admin).map_err(|_| KYCError::NotKYCOperator)?;
+        Self::apply_feature_requirement(env, admin, feature, requirement)
+    }
+
+    /// Set what a feature requires without an admin check; used by
+    /// governance execution.
+    pub(crate) fn apply_feature_requirement(
+        env: &Env,
+        actor: &Address,
+        feature: KYCFeature,
+        requirement: KYCRequirement,
+    ) -> Result<(), KYCError> {
+        if requirement.min_level < BASE_KYC_LEVEL {
+            return Err(KYCError::InvalidKYCLevel);
+        }
+        // Enforce storage limit (#160).
+        if requirement.jurisdictions.len() > MAX_REQUIREMENT_JURISDICTIONS {
+            return Err(KYCError::KYCDataTooLarge);
+        }
+
+        env.storage()
+            .persistent()
+            .set(&KYCStorageKey::FeatureRequirement(feature), &requirement);
+
+        // Emit security-critical event (#161): actor, feature, requirement, timestamp.
+        let timestamp = env.ledger().timestamp();
+        env.events().publish(
+            (symbol_short!("kyc"), symbol_short!("req_set")),
+            (actor.clone(), feature, requirement, timestamp),
+        );
+
+        Ok(())
+    }
+
+    /// Get what a feature requires; by default any verified user qualifies
+    pub fn get_feature_requirement(env: &Env, feature: KYCFeature) -> KYCRequirement {
+        env.storage()
+            .persistent()
+            .get(&KYCStorageKey::FeatureRequirement(feature))
+            .unwrap_or_else(|| KYCRequirement {
+                min_level: BASE_KYC_LEVEL,
+                jurisdictions: Vec::new(env),
+            })
+    }
+
     // ===== STATE TRANSITIONS =====
 
     /// Update KYC status with strict validation
@@ -372,6 +739,9 @@ impl KYCSystem {
             record.finalized_at = Some(timestamp);
             if new_status == KYCStatus::Rejected {
                 record.rejection_reason = reason;
+                record.verified_until = None;
+            } else {
+                record.verified_until = Some(timestamp + Self::get_verification_validity(env));
             }
         }
 
@@ -443,6 +813,243 @@ impl KYCSystem {
         Ok(())
     }
 
+    /// Start re-verification after a verification has lapsed
+    /// (user-initiated). The user keeps grace-window access while the
+    /// renewal is reviewed; it completes like a first submission.
+    pub fn renew_kyc(env: &Env, user: &Address) -> Result<(), KYCError> {
+        user.require_auth();
+
+        let mut record = Self::get_record(env, user);
+
+        // Can only renew if Expired
+        if record.status != KYCStatus::Expired {
+            return Err(KYCError::InvalidKYCStateTransition);
+        }
+
+        let timestamp = env.ledger().timestamp();
+        record.status = KYCStatus::Pending;
+        record.updated_at = timestamp;
+        record.updated_by = None;
+        record.rejection_reason = None;
+        record.expires_at = Some(timestamp + Self::get_pending_expiry_duration(env));
+
+        Self::save_record(env, user, &record);
+
+        env.events().publish(
+            (symbol_short!("kyc"), symbol_short!("renew")),
+            (user.clone(), record.expires_at),
+        );
+
+        Ok(())
+    }
+
+    // ===== ATTESTATIONS =====
+
+    /// Register the ed25519 key an operator signs attestations with
+    /// (operator only). Replaces any earlier key.
+    pub fn register_operator_key(
+        env: &Env,
+        operator: &Address,
+        public_key: BytesN<32>,
+    ) -> Result<(), KYCError> {
+        operator.require_auth();
+        Self::require_operator(env, operator)?;
+
+        env.storage()
+            .persistent()
+            .set(&KYCStorageKey::OperatorKey(operator.clone()), &public_key);
+
+        // Emit security-critical event (#161): actor, key, timestamp.
+        let timestamp = env.ledger().timestamp();
+        env.events().publish(
+            (symbol_short!("kyc_op"), symbol_short!("key_set")),
+            (operator.clone(), public_key, timestamp),
+        );
+
+        Ok(())
+    }
+
+    /// Get an operator's attestation signing key
+    pub fn get_operator_key(env: &Env, operator: &Address) -> Option<BytesN<32>> {
+        env.storage()
+            .persistent()
+            .get(&KYCStorageKey::OperatorKey(operator.clone()))
+    }
+
+    /// Bytes an operator signs: the attestation bound to this contract, so a
+    /// signature cannot be replayed against another deployment.
+    pub fn attestation_payload(env: &Env, attestation: &KYCAttestation) -> Bytes {
+        (env.current_contract_address(), attestation.clone()).to_xdr(env)
+    }
+
+    /// Verify a user from an operator-signed attestation. Anyone may submit
+    /// it; the signature is the operator's authorisation. A signature that
+    /// does not verify aborts the call.
+    pub fn submit_attestation(
+        env: &Env,
+        operator: &Address,
+        attestation: KYCAttestation,
+        signature: BytesN<64>,
+    ) -> Result<(), KYCError> {
+        Self::require_operator(env, operator)?;
+        let public_key =
+            Self::get_operator_key(env, operator).ok_or(KYCError::OperatorKeyNotRegistered)?;
+
+        // Prevent self-verification
+        if *operator == attestation.user {
+            return Err(KYCError::SelfVerificationNotAllowed);
+        }
+        if attestation.level < BASE_KYC_LEVEL {
+            return Err(KYCError::InvalidKYCLevel);
+        }
+        let timestamp = env.ledger().timestamp();
+        if attestation.expires_at <= timestamp {
+            return Err(KYCError::AttestationExpired);
+        }
+        let hash = attestation.credential_hash.clone();
+        if Self::is_revoked(env, &hash) {
+            return Err(KYCError::CredentialRevoked);
+        }
+        if env
+            .storage()
+            .persistent()
+            .has(&KYCStorageKey::Attestation(hash.clone()))
+        {
+            return Err(KYCError::AttestationAlreadySubmitted);
+        }
+
+        env.crypto().ed25519_verify(
+            &public_key,
+            &Self::attestation_payload(env, &attestation),
+            &signature,
+        );
+
+        let user = attestation.user.clone();
+        let mut record = Self::get_record(env, &user);
+        // A rejection still needs a governance override.
+        if record.status == KYCStatus::Rejected {
+            return Err(KYCError::KYCTerminalStateImmutable);
+        }
+        record.status = KYCStatus::Verified;
+        record.updated_at = timestamp;
+        record.updated_by = Some(operator.clone());
+        record.rejection_reason = None;
+        record.expires_at = None;
+        record.verified_until = Some(timestamp + Self::get_verification_validity(env));
+        record.jurisdiction = Some(attestation.jurisdiction.clone());
+        if record.finalized_at.is_none() {
+            record.finalized_at = Some(timestamp);
+        }
+        Self::save_record(env, &user, &record);
+
+        let level = attestation.level;
+        let jurisdiction = attestation.jurisdiction.clone();
+        env.storage().persistent().set(
+            &KYCStorageKey::Attestation(hash.clone()),
+            &AttestationRecord {
+                attestation,
+                operator: operator.clone(),
+                submitted_at: timestamp,
+            },
+        );
+        env.storage()
+            .persistent()
+            .set(&KYCStorageKey::UserAttestation(user.clone()), &hash);
+
+        // Emit security-critical event (#161): actor, target, level,
+        // jurisdiction, credential, timestamp.
+        env.events().publish(
+            (symbol_short!("kyc"), symbol_short!("attested")),
+            (operator.clone(), user, level, jurisdiction, hash, timestamp),
+        );
+
+        Ok(())
+    }
+
+    /// Get a submitted attestation by credential hash
+    pub fn get_attestation(env: &Env, credential_hash: &BytesN<32>) -> Option<AttestationRecord> {
+        env.storage()
+            .persistent()
+            .get(&KYCStorageKey::Attestation(credential_hash.clone()))
+    }
+
+    /// Get the attestation a user is currently verified by
+    pub fn get_user_attestation(env: &Env, user: &Address) -> Option<AttestationRecord> {
+        let hash: BytesN<32> = env
+            .storage()
+            .persistent()
+            .get(&KYCStorageKey::UserAttestation(user.clone()))?;
+        Self::get_attestation(env, &hash)
+    }
+
+    /// Put a credential on the revocation list (operator only). A credential
+    /// may be revoked before its attestation is submitted. If it backs a
+    /// user's current attestation, the user drops back to `Unverified`.
+    pub fn revoke_credential(
+        env: &Env,
+        operator: &Address,
+        credential_hash: BytesN<32>,
+        reason: Symbol,
+    ) -> Result<(), KYCError> {
+        operator.require_auth();
+        Self::require_operator(env, operator)?;
+
+        // Validate reason input (#159).
+        validate_symbol_length(&reason, MAX_REASON_LEN)?;
+
+        if Self::is_revoked(env, &credential_hash) {
+            return Err(KYCError::CredentialRevoked);
+        }
+
+        let timestamp = env.ledger().timestamp();
+        env.storage().persistent().set(
+            &KYCStorageKey::Revocation(credential_hash.clone()),
+            &CredentialRevocation {
+                revoked_by: operator.clone(),
+                reason: reason.clone(),
+                revoked_at: timestamp,
+            },
+        );
+
+        if let Some(submitted) = Self::get_attestation(env, &credential_hash) {
+            let user = submitted.attestation.user;
+            let user_key = KYCStorageKey::UserAttestation(user.clone());
+            let current: Option<BytesN<32>> = env.storage().persistent().get(&user_key);
+            if current == Some(credential_hash.clone()) {
+                env.storage().persistent().remove(&user_key);
+                let mut record = Self::get_record(env, &user);
+                record.status = KYCStatus::Unverified;
+                record.updated_at = timestamp;
+                record.updated_by = Some(operator.clone());
+                record.finalized_at = None;
+                record.verified_until = None;
+                Self::save_record(env, &user, &record);
+            }
+        }
+
+        // Emit security-critical event (#161): actor, credential, reason, timestamp.
+        env.events().publish(
+            (symbol_short!("kyc"), symbol_short!("revoked")),
+            (operator.clone(), credential_hash, reason, timestamp),
+        );
+
+        Ok(())
+    }
+
+    /// Check if a credential is on the revocation list
+    pub fn is_revoked(env: &Env, credential_hash: &BytesN<32>) -> bool {
+        env.storage()
+            .persistent()
+            .has(&KYCStorageKey::Revocation(credential_hash.clone()))
+    }
+
+    /// Get the revocation list entry for a credential
+    pub fn get_revocation(env: &Env, credential_hash: &BytesN<32>) -> Option<CredentialRevocation> {
+        env.storage()
+            .persistent()
+            .get(&KYCStorageKey::Revocation(credential_hash.clone()))
+    }
+
     // ===== GOVERNANCE OVERRIDES =====
 
     /// Set timelock duration (admin only)
@@ -453,7 +1060,16 @@ impl KYCSystem {
     ) -> Result<(), KYCError> {
         admin.require_auth();
         crate::admin::require_admin(env, admin).map_err(|_| KYCError::NotKYCOperator)?;
+        Self::apply_timelock_duration(env, admin, duration)
+    }
 
+    /// Set the override timelock without an admin check; used by governance
+    /// execution.
+    pub(crate) fn apply_timelock_duration(
+        env: &Env,
+        actor: &Address,
+        duration: u64,
+    ) -> Result<(), KYCError> {
         if duration < MIN_TIMELOCK_DURATION {
             return Err(KYCError::InvalidTimelockDuration);
         }
@@ -466,7 +1082,7 @@ impl KYCSystem {
         let timestamp = env.ledger().timestamp();
         env.events().publish(
             (symbol_short!("kyc"), symbol_short!("tl_set")),
-            (admin.clone(), duration, timestamp),
+            (actor.clone(), duration, timestamp),
         );
 
         Ok(())
@@ -488,7 +1104,16 @@ impl KYCSystem {
     ) -> Result<(), KYCError> {
         admin.require_auth();
         crate::admin::require_admin(env, admin).map_err(|_| KYCError::NotKYCOperator)?;
+        Self::apply_pending_expiry_duration(env, admin, duration)
+    }
 
+    /// Set the pending expiry without an admin check; used by governance
+    /// execution.
+    pub(crate) fn apply_pending_expiry_duration(
+        env: &Env,
+        actor: &Address,
+        duration: u64,
+    ) -> Result<(), KYCError> {
         if duration < MIN_PENDING_EXPIRY_DURATION {
             return Err(KYCError::InvalidExpiryDuration);
         }
@@ -501,7 +1126,7 @@ impl KYCSystem {
         let timestamp = env.ledger().timestamp();
         env.events().publish(
             (symbol_short!("kyc"), symbol_short!("exp_set")),
-            (admin.clone(), duration, timestamp),
+            (actor.clone(), duration, timestamp),
         );
 
         Ok(())
@@ -515,6 +1140,90 @@ impl KYCSystem {
             .unwrap_or(DEFAULT_PENDING_EXPIRY_DURATION)
     }
 
+    /// Set verification validity period (admin only)
+    pub fn set_verification_validity(
+        env: &Env,
+        admin: &Address,
+        duration: u64,
+    ) -> Result<(), KYCError> {
+        admin.require_auth();
+        crate::admin::require_admin(env, admin).map_err(|_| KYCError::NotKYCOperator)?;
+        Self::apply_verification_validity(env, admin, duration)
+    }
+
+    /// Set the verification validity without an admin check; used by
+    /// governance execution. Applies to verifications granted from now on.
+    pub(crate) fn apply_verification_validity(
+        env: &Env,
+        actor: &Address,
+        duration: u64,
+    ) -> Result<(), KYCError> {
+        if duration < MIN_VERIFICATION_VALIDITY {
+            return Err(KYCError::InvalidExpiryDuration);
+        }
+
+        env.storage()
+            .persistent()
+            .set(&KYCStorageKey::VerificationValidity, &duration);
+
+        // Emit security-critical event (#161): actor, new_value, timestamp.
+        let timestamp = env.ledger().timestamp();
+        env.events().publish(
+            (symbol_short!("kyc"), symbol_short!("valid_set")),
+            (actor.clone(), duration, timestamp),
+        );
+
+        Ok(())
+    }
+
+    /// Get verification validity period
+    pub fn get_verification_validity(env: &Env) -> u64 {
+        env.storage()
+            .persistent()
+            .get(&KYCStorageKey::VerificationValidity)
+            .unwrap_or(DEFAULT_VERIFICATION_VALIDITY)
+    }
+
+    /// Set grace window after a verification lapses (admin only)
+    pub fn set_grace_period(env: &Env, admin: &Address, duration: u64) -> Result<(), KYCError> {
+        admin.require_auth();
+        crate::admin::require_admin(env, admin).map_err(|_| KYCError::NotKYCOperator)?;
+        Self::apply_grace_period(env, admin, duration)
+    }
+
+    /// Set the grace window without an admin check; used by governance
+    /// execution.
+    pub(crate) fn apply_grace_period(
+        env: &Env,
+        actor: &Address,
+        duration: u64,
+    ) -> Result<(), KYCError> {
+        if duration > MAX_GRACE_PERIOD {
+            return Err(KYCError::InvalidExpiryDuration);
+        }
+
+        env.storage()
+            .persistent()
+            .set(&KYCStorageKey::GracePeriod, &duration);
+
+        // Emit security-critical event (#161): actor, new_value, timestamp.
+        let timestamp = env.ledger().timestamp();
+        env.events().publish(
+            (symbol_short!("kyc"), symbol_short!("grace_set")),
+            (actor.clone(), duration, timestamp),
+        );
+
+        Ok(())
+    }
+
+    /// Get grace window after a verification lapses
+    pub fn get_grace_period(env: &Env) -> u64 {
+        env.storage()
+            .persistent()
+            .get(&KYCStorageKey::GracePeriod)
+            .unwrap_or(DEFAULT_GRACE_PERIOD)
+    }
+
     /// Propose governance override for terminal state change
     pub fn propose_override(
         env: &Env,
@@ -606,6 +1315,11 @@ impl KYCSystem {
         } else {
             record.finalized_at = None;
         }
+        record.verified_until = if override_request.new_status == KYCStatus::Verified {
+            Some(timestamp + Self::get_verification_validity(env))
+        } else {
+            None
+        };
 
         Self::save_record(env, &override_request.user, &record);
 
@@ -659,9 +1373,11 @@ mod mxllv_tests {
     #[test]
     fn test_update_status_validates_reason() {
         let (env, contract_id, _admin, operator, user) = setup();
+        env.as_contract(&contract_id, || KYCSystem::submit_kyc(&env, &user).unwrap());
         env.as_contract(&contract_id, || {
-            KYCSystem::submit_kyc(&env, &user).unwrap();
             KYCSystem::update_status(&env, &operator, &user, KYCStatus::InReview, None).unwrap();
+        });
+        env.as_contract(&contract_id, || {
             // Valid reason is accepted.
             let reason = symbol_short!("fraud");
             assert!(KYCSystem::update_status(
@@ -680,15 +1396,17 @@ mod mxllv_tests {
     #[test]
     fn test_operator_limit_enforced() {
         let (env, contract_id, admin, _operator, _user) = setup();
-        env.as_contract(&contract_id, || {
-            // Fill up to MAX_OPERATORS (already 1 added in setup + admin counts via is_operator).
-            // Add MAX_OPERATORS - 1 more (setup already added 1).
-            for _ in 1..MAX_OPERATORS {
-                let op = Address::generate(&env);
+        // Fill up to MAX_OPERATORS (already 1 added in setup + admin counts via is_operator).
+        // Add MAX_OPERATORS - 1 more (setup already added 1).
+        for _ in 1..MAX_OPERATORS {
+            let op = Address::generate(&env);
+            env.as_contract(&contract_id, || {
                 KYCSystem::add_operator(&env, &admin, op).unwrap();
-            }
-            // The next one must fail.
-            let extra = Address::generate(&env);
+            });
+        }
+        // The next one must fail.
+        let extra = Address::generate(&env);
+        env.as_contract(&contract_id, || {
             assert_eq!(
                 KYCSystem::add_operator(&env, &admin, extra),
                 Err(KYCError::KYCOperatorLimitReached)
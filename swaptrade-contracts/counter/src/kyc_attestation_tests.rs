@@ -0,0 +1,365 @@
+#![cfg(test)]
+
+use ed25519_dalek::{Signer, SigningKey};
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::{symbol_short, vec, Address, BytesN, Env, Symbol};
+
+use crate::errors::ContractError;
+use crate::kyc::{KYCAttestation, KYCFeature, KYCRequirement, KYCStatus, KYCSystem};
+use crate::nft_errors::NFTError;
+use crate::storage::ADMIN_KEY;
+use crate::{CounterContract, CounterContractClient};
+
+const GB: Symbol = symbol_short!("GB");
+const FR: Symbol = symbol_short!("FR");
+
+struct Setup {
+    env: Env,
+    contract_id: Address,
+    client: CounterContractClient<'static>,
+    admin: Address,
+    operator: Address,
+    key: SigningKey,
+}
+
+fn setup() -> Setup {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|l| l.timestamp = 1_000_000);
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    let operator = Address::generate(&env);
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(&ADMIN_KEY, &admin);
+    });
+    client.kyc_add_operator(&admin, &operator);
+    let key = SigningKey::from_bytes(&[7; 32]);
+    client.kyc_register_operator_key(
+        &operator,
+        &BytesN::from_array(&env, &key.verifying_key().to_bytes()),
+    );
+    Setup {
+        env,
+        contract_id,
+        client,
+        admin,
+        operator,
+        key,
+    }
+}
+
+impl Setup {
+    fn attestation(
+        &self,
+        user: &Address,
+        jurisdiction: Symbol,
+        level: u32,
+        seed: u8,
+    ) -> KYCAttestation {
+        KYCAttestation {
+            user: user.clone(),
+            jurisdiction,
+            level,
+            expires_at: 2_000_000,
+            credential_hash: BytesN::from_array(&self.env, &[seed; 32]),
+        }
+    }
+
+    fn sign(&self, key: &SigningKey, attestation: &KYCAttestation) -> BytesN<64> {
+        let payload = self.env.as_contract(&self.contract_id, || {
+            KYCSystem::attestation_payload(&self.env, attestation)
+        });
+        let message: std::vec::Vec<u8> = payload.iter().collect();
+        BytesN::from_array(&self.env, &key.sign(&message).to_bytes())
+    }
+
+    fn submit(&self, attestation: &KYCAttestation) -> Result<(), ContractError> {
+        let signature = self.sign(&self.key, attestation);
+        match self
+            .client
+            .try_kyc_submit_attestation(&self.operator, attestation, &signature)
+        {
+            Ok(_) => Ok(()),
+            Err(err) => Err(err.unwrap()),
+        }
+    }
+
+    fn require_for(&self, user: &Address, feature: KYCFeature) -> Result<(), ContractError> {
+        self.env.as_contract(&self.contract_id, || {
+            KYCSystem::require_verified_for(&self.env, user, feature)
+        })
+    }
+}
+
+#[test]
+fn test_signed_attestation_verifies_user() {
+    let s = setup();
+    let user = Address::generate(&s.env);
+    let attestation = s.attestation(&user, GB, 2, 1);
+
+    // A signature from any other key aborts the call.
+    let forged = s.sign(&SigningKey::from_bytes(&[9; 32]), &attestation);
+    assert!(s
+        .client
+        .try_kyc_submit_attestation(&s.operator, &attestation, &forged)
+        .is_err());
+    assert!(!s.client.kyc_is_verified(&user));
+
+    s.submit(&attestation).unwrap();
+    assert!(s.client.kyc_is_verified(&user));
+    assert_eq!(s.client.kyc_verification_level(&user), 2);
+    let record = s.client.kyc_get_record(&user);
+    assert_eq!(record.status, KYCStatus::Verified);
+    assert_eq!(record.updated_by, Some(s.operator.clone()));
+    let stored = s.client.kyc_get_user_attestation(&user).unwrap();
+    assert_eq!(stored.attestation, attestation);
+    assert_eq!(
+        (stored.operator, stored.submitted_at),
+        (s.operator.clone(), 1_000_000)
+    );
+
+    assert_eq!(
+        s.submit(&attestation),
+        Err(ContractError::AttestationAlreadySubmitted)
+    );
+    let other = Address::generate(&s.env);
+    assert_eq!(
+        s.submit(&s.attestation(&other, GB, 0, 2)),
+        Err(ContractError::InvalidKYCLevel)
+    );
+    let mut expired = s.attestation(&other, GB, 1, 3);
+    expired.expires_at = 1_000_000;
+    assert_eq!(s.submit(&expired), Err(ContractError::AttestationExpired));
+
+    let keyless = Address::generate(&s.env);
+    s.client.kyc_add_operator(&s.admin, &keyless);
+    let attestation = s.attestation(&other, GB, 1, 4);
+    let signature = s.sign(&s.key, &attestation);
+    assert_eq!(
+        s.client
+            .try_kyc_submit_attestation(&keyless, &attestation, &signature),
+        Err(Ok(ContractError::OperatorKeyNotRegistered))
+    );
+}
+
+#[test]
+fn test_feature_requires_level_and_jurisdiction() {
+    let s = setup();
+    s.client.kyc_set_feature_requirement(
+        &s.admin,
+        &KYCFeature::Margin,
+        &KYCRequirement {
+            min_level: 2,
+            jurisdictions: vec![&s.env, GB],
+        },
+    );
+
+    // Verified by hand: basic level, no jurisdiction on record.
+    let manual = Address::generate(&s.env);
+    s.client.kyc_submit(&manual);
+    s.client
+        .kyc_update_status(&s.operator, &manual, &KYCStatus::InReview, &None);
+    s.client
+        .kyc_update_status(&s.operator, &manual, &KYCStatus::Verified, &None);
+    assert_eq!(s.client.kyc_verification_level(&manual), 1);
+    assert_eq!(s.require_for(&manual, KYCFeature::Swap), Ok(()));
+    assert_eq!(
+        s.require_for(&manual, KYCFeature::Margin),
+        Err(ContractError::KYCLevelTooLow)
+    );
+
+    let french = Address::generate(&s.env);
+    s.submit(&s.attestation(&french, FR, 2, 1)).unwrap();
+    assert_eq!(
+        s.require_for(&french, KYCFeature::Margin),
+        Err(ContractError::JurisdictionNotPermitted)
+    );
+
+    let british = Address::generate(&s.env);
+    s.submit(&s.attestation(&british, GB, 2, 2)).unwrap();
+    assert_eq!(s.require_for(&british, KYCFeature::Margin), Ok(()));
+
+    // Once the attestation lapses it no longer vouches for the level.
+    s.env.ledger().with_mut(|l| l.timestamp = 2_000_000);
+    assert_eq!(s.client.kyc_verification_level(&british), 1);
+    assert_eq!(
+        s.require_for(&british, KYCFeature::Margin),
+        Err(ContractError::KYCLevelTooLow)
+    );
+
+    let unverified = Address::generate(&s.env);
+    assert_eq!(
+        s.require_for(&unverified, KYCFeature::Swap),
+        Err(ContractError::KYCVerificationRequired)
+    );
+}
+
+#[test]
+fn test_revoked_credential_unverifies_user() {
+    let s = setup();
+    let user = Address::generate(&s.env);
+    let attestation = s.attestation(&user, GB, 2, 1);
+    s.submit(&attestation).unwrap();
+
+    let outsider = Address::generate(&s.env);
+    let hash = attestation.credential_hash.clone();
+    let reason = symbol_short!("forged");
+    assert_eq!(
+        s.client
+            .try_kyc_revoke_credential(&outsider, &hash, &reason),
+        Err(Ok(ContractError::NotKYCOperator))
+    );
+    s.client.kyc_revoke_credential(&s.operator, &hash, &reason);
+    assert!(!s.client.kyc_is_verified(&user));
+    assert_eq!(s.client.kyc_verification_level(&user), 0);
+    assert!(s.client.kyc_get_user_attestation(&user).is_none());
+    let revocation = s.client.kyc_get_revocation(&hash).unwrap();
+    assert_eq!(
+        (revocation.revoked_by, revocation.reason),
+        (s.operator.clone(), reason.clone())
+    );
+    // The attestation stays on record for audit.
+    assert_eq!(
+        s.client.kyc_get_attestation(&hash).unwrap().attestation,
+        attestation
+    );
+    assert_eq!(
+        s.client
+            .try_kyc_revoke_credential(&s.operator, &hash, &reason),
+        Err(Ok(ContractError::CredentialRevoked))
+    );
+
+    // A credential can be revoked before anyone submits it.
+    let fresh = s.attestation(&user, GB, 2, 2);
+    s.client
+        .kyc_revoke_credential(&s.operator, &fresh.credential_hash, &reason);
+    assert_eq!(s.submit(&fresh), Err(ContractError::CredentialRevoked));
+
+    s.submit(&s.attestation(&user, GB, 1, 3)).unwrap();
+    assert_eq!(s.client.kyc_verification_level(&user), 1);
+}
+
+#[test]
+fn test_requirement_geofences_features() {
+    let s = setup();
+    let british = Address::generate(&s.env);
+    s.submit(&s.attestation(&british, GB, 2, 1)).unwrap();
+    let french = Address::generate(&s.env);
+    s.submit(&s.attestation(&french, FR, 2, 2)).unwrap();
+    assert_eq!(s.client.kyc_get_record(&british).jurisdiction, Some(GB));
+
+    // Without a requirement every feature is open.
+    assert_eq!(s.require_for(&french, KYCFeature::Margin), Ok(()));
+
+    let gb_only = KYCRequirement {
+        min_level: 1,
+        jurisdictions: vec![&s.env, GB],
+    };
+    s.client
+        .kyc_set_feature_requirement(&s.admin, &KYCFeature::Margin, &gb_only);
+    s.client
+        .kyc_set_feature_requirement(&s.admin, &KYCFeature::ReferralPayout, &gb_only);
+    assert_eq!(s.require_for(&french, KYCFeature::Swap), Ok(()));
+    assert_eq!(
+        s.require_for(&french, KYCFeature::Margin),
+        Err(ContractError::JurisdictionNotPermitted)
+    );
+    assert!(!s
+        .client
+        .kyc_is_feature_allowed(&french, &KYCFeature::ReferralPayout));
+    assert_eq!(
+        s.client.try_withdraw_commission(&french),
+        Err(Ok(ContractError::JurisdictionNotPermitted))
+    );
+    assert_eq!(s.require_for(&british, KYCFeature::Margin), Ok(()));
+
+    // The jurisdiction stays on record once the attestation expires.
+    s.env.ledger().with_mut(|l| l.timestamp = 2_000_000);
+    assert_eq!(s.client.kyc_verification_level(&french), 1);
+    assert_eq!(
+        s.require_for(&french, KYCFeature::Margin),
+        Err(ContractError::JurisdictionNotPermitted)
+    );
+    assert_eq!(s.require_for(&british, KYCFeature::Margin), Ok(()));
+}
+
+#[test]
+fn test_hand_set_jurisdiction_is_geofenced() {
+    let s = setup();
+    let gb_only = KYCRequirement {
+        min_level: 1,
+        jurisdictions: vec![&s.env, GB],
+    };
+    s.client
+        .kyc_set_feature_requirement(&s.admin, &KYCFeature::Margin, &gb_only);
+    s.client
+        .kyc_set_feature_requirement(&s.admin, &KYCFeature::Faucet, &gb_only);
+
+    // Verified by hand: no jurisdiction until an operator records one.
+    let manual = Address::generate(&s.env);
+    s.client.kyc_submit(&manual);
+    s.client
+        .kyc_update_status(&s.operator, &manual, &KYCStatus::InReview, &None);
+    s.client
+        .kyc_update_status(&s.operator, &manual, &KYCStatus::Verified, &None);
+    assert_eq!(s.require_for(&manual, KYCFeature::Swap), Ok(()));
+    assert_eq!(
+        s.require_for(&manual, KYCFeature::Margin),
+        Err(ContractError::JurisdictionUnknown)
+    );
+    // Unverified users are geofenced too, for features that do not need KYC.
+    let visitor = Address::generate(&s.env);
+    assert_eq!(
+        s.client.try_claim_faucet(&visitor, &symbol_short!("XLM")),
+        Err(Ok(ContractError::JurisdictionUnknown))
+    );
+
+    let outsider = Address::generate(&s.env);
+    assert_eq!(
+        s.client.try_kyc_set_jurisdiction(&outsider, &manual, &GB),
+        Err(Ok(ContractError::NotKYCOperator))
+    );
+    s.client.kyc_set_jurisdiction(&s.operator, &manual, &FR);
+    assert_eq!(
+        s.require_for(&manual, KYCFeature::Margin),
+        Err(ContractError::JurisdictionNotPermitted)
+    );
+    s.client.kyc_set_jurisdiction(&s.operator, &manual, &GB);
+    assert_eq!(s.require_for(&manual, KYCFeature::Margin), Ok(()));
+}
+
+#[test]
+fn test_nft_lending_geofence_keeps_the_reason() {
+    let s = setup();
+    let gb_only = KYCRequirement {
+        min_level: 1,
+        jurisdictions: vec![&s.env, GB],
+    };
+    s.client
+        .kyc_set_feature_requirement(&s.admin, &KYCFeature::NftLending, &gb_only);
+    let lend = |user: &Address| -> Result<(), NFTError> {
+        s.env.as_contract(&s.contract_id, || {
+            KYCSystem::require_feature_allowed(&s.env, user, KYCFeature::NftLending)?;
+            Ok(())
+        })
+    };
+
+    let unknown = Address::generate(&s.env);
+    assert_eq!(lend(&unknown), Err(NFTError::JurisdictionUnknown));
+    let french = Address::generate(&s.env);
+    s.submit(&s.attestation(&french, FR, 2, 1)).unwrap();
+    assert_eq!(lend(&french), Err(NFTError::JurisdictionNotPermitted));
+    let british = Address::generate(&s.env);
+    s.submit(&s.attestation(&british, GB, 2, 2)).unwrap();
+    assert_eq!(lend(&british), Ok(()));
+
+    assert_eq!(
+        NFTError::from(ContractError::KYCLevelTooLow),
+        NFTError::KYCLevelTooLow
+    );
+    assert_eq!(
+        NFTError::from(ContractError::KYCVerificationRequired),
+        NFTError::KYCVerificationRequired
+    );
+}
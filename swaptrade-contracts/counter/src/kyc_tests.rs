@@ -6,7 +6,7 @@ use super::*;
 use crate::admin::set_admin;
 use crate::batch::{BatchOperation, BatchResult};
 use crate::errors::ContractError;
-use crate::kyc::KYCStorageKey;
+use crate::kyc::{KYCRecordV1, KYCStorageKey};
 use soroban_sdk::{
     symbol_short,
     testutils::{Address as _, Ledger},
@@ -195,6 +195,151 @@ fn test_kyc_expiry_cleared_on_status_transition() {
     assert!(record.expires_at.is_none()); // Should be cleared
 }
 
+#[test]
+fn test_verification_lapses_to_expired_with_reducing_only_grace() {
+    let (env, contract_id, _, operator, user, _) = setup_contract();
+    verify_user(&env, &contract_id, &operator, &user);
+
+    let verified_until = get_record(&env, &contract_id, &user)
+        .verified_until
+        .unwrap();
+    assert_eq!(
+        verified_until,
+        env.ledger().timestamp() + DEFAULT_VERIFICATION_VALIDITY
+    );
+
+    env.ledger()
+        .with_mut(|li| li.timestamp = verified_until - 1);
+    with_contract(&env, &contract_id, || {
+        assert!(KYCSystem::is_verified(&env, &user));
+        assert!(!KYCSystem::in_grace_period(&env, &user));
+    });
+
+    // Lapsed: reads as Expired at once, and only reducing calls pass.
+    env.ledger().with_mut(|li| li.timestamp = verified_until);
+    with_contract(&env, &contract_id, || {
+        assert_eq!(
+            KYCSystem::get_record(&env, &user).status,
+            KYCStatus::Expired
+        );
+        assert!(KYCSystem::in_grace_period(&env, &user));
+        assert_eq!(
+            require_verified_user(&env, &user),
+            Err(ContractError::KYCVerificationExpired)
+        );
+        assert_eq!(require_reducing_user(&env, &user), Ok(()));
+
+        // The stored record still says Verified until the downgrade is synced.
+        let stored: KYCRecord = env
+            .storage()
+            .persistent()
+            .get(&KYCStorageKey::RecordV2(user.clone()))
+            .unwrap();
+        assert_eq!(stored.status, KYCStatus::Verified);
+        assert_eq!(KYCSystem::sync_expiry(&env, &user), KYCStatus::Expired);
+        let stored: KYCRecord = env
+            .storage()
+            .persistent()
+            .get(&KYCStorageKey::RecordV2(user.clone()))
+            .unwrap();
+        assert_eq!(stored.status, KYCStatus::Expired);
+        assert!(stored.finalized_at.is_none());
+    });
+
+    env.ledger()
+        .with_mut(|li| li.timestamp = verified_until + DEFAULT_GRACE_PERIOD);
+    with_contract(&env, &contract_id, || {
+        assert!(!KYCSystem::in_grace_period(&env, &user));
+        assert_eq!(
+            require_reducing_user(&env, &user),
+            Err(ContractError::KYCVerificationExpired)
+        );
+    });
+}
+
+#[test]
+fn test_expired_user_renews_through_review() {
+    let (env, contract_id, admin, operator, user, other_user) = setup_contract();
+    with_contract(&env, &contract_id, || {
+        KYCSystem::set_verification_validity(&env, &admin, MIN_VERIFICATION_VALIDITY).unwrap();
+    });
+    with_contract(&env, &contract_id, || {
+        assert_eq!(
+            KYCSystem::set_verification_validity(&env, &admin, MIN_VERIFICATION_VALIDITY - 1),
+            Err(KYCError::InvalidExpiryDuration)
+        );
+    });
+    with_contract(&env, &contract_id, || {
+        assert_eq!(
+            KYCSystem::set_grace_period(&env, &admin, MAX_GRACE_PERIOD + 1),
+            Err(KYCError::InvalidExpiryDuration)
+        );
+    });
+    verify_user(&env, &contract_id, &operator, &user);
+
+    // Only a lapsed verification can be renewed.
+    with_contract(&env, &contract_id, || {
+        assert_eq!(
+            KYCSystem::renew_kyc(&env, &user),
+            Err(KYCError::InvalidKYCStateTransition)
+        );
+    });
+    with_contract(&env, &contract_id, || {
+        assert_eq!(
+            KYCSystem::renew_kyc(&env, &other_user),
+            Err(KYCError::InvalidKYCStateTransition)
+        );
+    });
+
+    env.ledger()
+        .with_mut(|li| li.timestamp += MIN_VERIFICATION_VALIDITY);
+    with_contract(&env, &contract_id, || {
+        KYCSystem::renew_kyc(&env, &user).unwrap();
+    });
+    let record = get_record(&env, &contract_id, &user);
+    assert_eq!(record.status, KYCStatus::Pending);
+    assert!(record.expires_at.is_some());
+
+    // Grace access continues while the renewal is reviewed.
+    move_to_review(&env, &contract_id, &operator, &user);
+    with_contract(&env, &contract_id, || {
+        assert_eq!(require_reducing_user(&env, &user), Ok(()));
+    });
+    approve_user(&env, &contract_id, &operator, &user);
+
+    let record = get_record(&env, &contract_id, &user);
+    assert_eq!(record.status, KYCStatus::Verified);
+    assert_eq!(
+        record.verified_until,
+        Some(env.ledger().timestamp() + MIN_VERIFICATION_VALIDITY)
+    );
+    with_contract(&env, &contract_id, || {
+        assert_eq!(require_verified_user(&env, &user), Ok(()));
+    });
+}
+
+#[test]
+fn test_rejected_renewal_ends_grace() {
+    let (env, contract_id, _, operator, user, _) = setup_contract();
+    verify_user(&env, &contract_id, &operator, &user);
+
+    env.ledger()
+        .with_mut(|li| li.timestamp += DEFAULT_VERIFICATION_VALIDITY);
+    with_contract(&env, &contract_id, || {
+        KYCSystem::renew_kyc(&env, &user).unwrap();
+    });
+    move_to_review(&env, &contract_id, &operator, &user);
+    reject_user(&env, &contract_id, &operator, &user, symbol_short!("stale"));
+
+    with_contract(&env, &contract_id, || {
+        assert!(!KYCSystem::in_grace_period(&env, &user));
+        assert_eq!(
+            require_reducing_user(&env, &user),
+            Err(ContractError::KYCVerificationRequired)
+        );
+    });
+}
+
 #[test]
 fn test_transition_matrix_matches_expected_fsm() {
     assert!(KYCStatus::Unverified.can_transition_to(&KYCStatus::Pending));
@@ -211,6 +356,8 @@ fn test_transition_matrix_matches_expected_fsm() {
     assert!(!KYCStatus::AdditionalInfoRequired.can_transition_to(&KYCStatus::Rejected));
     assert!(!KYCStatus::Verified.can_transition_to(&KYCStatus::Pending));
     assert!(!KYCStatus::Rejected.can_transition_to(&KYCStatus::InReview));
+    assert!(KYCStatus::Expired.can_transition_to(&KYCStatus::Pending));
+    assert!(!KYCStatus::Expired.can_transition_to(&KYCStatus::Verified));
 }
 
 #[test]
@@ -387,12 +534,54 @@ fn test_governance_override_requires_timelock_and_can_execute_after_delay() {
 }
 
 #[test]
-fn test_seeded_terminal_record_cannot_be_mutated_through_controlled_flow() {
+fn test_legacy_record_is_upgraded_with_a_validity_period() {
     let (env, contract_id, _, operator, user, _) = setup_contract();
+    let verified_at = env.ledger().timestamp();
 
     with_contract(&env, &contract_id, || {
         env.storage().persistent().set(
             &KYCStorageKey::Record(user.clone()),
+            &KYCRecordV1 {
+                status: KYCStatus::Verified,
+                updated_at: verified_at,
+                finalized_at: Some(verified_at),
+                updated_by: Some(operator.clone()),
+                rejection_reason: None,
+                expires_at: None,
+            },
+        );
+    });
+
+    let record = get_record(&env, &contract_id, &user);
+    assert_eq!(record.status, KYCStatus::Verified);
+    assert_eq!(
+        record.verified_until,
+        Some(verified_at + DEFAULT_VERIFICATION_VALIDITY)
+    );
+    assert_eq!(record.jurisdiction, None);
+
+    // The lapse is stored under the new key.
+    env.ledger()
+        .with_mut(|li| li.timestamp = verified_at + DEFAULT_VERIFICATION_VALIDITY);
+    with_contract(&env, &contract_id, || {
+        assert!(KYCSystem::in_grace_period(&env, &user));
+        assert_eq!(KYCSystem::sync_expiry(&env, &user), KYCStatus::Expired);
+        let stored: KYCRecord = env
+            .storage()
+            .persistent()
+            .get(&KYCStorageKey::RecordV2(user.clone()))
+            .unwrap();
+        assert_eq!(stored.status, KYCStatus::Expired);
+    });
+}
+
+#[test]
+fn test_seeded_terminal_record_cannot_be_mutated_through_controlled_flow() {
+    let (env, contract_id, _, operator, user, _) = setup_contract();
+
+    with_contract(&env, &contract_id, || {
+        env.storage().persistent().set(
+            &KYCStorageKey::RecordV2(user.clone()),
             &KYCRecord {
                 status: KYCStatus::Verified,
                 updated_at: env.ledger().timestamp(),
@@ -400,6 +589,8 @@ fn test_seeded_terminal_record_cannot_be_mutated_through_controlled_flow() {
                 updated_by: Some(operator.clone()),
                 rejection_reason: None,
                 expires_at: None,
+                verified_until: None,
+                jurisdiction: None,
             },
         );
 
@@ -428,6 +619,15 @@ fn test_sensitive_contract_entry_points_require_verified_kyc() {
         )
         .unwrap();
     });
+    with_contract(&env, &contract_id, || {
+        CounterContract::init_oracle_feed(
+            env.clone(),
+            admin.clone(),
+            (symbol_short!("XLM"), symbol_short!("USDCSIM")),
+            1_000_000_000_000_000_000,
+        )
+        .unwrap();
+    });
 
     assert_eq!(
         with_contract(&env, &contract_id, || {
@@ -495,6 +695,7 @@ fn test_sensitive_contract_entry_points_require_verified_kyc() {
             symbol_short!("USDCSIM"),
             100,
             user.clone(),
+            u64::MAX,
         )
     });
     assert!(safe_swap_output > 0);
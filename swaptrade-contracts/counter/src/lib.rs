@@ -10,7 +10,7 @@
 )]
 
 use soroban_sdk::{
-    contract, contractimpl, contracttype, symbol_short, Address, Env, Map, Symbol, Vec,
+    contract, contractimpl, contracttype, symbol_short, Address, BytesN, Env, Map, Symbol, Vec,
 };
 
 // Bring in modules from parent directory
@@ -24,17 +24,26 @@ mod gamification {
     include!("../gamification.rs");
 }
 mod bridge;
+mod decentralization;
+#[cfg(test)]
+mod decentralization_tests;
 mod emergency;
-mod emergency_stub;
+mod emergency_withdrawal;
+#[cfg(test)]
+mod emergency_withdrawal_tests;
 mod errors;
 mod events;
 mod faucet;
-mod flash_loan_stub;
+mod flash_loan;
+#[cfg(test)]
+mod flash_loan_tests;
+mod insurance_fund;
 mod invariants;
 mod kyc;
 #[cfg(test)]
 mod kyc_tests;
 mod liquidity_pool;
+mod margin;
 mod rate_limit;
 mod referral_system;
 mod rewards;
@@ -59,6 +68,10 @@ mod batch_opt_simple_test;
 mod batch_performance_tests;
 mod governance;
 mod governance_params;
+#[cfg(any(test, feature = "testutils"))]
+pub mod governance_simulation;
+#[cfg(test)]
+mod governance_simulation_tests;
 mod governance_system;
 mod governance_types;
 #[cfg(test)]
@@ -73,13 +86,10 @@ mod orders;
 mod orders_tests;
 mod risk_management;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "experimental"))]
 mod admin_controls_test;
 
-mod governance_system;
-mod governance;
-
-#[cfg(test)]
+#[cfg(all(test, feature = "experimental"))]
 mod governance_system_tests;
 #[cfg(test)]
 mod governance_multisig_tests;
@@ -120,6 +130,20 @@ mod staking_bonus;
 mod farming;
 #[cfg(test)]
 mod farming_tests;
+// Vote-Escrow (veToken) System
+mod vote_escrow;
+#[cfg(test)]
+mod vote_escrow_tests;
+#[cfg(test)]
+mod delegation_tests;
+#[cfg(test)]
+mod voting_modes_tests;
+// Gauge voting over farm emissions
+mod gauge_controller;
+#[cfg(test)]
+mod gauge_controller_tests;
+#[cfg(test)]
+mod governance_actions_tests;
 
 // Zero-Knowledge Privacy Transaction Modules
 mod private_transaction;
@@ -152,9 +176,16 @@ pub use emergency_override::{
 #[cfg(feature = "experimental")]
 pub use fee_adjustment_manager::FeeAdjustmentManager;
 pub use staking_bonus::{DistributionRecord, StakeRecord, StakingBonusKey, StakingBonusManager};
+pub use vote_escrow::{VeLock, VePoint, VoteEscrowKey, VoteEscrowManager};
+pub use gauge_controller::{GaugeController, GaugeEpoch, GaugeKey, GaugeVote};
+pub use emergency_withdrawal::{
+    EmergencyExitKey, EmergencyExitReceipt, EmergencyExitState, EmergencyWithdrawal,
+};
 
 #[cfg(feature = "nft")]
 pub mod nft;
+// Shared with the NFT lending and marketplace modules, which are not built.
+pub mod nft_errors;
 
 #[cfg(feature = "experimental")]
 mod private_transaction;
@@ -171,14 +202,30 @@ mod zkp_verification;
 
 // Re-export invariant functions for external use
 pub use invariants::verify_contract_invariants;
+pub use decentralization::{
+    AdminCallApproval, Decentralization, DecentralizationSchedule, GovernancePhase,
+};
+pub use flash_loan::{
+    FlashLoanLeg, FlashLoanManager, FlashLoanReceiver, FlashLoanReceiverClient,
+    FLASH_LOAN_FEE_BPS,
+};
+pub use insurance_fund::{InsuranceAccount, InsuranceFund, LossSource, Shortfall};
 pub use liquidity_pool::{LiquidityPool, PoolRegistry, Route};
+pub use margin::{LendingReserve, MarginConfig, MarginHealth, MarginManager, MarginPosition};
 
 // KYC exports for contract interface
 pub use kyc::{
-    GovernanceOverride, KYCError, KYCRecord, KYCStatus, KYCSystem, DEFAULT_PENDING_EXPIRY_DURATION,
-    DEFAULT_TIMELOCK_DURATION, MIN_PENDING_EXPIRY_DURATION, MIN_TIMELOCK_DURATION,
+    AttestationRecord, CredentialRevocation, GovernanceOverride, KYCAttestation, KYCError,
+    KYCFeature, KYCRecord, KYCRequirement, KYCStatus, KYCSystem, BASE_KYC_LEVEL,
+    DEFAULT_GRACE_PERIOD, DEFAULT_PENDING_EXPIRY_DURATION, DEFAULT_TIMELOCK_DURATION,
+    DEFAULT_VERIFICATION_VALIDITY, MAX_GRACE_PERIOD, MIN_PENDING_EXPIRY_DURATION,
+    MIN_TIMELOCK_DURATION, MIN_VERIFICATION_VALIDITY,
 };
 
+// Sanctions screening shared with the other workspace contracts
+pub use compliance::{DenylistEntry, DenylistMatches, DenylistRoot};
+use compliance::Denylist;
+
 // ZKP exports for contract interface
 #[cfg(feature = "experimental")]
 pub use private_transaction::{
@@ -196,14 +243,36 @@ pub use zkp_types::{
 #[cfg(feature = "experimental")]
 pub use zkp_verification::ProofVerifier;
 
-use portfolio::{Asset, CachedPortfolio, CachedTopTraders, LPPosition, Portfolio, TradeRecord};
-pub use portfolio::{Badge, Metrics, TradeRecord as PubTradeRecord, Transaction};
+use portfolio::{
+    Asset, CachedPnlSummary, CachedPortfolio, CachedTopTraders, LPPosition, Portfolio, TradeRecord,
+};
+pub use portfolio::{Badge, Metrics, PnLSummary, TradeRecord as PubTradeRecord, Transaction};
 pub use rate_limit::{RateLimitStatus, RateLimiter};
 pub use tiers::UserTier;
 use trading::perform_swap;
 
 use crate::errors::{ContractError, SwapTradeError};
+use crate::emergency::{
+    EmergencyAction, EmergencyConfig, EmergencyController, EmergencyLevel, OperationType,
+};
+use crate::governance::delegation::{Delegation, DelegationTopic};
+use crate::governance::quadratic_voting::{Tally, VotingMode};
+use crate::governance::rewards::{
+    Epoch as RewardEpoch, ParticipationRecord, RewardConfig, RewardManager,
+};
+use crate::governance::treasury::{
+    Payout, SpendingLimit, Treasury, TreasuryAuditHead, TreasuryConfig, TreasuryProposal,
+    TreasuryProposalType, TreasuryStream,
+};
+use crate::governance::upgrade::{
+    DeployedCode, UpgradeConfig, UpgradeManager, UpgradeProposal, UpgradeRecord, UpgradeType,
+    Version,
+};
 use crate::storage::{ADMIN_KEY, PAUSED_KEY};
+use crate::oracle_adapter::{OracleAdapter, OracleProvider};
+use crate::risk_management::{
+    AssetValuation, BreakerConfig, BreakerScope, PortfolioValuation, TripRule,
+};
 
 pub(crate) fn require_verified_user(env: &Env, user: &Address) -> Result<(), ContractError> {
     kyc::KYCSystem::require_verified(env, user)
@@ -214,6 +283,78 @@ fn require_authenticated_verified_user(env: &Env, user: &Address) -> Result<(),
     require_verified_user(env, user)
 }
 
+/// Like `require_verified_user`, but also admits users whose verification
+/// lapsed within the grace window. Only for calls that reduce positions.
+pub(crate) fn require_reducing_user(env: &Env, user: &Address) -> Result<(), ContractError> {
+    kyc::KYCSystem::require_verified_or_grace(env, user)
+}
+
+fn require_authenticated_reducing_user(env: &Env, user: &Address) -> Result<(), ContractError> {
+    user.require_auth();
+    require_reducing_user(env, user)
+}
+
+/// Like `require_verified_user`, but also enforces the feature's minimum
+/// KYC level and accepted jurisdictions.
+pub(crate) fn require_verified_user_for(
+    env: &Env,
+    user: &Address,
+    feature: KYCFeature,
+) -> Result<(), ContractError> {
+    kyc::KYCSystem::require_verified_for(env, user, feature)
+}
+
+fn require_authenticated_verified_user_for(
+    env: &Env,
+    user: &Address,
+    feature: KYCFeature,
+) -> Result<(), ContractError> {
+    user.require_auth();
+    require_verified_user_for(env, user, feature)
+}
+
+/// Gate for state-changing entrypoints: runs any storage migration left
+/// pending by an upgrade, then rejects `op` for `account` if the current
+/// emergency level forbids it or `account` is on the sanctions denylist.
+fn require_operation_allowed(
+    env: &Env,
+    op: OperationType,
+    account: &Address,
+) -> Result<(), ContractError> {
+    UpgradeManager::ensure_migrated(env)?;
+    if !EmergencyController::is_operation_allowed(env, &op, account) {
+        return Err(ContractError::EmergencyRestricted);
+    }
+    Denylist::screen(env, screening_context(&op), &[account])?;
+    Ok(())
+}
+
+/// Return escrowed staking principal to `user`'s XLM balance.
+fn release_staked_xlm(env: &Env, user: &Address, amount: i128) {
+    if amount <= 0 {
+        return;
+    }
+    let mut portfolio: Portfolio = env
+        .storage()
+        .instance()
+        .get(&())
+        .unwrap_or_else(|| Portfolio::new(env));
+    portfolio.credit(env, Asset::XLM, user.clone(), amount);
+    env.storage().instance().set(&(), &portfolio);
+}
+
+/// Context symbol passed to denylist screening for an operation.
+fn screening_context(op: &OperationType) -> Symbol {
+    match op {
+        OperationType::Swap => symbol_short!("swap"),
+        OperationType::LpDeposit => symbol_short!("lp_dep"),
+        OperationType::LpWithdraw => symbol_short!("lp_wd"),
+        OperationType::OrderPlacement => symbol_short!("order"),
+        OperationType::EmergencyWithdraw => symbol_short!("em_wd"),
+        OperationType::ProposalCreation => symbol_short!("proposal"),
+    }
+}
+
 fn require_not_paused(env: &Env) -> Result<(), ContractError> {
     let paused: bool = env.storage().persistent().get(&PAUSED_KEY).unwrap_or(false);
     if paused {
@@ -241,6 +382,7 @@ pub fn create_proposal(
     caller: Address,
     action: governance_system::ProposalAction,
 ) -> Result<u64, SwapTradeError> {
+    require_operation_allowed(&env, OperationType::ProposalCreation, &caller)?;
     governance_system::create_proposal(&env, caller, action)
 }
 
@@ -293,11 +435,49 @@ pub fn withdraw_treasury_fees(
         return Err(ContractError::NotAuthorized);
     }
     let mut registry = load_pool_registry(&env);
-    let fees = registry.withdraw_treasury_fees(&env, pool_id, caller)?;
+    let fees = registry.withdraw_treasury_fees(&env, pool_id, caller.clone())?;
     save_pool_registry(&env, &registry);
+    credit_treasury_fees(&env, &registry, pool_id, &caller, fees)?;
     Ok(fees)
 }
 
+/// Collect withdrawn pool fees from `from` as SEP-41 tokens and credit them
+/// to the on-chain treasury, less the insurance fund's share. Fails unless
+/// every fee-bearing pool token has a mapped treasury token.
+fn credit_treasury_fees(
+    env: &Env,
+    registry: &PoolRegistry,
+    pool_id: u64,
+    from: &Address,
+    fees: (i128, i128),
+) -> Result<(), ContractError> {
+    let pool = registry
+        .get_pool(pool_id)
+        .ok_or(ContractError::LPPositionNotFound)?;
+    for (symbol, amount) in [(pool.token_a, fees.0), (pool.token_b, fees.1)] {
+        if amount > 0 {
+            collect_treasury_fee(env, pool_id, from, symbol, amount)?;
+        }
+    }
+    Ok(())
+}
+
+/// Collect `amount` of pool fees in `symbol` from `from` as the mapped
+/// SEP-41 token and credit it to the treasury, less the share the insurance
+/// fund takes into custody.
+fn collect_treasury_fee(
+    env: &Env,
+    pool_id: u64,
+    from: &Address,
+    symbol: Symbol,
+    amount: i128,
+) -> Result<(), ContractError> {
+    let token = Treasury::collect_pool_fees(env, from, symbol.clone(), amount)?;
+    let insured = InsuranceFund::receive_fees(env, &symbol, &token, amount);
+    Treasury::receive_pool_fees(env, pool_id, &token, amount - insured);
+    Ok(())
+}
+
 // Batch imports
 use batch::{execute_batch_atomic, execute_batch_best_effort, BatchOperation, BatchResult};
 
@@ -418,17 +598,13 @@ impl CounterContract {
         end_time: u64,
     ) -> Result<(), seasons::SeasonError> {
         caller.require_auth();
-        if !admin::is_admin(&env, &caller) {
-            return Err(seasons::SeasonError::NotAdmin);
-        }
+        admin::require_admin(&env, &caller).map_err(|_| seasons::SeasonError::NotAdmin)?;
         seasons::start_season(&env, end_time)
     }
 
     pub fn end_season(env: Env, caller: Address) -> Result<(), seasons::SeasonError> {
         caller.require_auth();
-        if !admin::is_admin(&env, &caller) {
-            return Err(seasons::SeasonError::NotAdmin);
-        }
+        admin::require_admin(&env, &caller).map_err(|_| seasons::SeasonError::NotAdmin)?;
         seasons::end_season(&env)
     }
 
@@ -448,9 +624,10 @@ impl CounterContract {
         migration::get_stored_version(&env)
     }
 
-    /// Migrate contract data from V1 to V2
+    /// Run storage migrations up to the current schema. Also runs
+    /// automatically on the first gated call after an upgrade.
     pub fn migrate(env: Env) -> Result<(), SwapTradeError> {
-        migration::migrate_from_v1_to_v2(&env)
+        UpgradeManager::migrate(&env)
     }
 
     /// Set the admin address (admin only)
@@ -468,7 +645,7 @@ impl CounterContract {
     /// Pause trading (admin only)
     pub fn pause_trading(env: Env, caller: Address) -> Result<bool, SwapTradeError> {
         caller.require_auth();
-        crate::admin::require_admin(&env, &caller)?;
+        crate::admin::require_pause_admin(&env, &caller)?;
 
         env.storage().persistent().set(&PAUSED_KEY, &true);
         crate::events::admin_paused(&env, caller, env.ledger().timestamp() as i64);
@@ -478,7 +655,7 @@ impl CounterContract {
     /// Resume trading (admin only)
     pub fn resume_trading(env: Env, caller: Address) -> Result<bool, SwapTradeError> {
         caller.require_auth();
-        crate::admin::require_admin(&env, &caller)?;
+        crate::admin::require_pause_admin(&env, &caller)?;
 
         env.storage().persistent().set(&PAUSED_KEY, &false);
         crate::events::admin_resumed(&env, caller, env.ledger().timestamp() as i64);
@@ -550,7 +727,9 @@ impl CounterContract {
         user: Address,
     ) -> Result<i128, ContractError> {
         require_not_paused(&env)?;
-        require_authenticated_verified_user(&env, &user)?;
+        require_operation_allowed(&env, OperationType::Swap, &user)?;
+        user.require_auth();
+        require_verified_user_for(&env, &user, KYCFeature::Swap)?;
 
         // Oracle validation
         use crate::oracle::{AggregatorV3Interface, OracleWrapper};
@@ -584,16 +763,13 @@ impl CounterContract {
 
         // ===== RISK MANAGEMENT CHECKS =====
 
-        // Check circuit breaker
-        if risk_management::CircuitBreaker::is_circuit_breaker_active(&env) {
+        // Check circuit breakers, global and for both legs
+        if risk_management::CircuitBreaker::is_halted(&env, &from)
+            || risk_management::CircuitBreaker::is_halted(&env, &to)
+        {
             return Err(ContractError::CircuitBreakerActive);
         }
 
-        // Check concentration limits
-        if risk_management::ConcentrationRisk::check_concentration_limit(&env, &portfolio, &user) {
-            return Err(ContractError::InvalidAmount); // Use existing error for now
-        }
-
         // Check position limits for the asset being purchased
         let to_asset = if to == symbol_short!("XLM") {
             Asset::XLM
@@ -620,6 +796,24 @@ impl CounterContract {
             return Err(ContractError::InvalidAmount); // Position limit exceeded
         }
 
+        // Check concentration limits against the portfolio the swap leaves
+        let from_asset = if from == symbol_short!("XLM") {
+            Asset::XLM
+        } else {
+            Asset::Custom(from.clone())
+        };
+        if risk_management::ConcentrationRisk::swap_breaches_concentration_limit(
+            &env,
+            &portfolio,
+            &user,
+            &from_asset,
+            amount,
+            &to_asset,
+            estimated_out,
+        ) {
+            return Err(ContractError::InvalidAmount); // Use existing error for now
+        }
+
         let fee_bps = tiers::get_effective_fee_bps(&env, user_tier.clone());
 
         // Calculate fee amount (fee is collected on input amount)
@@ -655,6 +849,7 @@ impl CounterContract {
         )?;
 
         portfolio.record_trade(&env, user.clone());
+        portfolio.record_transaction(&env, user.clone(), from, to, amount, out_amount);
         portfolio.record_daily_portfolio_value(&env, user.clone(), env.ledger().timestamp());
 
         env.storage().instance().set(&(), &portfolio);
@@ -680,7 +875,11 @@ impl CounterContract {
         if require_not_paused(&env).is_err() {
             return 0;
         }
-        if require_authenticated_verified_user(&env, &user).is_err() {
+        if require_operation_allowed(&env, OperationType::Swap, &user).is_err() {
+            return 0;
+        }
+        user.require_auth();
+        if require_verified_user_for(&env, &user, KYCFeature::Swap).is_err() {
             return 0;
         }
 
@@ -725,7 +924,7 @@ impl CounterContract {
     
     /// Place a limit order on the orderbook
     /// Returns the new order ID
-    pub fn place_limit_order(
+    pub fn place_book_order(
         env: Env,
         base_token: Symbol,
         quote_token: Symbol,
@@ -736,7 +935,8 @@ impl CounterContract {
         owner: Address,
     ) -> Result<u64, crate::errors::ContractError> {
         require_not_paused(&env)?;
-        require_authenticated_verified_user(&env, &owner)?;
+        require_operation_allowed(&env, OperationType::OrderPlacement, &owner)?;
+        require_authenticated_verified_user_for(&env, &owner, KYCFeature::Swap)?;
         
         // Ensure user has enough balance to place the order
         let mut portfolio: crate::portfolio::Portfolio = env
@@ -764,7 +964,7 @@ impl CounterContract {
             crate::portfolio::Asset::Custom(reserve_token)
         };
         
-        let current_balance = portfolio.balance_of(&env, reserve_asset, owner.clone());
+        let current_balance = portfolio.balance_of(&env, reserve_asset.clone(), owner.clone());
         if current_balance < reserve_amount {
             return Err(crate::errors::ContractError::InvalidAmount);
         }
@@ -775,7 +975,7 @@ impl CounterContract {
         invalidate_query_cache(&env);
         
         // Place the order in the orderbook
-        let order_id = crate::orders::OrderManager::place_limit_order(
+        let order_id = crate::orders::OrderManager::place_book_order(
             &env,
             owner,
             base_token,
@@ -801,7 +1001,8 @@ impl CounterContract {
         taker: Address,
     ) -> Result<Vec<crate::orders::FillResult>, crate::errors::ContractError> {
         require_not_paused(&env)?;
-        require_authenticated_verified_user(&env, &taker)?;
+        require_operation_allowed(&env, OperationType::Swap, &taker)?;
+        require_authenticated_verified_user_for(&env, &taker, KYCFeature::Swap)?;
         
         // Execute the taker order fill
         let fills = crate::orders::OrderManager::take_order(
@@ -822,6 +1023,9 @@ impl CounterContract {
             .unwrap_or_else(|| crate::portfolio::Portfolio::new(&env));
         
         for fill in fills.iter() {
+            // Makers are counterparties to the taker's trade
+            Denylist::screen(&env, symbol_short!("fill"), &[&fill.maker])?;
+
             // Transfer assets between maker and taker
             match side {
                 crate::orders::OrderSide::Buy => {
@@ -869,14 +1073,16 @@ impl CounterContract {
         Ok(fills)
     }
     
-    /// Cancel an existing order
-    pub fn cancel_order(
+    /// Cancel an orderbook order and refund its reserved balance
+    pub fn cancel_book_order(
         env: Env,
         order_id: u64,
         owner: Address,
     ) -> Result<(), crate::errors::ContractError> {
         require_not_paused(&env)?;
-        require_authenticated_verified_user(&env, &owner)?;
+        // Cancelling only refunds the owner, so it is gated like a withdrawal.
+        require_operation_allowed(&env, OperationType::LpWithdraw, &owner)?;
+        require_authenticated_reducing_user(&env, &owner)?;
         
         // Get the order to calculate refund amount
         let order = crate::orders::OrderManager::get_order(&env, order_id)?;
@@ -1236,7 +1442,9 @@ impl CounterContract {
 
     pub fn execute_batch_atomic(env: Env, operations: Vec<BatchOperation>) -> BatchResult {
         // Check if trading is paused
-        if require_not_paused(&env).is_err() {
+        if require_not_paused(&env).is_err()
+            || !EmergencyController::current_level(&env).allows_operation(&OperationType::Swap)
+        {
             let mut result = BatchResult::new(&env);
             result.operations_failed = 1;
             return result;
@@ -1261,7 +1469,15 @@ impl CounterContract {
         // Require authentication from the caller
         if let Some(caller_addr) = &caller {
             caller_addr.require_auth();
-            if let Err(_) = require_verified_user(&env, caller_addr) {
+            let has_swap = operations
+                .iter()
+                .any(|op| matches!(op, BatchOperation::Swap(_, _, _, _)));
+            let verified = if has_swap {
+                require_verified_user_for(&env, caller_addr, KYCFeature::Swap)
+            } else {
+                require_verified_user(&env, caller_addr)
+            };
+            if verified.is_err() {
                 let mut result = BatchResult::new(&env);
                 result.operations_failed = 1;
                 return result;
@@ -1325,7 +1541,9 @@ impl CounterContract {
 
     pub fn execute_batch_best_effort(env: Env, operations: Vec<BatchOperation>) -> BatchResult {
         // Check if trading is paused
-        if require_not_paused(&env).is_err() {
+        if require_not_paused(&env).is_err()
+            || !EmergencyController::current_level(&env).allows_operation(&OperationType::Swap)
+        {
             let mut result = BatchResult::new(&env);
             result.operations_failed = 1;
             return result;
@@ -1350,7 +1568,15 @@ impl CounterContract {
         // Require authentication from the caller
         if let Some(caller_addr) = &caller {
             caller_addr.require_auth();
-            if let Err(_) = require_verified_user(&env, caller_addr) {
+            let has_swap = operations
+                .iter()
+                .any(|op| matches!(op, BatchOperation::Swap(_, _, _, _)));
+            let verified = if has_swap {
+                require_verified_user_for(&env, caller_addr, KYCFeature::Swap)
+            } else {
+                require_verified_user(&env, caller_addr)
+            };
+            if verified.is_err() {
                 let mut result = BatchResult::new(&env);
                 result.operations_failed = 1;
                 return result;
@@ -1427,6 +1653,7 @@ impl CounterContract {
         user: Address,
     ) -> Result<i128, ContractError> {
         require_not_paused(&env)?;
+        require_operation_allowed(&env, OperationType::LpDeposit, &user)?;
         require_authenticated_verified_user(&env, &user)?;
 
         if xlm_amount <= 0 || usdc_amount <= 0 {
@@ -1563,7 +1790,8 @@ impl CounterContract {
         user: Address,
     ) -> Result<(i128, i128), ContractError> {
         require_not_paused(&env)?;
-        require_authenticated_verified_user(&env, &user)?;
+        require_operation_allowed(&env, OperationType::LpWithdraw, &user)?;
+        require_authenticated_reducing_user(&env, &user)?;
 
         if lp_tokens <= 0 {
             return Err(ContractError::InvalidAmount);
@@ -1692,6 +1920,7 @@ impl CounterContract {
         provider: Address,
     ) -> Result<i128, ContractError> {
         require_not_paused(&env)?;
+        require_operation_allowed(&env, OperationType::LpDeposit, &provider)?;
         provider.require_auth();
         require_verified_user(&env, &provider)?;
 
@@ -1720,8 +1949,9 @@ impl CounterContract {
         provider: Address,
     ) -> Result<(i128, i128), ContractError> {
         require_not_paused(&env)?;
+        require_operation_allowed(&env, OperationType::LpWithdraw, &provider)?;
         provider.require_auth();
-        require_verified_user(&env, &provider)?;
+        require_reducing_user(&env, &provider)?;
 
         let mut registry = load_pool_registry(&env);
         let (amount_a, amount_b) =
@@ -1750,11 +1980,19 @@ impl CounterContract {
         trader: Address,
     ) -> Result<i128, ContractError> {
         require_not_paused(&env)?;
+        require_operation_allowed(&env, OperationType::Swap, &trader)?;
         trader.require_auth();
-        require_verified_user(&env, &trader)?;
+        require_verified_user_for(&env, &trader, KYCFeature::Swap)?;
 
         let mut registry = load_pool_registry(&env);
-        let result = registry.swap(&env, pool_id, token_in, amount_in, min_amount_out)?;
+        let fee = registry.swap_fee(pool_id, amount_in)?;
+        let result = registry.swap(&env, pool_id, token_in.clone(), amount_in, min_amount_out)?;
+        // Fees in a token the treasury holds are paid in by the trader and
+        // go straight into treasury custody instead of accruing in the pool.
+        if fee > 0 && Treasury::fee_token(&env, token_in.clone()).is_some() {
+            registry.settle_treasury_fee(pool_id, &token_in, fee)?;
+            collect_treasury_fee(&env, pool_id, &trader, token_in, fee)?;
+        }
         save_pool_registry(&env, &registry);
         Ok(result)
     }
@@ -1800,7 +2038,7 @@ impl CounterContract {
         trader: Address,
     ) -> Result<i128, ContractError> {
         trader.require_auth();
-        require_verified_user(&env, &trader)?;
+        require_verified_user_for(&env, &trader, KYCFeature::Swap)?;
 
         trading::execute_multihop_swap(&env, &route, amount_in, min_amount_out, &trader)
     }
@@ -1832,13 +2070,14 @@ impl CounterContract {
         risk_management::volume_circuit_breaker::get_status(&env)
     }
 
-    /// Reset the volume-threshold circuit breaker and restore trading (admin only).
+    /// Reset the global circuit breaker, clearing recorded volume (admin only).
     pub fn reset_circuit_breaker(env: Env, admin: Address) -> Result<(), SwapTradeError> {
         risk_management::volume_circuit_breaker::reset(&env, admin)
     }
 
     pub fn set_price(env: Env, token_pair: (Symbol, Symbol), price: u128) {
         set_stored_price(&env, token_pair, price);
+        invalidate_query_cache(&env);
     }
 
     pub fn get_current_price(env: Env, token_pair: (Symbol, Symbol)) -> u128 {
@@ -1878,14 +2117,27 @@ impl CounterContract {
 
     /// Stake tokens for a specified duration to earn bonuses
     /// Supports: 30, 60, 90, or 365-day stakes
+    /// The staked XLM is escrowed from the user's balance until the stake is
+    /// claimed or unstaked.
     pub fn stake(
         env: Env,
         user: Address,
         amount: i128,
         duration_days: u32,
     ) -> Result<u32, ContractError> {
+        require_operation_allowed(&env, OperationType::LpDeposit, &user)?;
         require_authenticated_verified_user(&env, &user)?;
-        let result = StakingBonusManager::stake(&env, user, amount, duration_days)?;
+        let mut portfolio: Portfolio = env
+            .storage()
+            .instance()
+            .get(&())
+            .unwrap_or_else(|| Portfolio::new(&env));
+        if amount > 0 && portfolio.balance_of(&env, Asset::XLM, user.clone()) < amount {
+            return Err(ContractError::InsufficientBalance);
+        }
+        let result = StakingBonusManager::stake(&env, user.clone(), amount, duration_days)?;
+        portfolio.debit(&env, Asset::XLM, user, amount);
+        env.storage().instance().set(&(), &portfolio);
         invalidate_query_cache(&env);
         Ok(result)
     }
@@ -1893,7 +2145,7 @@ impl CounterContract {
     /// Claim earned staking bonuses (after 30-day holding period)
     /// Returns total bonuses claimed
     pub fn claim_staking_bonuses(env: Env, user: Address) -> Result<i128, ContractError> {
-        require_authenticated_verified_user(&env, &user)?;
+        require_authenticated_reducing_user(&env, &user)?;
         let result = StakingBonusManager::claim_bonuses(&env, user)?;
         invalidate_query_cache(&env);
         Ok(result)
@@ -1902,21 +2154,26 @@ impl CounterContract {
     /// Claim staked principal after lock period expires
     /// Returns the principal amount
     pub fn claim_stake(env: Env, user: Address, stake_id: u32) -> Result<i128, ContractError> {
-        require_authenticated_verified_user(&env, &user)?;
-        let result = StakingBonusManager::claim_stake(&env, user, stake_id)?;
+        require_operation_allowed(&env, OperationType::LpWithdraw, &user)?;
+        require_authenticated_reducing_user(&env, &user)?;
+        let result = StakingBonusManager::claim_stake(&env, user.clone(), stake_id)?;
+        release_staked_xlm(&env, &user, result);
         invalidate_query_cache(&env);
         Ok(result)
     }
 
     /// Unstake early before lock period (incurs 10% penalty)
-    /// Returns (principal_after_penalty, penalty_amount)
+    /// Returns (principal_after_penalty, penalty_amount); only the principal
+    /// after penalty is returned to the user's balance.
     pub fn unstake_early(
         env: Env,
         user: Address,
         stake_id: u32,
     ) -> Result<(i128, i128), ContractError> {
-        require_authenticated_verified_user(&env, &user)?;
-        let result = StakingBonusManager::unstake_early(&env, user, stake_id)?;
+        require_operation_allowed(&env, OperationType::LpWithdraw, &user)?;
+        require_authenticated_reducing_user(&env, &user)?;
+        let result = StakingBonusManager::unstake_early(&env, user.clone(), stake_id)?;
+        release_staked_xlm(&env, &user, result.0);
         invalidate_query_cache(&env);
         Ok(result)
     }
@@ -1946,174 +2203,1020 @@ impl CounterContract {
     }
 
     // ────────────────────────────────────────────────────────────────────────
-    // Flash Loan Module
+    // Vote-Escrow (veToken) System
     // ────────────────────────────────────────────────────────────────────────
 
-    pub fn flash_loan(
+    /// Lock governance tokens until `unlock_time` (rounded down to a week,
+    /// at most ~4 years ahead) for decaying voting power
+    pub fn create_ve_lock(
         env: Env,
-        pool_id: u64,
-        receiver: Address,
-        asset: Symbol,
+        user: Address,
         amount: i128,
-        _data: soroban_sdk::Bytes,
-    ) -> Result<i128, ContractError> {
-        let _ = _data;
-        Err(ContractError::NotAuthorized)
+        unlock_time: u64,
+    ) -> Result<VeLock, ContractError> {
+        require_operation_allowed(&env, OperationType::LpDeposit, &user)?;
+        VoteEscrowManager::create_lock(&env, user, amount, unlock_time)
     }
 
-    // ────────────────────────────────────────────────────────────────────────
-    // Advanced Order Types (Limit & Stop-Loss)
-    // ────────────────────────────────────────────────────────────────────────
-
-    /// Place a limit order that executes when price reaches limit_price or better
-    pub fn place_limit_order(
+    /// Add tokens to an existing vote-escrow lock
+    pub fn increase_ve_amount(
         env: Env,
-        token_in: Symbol,
-        token_out: Symbol,
-        amount_in: i128,
-        limit_price: u128,
-        expires_at: Option<u64>,
         user: Address,
-    ) -> Result<u64, ContractError> {
-        require_authenticated_verified_user(&env, &user)?;
-        orders::OrderManager::place_limit_order(
-            &env,
-            user,
-            token_in,
-            token_out,
-            amount_in,
-            limit_price,
-            expires_at,
-        )
+        amount: i128,
+    ) -> Result<VeLock, ContractError> {
+        require_operation_allowed(&env, OperationType::LpDeposit, &user)?;
+        VoteEscrowManager::increase_amount(&env, user, amount)
     }
 
-    /// Place a stop-loss order that executes when price reaches trigger_price
-    pub fn place_stop_loss(
+    /// Extend an existing vote-escrow lock to a later unlock time
+    pub fn increase_ve_unlock_time(
         env: Env,
-        token_in: Symbol,
-        token_out: Symbol,
-        amount_in: i128,
-        trigger_price: u128,
-        expires_at: Option<u64>,
         user: Address,
-    ) -> Result<u64, ContractError> {
-        require_authenticated_verified_user(&env, &user)?;
-        orders::OrderManager::place_stop_loss(
-            &env,
-            user,
-            token_in,
-            token_out,
-            amount_in,
-            trigger_price,
-            expires_at,
-        )
+        unlock_time: u64,
+    ) -> Result<VeLock, ContractError> {
+        VoteEscrowManager::increase_unlock_time(&env, user, unlock_time)
     }
 
-    /// Cancel an existing order
-    /// Place a recurring (DCA) order that executes on a fixed schedule
-    pub fn place_recurring_order(
-        env: Env,
-        token_in: Symbol,
-        token_out: Symbol,
-        amount_in: i128,
-        interval_secs: u64,
-        occurrences: u64,
-        expires_at: Option<u64>,
-        user: Address,
-    ) -> Result<u64, ContractError> {
-        require_authenticated_verified_user(&env, &user)?;
-        orders::OrderManager::place_recurring_order(
-            &env,
-            user,
-            token_in,
-            token_out,
-            amount_in,
-            interval_secs,
-            occurrences,
-            expires_at,
-        )
+    /// Withdraw escrowed tokens after the lock expires
+    pub fn withdraw_ve_lock(env: Env, user: Address) -> Result<i128, ContractError> {
+        require_operation_allowed(&env, OperationType::LpWithdraw, &user)?;
+        VoteEscrowManager::withdraw(&env, user)
     }
 
-    /// Execute all due recurring orders
-    pub fn execute_due_orders(env: Env) -> Result<Vec<u64>, ContractError> {
-        orders::OrderManager::execute_due_orders(&env)
+    /// Checkpoint the global voting-power curve up to now
+    pub fn checkpoint_ve(env: Env) {
+        VoteEscrowManager::checkpoint(&env)
     }
 
-    /// Cancel an existing order
-    pub fn cancel_order(env: Env, order_id: u64, user: Address) -> Result<(), ContractError> {
-        require_authenticated_verified_user(&env, &user)?;
-        orders::OrderManager::cancel_order(&env, order_id, user)
+    /// Get a user's vote-escrow lock
+    pub fn get_ve_lock(env: Env, user: Address) -> VeLock {
+        VoteEscrowManager::get_lock(&env, &user)
     }
 
-    /// Get order details
-    pub fn get_order(env: Env, order_id: u64) -> Result<orders::Order, ContractError> {
-        orders::OrderManager::get_order(&env, order_id)
+    /// Get a user's voting power, now or at a past timestamp
+    pub fn get_voting_power(env: Env, user: Address, at: Option<u64>) -> i128 {
+        match at {
+            Some(ts) => VoteEscrowManager::balance_of_at(&env, &user, ts),
+            None => VoteEscrowManager::balance_of(&env, &user),
+        }
     }
 
-    /// Get all active orders for a user
-    pub fn get_user_orders(env: Env, user: Address) -> Vec<orders::Order> {
-        orders::OrderManager::get_user_orders(&env, user)
+    /// Get total voting power, now or at a past timestamp
+    pub fn get_total_voting_power(env: Env, at: Option<u64>) -> i128 {
+        match at {
+            Some(ts) => VoteEscrowManager::total_power_at(&env, ts),
+            None => VoteEscrowManager::total_power(&env),
+        }
     }
 
     // ────────────────────────────────────────────────────────────────────────
-    // Portfolio Analytics Dashboard
+    // Gauge Controller
     // ────────────────────────────────────────────────────────────────────────
 
-    /// Get comprehensive analytics summary for a user
-    /// Includes PnL, win rate, Sharpe ratio, and other metrics
-    pub fn get_analytics_summary(env: Env, user: Address) -> portfolio::AnalyticsSummary {
-        let portfolio: Portfolio = env
-            .storage()
-            .instance()
-            .get(&())
-            .unwrap_or_else(|| Portfolio::new(&env));
-        portfolio.get_analytics_summary(&env, user)
+    /// Admin only: register a farm gauge for a pool
+    pub fn add_gauge(env: Env, admin: Address, pool_id: u64) -> Result<(), ContractError> {
+        GaugeController::add_gauge(&env, admin, pool_id)
     }
 
-    /// Get total claimed bonuses for a user
-    pub fn get_user_claimed_bonuses(env: Env, user: Address) -> i128 {
-        StakingBonusManager::get_user_claimed_bonuses(&env, user)
+    /// Admin only: set the farm emission budget split across gauges per epoch
+    pub fn set_gauge_emission_budget(
+        env: Env,
+        admin: Address,
+        budget_per_epoch: i128,
+    ) -> Result<(), ContractError> {
+        GaugeController::set_emission_budget(&env, admin, budget_per_epoch)
     }
 
-    /// Get pending claimable bonuses for a user (after 30-day holding period)
-    pub fn get_user_pending_bonuses(env: Env, user: Address) -> i128 {
-        StakingBonusManager::get_user_pending_bonuses(&env, user)
+    /// Allocate a share (in bps) of the caller's voting power to a gauge
+    pub fn vote_gauge_weight(
+        env: Env,
+        user: Address,
+        pool_id: u64,
+        weight_bps: u32,
+    ) -> Result<(), ContractError> {
+        GaugeController::vote(&env, user, pool_id, weight_bps)
     }
 
-    /// Get global staking statistics (transparency)
-    /// Returns (total_staked, total_distributed, distribution_records_count)
-    pub fn get_staking_statistics(env: Env) -> (i128, i128, u64) {
-        StakingBonusManager::get_statistics(&env)
+    /// Apply gauge weights to farm emission rates at an epoch boundary
+    pub fn checkpoint_gauges(env: Env) -> Result<(), ContractError> {
+        GaugeController::checkpoint(&env)
     }
 
-    /// Get distribution history for auditing
-    pub fn get_distribution_history(env: Env) -> Vec<DistributionRecord> {
-        StakingBonusManager::get_distribution_history(&env)
+    /// List pools with a registered gauge
+    pub fn get_gauges(env: Env) -> Vec<u64> {
+        GaugeController::get_gauges(&env)
     }
 
-    /// Execute periodic bonus distribution (admin only typically)
-    pub fn execute_staking_distribution(env: Env) -> Result<DistributionRecord, ContractError> {
-        StakingBonusManager::execute_distribution(&env)
+    /// Get the current vote weight on a gauge
+    pub fn get_gauge_weight(env: Env, pool_id: u64) -> Result<i128, ContractError> {
+        GaugeController::get_gauge_weight(&env, pool_id)
+    }
+
+    /// Get a gauge's share of this epoch's emissions, in bps
+    pub fn get_gauge_relative_weight(env: Env, pool_id: u64) -> u32 {
+        GaugeController::get_relative_weight(&env, pool_id)
+    }
+
+    /// Get a user's vote on a gauge
+    pub fn get_gauge_vote(env: Env, user: Address, pool_id: u64) -> Option<GaugeVote> {
+        GaugeController::get_user_vote(&env, user, pool_id)
+    }
+
+    /// Get the emission split applied for the current epoch
+    pub fn get_gauge_epoch(env: Env) -> Option<GaugeEpoch> {
+        GaugeController::get_current_epoch(&env)
     }
 
     // ────────────────────────────────────────────────────────────────────────
-    // KYC Verification System
+    // Emergency Controls
     // ────────────────────────────────────────────────────────────────────────
 
-    /// Add a KYC operator (admin only)
-    pub fn kyc_add_operator(
+    /// Admin only: set the emergency signer set and approval thresholds
+    pub fn configure_emergency(
         env: Env,
         admin: Address,
-        operator: Address,
+        config: EmergencyConfig,
     ) -> Result<(), ContractError> {
-        kyc::KYCSystem::add_operator(&env, &admin, operator)
+        EmergencyController::configure(&env, admin, config)
     }
 
-    /// Remove a KYC operator (admin only)
-    pub fn kyc_remove_operator(
+    /// Propose an emergency level; it applies once the level's threshold of
+    /// signers has approved
+    pub fn set_emergency_level(
         env: Env,
-        admin: Address,
-        operator: Address,
+        initiator: Address,
+        level: EmergencyLevel,
+        reason: soroban_sdk::String,
+        expiry_secs: Option<u64>,
+    ) -> Result<u64, ContractError> {
+        EmergencyController::set_emergency_level(&env, initiator, level, reason, expiry_secs)
+    }
+
+    /// Approve a pending emergency action, returning its approval count
+    pub fn approve_emergency(
+        env: Env,
+        action_id: u64,
+        signer: Address,
+    ) -> Result<u32, ContractError> {
+        EmergencyController::approve_emergency(&env, action_id, signer)
+    }
+
+    /// Approve lifting a single emergency action, returning its lift
+    /// approval count
+    pub fn lift_emergency(env: Env, action_id: u64, signer: Address) -> Result<u32, ContractError> {
+        EmergencyController::lift_emergency(&env, action_id, signer)
+    }
+
+    /// Approve lifting every active emergency action, returning the resume
+    /// approval count
+    pub fn emergency_resume(
+        env: Env,
+        signer: Address,
+        reason: soroban_sdk::String,
+    ) -> Result<u32, ContractError> {
+        EmergencyController::emergency_resume(&env, signer, reason)
+    }
+
+    /// Mark expired emergency actions as such
+    pub fn tick_emergency(env: Env) {
+        EmergencyController::tick(&env)
+    }
+
+    /// Get the emergency level currently in force
+    pub fn get_emergency_level(env: Env) -> EmergencyLevel {
+        EmergencyController::current_level(&env)
+    }
+
+    /// Exempt an address from emergency restrictions below Critical
+    pub fn add_emergency_whitelist(
+        env: Env,
+        signer: Address,
+        address: Address,
+    ) -> Result<(), ContractError> {
+        EmergencyController::add_to_whitelist(&env, signer, address)
+    }
+
+    /// Remove an address from the emergency whitelist
+    pub fn remove_emergency_whitelist(
+        env: Env,
+        signer: Address,
+        address: Address,
+    ) -> Result<(), ContractError> {
+        EmergencyController::remove_from_whitelist(&env, signer, address)
+    }
+
+    /// Approve freezing or unfreezing a single account; it applies once the
+    /// standard threshold of signers has approved. Returns the approval count
+    pub fn set_user_frozen(
+        env: Env,
+        signer: Address,
+        user: Address,
+        frozen: bool,
+    ) -> Result<u32, ContractError> {
+        EmergencyController::set_frozen(&env, signer, user, frozen)
+    }
+
+    /// Get an emergency action by id
+    pub fn get_emergency_action(env: Env, action_id: u64) -> Option<EmergencyAction> {
+        EmergencyController::get_action(&env, action_id)
+    }
+
+    /// Approve arming emergency exit; returns the approval count
+    pub fn approve_emergency_exit(env: Env, signer: Address) -> Result<u32, ContractError> {
+        EmergencyWithdrawal::approve(&env, signer)
+    }
+
+    /// Disarm emergency exit
+    pub fn disable_emergency_exit(env: Env, signer: Address) -> Result<(), ContractError> {
+        EmergencyWithdrawal::disable(&env, signer)
+    }
+
+    /// Whether users can currently take the emergency exit
+    pub fn is_emergency_exit_enabled(env: Env) -> bool {
+        EmergencyWithdrawal::is_enabled(&env)
+    }
+
+    /// Withdraw balances, LP shares and staked principal at last-known values.
+    /// Balances are transferred out up to the user's custody deposits and the
+    /// rest is left in their Portfolio. Unclaimed rewards are forfeited.
+    pub fn emergency_exit(env: Env, user: Address) -> Result<EmergencyExitReceipt, ContractError> {
+        Denylist::screen(&env, symbol_short!("exit"), &[&user])?;
+        let receipt = EmergencyWithdrawal::exit(&env, user)?;
+        invalidate_query_cache(&env);
+        Ok(receipt)
+    }
+
+    /// Get a user's most recent emergency exit receipt
+    pub fn get_emergency_exit_receipt(env: Env, user: Address) -> Option<EmergencyExitReceipt> {
+        EmergencyWithdrawal::get_receipt(&env, user)
+    }
+
+    /// Register the SEP-41 token custody deposits of `asset` are made in (admin only)
+    pub fn set_custody_token(
+        env: Env,
+        admin: Address,
+        asset: Symbol,
+        token: Address,
+    ) -> Result<(), ContractError> {
+        EmergencyWithdrawal::set_custody_token(&env, admin, asset, token)
+    }
+
+    /// Get the custody token registered for `asset`
+    pub fn get_custody_token(env: Env, asset: Symbol) -> Option<Address> {
+        EmergencyWithdrawal::custody_token(&env, &asset)
+    }
+
+    /// Deposit SEP-41 tokens into custody and credit them to the user's
+    /// Portfolio balance
+    pub fn deposit_custody(
+        env: Env,
+        user: Address,
+        asset: Symbol,
+        amount: i128,
+    ) -> Result<(), ContractError> {
+        require_operation_allowed(&env, OperationType::LpDeposit, &user)?;
+        require_authenticated_verified_user(&env, &user)?;
+        EmergencyWithdrawal::deposit(&env, user, asset, amount)?;
+        invalidate_query_cache(&env);
+        Ok(())
+    }
+
+    /// Withdraw custody deposits back out of the user's Portfolio balance
+    pub fn withdraw_custody(
+        env: Env,
+        user: Address,
+        asset: Symbol,
+        amount: i128,
+    ) -> Result<(), ContractError> {
+        require_operation_allowed(&env, OperationType::LpWithdraw, &user)?;
+        require_authenticated_reducing_user(&env, &user)?;
+        EmergencyWithdrawal::withdraw(&env, user, asset, amount)?;
+        invalidate_query_cache(&env);
+        Ok(())
+    }
+
+    /// Custody deposits of `asset` that `user` can still withdraw
+    pub fn get_custody_deposit(env: Env, user: Address, asset: Symbol) -> i128 {
+        EmergencyWithdrawal::deposited(&env, &user, &asset)
+    }
+
+    // ────────────────────────────────────────────────────────────────────────
+    // Treasury
+    // ────────────────────────────────────────────────────────────────────────
+
+    /// Install the treasury signer set and thresholds (admin only)
+    pub fn configure_treasury(
+        env: Env,
+        admin: Address,
+        config: TreasuryConfig,
+    ) -> Result<(), ContractError> {
+        Treasury::configure(&env, admin, config)
+    }
+
+    /// Add a treasury signer (admin only)
+    pub fn add_treasury_signer(
+        env: Env,
+        admin: Address,
+        signer: Address,
+    ) -> Result<(), ContractError> {
+        Treasury::add_signer(&env, admin, signer)
+    }
+
+    /// Remove a treasury signer (admin only)
+    pub fn remove_treasury_signer(
+        env: Env,
+        admin: Address,
+        signer: Address,
+    ) -> Result<(), ContractError> {
+        Treasury::remove_signer(&env, admin, signer)
+    }
+
+    /// Change the treasury approval threshold (admin only)
+    pub fn update_treasury_threshold(
+        env: Env,
+        admin: Address,
+        threshold: u32,
+    ) -> Result<(), ContractError> {
+        Treasury::update_threshold(&env, admin, threshold)
+    }
+
+    /// Transfer SEP-41 tokens into the treasury
+    pub fn deposit_treasury(
+        env: Env,
+        from: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<(), ContractError> {
+        Treasury::deposit(&env, from, token, amount)
+    }
+
+    /// Map a pool token symbol to the SEP-41 token its fees are credited in
+    /// (admin only). Swap fees in that symbol are then paid in by the trader.
+    pub fn set_treasury_fee_token(
+        env: Env,
+        admin: Address,
+        symbol: Symbol,
+        token: Address,
+    ) -> Result<(), ContractError> {
+        Treasury::set_fee_token(&env, admin, symbol, token)
+    }
+
+    /// Cap treasury withdrawals of `token` per period (admin only)
+    pub fn set_treasury_spending_limit(
+        env: Env,
+        admin: Address,
+        token: Address,
+        max_per_period: i128,
+        period_duration_secs: u64,
+    ) -> Result<(), ContractError> {
+        Treasury::set_spending_limit(&env, admin, token, max_per_period, period_duration_secs)
+    }
+
+    /// Propose a treasury payout, paid at once or streamed along a schedule
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_treasury_proposal(
+        env: Env,
+        proposer: Address,
+        proposal_type: TreasuryProposalType,
+        token: Address,
+        amount: i128,
+        recipient: Address,
+        description: soroban_sdk::String,
+        ttl_secs: u64,
+        payout: Payout,
+    ) -> Result<u64, ContractError> {
+        Treasury::create_proposal(
+            &env,
+            proposer,
+            proposal_type,
+            token,
+            amount,
+            recipient,
+            description,
+            ttl_secs,
+            payout,
+        )
+    }
+
+    /// Approve a treasury proposal; returns the approval count
+    pub fn approve_treasury_proposal(
+        env: Env,
+        proposal_id: u64,
+        signer: Address,
+    ) -> Result<u32, ContractError> {
+        Treasury::approve_proposal(&env, proposal_id, signer)
+    }
+
+    /// Execute an approved treasury proposal; returns the stream id for scheduled payouts
+    pub fn execute_treasury_proposal(
+        env: Env,
+        proposal_id: u64,
+    ) -> Result<Option<u64>, ContractError> {
+        Treasury::execute_proposal(&env, proposal_id)
+    }
+
+    /// Reject a treasury proposal
+    pub fn reject_treasury_proposal(
+        env: Env,
+        proposal_id: u64,
+        signer: Address,
+    ) -> Result<(), ContractError> {
+        Treasury::reject_proposal(&env, proposal_id, signer)
+    }
+
+    /// Claim everything vested on a treasury payout stream
+    pub fn claim_treasury_stream(env: Env, stream_id: u64) -> Result<i128, ContractError> {
+        Treasury::claim_stream(&env, stream_id)
+    }
+
+    /// Get the treasury's accounted balance of a token
+    pub fn get_treasury_balance(env: Env, token: Address) -> i128 {
+        Treasury::balance_of(&env, &token)
+    }
+
+    pub fn get_treasury_config(env: Env) -> Option<TreasuryConfig> {
+        Treasury::get_config(&env)
+    }
+
+    pub fn get_treasury_proposal(env: Env, proposal_id: u64) -> Option<TreasuryProposal> {
+        Treasury::get_proposal(&env, proposal_id)
+    }
+
+    /// Ids of treasury proposals still awaiting execution
+    pub fn get_pending_treasury_proposals(env: Env) -> Vec<u64> {
+        Treasury::pending_proposals(&env)
+    }
+
+    pub fn get_treasury_stream(env: Env, stream_id: u64) -> Option<TreasuryStream> {
+        Treasury::get_stream(&env, stream_id)
+    }
+
+    pub fn get_treasury_spending_limit(env: Env, token: Address) -> Option<SpendingLimit> {
+        Treasury::get_spending_limit(&env, &token)
+    }
+
+    pub fn get_treasury_audit_head(env: Env) -> Option<TreasuryAuditHead> {
+        Treasury::get_audit_head(&env)
+    }
+
+    // ────────────────────────────────────────────────────────────────────────
+    // Governance Participation Rewards
+    // ────────────────────────────────────────────────────────────────────────
+
+    /// Claim a finalized epoch's participation reward, paid from the treasury
+    pub fn claim_governance_reward(
+        env: Env,
+        participant: Address,
+        epoch_id: u64,
+    ) -> Result<i128, ContractError> {
+        RewardManager::claim_rewards(&env, participant, epoch_id)
+    }
+
+    /// Finalize the reward epoch if it has ended; returns the open epoch id
+    pub fn sync_governance_reward_epoch(env: Env) -> Result<u64, ContractError> {
+        RewardManager::sync_epoch(&env)
+    }
+
+    pub fn get_governance_reward_config(env: Env) -> Option<RewardConfig> {
+        RewardManager::get_config(&env)
+    }
+
+    pub fn get_governance_reward_epoch(env: Env, epoch_id: u64) -> Option<RewardEpoch> {
+        RewardManager::get_epoch(&env, epoch_id)
+    }
+
+    pub fn get_governance_participation(
+        env: Env,
+        participant: Address,
+        epoch_id: u64,
+    ) -> Option<ParticipationRecord> {
+        RewardManager::participation_record(&env, &participant, epoch_id)
+    }
+
+    pub fn get_pending_governance_reward(env: Env, participant: Address, epoch_id: u64) -> i128 {
+        RewardManager::pending_reward(&env, &participant, epoch_id)
+    }
+
+    // ────────────────────────────────────────────────────────────────────────
+    // Upgrades
+    // ────────────────────────────────────────────────────────────────────────
+
+    /// Install upgrade signers and thresholds (admin only). The first call
+    /// records the deployed version and WASM hash.
+    pub fn configure_upgrades(
+        env: Env,
+        admin: Address,
+        config: UpgradeConfig,
+        version: Version,
+        wasm_hash: BytesN<32>,
+    ) -> Result<(), ContractError> {
+        UpgradeManager::configure(&env, admin, config, version, wasm_hash)
+    }
+
+    /// Propose switching to an installed WASM at a new semantic version
+    pub fn propose_upgrade(
+        env: Env,
+        proposer: Address,
+        wasm_hash: BytesN<32>,
+        to_version: Version,
+        upgrade_type: UpgradeType,
+        description: soroban_sdk::String,
+    ) -> Result<u64, ContractError> {
+        UpgradeManager::propose_upgrade(
+            &env,
+            proposer,
+            wasm_hash,
+            to_version,
+            upgrade_type,
+            description,
+        )
+    }
+
+    /// Propose returning to the WASM replaced by the last upgrade
+    pub fn propose_rollback(
+        env: Env,
+        proposer: Address,
+        description: soroban_sdk::String,
+    ) -> Result<u64, ContractError> {
+        UpgradeManager::propose_rollback(&env, proposer, description)
+    }
+
+    /// Approve an upgrade proposal; returns the approval count
+    pub fn approve_upgrade(
+        env: Env,
+        proposal_id: u64,
+        signer: Address,
+    ) -> Result<u32, ContractError> {
+        UpgradeManager::approve_upgrade(&env, proposal_id, signer)
+    }
+
+    /// Cancel an upgrade proposal that has not been executed
+    pub fn cancel_upgrade(env: Env, proposal_id: u64, signer: Address) -> Result<(), ContractError> {
+        UpgradeManager::cancel_upgrade(&env, proposal_id, signer)
+    }
+
+    /// Replace the contract WASM once the proposal's timelock has expired
+    pub fn execute_upgrade(env: Env, proposal_id: u64) -> Result<Version, ContractError> {
+        UpgradeManager::execute_upgrade(&env, proposal_id)
+    }
+
+    pub fn get_upgrade_proposal(env: Env, proposal_id: u64) -> Option<UpgradeProposal> {
+        UpgradeManager::get_proposal(&env, proposal_id)
+    }
+
+    /// Ids of upgrade proposals collecting approvals or in timelock
+    pub fn get_pending_upgrades(env: Env) -> Vec<u64> {
+        UpgradeManager::pending_upgrades(&env)
+    }
+
+    /// Version and WASM hash currently installed
+    pub fn get_deployed_code(env: Env) -> Option<DeployedCode> {
+        UpgradeManager::get_current(&env)
+    }
+
+    /// Version and WASM hash a rollback would restore
+    pub fn get_rollback_target(env: Env) -> Option<DeployedCode> {
+        UpgradeManager::get_previous(&env)
+    }
+
+    pub fn get_upgrade_history(env: Env) -> Vec<UpgradeRecord> {
+        UpgradeManager::history(&env)
+    }
+
+    // ────────────────────────────────────────────────────────────────────────
+    // Decentralization Schedule
+    // ────────────────────────────────────────────────────────────────────────
+
+    /// Commit the schedule that sunsets admin powers (admin only, once).
+    /// It must hash to the previously published `commitment`.
+    pub fn commit_decentralization_schedule(
+        env: Env,
+        admin: Address,
+        deployed_at: u64,
+        guardians: Vec<Address>,
+        dao: Address,
+        commitment: BytesN<32>,
+    ) -> Result<(), ContractError> {
+        admin.require_auth();
+        crate::admin::require_admin(&env, &admin)?;
+        Decentralization::commit(&env, deployed_at, guardians, dao, commitment)
+    }
+
+    /// Hash a candidate schedule the same way `commit_decentralization_schedule` does
+    pub fn compute_schedule_commitment(
+        env: Env,
+        deployed_at: u64,
+        guardians: Vec<Address>,
+        dao: Address,
+    ) -> BytesN<32> {
+        Decentralization::compute_commitment(&env, deployed_at, &guardians, &dao)
+    }
+
+    /// Check the committed schedule against a published hash
+    pub fn verify_schedule_commitment(env: Env, published: BytesN<32>) -> bool {
+        Decentralization::verify_commitment(&env, published)
+    }
+
+    pub fn get_decentralization_schedule(env: Env) -> Option<DecentralizationSchedule> {
+        Decentralization::get_schedule(&env)
+    }
+
+    /// Phase implied by the ledger clock
+    pub fn get_governance_phase(env: Env) -> GovernancePhase {
+        Decentralization::current_phase(&env)
+    }
+
+    /// Record the current phase on chain if it has advanced
+    pub fn sync_governance_phase(env: Env) -> GovernancePhase {
+        Decentralization::sync_phase(&env)
+    }
+
+    /// Guardian approval for the admin's next call during the MultiSig phase;
+    /// returns the approval count
+    pub fn approve_admin_call(env: Env, guardian: Address) -> Result<u32, ContractError> {
+        Decentralization::approve_admin_call(&env, guardian)
+    }
+
+    pub fn get_admin_call_approval(env: Env) -> Option<AdminCallApproval> {
+        Decentralization::live_admin_call(&env)
+    }
+
+    // ────────────────────────────────────────────────────────────────────────
+    // Flash Loan Module
+    // ────────────────────────────────────────────────────────────────────────
+
+    /// Borrow one or more assets across `PoolRegistry` pools within a single
+    /// transaction. `receiver` must authorize the call, implement
+    /// `FlashLoanReceiver` and approve this contract for every leg plus its
+    /// fee before `on_flash_loan` returns.
+    /// Returns the fee charged on each leg.
+    pub fn flash_loan(
+        env: Env,
+        initiator: Address,
+        receiver: Address,
+        legs: Vec<FlashLoanLeg>,
+        data: soroban_sdk::Bytes,
+    ) -> Result<Vec<i128>, ContractError> {
+        require_not_paused(&env)?;
+        require_operation_allowed(&env, OperationType::Swap, &initiator)?;
+        Denylist::screen(&env, symbol_short!("flash"), &[&receiver])?;
+        FlashLoanManager::flash_loan(&env, initiator, receiver, legs, data)
+    }
+
+    /// Register the SEP-41 token contract that flash loans of `asset` are
+    /// paid out of. Admin only.
+    pub fn set_flash_loan_token(
+        env: Env,
+        admin: Address,
+        asset: Symbol,
+        token: Address,
+    ) -> Result<(), ContractError> {
+        FlashLoanManager::set_token(&env, admin, asset, token)
+    }
+
+    pub fn get_flash_loan_token(env: Env, asset: Symbol) -> Option<Address> {
+        FlashLoanManager::token_for(&env, &asset).ok()
+    }
+
+    /// Fee charged on a flash-loan leg of `amount`.
+    pub fn get_flash_loan_fee(_env: Env, amount: i128) -> Result<i128, ContractError> {
+        FlashLoanManager::calculate_fee(amount)
+    }
+
+    // ────────────────────────────────────────────────────────────────────────
+    // Margin Trading
+    // ────────────────────────────────────────────────────────────────────────
+
+    /// Open a lending reserve that margin traders can borrow `asset` from,
+    /// at a simple annual rate of `borrow_rate_bps`.
+    pub fn create_lending_reserve(
+        env: Env,
+        admin: Address,
+        asset: Symbol,
+        borrow_rate_bps: u32,
+    ) -> Result<(), ContractError> {
+        admin.require_auth();
+        crate::admin::require_admin(&env, &admin)?;
+        MarginManager::create_reserve(&env, asset, borrow_rate_bps)
+    }
+
+    pub fn set_margin_config(
+        env: Env,
+        admin: Address,
+        config: MarginConfig,
+    ) -> Result<(), ContractError> {
+        admin.require_auth();
+        crate::admin::require_admin(&env, &admin)?;
+        MarginManager::set_config(&env, &config)
+    }
+
+    pub fn get_margin_config(env: Env) -> MarginConfig {
+        MarginManager::get_config(&env)
+    }
+
+    /// Supply `amount` of `asset` from the caller's balance to its lending
+    /// reserve. Returns the reserve shares minted.
+    pub fn supply_reserve(
+        env: Env,
+        provider: Address,
+        asset: Symbol,
+        amount: i128,
+    ) -> Result<i128, ContractError> {
+        require_not_paused(&env)?;
+        require_operation_allowed(&env, OperationType::LpDeposit, &provider)?;
+        provider.require_auth();
+        require_verified_user(&env, &provider)?;
+        MarginManager::supply(&env, &provider, asset, amount)
+    }
+
+    /// Redeem reserve shares for their share of the reserve's cash and
+    /// accrued interest. Returns the amount credited.
+    pub fn withdraw_reserve(
+        env: Env,
+        provider: Address,
+        asset: Symbol,
+        shares: i128,
+    ) -> Result<i128, ContractError> {
+        require_not_paused(&env)?;
+        require_operation_allowed(&env, OperationType::LpWithdraw, &provider)?;
+        provider.require_auth();
+        require_reducing_user(&env, &provider)?;
+        MarginManager::withdraw(&env, &provider, asset, shares)
+    }
+
+    /// Open an isolated-margin position in `pool_id`: post `collateral` of
+    /// `asset_in`, borrow the rest of `leverage_bps` from its reserve and buy
+    /// the pool's other token. Returns the position id.
+    pub fn open_margin_position(
+        env: Env,
+        user: Address,
+        pool_id: u64,
+        asset_in: Symbol,
+        collateral: i128,
+        leverage_bps: u32,
+        min_out: i128,
+    ) -> Result<u64, ContractError> {
+        require_not_paused(&env)?;
+        require_operation_allowed(&env, OperationType::Swap, &user)?;
+        user.require_auth();
+        require_verified_user_for(&env, &user, KYCFeature::Margin)?;
+        MarginManager::open(
+            &env,
+            &user,
+            pool_id,
+            asset_in,
+            collateral,
+            leverage_bps,
+            min_out,
+        )
+    }
+
+    /// Pay down a position's debt from the owner's balance. Returns the
+    /// debt still outstanding.
+    pub fn repay_margin_debt(
+        env: Env,
+        user: Address,
+        position_id: u64,
+        amount: i128,
+    ) -> Result<i128, ContractError> {
+        require_authenticated_reducing_user(&env, &user)?;
+        MarginManager::repay(&env, &user, position_id, amount)
+    }
+
+    /// Sell a position, repay its debt and credit the remainder to the
+    /// owner. Returns the amount credited.
+    pub fn close_margin_position(
+        env: Env,
+        user: Address,
+        position_id: u64,
+        min_out: i128,
+    ) -> Result<i128, ContractError> {
+        require_not_paused(&env)?;
+        require_operation_allowed(&env, OperationType::LpWithdraw, &user)?;
+        user.require_auth();
+        MarginManager::close(&env, &user, position_id, min_out)
+    }
+
+    /// Keeper entry point: liquidate a position below maintenance margin.
+    /// Returns the liquidator's share of the penalty.
+    pub fn liquidate_margin_position(
+        env: Env,
+        liquidator: Address,
+        position_id: u64,
+    ) -> Result<i128, ContractError> {
+        liquidator.require_auth();
+        MarginManager::liquidate(&env, &liquidator, position_id)
+    }
+
+    pub fn get_margin_position(env: Env, position_id: u64) -> Option<MarginPosition> {
+        MarginManager::get_position(&env, position_id)
+    }
+
+    pub fn get_margin_health(env: Env, position_id: u64) -> Result<MarginHealth, ContractError> {
+        MarginManager::health(&env, position_id)
+    }
+
+    pub fn get_lending_reserve(env: Env, asset: Symbol) -> Option<LendingReserve> {
+        MarginManager::get_reserve(&env, &asset)
+    }
+
+    pub fn get_user_margin_positions(env: Env, user: Address) -> Vec<u64> {
+        MarginManager::get_user_positions(&env, &user)
+    }
+
+    // ────────────────────────────────────────────────────────────────────────
+    // Insurance Fund
+    // ────────────────────────────────────────────────────────────────────────
+
+    /// Record a loss from an exploit, owed to `claimant`, for governance to
+    /// cover with a `CoverShortfall` proposal. Returns the shortfall id.
+    pub fn record_exploit_loss(
+        env: Env,
+        admin: Address,
+        asset: Symbol,
+        amount: i128,
+        claimant: Address,
+    ) -> Result<u64, ContractError> {
+        admin.require_auth();
+        crate::admin::require_admin(&env, &admin)?;
+        if amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        Ok(InsuranceFund::record_shortfall(
+            &env,
+            asset,
+            amount,
+            LossSource::Exploit(claimant),
+        ))
+    }
+
+    pub fn get_insurance_fund(env: Env, asset: Symbol) -> InsuranceAccount {
+        InsuranceFund::get_account(&env, &asset)
+    }
+
+    /// Fund balance over unsettled shortfalls and open margin debt in
+    /// `asset`, in basis points. `None` when there is nothing to cover.
+    pub fn get_insurance_coverage_ratio(env: Env, asset: Symbol) -> Option<i128> {
+        InsuranceFund::coverage_ratio_bps(&env, &asset)
+    }
+
+    pub fn get_insurance_shortfall(env: Env, shortfall_id: u64) -> Option<Shortfall> {
+        InsuranceFund::get_shortfall(&env, shortfall_id)
+    }
+
+    // ────────────────────────────────────────────────────────────────────────
+    // Advanced Order Types (Limit & Stop-Loss)
+    // ────────────────────────────────────────────────────────────────────────
+
+    /// Place a limit order that executes when price reaches limit_price or better
+    pub fn place_limit_order(
+        env: Env,
+        token_in: Symbol,
+        token_out: Symbol,
+        amount_in: i128,
+        limit_price: u128,
+        expires_at: Option<u64>,
+        user: Address,
+    ) -> Result<u64, ContractError> {
+        require_authenticated_verified_user_for(&env, &user, KYCFeature::Swap)?;
+        orders::OrderManager::place_limit_order(
+            &env,
+            user,
+            token_in,
+            token_out,
+            amount_in,
+            limit_price,
+            expires_at,
+        )
+    }
+
+    /// Place a stop-loss order that executes when price reaches trigger_price
+    pub fn place_stop_loss(
+        env: Env,
+        token_in: Symbol,
+        token_out: Symbol,
+        amount_in: i128,
+        trigger_price: u128,
+        expires_at: Option<u64>,
+        user: Address,
+    ) -> Result<u64, ContractError> {
+        require_authenticated_verified_user_for(&env, &user, KYCFeature::Swap)?;
+        orders::OrderManager::place_stop_loss(
+            &env,
+            user,
+            token_in,
+            token_out,
+            amount_in,
+            trigger_price,
+            expires_at,
+        )
+    }
+
+    /// Cancel an existing order
+    /// Place a recurring (DCA) order that executes on a fixed schedule
+    pub fn place_recurring_order(
+        env: Env,
+        token_in: Symbol,
+        token_out: Symbol,
+        amount_in: i128,
+        interval_secs: u64,
+        occurrences: u64,
+        expires_at: Option<u64>,
+        user: Address,
+    ) -> Result<u64, ContractError> {
+        require_authenticated_verified_user_for(&env, &user, KYCFeature::Swap)?;
+        orders::OrderManager::place_recurring_order(
+            &env,
+            user,
+            token_in,
+            token_out,
+            amount_in,
+            interval_secs,
+            occurrences,
+            expires_at,
+        )
+    }
+
+    /// Execute all due recurring orders
+    pub fn execute_due_orders(env: Env) -> Result<Vec<u64>, ContractError> {
+        orders::OrderManager::execute_due_orders(&env)
+    }
+
+    /// Cancel an existing order
+    pub fn cancel_order(env: Env, order_id: u64, user: Address) -> Result<(), ContractError> {
+        require_authenticated_reducing_user(&env, &user)?;
+        orders::OrderManager::cancel_order(&env, order_id, user)
+    }
+
+    /// Get order details
+    pub fn get_order(env: Env, order_id: u64) -> Result<orders::Order, ContractError> {
+        orders::OrderManager::get_order(&env, order_id)
+    }
+
+    /// Get all active orders for a user
+    pub fn get_user_orders(env: Env, user: Address) -> Vec<orders::Order> {
+        orders::OrderManager::get_user_orders(&env, user)
+    }
+
+    // ────────────────────────────────────────────────────────────────────────
+    // Portfolio Analytics Dashboard
+    // ────────────────────────────────────────────────────────────────────────
+
+    /// Get comprehensive analytics summary for a user
+    /// Includes PnL, win rate, Sharpe ratio, and other metrics
+    pub fn get_analytics_summary(env: Env, user: Address) -> portfolio::AnalyticsSummary {
+        let portfolio: Portfolio = env
+            .storage()
+            .instance()
+            .get(&())
+            .unwrap_or_else(|| Portfolio::new(&env));
+        portfolio.get_analytics_summary(&env, user)
+    }
+
+    /// Get total claimed bonuses for a user
+    pub fn get_user_claimed_bonuses(env: Env, user: Address) -> i128 {
+        StakingBonusManager::get_user_claimed_bonuses(&env, user)
+    }
+
+    /// Get pending claimable bonuses for a user (after 30-day holding period)
+    pub fn get_user_pending_bonuses(env: Env, user: Address) -> i128 {
+        StakingBonusManager::get_user_pending_bonuses(&env, user)
+    }
+
+    /// Get global staking statistics (transparency)
+    /// Returns (total_staked, total_distributed, distribution_records_count)
+    pub fn get_staking_statistics(env: Env) -> (i128, i128, u64) {
+        StakingBonusManager::get_statistics(&env)
+    }
+
+    /// Get distribution history for auditing
+    pub fn get_distribution_history(env: Env) -> Vec<DistributionRecord> {
+        StakingBonusManager::get_distribution_history(&env)
+    }
+
+    /// Execute periodic bonus distribution (admin only typically)
+    pub fn execute_staking_distribution(env: Env) -> Result<DistributionRecord, ContractError> {
+        StakingBonusManager::execute_distribution(&env)
+    }
+
+    // ────────────────────────────────────────────────────────────────────────
+    // KYC Verification System
+    // ────────────────────────────────────────────────────────────────────────
+
+    /// Add a KYC operator (admin only)
+    pub fn kyc_add_operator(
+        env: Env,
+        admin: Address,
+        operator: Address,
+    ) -> Result<(), ContractError> {
+        kyc::KYCSystem::add_operator(&env, &admin, operator)
+    }
+
+    /// Remove a KYC operator (admin only)
+    pub fn kyc_remove_operator(
+        env: Env,
+        admin: Address,
+        operator: Address,
     ) -> Result<(), ContractError> {
         kyc::KYCSystem::remove_operator(&env, &admin, operator)
     }
@@ -2133,6 +3236,22 @@ impl CounterContract {
         kyc::KYCSystem::resubmit_kyc(&env, &user)
     }
 
+    /// Start re-verification after a verification has lapsed (user-initiated)
+    pub fn kyc_renew(env: Env, user: Address) -> Result<(), ContractError> {
+        kyc::KYCSystem::renew_kyc(&env, &user)
+    }
+
+    /// Store the downgrade of a lapsed verification to `Expired`; returns the
+    /// user's status
+    pub fn kyc_sync_expiry(env: Env, user: Address) -> KYCStatus {
+        kyc::KYCSystem::sync_expiry(&env, &user)
+    }
+
+    /// Check if a user's verification has lapsed but is still in the grace window
+    pub fn kyc_in_grace_period(env: Env, user: Address) -> bool {
+        kyc::KYCSystem::in_grace_period(&env, &user)
+    }
+
     /// Update KYC status (operator only)
     pub fn kyc_update_status(
         env: Env,
@@ -2182,6 +3301,34 @@ impl CounterContract {
         kyc::KYCSystem::get_pending_expiry_duration(&env)
     }
 
+    /// Set how long a verification holds before re-verification (admin only)
+    pub fn kyc_set_verification_validity(
+        env: Env,
+        admin: Address,
+        duration: u64,
+    ) -> Result<(), ContractError> {
+        kyc::KYCSystem::set_verification_validity(&env, &admin, duration)
+    }
+
+    /// Get verification validity period
+    pub fn kyc_get_verification_validity(env: Env) -> u64 {
+        kyc::KYCSystem::get_verification_validity(&env)
+    }
+
+    /// Set the grace window after a verification lapses (admin only)
+    pub fn kyc_set_grace_period(
+        env: Env,
+        admin: Address,
+        duration: u64,
+    ) -> Result<(), ContractError> {
+        kyc::KYCSystem::set_grace_period(&env, &admin, duration)
+    }
+
+    /// Get the grace window after a verification lapses
+    pub fn kyc_get_grace_period(env: Env) -> u64 {
+        kyc::KYCSystem::get_grace_period(&env)
+    }
+
     /// Propose governance override for terminal state change (admin only)
     pub fn kyc_propose_override(
         env: Env,
@@ -2207,6 +3354,181 @@ impl CounterContract {
         kyc::KYCSystem::get_override(&env, override_id)
     }
 
+    /// Register the ed25519 key an operator signs attestations with (operator only)
+    pub fn kyc_register_operator_key(
+        env: Env,
+        operator: Address,
+        public_key: BytesN<32>,
+    ) -> Result<(), ContractError> {
+        kyc::KYCSystem::register_operator_key(&env, &operator, public_key)
+    }
+
+    /// Get an operator's attestation signing key
+    pub fn kyc_get_operator_key(env: Env, operator: Address) -> Option<BytesN<32>> {
+        kyc::KYCSystem::get_operator_key(&env, &operator)
+    }
+
+    /// Verify a user from an operator-signed attestation
+    pub fn kyc_submit_attestation(
+        env: Env,
+        operator: Address,
+        attestation: KYCAttestation,
+        signature: BytesN<64>,
+    ) -> Result<(), ContractError> {
+        kyc::KYCSystem::submit_attestation(&env, &operator, attestation, signature)
+    }
+
+    /// Get a submitted attestation by credential hash
+    pub fn kyc_get_attestation(env: Env, credential_hash: BytesN<32>) -> Option<AttestationRecord> {
+        kyc::KYCSystem::get_attestation(&env, &credential_hash)
+    }
+
+    /// Get the attestation a user is currently verified by
+    pub fn kyc_get_user_attestation(env: Env, user: Address) -> Option<AttestationRecord> {
+        kyc::KYCSystem::get_user_attestation(&env, &user)
+    }
+
+    /// Get a user's verification level; 0 if not verified
+    pub fn kyc_verification_level(env: Env, user: Address) -> u32 {
+        kyc::KYCSystem::verification_level(&env, &user).unwrap_or(0)
+    }
+
+    /// Put a credential on the revocation list (operator only)
+    pub fn kyc_revoke_credential(
+        env: Env,
+        operator: Address,
+        credential_hash: BytesN<32>,
+        reason: Symbol,
+    ) -> Result<(), ContractError> {
+        kyc::KYCSystem::revoke_credential(&env, &operator, credential_hash, reason)
+    }
+
+    /// Get the revocation list entry for a credential
+    pub fn kyc_get_revocation(
+        env: Env,
+        credential_hash: BytesN<32>,
+    ) -> Option<CredentialRevocation> {
+        kyc::KYCSystem::get_revocation(&env, &credential_hash)
+    }
+
+    /// Set the level and jurisdictions a feature requires (admin only)
+    pub fn kyc_set_feature_requirement(
+        env: Env,
+        admin: Address,
+        feature: KYCFeature,
+        requirement: KYCRequirement,
+    ) -> Result<(), ContractError> {
+        kyc::KYCSystem::set_feature_requirement(&env, &admin, feature, requirement)
+    }
+
+    /// Get the level and jurisdictions a feature requires
+    pub fn kyc_get_feature_requirement(env: Env, feature: KYCFeature) -> KYCRequirement {
+        kyc::KYCSystem::get_feature_requirement(&env, feature)
+    }
+
+    /// Record the jurisdiction of a user verified by hand (operator only)
+    pub fn kyc_set_jurisdiction(
+        env: Env,
+        operator: Address,
+        user: Address,
+        jurisdiction: Symbol,
+    ) -> Result<(), ContractError> {
+        kyc::KYCSystem::set_jurisdiction(&env, &operator, &user, jurisdiction)
+    }
+
+    /// Check whether a feature is open in the user's jurisdiction
+    pub fn kyc_is_feature_allowed(env: Env, user: Address, feature: KYCFeature) -> bool {
+        kyc::KYCSystem::require_feature_allowed(&env, &user, feature).is_ok()
+    }
+
+    // ── Sanctions Denylist ──────────────────────────────────────────────────
+
+    /// Set the compliance officer who maintains the denylist (admin only)
+    pub fn set_compliance_officer(
+        env: Env,
+        admin: Address,
+        officer: Address,
+    ) -> Result<(), ContractError> {
+        admin.require_auth();
+        crate::admin::require_admin(&env, &admin)?;
+        Denylist::set_officer(&env, &officer);
+        Ok(())
+    }
+
+    /// Get the compliance officer
+    pub fn get_compliance_officer(env: Env) -> Option<Address> {
+        Denylist::officer(&env)
+    }
+
+    /// Anchor the Merkle root of a newly published screening list (officer
+    /// only). Returns the new root version.
+    pub fn anchor_denylist_root(
+        env: Env,
+        officer: Address,
+        root: BytesN<32>,
+        entry_count: u32,
+    ) -> Result<u32, ContractError> {
+        Ok(Denylist::anchor_root(&env, &officer, root, entry_count)?)
+    }
+
+    /// Deny a batch of addresses, each proven against the anchored root
+    /// (officer only)
+    pub fn denylist_addresses(
+        env: Env,
+        officer: Address,
+        entries: Vec<Address>,
+        proofs: Vec<Vec<BytesN<32>>>,
+    ) -> Result<(), ContractError> {
+        Ok(Denylist::list(&env, &officer, entries, proofs)?)
+    }
+
+    /// Remove a batch of addresses from the denylist (officer only)
+    pub fn delist_addresses(
+        env: Env,
+        officer: Address,
+        entries: Vec<Address>,
+    ) -> Result<(), ContractError> {
+        Ok(Denylist::delist(&env, &officer, entries)?)
+    }
+
+    /// Check whether an address is on the denylist
+    pub fn is_denied(env: Env, address: Address) -> bool {
+        Denylist::is_denied(&env, &address)
+    }
+
+    /// Get the denylist entry for an address
+    pub fn get_denylist_entry(env: Env, address: Address) -> Option<DenylistEntry> {
+        Denylist::entry(&env, &address)
+    }
+
+    /// Get the currently anchored denylist root
+    pub fn get_denylist_root(env: Env) -> Option<DenylistRoot> {
+        Denylist::root(&env)
+    }
+
+    /// Screen a batch of addresses without rejecting on a match, e.g.
+    /// before submitting a trade. Matches are persisted and published as
+    /// `("deny", "match")` events; returns the listed addresses.
+    pub fn screen_addresses(
+        env: Env,
+        caller: Address,
+        context: Symbol,
+        addresses: Vec<Address>,
+    ) -> Result<Vec<Address>, ContractError> {
+        caller.require_auth();
+        Ok(Denylist::record_matches(&env, context, &addresses)?)
+    }
+
+    /// Get the screening matches recorded for an address
+    pub fn get_denylist_matches(env: Env, address: Address) -> Option<DenylistMatches> {
+        Denylist::matches(&env, &address)
+    }
+
+    /// Get the total number of screening matches recorded
+    pub fn get_denylist_match_total(env: Env) -> u64 {
+        Denylist::match_total(&env)
+    }
+
     // ── Referral System ─────────────────────────────────────────────────────
 
     /// Register a referral relationship
@@ -2229,8 +3551,10 @@ impl CounterContract {
     }
 
     /// Withdraw accumulated commission
-    pub fn withdraw_commission(env: Env, user: Address) -> i128 {
-        referral_system::withdraw_commission(&env, user)
+    pub fn withdraw_commission(env: Env, user: Address) -> Result<i128, ContractError> {
+        kyc::KYCSystem::require_feature_allowed(&env, &user, KYCFeature::ReferralPayout)?;
+        Denylist::screen(&env, symbol_short!("referral"), &[&user])?;
+        Ok(referral_system::withdraw_commission(&env, user))
     }
 
     // ── Tier-Based Fee Discounts ───────────────────────────────────────────
@@ -2268,6 +3592,7 @@ impl CounterContract {
     /// Claim simulated tokens from the faucet for a given asset.
     /// Enforces a per-user, per-asset cooldown set via `set_faucet_config`.
     pub fn claim_faucet(env: Env, user: Address, asset: Symbol) -> Result<i128, SwapTradeError> {
+        kyc::KYCSystem::require_feature_allowed(&env, &user, KYCFeature::Faucet)?;
         faucet::claim_faucet(&env, &user, asset)
     }
 
@@ -2300,6 +3625,7 @@ impl CounterContract {
         description: Symbol,
         voting_period: u64,
     ) -> Result<u64, SwapTradeError> {
+        require_operation_allowed(&env, OperationType::ProposalCreation, &proposer)?;
         governance_system::GovernanceSystem::create_proposal(
             &env,
             &proposer,
@@ -2336,8 +3662,120 @@ impl CounterContract {
         governance_system::GovernanceSystem::get_proposal(&env, proposal_id)
     }
 
+    /// Propose an action to be voted under a chosen voting mode
+    pub fn create_governance_action(
+        env: Env,
+        proposer: Address,
+        action: governance_system::ProposalAction,
+        mode: VotingMode,
+    ) -> Result<u64, SwapTradeError> {
+        require_operation_allowed(&env, OperationType::ProposalCreation, &proposer)?;
+        governance_system::create_proposal_with_mode(&env, proposer, action, mode)
+    }
+
+    /// Start the timelock on a proposal that passes under its voting mode
+    pub fn queue_governance_proposal(env: Env, proposal_id: u64) -> Result<u64, SwapTradeError> {
+        governance_system::queue_proposal(&env, proposal_id)
+    }
+
+    /// Current weighted tally of a proposal
+    pub fn get_governance_tally(env: Env, proposal_id: u64) -> Result<Tally, SwapTradeError> {
+        governance_system::get_tally(&env, proposal_id)
+    }
+
+    /// Delegate a share (in bps) of vote-escrow power on a topic
+    pub fn delegate_votes(
+        env: Env,
+        delegator: Address,
+        delegate: Address,
+        topic: DelegationTopic,
+        share_bps: u32,
+    ) -> Result<(), SwapTradeError> {
+        governance::delegation::delegate_vote(&env, delegator, delegate, topic, share_bps)
+    }
+
+    /// Withdraw a delegation made on a topic
+    pub fn revoke_vote_delegation(
+        env: Env,
+        delegator: Address,
+        delegate: Address,
+        topic: DelegationTopic,
+    ) -> Result<(), SwapTradeError> {
+        governance::delegation::revoke_delegation(&env, delegator, delegate, topic)
+    }
+
+    pub fn get_vote_delegations(env: Env, delegator: Address) -> Vec<Delegation> {
+        governance::delegation::get_delegations(&env, &delegator)
+    }
+
+    /// Power `voter` would vote with on a topic, including delegations, now
+    /// or at a past timestamp
+    pub fn get_delegated_voting_power(
+        env: Env,
+        voter: Address,
+        topic: DelegationTopic,
+        at: Option<u64>,
+    ) -> i128 {
+        let ts = at.unwrap_or_else(|| env.ledger().timestamp());
+        governance::delegation::voting_power_at(&env, &voter, topic, ts)
+    }
+
+    // ── Oracle Adapter ──────────────────────────────────────────────────────
+
+    /// Start a TWAP oracle feed for `pair` (admin only). Risk valuation reads
+    /// `(asset, USD)` pairs.
+    pub fn init_oracle_feed(
+        env: Env,
+        admin: Address,
+        pair: (Symbol, Symbol),
+        initial_price: u128,
+    ) -> Result<(), ContractError> {
+        admin.require_auth();
+        crate::admin::require_admin(&env, &admin)?;
+        if initial_price == 0 {
+            return Err(ContractError::InvalidPrice);
+        }
+        OracleAdapter::initialize_oracle(&env, pair, OracleProvider::Manual, initial_price)
+    }
+
+    /// Record a new oracle round for `pair` (admin only).
+    pub fn submit_oracle_price(
+        env: Env,
+        admin: Address,
+        pair: (Symbol, Symbol),
+        price: u128,
+    ) -> Result<(), ContractError> {
+        admin.require_auth();
+        crate::admin::require_admin(&env, &admin)?;
+        OracleAdapter::update_price(&env, pair, price)
+    }
+
+    pub fn get_oracle_price(env: Env, pair: (Symbol, Symbol)) -> Result<u128, ContractError> {
+        OracleAdapter::get_price(&env, pair)
+    }
+
     // ── Risk Management ─────────────────────────────────────────────────────
 
+    /// Oracle-priced risk metrics for a user, including one-day 95% VaR
+    pub fn get_risk_metrics(env: Env, user: Address) -> risk_management::RiskMetrics {
+        let portfolio: Portfolio = env
+            .storage()
+            .instance()
+            .get(&())
+            .unwrap_or_else(|| Portfolio::new(&env));
+        risk_management::PortfolioRisk::calculate_risk_metrics(&env, &portfolio, &user)
+    }
+
+    /// USD value of every balance, LP share and stake a user holds
+    pub fn get_portfolio_valuation(env: Env, user: Address) -> PortfolioValuation {
+        let portfolio: Portfolio = env
+            .storage()
+            .instance()
+            .get(&())
+            .unwrap_or_else(|| Portfolio::new(&env));
+        AssetValuation::value_portfolio(&env, &portfolio, &user)
+    }
+
     /// Check if concentration limit is exceeded for a user
     pub fn check_concentration_limit(env: Env, user: Address) -> bool {
         let portfolio: Portfolio = env
@@ -2348,11 +3786,99 @@ impl CounterContract {
         risk_management::ConcentrationRisk::check_concentration_limit(&env, &portfolio, &user)
     }
 
-    /// Get circuit breaker status
+    /// Get circuit breaker status, including the rule behind the latest trip
     pub fn get_circuit_breaker_status(env: Env) -> risk_management::CircuitBreakerState {
         risk_management::CircuitBreaker::get_circuit_breaker_state(&env)
     }
 
+    /// Get the status of an asset's own circuit breaker
+    pub fn get_asset_circuit_breaker_status(
+        env: Env,
+        asset: Symbol,
+    ) -> risk_management::CircuitBreakerState {
+        risk_management::CircuitBreaker::get_state(&env, &BreakerScope::Asset(asset))
+    }
+
+    /// Evaluate the price-move rule for an asset. Callable by anyone;
+    /// returns whether the asset's breaker tripped.
+    pub fn check_price_breaker(env: Env, asset: Symbol) -> bool {
+        risk_management::CircuitBreaker::evaluate(&env, &risk_management::PriceMove(asset))
+    }
+
+    /// Evaluate the oracle-divergence rule for an asset. Callable by anyone;
+    /// returns whether the asset's breaker tripped.
+    pub fn check_oracle_breaker(env: Env, asset: Symbol) -> bool {
+        risk_management::CircuitBreaker::evaluate(&env, &risk_management::OracleDivergence(asset))
+    }
+
+    /// Evaluate contract invariants. Callable by anyone; returns whether the
+    /// global breaker tripped.
+    pub fn check_invariant_breaker(env: Env) -> bool {
+        let portfolio: Portfolio = env
+            .storage()
+            .instance()
+            .get(&())
+            .unwrap_or_else(|| Portfolio::new(&env));
+        risk_management::CircuitBreaker::evaluate(
+            &env,
+            &risk_management::InvariantFailure(&portfolio),
+        )
+    }
+
+    /// Trip a circuit breaker by hand (admin only).
+    pub fn trip_circuit_breaker(
+        env: Env,
+        admin: Address,
+        scope: BreakerScope,
+    ) -> Result<(), SwapTradeError> {
+        admin.require_auth();
+        crate::admin::require_admin(&env, &admin)?;
+        risk_management::CircuitBreaker::trip(
+            &env,
+            scope,
+            TripRule::Manual,
+            risk_management::Breach {
+                observed: 0,
+                threshold: 0,
+            },
+        );
+        Ok(())
+    }
+
+    /// Close an asset's circuit breaker (admin only).
+    pub fn reset_asset_circuit_breaker(
+        env: Env,
+        admin: Address,
+        asset: Symbol,
+    ) -> Result<(), SwapTradeError> {
+        admin.require_auth();
+        crate::admin::require_admin(&env, &admin)?;
+        risk_management::CircuitBreaker::reset(&env, BreakerScope::Asset(asset));
+        Ok(())
+    }
+
+    /// Set the breaker cooldown and oracle-divergence threshold (admin only).
+    pub fn set_circuit_breaker_config(
+        env: Env,
+        admin: Address,
+        cooldown_secs: u64,
+        oracle_divergence_bps: u32,
+    ) -> Result<(), SwapTradeError> {
+        admin.require_auth();
+        crate::admin::require_admin(&env, &admin)?;
+        if oracle_divergence_bps == 0 || oracle_divergence_bps > 10_000 {
+            return Err(SwapTradeError::InvalidAmount);
+        }
+        risk_management::CircuitBreaker::set_config(
+            &env,
+            &BreakerConfig {
+                cooldown_secs,
+                oracle_divergence_bps,
+            },
+        );
+        Ok(())
+    }
+
     /// Check if a position increase would exceed limits
     pub fn check_risk_limits(
         env: Env,
@@ -2380,6 +3906,41 @@ impl CounterContract {
         .is_err()
     }
 
+    /// Per-asset position limit for a user at their current tier
+    pub fn get_position_limit(env: Env, user: Address, asset: Symbol) -> i128 {
+        let portfolio: Portfolio = env
+            .storage()
+            .instance()
+            .get(&())
+            .unwrap_or_else(|| Portfolio::new(&env));
+        let asset_type = if asset == symbol_short!("XLM") {
+            Asset::XLM
+        } else {
+            Asset::Custom(asset)
+        };
+        let config = risk_management::PositionLimits::get_risk_config(&env);
+        let tier = portfolio.get_user_tier(&env, user);
+        risk_management::PositionLimits::position_limit(&env, &config, &asset_type, &tier)
+    }
+
+    /// Total held of an asset across all users and its cap, if one is set
+    pub fn get_open_interest(env: Env, asset: Symbol) -> (i128, Option<i128>) {
+        let portfolio: Portfolio = env
+            .storage()
+            .instance()
+            .get(&())
+            .unwrap_or_else(|| Portfolio::new(&env));
+        let asset_type = if asset == symbol_short!("XLM") {
+            Asset::XLM
+        } else {
+            Asset::Custom(asset)
+        };
+        (
+            portfolio.open_interest(&asset_type),
+            risk_management::PositionLimits::open_interest_cap(&env, &asset_type),
+        )
+    }
+
     // ────────────────────────────────────────────────────────────────────────
     // Dynamic Gamification & Rewards System
     // ────────────────────────────────────────────────────────────────────────
@@ -2587,6 +4148,7 @@ impl CounterContract {
     }
 }
 
+#[cfg(all(test, feature = "experimental"))]
 mod governance_tests;
 #[cfg(test)]
 mod pnl_tests;
@@ -2594,3 +4156,17 @@ mod pnl_tests;
 mod migration_tests;
 #[cfg(test)]
 mod risk_management_tests;
+#[cfg(test)]
+mod risk_valuation_tests;
+#[cfg(test)]
+mod position_limit_tests;
+#[cfg(test)]
+mod circuit_breaker_tests;
+#[cfg(test)]
+mod margin_tests;
+#[cfg(test)]
+mod insurance_fund_tests;
+#[cfg(test)]
+mod kyc_attestation_tests;
+#[cfg(test)]
+mod denylist_tests;
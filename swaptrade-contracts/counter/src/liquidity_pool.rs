@@ -231,6 +231,17 @@ impl PoolRegistry {
         treasury: Address,
     ) -> Result<(i128, i128), ContractError> {
         treasury.require_auth();
+        self.take_treasury_fees(env, pool_id, treasury)
+    }
+
+    /// Zero a pool's accumulated treasury fees and report them as paid to
+    /// `treasury`. Callers are responsible for authorization.
+    pub fn take_treasury_fees(
+        &mut self,
+        env: &Env,
+        pool_id: u64,
+        treasury: Address,
+    ) -> Result<(i128, i128), ContractError> {
         let mut pool = self
             .pools
             .get(pool_id)
@@ -264,7 +275,17 @@ impl PoolRegistry {
     ) -> Result<(), ContractError> {
         admin.require_auth();
         crate::admin::require_admin(env, &admin)?;
+        self.set_fee_tier(env, pool_id, new_fee_tier)
+    }
 
+    /// Change a pool's fee tier without an admin check; used by governance
+    /// execution.
+    pub(crate) fn set_fee_tier(
+        &mut self,
+        env: &Env,
+        pool_id: u64,
+        new_fee_tier: u32,
+    ) -> Result<(), ContractError> {
         if ![1, 5, 30].contains(&new_fee_tier) {
             return Err(ContractError::InvalidAmount);
         }
@@ -331,6 +352,75 @@ impl PoolRegistry {
         Ok((amount_a, amount_b))
     }
 
+    /// Burn a provider's entire position in every pool for its pro-rata
+    /// reserves, skipping fee claims. Returns (pool_id, amount_a, amount_b)
+    /// for each pool the provider had shares in.
+    pub fn emergency_withdraw_all(
+        &mut self,
+        env: &Env,
+        provider: Address,
+    ) -> Result<Vec<(u64, i128, i128)>, ContractError> {
+        let mut withdrawn = Vec::new(env);
+        for pool_id in 1..self.next_pool_id {
+            let key = (pool_id, provider.clone());
+            let balance = self.lp_balances.get(key.clone()).unwrap_or(0);
+            if balance <= 0 {
+                continue;
+            }
+            let mut pool = match self.pools.get(pool_id) {
+                Some(pool) => pool,
+                None => continue,
+            };
+
+            let amount_a = ((balance as u128)
+                .checked_mul(pool.reserve_a as u128)
+                .ok_or(ContractError::AmountOverflow)?
+                / (pool.total_lp_tokens as u128)) as i128;
+            let amount_b = ((balance as u128)
+                .checked_mul(pool.reserve_b as u128)
+                .ok_or(ContractError::AmountOverflow)?
+                / (pool.total_lp_tokens as u128)) as i128;
+
+            pool.reserve_a -= amount_a;
+            pool.reserve_b -= amount_b;
+            pool.total_lp_tokens -= balance;
+            self.pools.set(pool_id, pool);
+            self.lp_balances.set(key, 0);
+            withdrawn.push_back((pool_id, amount_a, amount_b));
+        }
+        Ok(withdrawn)
+    }
+
+    /// Pro-rata reserves behind `provider`'s shares, as (pool_id, token,
+    /// amount) with one entry per side of each pool.
+    pub fn provider_underlying(&self, env: &Env, provider: &Address) -> Vec<(u64, Symbol, i128)> {
+        let mut underlying = Vec::new(env);
+        for pool_id in 1..self.next_pool_id {
+            let balance = self
+                .lp_balances
+                .get((pool_id, provider.clone()))
+                .unwrap_or(0);
+            if balance <= 0 {
+                continue;
+            }
+            let pool = match self.pools.get(pool_id) {
+                Some(pool) if pool.total_lp_tokens > 0 => pool,
+                _ => continue,
+            };
+            underlying.push_back((
+                pool_id,
+                pool.token_a.clone(),
+                balance * pool.reserve_a / pool.total_lp_tokens,
+            ));
+            underlying.push_back((
+                pool_id,
+                pool.token_b.clone(),
+                balance * pool.reserve_b / pool.total_lp_tokens,
+            ));
+        }
+        underlying
+    }
+
     pub fn swap(
         &mut self,
         env: &Env,
@@ -356,7 +446,7 @@ impl PoolRegistry {
         };
 
         // Calculate fee and amount after fee
-        let fee_amount = ((amount_in as u128) * (pool.fee_tier as u128) / 10000) as i128;
+        let fee_amount = Self::fee_for(&pool, amount_in);
         let amount_in_after_fee = amount_in - fee_amount;
 
         let amount_in_with_fee = amount_in_after_fee as u128;
@@ -408,6 +498,46 @@ impl PoolRegistry {
         Ok(amount_out)
     }
 
+    /// Fee a swap of `amount_in` through `pool_id` is charged.
+    pub fn swap_fee(&self, pool_id: u64, amount_in: i128) -> Result<i128, ContractError> {
+        let pool = self
+            .pools
+            .get(pool_id)
+            .ok_or(ContractError::LPPositionNotFound)?;
+        Ok(Self::fee_for(&pool, amount_in))
+    }
+
+    fn fee_for(pool: &LiquidityPool, amount_in: i128) -> i128 {
+        ((amount_in as u128) * (pool.fee_tier as u128) / 10000) as i128
+    }
+
+    /// Remove `amount` of `token` from a pool's accumulated fees once it has
+    /// been paid to the treasury directly.
+    pub fn settle_treasury_fee(
+        &mut self,
+        pool_id: u64,
+        token: &Symbol,
+        amount: i128,
+    ) -> Result<(), ContractError> {
+        let mut pool = self
+            .pools
+            .get(pool_id)
+            .ok_or(ContractError::LPPositionNotFound)?;
+        let fees = if *token == pool.token_a {
+            &mut pool.accumulated_fees_a
+        } else if *token == pool.token_b {
+            &mut pool.accumulated_fees_b
+        } else {
+            return Err(ContractError::InvalidTokenSymbol);
+        };
+        *fees = fees
+            .checked_sub(amount)
+            .filter(|left| *left >= 0)
+            .ok_or(ContractError::InsufficientBalance)?;
+        self.pools.set(pool_id, pool);
+        Ok(())
+    }
+
     pub fn find_best_route(
         &self,
         env: &Env,
@@ -536,6 +666,87 @@ impl PoolRegistry {
         (((amount_in as u128) * 10000) / (reserve_in as u128)).min(10000) as u32
     }
 
+    /// Reserve of `token` held by `pool_id`, i.e. the most that can be lent
+    /// out of the pool in a single flash loan.
+    pub fn available_liquidity(&self, pool_id: u64, token: &Symbol) -> Result<i128, ContractError> {
+        let pool = self
+            .pools
+            .get(pool_id)
+            .ok_or(ContractError::LPPositionNotFound)?;
+        if *token == pool.token_a {
+            Ok(pool.reserve_a)
+        } else if *token == pool.token_b {
+            Ok(pool.reserve_b)
+        } else {
+            Err(ContractError::InvalidTokenSymbol)
+        }
+    }
+
+    /// Credit fees earned outside of `swap` (e.g. flash-loan fees) to the
+    /// pool's LP fee accounting so they are paid out through `claim_fees`.
+    pub fn accrue_fees(
+        &mut self,
+        env: &Env,
+        pool_id: u64,
+        token: Symbol,
+        amount: i128,
+    ) -> Result<(), ContractError> {
+        if amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        let mut pool = self
+            .pools
+            .get(pool_id)
+            .ok_or(ContractError::LPPositionNotFound)?;
+        if token == pool.token_a {
+            pool.accumulated_fees_a = pool
+                .accumulated_fees_a
+                .checked_add(amount)
+                .ok_or(ContractError::AmountOverflow)?;
+        } else if token == pool.token_b {
+            pool.accumulated_fees_b = pool
+                .accumulated_fees_b
+                .checked_add(amount)
+                .ok_or(ContractError::AmountOverflow)?;
+        } else {
+            return Err(ContractError::InvalidTokenSymbol);
+        }
+        self.pools.set(pool_id, pool);
+
+        crate::events::fees_collected(env, token, amount, pool_id);
+        Ok(())
+    }
+
+    /// Move `delta` of `token` into (positive) or out of (negative) the
+    /// reserve of `pool_id`, e.g. while a flash loan is outstanding.
+    pub fn adjust_reserve(
+        &mut self,
+        pool_id: u64,
+        token: &Symbol,
+        delta: i128,
+    ) -> Result<(), ContractError> {
+        let mut pool = self
+            .pools
+            .get(pool_id)
+            .ok_or(ContractError::LPPositionNotFound)?;
+        let reserve = if *token == pool.token_a {
+            &mut pool.reserve_a
+        } else if *token == pool.token_b {
+            &mut pool.reserve_b
+        } else {
+            return Err(ContractError::InvalidTokenSymbol);
+        };
+        let updated = reserve
+            .checked_add(delta)
+            .ok_or(ContractError::AmountOverflow)?;
+        if updated < 0 {
+            return Err(ContractError::InsufficientBalance);
+        }
+        *reserve = updated;
+        self.pools.set(pool_id, pool);
+        Ok(())
+    }
+
     pub fn get_pool(&self, pool_id: u64) -> Option<LiquidityPool> {
         self.pools.get(pool_id)
     }
@@ -0,0 +1,711 @@
+//! Isolated-margin leveraged spot trading
+//!
+//! Liquidity providers supply an asset to its lending reserve and earn the
+//! interest borrowers pay. A trader posts collateral in one token of a
+//! `PoolRegistry` pool, borrows more of it from the reserve and swaps both
+//! into the pool's other token. Each position is isolated: it holds one asset
+//! and owes one debt, and its margin is measured only against itself.
+//!
+//! Positions are valued through the oracle adapter. A position whose margin
+//! falls below `maintenance_margin_bps` can be liquidated by any keeper. The
+//! sale must fetch the oracle value of the held asset, less
+//! `ParamKey::LiquidationSlippageBps`. Its proceeds repay the debt first;
+//! the keeper and the insurance fund split a penalty taken from what is
+//! left. Debt the proceeds cannot repay is written off against the reserve,
+//! so its suppliers share the loss until the insurance fund covers it.
+
+use soroban_sdk::{contracttype, symbol_short, Address, Env, Symbol, Vec};
+
+use crate::errors::ContractError;
+use crate::events;
+use crate::governance_params::{GovernanceParams, ParamKey};
+use crate::insurance_fund::{InsuranceFund, LossSource};
+use crate::portfolio::{Asset, Portfolio};
+use crate::risk_management::valuation::{AssetValuation, PRICE_PRECISION};
+use crate::risk_management::CircuitBreaker;
+use crate::tiers::UserTier;
+
+/// Fixed-point scale of `LendingReserve::borrow_index`.
+pub const INDEX_SCALE: i128 = 1_000_000_000_000;
+/// Liquidation slippage tolerated until governance sets
+/// `ParamKey::LiquidationSlippageBps`.
+pub const DEFAULT_LIQUIDATION_SLIPPAGE_BPS: u32 = 300;
+const YEAR_SECS: i128 = 31_536_000;
+const BPS: i128 = 10_000;
+
+#[contracttype]
+#[derive(Clone)]
+enum MarginKey {
+    MarginConfig,
+    Reserve(Symbol),
+    SupplyShares(Address, Symbol),
+    Position(u64),
+    NextPositionId,
+    UserPositions(Address),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MarginConfig {
+    /// Margin below which a position can be liquidated.
+    pub maintenance_margin_bps: u32,
+    /// Share of liquidation proceeds taken as a penalty and split between
+    /// the keeper and the insurance fund, out of what is left once the debt
+    /// is repaid.
+    pub liquidation_penalty_bps: u32,
+}
+
+impl Default for MarginConfig {
+    fn default() -> Self {
+        Self {
+            maintenance_margin_bps: 500,
+            liquidation_penalty_bps: 500,
+        }
+    }
+}
+
+/// Pool of one asset that margin traders borrow from.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LendingReserve {
+    pub asset: Symbol,
+    /// Supplied funds not currently lent out.
+    pub cash: i128,
+    /// Outstanding debt divided by `borrow_index`.
+    pub total_debt_scaled: i128,
+    /// Growth of one unit of debt since the reserve opened, scaled by `INDEX_SCALE`.
+    pub borrow_index: i128,
+    pub supply_shares: i128,
+    /// Simple annual borrow rate.
+    pub borrow_rate_bps: u32,
+    pub last_accrual: u64,
+    /// Debt written off by liquidations and not yet covered by the
+    /// insurance fund.
+    pub bad_debt: i128,
+}
+
+impl LendingReserve {
+    pub fn total_debt(&self) -> i128 {
+        scaled_to_debt(self.total_debt_scaled, self.borrow_index)
+    }
+
+    /// Cash plus outstanding debt: what the suppliers' shares are worth.
+    pub fn total_assets(&self) -> i128 {
+        self.cash + self.total_debt()
+    }
+
+    fn accrue(&mut self, now: u64) {
+        let elapsed = now.saturating_sub(self.last_accrual) as i128;
+        if elapsed > 0 && self.total_debt_scaled > 0 {
+            self.borrow_index +=
+                self.borrow_index * self.borrow_rate_bps as i128 * elapsed / (BPS * YEAR_SECS);
+        }
+        self.last_accrual = now;
+    }
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MarginPosition {
+    pub id: u64,
+    pub owner: Address,
+    pub pool_id: u64,
+    pub held_asset: Symbol,
+    pub held_amount: i128,
+    pub debt_asset: Symbol,
+    /// Debt divided by the reserve's `borrow_index`.
+    pub debt_scaled: i128,
+    /// Collateral posted at open, in `debt_asset`.
+    pub collateral: i128,
+    pub leverage_bps: u32,
+    pub opened_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MarginHealth {
+    pub held_value_usd: i128,
+    pub debt: i128,
+    pub debt_value_usd: i128,
+    pub equity_usd: i128,
+    /// Equity as a share of the held value; negative once underwater.
+    pub margin_bps: i128,
+    pub liquidatable: bool,
+}
+
+fn scaled_to_debt(scaled: i128, index: i128) -> i128 {
+    // Round debt up so the reserve is never owed less than it lent.
+    (scaled * index + INDEX_SCALE - 1) / INDEX_SCALE
+}
+
+fn debt_to_scaled(debt: i128, index: i128) -> i128 {
+    (debt * INDEX_SCALE + index - 1) / index
+}
+
+fn to_asset(symbol: &Symbol) -> Asset {
+    if *symbol == symbol_short!("XLM") {
+        Asset::XLM
+    } else {
+        Asset::Custom(symbol.clone())
+    }
+}
+
+fn load_portfolio(env: &Env) -> Portfolio {
+    env.storage()
+        .instance()
+        .get(&())
+        .unwrap_or_else(|| Portfolio::new(env))
+}
+
+fn save_portfolio(env: &Env, portfolio: &Portfolio) {
+    env.storage().instance().set(&(), portfolio);
+}
+
+pub struct MarginManager;
+
+impl MarginManager {
+    // ── Configuration ────────────────────────────────────────────────────────
+
+    pub fn get_config(env: &Env) -> MarginConfig {
+        env.storage()
+            .persistent()
+            .get(&MarginKey::MarginConfig)
+            .unwrap_or_default()
+    }
+
+    pub fn set_config(env: &Env, config: &MarginConfig) -> Result<(), ContractError> {
+        let bps = BPS as u32;
+        if config.maintenance_margin_bps == 0
+            || config.maintenance_margin_bps >= bps
+            || config.liquidation_penalty_bps >= bps
+        {
+            return Err(ContractError::InvalidAmount);
+        }
+        env.storage()
+            .persistent()
+            .set(&MarginKey::MarginConfig, config);
+        Ok(())
+    }
+
+    /// Highest leverage `tier` may open positions at.
+    pub fn max_leverage_bps(env: &Env, tier: &UserTier) -> u32 {
+        GovernanceParams::get_param(env, ParamKey::MaxLeverageBps(tier.clone()))
+            .map(|bps| bps as u32)
+            .unwrap_or_else(|| Self::default_max_leverage_bps(tier))
+    }
+
+    /// Largest shortfall from the oracle value a liquidation sale accepts.
+    pub fn liquidation_slippage_bps(env: &Env) -> u32 {
+        GovernanceParams::get_param(env, ParamKey::LiquidationSlippageBps)
+            .map(|bps| bps as u32)
+            .unwrap_or(DEFAULT_LIQUIDATION_SLIPPAGE_BPS)
+    }
+
+    pub fn default_max_leverage_bps(tier: &UserTier) -> u32 {
+        match tier {
+            UserTier::Novice => 20_000, // 2x
+            UserTier::Trader => 30_000, // 3x
+            UserTier::Expert => 50_000, // 5x
+            UserTier::Whale => 100_000, // 10x
+        }
+    }
+
+    // ── Lending reserves ─────────────────────────────────────────────────────
+
+    pub fn create_reserve(
+        env: &Env,
+        asset: Symbol,
+        borrow_rate_bps: u32,
+    ) -> Result<(), ContractError> {
+        let key = MarginKey::Reserve(asset.clone());
+        if env.storage().persistent().has(&key) {
+            return Err(ContractError::LendingReserveExists);
+        }
+        if borrow_rate_bps > BPS as u32 {
+            return Err(ContractError::InvalidAmount);
+        }
+        let reserve = LendingReserve {
+            asset,
+            cash: 0,
+            total_debt_scaled: 0,
+            borrow_index: INDEX_SCALE,
+            supply_shares: 0,
+            borrow_rate_bps,
+            last_accrual: env.ledger().timestamp(),
+            bad_debt: 0,
+        };
+        env.storage().persistent().set(&key, &reserve);
+        Ok(())
+    }
+
+    /// The reserve for `asset` with interest accrued to now.
+    pub fn get_reserve(env: &Env, asset: &Symbol) -> Option<LendingReserve> {
+        let mut reserve: LendingReserve = env
+            .storage()
+            .persistent()
+            .get(&MarginKey::Reserve(asset.clone()))?;
+        reserve.accrue(env.ledger().timestamp());
+        Some(reserve)
+    }
+
+    fn load_reserve(env: &Env, asset: &Symbol) -> Result<LendingReserve, ContractError> {
+        Self::get_reserve(env, asset).ok_or(ContractError::LendingReserveNotFound)
+    }
+
+    fn save_reserve(env: &Env, reserve: &LendingReserve) {
+        env.storage()
+            .persistent()
+            .set(&MarginKey::Reserve(reserve.asset.clone()), reserve);
+    }
+
+    pub fn get_supply_shares(env: &Env, provider: &Address, asset: &Symbol) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&MarginKey::SupplyShares(provider.clone(), asset.clone()))
+            .unwrap_or(0)
+    }
+
+    /// Move `amount` of `asset` from the provider's balance into the reserve.
+    /// Returns the shares minted.
+    pub fn supply(
+        env: &Env,
+        provider: &Address,
+        asset: Symbol,
+        amount: i128,
+    ) -> Result<i128, ContractError> {
+        if amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        let mut reserve = Self::load_reserve(env, &asset)?;
+        let mut portfolio = load_portfolio(env);
+        if portfolio.balance_of(env, to_asset(&asset), provider.clone()) < amount {
+            return Err(ContractError::InsufficientBalance);
+        }
+
+        let total_assets = reserve.total_assets();
+        let shares = if reserve.supply_shares == 0 || total_assets == 0 {
+            amount
+        } else {
+            amount * reserve.supply_shares / total_assets
+        };
+        if shares <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+
+        portfolio.debit(env, to_asset(&asset), provider.clone(), amount);
+        save_portfolio(env, &portfolio);
+        reserve.cash += amount;
+        reserve.supply_shares += shares;
+        Self::save_reserve(env, &reserve);
+
+        let key = MarginKey::SupplyShares(provider.clone(), asset);
+        let held = env.storage().persistent().get(&key).unwrap_or(0i128);
+        env.storage().persistent().set(&key, &(held + shares));
+        Ok(shares)
+    }
+
+    /// Burn `shares` and return their value to the provider's balance.
+    /// Fails while that much of the reserve is lent out.
+    pub fn withdraw(
+        env: &Env,
+        provider: &Address,
+        asset: Symbol,
+        shares: i128,
+    ) -> Result<i128, ContractError> {
+        let key = MarginKey::SupplyShares(provider.clone(), asset.clone());
+        let held: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+        if shares <= 0 || shares > held {
+            return Err(ContractError::InvalidAmount);
+        }
+        let mut reserve = Self::load_reserve(env, &asset)?;
+        let amount = shares * reserve.total_assets() / reserve.supply_shares;
+        if amount > reserve.cash {
+            return Err(ContractError::InsufficientReserveLiquidity);
+        }
+
+        reserve.cash -= amount;
+        reserve.supply_shares -= shares;
+        Self::save_reserve(env, &reserve);
+        env.storage().persistent().set(&key, &(held - shares));
+
+        let mut portfolio = load_portfolio(env);
+        portfolio.credit(env, to_asset(&asset), provider.clone(), amount);
+        save_portfolio(env, &portfolio);
+        Ok(amount)
+    }
+
+    // ── Positions ────────────────────────────────────────────────────────────
+
+    pub fn get_position(env: &Env, position_id: u64) -> Option<MarginPosition> {
+        env.storage()
+            .persistent()
+            .get(&MarginKey::Position(position_id))
+    }
+
+    fn load_position(env: &Env, position_id: u64) -> Result<MarginPosition, ContractError> {
+        Self::get_position(env, position_id).ok_or(ContractError::MarginPositionNotFound)
+    }
+
+    pub fn get_user_positions(env: &Env, user: &Address) -> Vec<u64> {
+        env.storage()
+            .persistent()
+            .get(&MarginKey::UserPositions(user.clone()))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    fn remove_position(env: &Env, position: &MarginPosition) {
+        env.storage()
+            .persistent()
+            .remove(&MarginKey::Position(position.id));
+        let mut ids = Self::get_user_positions(env, &position.owner);
+        if let Some(i) = ids.first_index_of(position.id) {
+            ids.remove(i);
+        }
+        env.storage()
+            .persistent()
+            .set(&MarginKey::UserPositions(position.owner.clone()), &ids);
+    }
+
+    /// Open a leveraged position: post `collateral` of `asset_in`, borrow up
+    /// to `leverage_bps` of it in total, and buy the pool's other token.
+    /// Returns the position id.
+    pub fn open(
+        env: &Env,
+        user: &Address,
+        pool_id: u64,
+        asset_in: Symbol,
+        collateral: i128,
+        leverage_bps: u32,
+        min_out: i128,
+    ) -> Result<u64, ContractError> {
+        if collateral <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        let config = Self::get_config(env);
+        let mut portfolio = load_portfolio(env);
+        let tier = portfolio.get_user_tier(env, user.clone());
+        if leverage_bps < BPS as u32
+            || leverage_bps > Self::max_leverage_bps(env, &tier)
+            || BPS * BPS / leverage_bps as i128 <= config.maintenance_margin_bps as i128
+        {
+            return Err(ContractError::LeverageTooHigh);
+        }
+
+        let mut registry = crate::load_pool_registry(env);
+        let pool = registry
+            .get_pool(pool_id)
+            .ok_or(ContractError::LPPositionNotFound)?;
+        let held_asset = if asset_in == pool.token_a {
+            pool.token_b
+        } else if asset_in == pool.token_b {
+            pool.token_a
+        } else {
+            return Err(ContractError::InvalidTokenSymbol);
+        };
+        if CircuitBreaker::is_halted(env, &asset_in) || CircuitBreaker::is_halted(env, &held_asset)
+        {
+            return Err(ContractError::CircuitBreakerTripped);
+        }
+        if AssetValuation::price_usd(env, &asset_in).is_none()
+            || AssetValuation::price_usd(env, &held_asset).is_none()
+        {
+            return Err(ContractError::MarginPriceUnavailable);
+        }
+        if portfolio.balance_of(env, to_asset(&asset_in), user.clone()) < collateral {
+            return Err(ContractError::InsufficientBalance);
+        }
+
+        let borrowed = collateral * (leverage_bps as i128 - BPS) / BPS;
+        let mut reserve = Self::load_reserve(env, &asset_in)?;
+        if borrowed > reserve.cash {
+            return Err(ContractError::InsufficientReserveLiquidity);
+        }
+        let debt_scaled = debt_to_scaled(borrowed, reserve.borrow_index);
+        reserve.cash -= borrowed;
+        reserve.total_debt_scaled += debt_scaled;
+
+        let held_amount = registry.swap(
+            env,
+            pool_id,
+            asset_in.clone(),
+            collateral + borrowed,
+            min_out,
+        )?;
+        portfolio.debit(env, to_asset(&asset_in), user.clone(), collateral);
+
+        let id: u64 = env
+            .storage()
+            .persistent()
+            .get(&MarginKey::NextPositionId)
+            .unwrap_or(1);
+        let now = env.ledger().timestamp();
+        let position = MarginPosition {
+            id,
+            owner: user.clone(),
+            pool_id,
+            held_asset: held_asset.clone(),
+            held_amount,
+            debt_asset: asset_in.clone(),
+            debt_scaled,
+            collateral,
+            leverage_bps,
+            opened_at: now,
+        };
+        if Self::health_with(env, &position, &reserve, &config)?.liquidatable {
+            return Err(ContractError::InsufficientMargin);
+        }
+
+        save_portfolio(env, &portfolio);
+        crate::save_pool_registry(env, &registry);
+        Self::save_reserve(env, &reserve);
+        env.storage()
+            .persistent()
+            .set(&MarginKey::Position(id), &position);
+        env.storage()
+            .persistent()
+            .set(&MarginKey::NextPositionId, &(id + 1));
+        let mut ids = Self::get_user_positions(env, user);
+        ids.push_back(id);
+        env.storage()
+            .persistent()
+            .set(&MarginKey::UserPositions(user.clone()), &ids);
+
+        events::margin_position_opened(
+            env,
+            user.clone(),
+            id,
+            held_asset,
+            held_amount,
+            asset_in,
+            borrowed,
+            leverage_bps,
+            now,
+        );
+        Ok(id)
+    }
+
+    /// Repay up to `amount` of the position's debt from the owner's balance.
+    /// Returns the debt still outstanding.
+    pub fn repay(
+        env: &Env,
+        user: &Address,
+        position_id: u64,
+        amount: i128,
+    ) -> Result<i128, ContractError> {
+        let mut position = Self::load_position(env, position_id)?;
+        if position.owner != *user {
+            return Err(ContractError::NotAuthorized);
+        }
+        if amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        let mut reserve = Self::load_reserve(env, &position.debt_asset)?;
+        let debt = scaled_to_debt(position.debt_scaled, reserve.borrow_index);
+        let repaid = amount.min(debt);
+
+        let mut portfolio = load_portfolio(env);
+        let asset = to_asset(&position.debt_asset);
+        if portfolio.balance_of(env, asset.clone(), user.clone()) < repaid {
+            return Err(ContractError::InsufficientBalance);
+        }
+        portfolio.debit(env, asset, user.clone(), repaid);
+        save_portfolio(env, &portfolio);
+
+        let repaid_scaled = if repaid == debt {
+            position.debt_scaled
+        } else {
+            repaid * INDEX_SCALE / reserve.borrow_index
+        };
+        reserve.cash += repaid;
+        reserve.total_debt_scaled -= repaid_scaled;
+        position.debt_scaled -= repaid_scaled;
+        Self::save_reserve(env, &reserve);
+        env.storage()
+            .persistent()
+            .set(&MarginKey::Position(position_id), &position);
+        Ok(debt - repaid)
+    }
+
+    /// Sell the held asset, repay the debt and credit what is left to the
+    /// owner. Fails if the sale does not cover the debt.
+    pub fn close(
+        env: &Env,
+        user: &Address,
+        position_id: u64,
+        min_out: i128,
+    ) -> Result<i128, ContractError> {
+        let position = Self::load_position(env, position_id)?;
+        if position.owner != *user {
+            return Err(ContractError::NotAuthorized);
+        }
+        let mut reserve = Self::load_reserve(env, &position.debt_asset)?;
+        let debt = scaled_to_debt(position.debt_scaled, reserve.borrow_index);
+
+        let mut registry = crate::load_pool_registry(env);
+        let proceeds = registry.swap(
+            env,
+            position.pool_id,
+            position.held_asset.clone(),
+            position.held_amount,
+            min_out,
+        )?;
+        if proceeds < debt {
+            return Err(ContractError::InsufficientMargin);
+        }
+        crate::save_pool_registry(env, &registry);
+
+        reserve.cash += debt;
+        reserve.total_debt_scaled -= position.debt_scaled;
+        Self::save_reserve(env, &reserve);
+
+        let refund = proceeds - debt;
+        if refund > 0 {
+            let mut portfolio = load_portfolio(env);
+            portfolio.credit(env, to_asset(&position.debt_asset), user.clone(), refund);
+            save_portfolio(env, &portfolio);
+        }
+        Self::remove_position(env, &position);
+
+        events::margin_position_closed(
+            env,
+            user.clone(),
+            position_id,
+            proceeds,
+            debt,
+            refund,
+            env.ledger().timestamp(),
+        );
+        Ok(refund)
+    }
+
+    /// Liquidate a position below maintenance margin. The held asset is
+    /// sold for no less than its oracle value minus the liquidation slippage
+    /// tolerance. The proceeds repay the debt first, then pay the penalty;
+    /// any surplus goes to the owner and any shortfall is written off
+    /// against the reserve. Returns the liquidator's reward.
+    pub fn liquidate(
+        env: &Env,
+        liquidator: &Address,
+        position_id: u64,
+    ) -> Result<i128, ContractError> {
+        let position = Self::load_position(env, position_id)?;
+        let config = Self::get_config(env);
+        let mut reserve = Self::load_reserve(env, &position.debt_asset)?;
+        let health = Self::health_with(env, &position, &reserve, &config)?;
+        if !health.liquidatable {
+            return Err(ContractError::PositionNotLiquidatable);
+        }
+        let debt = health.debt;
+
+        // health_with has checked that both assets are priced.
+        let debt_price = AssetValuation::price_usd(env, &position.debt_asset).unwrap();
+        let oracle_out = health
+            .held_value_usd
+            .checked_mul(PRICE_PRECISION as i128)
+            .ok_or(ContractError::AmountOverflow)?
+            / debt_price as i128;
+        let min_out = oracle_out * (BPS - Self::liquidation_slippage_bps(env) as i128) / BPS;
+
+        let mut registry = crate::load_pool_registry(env);
+        let proceeds = registry.swap(
+            env,
+            position.pool_id,
+            position.held_asset.clone(),
+            position.held_amount,
+            min_out,
+        )?;
+        crate::save_pool_registry(env, &registry);
+
+        let repaid = proceeds.min(debt);
+        let shortfall = debt - repaid;
+        let penalty =
+            (proceeds * config.liquidation_penalty_bps as i128 / BPS).min(proceeds - repaid);
+        let (insurance, _) = InsuranceFund::settle_liquidation(
+            env,
+            &position.debt_asset,
+            penalty,
+            shortfall,
+            LossSource::MarginReserve,
+        );
+        let reward = penalty - insurance;
+        let surplus = proceeds - repaid - penalty;
+
+        reserve.cash += repaid;
+        reserve.total_debt_scaled -= position.debt_scaled;
+        reserve.bad_debt += shortfall;
+        Self::save_reserve(env, &reserve);
+
+        let asset = to_asset(&position.debt_asset);
+        let mut portfolio = load_portfolio(env);
+        if reward > 0 {
+            portfolio.credit(env, asset.clone(), liquidator.clone(), reward);
+        }
+        if surplus > 0 {
+            portfolio.credit(env, asset, position.owner.clone(), surplus);
+        }
+        save_portfolio(env, &portfolio);
+        Self::remove_position(env, &position);
+
+        events::margin_position_liquidated(
+            env,
+            position.owner.clone(),
+            position_id,
+            liquidator.clone(),
+            proceeds,
+            reward,
+            insurance,
+            shortfall,
+            env.ledger().timestamp(),
+        );
+        Ok(reward)
+    }
+
+    // ── Health ───────────────────────────────────────────────────────────────
+
+    pub fn health(env: &Env, position_id: u64) -> Result<MarginHealth, ContractError> {
+        let position = Self::load_position(env, position_id)?;
+        let reserve = Self::load_reserve(env, &position.debt_asset)?;
+        Self::health_with(env, &position, &reserve, &Self::get_config(env))
+    }
+
+    fn health_with(
+        env: &Env,
+        position: &MarginPosition,
+        reserve: &LendingReserve,
+        config: &MarginConfig,
+    ) -> Result<MarginHealth, ContractError> {
+        if AssetValuation::price_usd(env, &position.held_asset).is_none()
+            || AssetValuation::price_usd(env, &position.debt_asset).is_none()
+        {
+            return Err(ContractError::MarginPriceUnavailable);
+        }
+        let debt = scaled_to_debt(position.debt_scaled, reserve.borrow_index);
+        let held_value_usd =
+            AssetValuation::value_usd(env, &position.held_asset, position.held_amount);
+        let debt_value_usd = AssetValuation::value_usd(env, &position.debt_asset, debt);
+        let equity_usd = held_value_usd - debt_value_usd;
+        let margin_bps = if held_value_usd > 0 {
+            equity_usd * BPS / held_value_usd
+        } else {
+            0
+        };
+        Ok(MarginHealth {
+            held_value_usd,
+            debt,
+            debt_value_usd,
+            equity_usd,
+            margin_bps,
+            liquidatable: margin_bps < config.maintenance_margin_bps as i128,
+        })
+    }
+
+    /// Return `amount` of written-off debt to the reserve. Used by the
+    /// insurance fund when it covers a margin shortfall.
+    pub fn restore_reserve(env: &Env, asset: &Symbol, amount: i128) -> Result<(), ContractError> {
+        let mut reserve = Self::load_reserve(env, asset)?;
+        reserve.cash += amount;
+        reserve.bad_debt -= amount.min(reserve.bad_debt);
+        Self::save_reserve(env, &reserve);
+        Ok(())
+    }
+}
@@ -0,0 +1,302 @@
+#![cfg(test)]
+
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::{symbol_short, Address, Env, Symbol};
+
+use crate::errors::ContractError;
+use crate::governance_params::{GovernanceParams, ParamKey};
+use crate::governance_system::{apply_action, ProposalAction};
+use crate::insurance_fund::{InsuranceFund, LossSource};
+use crate::kyc::KYCStatus;
+use crate::risk_management::valuation::{PRICE_PRECISION, USD};
+use crate::storage::ADMIN_KEY;
+use crate::tiers::UserTier;
+use crate::{CounterContract, CounterContractClient};
+
+const XLM: Symbol = symbol_short!("XLM");
+const USDC: Symbol = symbol_short!("USDC");
+const YEAR_SECS: u64 = 31_536_000;
+
+struct Setup {
+    env: Env,
+    contract_id: Address,
+    client: CounterContractClient<'static>,
+    admin: Address,
+    trader: Address,
+    supplier: Address,
+    pool_id: u64,
+}
+
+fn verified(env: &Env, client: &CounterContractClient, operator: &Address) -> Address {
+    let user = Address::generate(env);
+    client.kyc_submit(&user);
+    client.kyc_update_status(operator, &user, &KYCStatus::InReview, &None);
+    client.kyc_update_status(operator, &user, &KYCStatus::Verified, &None);
+    user
+}
+
+/// An XLM/USDC pool at 30 bps with both assets at $1, a USDC reserve
+/// lending at 10% a year with 100 000 supplied, and a trader holding 1 000
+/// USDC.
+fn setup() -> Setup {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|l| l.timestamp = 1_000_000);
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    let operator = Address::generate(&env);
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(&ADMIN_KEY, &admin);
+    });
+    client.kyc_add_operator(&admin, &operator);
+    let trader = verified(&env, &client, &operator);
+    let supplier = verified(&env, &client, &operator);
+
+    client.init_oracle_feed(&admin, &(XLM, USD), &PRICE_PRECISION);
+    client.init_oracle_feed(&admin, &(USDC, USD), &PRICE_PRECISION);
+    let pool_id = client.register_pool(&admin, &XLM, &USDC, &1_000_000, &1_000_000, &30);
+
+    client.create_lending_reserve(&admin, &USDC, &1_000);
+    client.mint(&USDC, &supplier, &100_000);
+    assert_eq!(client.supply_reserve(&supplier, &USDC, &100_000), 100_000);
+    client.mint(&USDC, &trader, &1_000);
+
+    Setup {
+        env,
+        contract_id,
+        client,
+        admin,
+        trader,
+        supplier,
+        pool_id,
+    }
+}
+
+impl Setup {
+    fn open(&self, leverage_bps: u32) -> Result<u64, ContractError> {
+        match self.client.try_open_margin_position(
+            &self.trader,
+            &self.pool_id,
+            &USDC,
+            &1_000,
+            &leverage_bps,
+            &0,
+        ) {
+            Ok(id) => Ok(id.unwrap()),
+            Err(err) => Err(err.unwrap()),
+        }
+    }
+
+    fn balance(&self, user: &Address) -> i128 {
+        self.client.balance_of(&USDC, user)
+    }
+
+    /// Walk the XLM feed down in steps the oracle accepts.
+    fn drop_xlm_to(&self, cents: &[u128]) {
+        for price in cents {
+            self.env.ledger().with_mut(|l| l.timestamp += 10);
+            self.client.submit_oracle_price(
+                &self.admin,
+                &(XLM, USD),
+                &(PRICE_PRECISION / 100 * price),
+            );
+        }
+    }
+}
+
+#[test]
+fn test_leverage_capped_by_tier() {
+    let s = setup();
+    // Minting 1 000 USDC in setup crosses the 100 volume threshold, so the
+    // trader is a Trader, who may borrow up to 3x, rather than a Novice.
+    assert_eq!(s.open(40_000), Err(ContractError::LeverageTooHigh));
+    assert_eq!(s.open(9_000), Err(ContractError::LeverageTooHigh));
+
+    s.env.as_contract(&s.contract_id, || {
+        GovernanceParams::apply_param_update(
+            &s.env,
+            ParamKey::MaxLeverageBps(UserTier::Trader),
+            40_000,
+        )
+        .unwrap();
+    });
+    let id = s.open(40_000).unwrap();
+    let position = s.client.get_margin_position(&id).unwrap();
+    assert_eq!(position.collateral, 1_000);
+    assert_eq!(s.client.get_margin_health(&id).debt, 3_000);
+    assert_eq!(s.client.get_user_margin_positions(&s.trader).len(), 1);
+    assert_eq!(s.client.get_lending_reserve(&USDC).unwrap().cash, 97_000);
+
+    // Both legs must be priced by the oracle.
+    let btc = symbol_short!("BTC");
+    let pool_id = s
+        .client
+        .register_pool(&s.admin, &btc, &USDC, &1_000_000, &1_000_000, &30);
+    assert_eq!(
+        s.client
+            .try_open_margin_position(&s.trader, &pool_id, &USDC, &100, &10_000, &0),
+        Err(Ok(ContractError::MarginPriceUnavailable))
+    );
+}
+
+#[test]
+fn test_open_accrue_and_close() {
+    let s = setup();
+    let id = s.open(20_000).unwrap();
+    assert_eq!(s.balance(&s.trader), 0);
+
+    let position = s.client.get_margin_position(&id).unwrap();
+    assert_eq!(
+        (position.held_asset.clone(), position.held_amount),
+        (XLM, 1_990)
+    );
+    let health = s.client.get_margin_health(&id);
+    assert_eq!((health.held_value_usd, health.debt), (1_990, 1_000));
+    assert_eq!(health.margin_bps, 4_974);
+    assert!(!health.liquidatable);
+
+    // A year at 10% grows the debt and what the suppliers' shares are worth.
+    s.env.ledger().with_mut(|l| l.timestamp += YEAR_SECS);
+    let reserve = s.client.get_lending_reserve(&USDC).unwrap();
+    assert_eq!(reserve.total_debt(), 1_100);
+    assert_eq!(reserve.total_assets(), 100_100);
+
+    let outsider = Address::generate(&s.env);
+    assert_eq!(
+        s.client.try_close_margin_position(&outsider, &id, &0),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+    let refund = s.client.close_margin_position(&s.trader, &id, &0);
+    assert_eq!(refund, 1_988 - 1_100);
+    assert_eq!(s.balance(&s.trader), refund);
+    assert!(s.client.get_margin_position(&id).is_none());
+    assert_eq!(s.client.get_user_margin_positions(&s.trader).len(), 0);
+
+    assert_eq!(
+        s.client.withdraw_reserve(&s.supplier, &USDC, &100_000),
+        100_100
+    );
+}
+
+#[test]
+fn test_keeper_liquidation_pays_penalty() {
+    let s = setup();
+    let id = s.open(20_000).unwrap();
+    let keeper = Address::generate(&s.env);
+
+    s.drop_xlm_to(&[91, 83, 76, 69, 63, 57]);
+    assert!(!s.client.get_margin_health(&id).liquidatable);
+    assert_eq!(
+        s.client.try_liquidate_margin_position(&keeper, &id),
+        Err(Ok(ContractError::PositionNotLiquidatable))
+    );
+
+    s.drop_xlm_to(&[52]);
+    // 1 034 USD of XLM against 1 001 of debt: 70 s of interest, rounded up.
+    let health = s.client.get_margin_health(&id);
+    assert!(health.liquidatable);
+    assert_eq!(health.margin_bps, 319);
+
+    // The pool still pays 1 988 USDC: 99 is the penalty, 1 001 repays the
+    // debt with the interest of the last 70 s, rounded up, and the rest
+    // goes back to the trader.
+    let reward = s.client.liquidate_margin_position(&keeper, &id);
+    assert_eq!(reward, 50);
+    assert_eq!(s.balance(&keeper), 50);
+    assert_eq!(s.client.get_insurance_fund(&USDC).penalties_received, 49);
+    assert_eq!(s.balance(&s.trader), 1_988 - 99 - 1_001);
+    let reserve = s.client.get_lending_reserve(&USDC).unwrap();
+    assert_eq!((reserve.cash, reserve.total_debt_scaled), (100_001, 0));
+    assert_eq!(reserve.bad_debt, 0);
+    assert!(s.client.get_margin_position(&id).is_none());
+}
+
+#[test]
+fn test_liquidation_rejects_sale_below_oracle_price() {
+    let s = setup();
+    let id = s.open(20_000).unwrap();
+    let keeper = Address::generate(&s.env);
+
+    // The pool is dumped but the oracle stops at 52 cents, so the sale
+    // would fetch far less than the position is worth.
+    s.client
+        .pool_swap(&s.pool_id, &XLM, &1_000_000, &0, &s.supplier);
+    s.drop_xlm_to(&[91, 83, 76, 69, 63, 57, 52]);
+    assert!(s.client.get_margin_health(&id).liquidatable);
+    assert_eq!(
+        s.client.try_liquidate_margin_position(&keeper, &id),
+        Err(Ok(ContractError::SlippageExceeded))
+    );
+    assert!(s.client.get_margin_position(&id).is_some());
+}
+
+#[test]
+fn test_repay_margin_debt() {
+    let s = setup();
+    let id = s.open(20_000).unwrap();
+    assert_eq!(
+        s.client.try_repay_margin_debt(&s.trader, &id, &400),
+        Err(Ok(ContractError::InsufficientBalance))
+    );
+
+    s.client.mint(&USDC, &s.trader, &1_500);
+    assert_eq!(s.client.repay_margin_debt(&s.trader, &id, &400), 600);
+    assert_eq!(s.client.get_margin_health(&id).debt, 600);
+    // Repaying more than is owed only takes the debt.
+    assert_eq!(s.client.repay_margin_debt(&s.trader, &id, &1_000), 0);
+    assert_eq!(s.balance(&s.trader), 500);
+    let reserve = s.client.get_lending_reserve(&USDC).unwrap();
+    assert_eq!((reserve.cash, reserve.total_debt_scaled), (100_000, 0));
+}
+
+#[test]
+fn test_shortfall_is_covered_by_the_insurance_fund() {
+    let s = setup();
+    let id = s.open(20_000).unwrap();
+    // The fund holds its share of earlier pool fees.
+    s.env.as_contract(&s.contract_id, || {
+        InsuranceFund::receive_fees(&s.env, &USDC, &Address::generate(&s.env), 2_000);
+    });
+    let fund = s.client.get_insurance_fund(&USDC).balance;
+
+    // Someone dumps XLM into the pool and the oracle follows.
+    s.client
+        .pool_swap(&s.pool_id, &XLM, &1_000_000, &0, &s.supplier);
+    s.drop_xlm_to(&[91, 83, 76, 69, 63, 57, 52, 47, 43, 39, 36, 33, 30, 28, 26]);
+    // Hold it at the pool's 25 cents for a full TWAP window.
+    s.drop_xlm_to(&[25; 10]);
+
+    // Everything the sale fetches repays the debt, so there is no penalty
+    // and no keeper reward.
+    let keeper = Address::generate(&s.env);
+    assert_eq!(s.client.liquidate_margin_position(&keeper, &id), 0);
+    assert_eq!(s.client.get_insurance_fund(&USDC).penalties_received, 0);
+    let reserve = s.client.get_lending_reserve(&USDC).unwrap();
+    assert!(reserve.bad_debt > 0);
+    assert_eq!(reserve.total_debt_scaled, 0);
+    // Interest accrued while the price fell is owed on top of the principal.
+    assert_eq!(reserve.total_assets(), 100_001 - reserve.bad_debt);
+    assert_eq!(s.balance(&s.trader), 0);
+
+    let shortfall = s.client.get_insurance_shortfall(&1).unwrap();
+    assert_eq!(shortfall.source, LossSource::MarginReserve);
+    assert_eq!(shortfall.amount, reserve.bad_debt);
+
+    // Governance pays what the fund holds; the suppliers share the rest
+    // pro rata.
+    assert!(fund > 0 && fund < shortfall.amount);
+    s.env.as_contract(&s.contract_id, || {
+        apply_action(&s.env, 0, &ProposalAction::CoverShortfall(1)).unwrap();
+    });
+    let socialised = shortfall.amount - fund;
+    assert_eq!(
+        s.client.get_lending_reserve(&USDC).unwrap().bad_debt,
+        socialised
+    );
+    assert_eq!(s.client.get_insurance_fund(&USDC).socialised, socialised);
+    assert_eq!(
+        s.client.withdraw_reserve(&s.supplier, &USDC, &100_000),
+        100_001 - socialised
+    );
+}
@@ -15,9 +15,14 @@ fn verify_trader(env: &Env, contract_id: &Address, admin: &Address, trader: &Add
 
     env.as_contract(contract_id, || {
         set_admin(env, admin);
-        let _ = KYCSystem::add_operator(env, admin, operator.clone());
-        let _ = KYCSystem::submit_kyc(env, trader);
-        let _ = KYCSystem::update_status(env, &operator, trader, KYCStatus::Verified, None);
+        KYCSystem::add_operator(env, admin, operator.clone()).unwrap();
+    });
+    env.as_contract(contract_id, || KYCSystem::submit_kyc(env, trader).unwrap());
+    env.as_contract(contract_id, || {
+        KYCSystem::update_status(env, &operator, trader, KYCStatus::InReview, None).unwrap();
+    });
+    env.as_contract(contract_id, || {
+        KYCSystem::update_status(env, &operator, trader, KYCStatus::Verified, None).unwrap();
     });
 }
 
@@ -40,7 +45,7 @@ fn test_two_hop_swap_execution() {
     let pool2 = client.register_pool(&admin, &usdc, &btc, &10000, &5000, &30);
 
     // Find route from XLM to BTC
-    let route = client.find_best_route(&xlm, &btc, &100);
+    let route = client.find_best_route(&xlm, &btc, &100, &3);
     assert!(route.is_some());
 
     let r = route.unwrap();
@@ -73,7 +78,7 @@ fn test_multi_hop_respects_slippage_tolerance() {
     client.register_pool(&admin, &usdc, &btc, &1000, &500, &30);
 
     // Find route
-    let route = client.find_best_route(&xlm, &btc, &500);
+    let route = client.find_best_route(&xlm, &btc, &500, &3);
     assert!(route.is_some());
 
     let r = route.unwrap();
@@ -105,7 +110,7 @@ fn test_multi_hop_atomic_execution() {
     // Missing USDC/BTC pool
 
     // Try to find route (should not find 2-hop route)
-    let route = client.find_best_route(&xlm, &btc, &100);
+    let route = client.find_best_route(&xlm, &btc, &100, &3);
 
     // Route should be None since second pool doesn't exist
     if route.is_none() {
@@ -140,7 +145,7 @@ fn test_multi_hop_emits_events() {
     client.register_pool(&admin, &usdc, &btc, &10000, &5000, &30);
 
     // Find and execute route
-    let route = client.find_best_route(&xlm, &btc, &100);
+    let route = client.find_best_route(&xlm, &btc, &100, &3);
     assert!(route.is_some());
 
     let r = route.unwrap();
@@ -168,7 +173,7 @@ fn test_single_hop_swap_via_route() {
     client.register_pool(&admin, &xlm, &usdc, &10000, &10000, &30);
 
     // Find direct route
-    let route = client.find_best_route(&xlm, &usdc, &100);
+    let route = client.find_best_route(&xlm, &usdc, &100, &3);
     assert!(route.is_some());
 
     let r = route.unwrap();
@@ -220,7 +225,7 @@ fn test_multi_hop_with_zero_amount() {
 
     client.register_pool(&admin, &xlm, &usdc, &10000, &10000, &30);
 
-    let route = client.find_best_route(&xlm, &usdc, &100);
+    let route = client.find_best_route(&xlm, &usdc, &100, &3);
     assert!(route.is_some());
 
     let r = route.unwrap();
@@ -251,7 +256,7 @@ fn test_three_hop_route_execution() {
     client.register_pool(&admin, &btc, &eth, &5000, &5000, &30);
 
     // Find route from XLM to ETH
-    let route = client.find_best_route(&xlm, &eth, &100);
+    let route = client.find_best_route(&xlm, &eth, &100, &3);
 
     // Note: Current find_best_route only supports up to 2 hops
     // This test validates that the execution function can handle it if route is provided
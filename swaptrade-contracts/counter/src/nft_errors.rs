@@ -1,3 +1,5 @@
+use crate::errors::SwapTradeError;
+
 /// NFT-specific errors for the SwapTrade NFT marketplace
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum NFTError {
@@ -22,6 +24,14 @@ pub enum NFTError {
     MarketplacePaused = 1008,
     /// Contract is frozen for this user
     UserFrozen = 1009,
+    /// NFT lending is geofenced and the user has no jurisdiction on record
+    JurisdictionUnknown = 1010,
+    /// NFT lending is not open in the user's jurisdiction
+    JurisdictionNotPermitted = 1011,
+    /// The user is not KYC verified
+    KYCVerificationRequired = 1012,
+    /// The user's verification level is below what NFT lending requires
+    KYCLevelTooLow = 1013,
 
     // ===== Ownership Errors (1100-1199) =====
     /// Caller is not the owner
@@ -149,3 +159,16 @@ pub enum NFTError {
     /// Oracle price not available
     OraclePriceNotAvailable = 1802,
 }
+
+/// Keeps the reason a KYC check on an NFT entrypoint failed.
+impl From<SwapTradeError> for NFTError {
+    fn from(err: SwapTradeError) -> Self {
+        match err {
+            SwapTradeError::JurisdictionUnknown => NFTError::JurisdictionUnknown,
+            SwapTradeError::JurisdictionNotPermitted => NFTError::JurisdictionNotPermitted,
+            SwapTradeError::KYCVerificationRequired => NFTError::KYCVerificationRequired,
+            SwapTradeError::KYCLevelTooLow => NFTError::KYCLevelTooLow,
+            _ => NFTError::Unauthorized,
+        }
+    }
+}
@@ -1,5 +1,7 @@
 #![cfg_attr(not(test), no_std)]
 use crate::emergency;
+use crate::insurance_fund::{InsuranceFund, LossSource};
+use crate::kyc::{KYCFeature, KYCSystem};
 use crate::nft_errors::NFTError;
 use crate::nft_minting::{get_nft, is_owner};
 use crate::nft_storage::*;
@@ -66,6 +68,8 @@ pub fn request_loan(
         return Err(NFTError::UserFrozen);
     }
 
+    KYCSystem::require_feature_allowed(env, &borrower, KYCFeature::NftLending)?;
+
     // Validate loan amount
     if loan_amount <= 0 {
         return Err(NFTError::InvalidAmount);
@@ -193,6 +197,8 @@ pub fn fund_loan(env: &Env, lender: Address, loan_id: u64) -> Result<(), NFTErro
         return Err(NFTError::UserFrozen);
     }
 
+    KYCSystem::require_feature_allowed(env, &lender, KYCFeature::NftLending)?;
+
     let mut loan_registry: LoanRegistry = env
         .storage()
         .instance()
@@ -580,6 +586,8 @@ pub fn deposit_to_lending_pool(
         return Err(NFTError::UserFrozen);
     }
 
+    KYCSystem::require_feature_allowed(env, &lender, KYCFeature::NftLending)?;
+
     if amount <= 0 {
         return Err(NFTError::InvalidAmount);
     }
@@ -801,6 +809,8 @@ pub fn borrow_from_lending_pool(
         return Err(NFTError::UserFrozen);
     }
 
+    KYCSystem::require_feature_allowed(env, &borrower, KYCFeature::NftLending)?;
+
     // Validate loan amount and duration
     if loan_amount <= 0 {
         return Err(NFTError::InvalidAmount);
@@ -1720,6 +1730,13 @@ pub fn execute_liquidation(env: &Env, loan_id: u64) -> Result<(), NFTError> {
             )?;
 
             clear_liquidation_bid(env, loan_id);
+            InsuranceFund::settle_liquidation(
+                env,
+                &symbol_short!("USDC"),
+                penalty,
+                bad_debt,
+                LossSource::NftLoan(loan_id, loan.lender.clone()),
+            );
             crate::nft_events::emit_liquidation_executed(
                 env,
                 loan_id,
@@ -1785,6 +1802,13 @@ pub fn execute_liquidation(env: &Env, loan_id: u64) -> Result<(), NFTError> {
             )?;
 
             clear_liquidation_bid(env, loan_id);
+            InsuranceFund::settle_liquidation(
+                env,
+                &symbol_short!("USDC"),
+                penalty,
+                bad_debt,
+                LossSource::NftLoan(loan_id, loan.lender.clone()),
+            );
             crate::nft_events::emit_liquidation_executed(
                 env,
                 loan_id,
@@ -16,13 +16,16 @@ fn setup_oracle(env: &Env, pair: (Symbol, Symbol)) {
 #[test]
 fn test_initialize_oracle() {
     let env = Env::default();
+    let contract_id = env.register(CounterContract, ());
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDC");
     let pair = (xlm.clone(), usdc.clone());
 
-    setup_oracle(&env, pair.clone());
+    env.as_contract(&contract_id, || setup_oracle(&env, pair.clone()));
 
-    let (config, state) = OracleAdapter::get_oracle_info(&env, pair).unwrap();
+    let (config, state) = env.as_contract(&contract_id, || {
+        OracleAdapter::get_oracle_info(&env, pair).unwrap()
+    });
 
     assert!(config.is_active);
     assert_eq!(config.staleness_threshold, 300);
@@ -36,17 +39,22 @@ fn test_initialize_oracle() {
 #[test]
 fn test_update_price_success() {
     let env = Env::default();
+    let contract_id = env.register(CounterContract, ());
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDC");
     let pair = (xlm.clone(), usdc.clone());
 
-    setup_oracle(&env, pair.clone());
+    env.as_contract(&contract_id, || setup_oracle(&env, pair.clone()));
 
     // Update price by 5% (within 10% threshold)
     let new_price = (PRECISION as u128).saturating_mul(10_500) / 10_000;
-    OracleAdapter::update_price(&env, pair.clone(), new_price).unwrap();
+    env.as_contract(&contract_id, || {
+        OracleAdapter::update_price(&env, pair.clone(), new_price).unwrap()
+    });
 
-    let (_, state) = OracleAdapter::get_oracle_info(&env, pair).unwrap();
+    let (_, state) = env.as_contract(&contract_id, || {
+        OracleAdapter::get_oracle_info(&env, pair).unwrap()
+    });
     assert_eq!(state.current_price, new_price);
     assert_eq!(state.price_history.len(), 1);
 }
@@ -54,20 +62,25 @@ fn test_update_price_success() {
 #[test]
 fn test_circuit_breaker_triggers_on_large_deviation() {
     let env = Env::default();
+    let contract_id = env.register(CounterContract, ());
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDC");
     let pair = (xlm.clone(), usdc.clone());
 
-    setup_oracle(&env, pair.clone());
+    env.as_contract(&contract_id, || setup_oracle(&env, pair.clone()));
 
     // Try to update price by 15% (exceeds 10% threshold)
     let new_price = (PRECISION as u128).saturating_mul(11_500) / 10_000;
-    let result = OracleAdapter::update_price(&env, pair.clone(), new_price);
+    let result = env.as_contract(&contract_id, || {
+        OracleAdapter::update_price(&env, pair.clone(), new_price)
+    });
 
     assert!(result.is_err());
     assert_eq!(result.unwrap_err(), ContractError::CircuitBreakerTriggered);
 
-    let (_, state) = OracleAdapter::get_oracle_info(&env, pair).unwrap();
+    let (_, state) = env.as_contract(&contract_id, || {
+        OracleAdapter::get_oracle_info(&env, pair).unwrap()
+    });
     assert!(state.circuit_breaker_active);
     assert_eq!(state.fallback_price, PRECISION);
 }
@@ -75,47 +88,58 @@ fn test_circuit_breaker_triggers_on_large_deviation() {
 #[test]
 fn test_get_price_returns_fallback_when_circuit_breaker_active() {
     let env = Env::default();
+    let contract_id = env.register(CounterContract, ());
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDC");
     let pair = (xlm.clone(), usdc.clone());
 
-    setup_oracle(&env, pair.clone());
+    env.as_contract(&contract_id, || setup_oracle(&env, pair.clone()));
 
     // Trigger circuit breaker
     let new_price = (PRECISION as u128).saturating_mul(11_500) / 10_000;
-    let _ = OracleAdapter::update_price(&env, pair.clone(), new_price);
+    let _ = env.as_contract(&contract_id, || {
+        OracleAdapter::update_price(&env, pair.clone(), new_price)
+    });
 
     // Should return fallback price
-    let price = OracleAdapter::get_price(&env, pair).unwrap();
+    let price = env.as_contract(&contract_id, || {
+        OracleAdapter::get_price(&env, pair).unwrap()
+    });
     assert_eq!(price, PRECISION);
 }
 
 #[test]
 fn test_staleness_check_returns_fallback() {
     let env = Env::default();
+    let contract_id = env.register(CounterContract, ());
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDC");
     let pair = (xlm.clone(), usdc.clone());
 
-    setup_oracle(&env, pair.clone());
+    env.as_contract(&contract_id, || setup_oracle(&env, pair.clone()));
 
     // Advance time beyond staleness threshold (300 seconds)
-    let mut ledger = env.ledger();
-    ledger.set_timestamp(env.ledger().timestamp() + 301);
+    let mut ledger = env.as_contract(&contract_id, || env.ledger());
+    env.as_contract(&contract_id, || {
+        ledger.set_timestamp(env.ledger().timestamp() + 301)
+    });
 
     // Should return fallback price
-    let price = OracleAdapter::get_price(&env, pair).unwrap();
+    let price = env.as_contract(&contract_id, || {
+        OracleAdapter::get_price(&env, pair).unwrap()
+    });
     assert_eq!(price, PRECISION);
 }
 
 #[test]
 fn test_twap_calculation() {
     let env = Env::default();
+    let contract_id = env.register(CounterContract, ());
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDC");
     let pair = (xlm.clone(), usdc.clone());
 
-    setup_oracle(&env, pair.clone());
+    env.as_contract(&contract_id, || setup_oracle(&env, pair.clone()));
 
     // Add 10 price observations with different prices
     let prices = vec![
@@ -131,24 +155,27 @@ fn test_twap_calculation() {
         (PRECISION as u128).saturating_mul(10_900) / 10_000, // 9% increase
     ];
 
-    for (i, price) in prices.iter().enumerate() {
+    // Setup only sets the spot price; every price below is an observation
+    for price in prices.iter() {
         // Advance time for each observation
-        let mut ledger = env.ledger();
-        ledger.set_timestamp(env.ledger().timestamp() + 10);
-
-        if i == 0 {
-            // First update is already done in setup
-            continue;
-        }
-        OracleAdapter::update_price(&env, pair.clone(), *price).unwrap();
+        let mut ledger = env.as_contract(&contract_id, || env.ledger());
+        env.as_contract(&contract_id, || {
+            ledger.set_timestamp(env.ledger().timestamp() + 10)
+        });
+
+        env.as_contract(&contract_id, || {
+            OracleAdapter::update_price(&env, pair.clone(), *price).unwrap()
+        });
     }
 
     // Get price should return TWAP
-    let twap_price = OracleAdapter::get_price(&env, pair).unwrap();
+    let twap_price = env.as_contract(&contract_id, || {
+        OracleAdapter::get_price(&env, pair).unwrap()
+    });
 
     // TWAP should be average of all observations
-    // Expected average is approximately 5.4% increase
-    let expected_avg = PRECISION.saturating_mul(10_540) / 10_000;
+    // Mean of the 0%..9% increases is a 4.5% increase
+    let expected_avg = PRECISION.saturating_mul(10_450) / 10_000;
 
     // Allow small rounding difference
     assert!(twap_price >= expected_avg - 1_000_000);
@@ -158,23 +185,28 @@ fn test_twap_calculation() {
 #[test]
 fn test_update_config() {
     let env = Env::default();
+    let contract_id = env.register(CounterContract, ());
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDC");
     let pair = (xlm.clone(), usdc.clone());
 
-    setup_oracle(&env, pair.clone());
+    env.as_contract(&contract_id, || setup_oracle(&env, pair.clone()));
 
     // Update configuration
-    OracleAdapter::update_config(
-        &env,
-        pair.clone(),
-        Some(600), // 10 minutes staleness
-        Some(500), // 5% circuit breaker
-        Some(20),  // TWAP window of 20
-    )
-    .unwrap();
-
-    let (config, _) = OracleAdapter::get_oracle_info(&env, pair).unwrap();
+    env.as_contract(&contract_id, || {
+        OracleAdapter::update_config(
+            &env,
+            pair.clone(),
+            Some(600), // 10 minutes staleness
+            Some(500), // 5% circuit breaker
+            Some(20),  // TWAP window of 20
+        )
+        .unwrap()
+    });
+
+    let (config, _) = env.as_contract(&contract_id, || {
+        OracleAdapter::get_oracle_info(&env, pair).unwrap()
+    });
 
     assert_eq!(config.staleness_threshold, 600);
     assert_eq!(config.circuit_breaker_threshold_bps, 500);
@@ -184,14 +216,17 @@ fn test_update_config() {
 #[test]
 fn test_invalid_config_rejected() {
     let env = Env::default();
+    let contract_id = env.register(CounterContract, ());
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDC");
     let pair = (xlm.clone(), usdc.clone());
 
-    setup_oracle(&env, pair.clone());
+    env.as_contract(&contract_id, || setup_oracle(&env, pair.clone()));
 
     // Try to set invalid TWAP window size (0)
-    let result = OracleAdapter::update_config(&env, pair.clone(), None, None, Some(0));
+    let result = env.as_contract(&contract_id, || {
+        OracleAdapter::update_config(&env, pair.clone(), None, None, Some(0))
+    });
 
     assert!(result.is_err());
     assert_eq!(result.unwrap_err(), ContractError::InvalidConfig);
@@ -200,20 +235,25 @@ fn test_invalid_config_rejected() {
 #[test]
 fn test_set_oracle_active() {
     let env = Env::default();
+    let contract_id = env.register(CounterContract, ());
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDC");
     let pair = (xlm.clone(), usdc.clone());
 
-    setup_oracle(&env, pair.clone());
+    env.as_contract(&contract_id, || setup_oracle(&env, pair.clone()));
 
     // Deactivate oracle
-    OracleAdapter::set_oracle_active(&env, pair.clone(), false).unwrap();
+    env.as_contract(&contract_id, || {
+        OracleAdapter::set_oracle_active(&env, pair.clone(), false).unwrap()
+    });
 
-    let (config, _) = OracleAdapter::get_oracle_info(&env, pair.clone()).unwrap();
+    let (config, _) = env.as_contract(&contract_id, || {
+        OracleAdapter::get_oracle_info(&env, pair.clone()).unwrap()
+    });
     assert!(!config.is_active);
 
     // Getting price should fail
-    let result = OracleAdapter::get_price(&env, pair);
+    let result = env.as_contract(&contract_id, || OracleAdapter::get_price(&env, pair));
     assert!(result.is_err());
     assert_eq!(result.unwrap_err(), ContractError::OracleNotActive);
 }
@@ -221,36 +261,46 @@ fn test_set_oracle_active() {
 #[test]
 fn test_reset_circuit_breaker() {
     let env = Env::default();
+    let contract_id = env.register(CounterContract, ());
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDC");
     let pair = (xlm.clone(), usdc.clone());
 
-    setup_oracle(&env, pair.clone());
+    env.as_contract(&contract_id, || setup_oracle(&env, pair.clone()));
 
     // Trigger circuit breaker
     let new_price = (PRECISION as u128).saturating_mul(11_500) / 10_000;
-    let _ = OracleAdapter::update_price(&env, pair.clone(), new_price);
+    let _ = env.as_contract(&contract_id, || {
+        OracleAdapter::update_price(&env, pair.clone(), new_price)
+    });
 
-    let (_, state) = OracleAdapter::get_oracle_info(&env, pair.clone()).unwrap();
+    let (_, state) = env.as_contract(&contract_id, || {
+        OracleAdapter::get_oracle_info(&env, pair.clone()).unwrap()
+    });
     assert!(state.circuit_breaker_active);
 
     // Reset circuit breaker
-    OracleAdapter::reset_circuit_breaker(&env, pair.clone()).unwrap();
+    env.as_contract(&contract_id, || {
+        OracleAdapter::reset_circuit_breaker(&env, pair.clone()).unwrap()
+    });
 
-    let (_, state) = OracleAdapter::get_oracle_info(&env, pair).unwrap();
+    let (_, state) = env.as_contract(&contract_id, || {
+        OracleAdapter::get_oracle_info(&env, pair).unwrap()
+    });
     assert!(!state.circuit_breaker_active);
 }
 
 #[test]
 fn test_zero_price_rejected() {
     let env = Env::default();
+    let contract_id = env.register(CounterContract, ());
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDC");
     let pair = (xlm.clone(), usdc.clone());
 
-    setup_oracle(&env, pair.clone());
+    env.as_contract(&contract_id, || setup_oracle(&env, pair.clone()));
 
-    let result = OracleAdapter::update_price(&env, pair, 0);
+    let result = env.as_contract(&contract_id, || OracleAdapter::update_price(&env, pair, 0));
     assert!(result.is_err());
     assert_eq!(result.unwrap_err(), ContractError::InvalidPrice);
 }
@@ -258,24 +308,33 @@ fn test_zero_price_rejected() {
 #[test]
 fn test_circuit_breaker_deactivates_on_stable_price() {
     let env = Env::default();
+    let contract_id = env.register(CounterContract, ());
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDC");
     let pair = (xlm.clone(), usdc.clone());
 
-    setup_oracle(&env, pair.clone());
+    env.as_contract(&contract_id, || setup_oracle(&env, pair.clone()));
 
     // Trigger circuit breaker with 15% deviation
     let high_price = (PRECISION as u128).saturating_mul(11_500) / 10_000;
-    let _ = OracleAdapter::update_price(&env, pair.clone(), high_price);
+    let _ = env.as_contract(&contract_id, || {
+        OracleAdapter::update_price(&env, pair.clone(), high_price)
+    });
 
-    let (_, state) = OracleAdapter::get_oracle_info(&env, pair.clone()).unwrap();
+    let (_, state) = env.as_contract(&contract_id, || {
+        OracleAdapter::get_oracle_info(&env, pair.clone()).unwrap()
+    });
     assert!(state.circuit_breaker_active);
 
     // Update with price within 5% of fallback (50% of threshold)
     let stable_price = (PRECISION as u128).saturating_mul(10_300) / 10_000;
-    OracleAdapter::update_price(&env, pair.clone(), stable_price).unwrap();
+    env.as_contract(&contract_id, || {
+        OracleAdapter::update_price(&env, pair.clone(), stable_price).unwrap()
+    });
 
-    let (_, state) = OracleAdapter::get_oracle_info(&env, pair).unwrap();
+    let (_, state) = env.as_contract(&contract_id, || {
+        OracleAdapter::get_oracle_info(&env, pair).unwrap()
+    });
     // Circuit breaker should still be active (3% deviation is within 5%)
     assert!(!state.circuit_breaker_active);
 }
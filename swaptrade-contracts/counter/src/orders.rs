@@ -50,7 +50,6 @@ pub struct Order {
     pub created_at: u64,
     pub expires_at: Option<u64>, // None means no expiry
     pub filled_at: Option<u64>,
-    pub interval_secs: Option<u64>, // For recurring: seconds between executions
     // Keep existing fields for backwards compatibility
     pub token_in: Symbol,
     pub token_out: Symbol,
@@ -215,7 +214,7 @@ impl OrderManager {
 
     /// Place a limit order with proper side specification
     /// Places an order on the orderbook with time-price priority
-    pub fn place_limit_order(
+    pub fn place_book_order(
         env: &Env,
         owner: Address,
         base_token: Symbol,
@@ -359,11 +358,15 @@ impl OrderManager {
             }
 
             if let Some(order_id) = candidate_orders.get(i) {
-                if let Ok(mut order) = Self::get_order(env, *order_id) {
+                if let Ok(mut order) = Self::get_order(env, order_id) {
                     // Skip expired, cancelled, or already filled orders
                     if order.status != OrderStatus::Pending && order.status != OrderStatus::PartiallyFilled {
                         continue;
                     }
+                    // Stop-loss orders only trigger through `match_pending_orders`
+                    if order.order_type != OrderType::Limit {
+                        continue;
+                    }
 
                     // Check expiry
                     if let Some(expires) = order.expires_at {
@@ -406,7 +409,7 @@ impl OrderManager {
                         order.status = OrderStatus::Filled;
                         order.filled_at = Some(current_time);
                         // Remove from orderbook
-                        if let Some(pos) = updated_orders.first_index_of(*order_id) {
+                        if let Some(pos) = updated_orders.first_index_of(order_id) {
                             updated_orders.remove(pos);
                         }
                     } else {
@@ -417,7 +420,7 @@ impl OrderManager {
 
                     // Record fill
                     fills.push_back(FillResult {
-                        order_id: *order_id,
+                        order_id,
                         filled_amount_base: fill_amount,
                         filled_amount_quote: fill_amount_quote as i128,
                         price: order.price,
@@ -428,7 +431,7 @@ impl OrderManager {
 
                     // Emit fill event
                     env.events().publish(
-                        (symbol_short!("fill"), *order_id),
+                        (symbol_short!("fill"), order_id),
                         (taker.clone(), order.owner, fill_amount, fill_amount_quote as i128, order.price),
                     );
                 }
@@ -471,7 +474,7 @@ impl OrderManager {
         let mut ask_counts: Map<u128, u32> = Map::new(env);
 
         // Process bid orders (buy orders) - aggregate by price
-        for &order_id in book.bids.iter() {
+        for order_id in book.bids.iter() {
             if let Ok(order) = Self::get_order(env, order_id) {
                 if order.status == OrderStatus::Pending || order.status == OrderStatus::PartiallyFilled {
                     let current = bid_levels.get(order.price).unwrap_or(0);
@@ -483,7 +486,7 @@ impl OrderManager {
         }
 
         // Process ask orders (sell orders) - aggregate by price
-        for &order_id in book.asks.iter() {
+        for order_id in book.asks.iter() {
             if let Ok(order) = Self::get_order(env, order_id) {
                 if order.status == OrderStatus::Pending || order.status == OrderStatus::PartiallyFilled {
                     let current = ask_levels.get(order.price).unwrap_or(0);
@@ -494,15 +497,10 @@ impl OrderManager {
             }
         }
 
-        // Convert to sorted vectors
+        // Map keys iterate in ascending order: bids read from the top,
+        // asks from the bottom
         let mut bids = Vec::new(env);
-        let mut ask_prices = Vec::new(env);
-        
-        // Collect bid prices, sort descending (highest first)
-        let mut bid_prices: Vec<u128> = bid_levels.keys().into_iter().collect();
-        bid_prices.sort_by(|a, b| b.cmp(a));
-        
-        for price in bid_prices.into_iter().take(max_levels as usize) {
+        for price in bid_levels.keys().iter().rev().take(max_levels as usize) {
             bids.push_back(OrderBookLevel {
                 price,
                 total_amount: bid_levels.get(price).unwrap(),
@@ -510,21 +508,8 @@ impl OrderManager {
             });
         }
 
-        // Collect ask prices, sort ascending (lowest first)
-        let mut ask_prices: Vec<u128> = ask_levels.keys().into_iter().collect();
-        ask_prices.sort();
-        
-        for price in ask_prices.into_iter().take(max_levels as usize) {
-            ask_levels.remove(price);
-            ask_counts.remove(price);
-        }
-        
-        // Collect prices again after filtering to get sorted list
-        let mut ask_prices: Vec<u128> = ask_levels.keys().into_iter().collect();
-        ask_prices.sort();
-        
         let mut asks = Vec::new(env);
-        for price in ask_prices.into_iter().take(max_levels as usize) {
+        for price in ask_levels.keys().iter().take(max_levels as usize) {
             asks.push_back(OrderBookLevel {
                 price,
                 total_amount: ask_levels.get(price).unwrap(),
@@ -676,10 +661,18 @@ impl OrderManager {
             .get(&symbol_short!("next_oid"))
             .unwrap_or(1);
 
+        // Legacy orders rest on the bid side of the (token_in, token_out)
+        // book, which is where `match_pending_orders` looks for them
         let order = Order {
             order_id: next_id,
             owner: owner.clone(),
             order_type: order_type.clone(),
+            side: OrderSide::Buy,
+            base_token: token_in.clone(),
+            quote_token: token_out.clone(),
+            amount: amount_in,
+            amount_remaining: amount_in,
+            price: limit_price.or(trigger_price).unwrap_or(0),
             token_in: token_in.clone(),
             token_out: token_out.clone(),
             amount_in,
@@ -710,7 +703,7 @@ impl OrderManager {
             .set(&Self::user_orders_key(&owner), &user_orders);
 
         // Add to order book
-        Self::add_to_order_book(env, token_in.clone(), token_out.clone(), next_id);
+        Self::add_to_order_book(env, token_in.clone(), token_out.clone(), order);
 
         // Increment next order ID
         env.storage()
@@ -773,6 +766,12 @@ impl OrderManager {
             order_id: next_id,
             owner: owner.clone(),
             order_type: OrderType::Recurring,
+            side: OrderSide::Buy,
+            base_token: token_in.clone(),
+            quote_token: token_out.clone(),
+            amount: amount_in,
+            amount_remaining: amount_in,
+            price: 0,
             token_in: token_in.clone(),
             token_out: token_out.clone(),
             amount_in,
@@ -801,8 +800,7 @@ impl OrderManager {
             .instance()
             .set(&Self::user_orders_key(&owner), &user_orders);
 
-        // Add to order book
-        Self::add_to_order_book(env, token_in.clone(), token_out.clone(), next_id);
+        // Recurring orders run from the recurring list, not the order book
 
         // Increment next order ID
         env.storage()
@@ -951,11 +949,11 @@ impl OrderManager {
             OrderSide::Buy => {
                 // Bids (buy orders) are sorted: higher prices first, same price: earlier orders first
                 let mut insert_idx = book.bids.len();
-                for (i, &existing_id) in book.bids.iter().enumerate() {
+                for (i, existing_id) in book.bids.iter().enumerate() {
                     if let Ok(existing_order) = Self::get_order(env, existing_id) {
                         // If current order's price is higher, insert before this one
                         if order.price > existing_order.price {
-                            insert_idx = i;
+                            insert_idx = i as u32;
                             break;
                         } else if order.price == existing_order.price {
                             // Same price, maintain time priority (add after older orders)
@@ -963,16 +961,16 @@ impl OrderManager {
                         }
                     }
                 }
-                book.bids.insert(insert_idx as u32, order.order_id);
+                book.bids.insert(insert_idx, order.order_id);
             },
             OrderSide::Sell => {
                 // Asks (sell orders) are sorted: lower prices first, same price: earlier orders first
                 let mut insert_idx = book.asks.len();
-                for (i, &existing_id) in book.asks.iter().enumerate() {
+                for (i, existing_id) in book.asks.iter().enumerate() {
                     if let Ok(existing_order) = Self::get_order(env, existing_id) {
                         // If current order's price is lower, insert before this one
                         if order.price < existing_order.price {
-                            insert_idx = i;
+                            insert_idx = i as u32;
                             break;
                         } else if order.price == existing_order.price {
                             // Same price, maintain time priority (add after older orders)
@@ -980,7 +978,7 @@ impl OrderManager {
                         }
                     }
                 }
-                book.asks.insert(insert_idx as u32, order.order_id);
+                book.asks.insert(insert_idx, order.order_id);
             }
         }
 
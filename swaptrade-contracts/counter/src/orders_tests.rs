@@ -2,7 +2,8 @@
 
 use super::*;
 use crate::errors::ContractError;
-use crate::orders::{OrderManager, OrderStatus, OrderType};
+use crate::orders::{OrderManager, OrderSide, OrderStatus, OrderType};
+use crate::CounterContract;
 use soroban_sdk::testutils::Address as _;
 use soroban_sdk::{symbol_short, Address, Env};
 
@@ -11,26 +12,32 @@ const PRECISION: u128 = 1_000_000_000_000_000_000;
 #[test]
 fn test_place_limit_order() {
     let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
     let user = Address::generate(&env);
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDC");
 
     // Place limit order
-    let order_id = OrderManager::place_limit_order(
-        &env,
-        user.clone(),
-        xlm.clone(),
-        usdc.clone(),
-        1000,
-        PRECISION, // 1:1 price
-        None,      // No expiry
-    )
-    .unwrap();
+    let order_id = env.as_contract(&contract_id, || {
+        OrderManager::place_limit_order(
+            &env,
+            user.clone(),
+            xlm.clone(),
+            usdc.clone(),
+            1000,
+            PRECISION, // 1:1 price
+            None,      // No expiry
+        )
+        .unwrap()
+    });
 
     assert_eq!(order_id, 1);
 
     // Verify order was created
-    let order = OrderManager::get_order(&env, order_id).unwrap();
+    let order = env.as_contract(&contract_id, || {
+        OrderManager::get_order(&env, order_id).unwrap()
+    });
     assert_eq!(order.order_id, 1);
     assert_eq!(order.owner, user);
     assert_eq!(order.order_type, OrderType::Limit);
@@ -44,27 +51,33 @@ fn test_place_limit_order() {
 #[test]
 fn test_place_stop_loss() {
     let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
     let user = Address::generate(&env);
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDC");
 
     // Place stop-loss order
     let trigger_price = (PRECISION as u128).saturating_mul(9_500) / 10_000; // 5% below
-    let order_id = OrderManager::place_stop_loss(
-        &env,
-        user.clone(),
-        xlm.clone(),
-        usdc.clone(),
-        500,
-        trigger_price,
-        None,
-    )
-    .unwrap();
+    let order_id = env.as_contract(&contract_id, || {
+        OrderManager::place_stop_loss(
+            &env,
+            user.clone(),
+            xlm.clone(),
+            usdc.clone(),
+            500,
+            trigger_price,
+            None,
+        )
+        .unwrap()
+    });
 
     assert_eq!(order_id, 1);
 
     // Verify order
-    let order = OrderManager::get_order(&env, order_id).unwrap();
+    let order = env.as_contract(&contract_id, || {
+        OrderManager::get_order(&env, order_id).unwrap()
+    });
     assert_eq!(order.order_type, OrderType::StopLoss);
     assert_eq!(order.trigger_price, Some(trigger_price));
 }
@@ -72,186 +85,229 @@ fn test_place_stop_loss() {
 #[test]
 fn test_cancel_order() {
     let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
     let user = Address::generate(&env);
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDC");
 
     // Place order
-    let order_id =
+    let order_id = env.as_contract(&contract_id, || {
         OrderManager::place_limit_order(&env, user.clone(), xlm, usdc, 1000, PRECISION, None)
-            .unwrap();
+            .unwrap()
+    });
 
     // Cancel order
-    OrderManager::cancel_order(&env, order_id, user.clone()).unwrap();
+    env.as_contract(&contract_id, || {
+        OrderManager::cancel_order(&env, order_id, user.clone()).unwrap()
+    });
 
     // Verify status
-    let order = OrderManager::get_order(&env, order_id).unwrap();
+    let order = env.as_contract(&contract_id, || {
+        OrderManager::get_order(&env, order_id).unwrap()
+    });
     assert_eq!(order.status, OrderStatus::Cancelled);
 }
 
 #[test]
 fn test_cancel_order_wrong_owner() {
     let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
     let user1 = Address::generate(&env);
     let user2 = Address::generate(&env);
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDC");
 
     // Place order with user1
-    let order_id =
+    let order_id = env.as_contract(&contract_id, || {
         OrderManager::place_limit_order(&env, user1.clone(), xlm, usdc, 1000, PRECISION, None)
-            .unwrap();
+            .unwrap()
+    });
 
     // Try to cancel with user2 (should fail)
-    let result = OrderManager::cancel_order(&env, order_id, user2);
+    let result = env.as_contract(&contract_id, || {
+        OrderManager::cancel_order(&env, order_id, user2)
+    });
     assert!(result.is_err());
 }
 
 #[test]
 fn test_get_user_orders() {
     let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
     let user = Address::generate(&env);
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDC");
 
     // Place multiple orders
-    OrderManager::place_limit_order(
-        &env,
-        user.clone(),
-        xlm.clone(),
-        usdc.clone(),
-        1000,
-        PRECISION,
-        None,
-    )
-    .unwrap();
-    OrderManager::place_stop_loss(
-        &env,
-        user.clone(),
-        xlm.clone(),
-        usdc.clone(),
-        500,
-        PRECISION,
-        None,
-    )
-    .unwrap();
+    env.as_contract(&contract_id, || {
+        OrderManager::place_limit_order(
+            &env,
+            user.clone(),
+            xlm.clone(),
+            usdc.clone(),
+            1000,
+            PRECISION,
+            None,
+        )
+        .unwrap()
+    });
+    env.as_contract(&contract_id, || {
+        OrderManager::place_stop_loss(
+            &env,
+            user.clone(),
+            xlm.clone(),
+            usdc.clone(),
+            500,
+            PRECISION,
+            None,
+        )
+        .unwrap()
+    });
 
     // Get user orders
-    let orders = OrderManager::get_user_orders(&env, user);
+    let orders = env.as_contract(&contract_id, || OrderManager::get_user_orders(&env, user));
     assert_eq!(orders.len(), 2);
 }
 
 #[test]
 fn test_order_with_expiry() {
     let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
     let user = Address::generate(&env);
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDC");
 
     // Place order with expiry (1 hour from now)
-    let expiry = env.ledger().timestamp() + 3600;
-    let order_id = OrderManager::place_limit_order(
-        &env,
-        user.clone(),
-        xlm,
-        usdc,
-        1000,
-        PRECISION,
-        Some(expiry),
-    )
-    .unwrap();
-
-    let order = OrderManager::get_order(&env, order_id).unwrap();
+    let expiry = env.as_contract(&contract_id, || env.ledger().timestamp() + 3600);
+    let order_id = env.as_contract(&contract_id, || {
+        OrderManager::place_limit_order(
+            &env,
+            user.clone(),
+            xlm,
+            usdc,
+            1000,
+            PRECISION,
+            Some(expiry),
+        )
+        .unwrap()
+    });
+
+    let order = env.as_contract(&contract_id, || {
+        OrderManager::get_order(&env, order_id).unwrap()
+    });
     assert_eq!(order.expires_at, Some(expiry));
 }
 
 #[test]
 fn test_invalid_order_amount() {
     let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
     let user = Address::generate(&env);
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDC");
 
     // Try to place order with zero amount
-    let result = OrderManager::place_limit_order(
-        &env,
-        user.clone(),
-        xlm.clone(),
-        usdc.clone(),
-        0,
-        PRECISION,
-        None,
-    );
+    let result = env.as_contract(&contract_id, || {
+        OrderManager::place_limit_order(
+            &env,
+            user.clone(),
+            xlm.clone(),
+            usdc.clone(),
+            0,
+            PRECISION,
+            None,
+        )
+    });
     assert!(result.is_err());
 
     // Try to place order with negative amount
-    let result =
-        OrderManager::place_stop_loss(&env, user.clone(), xlm, usdc, -100, PRECISION, None);
+    let result = env.as_contract(&contract_id, || {
+        OrderManager::place_stop_loss(&env, user.clone(), xlm, usdc, -100, PRECISION, None)
+    });
     assert!(result.is_err());
 }
 
 #[test]
 fn test_invalid_order_price() {
     let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
     let user = Address::generate(&env);
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDC");
 
     // Try to place limit order with zero price
-    let result = OrderManager::place_limit_order(
-        &env,
-        user.clone(),
-        xlm.clone(),
-        usdc.clone(),
-        1000,
-        0,
-        None,
-    );
+    let result = env.as_contract(&contract_id, || {
+        OrderManager::place_limit_order(
+            &env,
+            user.clone(),
+            xlm.clone(),
+            usdc.clone(),
+            1000,
+            0,
+            None,
+        )
+    });
     assert!(result.is_err());
 
     // Try to place stop-loss with zero trigger
-    let result = OrderManager::place_stop_loss(&env, user, xlm, usdc, 500, 0, None);
+    let result = env.as_contract(&contract_id, || {
+        OrderManager::place_stop_loss(&env, user, xlm, usdc, 500, 0, None)
+    });
     assert!(result.is_err());
 }
 
 #[test]
 fn test_order_id_increment() {
     let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
     let user = Address::generate(&env);
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDC");
 
     // Place multiple orders
-    let id1 = OrderManager::place_limit_order(
-        &env,
-        user.clone(),
-        xlm.clone(),
-        usdc.clone(),
-        100,
-        PRECISION,
-        None,
-    )
-    .unwrap();
-    let id2 = OrderManager::place_limit_order(
-        &env,
-        user.clone(),
-        xlm.clone(),
-        usdc.clone(),
-        200,
-        PRECISION,
-        None,
-    )
-    .unwrap();
-    let id3 = OrderManager::place_stop_loss(
-        &env,
-        user.clone(),
-        xlm.clone(),
-        usdc.clone(),
-        300,
-        PRECISION,
-        None,
-    )
-    .unwrap();
+    let id1 = env.as_contract(&contract_id, || {
+        OrderManager::place_limit_order(
+            &env,
+            user.clone(),
+            xlm.clone(),
+            usdc.clone(),
+            100,
+            PRECISION,
+            None,
+        )
+        .unwrap()
+    });
+    let id2 = env.as_contract(&contract_id, || {
+        OrderManager::place_limit_order(
+            &env,
+            user.clone(),
+            xlm.clone(),
+            usdc.clone(),
+            200,
+            PRECISION,
+            None,
+        )
+        .unwrap()
+    });
+    let id3 = env.as_contract(&contract_id, || {
+        OrderManager::place_stop_loss(
+            &env,
+            user.clone(),
+            xlm.clone(),
+            usdc.clone(),
+            300,
+            PRECISION,
+            None,
+        )
+        .unwrap()
+    });
 
     assert_eq!(id1, 1);
     assert_eq!(id2, 2);
@@ -261,57 +317,71 @@ fn test_order_id_increment() {
 #[test]
 fn test_match_pending_orders() {
     let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
     let user = Address::generate(&env);
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDC");
 
     // Place limit order at PRECISION
-    let order_id = OrderManager::place_limit_order(
-        &env,
-        user.clone(),
-        xlm.clone(),
-        usdc.clone(),
-        1000,
-        PRECISION,
-        None,
-    )
-    .unwrap();
+    let order_id = env.as_contract(&contract_id, || {
+        OrderManager::place_limit_order(
+            &env,
+            user.clone(),
+            xlm.clone(),
+            usdc.clone(),
+            1000,
+            PRECISION,
+            None,
+        )
+        .unwrap()
+    });
 
     // Match orders with current price at or below limit
     let current_price = (PRECISION as u128).saturating_mul(9_900) / 10_000; // 1% below limit
-    let executed =
-        OrderManager::match_pending_orders(&env, xlm.clone(), usdc.clone(), current_price).unwrap();
+    let executed = env.as_contract(&contract_id, || {
+        OrderManager::match_pending_orders(&env, xlm.clone(), usdc.clone(), current_price).unwrap()
+    });
 
     // Order should be executed
     assert!(executed.len() > 0);
 
-    let order = OrderManager::get_order(&env, order_id).unwrap();
+    let order = env.as_contract(&contract_id, || {
+        OrderManager::get_order(&env, order_id).unwrap()
+    });
     assert_eq!(order.status, OrderStatus::Filled);
 }
 
 #[test]
 fn test_new_place_limit_order() {
     let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
     let maker = Address::generate(&env);
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDC");
 
     // Place limit sell order (sell XLM for USDC at 1.0 USDC/XLM)
-    let order_id = OrderManager::place_limit_order(
-        &env,
-        maker.clone(),
-        xlm.clone(),
-        usdc.clone(),
-        OrderSide::Sell,
-        1000, // 1000 XLM
-        PRECISION, // 1 USDC per XLM
-        None,
-    ).unwrap();
+    let order_id = env.as_contract(&contract_id, || {
+        OrderManager::place_book_order(
+            &env,
+            maker.clone(),
+            xlm.clone(),
+            usdc.clone(),
+            OrderSide::Sell,
+            1000,      // 1000 XLM
+            PRECISION, // 1 USDC per XLM
+            None,
+        )
+        .unwrap()
+    });
 
     assert_eq!(order_id, 1);
 
     // Verify order was created correctly
-    let order = OrderManager::get_order(&env, order_id).unwrap();
+    let order = env.as_contract(&contract_id, || {
+        OrderManager::get_order(&env, order_id).unwrap()
+    });
     assert_eq!(order.order_id, 1);
     assert_eq!(order.owner, maker);
     assert_eq!(order.side, OrderSide::Sell);
@@ -326,33 +396,41 @@ fn test_new_place_limit_order() {
 #[test]
 fn test_take_order_full_fill() {
     let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
     let maker = Address::generate(&env);
     let taker = Address::generate(&env);
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDC");
 
     // Maker places a limit sell order: sell 1000 XLM at 1.0 USDC/XLM
-    let order_id = OrderManager::place_limit_order(
-        &env,
-        maker.clone(),
-        xlm.clone(),
-        usdc.clone(),
-        OrderSide::Sell,
-        1000,
-        PRECISION,
-        None,
-    ).unwrap();
+    let order_id = env.as_contract(&contract_id, || {
+        OrderManager::place_book_order(
+            &env,
+            maker.clone(),
+            xlm.clone(),
+            usdc.clone(),
+            OrderSide::Sell,
+            1000,
+            PRECISION,
+            None,
+        )
+        .unwrap()
+    });
 
     // Taker places a buy order for 1000 XLM, accepts any price
-    let fills = OrderManager::take_order(
-        &env,
-        taker.clone(),
-        xlm.clone(),
-        usdc.clone(),
-        OrderSide::Buy,
-        1000,
-        None,
-    ).unwrap();
+    let fills = env.as_contract(&contract_id, || {
+        OrderManager::take_order(
+            &env,
+            taker.clone(),
+            xlm.clone(),
+            usdc.clone(),
+            OrderSide::Buy,
+            1000,
+            None,
+        )
+        .unwrap()
+    });
 
     // Verify single full fill
     assert_eq!(fills.len(), 1);
@@ -365,7 +443,9 @@ fn test_take_order_full_fill() {
     assert_eq!(fill.taker, taker);
 
     // Verify order is now filled
-    let order = OrderManager::get_order(&env, order_id).unwrap();
+    let order = env.as_contract(&contract_id, || {
+        OrderManager::get_order(&env, order_id).unwrap()
+    });
     assert_eq!(order.status, OrderStatus::Filled);
     assert_eq!(order.amount_remaining, 0);
     assert_eq!(order.amount_filled, 1000);
@@ -374,33 +454,41 @@ fn test_take_order_full_fill() {
 #[test]
 fn test_take_order_partial_fill() {
     let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
     let maker = Address::generate(&env);
     let taker = Address::generate(&env);
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDC");
 
     // Maker places a limit sell order: sell 1000 XLM at 1.0 USDC/XLM
-    let order_id = OrderManager::place_limit_order(
-        &env,
-        maker.clone(),
-        xlm.clone(),
-        usdc.clone(),
-        OrderSide::Sell,
-        1000,
-        PRECISION,
-        None,
-    ).unwrap();
+    let order_id = env.as_contract(&contract_id, || {
+        OrderManager::place_book_order(
+            &env,
+            maker.clone(),
+            xlm.clone(),
+            usdc.clone(),
+            OrderSide::Sell,
+            1000,
+            PRECISION,
+            None,
+        )
+        .unwrap()
+    });
 
     // Taker only buys 400 XLM (partial fill)
-    let fills = OrderManager::take_order(
-        &env,
-        taker.clone(),
-        xlm.clone(),
-        usdc.clone(),
-        OrderSide::Buy,
-        400,
-        None,
-    ).unwrap();
+    let fills = env.as_contract(&contract_id, || {
+        OrderManager::take_order(
+            &env,
+            taker.clone(),
+            xlm.clone(),
+            usdc.clone(),
+            OrderSide::Buy,
+            400,
+            None,
+        )
+        .unwrap()
+    });
 
     // Verify partial fill
     assert_eq!(fills.len(), 1);
@@ -409,7 +497,9 @@ fn test_take_order_partial_fill() {
     assert_eq!(fill.is_complete_fill, false);
 
     // Verify order is partially filled
-    let order = OrderManager::get_order(&env, order_id).unwrap();
+    let order = env.as_contract(&contract_id, || {
+        OrderManager::get_order(&env, order_id).unwrap()
+    });
     assert_eq!(order.status, OrderStatus::PartiallyFilled);
     assert_eq!(order.amount_remaining, 600);
     assert_eq!(order.amount_filled, 400);
@@ -418,6 +508,8 @@ fn test_take_order_partial_fill() {
 #[test]
 fn test_time_price_priority() {
     let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
     let maker1 = Address::generate(&env);
     let maker2 = Address::generate(&env);
     let maker3 = Address::generate(&env);
@@ -427,52 +519,113 @@ fn test_time_price_priority() {
 
     // Place multiple sell orders with different prices
     // Maker1: sell at 1.1 USDC/XLM
-    OrderManager::place_limit_order(
-        &env, maker1.clone(), xlm.clone(), usdc.clone(), OrderSide::Sell, 500, (PRECISION*11/10), None
-    ).unwrap();
-    
+    env.as_contract(&contract_id, || {
+        OrderManager::place_book_order(
+            &env,
+            maker1.clone(),
+            xlm.clone(),
+            usdc.clone(),
+            OrderSide::Sell,
+            500,
+            PRECISION * 11 / 10,
+            None,
+        )
+        .unwrap()
+    });
+
     // Maker2: sell at 1.0 USDC/XLM (better price for taker)
-    OrderManager::place_limit_order(
-        &env, maker2.clone(), xlm.clone(), usdc.clone(), OrderSide::Sell, 500, PRECISION, None
-    ).unwrap();
-    
+    env.as_contract(&contract_id, || {
+        OrderManager::place_book_order(
+            &env,
+            maker2.clone(),
+            xlm.clone(),
+            usdc.clone(),
+            OrderSide::Sell,
+            500,
+            PRECISION,
+            None,
+        )
+        .unwrap()
+    });
+
     // Maker3: sell at 0.9 USDC/XLM (best price)
-    let order_id3 = OrderManager::place_limit_order(
-        &env, maker3.clone(), xlm.clone(), usdc.clone(), OrderSide::Sell, 500, (PRECISION*9/10), None
-    ).unwrap();
+    let order_id3 = env.as_contract(&contract_id, || {
+        OrderManager::place_book_order(
+            &env,
+            maker3.clone(),
+            xlm.clone(),
+            usdc.clone(),
+            OrderSide::Sell,
+            500,
+            PRECISION * 9 / 10,
+            None,
+        )
+        .unwrap()
+    });
 
     // Taker buys 400 XLM - should fill the lowest price first (maker3's order)
-    let fills = OrderManager::take_order(
-        &env, taker.clone(), xlm.clone(), usdc.clone(), OrderSide::Buy, 400, None
-    ).unwrap();
+    let fills = env.as_contract(&contract_id, || {
+        OrderManager::take_order(
+            &env,
+            taker.clone(),
+            xlm.clone(),
+            usdc.clone(),
+            OrderSide::Buy,
+            400,
+            None,
+        )
+        .unwrap()
+    });
 
     assert_eq!(fills.len(), 1);
     assert_eq!(fills.get(0).unwrap().order_id, order_id3);
-    assert_eq!(fills.get(0).unwrap().price, (PRECISION*9/10));
+    assert_eq!(fills.get(0).unwrap().price, PRECISION * 9 / 10);
 }
 
 #[test]
 fn test_orderbook_snapshot() {
     let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
     let maker1 = Address::generate(&env);
     let maker2 = Address::generate(&env);
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDC");
 
     // Place bid orders (buy XLM)
-    OrderManager::place_limit_order(
-        &env, maker1.clone(), xlm.clone(), usdc.clone(), OrderSide::Buy, 1000, PRECISION, None
-    ).unwrap();
-    
+    env.as_contract(&contract_id, || {
+        OrderManager::place_book_order(
+            &env,
+            maker1.clone(),
+            xlm.clone(),
+            usdc.clone(),
+            OrderSide::Buy,
+            1000,
+            PRECISION,
+            None,
+        )
+        .unwrap()
+    });
+
     // Place another bid at same price
-    OrderManager::place_limit_order(
-        &env, maker2.clone(), xlm.clone(), usdc.clone(), OrderSide::Buy, 2000, PRECISION, None
-    ).unwrap();
+    env.as_contract(&contract_id, || {
+        OrderManager::place_book_order(
+            &env,
+            maker2.clone(),
+            xlm.clone(),
+            usdc.clone(),
+            OrderSide::Buy,
+            2000,
+            PRECISION,
+            None,
+        )
+        .unwrap()
+    });
 
     // Get orderbook snapshot
-    let snapshot = OrderManager::get_orderbook_snapshot(
-        &env, xlm.clone(), usdc.clone(), 10
-    ).unwrap();
+    let snapshot = env.as_contract(&contract_id, || {
+        OrderManager::get_orderbook_snapshot(&env, xlm.clone(), usdc.clone(), 10).unwrap()
+    });
 
     // Verify snapshot contains aggregated bids
     assert_eq!(snapshot.bids.len(), 1);
@@ -485,27 +638,52 @@ fn test_orderbook_snapshot() {
 #[test]
 fn test_cancel_order_frees_balance() {
     let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = env.register(CounterContract, ());
     let maker = Address::generate(&env);
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDC");
 
     // Place order
-    let order_id = OrderManager::place_limit_order(
-        &env, maker.clone(), xlm.clone(), usdc.clone(), OrderSide::Sell, 1000, PRECISION, None
-    ).unwrap();
+    let order_id = env.as_contract(&contract_id, || {
+        OrderManager::place_book_order(
+            &env,
+            maker.clone(),
+            xlm.clone(),
+            usdc.clone(),
+            OrderSide::Sell,
+            1000,
+            PRECISION,
+            None,
+        )
+        .unwrap()
+    });
 
     // Cancel order
-    OrderManager::cancel_order(&env, order_id, maker.clone()).unwrap();
+    env.as_contract(&contract_id, || {
+        OrderManager::cancel_order(&env, order_id, maker.clone()).unwrap()
+    });
 
     // Verify order is cancelled
-    let order = OrderManager::get_order(&env, order_id).unwrap();
+    let order = env.as_contract(&contract_id, || {
+        OrderManager::get_order(&env, order_id).unwrap()
+    });
     assert_eq!(order.status, OrderStatus::Cancelled);
-    
+
     // Verify it's not in the orderbook anymore (won't be filled in future takes)
     let taker = Address::generate(&env);
-    let fills = OrderManager::take_order(
-        &env, taker.clone(), xlm.clone(), usdc.clone(), OrderSide::Buy, 1000, None
-    ).unwrap();
-    
+    let fills = env.as_contract(&contract_id, || {
+        OrderManager::take_order(
+            &env,
+            taker.clone(),
+            xlm.clone(),
+            usdc.clone(),
+            OrderSide::Buy,
+            1000,
+            None,
+        )
+        .unwrap()
+    });
+
     assert_eq!(fills.len(), 0); // No orders left to fill
-}
\ No newline at end of file
+}
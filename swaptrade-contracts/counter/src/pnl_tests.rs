@@ -5,12 +5,14 @@
 mod pnl_tests {
     use crate::errors::ContractError;
     use crate::{CounterContract, CounterContractClient};
+    use soroban_sdk::testutils::Address as _;
     use soroban_sdk::{symbol_short, Address, Env};
 
     const PRECISION: u128 = 1_000_000_000_000_000_000; // 1e18, matches trading.rs
 
     fn setup() -> (Env, CounterContractClient<'static>, Address) {
         let env = Env::default();
+        env.mock_all_auths();
         let contract_id = env.register(CounterContract, ());
         let client = CounterContractClient::new(&env, &contract_id);
         let user = Address::generate(&env);
@@ -120,6 +122,7 @@ mod pnl_tests {
     #[test]
     fn unknown_price_is_rejected() {
         let env = Env::default();
+        env.mock_all_auths();
         let contract_id = env.register(CounterContract, ());
         let client = CounterContractClient::new(&env, &contract_id);
         let user = Address::generate(&env);
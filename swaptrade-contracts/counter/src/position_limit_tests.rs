@@ -0,0 +1,132 @@
+#![cfg(test)]
+
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{symbol_short, Address, Env};
+
+use crate::errors::SwapTradeError;
+use crate::governance_params::{GovernanceParams, ParamKey};
+use crate::portfolio::{Asset, Portfolio};
+use crate::risk_management::{PositionLimits, RiskConfig};
+use crate::tiers::UserTier;
+use crate::{CounterContract, CounterContractClient};
+
+struct Setup {
+    env: Env,
+    contract_id: Address,
+    client: CounterContractClient<'static>,
+}
+
+fn setup() -> Setup {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+    Setup {
+        env,
+        contract_id,
+        client,
+    }
+}
+
+impl Setup {
+    fn apply(&self, param: ParamKey, value: i128) -> Result<(), SwapTradeError> {
+        self.env.as_contract(&self.contract_id, || {
+            GovernanceParams::apply_param_update(&self.env, param, value)
+        })
+    }
+
+    fn credit(&self, user: &Address, asset: Asset, amount: i128) {
+        self.env.as_contract(&self.contract_id, || {
+            let mut portfolio: Portfolio = self
+                .env
+                .storage()
+                .instance()
+                .get(&())
+                .unwrap_or_else(|| Portfolio::new(&self.env));
+            portfolio.credit(&self.env, asset, user.clone(), amount);
+            self.env.storage().instance().set(&(), &portfolio);
+        });
+    }
+}
+
+#[test]
+fn test_asset_override_replaces_base_limit() {
+    let s = setup();
+    let user = Address::generate(&s.env);
+    let xlm = symbol_short!("XLM");
+    let usdc = symbol_short!("USDCSIM");
+    let base = RiskConfig::default().max_position_per_asset;
+
+    // Novices get 10% of the base.
+    assert_eq!(s.client.get_position_limit(&user, &usdc), base / 10);
+
+    s.apply(ParamKey::AssetPositionLimit(usdc.clone()), base * 4)
+        .unwrap();
+    assert_eq!(s.client.get_position_limit(&user, &usdc), base * 4 / 10);
+    assert_eq!(s.client.get_position_limit(&user, &xlm), base / 10);
+
+    s.apply(ParamKey::MaxPositionPerAsset, base / 2).unwrap();
+    assert_eq!(s.client.get_position_limit(&user, &xlm), base / 20);
+    s.env.as_contract(&s.contract_id, || {
+        assert_eq!(
+            PositionLimits::get_risk_config(&s.env).max_position_per_asset,
+            base / 2
+        );
+    });
+}
+
+#[test]
+fn test_tier_shares_keep_tier_order() {
+    let s = setup();
+    let user = Address::generate(&s.env);
+    let xlm = symbol_short!("XLM");
+    let base = RiskConfig::default().max_position_per_asset;
+
+    s.apply(ParamKey::TierPositionLimitBps(UserTier::Novice), 2_000)
+        .unwrap();
+    assert_eq!(s.client.get_position_limit(&user, &xlm), base / 5);
+
+    // Novices may not be allowed more than traders (25%).
+    assert_eq!(
+        s.apply(ParamKey::TierPositionLimitBps(UserTier::Novice), 3_000),
+        Err(SwapTradeError::InvalidAmount)
+    );
+    // Nor may a tier exceed the base.
+    assert_eq!(
+        s.apply(ParamKey::TierPositionLimitBps(UserTier::Whale), 10_001),
+        Err(SwapTradeError::InvalidAmount)
+    );
+    s.env.as_contract(&s.contract_id, || {
+        assert_eq!(
+            PositionLimits::tier_limit_bps(&s.env, &UserTier::Novice),
+            2_000
+        );
+        assert_eq!(
+            PositionLimits::tier_limit_bps(&s.env, &UserTier::Trader),
+            2_500
+        );
+    });
+}
+
+#[test]
+fn test_open_interest_cap_spans_all_users() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+    let xlm = symbol_short!("XLM");
+    s.credit(&alice, Asset::XLM, 600);
+    s.credit(&bob, Asset::XLM, 300);
+
+    assert_eq!(s.client.get_open_interest(&xlm), (900, None));
+    assert!(!s.client.check_risk_limits(&bob, &xlm, &10_000));
+
+    s.apply(ParamKey::OpenInterestCap(xlm.clone()), 1_000)
+        .unwrap();
+    assert_eq!(s.client.get_open_interest(&xlm), (900, Some(1_000)));
+    assert!(!s.client.check_risk_limits(&bob, &xlm, &100));
+    assert!(s.client.check_risk_limits(&bob, &xlm, &101));
+    // The cap is per asset.
+    assert!(!s
+        .client
+        .check_risk_limits(&bob, &symbol_short!("USDCSIM"), &10_000));
+}
@@ -146,11 +146,31 @@ proptest! {
     #[test]
     fn proptest_debit_never_exceeds_balance(
         balance in 100_i128..1_000_000_000_i128,
-        debit in 0_i128..1_000_000_000_i128,
+        debit in 1_i128..1_000_000_000_i128,
     ) {
-        if debit > balance {
-            let after = balance.saturating_sub(debit);
-            prop_assert!(after >= 0, "Saturating sub should prevent negative balance");
+        let env = Env::default();
+        let contract_id = env.register(crate::CounterContract, ());
+        let user = Address::generate(&env);
+        let after = env.as_contract(&contract_id, || {
+            let mut portfolio = Portfolio::new(&env);
+            portfolio.mint(&env, Asset::XLM, user.clone(), balance);
+            // An overdraft must be refused rather than clamped or wrapped.
+            let debited = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                let mut attempt = portfolio.clone();
+                attempt.debit(&env, Asset::XLM, user.clone(), debit);
+                attempt.balance_of(&env, Asset::XLM, user.clone())
+            }));
+            (debited.ok(), portfolio.balance_of(&env, Asset::XLM, user.clone()))
+        });
+        match after {
+            (Some(remaining), _) => {
+                prop_assert!(debit <= balance, "Overdraft of {} against {} accepted", debit, balance);
+                prop_assert_eq!(remaining, balance - debit);
+            }
+            (None, untouched) => {
+                prop_assert!(debit > balance, "Debit of {} against {} refused", debit, balance);
+                prop_assert_eq!(untouched, balance);
+            }
         }
     }
 }
@@ -188,12 +208,12 @@ proptest! {
 proptest! {
     #[test]
     fn proptest_slippage_within_bounds(
-        expected in 0_i128..1_000_000_000_i128,
+        expected in 0_u128..1_000_000_000_u128,
         slippage_pct in 0_u128..100_u128,
     ) {
         // If actual equals expected, slippage is 0 which is always within bounds
         let actual = expected;
-        let max_slippage = 10000_u128; // 100%
+        let max_slippage = 10000_u32; // 100%
         prop_assert!(
             invariant_slippage_bounds(expected, actual, max_slippage),
             "Zero slippage should always pass"
@@ -202,7 +222,7 @@ proptest! {
 
     #[test]
     fn proptest_max_slippage_allows_zero(
-        expected in 1_i128..1_000_000_000_i128,
+        expected in 1_u128..1_000_000_000_u128,
     ) {
         // Zero slippage with any max should pass
         prop_assert!(
@@ -261,7 +281,7 @@ proptest! {
     fn proptest_lp_tokens_non_negative(xlm in 1_i128..1_000_000_000_i128, usdc in 1_i128..1_000_000_000_i128) {
         let product = (xlm as u128).saturating_mul(usdc as u128);
         let lp_tokens = integer_sqrt(product);
-        prop_assert!(lp_tokens >= 0, "LP tokens should be non-negative");
+        prop_assert!(lp_tokens > 0, "LP tokens should be positive for non-empty reserves");
     }
 
     #[test]
@@ -355,11 +375,12 @@ proptest! {
         operations in prop::collection::vec(0_u8..5, 1..20),
     ) {
         let env = Env::default();
+        let contract_id = env.register(crate::CounterContract, ());
         let mut portfolio = Portfolio::new(&env);
 
         for (i, op) in operations.into_iter().enumerate() {
             let user = Address::generate(&env);
-            match op {
+            env.as_contract(&contract_id, || match op {
                 0 => {
                     let amount = ((i as i128) + 1) * 1000;
                     portfolio.mint(&env, Asset::XLM, user, amount);
@@ -381,7 +402,7 @@ proptest! {
                     portfolio.collect_fee(fee);
                 }
                 _ => {}
-            }
+            });
 
             // Verify all invariants hold after each operation
             prop_assert!(
@@ -477,7 +477,7 @@ impl SensitiveRateLimiter {
 #[cfg(test)]
 mod sensitive_tests {
     use super::*;
-    use crate::set_admin;
+    use crate::admin::set_admin;
     use soroban_sdk::{
         testutils::{Address as _, Ledger},
         Address, Env,
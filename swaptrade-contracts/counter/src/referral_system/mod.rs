@@ -90,8 +90,15 @@ pub fn register_referral(
     let info_key = DataKey::ReferralInfo(referred.clone());
     env.storage().instance().set(&info_key, &referral_info);
 
-    // Update referrer stats
+    // Update referrer stats; the referrer's own referrer gains an indirect referral
     update_referrer_stats(env, &referrer, ReferralLevel::Direct);
+    if let Some(indirect_referrer) = env
+        .storage()
+        .instance()
+        .get::<_, Address>(&DataKey::Referrer(referrer.clone()))
+    {
+        update_referrer_stats(env, &indirect_referrer, ReferralLevel::Indirect);
+    }
 
     // Emit event
     env.events().publish(
@@ -174,13 +181,11 @@ pub fn calculate_and_distribute_commission(env: &Env, trader: Address, fee_amoun
         return;
     }
 
-    // Get trader's total volume for tier calculation
+    // Update trader's volume; the tier includes the volume of this trade
+    update_user_trading_volume(env, trader.clone(), fee_amount);
     let trader_volume = get_user_trading_volume(env, trader.clone());
     let tier_config = get_tier_for_volume(env, trader_volume);
 
-    // Update trader's volume
-    update_user_trading_volume(env, trader.clone(), fee_amount);
-
     // Get direct referrer
     if let Some(direct_referrer) = env
         .storage()
@@ -271,7 +276,7 @@ pub fn withdraw_commission(env: &Env, user: Address) -> i128 {
     // Reset balance to zero before transfer (security best practice)
     env.storage()
         .instance()
-        .set(&DataKey::CommissionBalance(user.clone()), &0);
+        .set(&DataKey::CommissionBalance(user.clone()), &0i128);
 
     // Emit event
     env.events()
@@ -1,158 +1,255 @@
-use crate::oracle::{get_stored_price, ContractError};
-use crate::risk_management::{CircuitBreakerState, RiskConfig};
-use soroban_sdk::{contracttype, Address, Env, Map, Symbol, Vec};
+//! Unified circuit breaker
+//!
+//! There is one breaker per scope: `Global`, or a single asset. Any rule in
+//! `TripRule` can trip it. A tripped breaker is `Open` and halts trading in
+//! its scope. Once `cooldown_secs` have passed it reads as `HalfOpen`:
+//! trading resumes and the rule that tripped it is evaluated again. A
+//! passing evaluation closes the breaker and a breach opens it again.
+//! Admins and governance can reset a breaker at any time.
+//!
+//! Volume is checked inline on every swap (see `volume_circuit_breaker`).
+//! Price moves, oracle divergence and invariant failures are
+//! `TripCondition`s, passed to `CircuitBreaker::evaluate` by keepers.
+
+use crate::events;
+use crate::risk_management::CircuitBreakerState;
+use soroban_sdk::{contracttype, symbol_short, Env, Symbol};
+
+/// Time an open breaker stays open before it becomes half-open.
+pub const DEFAULT_COOLDOWN_SECS: u64 = 3_600;
+/// Largest gap between the two price oracles before trading halts.
+pub const DEFAULT_ORACLE_DIVERGENCE_BPS: u32 = 500;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BreakerScope {
+    Global,
+    Asset(Symbol),
+}
 
-/// Circuit breaker for extreme market moves
-pub struct CircuitBreaker;
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TripRule {
+    PriceMove,
+    Volume,
+    OracleDivergence,
+    InvariantFailure,
+    Manual,
+}
 
-impl CircuitBreaker {
-    /// Check if circuit breaker should be triggered
-    pub fn check_circuit_breaker(env: &Env, asset_symbol: &Symbol) -> Result<bool, ContractError> {
-        let config = Self::get_risk_config(env);
-        let current_time = env.ledger().timestamp();
+impl TripRule {
+    /// Short reason recorded in `CircuitBreakerState::trigger_reason`.
+    pub fn reason(&self) -> Symbol {
+        match self {
+            TripRule::PriceMove => symbol_short!("price"),
+            TripRule::Volume => symbol_short!("volume"),
+            TripRule::OracleDivergence => symbol_short!("oracle"),
+            TripRule::InvariantFailure => symbol_short!("invariant"),
+            TripRule::Manual => symbol_short!("manual"),
+        }
+    }
+}
 
-        // Get price history for the time window
-        let price_changes = Self::get_price_changes_in_window(
-            env,
-            asset_symbol,
-            current_time - config.circuit_breaker_window,
-            current_time,
-        )?;
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BreakerPhase {
+    Closed,
+    Open,
+    HalfOpen,
+}
 
-        if price_changes.is_empty() {
-            return Ok(false);
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BreakerConfig {
+    pub cooldown_secs: u64,
+    pub oracle_divergence_bps: u32,
+}
+
+impl Default for BreakerConfig {
+    fn default() -> Self {
+        Self {
+            cooldown_secs: DEFAULT_COOLDOWN_SECS,
+            oracle_divergence_bps: DEFAULT_ORACLE_DIVERGENCE_BPS,
         }
+    }
+}
 
-        // Calculate maximum price change in the window
-        let max_change_pct = Self::calculate_max_price_change(&price_changes);
+#[contracttype]
+#[derive(Clone)]
+enum CircuitBreakerKey {
+    BreakerConfig,
+    State(BreakerScope),
+}
 
-        Ok(max_change_pct >= config.circuit_breaker_threshold)
+/// What a rule observed when it breached its threshold.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Breach {
+    pub observed: i128,
+    pub threshold: i128,
+}
+
+/// A condition that can trip a breaker.
+pub trait TripCondition {
+    fn rule(&self) -> TripRule;
+    fn scope(&self) -> BreakerScope;
+    /// The breach, if the condition currently holds.
+    fn evaluate(&self, env: &Env) -> Option<Breach>;
+}
+
+/// Circuit breaker for extreme market conditions
+pub struct CircuitBreaker;
+
+impl CircuitBreaker {
+    /// Evaluate `condition` and apply the result. Returns `true` iff this
+    /// call tripped the breaker.
+    pub fn evaluate(env: &Env, condition: &impl TripCondition) -> bool {
+        Self::observe(
+            env,
+            condition.scope(),
+            condition.rule(),
+            condition.evaluate(env),
+        )
     }
 
-    /// Trigger circuit breaker
-    pub fn trigger_circuit_breaker(env: &Env, reason: Symbol, price_change_pct: u32) {
-        let mut state = Self::get_circuit_breaker_state(env);
+    /// Apply one evaluation of `rule` in `scope`. A breach trips the
+    /// breaker unless it is already open. A pass closes a half-open breaker
+    /// that `rule` tripped. Returns `true` iff this call tripped it.
+    pub fn observe(env: &Env, scope: BreakerScope, rule: TripRule, breach: Option<Breach>) -> bool {
+        let state = Self::get_state(env, &scope);
+        match breach {
+            Some(_) if state.phase == BreakerPhase::Open => false,
+            Some(breach) => {
+                Self::trip(env, scope, rule, breach);
+                true
+            }
+            None => {
+                if state.phase == BreakerPhase::HalfOpen && state.rule == rule {
+                    Self::close(env, scope, false);
+                }
+                false
+            }
+        }
+    }
+
+    /// Open the breaker for `scope` and emit the cause.
+    pub fn trip(env: &Env, scope: BreakerScope, rule: TripRule, breach: Breach) {
+        let now = env.ledger().timestamp();
+        let mut state = Self::get_state(env, &scope);
         state.is_active = true;
-        state.triggered_at = env.ledger().timestamp();
-        state.trigger_reason = reason;
-        state.price_change_pct = price_change_pct;
+        state.phase = BreakerPhase::Open;
+        state.rule = rule;
+        state.triggered_at = now;
+        state.trigger_reason = rule.reason();
+        state.observed = breach.observed;
+        state.threshold = breach.threshold;
+        Self::save_state(env, &state);
+
+        events::circuit_breaker_tripped(env, scope, rule, breach.observed, breach.threshold, now);
+    }
 
-        env.storage()
-            .instance()
-            .set(&Symbol::short("circuit"), &state);
+    /// Close the breaker for `scope` whatever its phase.
+    pub fn reset(env: &Env, scope: BreakerScope) {
+        Self::close(env, scope, true);
     }
 
-    /// Reset circuit breaker
-    pub fn reset_circuit_breaker(env: &Env) {
-        let mut state = Self::get_circuit_breaker_state(env);
+    fn close(env: &Env, scope: BreakerScope, manual: bool) {
+        let mut state = Self::get_state(env, &scope);
         state.is_active = false;
-        state.trigger_reason = Symbol::short("reset");
+        state.phase = BreakerPhase::Closed;
+        state.trigger_reason = if manual {
+            symbol_short!("reset")
+        } else {
+            symbol_short!("recovered")
+        };
         state.price_change_pct = 0;
         state.recovery_price = None;
+        Self::save_state(env, &state);
 
-        env.storage()
-            .instance()
-            .set(&Symbol::short("circuit"), &state);
+        events::circuit_breaker_closed(env, scope, manual, env.ledger().timestamp());
     }
 
-    /// Check if circuit breaker is currently active
-    pub fn is_circuit_breaker_active(env: &Env) -> bool {
-        Self::get_circuit_breaker_state(env).is_active
+    /// State of the breaker for `scope`. An open breaker whose cooldown has
+    /// elapsed is reported as half-open.
+    pub fn get_state(env: &Env, scope: &BreakerScope) -> CircuitBreakerState {
+        let mut state: CircuitBreakerState = env
+            .storage()
+            .persistent()
+            .get(&CircuitBreakerKey::State(scope.clone()))
+            .unwrap_or_else(|| CircuitBreakerState {
+                scope: scope.clone(),
+                ..Default::default()
+            });
+        let cooldown = Self::get_config(env).cooldown_secs;
+        if state.phase == BreakerPhase::Open
+            && env.ledger().timestamp() >= state.triggered_at.saturating_add(cooldown)
+        {
+            state.phase = BreakerPhase::HalfOpen;
+            state.is_active = false;
+        }
+        state
     }
 
-    /// Get circuit breaker state
-    pub fn get_circuit_breaker_state(env: &Env) -> CircuitBreakerState {
+    fn save_state(env: &Env, state: &CircuitBreakerState) {
         env.storage()
-            .instance()
-            .get(&Symbol::short("circuit"))
-            .unwrap_or_default()
+            .persistent()
+            .set(&CircuitBreakerKey::State(state.scope.clone()), state);
     }
 
-    /// Get price changes within a time window
-    fn get_price_changes_in_window(
-        env: &Env,
-        asset_symbol: &Symbol,
-        start_time: u64,
-        end_time: u64,
-    ) -> Result<Vec<(u64, u128)>, ContractError> {
-        // This is a simplified implementation
-        // In production, you'd need to store price history
-        // For now, we'll check recent prices
-
-        let mut prices = Vec::new(env);
-
-        // Try to get current price
-        if let Some(current_data) =
-            get_stored_price(env, (asset_symbol.clone(), Symbol::short("USD")))
-        {
-            if current_data.timestamp >= start_time && current_data.timestamp <= end_time {
-                prices.push_back((current_data.timestamp, current_data.price));
-            }
-        }
-
-        // Try inverse pair
-        if let Some(current_data) =
-            get_stored_price(env, (Symbol::short("USD"), asset_symbol.clone()))
-        {
-            if current_data.timestamp >= start_time && current_data.timestamp <= end_time {
-                // Invert price
-                if current_data.price > 0 {
-                    let inverted = (1_000_000_000_000_000_000u128 * 1_000_000_000_000_000_000u128)
-                        / current_data.price;
-                    prices.push_back((current_data.timestamp, inverted));
-                }
-            }
-        }
+    pub fn is_open(env: &Env, scope: &BreakerScope) -> bool {
+        Self::get_state(env, scope).phase == BreakerPhase::Open
+    }
 
-        Ok(prices)
+    /// Whether trading in `asset` is halted by the global breaker or the
+    /// asset's own.
+    pub fn is_halted(env: &Env, asset: &Symbol) -> bool {
+        Self::is_open(env, &BreakerScope::Global)
+            || Self::is_open(env, &BreakerScope::Asset(asset.clone()))
     }
 
-    /// Calculate maximum price change percentage in basis points
-    fn calculate_max_price_change(prices: &Vec<(u64, u128)>) -> u32 {
-        if prices.len() < 2 {
-            return 0;
-        }
+    pub fn get_config(env: &Env) -> BreakerConfig {
+        env.storage()
+            .persistent()
+            .get(&CircuitBreakerKey::BreakerConfig)
+            .unwrap_or_default()
+    }
 
-        let mut max_change = 0u32;
-
-        // Sort by timestamp
-        let mut sorted_prices = prices.clone();
-        // Simple bubble sort for small arrays
-        for i in 0..sorted_prices.len() {
-            for j in (i + 1)..sorted_prices.len() {
-                if sorted_prices.get(j).unwrap().0 < sorted_prices.get(i).unwrap().0 {
-                    let temp = sorted_prices.get(i).unwrap();
-                    sorted_prices.set(i, sorted_prices.get(j).unwrap());
-                    sorted_prices.set(j, temp);
-                }
-            }
-        }
+    pub fn set_config(env: &Env, config: &BreakerConfig) {
+        env.storage()
+            .persistent()
+            .set(&CircuitBreakerKey::BreakerConfig, config);
+    }
 
-        for i in 1..sorted_prices.len() {
-            let prev_price = sorted_prices.get(i - 1).unwrap().1;
-            let curr_price = sorted_prices.get(i).unwrap().1;
+    // ── Global breaker ───────────────────────────────────────────────────────
 
-            if prev_price > 0 {
-                let change = if curr_price > prev_price {
-                    ((curr_price - prev_price) * 10000) / prev_price
-                } else {
-                    ((prev_price - curr_price) * 10000) / prev_price
-                };
+    /// Trip the global breaker by hand
+    pub fn trigger_circuit_breaker(env: &Env, reason: Symbol, price_change_pct: u32) {
+        Self::trip(
+            env,
+            BreakerScope::Global,
+            TripRule::Manual,
+            Breach {
+                observed: price_change_pct as i128,
+                threshold: 0,
+            },
+        );
+        let mut state = Self::get_state(env, &BreakerScope::Global);
+        state.trigger_reason = reason;
+        state.price_change_pct = price_change_pct;
+        Self::save_state(env, &state);
+    }
 
-                if change as u32 > max_change {
-                    max_change = change as u32;
-                }
-            }
-        }
+    /// Reset the global breaker
+    pub fn reset_circuit_breaker(env: &Env) {
+        Self::reset(env, BreakerScope::Global);
+    }
 
-        max_change
+    /// Check if the global breaker is currently open
+    pub fn is_circuit_breaker_active(env: &Env) -> bool {
+        Self::is_open(env, &BreakerScope::Global)
     }
 
-    /// Get risk configuration
-    fn get_risk_config(env: &Env) -> RiskConfig {
-        env.storage()
-            .instance()
-            .get(&Symbol::short("risk_cfg"))
-            .unwrap_or_default()
+    /// Get the global breaker state
+    pub fn get_circuit_breaker_state(env: &Env) -> CircuitBreakerState {
+        Self::get_state(env, &BreakerScope::Global)
     }
 }
@@ -20,7 +20,12 @@ impl ConcentrationRisk {
         // Calculate concentration as percentage of largest position
         let max_position = xlm_value.max(usdc_value);
         let concentration_bps = ((max_position * 10000) / total_value) as u32;
+        Self::score_concentration(concentration_bps)
+    }
 
+    /// Convert the largest position's share of a portfolio (bps) to a risk
+    /// score (0-100)
+    pub fn score_concentration(concentration_bps: u32) -> u32 {
         // Convert to risk score (0-100)
         // Risk increases exponentially as concentration approaches 100%
         if concentration_bps >= 8000 {
@@ -76,6 +81,54 @@ impl ConcentrationRisk {
         concentration_bps >= config.concentration_limit_threshold
     }
 
+    /// Check if a swap of `amount_in` of `from` into `amount_out` of `to`
+    /// leaves the portfolio at or over the concentration limit and more
+    /// concentrated than before. Trades that diversify always pass, so a
+    /// user over the limit can still trade out of it.
+    pub fn swap_breaches_concentration_limit(
+        env: &Env,
+        portfolio: &Portfolio,
+        user: &Address,
+        from: &Asset,
+        amount_in: i128,
+        to: &Asset,
+        amount_out: i128,
+    ) -> bool {
+        let config = Self::get_risk_config(env);
+        let usdc = Asset::Custom(Symbol::short("USDCSIM"));
+        let xlm_value = portfolio.balance_of(env, Asset::XLM, user.clone());
+        let usdc_value = portfolio.balance_of(env, usdc.clone(), user.clone());
+        let before = Self::concentration_bps(xlm_value, usdc_value);
+
+        let shift = |(xlm, usdc_bal): (i128, i128), asset: &Asset, delta: i128| {
+            if *asset == Asset::XLM {
+                (xlm.saturating_add(delta), usdc_bal)
+            } else if *asset == usdc {
+                (xlm, usdc_bal.saturating_add(delta))
+            } else {
+                (xlm, usdc_bal)
+            }
+        };
+        let (xlm_after, usdc_after) = shift(
+            shift((xlm_value, usdc_value), from, amount_in.saturating_neg()),
+            to,
+            amount_out,
+        );
+        let after = Self::concentration_bps(xlm_after, usdc_after);
+
+        after >= config.concentration_limit_threshold && after > before
+    }
+
+    /// Largest position's share of `xlm + usdc`, in basis points
+    fn concentration_bps(xlm_value: i128, usdc_value: i128) -> u32 {
+        let total_value = xlm_value.max(0).saturating_add(usdc_value.max(0));
+        if total_value == 0 {
+            return 0;
+        }
+        let max_position = xlm_value.max(usdc_value);
+        ((max_position.saturating_mul(10000)) / total_value) as u32
+    }
+
     /// Get largest position percentage
     pub fn get_largest_position_percentage(
         env: &Env,
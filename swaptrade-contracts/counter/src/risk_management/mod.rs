@@ -5,11 +5,16 @@ pub mod portfolio;
 pub mod position;
 pub mod position_limits;
 pub mod risk_metrics;
+pub mod trip_conditions;
+pub mod valuation;
 pub mod volatility;
 pub mod volume_circuit_breaker;
 
 pub use circuit_breaker::*;
 pub use concentration_risk::*;
+pub use portfolio::PortfolioRisk;
 pub use position_limits::*;
 pub use risk_metrics::*;
+pub use trip_conditions::{InvariantFailure, OracleDivergence, PriceMove};
+pub use valuation::{AssetValuation, PortfolioValuation, PositionSource, PositionValue};
 pub use volume_circuit_breaker::VolumeCircuitBreakerStatus;
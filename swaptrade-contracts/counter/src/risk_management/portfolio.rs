@@ -1,4 +1,7 @@
 use crate::portfolio::Portfolio;
+use crate::risk_management::valuation::{
+    AssetValuation, PortfolioValuation, MAX_RISK_VOLATILITY_BPS,
+};
 use crate::risk_management::{CircuitBreaker, ConcentrationRisk, RiskConfig, RiskMetrics};
 use soroban_sdk::{Address, Env, Map, Symbol};
 
@@ -6,15 +9,17 @@ use soroban_sdk::{Address, Env, Map, Symbol};
 pub struct PortfolioRisk;
 
 impl PortfolioRisk {
-    /// Calculate comprehensive risk metrics for a user
+    /// Calculate comprehensive risk metrics for a user. Exposure,
+    /// concentration and volatility are measured on oracle USD values.
     pub fn calculate_risk_metrics(env: &Env, portfolio: &Portfolio, user: &Address) -> RiskMetrics {
         let config = Self::get_risk_config(env);
+        let valuation = AssetValuation::value_portfolio(env, portfolio, user);
 
         // Calculate individual risk components
         let concentration_risk =
-            ConcentrationRisk::calculate_concentration_risk(env, portfolio, user);
+            ConcentrationRisk::score_concentration(valuation.largest_asset_bps);
         let position_size_risk = Self::calculate_position_size_risk(env, portfolio, user);
-        let volatility_risk = Self::calculate_volatility_risk(env);
+        let volatility_risk = Self::calculate_volatility_risk(env, &valuation);
 
         // Calculate overall risk score using weighted average
         let overall_risk_score = ((concentration_risk as u32
@@ -23,10 +28,6 @@ impl PortfolioRisk {
             + (volatility_risk as u32 * config.risk_weights.volatility_weight))
             / 100;
 
-        // Calculate exposure and other metrics
-        let total_exposure_usd = Self::calculate_total_exposure(env, portfolio, user);
-        let largest_position_pct =
-            ConcentrationRisk::get_largest_position_percentage(env, portfolio, user);
         let positions_over_limit = Self::count_positions_over_limit(env, portfolio, user);
         let circuit_breaker_active = CircuitBreaker::is_circuit_breaker_active(env);
 
@@ -35,8 +36,10 @@ impl PortfolioRisk {
             concentration_risk,
             position_size_risk,
             volatility_risk,
-            total_exposure_usd,
-            largest_position_pct,
+            total_exposure_usd: valuation.total_value_usd,
+            value_at_risk_usd: valuation.value_at_risk_usd,
+            unpriced_positions: valuation.unpriced_positions,
+            largest_position_pct: valuation.largest_asset_bps,
             positions_over_limit,
             circuit_breaker_active,
             last_assessment: env.ledger().timestamp(),
@@ -52,10 +55,10 @@ impl PortfolioRisk {
         let mut total_risk = 0u32;
         let mut position_count = 0u32;
 
-        for (_, size) in positions.iter() {
+        for (asset, size) in positions.iter() {
             let size_abs = if size < 0 { -size } else { size };
-            let max_allowed = crate::risk_management::PositionLimits::get_tier_position_limit(
-                &config, &user_tier,
+            let max_allowed = crate::risk_management::PositionLimits::position_limit(
+                env, &config, &asset, &user_tier,
             );
 
             if max_allowed > 0 {
@@ -83,32 +86,17 @@ impl PortfolioRisk {
         }
     }
 
-    /// Calculate volatility risk (0-100)
-    fn calculate_volatility_risk(env: &Env) -> u32 {
-        // Simplified volatility calculation
-        // In production, this would analyze price movements over time
-        let circuit_breaker_active = CircuitBreaker::is_circuit_breaker_active(env);
-
-        if circuit_breaker_active {
-            100
-        } else {
-            // Base volatility - could be enhanced with actual price data
-            25
+    /// Calculate volatility risk (0-100) from the value-weighted daily
+    /// volatility of the user's holdings
+    fn calculate_volatility_risk(env: &Env, valuation: &PortfolioValuation) -> u32 {
+        if CircuitBreaker::is_circuit_breaker_active(env) {
+            return 100;
         }
-    }
-
-    /// Calculate total exposure in USD
-    fn calculate_total_exposure(env: &Env, portfolio: &Portfolio, user: &Address) -> i128 {
-        // Simple calculation - in production should use oracle prices
-        let xlm_balance = portfolio.balance_of(env, crate::portfolio::Asset::XLM, user.clone());
-        let usdc_balance = portfolio.balance_of(
-            env,
-            crate::portfolio::Asset::Custom(Symbol::short("USDCSIM")),
-            user.clone(),
-        );
-
-        // Assume 1 XLM = 1 USD for simulation
-        xlm_balance + usdc_balance
+        (valuation
+            .weighted_volatility_bps
+            .min(MAX_RISK_VOLATILITY_BPS)
+            * 100)
+            / MAX_RISK_VOLATILITY_BPS
     }
 
     /// Count positions that exceed limits
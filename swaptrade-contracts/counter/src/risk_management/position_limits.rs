@@ -1,9 +1,18 @@
+use crate::errors::SwapTradeError;
+use crate::governance_params::{GovernanceParams, ParamKey};
 use crate::portfolio::{Asset, Portfolio};
-use crate::risk_management::RiskConfig;
+use crate::risk_management::{AssetValuation, RiskConfig};
 use crate::tiers::{calculate_user_tier, UserTier};
 use soroban_sdk::{contracttype, Address, Env, Map, Symbol, Vec};
 
+const BPS: i128 = 10_000;
+
 /// Position limit enforcement
+///
+/// The base limits live in `RiskConfig`. Governance can override the base
+/// for single assets (`ParamKey::AssetPositionLimit`), the share of the base
+/// each tier receives (`ParamKey::TierPositionLimitBps`) and the total held
+/// of an asset across all users (`ParamKey::OpenInterestCap`).
 pub struct PositionLimits;
 
 impl PositionLimits {
@@ -23,7 +32,7 @@ impl PositionLimits {
         let new_size = current_size + additional_amount;
 
         // Check per-asset limit
-        let max_per_asset = Self::get_tier_position_limit(&config, &user_tier);
+        let max_per_asset = Self::position_limit(env, &config, asset, &user_tier);
         if new_size > max_per_asset {
             return Err(PositionLimitError::AssetLimitExceeded {
                 current: current_size,
@@ -32,53 +41,117 @@ impl PositionLimits {
             });
         }
 
-        // Check total portfolio limit
+        // Check total portfolio limit, in USD
         let total_portfolio = Self::calculate_total_portfolio_value(env, portfolio, user);
-        let max_portfolio = Self::get_tier_portfolio_limit(&config, &user_tier);
-        if total_portfolio + additional_amount > max_portfolio {
+        let additional_usd =
+            AssetValuation::value_usd(env, &AssetValuation::asset_symbol(asset), additional_amount);
+        let max_portfolio = Self::portfolio_limit(env, &config, &user_tier);
+        if total_portfolio + additional_usd > max_portfolio {
             return Err(PositionLimitError::PortfolioLimitExceeded {
                 current: total_portfolio,
-                requested: total_portfolio + additional_amount,
+                requested: total_portfolio + additional_usd,
                 limit: max_portfolio,
             });
         }
 
+        // Check open interest across all users; only increases are blocked
+        if additional_amount > 0 {
+            if let Some(cap) = Self::open_interest_cap(env, asset) {
+                let open_interest = portfolio.open_interest(asset);
+                if open_interest + additional_amount > cap {
+                    return Err(PositionLimitError::OpenInterestExceeded {
+                        current: open_interest,
+                        requested: open_interest + additional_amount,
+                        limit: cap,
+                    });
+                }
+            }
+        }
+
         Ok(())
     }
 
-    /// Get position limit based on user tier
-    pub fn get_tier_position_limit(config: &RiskConfig, tier: &UserTier) -> i128 {
+    /// Per-asset limit for `tier`, after governance overrides
+    pub fn position_limit(env: &Env, config: &RiskConfig, asset: &Asset, tier: &UserTier) -> i128 {
+        let base = GovernanceParams::get_param(
+            env,
+            ParamKey::AssetPositionLimit(AssetValuation::asset_symbol(asset)),
+        )
+        .unwrap_or(config.max_position_per_asset);
+        base.saturating_mul(Self::tier_limit_bps(env, tier) as i128) / BPS
+    }
+
+    /// Portfolio limit in USD for `tier`, after governance overrides
+    pub fn portfolio_limit(env: &Env, config: &RiskConfig, tier: &UserTier) -> i128 {
+        config
+            .max_position_per_user
+            .saturating_mul(Self::tier_limit_bps(env, tier) as i128)
+            / BPS
+    }
+
+    /// Governance-set cap on the total held of `asset`, if any
+    pub fn open_interest_cap(env: &Env, asset: &Asset) -> Option<i128> {
+        GovernanceParams::get_param(
+            env,
+            ParamKey::OpenInterestCap(AssetValuation::asset_symbol(asset)),
+        )
+    }
+
+    /// Share of the base limits granted to `tier`, in bps
+    pub fn tier_limit_bps(env: &Env, tier: &UserTier) -> u32 {
+        GovernanceParams::get_param(env, ParamKey::TierPositionLimitBps(tier.clone()))
+            .map(|bps| bps as u32)
+            .unwrap_or_else(|| Self::default_tier_limit_bps(tier))
+    }
+
+    pub fn default_tier_limit_bps(tier: &UserTier) -> u32 {
         match tier {
-            UserTier::Novice => config.max_position_per_asset / 10, // 10% of base
-            UserTier::Trader => config.max_position_per_asset / 4,  // 25% of base
-            UserTier::Expert => config.max_position_per_asset / 2,  // 50% of base
-            UserTier::Whale => config.max_position_per_asset,       // 100% of base
+            UserTier::Novice => 1_000, // 10% of base
+            UserTier::Trader => 2_500, // 25% of base
+            UserTier::Expert => 5_000, // 50% of base
+            UserTier::Whale => 10_000, // 100% of base
         }
     }
 
-    /// Get portfolio limit based on user tier
-    pub fn get_tier_portfolio_limit(config: &RiskConfig, tier: &UserTier) -> i128 {
-        match tier {
-            UserTier::Novice => config.max_position_per_user / 10, // 10% of base
-            UserTier::Trader => config.max_position_per_user / 4,  // 25% of base
-            UserTier::Expert => config.max_position_per_user / 2,  // 50% of base
-            UserTier::Whale => config.max_position_per_user,       // 100% of base
+    /// Reject a tier share that would give a tier more than the tier above
+    /// it or less than the tier below it.
+    pub fn validate_tier_limit_bps(
+        env: &Env,
+        tier: &UserTier,
+        bps: u32,
+    ) -> Result<(), SwapTradeError> {
+        let (below, above) = match tier {
+            UserTier::Novice => (None, Some(UserTier::Trader)),
+            UserTier::Trader => (Some(UserTier::Novice), Some(UserTier::Expert)),
+            UserTier::Expert => (Some(UserTier::Trader), Some(UserTier::Whale)),
+            UserTier::Whale => (Some(UserTier::Expert), None),
+        };
+        if below.is_some_and(|t| bps < Self::tier_limit_bps(env, &t))
+            || above.is_some_and(|t| bps > Self::tier_limit_bps(env, &t))
+        {
+            return Err(SwapTradeError::InvalidAmount);
         }
+        Ok(())
     }
 
-    /// Calculate total portfolio value across all assets
+    /// Get position limit based on user tier, with the default tier shares
+    pub fn get_tier_position_limit(config: &RiskConfig, tier: &UserTier) -> i128 {
+        config.max_position_per_asset * Self::default_tier_limit_bps(tier) as i128 / BPS
+    }
+
+    /// Get portfolio limit based on user tier, with the default tier shares
+    pub fn get_tier_portfolio_limit(config: &RiskConfig, tier: &UserTier) -> i128 {
+        config.max_position_per_user * Self::default_tier_limit_bps(tier) as i128 / BPS
+    }
+
+    /// Calculate total portfolio value in USD across balances, LP shares
+    /// and stakes
     pub fn calculate_total_portfolio_value(
         env: &Env,
         portfolio: &Portfolio,
         user: &Address,
     ) -> i128 {
-        // For simplicity, sum all balances (assuming same decimals)
-        // In production, this should convert to USD value using oracles
-        let xlm_balance = portfolio.balance_of(env, Asset::XLM, user.clone());
-        let usdc_balance =
-            portfolio.balance_of(env, Asset::Custom(Symbol::short("USDCSIM")), user.clone());
-
-        xlm_balance + usdc_balance
+        AssetValuation::value_portfolio(env, portfolio, user).total_value_usd
     }
 
     /// Get risk configuration from storage
@@ -113,4 +186,9 @@ pub enum PositionLimitError {
         requested: i128,
         limit: i128,
     },
+    OpenInterestExceeded {
+        current: i128,
+        requested: i128,
+        limit: i128,
+    },
 }
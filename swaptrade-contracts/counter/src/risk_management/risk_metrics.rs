@@ -1,4 +1,5 @@
 use crate::portfolio::Asset;
+use crate::risk_management::circuit_breaker::{BreakerPhase, BreakerScope, TripRule};
 use soroban_sdk::{contracttype, Address, Env, Map, Symbol, Vec};
 
 /// Risk metrics returned by get_risk_metrics()
@@ -13,8 +14,12 @@ pub struct RiskMetrics {
     pub position_size_risk: u32,
     /// Market volatility risk (0-100)
     pub volatility_risk: u32,
-    /// Total portfolio exposure in USD
+    /// Total portfolio exposure in USD, oracle-priced
     pub total_exposure_usd: i128,
+    /// One-day 95% value-at-risk in USD
+    pub value_at_risk_usd: i128,
+    /// Positions valued at par for lack of an oracle price
+    pub unpriced_positions: u32,
     /// Largest single position percentage (0-10000 bps)
     pub largest_position_pct: u32,
     /// Number of positions exceeding limits
@@ -89,13 +94,21 @@ impl RiskWeights {
     }
 }
 
-/// Circuit breaker state
+/// Circuit breaker state for one scope
 #[contracttype]
 #[derive(Clone, Debug, PartialEq)]
 pub struct CircuitBreakerState {
+    /// Whether the breaker is open and halting trading
     pub is_active: bool,
+    pub phase: BreakerPhase,
+    pub scope: BreakerScope,
+    /// Rule behind the latest trip, once `triggered_at` is set
+    pub rule: TripRule,
     pub triggered_at: u64,
     pub trigger_reason: Symbol,
+    /// What the rule observed and the threshold it breached
+    pub observed: i128,
+    pub threshold: i128,
     pub price_change_pct: u32,
     pub recovery_price: Option<i128>,
 }
@@ -104,8 +117,13 @@ impl Default for CircuitBreakerState {
     fn default() -> Self {
         Self {
             is_active: false,
+            phase: BreakerPhase::Closed,
+            scope: BreakerScope::Global,
+            rule: TripRule::Manual,
             triggered_at: 0,
             trigger_reason: Symbol::short("none"),
+            observed: 0,
+            threshold: 0,
             price_change_pct: 0,
             recovery_price: None,
         }
@@ -0,0 +1,109 @@
+//! Trip conditions evaluated on demand
+//!
+//! Each condition reads its own inputs and reports a `Breach` when it holds.
+//! `CircuitBreaker::evaluate` turns that into a trip, or closes a half-open
+//! breaker that the same rule tripped earlier.
+
+use crate::invariants;
+use crate::oracle::get_stored_price;
+use crate::oracle_adapter::OracleAdapter;
+use crate::portfolio::Portfolio;
+use crate::risk_management::valuation::USD;
+use crate::risk_management::{
+    Breach, BreakerScope, CircuitBreaker, PositionLimits, TripCondition, TripRule,
+};
+use soroban_sdk::{Env, Symbol};
+
+/// Largest move of `asset` against USD within `RiskConfig::circuit_breaker_window`,
+/// measured from the first oracle round in the window.
+pub struct PriceMove(pub Symbol);
+
+impl TripCondition for PriceMove {
+    fn rule(&self) -> TripRule {
+        TripRule::PriceMove
+    }
+
+    fn scope(&self) -> BreakerScope {
+        BreakerScope::Asset(self.0.clone())
+    }
+
+    fn evaluate(&self, env: &Env) -> Option<Breach> {
+        let config = PositionLimits::get_risk_config(env);
+        let (_, state) = OracleAdapter::get_oracle_info(env, (self.0.clone(), USD)).ok()?;
+        let since = env
+            .ledger()
+            .timestamp()
+            .saturating_sub(config.circuit_breaker_window);
+
+        let mut base = 0u128;
+        let mut max_move_bps = 0u32;
+        for obs in state.price_history.iter() {
+            if obs.timestamp < since {
+                continue;
+            }
+            if base == 0 {
+                base = obs.price;
+                continue;
+            }
+            max_move_bps =
+                max_move_bps.max(OracleAdapter::calculate_deviation_bps(base, obs.price));
+        }
+
+        (max_move_bps >= config.circuit_breaker_threshold).then(|| Breach {
+            observed: max_move_bps as i128,
+            threshold: config.circuit_breaker_threshold as i128,
+        })
+    }
+}
+
+/// Gap between the TWAP oracle adapter and the directly stored price of
+/// `asset` against USD. Both feeds use the same 1e18 precision.
+pub struct OracleDivergence(pub Symbol);
+
+impl TripCondition for OracleDivergence {
+    fn rule(&self) -> TripRule {
+        TripRule::OracleDivergence
+    }
+
+    fn scope(&self) -> BreakerScope {
+        BreakerScope::Asset(self.0.clone())
+    }
+
+    fn evaluate(&self, env: &Env) -> Option<Breach> {
+        let pair = (self.0.clone(), USD);
+        let adapter = OracleAdapter::get_price(env, pair.clone()).ok()?;
+        let stored = get_stored_price(env, pair)?.price;
+        let divergence_bps = OracleAdapter::calculate_deviation_bps(stored, adapter);
+        let threshold = CircuitBreaker::get_config(env).oracle_divergence_bps;
+
+        (divergence_bps > threshold).then(|| Breach {
+            observed: divergence_bps as i128,
+            threshold: threshold as i128,
+        })
+    }
+}
+
+/// Contract-wide invariants on the portfolio; trips the global breaker.
+pub struct InvariantFailure<'a>(pub &'a Portfolio);
+
+impl TripCondition for InvariantFailure<'_> {
+    fn rule(&self) -> TripRule {
+        TripRule::InvariantFailure
+    }
+
+    fn scope(&self) -> BreakerScope {
+        BreakerScope::Global
+    }
+
+    fn evaluate(&self, env: &Env) -> Option<Breach> {
+        let failed = invariants::get_invariant_report(env, self.0)
+            .iter()
+            .filter(|(_, held)| !held)
+            .count();
+
+        (failed > 0).then(|| Breach {
+            observed: failed as i128,
+            threshold: 0,
+        })
+    }
+}
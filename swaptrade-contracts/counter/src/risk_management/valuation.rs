@@ -0,0 +1,222 @@
+//! USD valuation of a user's holdings through the oracle adapter
+//!
+//! Every asset balance, LP share (registry pools and the legacy XLM/USDCSIM
+//! pool) and staked amount is priced against the `(asset, USD)` oracle pair.
+//! Holdings without a usable price are kept at par (one unit = one USD) and
+//! treated as maximally volatile, so an unpriced asset can never make a
+//! portfolio look smaller or safer than it is; `unpriced_positions` reports
+//! how many positions fell back.
+
+use soroban_sdk::{contracttype, symbol_short, Address, Env, Map, Symbol, Vec};
+
+use crate::oracle_adapter::OracleAdapter;
+use crate::portfolio::{Asset, Portfolio};
+use crate::risk_management::volatility;
+use crate::staking_bonus::StakingBonusManager;
+
+/// Quote symbol of every valuation oracle pair.
+pub const USD: Symbol = symbol_short!("USD");
+/// Fixed-point scale of oracle prices.
+pub const PRICE_PRECISION: u128 = 1_000_000_000_000_000_000;
+/// Staking is accounted in the native asset.
+pub const STAKED_ASSET: Symbol = symbol_short!("XLM");
+/// Pool id reported for the legacy XLM/USDCSIM pool held in the portfolio.
+pub const LEGACY_POOL_ID: u64 = 0;
+/// Daily volatility at which volatility risk is scored 100.
+pub const MAX_RISK_VOLATILITY_BPS: u32 = 2_000;
+/// One-sided 95% normal quantile (1.645) in bps.
+const VAR_95_Z_BPS: i128 = 16_450;
+const BPS: i128 = 10_000;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PositionSource {
+    Balance,
+    /// Pro-rata share of a pool's reserves.
+    LpShare(u64),
+    Staked,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PositionValue {
+    pub source: PositionSource,
+    pub asset: Symbol,
+    pub amount: i128,
+    /// Oracle price scaled by `PRICE_PRECISION`; 0 when unpriced.
+    pub price_usd: u128,
+    pub value_usd: i128,
+    pub daily_volatility_bps: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PortfolioValuation {
+    pub positions: Vec<PositionValue>,
+    pub total_value_usd: i128,
+    /// One-day 95% value-at-risk, summed per position (no diversification
+    /// credit) and capped at each position's value.
+    pub value_at_risk_usd: i128,
+    /// Value-weighted one-day volatility across all positions.
+    pub weighted_volatility_bps: u32,
+    /// Largest single asset's share of the total, netted across sources.
+    pub largest_asset_bps: u32,
+    pub unpriced_positions: u32,
+}
+
+pub struct AssetValuation;
+
+impl AssetValuation {
+    pub fn asset_symbol(asset: &Asset) -> Symbol {
+        match asset {
+            Asset::XLM => symbol_short!("XLM"),
+            Asset::Custom(symbol) => symbol.clone(),
+        }
+    }
+
+    pub fn price_usd(env: &Env, asset: &Symbol) -> Option<u128> {
+        if *asset == USD {
+            return Some(PRICE_PRECISION);
+        }
+        OracleAdapter::get_price(env, (asset.clone(), USD))
+            .ok()
+            .filter(|price| *price > 0)
+    }
+
+    /// Value `amount` of `asset` in USD, at par when no price is available.
+    pub fn value_usd(env: &Env, asset: &Symbol, amount: i128) -> i128 {
+        match Self::price_usd(env, asset) {
+            Some(price) => Self::apply_price(amount, price),
+            None => amount,
+        }
+    }
+
+    pub fn position(
+        env: &Env,
+        source: PositionSource,
+        asset: Symbol,
+        amount: i128,
+    ) -> PositionValue {
+        let (price_usd, value_usd, daily_volatility_bps) = match Self::price_usd(env, &asset) {
+            Some(price) => (
+                price,
+                Self::apply_price(amount, price),
+                if asset == USD {
+                    0
+                } else {
+                    volatility::daily_volatility_bps(env, asset.clone(), USD)
+                        .unwrap_or(MAX_RISK_VOLATILITY_BPS)
+                },
+            ),
+            None => (0, amount, MAX_RISK_VOLATILITY_BPS),
+        };
+        PositionValue {
+            source,
+            asset,
+            amount,
+            price_usd,
+            value_usd,
+            daily_volatility_bps,
+        }
+    }
+
+    /// Price every holding of `user` and estimate the portfolio's VaR.
+    pub fn value_portfolio(env: &Env, portfolio: &Portfolio, user: &Address) -> PortfolioValuation {
+        let mut positions = Vec::new(env);
+
+        for (asset, amount) in portfolio.assets_of(env, user).iter() {
+            positions.push_back(Self::position(
+                env,
+                PositionSource::Balance,
+                Self::asset_symbol(&asset),
+                amount,
+            ));
+        }
+
+        if let Some(lp) = portfolio.get_lp_position(user.clone()) {
+            let total = portfolio.get_total_lp_tokens();
+            if lp.lp_tokens_minted > 0 && total > 0 {
+                for asset in [Asset::XLM, Asset::Custom(symbol_short!("USDCSIM"))] {
+                    let share =
+                        portfolio.get_liquidity(asset.clone()) * lp.lp_tokens_minted / total;
+                    if share != 0 {
+                        positions.push_back(Self::position(
+                            env,
+                            PositionSource::LpShare(LEGACY_POOL_ID),
+                            Self::asset_symbol(&asset),
+                            share,
+                        ));
+                    }
+                }
+            }
+        }
+
+        let registry = crate::load_pool_registry(env);
+        for (pool_id, token, amount) in registry.provider_underlying(env, user).iter() {
+            positions.push_back(Self::position(
+                env,
+                PositionSource::LpShare(pool_id),
+                token,
+                amount,
+            ));
+        }
+
+        let staked = StakingBonusManager::get_user_total_staked(env, user.clone());
+        if staked > 0 {
+            positions.push_back(Self::position(
+                env,
+                PositionSource::Staked,
+                STAKED_ASSET,
+                staked,
+            ));
+        }
+
+        Self::summarize(env, positions)
+    }
+
+    fn summarize(env: &Env, positions: Vec<PositionValue>) -> PortfolioValuation {
+        let mut total_value_usd = 0i128;
+        let mut value_at_risk_usd = 0i128;
+        let mut weighted_volatility = 0i128;
+        let mut unpriced_positions = 0u32;
+        let mut per_asset: Map<Symbol, i128> = Map::new(env);
+
+        for position in positions.iter() {
+            let value = position.value_usd.max(0);
+            let volatility = position.daily_volatility_bps as i128;
+            total_value_usd += value;
+            value_at_risk_usd += (value * volatility * VAR_95_Z_BPS / (BPS * BPS)).min(value);
+            weighted_volatility += value * volatility;
+            if position.price_usd == 0 {
+                unpriced_positions += 1;
+            }
+            per_asset.set(
+                position.asset.clone(),
+                per_asset.get(position.asset.clone()).unwrap_or(0) + value,
+            );
+        }
+
+        let (weighted_volatility_bps, largest_asset_bps) = if total_value_usd > 0 {
+            let largest = per_asset.values().iter().max().unwrap_or(0);
+            (
+                (weighted_volatility / total_value_usd) as u32,
+                (largest * BPS / total_value_usd) as u32,
+            )
+        } else {
+            (0, 0)
+        };
+
+        PortfolioValuation {
+            positions,
+            total_value_usd,
+            value_at_risk_usd,
+            weighted_volatility_bps,
+            largest_asset_bps,
+            unpriced_positions,
+        }
+    }
+
+    fn apply_price(amount: i128, price: u128) -> i128 {
+        amount.saturating_mul(price as i128) / PRICE_PRECISION as i128
+    }
+}
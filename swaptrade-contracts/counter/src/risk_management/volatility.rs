@@ -1,6 +1,70 @@
-use soroban_sdk::{Env, Symbol};
+//! Historical volatility from oracle price rounds
+//!
+//! Round-to-round returns are taken from the adapter's price history and
+//! their sample standard deviation is scaled to a one-day horizon using the
+//! average spacing between rounds (square-root-of-time rule).
 
-pub fn check_circuit_breaker(_env: &Env, _asset: Symbol) -> bool {
-    // TODO: implement with correct soroban_sdk v27 APIs
-    false
+use soroban_sdk::{Env, Symbol, Vec};
+
+use crate::oracle_adapter::{OracleAdapter, PriceObservation};
+
+pub const DAY_SECS: u64 = 86_400;
+/// Returns are computed in units of 1e-8 so that small moves survive the
+/// integer arithmetic; 1 bps = 10_000 units.
+const RETURN_SCALE: i128 = 100_000_000;
+const UNITS_PER_BPS: i128 = 10_000;
+
+/// One-day volatility in bps of `asset` against `quote`, or `None` when the
+/// oracle is not configured or has fewer than three rounds on record.
+pub fn daily_volatility_bps(env: &Env, asset: Symbol, quote: Symbol) -> Option<u32> {
+    let (_, state) = OracleAdapter::get_oracle_info(env, (asset, quote)).ok()?;
+    volatility_from_history(&state.price_history)
+}
+
+pub fn volatility_from_history(history: &Vec<PriceObservation>) -> Option<u32> {
+    if history.len() < 3 {
+        return None;
+    }
+
+    let mut returns: Vec<i128> = Vec::new(history.env());
+    let mut prev = history.get(0).unwrap();
+    for obs in history.iter().skip(1) {
+        if prev.price == 0 {
+            return None;
+        }
+        let prev_price = prev.price as i128;
+        returns.push_back((obs.price as i128 - prev_price) * RETURN_SCALE / prev_price);
+        prev = obs;
+    }
+
+    let n = returns.len() as i128;
+    let mean = returns.iter().sum::<i128>() / n;
+    let variance = returns
+        .iter()
+        .map(|r| (r - mean) * (r - mean))
+        .sum::<i128>()
+        / (n - 1);
+    let per_round = isqrt(variance);
+
+    let first = history.get(0).unwrap().timestamp;
+    let last = history.get(history.len() - 1).unwrap().timestamp;
+    let interval = (last.saturating_sub(first) / (history.len() as u64 - 1)).max(1);
+    // sqrt(DAY / interval), scaled by 1e4.
+    let horizon = isqrt(DAY_SECS as i128 * RETURN_SCALE / interval as i128);
+
+    let daily_bps = per_round * horizon / (UNITS_PER_BPS * UNITS_PER_BPS);
+    Some(daily_bps.min(u32::MAX as i128) as u32)
+}
+
+fn isqrt(n: i128) -> i128 {
+    if n < 2 {
+        return n.max(0);
+    }
+    let mut x = n;
+    let mut y = (x + 1) / 2;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
 }
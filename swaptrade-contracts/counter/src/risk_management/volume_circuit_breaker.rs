@@ -1,5 +1,5 @@
 use crate::errors::SwapTradeError;
-use crate::events;
+use crate::risk_management::{Breach, BreakerScope, CircuitBreaker, TripRule};
 use soroban_sdk::{contracttype, symbol_short, Address, Env, Symbol, Vec};
 
 /// Configuration for the volume-threshold circuit breaker.
@@ -32,8 +32,6 @@ pub struct VolumeCircuitBreakerStatus {
 const CONFIG_KEY: Symbol = symbol_short!("vcb_cfg");
 /// Persistent key for the volume entry list `(timestamp, volume)`.
 const VOLUME_KEY: Symbol = symbol_short!("vcb_vol");
-/// Persistent key for the tripped boolean flag.
-const TRIPPED_KEY: Symbol = symbol_short!("vcb_tri");
 
 // ── Default values ───────────────────────────────────────────────────────────
 
@@ -50,8 +48,19 @@ pub fn set_threshold(
     window_secs: u64,
     max_volume: i128,
 ) -> Result<(), SwapTradeError> {
-    if !crate::admin::is_admin(env, &admin) {
-        return Err(SwapTradeError::NotAdmin);
+    crate::admin::require_admin(env, &admin)?;
+    apply_threshold(env, window_secs, max_volume)
+}
+
+/// Set the threshold without an admin check; used by governance execution.
+pub(crate) fn apply_threshold(
+    env: &Env,
+    window_secs: u64,
+    max_volume: i128,
+) -> Result<(), SwapTradeError> {
+    // A zero `max_volume` disables the breaker.
+    if window_secs == 0 || max_volume < 0 {
+        return Err(SwapTradeError::InvalidAmount);
     }
 
     let config = VolumeCircuitBreakerConfig {
@@ -65,23 +74,19 @@ pub fn set_threshold(
 /// Reset (clear) the circuit breaker state and restore trading.
 /// `admin` must be the registered contract admin.
 pub fn reset(env: &Env, admin: Address) -> Result<(), SwapTradeError> {
-    if !crate::admin::is_admin(env, &admin) {
-        return Err(SwapTradeError::NotAdmin);
-    }
+    crate::admin::require_admin(env, &admin)?;
+    apply_reset(env);
+    Ok(())
+}
 
+/// Clear the breaker without an admin check; used by governance execution.
+pub(crate) fn apply_reset(env: &Env) {
     // Clear volume history
     let empty: Vec<(u64, i128)> = Vec::new(env);
     env.storage().persistent().set(&VOLUME_KEY, &empty);
 
-    // Clear tripped flag
-    env.storage().persistent().set(&TRIPPED_KEY, &false);
-
-    // Unpause trading via the shared pause mechanism
-    env.storage()
-        .instance()
-        .set(&crate::storage::PAUSED_KEY, &false);
-
-    Ok(())
+    // Close the global breaker, restoring trading
+    CircuitBreaker::reset(env, BreakerScope::Global);
 }
 
 // ── Observability ─────────────────────────────────────────────────────────────
@@ -105,15 +110,16 @@ pub fn get_status(env: &Env) -> VolumeCircuitBreakerStatus {
 /// Check whether adding `amount` would exceed the volume threshold.
 ///
 /// *Prunes* stale entries outside the sliding window, records the new volume,
-/// and trips the circuit breaker (pausing trading / emitting an event) if the
-/// accumulated volume exceeds `max_volume`.
+/// and trips the global circuit breaker if the accumulated volume exceeds
+/// `max_volume`. Staying under the threshold closes a half-open breaker
+/// that volume tripped.
 ///
 /// Returns `true` **iff** the breaker was just tripped by this call.
 pub fn check_and_record_volume(env: &Env, amount: i128) -> bool {
     let now = env.ledger().timestamp();
     let config = get_config(env);
 
-    // Short-circuit: if the breaker is already tripped, no need to re-check.
+    // Short-circuit: if the breaker is already open, no need to re-check.
     if is_tripped(env) {
         return false;
     }
@@ -129,7 +135,7 @@ pub fn check_and_record_volume(env: &Env, amount: i128) -> bool {
     let mut pruned: Vec<(u64, i128)> = Vec::new(env);
     for i in 0..entries.len() {
         if let Some(entry) = entries.get(i) {
-            if entry.0 >= cutoff {
+            if entry.0 > cutoff {
                 pruned.push_back(entry);
             }
         }
@@ -150,38 +156,22 @@ pub fn check_and_record_volume(env: &Env, amount: i128) -> bool {
     env.storage().persistent().set(&VOLUME_KEY, &pruned);
 
     // ── 5. Trip if threshold exceeded ──
-    if total > config.max_volume && config.max_volume > 0 {
-        // Mark as tripped
-        env.storage().persistent().set(&TRIPPED_KEY, &true);
-
-        // Pause trading via the shared pause mechanism
-        env.storage()
-            .instance()
-            .set(&crate::storage::PAUSED_KEY, &true);
-
-        // Emit a contract event for off-chain indexers / alerting
-        events::circuit_breaker_tripped(env, total, config.max_volume, config.window_secs, now);
-
-        return true;
-    }
-
-    false
+    let breach = (total > config.max_volume && config.max_volume > 0).then(|| Breach {
+        observed: total,
+        threshold: config.max_volume,
+    });
+    CircuitBreaker::observe(env, BreakerScope::Global, TripRule::Volume, breach)
 }
 
-/// Returns `true` if the volume circuit breaker is currently tripped.
+/// Returns `true` if the global circuit breaker is open, whichever rule
+/// tripped it.
 pub fn is_tripped(env: &Env) -> bool {
-    env.storage()
-        .persistent()
-        .get(&TRIPPED_KEY)
-        .unwrap_or(false)
+    CircuitBreaker::is_circuit_breaker_active(env)
 }
 
-/// Returns the number of open pauses (0 or 1). Used for testing.
+/// Returns whether trading is halted by the global breaker. Used for testing.
 pub fn is_paused(env: &Env) -> bool {
-    env.storage()
-        .instance()
-        .get(&crate::storage::PAUSED_KEY)
-        .unwrap_or(false)
+    CircuitBreaker::is_circuit_breaker_active(env)
 }
 
 // ── Internal helpers ──────────────────────────────────────────────────────────
@@ -211,7 +201,7 @@ fn calculate_current_window_volume(env: &Env) -> i128 {
     let mut total: i128 = 0;
     for i in 0..entries.len() {
         if let Some(entry) = entries.get(i) {
-            if entry.0 >= cutoff {
+            if entry.0 > cutoff {
                 total = total.saturating_add(entry.1);
             }
         }
@@ -227,18 +217,20 @@ fn calculate_current_window_volume(env: &Env) -> i128 {
 mod tests {
     use super::*;
     use crate::risk_management::volume_circuit_breaker;
-    use soroban_sdk::{testutils::Address as _, Address, Env};
+    use soroban_sdk::{
+        testutils::{Address as _, Ledger as _},
+        Address, Env,
+    };
 
     // ── Helpers ───────────────────────────────────────────────────────────────
 
-    fn setup_env() -> (Env, Address) {
+    fn setup_env() -> (Env, Address, Address) {
         let env = Env::default();
+        let contract_id = env.register(crate::CounterContract, ());
         let admin = Address::generate(&env);
         // Store the admin address so that admin checks pass.
-        env.storage()
-            .persistent()
-            .set(&crate::storage::ADMIN_KEY, &admin);
-        (env, admin)
+        env.as_contract(&contract_id, || crate::admin::set_admin(&env, &admin));
+        (env, contract_id, admin)
     }
 
     fn set_ledger_time(env: &Env, ts: u64) {
@@ -249,22 +241,26 @@ mod tests {
 
     #[test]
     fn test_set_threshold_admin() {
-        let (env, admin) = setup_env();
+        let (env, contract_id, admin) = setup_env();
 
-        let result = volume_circuit_breaker::set_threshold(&env, admin, 1800, 500_000);
+        let result = env.as_contract(&contract_id, || {
+            volume_circuit_breaker::set_threshold(&env, admin, 1800, 500_000)
+        });
         assert!(result.is_ok());
 
-        let config = get_config(&env);
+        let config = env.as_contract(&contract_id, || get_config(&env));
         assert_eq!(config.window_secs, 1800);
         assert_eq!(config.max_volume, 500_000);
     }
 
     #[test]
     fn test_set_threshold_non_admin_fails() {
-        let (env, _) = setup_env();
+        let (env, contract_id, _) = setup_env();
         let non_admin = Address::generate(&env);
 
-        let result = volume_circuit_breaker::set_threshold(&env, non_admin, 1800, 500_000);
+        let result = env.as_contract(&contract_id, || {
+            volume_circuit_breaker::set_threshold(&env, non_admin, 1800, 500_000)
+        });
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), SwapTradeError::NotAdmin);
     }
@@ -273,10 +269,10 @@ mod tests {
 
     #[test]
     fn test_get_status_defaults() {
-        let (env, _) = setup_env();
-        set_ledger_time(&env, 1000);
+        let (env, contract_id, _) = setup_env();
+        env.as_contract(&contract_id, || set_ledger_time(&env, 1000));
 
-        let status = volume_circuit_breaker::get_status(&env);
+        let status = env.as_contract(&contract_id, || volume_circuit_breaker::get_status(&env));
         assert!(!status.tripped);
         assert_eq!(status.current_volume, 0);
         assert_eq!(status.threshold, DEFAULT_MAX_VOLUME);
@@ -285,17 +281,19 @@ mod tests {
 
     #[test]
     fn test_get_status_after_volume() {
-        let (env, admin) = setup_env();
-        set_ledger_time(&env, 1000);
+        let (env, contract_id, admin) = setup_env();
+        env.as_contract(&contract_id, || set_ledger_time(&env, 1000));
 
         // Set a small threshold so we can observe volume without tripping
-        volume_circuit_breaker::set_threshold(&env, admin, 3600, 1_000_000).unwrap();
+        env.as_contract(&contract_id, || {
+            volume_circuit_breaker::set_threshold(&env, admin, 3600, 1_000_000).unwrap()
+        });
 
         // Record some volume
-        check_and_record_volume(&env, 100_000);
-        check_and_record_volume(&env, 200_000);
+        env.as_contract(&contract_id, || check_and_record_volume(&env, 100_000));
+        env.as_contract(&contract_id, || check_and_record_volume(&env, 200_000));
 
-        let status = volume_circuit_breaker::get_status(&env);
+        let status = env.as_contract(&contract_id, || volume_circuit_breaker::get_status(&env));
         assert!(!status.tripped);
         assert_eq!(status.current_volume, 300_000);
         assert_eq!(status.threshold, 1_000_000);
@@ -306,72 +304,83 @@ mod tests {
 
     #[test]
     fn test_volume_accumulates_and_trips() {
-        let (env, admin) = setup_env();
-        set_ledger_time(&env, 1000);
+        let (env, contract_id, admin) = setup_env();
+        env.as_contract(&contract_id, || set_ledger_time(&env, 1000));
 
         // Set threshold: max 500 volume in a 60s window
-        volume_circuit_breaker::set_threshold(&env, admin, 60, 500).unwrap();
+        env.as_contract(&contract_id, || {
+            volume_circuit_breaker::set_threshold(&env, admin, 60, 500).unwrap()
+        });
 
         // Accumulate 300 → still OK
-        check_and_record_volume(&env, 300);
-        assert!(!is_tripped(&env));
-        let status = get_status(&env);
+        env.as_contract(&contract_id, || check_and_record_volume(&env, 300));
+        env.as_contract(&contract_id, || assert!(!is_tripped(&env)));
+        let status = env.as_contract(&contract_id, || get_status(&env));
         assert_eq!(status.current_volume, 300);
 
         // Accumulate another 300 → 600 > 500 → trips
-        let tripped = check_and_record_volume(&env, 300);
+        let tripped = env.as_contract(&contract_id, || check_and_record_volume(&env, 300));
         assert!(tripped);
-        assert!(is_tripped(&env));
-        assert!(is_paused(&env));
+        env.as_contract(&contract_id, || assert!(is_tripped(&env)));
+        env.as_contract(&contract_id, || assert!(is_paused(&env)));
     }
 
     #[test]
     fn test_does_not_trip_below_threshold() {
-        let (env, admin) = setup_env();
-        set_ledger_time(&env, 1000);
+        let (env, contract_id, admin) = setup_env();
+        env.as_contract(&contract_id, || set_ledger_time(&env, 1000));
 
-        volume_circuit_breaker::set_threshold(&env, admin, 60, 1000).unwrap();
+        env.as_contract(&contract_id, || {
+            volume_circuit_breaker::set_threshold(&env, admin, 60, 1000).unwrap()
+        });
 
-        let tripped = check_and_record_volume(&env, 500);
+        let tripped = env.as_contract(&contract_id, || check_and_record_volume(&env, 500));
         assert!(!tripped);
-        assert!(!is_tripped(&env));
+        env.as_contract(&contract_id, || assert!(!is_tripped(&env)));
     }
 
     #[test]
     fn test_tripped_after_reset_recovers() {
-        let (env, admin) = setup_env();
-        set_ledger_time(&env, 1000);
+        let (env, contract_id, admin) = setup_env();
+        env.as_contract(&contract_id, || set_ledger_time(&env, 1000));
 
-        volume_circuit_breaker::set_threshold(&env, admin.clone(), 60, 500).unwrap();
+        env.as_contract(&contract_id, || {
+            volume_circuit_breaker::set_threshold(&env, admin.clone(), 60, 500).unwrap()
+        });
 
         // Trip the breaker
-        check_and_record_volume(&env, 600);
-        assert!(is_tripped(&env));
-        assert!(is_paused(&env));
+        env.as_contract(&contract_id, || check_and_record_volume(&env, 600));
+        env.as_contract(&contract_id, || assert!(is_tripped(&env)));
+        env.as_contract(&contract_id, || assert!(is_paused(&env)));
 
         // Reset
-        let reset_result = volume_circuit_breaker::reset(&env, admin);
+        let reset_result =
+            env.as_contract(&contract_id, || volume_circuit_breaker::reset(&env, admin));
         assert!(reset_result.is_ok());
-        assert!(!is_tripped(&env));
-        assert!(!is_paused(&env));
+        env.as_contract(&contract_id, || assert!(!is_tripped(&env)));
+        env.as_contract(&contract_id, || assert!(!is_paused(&env)));
 
         // Verify status reflects reset
-        let status = get_status(&env);
+        let status = env.as_contract(&contract_id, || get_status(&env));
         assert!(!status.tripped);
         assert_eq!(status.current_volume, 0);
     }
 
     #[test]
     fn test_reset_non_admin_fails() {
-        let (env, admin) = setup_env();
-        set_ledger_time(&env, 1000);
+        let (env, contract_id, admin) = setup_env();
+        env.as_contract(&contract_id, || set_ledger_time(&env, 1000));
 
-        volume_circuit_breaker::set_threshold(&env, admin, 60, 500).unwrap();
-        check_and_record_volume(&env, 600);
-        assert!(is_tripped(&env));
+        env.as_contract(&contract_id, || {
+            volume_circuit_breaker::set_threshold(&env, admin, 60, 500).unwrap()
+        });
+        env.as_contract(&contract_id, || check_and_record_volume(&env, 600));
+        env.as_contract(&contract_id, || assert!(is_tripped(&env)));
 
         let non_admin = Address::generate(&env);
-        let result = volume_circuit_breaker::reset(&env, non_admin);
+        let result = env.as_contract(&contract_id, || {
+            volume_circuit_breaker::reset(&env, non_admin)
+        });
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), SwapTradeError::NotAdmin);
     }
@@ -380,47 +389,51 @@ mod tests {
 
     #[test]
     fn test_volume_expires_after_window() {
-        let (env, admin) = setup_env();
-        set_ledger_time(&env, 1000);
+        let (env, contract_id, admin) = setup_env();
+        env.as_contract(&contract_id, || set_ledger_time(&env, 1000));
 
-        volume_circuit_breaker::set_threshold(&env, admin, 60, 500).unwrap();
+        env.as_contract(&contract_id, || {
+            volume_circuit_breaker::set_threshold(&env, admin, 60, 500).unwrap()
+        });
 
         // Add 400 at t=1000
-        check_and_record_volume(&env, 400);
-        assert!(!is_tripped(&env));
+        env.as_contract(&contract_id, || check_and_record_volume(&env, 400));
+        env.as_contract(&contract_id, || assert!(!is_tripped(&env)));
 
         // Advance time to t=1060 (exactly at window boundary)
-        set_ledger_time(&env, 1060);
+        env.as_contract(&contract_id, || set_ledger_time(&env, 1060));
 
         // Add 200 at t=1060 — total should be 200 (400 is now outside window)
-        let tripped = check_and_record_volume(&env, 200);
+        let tripped = env.as_contract(&contract_id, || check_and_record_volume(&env, 200));
         assert!(!tripped);
-        assert!(!is_tripped(&env));
+        env.as_contract(&contract_id, || assert!(!is_tripped(&env)));
 
-        let status = get_status(&env);
+        let status = env.as_contract(&contract_id, || get_status(&env));
         assert_eq!(status.current_volume, 200);
     }
 
     #[test]
     fn test_volume_partially_expired() {
-        let (env, admin) = setup_env();
-        set_ledger_time(&env, 1000);
+        let (env, contract_id, admin) = setup_env();
+        env.as_contract(&contract_id, || set_ledger_time(&env, 1000));
 
-        volume_circuit_breaker::set_threshold(&env, admin, 100, 1000).unwrap();
+        env.as_contract(&contract_id, || {
+            volume_circuit_breaker::set_threshold(&env, admin, 100, 1000).unwrap()
+        });
 
         // Add 300 at t=1000
-        check_and_record_volume(&env, 300);
+        env.as_contract(&contract_id, || check_and_record_volume(&env, 300));
 
         // Advance to t=1050 (50s later, first entry still within 100s window)
-        set_ledger_time(&env, 1050);
-        check_and_record_volume(&env, 300);
+        env.as_contract(&contract_id, || set_ledger_time(&env, 1050));
+        env.as_contract(&contract_id, || check_and_record_volume(&env, 300));
 
         // Advance to t=1101 (first entry at t=1000 is now 101s old → outside window)
-        set_ledger_time(&env, 1101);
-        check_and_record_volume(&env, 300);
+        env.as_contract(&contract_id, || set_ledger_time(&env, 1101));
+        env.as_contract(&contract_id, || check_and_record_volume(&env, 300));
 
         // Only entries at t=1050 (300) and t=1101 (300) should remain = 600
-        let status = get_status(&env);
+        let status = env.as_contract(&contract_id, || get_status(&env));
         assert_eq!(status.current_volume, 600);
     }
 
@@ -428,54 +441,62 @@ mod tests {
 
     #[test]
     fn test_event_emitted_on_trip() {
-        let (env, admin) = setup_env();
-        set_ledger_time(&env, 1000);
+        let (env, contract_id, admin) = setup_env();
+        env.as_contract(&contract_id, || set_ledger_time(&env, 1000));
 
-        volume_circuit_breaker::set_threshold(&env, admin, 60, 500).unwrap();
+        env.as_contract(&contract_id, || {
+            volume_circuit_breaker::set_threshold(&env, admin, 60, 500).unwrap()
+        });
 
         // No event expected yet
-        assert!(!is_tripped(&env));
+        env.as_contract(&contract_id, || assert!(!is_tripped(&env)));
 
         // Trigger trip
-        check_and_record_volume(&env, 600);
+        env.as_contract(&contract_id, || check_and_record_volume(&env, 600));
 
         // Verify tripped
-        assert!(is_tripped(&env));
+        env.as_contract(&contract_id, || assert!(is_tripped(&env)));
 
         // Verify the event was emitted by checking storage / event log
         // Soroban events are published to the environment; we verify indirectly
         // by ensuring the breaker is tripped and paused.
-        assert!(is_paused(&env));
+        env.as_contract(&contract_id, || assert!(is_paused(&env)));
     }
 
     // ── Edge cases ───────────────────────────────────────────────────────────
 
     #[test]
     fn test_zero_max_volume_never_trips() {
-        let (env, admin) = setup_env();
-        set_ledger_time(&env, 1000);
+        let (env, contract_id, admin) = setup_env();
+        env.as_contract(&contract_id, || set_ledger_time(&env, 1000));
 
         // Setting max_volume = 0 means the breaker is disabled
-        volume_circuit_breaker::set_threshold(&env, admin, 60, 0).unwrap();
+        env.as_contract(&contract_id, || {
+            volume_circuit_breaker::set_threshold(&env, admin, 60, 0).unwrap()
+        });
 
-        let tripped = check_and_record_volume(&env, 1_000_000_000);
+        let tripped = env.as_contract(&contract_id, || {
+            check_and_record_volume(&env, 1_000_000_000)
+        });
         assert!(!tripped);
-        assert!(!is_tripped(&env));
+        env.as_contract(&contract_id, || assert!(!is_tripped(&env)));
     }
 
     #[test]
     fn test_already_tripped_does_not_retrip() {
-        let (env, admin) = setup_env();
-        set_ledger_time(&env, 1000);
+        let (env, contract_id, admin) = setup_env();
+        env.as_contract(&contract_id, || set_ledger_time(&env, 1000));
 
-        volume_circuit_breaker::set_threshold(&env, admin, 60, 500).unwrap();
+        env.as_contract(&contract_id, || {
+            volume_circuit_breaker::set_threshold(&env, admin, 60, 500).unwrap()
+        });
 
         // First trip
-        check_and_record_volume(&env, 600);
-        assert!(is_tripped(&env));
+        env.as_contract(&contract_id, || check_and_record_volume(&env, 600));
+        env.as_contract(&contract_id, || assert!(is_tripped(&env)));
 
         // Subsequent call should not re-trip
-        let tripped = check_and_record_volume(&env, 100);
+        let tripped = env.as_contract(&contract_id, || check_and_record_volume(&env, 100));
         assert!(!tripped); // Already tripped, returns false
     }
 }
@@ -7,6 +7,7 @@ mod risk_management_tests {
         PositionLimits, RiskConfig, RiskMetrics,
     };
     use crate::tiers::UserTier;
+    use crate::CounterContract;
     use soroban_sdk::{symbol_short, testutils::Address as _, Address, Env, Symbol};
 
     // ===== POSITION LIMITS TESTS =====
@@ -14,20 +15,25 @@ mod risk_management_tests {
     #[test]
     fn test_position_limits_basic() {
         let env = Env::default();
+        let contract_id = env.register(CounterContract, ());
         let user = Address::generate(&env);
-        let mut portfolio = Portfolio::new(&env);
+        let mut portfolio = env.as_contract(&contract_id, || Portfolio::new(&env));
 
         // Set up user with some balance
-        portfolio.credit(&env, Asset::XLM, user.clone(), 1000);
+        env.as_contract(&contract_id, || {
+            portfolio.credit(&env, Asset::XLM, user.clone(), 1000)
+        });
 
         // Test basic position limit check
-        let result = PositionLimits::check_position_limits(
-            &env,
-            &portfolio,
-            &user,
-            &Asset::XLM,
-            100, // Additional amount
-        );
+        let result = env.as_contract(&contract_id, || {
+            PositionLimits::check_position_limits(
+                &env,
+                &portfolio,
+                &user,
+                &Asset::XLM,
+                100, // Additional amount
+            )
+        });
 
         assert!(result.is_ok());
     }
@@ -35,20 +41,27 @@ mod risk_management_tests {
     #[test]
     fn test_position_limits_exceeded() {
         let env = Env::default();
+        let contract_id = env.register(CounterContract, ());
         let user = Address::generate(&env);
-        let mut portfolio = Portfolio::new(&env);
+        let mut portfolio = env.as_contract(&contract_id, || Portfolio::new(&env));
 
-        // Set up user with large balance
-        portfolio.credit(&env, Asset::XLM, user.clone(), 1_000_000_000);
+        // Set up user at a new (Novice) trader's per-asset limit
+        let limit =
+            PositionLimits::get_tier_position_limit(&RiskConfig::default(), &UserTier::Novice);
+        env.as_contract(&contract_id, || {
+            portfolio.credit(&env, Asset::XLM, user.clone(), limit)
+        });
 
         // Try to add amount that exceeds limit
-        let result = PositionLimits::check_position_limits(
-            &env,
-            &portfolio,
-            &user,
-            &Asset::XLM,
-            1_000_000_000, // This should exceed default limits
-        );
+        let result = env.as_contract(&contract_id, || {
+            PositionLimits::check_position_limits(
+                &env,
+                &portfolio,
+                &user,
+                &Asset::XLM,
+                1, // This should exceed default limits
+            )
+        });
 
         assert!(result.is_err());
     }
@@ -56,6 +69,7 @@ mod risk_management_tests {
     #[test]
     fn test_tier_based_limits() {
         let env = Env::default();
+        let contract_id = env.register(CounterContract, ());
         let config = RiskConfig::default();
 
         // Test different tier limits
@@ -72,57 +86,128 @@ mod risk_management_tests {
     #[test]
     fn test_concentration_risk_low() {
         let env = Env::default();
+        let contract_id = env.register(CounterContract, ());
         let user = Address::generate(&env);
-        let mut portfolio = Portfolio::new(&env);
+        let mut portfolio = env.as_contract(&contract_id, || Portfolio::new(&env));
 
         // Balanced portfolio: 50% XLM, 50% USDC
-        portfolio.credit(&env, Asset::XLM, user.clone(), 500);
-        portfolio.credit(
-            &env,
-            Asset::Custom(symbol_short!("USDCSIM")),
-            user.clone(),
-            500,
-        );
-
-        let risk = ConcentrationRisk::calculate_concentration_risk(&env, &portfolio, &user);
-        assert!(risk < 30); // Should be low risk
+        env.as_contract(&contract_id, || {
+            portfolio.credit(&env, Asset::XLM, user.clone(), 500)
+        });
+        env.as_contract(&contract_id, || {
+            portfolio.credit(
+                &env,
+                Asset::Custom(symbol_short!("USDCSIM")),
+                user.clone(),
+                500,
+            )
+        });
+
+        let risk = env.as_contract(&contract_id, || {
+            ConcentrationRisk::calculate_concentration_risk(&env, &portfolio, &user)
+        });
+        // Two assets can't sit below 50% concentration, but an even split
+        // stays under the top band
+        assert!(risk < ConcentrationRisk::score_concentration(6000));
     }
 
     #[test]
     fn test_concentration_risk_high() {
         let env = Env::default();
+        let contract_id = env.register(CounterContract, ());
         let user = Address::generate(&env);
-        let mut portfolio = Portfolio::new(&env);
+        let mut portfolio = env.as_contract(&contract_id, || Portfolio::new(&env));
 
         // Highly concentrated: 95% XLM, 5% USDC
-        portfolio.credit(&env, Asset::XLM, user.clone(), 950);
-        portfolio.credit(
-            &env,
-            Asset::Custom(symbol_short!("USDCSIM")),
-            user.clone(),
-            50,
-        );
-
-        let risk = ConcentrationRisk::calculate_concentration_risk(&env, &portfolio, &user);
+        env.as_contract(&contract_id, || {
+            portfolio.credit(&env, Asset::XLM, user.clone(), 950)
+        });
+        env.as_contract(&contract_id, || {
+            portfolio.credit(
+                &env,
+                Asset::Custom(symbol_short!("USDCSIM")),
+                user.clone(),
+                50,
+            )
+        });
+
+        let risk = env.as_contract(&contract_id, || {
+            ConcentrationRisk::calculate_concentration_risk(&env, &portfolio, &user)
+        });
         assert!(risk > 70); // Should be high risk
     }
 
+    #[test]
+    fn test_swap_concentration_limit_allows_diversifying() {
+        let env = Env::default();
+        let contract_id = env.register(CounterContract, ());
+        let user = Address::generate(&env);
+        let mut portfolio = env.as_contract(&contract_id, || Portfolio::new(&env));
+        let usdc = Asset::Custom(symbol_short!("USDCSIM"));
+
+        // 100% XLM is over the 50% limit already
+        env.as_contract(&contract_id, || {
+            portfolio.credit(&env, Asset::XLM, user.clone(), 1000)
+        });
+
+        // Selling XLM lowers concentration, so it passes
+        let breaches = env.as_contract(&contract_id, || {
+            ConcentrationRisk::swap_breaches_concentration_limit(
+                &env,
+                &portfolio,
+                &user,
+                &Asset::XLM,
+                300,
+                &usdc,
+                300,
+            )
+        });
+        assert!(!breaches);
+
+        // Buying back XLM from 70/30 pushes concentration up, so it breaches
+        env.as_contract(&contract_id, || {
+            portfolio.debit(&env, Asset::XLM, user.clone(), 300)
+        });
+        env.as_contract(&contract_id, || {
+            portfolio.credit(&env, usdc.clone(), user.clone(), 300)
+        });
+        let breaches = env.as_contract(&contract_id, || {
+            ConcentrationRisk::swap_breaches_concentration_limit(
+                &env,
+                &portfolio,
+                &user,
+                &usdc,
+                100,
+                &Asset::XLM,
+                100,
+            )
+        });
+        assert!(breaches);
+    }
+
     #[test]
     fn test_concentration_warning_threshold() {
         let env = Env::default();
+        let contract_id = env.register(CounterContract, ());
         let user = Address::generate(&env);
-        let mut portfolio = Portfolio::new(&env);
+        let mut portfolio = env.as_contract(&contract_id, || Portfolio::new(&env));
 
         // 35% concentration (above 30% threshold)
-        portfolio.credit(&env, Asset::XLM, user.clone(), 700);
-        portfolio.credit(
-            &env,
-            Asset::Custom(symbol_short!("USDCSIM")),
-            user.clone(),
-            300,
-        );
-
-        let warning = ConcentrationRisk::check_concentration_warning(&env, &portfolio, &user);
+        env.as_contract(&contract_id, || {
+            portfolio.credit(&env, Asset::XLM, user.clone(), 700)
+        });
+        env.as_contract(&contract_id, || {
+            portfolio.credit(
+                &env,
+                Asset::Custom(symbol_short!("USDCSIM")),
+                user.clone(),
+                300,
+            )
+        });
+
+        let warning = env.as_contract(&contract_id, || {
+            ConcentrationRisk::check_concentration_warning(&env, &portfolio, &user)
+        });
         assert!(warning);
     }
 
@@ -131,22 +216,32 @@ mod risk_management_tests {
     #[test]
     fn test_circuit_breaker_inactive_by_default() {
         let env = Env::default();
+        let contract_id = env.register(CounterContract, ());
 
-        let active = CircuitBreaker::is_circuit_breaker_active(&env);
+        let active = env.as_contract(&contract_id, || {
+            CircuitBreaker::is_circuit_breaker_active(&env)
+        });
         assert!(!active);
     }
 
     #[test]
     fn test_circuit_breaker_trigger() {
         let env = Env::default();
+        let contract_id = env.register(CounterContract, ());
         let reason = symbol_short!("TEST");
 
-        CircuitBreaker::trigger_circuit_breaker(&env, reason.clone(), 2000); // 20% change
+        env.as_contract(&contract_id, || {
+            CircuitBreaker::trigger_circuit_breaker(&env, reason.clone(), 2000) // 20% change
+        });
 
-        let active = CircuitBreaker::is_circuit_breaker_active(&env);
+        let active = env.as_contract(&contract_id, || {
+            CircuitBreaker::is_circuit_breaker_active(&env)
+        });
         assert!(active);
 
-        let state = CircuitBreaker::get_circuit_breaker_state(&env);
+        let state = env.as_contract(&contract_id, || {
+            CircuitBreaker::get_circuit_breaker_state(&env)
+        });
         assert_eq!(state.trigger_reason, reason);
         assert_eq!(state.price_change_pct, 2000);
     }
@@ -154,14 +249,21 @@ mod risk_management_tests {
     #[test]
     fn test_circuit_breaker_reset() {
         let env = Env::default();
+        let contract_id = env.register(CounterContract, ());
 
         // Trigger first
-        CircuitBreaker::trigger_circuit_breaker(&env, symbol_short!("TEST"), 1500);
-        assert!(CircuitBreaker::is_circuit_breaker_active(&env));
+        env.as_contract(&contract_id, || {
+            CircuitBreaker::trigger_circuit_breaker(&env, symbol_short!("TEST"), 1500)
+        });
+        env.as_contract(&contract_id, || {
+            assert!(CircuitBreaker::is_circuit_breaker_active(&env))
+        });
 
         // Reset
-        CircuitBreaker::reset_circuit_breaker(&env);
-        assert!(!CircuitBreaker::is_circuit_breaker_active(&env));
+        env.as_contract(&contract_id, || CircuitBreaker::reset_circuit_breaker(&env));
+        env.as_contract(&contract_id, || {
+            assert!(!CircuitBreaker::is_circuit_breaker_active(&env))
+        });
     }
 
     // ===== RISK METRICS TESTS =====
@@ -169,19 +271,26 @@ mod risk_management_tests {
     #[test]
     fn test_risk_metrics_calculation() {
         let env = Env::default();
+        let contract_id = env.register(CounterContract, ());
         let user = Address::generate(&env);
-        let mut portfolio = Portfolio::new(&env);
+        let mut portfolio = env.as_contract(&contract_id, || Portfolio::new(&env));
 
         // Set up a test portfolio
-        portfolio.credit(&env, Asset::XLM, user.clone(), 600);
-        portfolio.credit(
-            &env,
-            Asset::Custom(symbol_short!("USDCSIM")),
-            user.clone(),
-            400,
-        );
-
-        let metrics = PortfolioRisk::calculate_risk_metrics(&env, &portfolio, &user);
+        env.as_contract(&contract_id, || {
+            portfolio.credit(&env, Asset::XLM, user.clone(), 600)
+        });
+        env.as_contract(&contract_id, || {
+            portfolio.credit(
+                &env,
+                Asset::Custom(symbol_short!("USDCSIM")),
+                user.clone(),
+                400,
+            )
+        });
+
+        let metrics = env.as_contract(&contract_id, || {
+            PortfolioRisk::calculate_risk_metrics(&env, &portfolio, &user)
+        });
 
         // Check that metrics are reasonable
         assert!(metrics.overall_risk_score <= 100);
@@ -209,19 +318,23 @@ mod risk_management_tests {
     #[test]
     fn test_risk_limits_integration() {
         let env = Env::default();
+        let contract_id = env.register(CounterContract, ());
         let user = Address::generate(&env);
-        let mut portfolio = Portfolio::new(&env);
+        let mut portfolio = env.as_contract(&contract_id, || Portfolio::new(&env));
 
         // Set up user with high balance
-        portfolio.credit(&env, Asset::XLM, user.clone(), 1_000_000);
+        env.as_contract(&contract_id, || {
+            portfolio.credit(&env, Asset::XLM, user.clone(), 1_000_000)
+        });
 
         // Test the contract's risk limit checking function
         let from = symbol_short!("XLM");
         let to = symbol_short!("USDCSIM");
         let amount = 100; // Small amount should be OK
 
-        let would_exceed =
-            CounterContract::check_risk_limits(env.clone(), user.clone(), to, amount);
+        let would_exceed = env.as_contract(&contract_id, || {
+            CounterContract::check_risk_limits(env.clone(), user.clone(), to, amount)
+        });
 
         assert!(!would_exceed);
     }
@@ -229,33 +342,45 @@ mod risk_management_tests {
     #[test]
     fn test_concentration_limit_integration() {
         let env = Env::default();
+        let contract_id = env.register(CounterContract, ());
         let user = Address::generate(&env);
-        let mut portfolio = Portfolio::new(&env);
+        let mut portfolio = env.as_contract(&contract_id, || Portfolio::new(&env));
 
         // Create highly concentrated portfolio
-        portfolio.credit(&env, Asset::XLM, user.clone(), 900);
-        portfolio.credit(
-            &env,
-            Asset::Custom(symbol_short!("USDCSIM")),
-            user.clone(),
-            100,
-        );
+        env.as_contract(&contract_id, || {
+            portfolio.credit(&env, Asset::XLM, user.clone(), 900)
+        });
+        env.as_contract(&contract_id, || {
+            portfolio.credit(
+                &env,
+                Asset::Custom(symbol_short!("USDCSIM")),
+                user.clone(),
+                100,
+            )
+        });
 
         // Save portfolio to storage for contract functions
-        env.storage().instance().set(&(), &portfolio);
+        env.as_contract(&contract_id, || {
+            env.storage().instance().set(&(), &portfolio)
+        });
 
-        let limit_exceeded = CounterContract::check_concentration_limit(env, user);
+        let limit_exceeded = env.as_contract(&contract_id, || {
+            CounterContract::check_concentration_limit(env.clone(), user)
+        });
         assert!(limit_exceeded); // Should exceed 50% limit
     }
 
     #[test]
     fn test_circuit_breaker_integration() {
         let env = Env::default();
+        let contract_id = env.register(CounterContract, ());
         let admin = Address::generate(&env);
 
         // Mock admin check (in real test, would need proper setup)
         // For now, test the state functions
-        let initial_state = CounterContract::get_circuit_breaker_status(env.clone());
+        let initial_state = env.as_contract(&contract_id, || {
+            CounterContract::get_circuit_breaker_status(env.clone())
+        });
         assert!(!initial_state.is_active);
     }
 
@@ -264,43 +389,57 @@ mod risk_management_tests {
     #[test]
     fn test_zero_balance_portfolio() {
         let env = Env::default();
+        let contract_id = env.register(CounterContract, ());
         let user = Address::generate(&env);
-        let portfolio = Portfolio::new(&env);
+        let portfolio = env.as_contract(&contract_id, || Portfolio::new(&env));
 
-        let risk = ConcentrationRisk::calculate_concentration_risk(&env, &portfolio, &user);
+        let risk = env.as_contract(&contract_id, || {
+            ConcentrationRisk::calculate_concentration_risk(&env, &portfolio, &user)
+        });
         assert_eq!(risk, 0); // Zero balance should have zero risk
     }
 
     #[test]
     fn test_single_asset_portfolio() {
         let env = Env::default();
+        let contract_id = env.register(CounterContract, ());
         let user = Address::generate(&env);
-        let mut portfolio = Portfolio::new(&env);
+        let mut portfolio = env.as_contract(&contract_id, || Portfolio::new(&env));
 
         // 100% in one asset
-        portfolio.credit(&env, Asset::XLM, user.clone(), 1000);
+        env.as_contract(&contract_id, || {
+            portfolio.credit(&env, Asset::XLM, user.clone(), 1000)
+        });
 
-        let risk = ConcentrationRisk::calculate_concentration_risk(&env, &portfolio, &user);
+        let risk = env.as_contract(&contract_id, || {
+            ConcentrationRisk::calculate_concentration_risk(&env, &portfolio, &user)
+        });
         assert_eq!(risk, 100); // Maximum risk
     }
 
     #[test]
     fn test_maximum_position_limits() {
         let env = Env::default();
+        let contract_id = env.register(CounterContract, ());
         let user = Address::generate(&env);
-        let mut portfolio = Portfolio::new(&env);
-
-        // Try to add maximum possible amount
-        let max_limit = RiskConfig::default().max_position_per_asset;
-        portfolio.credit(&env, Asset::XLM, user.clone(), max_limit - 1);
-
-        let result = PositionLimits::check_position_limits(
-            &env,
-            &portfolio,
-            &user,
-            &Asset::XLM,
-            1, // Should be OK
-        );
+        let mut portfolio = env.as_contract(&contract_id, || Portfolio::new(&env));
+
+        // Try to add maximum possible amount for a new (Novice) trader
+        let max_limit =
+            PositionLimits::get_tier_position_limit(&RiskConfig::default(), &UserTier::Novice);
+        env.as_contract(&contract_id, || {
+            portfolio.credit(&env, Asset::XLM, user.clone(), max_limit - 1)
+        });
+
+        let result = env.as_contract(&contract_id, || {
+            PositionLimits::check_position_limits(
+                &env,
+                &portfolio,
+                &user,
+                &Asset::XLM,
+                1, // Should be OK
+            )
+        });
 
         assert!(result.is_ok());
     }
@@ -308,19 +447,24 @@ mod risk_management_tests {
     #[test]
     fn test_risk_config_updates() {
         let env = Env::default();
+        let contract_id = env.register(CounterContract, ());
 
         // Get default config
-        let original_config = PositionLimits::get_risk_config(&env);
+        let original_config =
+            env.as_contract(&contract_id, || PositionLimits::get_risk_config(&env));
 
         // Create modified config
         let mut new_config = original_config.clone();
         new_config.max_position_per_user = 2000000000000; // 2x default
 
         // Set new config
-        PositionLimits::set_risk_config(&env, &new_config);
+        env.as_contract(&contract_id, || {
+            PositionLimits::set_risk_config(&env, &new_config)
+        });
 
         // Verify it was updated
-        let retrieved_config = PositionLimits::get_risk_config(&env);
+        let retrieved_config =
+            env.as_contract(&contract_id, || PositionLimits::get_risk_config(&env));
         assert_eq!(retrieved_config.max_position_per_user, 2000000000000);
     }
 }
@@ -0,0 +1,200 @@
+#![cfg(test)]
+
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::{symbol_short, Address, Env, Symbol};
+
+use crate::kyc::KYCStatus;
+use crate::oracle_adapter::PriceObservation;
+use crate::portfolio::{Asset, Portfolio};
+use crate::risk_management::valuation::{PRICE_PRECISION, USD};
+use crate::risk_management::volatility::{volatility_from_history, DAY_SECS};
+use crate::risk_management::PositionSource;
+use crate::staking_bonus::StakingBonusManager;
+use crate::storage::ADMIN_KEY;
+use crate::{CounterContract, CounterContractClient};
+
+const XLM_PRICE: u128 = PRICE_PRECISION / 5; // $0.20
+
+struct Setup {
+    env: Env,
+    contract_id: Address,
+    client: CounterContractClient<'static>,
+    admin: Address,
+    user: Address,
+}
+
+fn setup() -> Setup {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|l| l.timestamp = 1_000_000);
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(&ADMIN_KEY, &admin);
+        let mut portfolio = Portfolio::new(&env);
+        portfolio.credit(&env, Asset::XLM, user.clone(), 1_000);
+        portfolio.credit(
+            &env,
+            Asset::Custom(symbol_short!("USDCSIM")),
+            user.clone(),
+            300,
+        );
+        env.storage().instance().set(&(), &portfolio);
+    });
+    client.init_oracle_feed(&admin, &(symbol_short!("XLM"), USD), &XLM_PRICE);
+    client.init_oracle_feed(&admin, &(symbol_short!("USDCSIM"), USD), &PRICE_PRECISION);
+
+    Setup {
+        env,
+        contract_id,
+        client,
+        admin,
+        user,
+    }
+}
+
+fn observations(env: &Env, prices: &[u128], interval: u64) -> soroban_sdk::Vec<PriceObservation> {
+    let mut history = soroban_sdk::Vec::new(env);
+    for (i, price) in prices.iter().enumerate() {
+        history.push_back(PriceObservation {
+            price: *price,
+            timestamp: i as u64 * interval,
+        });
+    }
+    history
+}
+
+#[test]
+fn test_volatility_scales_to_one_day() {
+    let env = Env::default();
+    let swings = [100, 110, 100, 110];
+
+    assert_eq!(
+        volatility_from_history(&observations(&env, &swings, DAY_SECS)),
+        Some(1_102)
+    );
+    // The same swings every hour are sqrt(24) times as volatile per day.
+    assert_eq!(
+        volatility_from_history(&observations(&env, &swings, 3_600)),
+        Some(5_399)
+    );
+    assert_eq!(
+        volatility_from_history(&observations(&env, &[100, 100, 100], 60)),
+        Some(0)
+    );
+    assert_eq!(
+        volatility_from_history(&observations(&env, &[100, 110], DAY_SECS)),
+        None
+    );
+}
+
+#[test]
+fn test_valuation_prices_balances_lp_shares_and_stakes() {
+    let s = setup();
+    let operator = Address::generate(&s.env);
+    s.client.kyc_add_operator(&s.admin, &operator);
+    s.client.kyc_submit(&s.user);
+    s.client
+        .kyc_update_status(&operator, &s.user, &KYCStatus::InReview, &None);
+    s.client
+        .kyc_update_status(&operator, &s.user, &KYCStatus::Verified, &None);
+
+    let xlm = symbol_short!("XLM");
+    let usdc = symbol_short!("USDCSIM");
+    let pool_id = s
+        .client
+        .register_pool(&s.admin, &xlm, &usdc, &10_000, &2_000, &30);
+    let (amount_a, amount_b) = if s.client.get_pool(&pool_id).unwrap().token_a == xlm {
+        (5_000, 1_000)
+    } else {
+        (1_000, 5_000)
+    };
+    s.client
+        .pool_add_liquidity(&pool_id, &amount_a, &amount_b, &s.user);
+
+    let toka = symbol_short!("TOKA");
+    s.env.as_contract(&s.contract_id, || {
+        let mut portfolio: Portfolio = s.env.storage().instance().get(&()).unwrap();
+        portfolio.credit(&s.env, Asset::Custom(toka.clone()), s.user.clone(), 50);
+        s.env.storage().instance().set(&(), &portfolio);
+        StakingBonusManager::stake(&s.env, s.user.clone(), 500, 30).unwrap();
+    });
+
+    let valuation = s.client.get_portfolio_valuation(&s.user);
+    let value_of = |source: PositionSource, asset: &Symbol| -> i128 {
+        valuation
+            .positions
+            .iter()
+            .filter(|p| p.source == source && p.asset == *asset)
+            .map(|p| p.value_usd)
+            .sum()
+    };
+
+    assert_eq!(value_of(PositionSource::Balance, &xlm), 200);
+    assert_eq!(value_of(PositionSource::Balance, &usdc), 300);
+    // No TOKA feed: held at par and reported.
+    assert_eq!(value_of(PositionSource::Balance, &toka), 50);
+    assert_eq!(valuation.unpriced_positions, 1);
+    assert_eq!(value_of(PositionSource::Staked, &xlm), 100);
+
+    let lp_xlm = value_of(PositionSource::LpShare(pool_id), &xlm);
+    let lp_usdc = value_of(PositionSource::LpShare(pool_id), &usdc);
+    assert!((999..=1_000).contains(&lp_xlm));
+    assert!((999..=1_000).contains(&lp_usdc));
+
+    assert_eq!(
+        valuation.total_value_usd,
+        200 + 300 + 50 + 100 + lp_xlm + lp_usdc
+    );
+    assert_eq!(
+        s.client.get_risk_metrics(&s.user).total_exposure_usd,
+        valuation.total_value_usd
+    );
+}
+
+#[test]
+fn test_risk_metrics_use_oracle_volatility_for_var() {
+    let s = setup();
+    let xlm_pair = (symbol_short!("XLM"), USD);
+    let usdc_pair = (symbol_short!("USDCSIM"), USD);
+    let high = XLM_PRICE * 11 / 10;
+    for price in [XLM_PRICE, high, XLM_PRICE, high] {
+        s.env.ledger().with_mut(|l| l.timestamp += 3_600);
+        s.client.submit_oracle_price(&s.admin, &xlm_pair, &price);
+        s.client
+            .submit_oracle_price(&s.admin, &usdc_pair, &PRICE_PRECISION);
+    }
+
+    let valuation = s.client.get_portfolio_valuation(&s.user);
+    let xlm = valuation
+        .positions
+        .iter()
+        .find(|p| p.asset == symbol_short!("XLM"))
+        .unwrap();
+    assert_eq!(xlm.value_usd, 220);
+    assert_eq!(xlm.daily_volatility_bps, 5_399);
+
+    let metrics = s.client.get_risk_metrics(&s.user);
+    assert_eq!(metrics.total_exposure_usd, 520);
+    // 1.645 * 53.99% of $220; the stablecoin leg carries no risk.
+    assert_eq!(metrics.value_at_risk_usd, 195);
+    assert_eq!(metrics.unpriced_positions, 0);
+    assert_eq!(metrics.largest_position_pct, 300 * 10_000 / 520);
+    assert_eq!(metrics.volatility_risk, 100);
+}
+
+#[test]
+fn test_oracle_feeds_are_admin_only() {
+    let s = setup();
+    let outsider = Address::generate(&s.env);
+    assert!(s
+        .client
+        .try_submit_oracle_price(&outsider, &(symbol_short!("XLM"), USD), &XLM_PRICE)
+        .is_err());
+    assert_eq!(
+        s.client.get_oracle_price(&(symbol_short!("XLM"), USD)),
+        XLM_PRICE
+    );
+}
@@ -12,7 +12,7 @@ use crate::errors::SwapTradeError;
 /// - 60 days:  12% bonus
 /// - 90 days:  20% bonus
 /// - 365 days: 50% bonus
-use soroban_sdk::{contracttype, symbol_short, Address, Env, Vec};
+use soroban_sdk::{contracttype, symbol_short, Address, Env, Map, Vec};
 
 // ────────────────────────────────────────────────────────────────────────────
 // Constants
@@ -107,6 +107,8 @@ pub enum StakingBonusKey {
     TotalBonusesDistributed,
     /// Registry of all staker addresses (for distribution iteration)
     StakerRegistry,
+    /// Active stake still locked, bucketed by unlock time (Map<u64, i128>)
+    LockedByUnlockTime,
 }
 
 // ────────────────────────────────────────────────────────────────────────────
@@ -235,6 +237,7 @@ impl StakingBonusManager {
         env.storage()
             .persistent()
             .set(&StakingBonusKey::TotalStaked, &(prev_global + amount));
+        Self::adjust_locked(env, unlock_time, amount);
 
         // Emit event
         env.events().publish(
@@ -279,6 +282,7 @@ impl StakingBonusManager {
         let penalty = (stake.amount * 10) / 100;
         let principal_returned = stake.amount - penalty;
         let staked_amount = stake.amount;
+        Self::adjust_locked(env, stake.unlock_at, -staked_amount);
 
         // Mark stake as inactive
         stake.is_active = false;
@@ -474,6 +478,57 @@ impl StakingBonusManager {
         Ok(principal)
     }
 
+    /// Close every active stake at full principal, ignoring lock times.
+    /// Used by emergency exit, which credits the returned principal to the
+    /// user's `STAKED_ASSET` balance; unclaimed bonuses are forfeited.
+    ///
+    /// # Returns
+    /// (principal_returned, bonuses_forfeited)
+    pub fn emergency_withdraw(env: &Env, user: Address) -> (i128, i128) {
+        let mut stakes = Self::get_user_stakes(env, user.clone());
+        let mut principal = 0i128;
+        let mut forfeited = 0i128;
+
+        for i in 0..stakes.len() {
+            let mut stake = stakes.get(i).unwrap();
+            if !stake.is_active {
+                continue;
+            }
+            principal += stake.amount;
+            Self::adjust_locked(env, stake.unlock_at, -stake.amount);
+            if !stake.bonus_claimed {
+                forfeited += stake.bonus_amount;
+            }
+            stake.is_active = false;
+            stakes.set(i, stake);
+        }
+
+        if principal == 0 {
+            return (0, 0);
+        }
+
+        env.storage()
+            .persistent()
+            .set(&StakingBonusKey::UserStakes(user.clone()), &stakes);
+
+        let prev_total: i128 = env
+            .storage()
+            .persistent()
+            .get(&StakingBonusKey::UserTotalStaked(user.clone()))
+            .unwrap_or(0);
+        env.storage().persistent().set(
+            &StakingBonusKey::UserTotalStaked(user),
+            &(prev_total - principal),
+        );
+
+        let prev_global = Self::get_total_staked(env);
+        env.storage()
+            .persistent()
+            .set(&StakingBonusKey::TotalStaked, &(prev_global - principal));
+
+        (principal, forfeited)
+    }
+
     // ────────────────────────────────────────────────────────────────────────
     // Distribution Operations
     // ────────────────────────────────────────────────────────────────────────
@@ -601,6 +656,66 @@ impl StakingBonusManager {
             .unwrap_or(0)
     }
 
+    /// Get the amount a user still has locked, i.e. active stakes whose
+    /// unlock time has not been reached yet
+    pub fn get_user_locked_stake(env: &Env, user: Address) -> i128 {
+        let now = env.ledger().timestamp();
+        let stakes = Self::get_user_stakes(env, user);
+        let mut total = 0i128;
+        for stake in stakes.iter() {
+            if stake.is_active && stake.unlock_at > now {
+                total += stake.amount;
+            }
+        }
+        total
+    }
+
+    /// Get the amount still locked across all users, i.e. the global
+    /// counterpart of `get_user_locked_stake`
+    pub fn get_total_locked_stake(env: &Env) -> i128 {
+        let now = env.ledger().timestamp();
+        let buckets: Map<u64, i128> = env
+            .storage()
+            .persistent()
+            .get(&StakingBonusKey::LockedByUnlockTime)
+            .unwrap_or_else(|| Map::new(env));
+        let mut total = 0i128;
+        for (unlock_at, amount) in buckets.iter() {
+            if unlock_at > now {
+                total += amount;
+            }
+        }
+        total
+    }
+
+    /// Add `delta` to the locked bucket for `unlock_at`, dropping buckets
+    /// whose unlock time has passed
+    fn adjust_locked(env: &Env, unlock_at: u64, delta: i128) {
+        let now = env.ledger().timestamp();
+        let stored: Map<u64, i128> = env
+            .storage()
+            .persistent()
+            .get(&StakingBonusKey::LockedByUnlockTime)
+            .unwrap_or_else(|| Map::new(env));
+        let mut buckets = Map::new(env);
+        for (time, amount) in stored.iter() {
+            if time > now {
+                buckets.set(time, amount);
+            }
+        }
+        if unlock_at > now {
+            let amount = buckets.get(unlock_at).unwrap_or(0) + delta;
+            if amount > 0 {
+                buckets.set(unlock_at, amount);
+            } else {
+                buckets.remove(unlock_at);
+            }
+        }
+        env.storage()
+            .persistent()
+            .set(&StakingBonusKey::LockedByUnlockTime, &buckets);
+    }
+
     /// Get total staked amount globally
     pub fn get_total_staked(env: &Env) -> i128 {
         env.storage()
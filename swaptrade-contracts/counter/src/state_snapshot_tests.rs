@@ -8,12 +8,53 @@ mod tests {
     };
     use crate::CounterContract;
     use soroban_sdk::testutils::{Address as _, Ledger};
+    use crate::kyc::KYCStatus;
+    use crate::risk_management::PositionLimits;
+    use crate::tiers::UserTier;
     use soroban_sdk::{symbol_short, Address, Env};
 
+    /// `amount` less the swap fee a new (Novice) trader pays.
+    fn net_of_fee(client: &crate::CounterContractClient, amount: i128) -> i128 {
+        let fee_bps = client.get_effective_fee_bps(&UserTier::Novice) as i128;
+        amount - amount * fee_bps / 10000
+    }
+
+    /// Pass `user` through KYC so the client's gated entrypoints accept them,
+    /// and seed the XLM/USDCSIM price that `swap` validates against.
+    fn verify_user(env: &Env, contract_id: &Address, user: &Address) {
+        let client = crate::CounterContractClient::new(env, contract_id);
+        let admin = Address::generate(env);
+        let operator = Address::generate(env);
+        env.as_contract(contract_id, || {
+            crate::admin::set_admin(env, &admin);
+            // Single-asset portfolios sit at 100% concentration; lift the
+            // limit so these tests exercise the swap path itself.
+            let mut config = PositionLimits::get_risk_config(env);
+            config.concentration_limit_threshold = 10_001;
+            PositionLimits::set_risk_config(env, &config);
+        });
+        client.kyc_add_operator(&admin, &operator);
+        client.kyc_submit(user);
+        client.kyc_update_status(&operator, user, &KYCStatus::InReview, &None);
+        client.kyc_update_status(&operator, user, &KYCStatus::Verified, &None);
+        // A second trader in the same test finds the feed already set up.
+        let _ = client.try_init_oracle_feed(
+            &admin,
+            &(symbol_short!("XLM"), symbol_short!("USDCSIM")),
+            &crate::risk_management::valuation::PRICE_PRECISION,
+        );
+    }
+
     #[test]
     fn test_snapshot_creation() {
         let env = Env::default();
-        let snapshot = StateSnapshotManager::create_snapshot(&env);
+        env.ledger().with_mut(|li| {
+            li.timestamp = 1_000;
+            li.sequence_number = 10;
+        });
+        let contract_id = env.register(CounterContract, ());
+        let snapshot =
+            env.as_contract(&contract_id, || StateSnapshotManager::create_snapshot(&env));
 
         assert!(snapshot.timestamp > 0);
         assert!(snapshot.block_number > 0);
@@ -23,17 +64,23 @@ mod tests {
     #[test]
     fn test_snapshot_validation_same_block() {
         let env = Env::default();
-        let snapshot = StateSnapshotManager::create_snapshot(&env);
+        let contract_id = env.register(CounterContract, ());
+        let snapshot =
+            env.as_contract(&contract_id, || StateSnapshotManager::create_snapshot(&env));
 
         // Should be valid in same block
-        let valid = StateSnapshotManager::validate_snapshot(&env, &snapshot);
+        let valid = env.as_contract(&contract_id, || {
+            StateSnapshotManager::validate_snapshot(&env, &snapshot)
+        });
         assert!(valid);
     }
 
     #[test]
     fn test_snapshot_validation_next_block() {
         let env = Env::default();
-        let snapshot = StateSnapshotManager::create_snapshot(&env);
+        let contract_id = env.register(CounterContract, ());
+        let snapshot =
+            env.as_contract(&contract_id, || StateSnapshotManager::create_snapshot(&env));
 
         // Advance one block
         env.ledger().with_mut(|li| {
@@ -41,14 +88,18 @@ mod tests {
         });
 
         // Should still be valid
-        let valid = StateSnapshotManager::validate_snapshot(&env, &snapshot);
+        let valid = env.as_contract(&contract_id, || {
+            StateSnapshotManager::validate_snapshot(&env, &snapshot)
+        });
         assert!(valid);
     }
 
     #[test]
     fn test_snapshot_invalid_after_multiple_blocks() {
         let env = Env::default();
-        let snapshot = StateSnapshotManager::create_snapshot(&env);
+        let contract_id = env.register(CounterContract, ());
+        let snapshot =
+            env.as_contract(&contract_id, || StateSnapshotManager::create_snapshot(&env));
 
         // Advance multiple blocks
         env.ledger().with_mut(|li| {
@@ -56,17 +107,20 @@ mod tests {
         });
 
         // Should be invalid
-        let valid = StateSnapshotManager::validate_snapshot(&env, &snapshot);
+        let valid = env.as_contract(&contract_id, || {
+            StateSnapshotManager::validate_snapshot(&env, &snapshot)
+        });
         assert!(!valid);
     }
 
     #[test]
     fn test_read_consistency_guard() {
         let env = Env::default();
-        let guard = ReadConsistencyGuard::new(&env);
+        let contract_id = env.register(CounterContract, ());
+        let guard = env.as_contract(&contract_id, || ReadConsistencyGuard::new(&env));
 
         // Should be valid initially
-        let valid = guard.validate(&env);
+        let valid = env.as_contract(&contract_id, || guard.validate(&env));
         assert!(valid);
 
         // Advance one block - still valid
@@ -74,7 +128,7 @@ mod tests {
             li.sequence_number += 1;
         });
 
-        let valid = guard.validate(&env);
+        let valid = env.as_contract(&contract_id, || guard.validate(&env));
         assert!(valid);
     }
 
@@ -122,9 +176,12 @@ mod tests {
     #[test]
     fn test_snapshot_ids_increment() {
         let env = Env::default();
+        let contract_id = env.register(CounterContract, ());
 
-        let snapshot1 = StateSnapshotManager::create_snapshot(&env);
-        let snapshot2 = StateSnapshotManager::create_snapshot(&env);
+        let snapshot1 =
+            env.as_contract(&contract_id, || StateSnapshotManager::create_snapshot(&env));
+        let snapshot2 =
+            env.as_contract(&contract_id, || StateSnapshotManager::create_snapshot(&env));
 
         assert_eq!(snapshot2.snapshot_id, snapshot1.snapshot_id + 1);
     }
@@ -132,21 +189,29 @@ mod tests {
     #[test]
     fn test_atomic_operation_executes_successfully() {
         let env = Env::default();
+        env.ledger().with_mut(|li| {
+            li.timestamp = 1_000;
+            li.sequence_number = 10;
+        });
+        let contract_id = env.register(CounterContract, ());
 
-        let result = AtomicOperation::execute(&env, |env, snapshot| {
-            // Operation can use both env and snapshot
-            assert!(snapshot.timestamp > 0);
-            env.ledger().sequence()
+        let result = env.as_contract(&contract_id, || {
+            AtomicOperation::execute(&env, |env, snapshot| {
+                // Operation can use both env and snapshot
+                assert!(snapshot.timestamp > 0);
+                env.ledger().sequence()
+            })
         });
 
         assert!(result > 0);
     }
 
     #[test]
-    #[should_panic(expected = "State changed during operation execution")]
+    #[should_panic(expected = "state changed during operation")]
     fn test_read_consistency_guard_panics_on_invalid() {
         let env = Env::default();
-        let guard = ReadConsistencyGuard::new(&env);
+        let contract_id = env.register(CounterContract, ());
+        let guard = env.as_contract(&contract_id, || ReadConsistencyGuard::new(&env));
 
         // Advance multiple blocks to make it invalid
         env.ledger().with_mut(|li| {
@@ -154,7 +219,7 @@ mod tests {
         });
 
         // Should panic when ensuring consistency
-        guard.ensure_consistent(&env);
+        env.as_contract(&contract_id, || guard.ensure_consistent(&env));
     }
 
     // ===== Critical Operation Tests =====
@@ -162,10 +227,12 @@ mod tests {
     #[test]
     fn test_swap_consistent_state_snapshot() {
         let env = Env::default();
+        env.mock_all_auths();
         let contract_id = env.register(CounterContract, ());
         let client = crate::CounterContractClient::new(&env, &contract_id);
 
         let user = Address::generate(&env);
+        verify_user(&env, &contract_id, &user);
         let xlm = symbol_short!("XLM");
         let usdc = symbol_short!("USDCSIM");
 
@@ -178,7 +245,7 @@ mod tests {
 
         // Execute swap - internally should read all state before mutation
         let out = client.swap(&xlm, &usdc, &500, &user);
-        assert_eq!(out, 500);
+        assert_eq!(out, net_of_fee(&client, 500));
 
         // Verify state consistency after swap
         let xlm_after = client.get_balance(&xlm, &user);
@@ -186,16 +253,18 @@ mod tests {
 
         // Balances should be consistent
         assert_eq!(xlm_after, 500);
-        assert_eq!(usdc_after, 500);
+        assert_eq!(usdc_after, out);
     }
 
     #[test]
     fn test_add_liquidity_consistent_state_snapshot() {
         let env = Env::default();
+        env.mock_all_auths();
         let contract_id = env.register(CounterContract, ());
         let client = crate::CounterContractClient::new(&env, &contract_id);
 
         let user = Address::generate(&env);
+        verify_user(&env, &contract_id, &user);
         let xlm = symbol_short!("XLM");
         let usdc = symbol_short!("USDCSIM");
 
@@ -222,10 +291,12 @@ mod tests {
     #[test]
     fn test_remove_liquidity_consistent_state_snapshot() {
         let env = Env::default();
+        env.mock_all_auths();
         let contract_id = env.register(CounterContract, ());
         let client = crate::CounterContractClient::new(&env, &contract_id);
 
         let user = Address::generate(&env);
+        verify_user(&env, &contract_id, &user);
         let xlm = symbol_short!("XLM");
         let usdc = symbol_short!("USDCSIM");
 
@@ -254,11 +325,13 @@ mod tests {
     #[test]
     fn test_pool_swap_consistent_state_snapshot() {
         let env = Env::default();
+        env.mock_all_auths();
         let contract_id = env.register(CounterContract, ());
         let client = crate::CounterContractClient::new(&env, &contract_id);
 
         let admin = Address::generate(&env);
         let trader = Address::generate(&env);
+        verify_user(&env, &contract_id, &trader);
         let token_a = symbol_short!("TOKA");
         let token_b = symbol_short!("TOKB");
 
@@ -283,11 +356,14 @@ mod tests {
     #[test]
     fn test_concurrent_swaps_maintain_consistency() {
         let env = Env::default();
+        env.mock_all_auths();
         let contract_id = env.register(CounterContract, ());
         let client = crate::CounterContractClient::new(&env, &contract_id);
 
         let user1 = Address::generate(&env);
         let user2 = Address::generate(&env);
+        verify_user(&env, &contract_id, &user1);
+        verify_user(&env, &contract_id, &user2);
         let xlm = symbol_short!("XLM");
         let usdc = symbol_short!("USDCSIM");
 
@@ -300,23 +376,25 @@ mod tests {
         let out2 = client.swap(&xlm, &usdc, &500, &user2);
 
         // Both swaps should succeed
-        assert_eq!(out1, 500);
-        assert_eq!(out2, 500);
+        assert_eq!(out1, net_of_fee(&client, 500));
+        assert_eq!(out2, out1);
 
         // Verify final state consistency
         assert_eq!(client.get_balance(&xlm, &user1), 500);
-        assert_eq!(client.get_balance(&usdc, &user1), 500);
+        assert_eq!(client.get_balance(&usdc, &user1), out1);
         assert_eq!(client.get_balance(&xlm, &user2), 500);
-        assert_eq!(client.get_balance(&usdc, &user2), 500);
+        assert_eq!(client.get_balance(&usdc, &user2), out2);
     }
 
     #[test]
     fn test_state_read_write_ordering() {
         let env = Env::default();
+        env.mock_all_auths();
         let contract_id = env.register(CounterContract, ());
         let client = crate::CounterContractClient::new(&env, &contract_id);
 
         let user = Address::generate(&env);
+        verify_user(&env, &contract_id, &user);
         let xlm = symbol_short!("XLM");
         let usdc = symbol_short!("USDCSIM");
 
@@ -324,44 +402,49 @@ mod tests {
         client.mint(&xlm, &user, &1000);
 
         // Create snapshot before operation
-        let snapshot_before = StateSnapshotManager::create_snapshot(&env);
+        let snapshot_before =
+            env.as_contract(&contract_id, || StateSnapshotManager::create_snapshot(&env));
 
         // Execute operation
         let out = client.swap(&xlm, &usdc, &500, &user);
-        assert_eq!(out, 500);
+        assert_eq!(out, net_of_fee(&client, 500));
 
         // Create snapshot after operation
-        let snapshot_after = StateSnapshotManager::create_snapshot(&env);
+        let snapshot_after =
+            env.as_contract(&contract_id, || StateSnapshotManager::create_snapshot(&env));
 
         // Snapshots should have different IDs (operations occurred)
         assert!(snapshot_after.snapshot_id > snapshot_before.snapshot_id);
 
         // State should be consistent
         assert_eq!(client.get_balance(&xlm, &user), 500);
-        assert_eq!(client.get_balance(&usdc, &user), 500);
+        assert_eq!(client.get_balance(&usdc, &user), out);
     }
 
     #[test]
     fn test_atomic_operation_with_validation() {
         let env = Env::default();
+        let contract_id = env.register(CounterContract, ());
 
-        let result = AtomicOperation::execute_validated(
-            &env,
-            |env, snapshot| {
-                // Simulate reading state
-                let block = env.ledger().sequence();
-                assert_eq!(block, snapshot.block_number);
-                42
-            },
-            |_env, _snapshot, result| {
-                // Validate result
-                if *result == 42 {
-                    Ok(())
-                } else {
-                    Err("Invalid result")
-                }
-            },
-        );
+        let result = env.as_contract(&contract_id, || {
+            AtomicOperation::execute_validated(
+                &env,
+                |env, snapshot| {
+                    // Simulate reading state
+                    let block = env.ledger().sequence();
+                    assert_eq!(block, snapshot.block_number);
+                    42
+                },
+                |_env, _snapshot, result| {
+                    // Validate result
+                    if *result == 42 {
+                        Ok(())
+                    } else {
+                        Err("Invalid result")
+                    }
+                },
+            )
+        });
 
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), 42);
@@ -370,10 +453,12 @@ mod tests {
     #[test]
     fn test_multiple_operations_maintain_snapshot_consistency() {
         let env = Env::default();
+        env.mock_all_auths();
         let contract_id = env.register(CounterContract, ());
         let client = crate::CounterContractClient::new(&env, &contract_id);
 
         let user = Address::generate(&env);
+        verify_user(&env, &contract_id, &user);
         let xlm = symbol_short!("XLM");
         let usdc = symbol_short!("USDCSIM");
 
@@ -382,14 +467,24 @@ mod tests {
         client.mint(&usdc, &user, &2000);
 
         // Operation 1: Swap
-        let snapshot1 = StateSnapshotManager::create_snapshot(&env);
-        client.swap(&xlm, &usdc, &500, &user);
-        assert!(StateSnapshotManager::validate_snapshot(&env, &snapshot1));
+        let snapshot1 =
+            env.as_contract(&contract_id, || StateSnapshotManager::create_snapshot(&env));
+        let out = client.swap(&xlm, &usdc, &500, &user);
+        assert!(
+            env.as_contract(&contract_id, || StateSnapshotManager::validate_snapshot(
+                &env, &snapshot1
+            ))
+        );
 
         // Operation 2: Add liquidity
-        let snapshot2 = StateSnapshotManager::create_snapshot(&env);
+        let snapshot2 =
+            env.as_contract(&contract_id, || StateSnapshotManager::create_snapshot(&env));
         client.add_liquidity(&500, &500, &user);
-        assert!(StateSnapshotManager::validate_snapshot(&env, &snapshot2));
+        assert!(
+            env.as_contract(&contract_id, || StateSnapshotManager::validate_snapshot(
+                &env, &snapshot2
+            ))
+        );
 
         // Verify final state is consistent
         let xlm_final = client.get_balance(&xlm, &user);
@@ -397,7 +492,7 @@ mod tests {
 
         // 2000 - 500 (swap) - 500 (LP) = 1000
         assert_eq!(xlm_final, 1000);
-        // 2000 + 500 (swap) - 500 (LP) = 2000
-        assert_eq!(usdc_final, 2000);
+        // 2000 + out (swap) - 500 (LP)
+        assert_eq!(usdc_final, 1500 + out);
     }
 }
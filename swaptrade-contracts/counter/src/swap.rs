@@ -3,7 +3,7 @@ use crate::errors::SwapTradeError;
 use crate::private_transaction::{
     private_swap::perform_private_swap as private_swap_exec, PrivateTransactionProcessor,
 };
-use crate::risk_management::volume_circuit_breaker;
+use crate::risk_management::{volume_circuit_breaker, CircuitBreaker};
 use crate::zkp_types::{CircuitParameters, PrivateTransaction};
 use crate::zkp_verification::ProofVerifier;
 use soroban_sdk::{Address, Bytes, Env, Symbol};
@@ -19,33 +19,26 @@ pub fn perform_swap(
     amount: i128,
     user: Address,
 ) -> Result<i128, SwapTradeError> {
-    if emergency::is_paused(env) {
+    if !emergency::EmergencyController::is_operation_allowed(
+        env,
+        &emergency::OperationType::Swap,
+        &user,
+    ) {
         return Err(SwapTradeError::TradingPaused);
     }
     if emergency::is_frozen(env, user.clone()) {
         return Err(SwapTradeError::UserFrozen);
     }
 
-    // Volume-threshold circuit breaker check
-    // This prunes stale entries, records the volume, and trips/pauses if the
-    // accumulated volume exceeds the configured max_volume within the window.
-    // If already tripped, the is_paused check above will catch it.
-    if volume_circuit_breaker::is_tripped(env) {
+    // Circuit breaker check, global and for both legs
+    if CircuitBreaker::is_halted(env, &from) || CircuitBreaker::is_halted(env, &to) {
         return Err(SwapTradeError::CircuitBreakerTripped);
     }
 
-    // Check and record volume — trips the breaker if threshold is exceeded
+    // Check and record volume — trips the global breaker if the accumulated
+    // volume exceeds the configured max_volume within the window
     volume_circuit_breaker::check_and_record_volume(env, amount);
 
-    // Legacy block-level circuit breaker check
-    let normal_volume = 1000;
-    if emergency::would_trip_circuit_breaker(env, amount, normal_volume) {
-        return Err(SwapTradeError::CircuitBreakerTripped);
-    }
-
-    // record volume (legacy block-based tracking)
-    emergency::record_volume(env, amount);
-
     // ... rest of swap code
     Ok(0)
 }
@@ -59,7 +52,11 @@ pub fn perform_private_swap(
     from: Symbol,
     to: Symbol,
 ) -> Result<Bytes, SwapTradeError> {
-    if emergency::is_paused(env) {
+    if !emergency::EmergencyController::is_operation_allowed(
+        env,
+        &emergency::OperationType::Swap,
+        &user,
+    ) {
         return Err(SwapTradeError::TradingPaused);
     }
     if emergency::is_frozen(env, user.clone()) {
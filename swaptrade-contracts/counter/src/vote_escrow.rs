@@ -0,0 +1,526 @@
+use crate::errors::SwapTradeError;
+/// Vote-Escrow (veToken) System
+///
+/// Governance tokens (the user's XLM balance in the Portfolio) are locked
+/// until a week-aligned unlock time in exchange for voting power that decays
+/// linearly to zero at unlock:
+///
+///   power(t) = amount * (unlock_time - t) / MAX_LOCK_SECS
+///
+/// Locks can be topped up or extended but never shortened. Every change
+/// writes a user checkpoint, and the global curve is checkpointed once per
+/// epoch (week) so voting power can be read at any past timestamp. Governance
+/// snapshots power at proposal creation, so balances acquired afterwards —
+/// including flash-borrowed ones — carry no weight.
+use soroban_sdk::{contracttype, symbol_short, Address, Env};
+
+use crate::portfolio::{Asset, Portfolio};
+
+// ────────────────────────────────────────────────────────────────────────────
+// Constants
+// ────────────────────────────────────────────────────────────────────────────
+
+/// Epoch length: unlock times are rounded down to a whole week
+pub const WEEK_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// Maximum lock duration: 208 weeks (~4 years) gives full voting power
+pub const MAX_LOCK_SECS: u64 = 208 * WEEK_SECS;
+
+/// Fixed-point scale applied to slopes to avoid truncating small locks
+const SLOPE_PRECISION: i128 = 1_000_000_000;
+
+/// Upper bound on weeks walked when projecting the global curve
+const MAX_CHECKPOINT_EPOCHS: u32 = 255;
+
+// ────────────────────────────────────────────────────────────────────────────
+// Data Structures
+// ────────────────────────────────────────────────────────────────────────────
+
+/// A user's escrowed balance
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[contracttype]
+pub struct VeLock {
+    /// Governance tokens held in escrow
+    pub amount: i128,
+    /// Week-aligned timestamp at which the lock expires
+    pub unlock_time: u64,
+}
+
+/// Point on a voting-power curve: `bias - slope * (t - ts)`, scaled by
+/// `SLOPE_PRECISION`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[contracttype]
+pub struct VePoint {
+    pub bias: i128,
+    pub slope: i128,
+    pub ts: u64,
+}
+
+/// Storage keys for vote-escrow data
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum VoteEscrowKey {
+    /// User's current lock (Address, VeLock)
+    Lock(Address),
+    /// Number of checkpoints written for a user
+    UserPointCount(Address),
+    /// User checkpoint by index
+    UserPoint(Address, u32),
+    /// Index of the latest global checkpoint
+    GlobalEpoch,
+    /// Global checkpoint by index
+    GlobalPoint(u32),
+    /// Slope removed from the global curve at a week-aligned timestamp
+    SlopeChange(u64),
+    /// Total governance tokens held in escrow
+    TotalLocked,
+}
+
+// ────────────────────────────────────────────────────────────────────────────
+// Vote-Escrow Manager
+// ────────────────────────────────────────────────────────────────────────────
+
+pub struct VoteEscrowManager;
+
+impl VoteEscrowManager {
+    // ────────────────────────────────────────────────────────────────────────
+    // Lock Operations
+    // ────────────────────────────────────────────────────────────────────────
+
+    /// Lock `amount` governance tokens until `unlock_time` (rounded down to
+    /// a whole week, at most `MAX_LOCK_SECS` ahead)
+    pub fn create_lock(
+        env: &Env,
+        user: Address,
+        amount: i128,
+        unlock_time: u64,
+    ) -> Result<VeLock, SwapTradeError> {
+        user.require_auth();
+        if amount <= 0 {
+            return Err(SwapTradeError::InvalidAmount);
+        }
+
+        let old = Self::get_lock(env, &user);
+        if old.amount > 0 {
+            return Err(SwapTradeError::VeLockExists);
+        }
+
+        let unlock_time = Self::validate_unlock_time(env, unlock_time)?;
+        let new = VeLock {
+            amount,
+            unlock_time,
+        };
+        Self::debit_balance(env, &user, amount)?;
+        Self::deposit(env, &user, old, new.clone(), amount)?;
+
+        env.events()
+            .publish((symbol_short!("ve_lock"), user), (amount, unlock_time));
+
+        Ok(new)
+    }
+
+    /// Add `amount` tokens to an existing, unexpired lock without changing
+    /// its unlock time
+    pub fn increase_amount(
+        env: &Env,
+        user: Address,
+        amount: i128,
+    ) -> Result<VeLock, SwapTradeError> {
+        user.require_auth();
+        if amount <= 0 {
+            return Err(SwapTradeError::InvalidAmount);
+        }
+
+        let old = Self::get_active_lock(env, &user)?;
+        let new = VeLock {
+            amount: old
+                .amount
+                .checked_add(amount)
+                .ok_or(SwapTradeError::AmountOverflow)?,
+            unlock_time: old.unlock_time,
+        };
+        Self::debit_balance(env, &user, amount)?;
+        Self::deposit(env, &user, old, new.clone(), amount)?;
+
+        env.events()
+            .publish((symbol_short!("ve_incamt"), user), (amount, new.amount));
+
+        Ok(new)
+    }
+
+    /// Extend an existing, unexpired lock to a later `unlock_time`
+    pub fn increase_unlock_time(
+        env: &Env,
+        user: Address,
+        unlock_time: u64,
+    ) -> Result<VeLock, SwapTradeError> {
+        user.require_auth();
+
+        let old = Self::get_active_lock(env, &user)?;
+        let unlock_time = Self::validate_unlock_time(env, unlock_time)?;
+        if unlock_time <= old.unlock_time {
+            return Err(SwapTradeError::InvalidUnlockTime);
+        }
+
+        let new = VeLock {
+            amount: old.amount,
+            unlock_time,
+        };
+        Self::deposit(env, &user, old, new.clone(), 0)?;
+
+        env.events()
+            .publish((symbol_short!("ve_extend"), user), unlock_time);
+
+        Ok(new)
+    }
+
+    /// Return the escrowed tokens once the lock has expired
+    pub fn withdraw(env: &Env, user: Address) -> Result<i128, SwapTradeError> {
+        user.require_auth();
+
+        let old = Self::get_lock(env, &user);
+        if old.amount <= 0 {
+            return Err(SwapTradeError::VeLockNotFound);
+        }
+        if env.ledger().timestamp() < old.unlock_time {
+            return Err(SwapTradeError::VeLockNotExpired);
+        }
+
+        let amount = old.amount;
+        Self::checkpoint_user(env, &user, &old, &VeLock::default());
+        env.storage()
+            .persistent()
+            .remove(&VoteEscrowKey::Lock(user.clone()));
+
+        let total = Self::get_total_locked(env);
+        env.storage()
+            .persistent()
+            .set(&VoteEscrowKey::TotalLocked, &(total - amount));
+        Self::credit_balance(env, &user, amount);
+
+        env.events().publish((symbol_short!("ve_wd"), user), amount);
+
+        Ok(amount)
+    }
+
+    /// Bring the global curve up to the current timestamp. Anyone may call
+    /// this to keep historical lookups cheap after long idle periods.
+    pub fn checkpoint(env: &Env) {
+        let (epoch, point) = Self::advance_global(env);
+        Self::write_global(env, epoch, point);
+    }
+
+    // ────────────────────────────────────────────────────────────────────────
+    // Voting Power Queries
+    // ────────────────────────────────────────────────────────────────────────
+
+    /// Current voting power of `user`
+    pub fn balance_of(env: &Env, user: &Address) -> i128 {
+        Self::balance_of_at(env, user, env.ledger().timestamp())
+    }
+
+    /// Voting power of `user` at timestamp `ts`
+    pub fn balance_of_at(env: &Env, user: &Address, ts: u64) -> i128 {
+        let count: u32 = env
+            .storage()
+            .persistent()
+            .get(&VoteEscrowKey::UserPointCount(user.clone()))
+            .unwrap_or(0);
+        if count == 0 {
+            return 0;
+        }
+
+        let read = |i: u32| -> VePoint {
+            env.storage()
+                .persistent()
+                .get(&VoteEscrowKey::UserPoint(user.clone(), i))
+                .unwrap()
+        };
+        match Self::find_point(count - 1, ts, read) {
+            Some(point) => Self::power_at(&point, ts),
+            None => 0,
+        }
+    }
+
+    /// Current total voting power across all locks
+    pub fn total_power(env: &Env) -> i128 {
+        Self::total_power_at(env, env.ledger().timestamp())
+    }
+
+    /// Total voting power across all locks at timestamp `ts`
+    pub fn total_power_at(env: &Env, ts: u64) -> i128 {
+        let epoch: Option<u32> = env.storage().persistent().get(&VoteEscrowKey::GlobalEpoch);
+        let Some(epoch) = epoch else {
+            return 0;
+        };
+
+        let read = |i: u32| Self::get_global_point(env, i);
+        let Some(mut point) = Self::find_point(epoch, ts, read) else {
+            return 0;
+        };
+
+        // Walk the curve forward to `ts`, applying scheduled slope changes.
+        let mut t_i = Self::round_to_week(point.ts);
+        for _ in 0..MAX_CHECKPOINT_EPOCHS {
+            t_i += WEEK_SECS;
+            let mut d_slope = 0;
+            if t_i > ts {
+                t_i = ts;
+            } else {
+                d_slope = Self::get_slope_change(env, t_i);
+            }
+            point.bias -= point.slope * (t_i - point.ts) as i128;
+            if t_i == ts {
+                break;
+            }
+            point.slope -= d_slope;
+            point.ts = t_i;
+        }
+
+        Self::to_power(point.bias)
+    }
+
+    /// Current lock for `user` (zero amount if none)
+    pub fn get_lock(env: &Env, user: &Address) -> VeLock {
+        env.storage()
+            .persistent()
+            .get(&VoteEscrowKey::Lock(user.clone()))
+            .unwrap_or_default()
+    }
+
+    /// Total governance tokens held in escrow
+    pub fn get_total_locked(env: &Env) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&VoteEscrowKey::TotalLocked)
+            .unwrap_or(0)
+    }
+
+    // ────────────────────────────────────────────────────────────────────────
+    // Internal
+    // ────────────────────────────────────────────────────────────────────────
+
+    pub(crate) fn round_to_week(ts: u64) -> u64 {
+        ts / WEEK_SECS * WEEK_SECS
+    }
+
+    fn validate_unlock_time(env: &Env, unlock_time: u64) -> Result<u64, SwapTradeError> {
+        let now = env.ledger().timestamp();
+        let rounded = Self::round_to_week(unlock_time);
+        if rounded <= now || rounded > now + MAX_LOCK_SECS {
+            return Err(SwapTradeError::InvalidUnlockTime);
+        }
+        Ok(rounded)
+    }
+
+    fn get_active_lock(env: &Env, user: &Address) -> Result<VeLock, SwapTradeError> {
+        let lock = Self::get_lock(env, user);
+        if lock.amount <= 0 {
+            return Err(SwapTradeError::VeLockNotFound);
+        }
+        if lock.unlock_time <= env.ledger().timestamp() {
+            return Err(SwapTradeError::VeLockExpired);
+        }
+        Ok(lock)
+    }
+
+    fn deposit(
+        env: &Env,
+        user: &Address,
+        old: VeLock,
+        new: VeLock,
+        added: i128,
+    ) -> Result<(), SwapTradeError> {
+        Self::checkpoint_user(env, user, &old, &new);
+        env.storage()
+            .persistent()
+            .set(&VoteEscrowKey::Lock(user.clone()), &new);
+
+        if added > 0 {
+            let total = Self::get_total_locked(env)
+                .checked_add(added)
+                .ok_or(SwapTradeError::AmountOverflow)?;
+            env.storage()
+                .persistent()
+                .set(&VoteEscrowKey::TotalLocked, &total);
+        }
+        Ok(())
+    }
+
+    /// Curve point of a lock at the current timestamp
+    pub(crate) fn lock_point(env: &Env, lock: &VeLock) -> VePoint {
+        let now = env.ledger().timestamp();
+        if lock.amount <= 0 || lock.unlock_time <= now {
+            return VePoint {
+                bias: 0,
+                slope: 0,
+                ts: now,
+            };
+        }
+        let slope = lock.amount * SLOPE_PRECISION / MAX_LOCK_SECS as i128;
+        VePoint {
+            bias: slope * (lock.unlock_time - now) as i128,
+            slope,
+            ts: now,
+        }
+    }
+
+    /// Record a lock change for `user` and fold the delta into the global
+    /// curve and its scheduled slope changes.
+    fn checkpoint_user(env: &Env, user: &Address, old: &VeLock, new: &VeLock) {
+        let now = env.ledger().timestamp();
+        let old_point = Self::lock_point(env, old);
+        let new_point = Self::lock_point(env, new);
+
+        let (epoch, mut global) = Self::advance_global(env);
+        global.slope = (global.slope + new_point.slope - old_point.slope).max(0);
+        global.bias = (global.bias + new_point.bias - old_point.bias).max(0);
+        Self::write_global(env, epoch, global);
+
+        if old.unlock_time > now {
+            let change = Self::get_slope_change(env, old.unlock_time) - old_point.slope;
+            Self::set_slope_change(env, old.unlock_time, change);
+        }
+        if new.unlock_time > now {
+            let change = Self::get_slope_change(env, new.unlock_time) + new_point.slope;
+            Self::set_slope_change(env, new.unlock_time, change);
+        }
+
+        let count: u32 = env
+            .storage()
+            .persistent()
+            .get(&VoteEscrowKey::UserPointCount(user.clone()))
+            .unwrap_or(0);
+        env.storage()
+            .persistent()
+            .set(&VoteEscrowKey::UserPoint(user.clone(), count), &new_point);
+        env.storage()
+            .persistent()
+            .set(&VoteEscrowKey::UserPointCount(user.clone()), &(count + 1));
+    }
+
+    /// Project the global curve to the current timestamp and return the next
+    /// epoch index with it. Weeks in between are not stored: `total_power_at`
+    /// walks forward through the same slope changes, and writing a point per
+    /// idle week would exceed the ledger write limit after long gaps.
+    fn advance_global(env: &Env) -> (u32, VePoint) {
+        let now = env.ledger().timestamp();
+        let stored: Option<u32> = env.storage().persistent().get(&VoteEscrowKey::GlobalEpoch);
+        let Some(epoch) = stored else {
+            return (
+                0,
+                VePoint {
+                    bias: 0,
+                    slope: 0,
+                    ts: now,
+                },
+            );
+        };
+
+        let mut last = Self::get_global_point(env, epoch);
+        let mut t_i = Self::round_to_week(last.ts);
+        for _ in 0..MAX_CHECKPOINT_EPOCHS {
+            t_i += WEEK_SECS;
+            let mut d_slope = 0;
+            if t_i > now {
+                t_i = now;
+            } else {
+                d_slope = Self::get_slope_change(env, t_i);
+            }
+            last.bias = (last.bias - last.slope * (t_i - last.ts) as i128).max(0);
+            last.slope = (last.slope - d_slope).max(0);
+            last.ts = t_i;
+            if t_i == now {
+                break;
+            }
+        }
+        // Every lock is at most MAX_LOCK_SECS long, so after the epoch cap
+        // the curve is already flat at zero.
+        last.ts = now;
+
+        (epoch + 1, last)
+    }
+
+    fn write_global(env: &Env, epoch: u32, point: VePoint) {
+        env.storage()
+            .persistent()
+            .set(&VoteEscrowKey::GlobalPoint(epoch), &point);
+        env.storage()
+            .persistent()
+            .set(&VoteEscrowKey::GlobalEpoch, &epoch);
+    }
+
+    fn get_global_point(env: &Env, epoch: u32) -> VePoint {
+        env.storage()
+            .persistent()
+            .get(&VoteEscrowKey::GlobalPoint(epoch))
+            .unwrap_or_default()
+    }
+
+    fn get_slope_change(env: &Env, ts: u64) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&VoteEscrowKey::SlopeChange(ts))
+            .unwrap_or(0)
+    }
+
+    fn set_slope_change(env: &Env, ts: u64, slope: i128) {
+        env.storage()
+            .persistent()
+            .set(&VoteEscrowKey::SlopeChange(ts), &slope);
+    }
+
+    /// Binary search for the latest checkpoint in `0..=last` taken at or
+    /// before `ts`.
+    fn find_point(last: u32, ts: u64, read: impl Fn(u32) -> VePoint) -> Option<VePoint> {
+        if read(0).ts > ts {
+            return None;
+        }
+        let (mut lo, mut hi) = (0u32, last);
+        while lo < hi {
+            let mid = (lo + hi + 1) / 2;
+            if read(mid).ts <= ts {
+                lo = mid;
+            } else {
+                hi = mid - 1;
+            }
+        }
+        Some(read(lo))
+    }
+
+    fn power_at(point: &VePoint, ts: u64) -> i128 {
+        let elapsed = ts.saturating_sub(point.ts) as i128;
+        Self::to_power(point.bias - point.slope * elapsed)
+    }
+
+    /// Unscale a bias, rounding up so the truncated slope does not shave a
+    /// unit off full-length locks.
+    pub(crate) fn to_power(bias: i128) -> i128 {
+        (bias.max(0) + SLOPE_PRECISION - 1) / SLOPE_PRECISION
+    }
+
+    fn load_portfolio(env: &Env) -> Portfolio {
+        env.storage()
+            .instance()
+            .get(&())
+            .unwrap_or_else(|| Portfolio::new(env))
+    }
+
+    /// Move `amount` XLM from the user's Portfolio balance into escrow
+    fn debit_balance(env: &Env, user: &Address, amount: i128) -> Result<(), SwapTradeError> {
+        let mut portfolio = Self::load_portfolio(env);
+        if portfolio.balance_of(env, Asset::XLM, user.clone()) < amount {
+            return Err(SwapTradeError::InsufficientBalance);
+        }
+        portfolio.debit(env, Asset::XLM, user.clone(), amount);
+        env.storage().instance().set(&(), &portfolio);
+        Ok(())
+    }
+
+    /// Return `amount` escrowed XLM to the user's Portfolio balance
+    fn credit_balance(env: &Env, user: &Address, amount: i128) {
+        let mut portfolio = Self::load_portfolio(env);
+        portfolio.credit(env, Asset::XLM, user.clone(), amount);
+        env.storage().instance().set(&(), &portfolio);
+    }
+}
@@ -0,0 +1,182 @@
+#![cfg(test)]
+
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::{symbol_short, Address, Env, Map};
+
+use crate::errors::ContractError;
+use crate::governance_system::{self, ProposalAction, ProposalState};
+use crate::storage::PROPOSAL_STATE_KEY;
+use crate::vote_escrow::{MAX_LOCK_SECS, WEEK_SECS};
+use crate::{CounterContract, CounterContractClient};
+
+const START: u64 = 100 * WEEK_SECS;
+
+fn setup() -> (Env, Address, CounterContractClient<'static>) {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|l| l.timestamp = START);
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+    (env, contract_id, client)
+}
+
+fn fund(client: &CounterContractClient, user: &Address, amount: i128) {
+    client.mint(&symbol_short!("XLM"), user, &amount);
+}
+
+fn balance(client: &CounterContractClient, user: &Address) -> i128 {
+    client.balance_of(&symbol_short!("XLM"), user)
+}
+
+fn advance(env: &Env, secs: u64) {
+    env.ledger().with_mut(|l| l.timestamp += secs);
+}
+
+#[test]
+fn test_voting_power_decays_linearly_to_zero() {
+    let (env, contract_id, client) = setup();
+    let user = Address::generate(&env);
+    fund(&client, &user, 10_000);
+
+    let lock = client.create_ve_lock(&user, &10_000, &(START + MAX_LOCK_SECS));
+    assert_eq!(lock.unlock_time, START + MAX_LOCK_SECS);
+    assert_eq!(balance(&client, &user), 0);
+    assert_eq!(client.get_voting_power(&user, &None), 10_000);
+
+    advance(&env, MAX_LOCK_SECS / 2);
+    assert_eq!(client.get_voting_power(&user, &None), 5_000);
+    assert_eq!(client.get_total_voting_power(&None), 5_000);
+
+    assert_eq!(
+        client.try_withdraw_ve_lock(&user),
+        Err(Ok(ContractError::VeLockNotExpired))
+    );
+
+    advance(&env, MAX_LOCK_SECS / 2);
+    assert_eq!(client.get_voting_power(&user, &None), 0);
+    assert_eq!(client.get_total_voting_power(&None), 0);
+
+    assert_eq!(client.withdraw_ve_lock(&user), 10_000);
+    assert_eq!(balance(&client, &user), 10_000);
+    assert_eq!(client.get_ve_lock(&user).amount, 0);
+}
+
+#[test]
+fn test_unlock_time_rounds_down_to_week_and_is_capped() {
+    let (env, contract_id, client) = setup();
+    let user = Address::generate(&env);
+    fund(&client, &user, 1_000);
+
+    assert_eq!(
+        client.try_create_ve_lock(&user, &1_000, &(START + MAX_LOCK_SECS + WEEK_SECS)),
+        Err(Ok(ContractError::InvalidUnlockTime))
+    );
+    // Rounds down to `START`, which is not in the future.
+    assert_eq!(
+        client.try_create_ve_lock(&user, &1_000, &(START + WEEK_SECS - 1)),
+        Err(Ok(ContractError::InvalidUnlockTime))
+    );
+
+    let lock = client.create_ve_lock(&user, &500, &(START + 10 * WEEK_SECS + 3_600));
+    assert_eq!(lock.unlock_time, START + 10 * WEEK_SECS);
+    assert_eq!(
+        client.try_create_ve_lock(&user, &500, &(START + 10 * WEEK_SECS)),
+        Err(Ok(ContractError::VeLockExists))
+    );
+    assert_eq!(
+        client.try_increase_ve_unlock_time(&user, &(START + 5 * WEEK_SECS)),
+        Err(Ok(ContractError::InvalidUnlockTime))
+    );
+    assert_eq!(
+        client.try_increase_ve_amount(&user, &1_000),
+        Err(Ok(ContractError::InsufficientBalance))
+    );
+}
+
+#[test]
+fn test_increase_amount_and_unlock_time_keep_history() {
+    let (env, contract_id, client) = setup();
+    let user = Address::generate(&env);
+    fund(&client, &user, 20_000);
+
+    client.create_ve_lock(&user, &10_000, &(START + MAX_LOCK_SECS / 2));
+    assert_eq!(client.get_voting_power(&user, &None), 5_000);
+
+    advance(&env, WEEK_SECS);
+    let t1 = START + WEEK_SECS;
+    let before = client.get_voting_power(&user, &None);
+
+    client.increase_ve_amount(&user, &10_000);
+    assert_eq!(client.get_voting_power(&user, &None), before * 2);
+
+    advance(&env, WEEK_SECS);
+    client.increase_ve_unlock_time(&user, &(START + MAX_LOCK_SECS + 2 * WEEK_SECS));
+    assert_eq!(client.get_voting_power(&user, &None), 20_000);
+
+    // Historical lookups still see the earlier curve.
+    assert_eq!(client.get_voting_power(&user, &Some(START)), 5_000);
+    assert_eq!(client.get_voting_power(&user, &Some(t1 - 1)), before);
+    assert_eq!(client.get_voting_power(&user, &Some(START - 1)), 0);
+    assert_eq!(client.get_total_voting_power(&Some(START)), 5_000);
+}
+
+#[test]
+fn test_total_power_tracks_expiring_locks_across_epochs() {
+    let (env, contract_id, client) = setup();
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    fund(&client, &alice, 208_000);
+    fund(&client, &bob, 208_000);
+
+    client.create_ve_lock(&alice, &208_000, &(START + 4 * WEEK_SECS));
+    client.create_ve_lock(&bob, &208_000, &(START + 8 * WEEK_SECS));
+    assert_eq!(client.get_total_voting_power(&None), 4_000 + 8_000);
+
+    // No transactions for six weeks; the global curve is filled lazily.
+    advance(&env, 6 * WEEK_SECS);
+    assert_eq!(client.get_total_voting_power(&None), 2_000);
+    client.checkpoint_ve();
+    assert_eq!(client.get_total_voting_power(&None), 2_000);
+
+    let past = START + 2 * WEEK_SECS;
+    assert_eq!(client.get_total_voting_power(&Some(past)), 2_000 + 6_000);
+    assert_eq!(
+        client.get_total_voting_power(&Some(past)),
+        client.get_voting_power(&alice, &Some(past)) + client.get_voting_power(&bob, &Some(past))
+    );
+}
+
+#[test]
+fn test_governance_vote_uses_power_at_proposal_creation() {
+    let (env, contract_id, client) = setup();
+    let proposer = Address::generate(&env);
+    let late = Address::generate(&env);
+    fund(&client, &proposer, 10_000);
+    fund(&client, &late, 1_000_000);
+
+    client.create_ve_lock(&proposer, &10_000, &(START + MAX_LOCK_SECS));
+    advance(&env, 60);
+
+    let id = env.as_contract(&contract_id, || {
+        governance_system::create_proposal(&env, proposer.clone(), ProposalAction::PauseTrading)
+            .unwrap()
+    });
+
+    // Tokens locked after the proposal was created carry no weight.
+    advance(&env, 60);
+    client.create_ve_lock(&late, &1_000_000, &(START + MAX_LOCK_SECS));
+    env.as_contract(&contract_id, || {
+        governance_system::cast_vote(&env, late.clone(), id, true).unwrap()
+    });
+    env.as_contract(&contract_id, || {
+        governance_system::cast_vote(&env, proposer.clone(), id, true).unwrap()
+    });
+
+    env.as_contract(&contract_id, || {
+        let states: Map<u64, ProposalState> =
+            env.storage().persistent().get(&PROPOSAL_STATE_KEY).unwrap();
+        let votes = states.get(id).unwrap().votes;
+        assert_eq!(votes.get(0).unwrap().vote_weight, 0);
+        assert!(votes.get(1).unwrap().vote_weight > 0);
+    });
+}
@@ -0,0 +1,287 @@
+#![cfg(test)]
+
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::token::{StellarAssetClient, TokenClient};
+use soroban_sdk::{symbol_short, Address, Env, Map, Vec};
+
+use crate::errors::ContractError;
+use crate::governance::quadratic_voting::{
+    conviction_multiplier_bps, isqrt, VotingMode, CONVICTION_RAMP_SECS,
+};
+use crate::governance_system::{self, ProposalAction, TIMELOCK_SECS};
+use crate::governance_types::VoteOption;
+use crate::kyc::KYCStatus;
+use crate::storage::{ADMIN_KEY, GOV_COUNCIL_KEY};
+use crate::vote_escrow::{MAX_LOCK_SECS, WEEK_SECS};
+use crate::{CounterContract, CounterContractClient};
+
+const START: u64 = 100 * WEEK_SECS;
+
+struct Setup {
+    env: Env,
+    contract_id: Address,
+    client: CounterContractClient<'static>,
+    kyc_operator: Address,
+}
+
+impl Setup {
+    /// A user holding a max-length lock of `amount`.
+    fn locked_user(&self, amount: i128) -> Address {
+        let user = Address::generate(&self.env);
+        self.client.mint(&symbol_short!("XLM"), &user, &amount);
+        self.client
+            .create_ve_lock(&user, &amount, &(START + MAX_LOCK_SECS));
+        user
+    }
+
+    fn verified_user(&self, amount: i128) -> Address {
+        let user = self.locked_user(amount);
+        self.verify(&user);
+        user
+    }
+
+    fn verify(&self, user: &Address) {
+        self.client.kyc_submit(user);
+        self.client
+            .kyc_update_status(&self.kyc_operator, user, &KYCStatus::InReview, &None);
+        self.client
+            .kyc_update_status(&self.kyc_operator, user, &KYCStatus::Verified, &None);
+    }
+
+    fn vote(&self, voter: &Address, id: u64, in_favor: bool) -> Result<(), ContractError> {
+        let option = if in_favor {
+            VoteOption::For
+        } else {
+            VoteOption::Against
+        };
+        match self.client.try_cast_governance_vote(voter, &id, &option) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(e.unwrap()),
+        }
+    }
+
+    fn sign_by_council(&self, id: u64) {
+        self.env.as_contract(&self.contract_id, || {
+            let council: Vec<Address> = self
+                .env
+                .storage()
+                .persistent()
+                .get(&GOV_COUNCIL_KEY)
+                .unwrap();
+            for member in council.iter() {
+                governance_system::sign_proposal(&self.env, member, id).unwrap();
+            }
+        });
+    }
+}
+
+fn setup() -> Setup {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|l| l.timestamp = START);
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    let kyc_operator = Address::generate(&env);
+
+    let mut council = Vec::new(&env);
+    for _ in 0..5 {
+        council.push_back(Address::generate(&env));
+    }
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(&ADMIN_KEY, &admin);
+        env.storage().persistent().set(&GOV_COUNCIL_KEY, &council);
+    });
+    client.kyc_add_operator(&admin, &kyc_operator);
+
+    Setup {
+        env,
+        contract_id,
+        client,
+        kyc_operator,
+    }
+}
+
+#[test]
+fn test_weights_per_mode() {
+    assert_eq!(isqrt(0), 0);
+    assert_eq!(isqrt(99), 9);
+    assert_eq!(isqrt(10_000), 100);
+    assert_eq!(isqrt(1_000_000_000_000), 1_000_000);
+
+    assert_eq!(conviction_multiplier_bps(0), 10_000);
+    assert_eq!(conviction_multiplier_bps(CONVICTION_RAMP_SECS / 2), 20_000);
+    assert_eq!(conviction_multiplier_bps(CONVICTION_RAMP_SECS * 4), 30_000);
+}
+
+#[test]
+fn test_quadratic_votes_require_verified_identity() {
+    let s = setup();
+    let proposer = s.verified_user(1_000);
+    let unverified = s.locked_user(1_000);
+
+    let id = s.client.create_governance_action(
+        &proposer,
+        &ProposalAction::PauseTrading,
+        &VotingMode::Quadratic,
+    );
+    assert_eq!(
+        s.vote(&unverified, id, true),
+        Err(ContractError::IdentityNotVerified)
+    );
+
+    s.vote(&proposer, id, true).unwrap();
+    let power = s.client.get_voting_power(&proposer, &None);
+    assert_eq!(s.client.get_governance_tally(&id).votes_for, isqrt(power));
+    assert_eq!(
+        s.vote(&proposer, id, false),
+        Err(ContractError::VotingEnded)
+    );
+}
+
+#[test]
+fn test_quadratic_grant_resists_whale_capture() {
+    let s = setup();
+    let whale = s.verified_user(10_000);
+    let mut small = Vec::new(&s.env);
+    for _ in 0..7 {
+        small.push_back(s.verified_user(100));
+    }
+    let token = s
+        .env
+        .register_stellar_asset_contract_v2(Address::generate(&s.env))
+        .address();
+    let grant = ProposalAction::TreasuryGrant(token, whale.clone(), 1_000);
+
+    assert_eq!(
+        s.client
+            .try_create_governance_action(&whale, &grant, &VotingMode::Simple),
+        Err(Ok(ContractError::InvalidVotingMode))
+    );
+
+    let id = s.env.as_contract(&s.contract_id, || {
+        governance_system::create_proposal(&s.env, whale.clone(), grant).unwrap()
+    });
+    for voter in small.iter() {
+        s.vote(&voter, id, false).unwrap();
+    }
+    s.vote(&whale, id, true).unwrap();
+
+    // The whale holds ~93% of the power but only ~59% of the quadratic
+    // weight, short of the 60% approval threshold.
+    let tally = s.client.get_governance_tally(&id);
+    assert!(tally.votes_for > tally.votes_against);
+    assert_eq!(
+        s.client.try_queue_governance_proposal(&id),
+        Err(Ok(ContractError::ProposalFailed))
+    );
+}
+
+#[test]
+fn test_quadratic_grant_pays_out_from_treasury() {
+    let s = setup();
+    let proposer = s.verified_user(1_000);
+    let backer = s.verified_user(1_000);
+    let recipient = Address::generate(&s.env);
+    let funder = Address::generate(&s.env);
+    let token = s
+        .env
+        .register_stellar_asset_contract_v2(Address::generate(&s.env))
+        .address();
+    StellarAssetClient::new(&s.env, &token).mint(&funder, &5_000);
+    s.client.deposit_treasury(&funder, &token, &5_000);
+
+    let id = s.client.create_governance_action(
+        &proposer,
+        &ProposalAction::TreasuryGrant(token.clone(), recipient.clone(), 1_200),
+        &VotingMode::Quadratic,
+    );
+    s.vote(&proposer, id, true).unwrap();
+    assert_eq!(s.vote(&backer, id, true), Err(ContractError::VotingEnded));
+    s.sign_by_council(id);
+
+    let executor = Address::generate(&s.env);
+    assert_eq!(
+        s.client.try_execute_governance_proposal(&executor, &id),
+        Err(Ok(ContractError::TimelockNotElapsed))
+    );
+    s.env.ledger().with_mut(|l| l.timestamp += TIMELOCK_SECS);
+    s.client.execute_governance_proposal(&executor, &id);
+
+    assert_eq!(TokenClient::new(&s.env, &token).balance(&recipient), 1_200);
+    assert_eq!(s.client.get_treasury_balance(&token), 3_800);
+}
+
+#[test]
+fn test_conviction_grows_while_held() {
+    let s = setup();
+    let alice = s.locked_user(2_000);
+    let bob = s.locked_user(500);
+    s.locked_user(7_500);
+
+    let id = s.client.create_governance_action(
+        &alice,
+        &ProposalAction::PauseTrading,
+        &VotingMode::Conviction,
+    );
+    s.vote(&alice, id, true).unwrap();
+    s.vote(&bob, id, true).unwrap();
+    assert_eq!(s.vote(&bob, id, true), Err(ContractError::VoteUnchanged));
+
+    // 25% turnout at 1x is short of the 30% quorum.
+    assert_eq!(
+        s.client.try_queue_governance_proposal(&id),
+        Err(Ok(ContractError::QuorumNotReached))
+    );
+
+    s.env
+        .ledger()
+        .with_mut(|l| l.timestamp += CONVICTION_RAMP_SECS);
+    // Switching sides restarts Bob's ramp at 1x.
+    s.vote(&bob, id, false).unwrap();
+    let alice_power = s.client.get_delegated_voting_power(
+        &alice,
+        &ProposalAction::PauseTrading.topic(),
+        &Some(START),
+    );
+    let tally = s.client.get_governance_tally(&id);
+    assert_eq!(tally.votes_for, alice_power * 3);
+    assert!(tally.votes_against < tally.votes_for / 10);
+
+    let executable_at = s.client.queue_governance_proposal(&id);
+    assert_eq!(executable_at, START + CONVICTION_RAMP_SECS + TIMELOCK_SECS);
+    assert_eq!(s.vote(&bob, id, true), Err(ContractError::VotingEnded));
+}
+
+#[test]
+fn test_quadratic_weight_counts_only_escrowed_balance() {
+    let s = setup();
+    let xlm = symbol_short!("XLM");
+    let proposer = s.verified_user(10_000);
+
+    // Neither an unlocked balance nor a lock taken after the snapshot
+    // carries any weight.
+    let holder = Address::generate(&s.env);
+    s.client.mint(&xlm, &holder, &1_000_000);
+    s.verify(&holder);
+    let late = s.verified_user(1);
+
+    let id = s.client.create_governance_action(
+        &proposer,
+        &ProposalAction::PauseTrading,
+        &VotingMode::Quadratic,
+    );
+    s.env.ledger().with_mut(|l| l.timestamp += 1);
+    s.client.mint(&xlm, &late, &1_000_000);
+    s.client.increase_ve_amount(&late, &1_000_000);
+    assert_eq!(s.client.balance_of(&xlm, &late), 0);
+
+    s.vote(&holder, id, false).unwrap();
+    s.vote(&late, id, false).unwrap();
+    // Only the single unit `late` had escrowed at the snapshot counts.
+    assert_eq!(s.client.get_governance_tally(&id).votes_against, 1);
+
+    s.vote(&proposer, id, true).unwrap();
+    let power = s.client.get_voting_power(&proposer, &Some(START));
+    assert_eq!(s.client.get_governance_tally(&id).votes_for, isqrt(power));
+}
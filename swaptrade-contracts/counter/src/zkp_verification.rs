@@ -235,9 +235,9 @@ mod tests {
     #[test]
     fn test_proof_verifier_creation() {
         let params = CircuitParameters {
-            domain: Bytes::new(&soroban_sdk::Env::new()),
-            generator_g: Bytes::new(&soroban_sdk::Env::new()),
-            generator_h: Bytes::new(&soroban_sdk::Env::new()),
+            domain: Bytes::new(&soroban_sdk::Env::default()),
+            generator_g: Bytes::new(&soroban_sdk::Env::default()),
+            generator_h: Bytes::new(&soroban_sdk::Env::default()),
             hash_function: 1,
         };
         let verifier = ProofVerifier::new(params);
@@ -265,8 +265,8 @@ mod tests {
 
     #[test]
     fn test_crypto_helper_functions() {
-        let blinding = Bytes::new(&soroban_sdk::Env::new());
-        let proof = Bytes::new(&soroban_sdk::Env::new());
+        let blinding = Bytes::new(&soroban_sdk::Env::default());
+        let proof = Bytes::new(&soroban_sdk::Env::default());
 
         // These should not panic
         let _ = crypto_helpers::verify_hash_proof(100, &proof);
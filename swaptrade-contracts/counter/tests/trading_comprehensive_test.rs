@@ -9,22 +9,61 @@
     unused_mut
 )]
 
-use counter::{CounterContract, CounterContractClient};
+use counter::{CounterContract, CounterContractClient, KYCStatus, UserTier};
 use soroban_sdk::{
     symbol_short,
     testutils::{Address as _, Ledger},
     Address, Env, Symbol,
 };
 
+const PRICE_PRECISION: u128 = 1_000_000_000_000_000_000;
+
 fn setup_test_portfolio(env: &Env) -> (CounterContractClient, Address, Symbol, Symbol) {
+    setup_test_portfolio_at(env, PRICE_PRECISION)
+}
+
+/// Register the contract with one verified user, quoting USDCSIM per XLM at
+/// `price` (scaled by `PRICE_PRECISION`).
+fn setup_test_portfolio_at(
+    env: &Env,
+    price: u128,
+) -> (CounterContractClient, Address, Symbol, Symbol) {
     let contract_id = env.register(CounterContract, ());
     let client = CounterContractClient::new(env, &contract_id);
     let user = Address::generate(env);
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDCSIM");
+
+    // `swap` only serves verified users and validates the oracle feed of
+    // the pair it trades, so install an admin, pass the user through KYC
+    // and seed both directions of XLM/USDCSIM.
+    let admin = Address::generate(env);
+    let operator = Address::generate(env);
+    env.as_contract(&contract_id, || {
+        env.storage()
+            .persistent()
+            .set(&symbol_short!("admin"), &admin)
+    });
+    client.kyc_add_operator(&admin, &operator);
+    client.kyc_submit(&user);
+    client.kyc_update_status(&operator, &user, &KYCStatus::InReview, &None);
+    client.kyc_update_status(&operator, &user, &KYCStatus::Verified, &None);
+    client.init_oracle_feed(&admin, &(xlm.clone(), usdc.clone()), &price);
+    client.init_oracle_feed(
+        &admin,
+        &(usdc.clone(), xlm.clone()),
+        &(PRICE_PRECISION * PRICE_PRECISION / price),
+    );
+
     (client, user, xlm, usdc)
 }
 
+/// `amount` less the swap fee `user` pays at their current tier.
+fn net_of_fee(client: &CounterContractClient, user: &Address, amount: i128) -> i128 {
+    let fee_bps = client.get_effective_fee_bps(&client.get_user_tier(user)) as i128;
+    amount - amount * fee_bps / 10000
+}
+
 #[test]
 fn test_swap_basic_xlm_to_usdc() {
     let env = Env::default();
@@ -32,10 +71,11 @@ fn test_swap_basic_xlm_to_usdc() {
     let (client, user, xlm, usdc) = setup_test_portfolio(&env);
 
     client.mint(&xlm, &user, &1000);
+    let expected = net_of_fee(&client, &user, 100);
     let out = client.swap(&xlm, &usdc, &100, &user);
-    assert_eq!(out, 100);
+    assert_eq!(out, expected);
     assert_eq!(client.get_balance(&xlm, &user), 900);
-    assert_eq!(client.get_balance(&usdc, &user), 100);
+    assert_eq!(client.get_balance(&usdc, &user), expected);
 }
 
 #[test]
@@ -45,10 +85,11 @@ fn test_swap_basic_usdc_to_xlm() {
     let (client, user, xlm, usdc) = setup_test_portfolio(&env);
 
     client.mint(&usdc, &user, &1000);
+    let expected = net_of_fee(&client, &user, 200);
     let out = client.swap(&usdc, &xlm, &200, &user);
-    assert_eq!(out, 200);
+    assert_eq!(out, expected);
     assert_eq!(client.get_balance(&usdc, &user), 800);
-    assert_eq!(client.get_balance(&xlm, &user), 200);
+    assert_eq!(client.get_balance(&xlm, &user), expected);
 }
 
 #[test]
@@ -72,18 +113,20 @@ fn test_swap_sequential() {
 
     client.mint(&xlm, &user, &5000);
 
-    // Trade 1
-    client.swap(&xlm, &usdc, &1000, &user);
-    assert_eq!(client.get_balance(&xlm, &user), 4000);
-    assert_eq!(client.get_balance(&usdc, &user), 997); // 1000 - 0.3% fee
-
-    // Trade 2
-    client.swap(&xlm, &usdc, &1000, &user);
-    assert_eq!(client.get_balance(&xlm, &user), 3000);
-
-    // Trade 3: Reverse
+    // Trades 1-3 each sell 1000 XLM
+    let mut usdc_balance = 0;
+    for xlm_left in [4000, 3000, 2000] {
+        usdc_balance += net_of_fee(&client, &user, 1000);
+        client.swap(&xlm, &usdc, &1000, &user);
+        assert_eq!(client.get_balance(&xlm, &user), xlm_left);
+        assert_eq!(client.get_balance(&usdc, &user), usdc_balance);
+    }
+
+    // Trade 4: Reverse
+    let xlm_back = net_of_fee(&client, &user, 500);
     client.swap(&usdc, &xlm, &500, &user);
-    assert_eq!(client.get_balance(&usdc, &user), 1495);
+    assert_eq!(client.get_balance(&usdc, &user), usdc_balance - 500);
+    assert_eq!(client.get_balance(&xlm, &user), 2000 + xlm_back);
 }
 
 #[test]
@@ -118,11 +161,8 @@ fn test_swap_state_consistency() {
 fn test_swap_rounding() {
     let env = Env::default();
     env.mock_all_auths();
-    let (client, user, xlm, usdc) = setup_test_portfolio(&env);
-
-    let precision: u128 = 1_000_000_000_000_000_000;
-    let price: u128 = (25 * precision) / 10;
-    client.set_price(&(xlm.clone(), usdc.clone()), &price);
+    // 2.5 USDCSIM per XLM
+    let (client, user, xlm, usdc) = setup_test_portfolio_at(&env, 25 * PRICE_PRECISION / 10);
 
     client.mint(&xlm, &user, &1000);
     let out = client.swap(&xlm, &usdc, &3, &user);
@@ -138,19 +178,31 @@ fn test_swap_large_amount() {
     let safe_max = 100_000_000_000_000_000_000;
     client.mint(&xlm, &user, &100_000_000_000_000_000_000_000);
 
-    let _ = client.swap(&xlm, &usdc, &safe_max, &user);
+    // Far beyond the default position limit: rejected without moving funds
+    assert!(client.try_swap(&xlm, &usdc, &safe_max, &user).is_err());
+    assert_eq!(
+        client.get_balance(&xlm, &user),
+        100_000_000_000_000_000_000_000
+    );
+    assert_eq!(client.get_balance(&usdc, &user), 0);
 }
 
 #[test]
-fn test_tier_fees_novice() {
+fn test_tier_fees_trader() {
     let env = Env::default();
     env.mock_all_auths();
     let (client, user, xlm, usdc) = setup_test_portfolio(&env);
 
+    // Holding 100+ counts as trading volume, so this user is a Trader
     client.mint(&xlm, &user, &10000);
+    assert_eq!(client.get_user_tier(&user), UserTier::Trader);
+
     client.swap(&xlm, &usdc, &1000, &user);
-    // Novice: 30 bps. Fee = 3. Out = 997.
-    assert_eq!(client.get_balance(&usdc, &user), 997);
+    let fee_bps = client.get_effective_fee_bps(&UserTier::Trader) as i128;
+    assert_eq!(
+        client.get_balance(&usdc, &user),
+        1000 - 1000 * fee_bps / 10000
+    );
 }
 
 macro_rules! test_swap_amount {
@@ -163,10 +215,8 @@ macro_rules! test_swap_amount {
 
             client.mint(&xlm, &user, &1000000000);
 
+            let expected = net_of_fee(&client, &user, $amount);
             let out = client.swap(&xlm, &usdc, &$amount, &user);
-
-            let fee = ($amount * 30) / 10000;
-            let expected = $amount - fee;
             assert_eq!(out, expected);
         }
     };
@@ -189,9 +239,8 @@ macro_rules! test_swap_reverse {
 
             client.mint(&usdc, &user, &1000000000);
 
+            let expected = net_of_fee(&client, &user, $amount);
             let out = client.swap(&usdc, &xlm, &$amount, &user);
-            let fee = ($amount * 30) / 10000;
-            let expected = $amount - fee;
             assert_eq!(out, expected);
         }
     };
@@ -214,6 +263,7 @@ fn test_swap_identical_tokens() {
     let (client, user, xlm, _) = setup_test_portfolio(&env);
 
     client.mint(&xlm, &user, &1000);
-    // Swapping XLM -> XLM should fail or be identity
-    let _ = client.swap(&xlm, &xlm, &100, &user);
+    // There is no XLM/XLM market, so the swap is rejected
+    assert!(client.try_swap(&xlm, &xlm, &100, &user).is_err());
+    assert_eq!(client.get_balance(&xlm, &user), 1000);
 }
@@ -59,6 +59,18 @@ pub fn set_tier_discount_bps(
     discount_bps: u32,
 ) -> Result<(), ContractError> {
     crate::admin::require_admin(env, admin)?;
+    apply_tier_discount_bps(env, tier, discount_bps)
+}
+
+/// Set a tier discount without an admin check; used by governance execution.
+pub(crate) fn apply_tier_discount_bps(
+    env: &Env,
+    tier: UserTier,
+    discount_bps: u32,
+) -> Result<(), ContractError> {
+    if discount_bps > 10_000 {
+        return Err(ContractError::InvalidAmount);
+    }
     let mut table: Map<UserTier, u32> = env
         .storage()
         .instance()
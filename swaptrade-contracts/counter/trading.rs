@@ -127,14 +127,15 @@ pub fn perform_swap(
         return Err(SwapTradeError::UserFrozen);
     }
 
-    // Circuit breaker check
-    let normal_volume = 1000;
-    if crate::emergency::would_trip_circuit_breaker(env, amount, normal_volume) {
+    // Circuit breaker check, global and for both legs
+    if crate::risk_management::CircuitBreaker::is_halted(env, &from)
+        || crate::risk_management::CircuitBreaker::is_halted(env, &to)
+    {
         return Err(SwapTradeError::CircuitBreakerTripped);
     }
 
-    // Record volume for circuit breaker tracking
-    crate::emergency::record_volume(env, amount);
+    // Record volume; trips the global breaker past the volume threshold
+    crate::risk_management::volume_circuit_breaker::check_and_record_volume(env, amount);
 
     let amount_u128 = amount as u128;
 
@@ -211,21 +212,22 @@ pub fn perform_swap(
             numerator / denominator
         }
     } else {
-        amount_u128 // Fallback to 1:1
+        actual_out // Priced straight off the oracle, so no slippage
     };
 
     let max_slip = env.storage().instance().get(&symbol_short!("MAX_SLIP")).unwrap_or(10000u32);
     if theoretical_out > 0 {
-        let slippage_bps = ((theoretical_out - actual_out) * 10000) / theoretical_out;
+        let slippage_bps =
+            (theoretical_out.saturating_sub(actual_out) * 10000) / theoretical_out;
         if slippage_bps > max_slip as u128 {
             return Err(SwapTradeError::SlippageExceeded);
         }
     }
 
     // 7. Execute the swap in the portfolio
-    portfolio.transfer_asset(env, from_asset.clone(), to_asset.clone(), user.clone(), amount);
     portfolio.debit(env, from_asset.clone(), user.clone(), amount);
     portfolio.credit(env, to_asset.clone(), user.clone(), out_amount);
+    portfolio.update_stats_on_trade(env, user.clone(), amount);
 
     // 8. Update pool liquidity
     if reserve_in > 0 && reserve_out > 0 {
@@ -282,22 +284,32 @@ pub fn set_oracle_staleness_threshold(
 /// Implements atomic execution: if any hop fails, entire transaction reverts
 /// Each hop respects slippage tolerance and oracle-based minimum rates
 pub fn execute_multihop_swap(
-    _env: &Env,
+    env: &Env,
     route: &crate::liquidity_pool::Route,
     amount_in: i128,
-    _min_amount_out: i128,
+    min_amount_out: i128,
     _trader: &soroban_sdk::Address,
 ) -> Result<i128, crate::errors::SwapTradeError> {
-    if route.pools.is_empty() {
+    if route.pools.is_empty() || route.tokens.len() != route.pools.len() + 1 {
         return Err(SwapTradeError::InvalidAmount);
     }
     if amount_in <= 0 {
         return Err(SwapTradeError::InvalidAmount);
     }
-    // TODO: Implement proper multi-hop swap using registry.swap()
-    // route.pools is Vec<u64> (pool IDs) and route.tokens is Vec<Symbol>
-    // Each hop needs: pool_id, token_in, token_out
-    Err(SwapTradeError::NotAuthorized)
+
+    // Hops run against an in-memory registry that is only saved once the
+    // final output clears `min_amount_out`, so a failing hop reverts them all.
+    let mut registry = crate::load_pool_registry(env);
+    let mut amount = amount_in;
+    for (i, pool_id) in route.pools.iter().enumerate() {
+        let token_in = route.tokens.get(i as u32).ok_or(SwapTradeError::InvalidAmount)?;
+        amount = registry.swap(env, pool_id, token_in, amount, 0)?;
+    }
+    if amount < min_amount_out {
+        return Err(SwapTradeError::SlippageExceeded);
+    }
+    crate::save_pool_registry(env, &registry);
+    Ok(amount)
 }
 
 /// Legacy wrapper for backward compatibility - calls the new perform_swap with None for min_amount_out
@@ -1,3 +1,4 @@
+use compliance::DenylistError;
 use soroban_sdk::contracterror;
 
 /// Error types for the escrow-dispute contract.
@@ -30,4 +31,26 @@ pub enum EscrowError {
     InsufficientSignatures = 12,
     /// Dispute has not reached its deadline yet (for auto-refund).
     DeadlineNotReached = 13,
+    /// The seller or buyer is on the sanctions denylist.
+    AddressDenied = 14,
+    /// Caller is not the compliance officer.
+    NotComplianceOfficer = 15,
+    /// No denylist root has been anchored yet.
+    DenylistRootNotAnchored = 16,
+    /// A denylist proof does not match the anchored root.
+    InvalidDenylistProof = 17,
+    /// Denylist batch is empty or too large.
+    DenylistBatchTooLarge = 18,
+}
+
+impl From<DenylistError> for EscrowError {
+    fn from(err: DenylistError) -> Self {
+        match err {
+            DenylistError::NotComplianceOfficer => EscrowError::NotComplianceOfficer,
+            DenylistError::RootNotAnchored => EscrowError::DenylistRootNotAnchored,
+            DenylistError::InvalidProof => EscrowError::InvalidDenylistProof,
+            DenylistError::BatchTooLarge => EscrowError::DenylistBatchTooLarge,
+            DenylistError::AddressDenied => EscrowError::AddressDenied,
+        }
+    }
 }
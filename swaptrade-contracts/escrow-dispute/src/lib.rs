@@ -16,10 +16,12 @@ mod events;
 mod storage;
 mod types;
 
+pub use compliance::{DenylistMatches, DenylistRoot};
 pub use errors::EscrowError;
 pub use types::{Dispute, DisputeEvidence, DisputeStatus, DisputeVote, Escrow, EscrowState};
 
-use soroban_sdk::{contract, contractimpl, Address, BytesN, Env, Symbol, Vec};
+use compliance::Denylist;
+use soroban_sdk::{contract, contractimpl, symbol_short, Address, BytesN, Env, Symbol, Vec};
 
 /// Soroban escrow contract with time-locked dispute resolution.
 ///
@@ -30,9 +32,18 @@ use soroban_sdk::{contract, contractimpl, Address, BytesN, Env, Symbol, Vec};
 /// 4. Evidence is submitted off-chain (IPFS/Arweave) with on-chain hash references.
 /// 5. Multisig signers resolve the dispute (release to seller or refund to buyer).
 /// 6. If no resolution within the dispute window, anyone can trigger auto-refund.
+///
+/// Every call that moves assets screens both the seller and the buyer
+/// against the sanctions denylist, whoever the caller is.
 #[contract]
 pub struct EscrowDisputeContract;
 
+/// Fail if the seller or buyer of `escrow` is on the denylist.
+fn screen_parties(env: &Env, context: Symbol, escrow: &Escrow) -> Result<(), EscrowError> {
+    Denylist::screen(env, context, &[&escrow.seller, &escrow.buyer])?;
+    Ok(())
+}
+
 #[contractimpl]
 impl EscrowDisputeContract {
     // ════════════════════════════════════════════════════════
@@ -112,6 +123,7 @@ impl EscrowDisputeContract {
         if seller == buyer {
             return Err(EscrowError::Unauthorized);
         }
+        Denylist::screen(&env, symbol_short!("create"), &[&seller, &buyer])?;
 
         let now = env.ledger().timestamp();
         let min_tl = storage::min_timelock(&env);
@@ -167,6 +179,7 @@ impl EscrowDisputeContract {
         if escrow.state != EscrowState::Created {
             return Err(EscrowError::InvalidState);
         }
+        screen_parties(&env, symbol_short!("fund"), &escrow)?;
 
         // ── Trustline check ────────────────────────────────
         if !storage::has_trustline(&env, &funder, &escrow.asset) {
@@ -368,6 +381,7 @@ impl EscrowDisputeContract {
         let threshold = storage::load_threshold(&env);
         let release_votes = storage::count_release_votes(&env, escrow_id);
         let refund_votes = storage::count_refund_votes(&env, escrow_id);
+        screen_parties(&env, symbol_short!("resolve"), &escrow)?;
 
         let contract_addr = env.current_contract_address();
 
@@ -436,6 +450,7 @@ impl EscrowDisputeContract {
         if now < dispute.deadline {
             return Err(EscrowError::DeadlineNotReached);
         }
+        screen_parties(&env, symbol_short!("refund"), &escrow)?;
 
         // ── Auto-refund to buyer ───────────────────────────
         dispute.status = DisputeStatus::AutoRefunded;
@@ -551,4 +566,91 @@ impl EscrowDisputeContract {
         caller.require_auth();
         storage::set_min_timelock(&env, seconds);
     }
+
+    // ════════════════════════════════════════════════════════
+    //  SANCTIONS DENYLIST
+    // ════════════════════════════════════════════════════════
+
+    /// Signer: set the compliance officer who maintains the denylist.
+    pub fn set_compliance_officer(
+        env: Env,
+        signer: Address,
+        officer: Address,
+    ) -> Result<(), EscrowError> {
+        signer.require_auth();
+        if !storage::is_signer(&env, &signer) {
+            return Err(EscrowError::Unauthorized);
+        }
+        Denylist::set_officer(&env, &officer);
+        Ok(())
+    }
+
+    /// Get the compliance officer, if one has been set.
+    pub fn get_compliance_officer(env: Env) -> Option<Address> {
+        Denylist::officer(&env)
+    }
+
+    /// Officer: anchor the Merkle root of a newly published screening list.
+    /// Returns the new root version.
+    pub fn anchor_denylist_root(
+        env: Env,
+        officer: Address,
+        root: BytesN<32>,
+        entry_count: u32,
+    ) -> Result<u32, EscrowError> {
+        Ok(Denylist::anchor_root(&env, &officer, root, entry_count)?)
+    }
+
+    /// Officer: deny `entries`, each proven against the anchored root by
+    /// the matching entry of `proofs`.
+    pub fn denylist_addresses(
+        env: Env,
+        officer: Address,
+        entries: Vec<Address>,
+        proofs: Vec<Vec<BytesN<32>>>,
+    ) -> Result<(), EscrowError> {
+        Ok(Denylist::list(&env, &officer, entries, proofs)?)
+    }
+
+    /// Officer: remove `entries` from the denylist.
+    pub fn delist_addresses(
+        env: Env,
+        officer: Address,
+        entries: Vec<Address>,
+    ) -> Result<(), EscrowError> {
+        Ok(Denylist::delist(&env, &officer, entries)?)
+    }
+
+    /// Check whether `address` is on the denylist.
+    pub fn is_denied(env: Env, address: Address) -> bool {
+        Denylist::is_denied(&env, &address)
+    }
+
+    /// Get the currently anchored denylist root.
+    pub fn get_denylist_root(env: Env) -> Option<DenylistRoot> {
+        Denylist::root(&env)
+    }
+
+    /// Screen `addresses` without rejecting on a match, e.g. before
+    /// submitting a value-moving call. Matches are persisted and published
+    /// as `("deny", "match")` events; returns the listed addresses.
+    pub fn screen_addresses(
+        env: Env,
+        caller: Address,
+        context: Symbol,
+        addresses: Vec<Address>,
+    ) -> Result<Vec<Address>, EscrowError> {
+        caller.require_auth();
+        Ok(Denylist::record_matches(&env, context, &addresses)?)
+    }
+
+    /// Get the screening matches recorded for `address`.
+    pub fn get_denylist_matches(env: Env, address: Address) -> Option<DenylistMatches> {
+        Denylist::matches(&env, &address)
+    }
+
+    /// Get the total number of screening matches recorded.
+    pub fn get_denylist_match_total(env: Env) -> u64 {
+        Denylist::match_total(&env)
+    }
 }
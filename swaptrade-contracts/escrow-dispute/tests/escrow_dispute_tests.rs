@@ -3,7 +3,8 @@
 extern crate std;
 use std::println;
 
-use escrow_dispute::{DisputeStatus, EscrowDisputeContract, EscrowState};
+use compliance::Denylist;
+use escrow_dispute::{DisputeStatus, EscrowDisputeContract, EscrowError, EscrowState};
 use soroban_sdk::testutils::{Address as _, Ledger};
 use soroban_sdk::{contract, contractimpl, symbol_short, Address, BytesN, Env, IntoVal, Symbol, Vec};
 
@@ -845,3 +846,78 @@ fn escrow_with_no_dispute_remains_escaped() {
     assert_eq!(get_escrow_state(&ctx, id), EscrowState::Disputed);
     println!("✓ escrow_with_no_dispute_remains_escaped passed");
 }
+
+// ══════════════════════════════════════════════════════════════
+//  TESTS — Sanctions denylist
+// ══════════════════════════════════════════════════════════════
+
+/// Install an officer, anchor a two-leaf list of `target` and a filler
+/// address, and deny `target` with its proof.
+fn deny(ctx: &TestContext, target: &Address) -> Address {
+    let env = &ctx.env;
+    let officer = Address::generate(env);
+    ctx.client.set_compliance_officer(&ctx.signer1, &officer);
+
+    let filler = Address::generate(env);
+    let sibling = Denylist::leaf(env, &filler);
+    let root = Denylist::hash_pair(env, &Denylist::leaf(env, target), &sibling);
+    ctx.client.anchor_denylist_root(&officer, &root, &2u32);
+
+    let proofs: Vec<Vec<BytesN<32>>> = soroban_sdk::vec![env, soroban_sdk::vec![env, sibling]];
+    ctx.client
+        .denylist_addresses(&officer, &soroban_sdk::vec![env, target.clone()], &proofs);
+    officer
+}
+
+#[test]
+fn denied_buyer_blocks_create() {
+    let ctx = setup();
+    deny(&ctx, &ctx.buyer);
+
+    let result = ctx.client.try_create_escrow(
+        &ctx.seller,
+        &ctx.buyer,
+        &ctx.asset,
+        &100i128,
+        &86400u64,
+        &1u64,
+    );
+    assert_eq!(result, Err(Ok(EscrowError::AddressDenied)));
+    println!("✓ denied_buyer_blocks_create passed");
+}
+
+#[test]
+fn denied_seller_freezes_disputed_funds() {
+    let ctx = setup();
+    let id = create_escrow_helper(&ctx, 1);
+    fund_escrow_helper(&ctx, id);
+    raise_dispute_helper(&ctx, id, &ctx.buyer, 3600);
+    vote_helper(&ctx, id, &ctx.signer1, true);
+    vote_helper(&ctx, id, &ctx.signer2, true);
+
+    let officer = deny(&ctx, &ctx.seller);
+    let result = ctx.client.try_resolve_dispute(&id, &ctx.signer1);
+    assert_eq!(result, Err(Ok(EscrowError::AddressDenied)));
+
+    // The timelock fallback is screened too.
+    let now = ctx.env.ledger().timestamp();
+    ctx.env.ledger().set_timestamp(now + 3601);
+    let result = ctx.client.try_auto_refund(&id);
+    assert_eq!(result, Err(Ok(EscrowError::AddressDenied)));
+    assert_eq!(get_escrow_state(&ctx, id), EscrowState::Disputed);
+
+    ctx.client
+        .delist_addresses(&officer, &soroban_sdk::vec![&ctx.env, ctx.seller.clone()]);
+    ctx.client.resolve_dispute(&id, &ctx.signer1);
+    assert_eq!(get_escrow_state(&ctx, id), EscrowState::Released);
+    println!("✓ denied_seller_freezes_disputed_funds passed");
+}
+
+#[test]
+fn non_signer_cannot_set_compliance_officer() {
+    let ctx = setup();
+    let result = ctx.client.try_set_compliance_officer(&ctx.buyer, &ctx.buyer);
+    assert_eq!(result, Err(Ok(EscrowError::Unauthorized)));
+    assert_eq!(ctx.client.get_compliance_officer(), None);
+    println!("✓ non_signer_cannot_set_compliance_officer passed");
+}
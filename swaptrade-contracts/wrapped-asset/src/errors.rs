@@ -0,0 +1,31 @@
+use soroban_sdk::contracterror;
+
+/// Error types for the wrapped asset contract.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum WrappedAssetError {
+    /// `initialize` was called on a contract that already has an admin.
+    AlreadyInitialized = 1,
+    /// The contract has not been initialized.
+    NotInitialized = 2,
+    /// Amount must be strictly positive (or non-negative for approvals).
+    InvalidAmount = 3,
+    /// The holder's balance is below the amount being spent.
+    InsufficientBalance = 4,
+    /// The spender's allowance is below the amount being spent.
+    InsufficientAllowance = 5,
+    /// Allowance expiry is in the past for a non-zero approval.
+    InvalidExpiration = 6,
+    /// Arithmetic overflow.
+    Overflow = 7,
+    /// Flash-mint cap is negative or the fee exceeds 100%.
+    InvalidFlashMintConfig = 8,
+    /// Requested amount exceeds the flash-mint cap, or flash minting is paused.
+    FlashMintCapExceeded = 9,
+    /// A flash mint was requested while another one is still in progress.
+    FlashMintInProgress = 10,
+    /// The receiver returned `false` from `on_flash_mint`.
+    FlashMintCallbackFailed = 11,
+    /// `mint_flash` was called before the admin set a flash minter.
+    FlashMinterNotSet = 12,
+}
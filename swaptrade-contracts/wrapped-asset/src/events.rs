@@ -0,0 +1,45 @@
+// `Events::publish` is deprecated in favour of `#[contractevent]`; the topic
+// layout here matches the other SwapTrade contracts.
+#![allow(deprecated)]
+
+use soroban_sdk::{symbol_short, Address, Env, Symbol};
+
+// ── Event topics ─────────────────────────────────────────────
+const TOPIC_APPROVE: Symbol = symbol_short!("approve");
+const TOPIC_TRANSFER: Symbol = symbol_short!("transfer");
+const TOPIC_MINT: Symbol = symbol_short!("mint");
+const TOPIC_BURN: Symbol = symbol_short!("burn");
+const TOPIC_FM_PAUSE: Symbol = symbol_short!("fm_pause");
+const TOPIC_FLASH_MINT: Symbol = symbol_short!("flashmint");
+
+/// Emitted when `from` approves `spender`.
+pub fn approve(env: &Env, from: Address, spender: Address, amount: i128, expiration_ledger: u32) {
+    env.events()
+        .publish((TOPIC_APPROVE, from, spender), (amount, expiration_ledger));
+}
+
+/// Emitted on every balance move between two holders.
+pub fn transfer(env: &Env, from: Address, to: Address, amount: i128) {
+    env.events().publish((TOPIC_TRANSFER, from, to), amount);
+}
+
+/// Emitted when new units are minted to `to`.
+pub fn mint(env: &Env, admin: Address, to: Address, amount: i128) {
+    env.events().publish((TOPIC_MINT, admin, to), amount);
+}
+
+/// Emitted when units are burned from `from`.
+pub fn burn(env: &Env, from: Address, amount: i128) {
+    env.events().publish((TOPIC_BURN, from), amount);
+}
+
+/// Emitted when flash minting is paused or resumed.
+pub fn flash_mint_paused(env: &Env, paused: bool) {
+    env.events().publish((TOPIC_FM_PAUSE,), paused);
+}
+
+/// Emitted after a flash mint has been repaid.
+pub fn flash_mint(env: &Env, receiver: Address, initiator: Address, amount: i128, fee: i128) {
+    env.events()
+        .publish((TOPIC_FLASH_MINT, receiver), (initiator, amount, fee));
+}
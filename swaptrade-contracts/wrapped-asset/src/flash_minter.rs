@@ -0,0 +1,146 @@
+//! ERC-3156 style flash minting for a [`WrappedAsset`](crate::WrappedAsset).
+//!
+//! Soroban forbids re-entering a contract that is already on the call stack,
+//! so a token that called its borrower back itself would leave the borrower
+//! unable to transfer, swap or approve the minted units. The lender is
+//! therefore a separate contract: it mints through the token's flash-minter
+//! role, calls the borrower, and burns the repayment with `burn_from` under
+//! an allowance the borrower grants during its callback.
+//!
+//! `FlashMinter` is deployed as its own instance of this crate's wasm; its
+//! entrypoint names are distinct from the token's so both interfaces fit in
+//! one module.
+
+use soroban_sdk::{contract, contractclient, contractimpl, token, Address, Bytes, Env};
+
+use crate::errors::WrappedAssetError;
+use crate::{events, storage, WrappedAssetClient};
+
+/// ERC-3156 style callback implemented by flash-mint borrowers.
+///
+/// `on_flash_mint` is called after `amount` has been minted to the receiver.
+/// Before returning `true` it must approve the lender (the caller) to spend
+/// `amount + fee` of `token`; the lender then burns that much from the
+/// receiver.
+#[contractclient(name = "FlashMintReceiverClient")]
+pub trait FlashMintReceiver {
+    fn on_flash_mint(
+        env: Env,
+        initiator: Address,
+        token: Address,
+        amount: i128,
+        fee: i128,
+        data: Bytes,
+    ) -> bool;
+}
+
+/// Flash-mint lender for one wrapped asset, with a per-call cap, a fee and a
+/// global kill switch.
+#[contract]
+pub struct FlashMinter;
+
+#[contractimpl]
+impl FlashMinter {
+    /// Initialize the lender for `token`. Can only be called once. The token
+    /// admin must still grant this contract the flash-minter role.
+    pub fn init_flash_minter(
+        env: Env,
+        admin: Address,
+        token: Address,
+    ) -> Result<(), WrappedAssetError> {
+        if storage::has_admin(&env) {
+            return Err(WrappedAssetError::AlreadyInitialized);
+        }
+        storage::set_flash_mint_lender(&env, &admin, &token);
+        Ok(())
+    }
+
+    pub fn flash_mint_token(env: Env) -> Result<Address, WrappedAssetError> {
+        storage::flash_mint_token(&env)
+    }
+
+    /// Configure flash minting: the per-call cap and the fee in basis points.
+    pub fn set_flash_mint_config(
+        env: Env,
+        max_amount: i128,
+        fee_bps: u32,
+    ) -> Result<(), WrappedAssetError> {
+        storage::admin(&env)?.require_auth();
+        if max_amount < 0 || fee_bps > 10_000 {
+            return Err(WrappedAssetError::InvalidFlashMintConfig);
+        }
+        storage::set_flash_mint_config(&env, max_amount, fee_bps);
+        Ok(())
+    }
+
+    /// Global kill switch for flash minting.
+    pub fn set_flash_mint_paused(env: Env, paused: bool) -> Result<(), WrappedAssetError> {
+        storage::admin(&env)?.require_auth();
+        storage::set_flash_mint_paused(&env, paused);
+        events::flash_mint_paused(&env, paused);
+        Ok(())
+    }
+
+    /// Largest amount that can currently be flash minted in one call.
+    /// Zero while flash minting is paused or no cap has been configured.
+    pub fn max_flash_mint(env: Env) -> i128 {
+        if storage::flash_mint_paused(&env) {
+            return 0;
+        }
+        storage::flash_mint_cap(&env)
+    }
+
+    /// Fee charged for flash minting `amount`, never less than 1 so small
+    /// mints cannot borrow for free.
+    pub fn flash_fee(env: Env, amount: i128) -> Result<i128, WrappedAssetError> {
+        amount
+            .checked_mul(storage::flash_mint_fee_bps(&env) as i128)
+            .map(|scaled| (scaled / 10_000).max(1))
+            .ok_or(WrappedAssetError::Overflow)
+    }
+
+    /// Mint `amount` to `receiver`, invoke its `on_flash_mint` callback, then
+    /// burn `amount + fee` from it through the allowance it granted. Total
+    /// supply ends up reduced by the fee, so the call fails unless the
+    /// receiver holds it.
+    pub fn flash_mint(
+        env: Env,
+        initiator: Address,
+        receiver: Address,
+        amount: i128,
+        data: Bytes,
+    ) -> Result<i128, WrappedAssetError> {
+        initiator.require_auth();
+
+        if storage::flash_mint_locked(&env) {
+            return Err(WrappedAssetError::FlashMintInProgress);
+        }
+        if amount <= 0 {
+            return Err(WrappedAssetError::InvalidAmount);
+        }
+        if amount > Self::max_flash_mint(env.clone()) {
+            return Err(WrappedAssetError::FlashMintCapExceeded);
+        }
+
+        let token = storage::flash_mint_token(&env)?;
+        let fee = Self::flash_fee(env.clone(), amount)?;
+        let repayment = amount.checked_add(fee).ok_or(WrappedAssetError::Overflow)?;
+
+        storage::set_flash_mint_lock(&env, true);
+
+        WrappedAssetClient::new(&env, &token).mint_flash(&receiver, &amount);
+
+        let client = FlashMintReceiverClient::new(&env, &receiver);
+        if !client.on_flash_mint(&initiator, &token, &amount, &fee, &data) {
+            return Err(WrappedAssetError::FlashMintCallbackFailed);
+        }
+
+        let this = env.current_contract_address();
+        token::Client::new(&env, &token).burn_from(&this, &receiver, &repayment);
+
+        storage::set_flash_mint_lock(&env, false);
+
+        events::flash_mint(&env, receiver, initiator, amount, fee);
+        Ok(fee)
+    }
+}
@@ -0,0 +1,169 @@
+#![cfg_attr(all(not(test), target_family = "wasm"), no_std)]
+
+mod errors;
+mod events;
+mod flash_minter;
+mod storage;
+
+pub use errors::WrappedAssetError;
+pub use flash_minter::{
+    FlashMintReceiver, FlashMintReceiverClient, FlashMinter, FlashMinterClient,
+};
+pub use storage::DEFAULT_FLASH_FEE_BPS;
+
+use soroban_sdk::{
+    contract, contractimpl, panic_with_error, token, Address, Env, MuxedAddress, String,
+};
+
+/// Bridge-issued wrapped asset.
+///
+/// A SEP-41 token whose balances live in this contract's own storage. Only
+/// the admin (the bridge) can mint; holders can burn to redeem. Flash mints
+/// are issued by a separate [`FlashMinter`] holding the flash-minter role, so
+/// the borrower can use the token freely during its callback.
+#[contract]
+pub struct WrappedAsset;
+
+fn require_positive(env: &Env, amount: i128) {
+    if amount <= 0 {
+        panic_with_error!(env, WrappedAssetError::InvalidAmount);
+    }
+}
+
+fn unwrap_or_panic<T>(env: &Env, result: Result<T, WrappedAssetError>) -> T {
+    result.unwrap_or_else(|err| panic_with_error!(env, err))
+}
+
+#[contractimpl]
+impl WrappedAsset {
+    /// Initialize the wrapped asset contract. Can only be called once.
+    pub fn initialize(
+        env: Env,
+        admin: Address,
+        name: String,
+        symbol: String,
+        decimals: u32,
+    ) -> Result<(), WrappedAssetError> {
+        if storage::has_admin(&env) {
+            return Err(WrappedAssetError::AlreadyInitialized);
+        }
+        storage::set_metadata(&env, &admin, &name, &symbol, decimals);
+        Ok(())
+    }
+
+    pub fn admin(env: Env) -> Result<Address, WrappedAssetError> {
+        storage::admin(&env)
+    }
+
+    pub fn total_supply(env: Env) -> i128 {
+        storage::total_supply(&env)
+    }
+
+    /// Mint new wrapped assets. Admin only.
+    pub fn mint(env: Env, to: Address, amount: i128) -> Result<(), WrappedAssetError> {
+        let admin = storage::admin(&env)?;
+        admin.require_auth();
+        if amount <= 0 {
+            return Err(WrappedAssetError::InvalidAmount);
+        }
+        storage::mint_to(&env, &to, amount)?;
+        events::mint(&env, admin, to, amount);
+        Ok(())
+    }
+
+    /// Grant the flash-minter role to `minter`, replacing any previous
+    /// holder. Admin only.
+    pub fn set_flash_minter(env: Env, minter: Address) -> Result<(), WrappedAssetError> {
+        storage::admin(&env)?.require_auth();
+        storage::set_flash_minter(&env, &minter);
+        Ok(())
+    }
+
+    pub fn flash_minter(env: Env) -> Option<Address> {
+        storage::flash_minter(&env)
+    }
+
+    /// Mint `amount` to `to` for an in-flight flash mint. Flash minter only;
+    /// the minter is responsible for burning it back before it returns.
+    pub fn mint_flash(env: Env, to: Address, amount: i128) -> Result<(), WrappedAssetError> {
+        let minter = storage::flash_minter(&env).ok_or(WrappedAssetError::FlashMinterNotSet)?;
+        minter.require_auth();
+        if amount <= 0 {
+            return Err(WrappedAssetError::InvalidAmount);
+        }
+        storage::mint_to(&env, &to, amount)?;
+        events::mint(&env, minter, to, amount);
+        Ok(())
+    }
+}
+
+#[contractimpl]
+impl token::TokenInterface for WrappedAsset {
+    fn allowance(env: Env, from: Address, spender: Address) -> i128 {
+        storage::allowance(&env, &from, &spender)
+    }
+
+    fn approve(env: Env, from: Address, spender: Address, amount: i128, expiration_ledger: u32) {
+        from.require_auth();
+        unwrap_or_panic(
+            &env,
+            storage::write_allowance(&env, &from, &spender, amount, expiration_ledger),
+        );
+        events::approve(&env, from, spender, amount, expiration_ledger);
+    }
+
+    fn balance(env: Env, id: Address) -> i128 {
+        storage::balance(&env, &id)
+    }
+
+    fn transfer(env: Env, from: Address, to_muxed: MuxedAddress, amount: i128) {
+        from.require_auth();
+        require_positive(&env, amount);
+        let to = to_muxed.address();
+        unwrap_or_panic(&env, storage::spend_balance(&env, &from, amount));
+        unwrap_or_panic(&env, storage::receive_balance(&env, &to, amount));
+        events::transfer(&env, from, to, amount);
+    }
+
+    fn transfer_from(env: Env, spender: Address, from: Address, to: Address, amount: i128) {
+        spender.require_auth();
+        require_positive(&env, amount);
+        unwrap_or_panic(
+            &env,
+            storage::spend_allowance(&env, &from, &spender, amount),
+        );
+        unwrap_or_panic(&env, storage::spend_balance(&env, &from, amount));
+        unwrap_or_panic(&env, storage::receive_balance(&env, &to, amount));
+        events::transfer(&env, from, to, amount);
+    }
+
+    fn burn(env: Env, from: Address, amount: i128) {
+        from.require_auth();
+        require_positive(&env, amount);
+        unwrap_or_panic(&env, storage::burn_from_balance(&env, &from, amount));
+        events::burn(&env, from, amount);
+    }
+
+    fn burn_from(env: Env, spender: Address, from: Address, amount: i128) {
+        spender.require_auth();
+        require_positive(&env, amount);
+        unwrap_or_panic(
+            &env,
+            storage::spend_allowance(&env, &from, &spender, amount),
+        );
+        unwrap_or_panic(&env, storage::burn_from_balance(&env, &from, amount));
+        events::burn(&env, from, amount);
+    }
+
+    fn decimals(env: Env) -> u32 {
+        storage::decimals(&env)
+    }
+
+    fn name(env: Env) -> String {
+        storage::name(&env)
+    }
+
+    fn symbol(env: Env) -> String {
+        storage::symbol(&env)
+    }
+}
@@ -0,0 +1,267 @@
+use soroban_sdk::{contracttype, Address, Env, String};
+
+use crate::errors::WrappedAssetError;
+
+/// Default flash-mint fee in basis points, applied until the admin sets one.
+pub const DEFAULT_FLASH_FEE_BPS: u32 = 5;
+
+// ── Storage keys ─────────────────────────────────────────────
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Admin,
+    Name,
+    Symbol,
+    Decimals,
+    TotalSupply,
+    Balance(Address),
+    Allowance(Address, Address),
+    /// Lender allowed to mint through `mint_flash`.
+    FlashMinter,
+    /// Wrapped asset a `FlashMinter` lends.
+    FlashMintToken,
+    FlashMintCap,
+    FlashMintFeeBps,
+    FlashMintPaused,
+    /// Temporary entry held for the duration of a flash mint.
+    FlashMintLock,
+}
+
+/// Remaining approval of a spender and the ledger it lapses after.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Allowance {
+    pub amount: i128,
+    pub expiration_ledger: u32,
+}
+
+// ── Admin & metadata ─────────────────────────────────────────
+
+pub fn has_admin(env: &Env) -> bool {
+    env.storage().instance().has(&DataKey::Admin)
+}
+
+pub fn admin(env: &Env) -> Result<Address, WrappedAssetError> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .ok_or(WrappedAssetError::NotInitialized)
+}
+
+pub fn set_metadata(env: &Env, admin: &Address, name: &String, symbol: &String, decimals: u32) {
+    let storage = env.storage().instance();
+    storage.set(&DataKey::Admin, admin);
+    storage.set(&DataKey::Name, name);
+    storage.set(&DataKey::Symbol, symbol);
+    storage.set(&DataKey::Decimals, &decimals);
+}
+
+pub fn name(env: &Env) -> String {
+    env.storage()
+        .instance()
+        .get(&DataKey::Name)
+        .unwrap_or_else(|| String::from_str(env, ""))
+}
+
+pub fn symbol(env: &Env) -> String {
+    env.storage()
+        .instance()
+        .get(&DataKey::Symbol)
+        .unwrap_or_else(|| String::from_str(env, ""))
+}
+
+pub fn decimals(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::Decimals)
+        .unwrap_or(0)
+}
+
+// ── Balances ─────────────────────────────────────────────────
+
+pub fn balance(env: &Env, id: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Balance(id.clone()))
+        .unwrap_or(0)
+}
+
+pub fn total_supply(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKey::TotalSupply)
+        .unwrap_or(0)
+}
+
+/// Add `amount` to `id`'s balance.
+pub fn receive_balance(env: &Env, id: &Address, amount: i128) -> Result<(), WrappedAssetError> {
+    let updated = balance(env, id)
+        .checked_add(amount)
+        .ok_or(WrappedAssetError::Overflow)?;
+    env.storage()
+        .persistent()
+        .set(&DataKey::Balance(id.clone()), &updated);
+    Ok(())
+}
+
+/// Remove `amount` from `id`'s balance.
+pub fn spend_balance(env: &Env, id: &Address, amount: i128) -> Result<(), WrappedAssetError> {
+    let current = balance(env, id);
+    if current < amount {
+        return Err(WrappedAssetError::InsufficientBalance);
+    }
+    env.storage()
+        .persistent()
+        .set(&DataKey::Balance(id.clone()), &(current - amount));
+    Ok(())
+}
+
+/// Create `amount` new units owned by `to`.
+pub fn mint_to(env: &Env, to: &Address, amount: i128) -> Result<(), WrappedAssetError> {
+    receive_balance(env, to, amount)?;
+    let supply = total_supply(env)
+        .checked_add(amount)
+        .ok_or(WrappedAssetError::Overflow)?;
+    env.storage().instance().set(&DataKey::TotalSupply, &supply);
+    Ok(())
+}
+
+/// Destroy `amount` units owned by `from`.
+pub fn burn_from_balance(env: &Env, from: &Address, amount: i128) -> Result<(), WrappedAssetError> {
+    spend_balance(env, from, amount)?;
+    env.storage()
+        .instance()
+        .set(&DataKey::TotalSupply, &(total_supply(env) - amount));
+    Ok(())
+}
+
+// ── Allowances ───────────────────────────────────────────────
+
+/// Live allowance of `spender` over `from`'s balance; zero once expired.
+pub fn allowance(env: &Env, from: &Address, spender: &Address) -> i128 {
+    let entry: Option<Allowance> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Allowance(from.clone(), spender.clone()));
+    match entry {
+        Some(a) if a.expiration_ledger >= env.ledger().sequence() => a.amount,
+        _ => 0,
+    }
+}
+
+pub fn write_allowance(
+    env: &Env,
+    from: &Address,
+    spender: &Address,
+    amount: i128,
+    expiration_ledger: u32,
+) -> Result<(), WrappedAssetError> {
+    if amount < 0 {
+        return Err(WrappedAssetError::InvalidAmount);
+    }
+    if amount > 0 && expiration_ledger < env.ledger().sequence() {
+        return Err(WrappedAssetError::InvalidExpiration);
+    }
+    env.storage().persistent().set(
+        &DataKey::Allowance(from.clone(), spender.clone()),
+        &Allowance {
+            amount,
+            expiration_ledger,
+        },
+    );
+    Ok(())
+}
+
+/// Consume `amount` of `spender`'s allowance over `from`'s balance.
+pub fn spend_allowance(
+    env: &Env,
+    from: &Address,
+    spender: &Address,
+    amount: i128,
+) -> Result<(), WrappedAssetError> {
+    let key = DataKey::Allowance(from.clone(), spender.clone());
+    let current = allowance(env, from, spender);
+    if current < amount {
+        return Err(WrappedAssetError::InsufficientAllowance);
+    }
+    let mut entry: Allowance = env.storage().persistent().get(&key).unwrap();
+    entry.amount = current - amount;
+    env.storage().persistent().set(&key, &entry);
+    Ok(())
+}
+
+// ── Flash-mint roles ─────────────────────────────────────────
+
+pub fn flash_minter(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&DataKey::FlashMinter)
+}
+
+pub fn set_flash_minter(env: &Env, minter: &Address) {
+    env.storage().instance().set(&DataKey::FlashMinter, minter);
+}
+
+pub fn flash_mint_token(env: &Env) -> Result<Address, WrappedAssetError> {
+    env.storage()
+        .instance()
+        .get(&DataKey::FlashMintToken)
+        .ok_or(WrappedAssetError::NotInitialized)
+}
+
+pub fn set_flash_mint_lender(env: &Env, admin: &Address, token: &Address) {
+    let storage = env.storage().instance();
+    storage.set(&DataKey::Admin, admin);
+    storage.set(&DataKey::FlashMintToken, token);
+}
+
+// ── Flash-mint config ────────────────────────────────────────
+
+pub fn flash_mint_cap(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKey::FlashMintCap)
+        .unwrap_or(0)
+}
+
+pub fn flash_mint_fee_bps(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::FlashMintFeeBps)
+        .unwrap_or(DEFAULT_FLASH_FEE_BPS)
+}
+
+pub fn set_flash_mint_config(env: &Env, max_amount: i128, fee_bps: u32) {
+    env.storage()
+        .instance()
+        .set(&DataKey::FlashMintCap, &max_amount);
+    env.storage()
+        .instance()
+        .set(&DataKey::FlashMintFeeBps, &fee_bps);
+}
+
+pub fn flash_mint_paused(env: &Env) -> bool {
+    env.storage()
+        .instance()
+        .get(&DataKey::FlashMintPaused)
+        .unwrap_or(false)
+}
+
+pub fn set_flash_mint_paused(env: &Env, paused: bool) {
+    env.storage()
+        .instance()
+        .set(&DataKey::FlashMintPaused, &paused);
+}
+
+pub fn flash_mint_locked(env: &Env) -> bool {
+    env.storage().temporary().has(&DataKey::FlashMintLock)
+}
+
+pub fn set_flash_mint_lock(env: &Env, locked: bool) {
+    if locked {
+        env.storage()
+            .temporary()
+            .set(&DataKey::FlashMintLock, &true);
+    } else {
+        env.storage().temporary().remove(&DataKey::FlashMintLock);
+    }
+}
@@ -0,0 +1,354 @@
+#![cfg(test)]
+
+use soroban_sdk::testutils::{Address as _, MockAuth, MockAuthInvoke};
+use soroban_sdk::{
+    contract, contractimpl, symbol_short, Address, Bytes, Env, IntoVal, String, Symbol,
+};
+use wrapped_asset::{
+    FlashMintReceiver, FlashMinter, FlashMinterClient, WrappedAsset, WrappedAssetClient,
+    WrappedAssetError,
+};
+
+// ══════════════════════════════════════════════════════════════
+//  Example flash-mint receiver
+// ══════════════════════════════════════════════════════════════
+
+const ACCEPT_KEY: Symbol = symbol_short!("accept");
+const APPROVE_KEY: Symbol = symbol_short!("approve");
+const LENDER_KEY: Symbol = symbol_short!("lender");
+const MARKET_KEY: Symbol = symbol_short!("market");
+const SEEN_KEY: Symbol = symbol_short!("seen");
+
+#[contract]
+pub struct Borrower;
+
+#[contractimpl]
+impl Borrower {
+    /// `accept` is returned from the callback, `approve` decides whether the
+    /// lender is approved for the repayment.
+    pub fn init(env: Env, lender: Address, accept: bool, approve: bool) {
+        env.storage().instance().set(&LENDER_KEY, &lender);
+        env.storage().instance().set(&ACCEPT_KEY, &accept);
+        env.storage().instance().set(&APPROVE_KEY, &approve);
+    }
+
+    /// Trade the minted units with `market` during the callback.
+    pub fn set_market(env: Env, market: Address) {
+        env.storage().instance().set(&MARKET_KEY, &market);
+    }
+
+    /// Amount and fee the borrower was offered in its last callback.
+    pub fn seen(env: Env) -> (i128, i128) {
+        env.storage().instance().get(&SEEN_KEY).unwrap_or((0, 0))
+    }
+}
+
+#[contractimpl]
+impl FlashMintReceiver for Borrower {
+    fn on_flash_mint(
+        env: Env,
+        _initiator: Address,
+        token: Address,
+        amount: i128,
+        fee: i128,
+        _data: Bytes,
+    ) -> bool {
+        env.storage().instance().set(&SEEN_KEY, &(amount, fee));
+        let this = env.current_contract_address();
+        let asset = WrappedAssetClient::new(&env, &token);
+
+        // Sell the minted units and buy them back with a profit that covers
+        // the fee, as an arbitrageur rebalancing a pool would.
+        let market: Option<Address> = env.storage().instance().get(&MARKET_KEY);
+        if let Some(market) = market {
+            asset.transfer(&this, &market, &amount);
+            asset.transfer(&market, &this, &(amount + fee));
+        }
+
+        if env.storage().instance().get(&APPROVE_KEY).unwrap() {
+            let lender: Address = env.storage().instance().get(&LENDER_KEY).unwrap();
+            asset.approve(&this, &lender, &(amount + fee), &env.ledger().sequence());
+        }
+        env.storage().instance().get(&ACCEPT_KEY).unwrap()
+    }
+}
+
+// ══════════════════════════════════════════════════════════════
+//  Helpers
+// ══════════════════════════════════════════════════════════════
+
+struct Setup {
+    env: Env,
+    asset: WrappedAssetClient<'static>,
+    lender: FlashMinterClient<'static>,
+    admin: Address,
+    borrower: Address,
+    initiator: Address,
+}
+
+fn setup_with(accept: bool, approve: bool) -> Setup {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+
+    let admin = Address::generate(&env);
+    let asset_id = env.register(WrappedAsset, ());
+    let asset = WrappedAssetClient::new(&env, &asset_id);
+    asset.initialize(
+        &admin,
+        &String::from_str(&env, "Wrapped Ether"),
+        &String::from_str(&env, "wETH"),
+        &7,
+    );
+
+    let lender_id = env.register(FlashMinter, ());
+    let lender = FlashMinterClient::new(&env, &lender_id);
+    lender.init_flash_minter(&admin, &asset_id);
+    lender.set_flash_mint_config(&1_000_000, &10);
+    asset.set_flash_minter(&lender_id);
+
+    let borrower = env.register(Borrower, ());
+    BorrowerClient::new(&env, &borrower).init(&lender_id, &accept, &approve);
+
+    Setup {
+        initiator: Address::generate(&env),
+        env,
+        asset,
+        lender,
+        admin,
+        borrower,
+    }
+}
+
+fn setup(accept: bool) -> Setup {
+    setup_with(accept, true)
+}
+
+// ══════════════════════════════════════════════════════════════
+//  Token basics
+// ══════════════════════════════════════════════════════════════
+
+#[test]
+fn test_initialize_only_once() {
+    let s = setup(true);
+    let result = s.asset.try_initialize(
+        &s.admin,
+        &String::from_str(&s.env, "Other"),
+        &String::from_str(&s.env, "OTH"),
+        &7,
+    );
+    assert_eq!(result, Err(Ok(WrappedAssetError::AlreadyInitialized)));
+    assert_eq!(s.asset.symbol(), String::from_str(&s.env, "wETH"));
+    assert_eq!(s.asset.decimals(), 7);
+}
+
+#[test]
+fn test_mint_transfer_and_burn_move_internal_balances() {
+    let s = setup(true);
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+
+    s.asset.mint(&alice, &1_000);
+    s.asset.transfer(&alice, &bob, &300);
+    s.asset.burn(&bob, &100);
+
+    assert_eq!(s.asset.balance(&alice), 700);
+    assert_eq!(s.asset.balance(&bob), 200);
+    assert_eq!(s.asset.total_supply(), 900);
+}
+
+#[test]
+fn test_transfer_from_consumes_allowance() {
+    let s = setup(true);
+    let alice = Address::generate(&s.env);
+    let spender = Address::generate(&s.env);
+    s.asset.mint(&alice, &1_000);
+
+    s.asset
+        .approve(&alice, &spender, &400, &(s.env.ledger().sequence() + 10));
+    s.asset.transfer_from(&spender, &alice, &spender, &250);
+
+    assert_eq!(s.asset.allowance(&alice, &spender), 150);
+    assert_eq!(s.asset.balance(&spender), 250);
+    assert!(s
+        .asset
+        .try_transfer_from(&spender, &alice, &spender, &200)
+        .is_err());
+}
+
+#[test]
+fn test_mint_rejects_non_positive_amount() {
+    let s = setup(true);
+    let alice = Address::generate(&s.env);
+    assert_eq!(
+        s.asset.try_mint(&alice, &0),
+        Err(Ok(WrappedAssetError::InvalidAmount))
+    );
+}
+
+// ══════════════════════════════════════════════════════════════
+//  Flash mint
+// ══════════════════════════════════════════════════════════════
+
+#[test]
+fn test_flash_mint_mints_then_burns_principal_and_fee() {
+    let s = setup(true);
+    // The borrower already holds enough to cover the fee.
+    s.asset.mint(&s.borrower, &500);
+
+    let fee = s
+        .lender
+        .flash_mint(&s.initiator, &s.borrower, &100_000, &Bytes::new(&s.env));
+
+    assert_eq!(fee, 100);
+    let borrower = BorrowerClient::new(&s.env, &s.borrower);
+    assert_eq!(borrower.seen(), (100_000, 100));
+    assert_eq!(s.asset.balance(&s.borrower), 400);
+    assert_eq!(s.asset.total_supply(), 400);
+    assert_eq!(s.asset.allowance(&s.borrower, &s.lender.address), 0);
+}
+
+#[test]
+fn test_flash_mint_receiver_can_trade_minted_units() {
+    let s = setup(true);
+    let market = Address::generate(&s.env);
+    s.asset.mint(&market, &1_000);
+    BorrowerClient::new(&s.env, &s.borrower).set_market(&market);
+
+    let fee = s
+        .lender
+        .flash_mint(&s.initiator, &s.borrower, &100_000, &Bytes::new(&s.env));
+
+    // The borrower started empty and paid the fee out of its trade.
+    assert_eq!(fee, 100);
+    assert_eq!(s.asset.balance(&s.borrower), 0);
+    assert_eq!(s.asset.balance(&market), 900);
+    assert_eq!(s.asset.total_supply(), 900);
+}
+
+#[test]
+fn test_flash_mint_without_approval_reverts() {
+    let s = setup_with(true, false);
+    s.asset.mint(&s.borrower, &500);
+
+    let result = s
+        .lender
+        .try_flash_mint(&s.initiator, &s.borrower, &100_000, &Bytes::new(&s.env));
+
+    assert!(result.is_err());
+    assert_eq!(s.asset.balance(&s.borrower), 500);
+    assert_eq!(s.asset.total_supply(), 500);
+}
+
+#[test]
+fn test_flash_mint_without_fee_balance_reverts() {
+    let s = setup(true);
+
+    let result = s
+        .lender
+        .try_flash_mint(&s.initiator, &s.borrower, &100_000, &Bytes::new(&s.env));
+
+    assert!(result.is_err());
+    assert_eq!(s.asset.total_supply(), 0);
+}
+
+#[test]
+fn test_flash_mint_rejected_by_receiver() {
+    let s = setup(false);
+    s.asset.mint(&s.borrower, &500);
+
+    let result = s
+        .lender
+        .try_flash_mint(&s.initiator, &s.borrower, &100_000, &Bytes::new(&s.env));
+
+    assert_eq!(result, Err(Ok(WrappedAssetError::FlashMintCallbackFailed)));
+    assert_eq!(s.asset.total_supply(), 500);
+}
+
+#[test]
+fn test_flash_mint_respects_cap_and_pause() {
+    let s = setup(true);
+    s.asset.mint(&s.borrower, &5_000);
+
+    let over_cap =
+        s.lender
+            .try_flash_mint(&s.initiator, &s.borrower, &1_000_001, &Bytes::new(&s.env));
+    assert_eq!(over_cap, Err(Ok(WrappedAssetError::FlashMintCapExceeded)));
+
+    s.lender.set_flash_mint_paused(&true);
+    assert_eq!(s.lender.max_flash_mint(), 0);
+    let paused = s
+        .lender
+        .try_flash_mint(&s.initiator, &s.borrower, &1_000, &Bytes::new(&s.env));
+    assert_eq!(paused, Err(Ok(WrappedAssetError::FlashMintCapExceeded)));
+
+    s.lender.set_flash_mint_paused(&false);
+    assert_eq!(s.lender.max_flash_mint(), 1_000_000);
+}
+
+#[test]
+fn test_flash_mint_config_validation() {
+    let s = setup(true);
+    assert_eq!(
+        s.lender.try_set_flash_mint_config(&-1, &10),
+        Err(Ok(WrappedAssetError::InvalidFlashMintConfig))
+    );
+    assert_eq!(
+        s.lender.try_set_flash_mint_config(&1_000, &10_001),
+        Err(Ok(WrappedAssetError::InvalidFlashMintConfig))
+    );
+    assert_eq!(s.lender.flash_fee(&1_000_000), 1_000);
+}
+
+#[test]
+fn test_flash_fee_is_at_least_one() {
+    let s = setup(true);
+    assert_eq!(s.lender.flash_fee(&1), 1);
+    assert_eq!(s.lender.flash_fee(&999), 1);
+
+    s.lender.set_flash_mint_config(&1_000_000, &0);
+    assert_eq!(s.lender.flash_fee(&1_000_000), 1);
+}
+
+#[test]
+fn test_mint_flash_requires_flash_minter_role() {
+    let s = setup(true);
+    let alice = Address::generate(&s.env);
+
+    // Only the lender may mint; the admin signing is not enough.
+    s.env.mock_auths(&[MockAuth {
+        address: &s.admin,
+        invoke: &MockAuthInvoke {
+            contract: &s.asset.address,
+            fn_name: "mint_flash",
+            args: (&alice, 100i128).into_val(&s.env),
+            sub_invokes: &[],
+        },
+    }]);
+    assert!(s.asset.try_mint_flash(&alice, &100).is_err());
+    assert_eq!(s.asset.balance(&alice), 0);
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let asset = WrappedAssetClient::new(&env, &env.register(WrappedAsset, ()));
+    asset.initialize(
+        &Address::generate(&env),
+        &String::from_str(&env, "Wrapped Ether"),
+        &String::from_str(&env, "wETH"),
+        &7,
+    );
+    assert_eq!(
+        asset.try_mint_flash(&Address::generate(&env), &100),
+        Err(Ok(WrappedAssetError::FlashMinterNotSet))
+    );
+}
+
+#[test]
+fn test_flash_minter_initialize_only_once() {
+    let s = setup(true);
+    assert_eq!(
+        s.lender.try_init_flash_minter(&s.admin, &s.asset.address),
+        Err(Ok(WrappedAssetError::AlreadyInitialized))
+    );
+    assert_eq!(s.lender.flash_mint_token(), s.asset.address);
+    assert_eq!(s.asset.flash_minter(), Some(s.lender.address.clone()));
+}